@@ -0,0 +1,83 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use criterion::SamplingMode::Flat;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use benchmark::data_loader;
+use posemir::point_set::index::KdTree;
+use posemir::point_set::pattern::Pattern;
+use posemir::point_set::point::Point2Df64;
+use posemir::point_set::set::PointSet;
+use posemir::search::pattern_matcher::PatternMatcher;
+use posemir::search::tolerant_matcher::TolerantMatcher;
+
+fn load_random_datasets() -> Vec<PointSet<Point2Df64>> {
+    let config = data_loader::Config::default_counts(String::from("random/random_points_"));
+    data_loader::datasets(&config)
+}
+
+fn kd_tree_benchmarks(c: &mut Criterion) {
+    let datasets = load_random_datasets();
+    let mut group = c.benchmark_group("KdTree - random/random_points_");
+    group.sampling_mode(Flat);
+
+    for point_set in &datasets {
+        let size = point_set.len() as u64;
+        group.bench_with_input(BenchmarkId::new("build", size), &point_set, |b, &input| {
+            b.iter(|| criterion::black_box(KdTree::new(input)))
+        });
+
+        let tree = KdTree::new(point_set);
+        let extent = Point2Df64 { x: 1.0, y: 2.0 };
+        group.bench_with_input(BenchmarkId::new("range", size), &point_set, |b, &input| {
+            b.iter(|| {
+                for point in input {
+                    criterion::black_box(tree.range(&(*point - extent), &(*point + extent)));
+                }
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("nearest", size),
+            &point_set,
+            |b, &input| {
+                b.iter(|| {
+                    for point in input {
+                        criterion::black_box(tree.nearest(&(*point + extent)));
+                    }
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn tolerant_matcher_benchmarks(c: &mut Criterion) {
+    let datasets = load_random_datasets();
+    let mut group = c.benchmark_group("TolerantMatcher - random/random_points_");
+    group.sampling_mode(Flat);
+
+    let matcher = TolerantMatcher {
+        onset_tolerance: 0.5,
+        pitch_tolerance: 1.0,
+    };
+
+    for point_set in &datasets {
+        let size = point_set.len() as u64;
+        let query_size = point_set.len().min(10);
+        let query: Pattern<Point2Df64> =
+            point_set.get_pattern(&(0..query_size).collect::<Vec<_>>());
+        group.bench_with_input(BenchmarkId::new("", size), &point_set, |b, &input| {
+            b.iter(|| criterion::black_box(matcher.find_indices(&query, input)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(name = index_benchmarks;
+    config = Criterion::default().sample_size(10);
+    targets = kd_tree_benchmarks, tolerant_matcher_benchmarks);
+criterion_main!(index_benchmarks);
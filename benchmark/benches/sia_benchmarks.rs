@@ -10,19 +10,19 @@ use posemir::discovery::sia::Sia;
 
 fn sia_benchmarks_with_random(c: &mut Criterion) {
     let config = data_loader::Config::default_counts(String::from("random/random_points_"));
-    mtp_benchmark::run_mtp_benchmarks(&Sia {}, "SIA", &config, c);
+    mtp_benchmark::run_mtp_benchmarks(&Sia::new(), "SIA", &config, c);
 }
 
 fn sia_benchmarks_with_min_pattern_count(c: &mut Criterion) {
     let config =
         data_loader::Config::default_counts(String::from("min_pattern_count/min_pattern_count_"));
-    mtp_benchmark::run_mtp_benchmarks(&Sia {}, "SIA", &config, c);
+    mtp_benchmark::run_mtp_benchmarks(&Sia::new(), "SIA", &config, c);
 }
 
 fn sia_benchmarks_with_max_pattern_count(c: &mut Criterion) {
     let config =
         data_loader::Config::default_counts(String::from("max_pattern_count/max_pattern_count_"));
-    mtp_benchmark::run_mtp_benchmarks(&Sia {}, "SIA", &config, c);
+    mtp_benchmark::run_mtp_benchmarks(&Sia::new(), "SIA", &config, c);
 }
 
 criterion_group!(name = sia_benchmarks;
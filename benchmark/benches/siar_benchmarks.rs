@@ -10,21 +10,21 @@ use posemir::discovery::siar::SiaR;
 
 fn siar_benchmarks_with_random(c: &mut Criterion) {
     let config = data_loader::Config::default_counts(String::from("random/random_points_"));
-    let algorithm = SiaR { r: 1 };
+    let algorithm = SiaR::new(1);
     mtp_benchmark::run_mtp_benchmarks(&algorithm, "SIAR(1)", &config, c);
 }
 
 fn siar_benchmarks_with_min_pattern_count(c: &mut Criterion) {
     let config =
         data_loader::Config::default_counts(String::from("min_pattern_count/min_pattern_count_"));
-    let algorithm = SiaR { r: 1 };
+    let algorithm = SiaR::new(1);
     mtp_benchmark::run_mtp_benchmarks(&algorithm, "SIAR(1)", &config, c);
 }
 
 fn siar_benchmarks_with_max_pattern_count(c: &mut Criterion) {
     let config =
         data_loader::Config::default_counts(String::from("max_pattern_count/max_pattern_count_"));
-    let algorithm = SiaR { r: 1 };
+    let algorithm = SiaR::new(1);
     mtp_benchmark::run_mtp_benchmarks(&algorithm, "SIAR(1)", &config, c);
 }
 
@@ -10,19 +10,19 @@ use posemir::discovery::siatec_c::SiatecC;
 
 fn siatec_c_benchmarks_with_random(c: &mut Criterion) {
     let config = data_loader::Config::default_counts(String::from("random/random_points_"));
-    tec_benchmark::run_tec_benchmarks(&SiatecC { max_ioi: 50.0 }, "SIATEC-C(50)", &config, c);
+    tec_benchmark::run_tec_benchmarks(&SiatecC::new(50.0), "SIATEC-C(50)", &config, c);
 }
 
 fn siatec_c_benchmarks_with_min_pattern_count(c: &mut Criterion) {
     let config =
         data_loader::Config::default_counts(String::from("min_pattern_count/min_pattern_count_"));
-    tec_benchmark::run_tec_benchmarks(&SiatecC { max_ioi: 50.0 }, "SIATEC-C(50)", &config, c);
+    tec_benchmark::run_tec_benchmarks(&SiatecC::new(50.0), "SIATEC-C(50)", &config, c);
 }
 
 fn siatec_c_benchmarks_with_max_pattern_count(c: &mut Criterion) {
     let config =
         data_loader::Config::default_counts(String::from("max_pattern_count/max_pattern_count_"));
-    tec_benchmark::run_tec_benchmarks(&SiatecC { max_ioi: 50.0 }, "SIATEC-C(50)", &config, c);
+    tec_benchmark::run_tec_benchmarks(&SiatecC::new(50.0), "SIATEC-C(50)", &config, c);
 }
 
 criterion_group!(name = siatec_c_benchmarks;
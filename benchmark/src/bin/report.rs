@@ -0,0 +1,124 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use clap::{Arg, Command};
+
+use benchmark::report::{run_matrix, write_csv, write_json};
+use posemir::discovery::registry::{MAX_IOI, MEMORY_BUDGET, SUB_DIAGONALS};
+use posemir::error::PoSemirError;
+use posemir::io::csv::csv_to_2d_point_f64;
+use posemir::point_set::set::PointSet;
+
+/// Runs a matrix of algorithms and datasets and writes the wall times, peak memory usage,
+/// numbers of patterns and coverages as a CSV or JSON report, e.g.:
+/// `report -a SIA,SIATEC-C -o report.csv data/random/random_points_100.csv`
+fn main() {
+    let matches = Command::new("report")
+        .about("Runs algorithms on datasets and writes a summary report of the runs")
+        .arg(
+            Arg::new("algorithms")
+                .long("algos")
+                .short('a')
+                .takes_value(true)
+                .help("Comma separated names of the algorithms to run, e.g., SIA,SIATEC-C")
+                .required(true),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .takes_value(true)
+                .help("Path to the report file. The report is written as JSON if the extension is .json and as CSV otherwise")
+                .required(true),
+        )
+        .arg(
+            Arg::new("max-ioi")
+                .long("max-ioi")
+                .takes_value(true)
+                .default_value("10.0"),
+        )
+        .arg(
+            Arg::new("sub-diagonals")
+                .long("sub-diag")
+                .takes_value(true)
+                .default_value("3"),
+        )
+        .arg(
+            Arg::new("memory-budget")
+                .long("memory-budget")
+                .takes_value(true)
+                .default_value("1024"),
+        )
+        .arg(
+            Arg::new("datasets")
+                .takes_value(true)
+                .multiple_values(true)
+                .help("Paths to the datasets as CSV files")
+                .required(true),
+        )
+        .get_matches();
+
+    let algorithms: Vec<String> = matches
+        .value_of("algorithms")
+        .unwrap()
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .collect();
+    let output = Path::new(matches.value_of("output").unwrap());
+    let dataset_paths: Vec<&str> = matches.values_of("datasets").unwrap().collect();
+
+    let mut parameters = HashMap::new();
+    for (parameter, arg) in [
+        (MAX_IOI, "max-ioi"),
+        (SUB_DIAGONALS, "sub-diagonals"),
+        (MEMORY_BUDGET, "memory-budget"),
+    ] {
+        let value: f64 = matches.value_of(arg).unwrap().parse().unwrap();
+        parameters.insert(parameter.to_string(), value);
+    }
+
+    if let Err(error) = run(&algorithms, &dataset_paths, &parameters, output) {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
+}
+
+fn run(
+    algorithms: &[String],
+    dataset_paths: &[&str],
+    parameters: &HashMap<String, f64>,
+    output: &Path,
+) -> Result<(), PoSemirError> {
+    let mut datasets = Vec::with_capacity(dataset_paths.len());
+    for path in dataset_paths {
+        let path = Path::new(path);
+        let name = path.file_stem().unwrap().to_string_lossy().to_string();
+        datasets.push((name, PointSet::new(csv_to_2d_point_f64(path)?)));
+    }
+
+    let rows = run_matrix(algorithms, &datasets, parameters)?;
+
+    let mut writer = BufWriter::new(File::create(output)?);
+    if output
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        write_json(&rows, &mut writer)?;
+    } else {
+        write_csv(&rows, &mut writer)?;
+    }
+
+    println!(
+        "Ran {} algorithms on {} datasets, see {}",
+        algorithms.len(),
+        datasets.len(),
+        output.display()
+    );
+    Ok(())
+}
@@ -8,6 +8,7 @@ use std::path::Path;
 use posemir::io::csv::csv_to_2d_point_f64;
 use posemir::point_set::point::Point2Df64;
 use posemir::point_set::set::PointSet;
+use posemir::synthetic::SyntheticGenerator;
 
 /// Configuration for running a benchmark
 pub struct Config {
@@ -74,3 +75,42 @@ pub fn load_datasets(data_path: &Path, config: &Config) -> Vec<PointSet<Point2Df
 
     point_sets
 }
+
+/// Returns the datasets of sizes defined by min, max, and step. The datasets are loaded from
+/// the data directory given in the environment variable BENCHMARK_DATA_PATH, or generated
+/// with `generate_datasets` if the variable is not set.
+///
+/// # Arguments
+/// * `config` - The config that defines which types of data sets to use
+///
+pub fn datasets(config: &Config) -> Vec<PointSet<Point2Df64>> {
+    match env::var("BENCHMARK_DATA_PATH") {
+        Ok(data_path) => load_datasets(Path::new(&data_path), config),
+        Err(_) => generate_datasets(config),
+    }
+}
+
+/// Generates synthetic datasets of sizes defined by min, max, and step. The type of the
+/// datasets is selected by the path of the config as in the data directory: the
+/// min_pattern_count and max_pattern_count datasets have the minimal and maximal numbers of
+/// MTPs, and other datasets consist of random points.
+///
+/// # Arguments
+/// * `config` - The config that defines which types of data sets to generate
+///
+pub fn generate_datasets(config: &Config) -> Vec<PointSet<Point2Df64>> {
+    let mut generator = SyntheticGenerator::new(config.min as u64);
+
+    (config.min..config.max + 1)
+        .step_by(config.step)
+        .map(|size| {
+            if config.path_str.starts_with("min_pattern_count") {
+                SyntheticGenerator::min_pattern_count(size)
+            } else if config.path_str.starts_with("max_pattern_count") {
+                SyntheticGenerator::max_pattern_count(size)
+            } else {
+                generator.random(size, size as i64, 128)
+            }
+        })
+        .collect()
+}
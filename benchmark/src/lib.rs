@@ -4,4 +4,5 @@
  */
 pub mod data_loader;
 pub mod mtp_benchmark;
+pub mod report;
 pub mod tec_benchmark;
@@ -2,8 +2,7 @@
  * (c) Otso Björklund (2021)
  * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
  */
-use std::env;
-use std::path::Path;
+use std::ops::ControlFlow;
 
 use criterion::SamplingMode::Flat;
 use criterion::{BenchmarkId, Criterion};
@@ -20,8 +19,7 @@ pub fn run_mtp_benchmarks<T: MtpAlgorithm<Point2Df64>>(
     config: &data_loader::Config,
     c: &mut Criterion,
 ) {
-    let data_path = env::var("BENCHMARK_DATA_PATH").unwrap();
-    let datasets = data_loader::load_datasets(Path::new(&data_path), config);
+    let datasets = data_loader::datasets(config);
 
     let group_name = format!("{} - {}", algorithm_name, config.path_str);
     let mut group = c.benchmark_group(&group_name);
@@ -29,6 +27,7 @@ pub fn run_mtp_benchmarks<T: MtpAlgorithm<Point2Df64>>(
 
     let on_output = |mtp: Mtp<Point2Df64>| {
         criterion::black_box(mtp);
+        ControlFlow::Continue(())
     };
 
     for point_set in &datasets {
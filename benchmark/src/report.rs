@@ -0,0 +1,174 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::ops::ControlFlow;
+use std::time::Instant;
+
+use serde_json::json;
+
+use posemir::discovery::algorithm::{MtpAlgorithm, TecAlgorithm};
+use posemir::discovery::registry::{create_algorithm, DiscoveryAlgorithm};
+use posemir::error::PoSemirError;
+use posemir::point_set::point::Point2Df64;
+use posemir::point_set::set::PointSet;
+use posemir::point_set::tec::Tec;
+
+/// The measurements of a single run of an algorithm on a dataset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportRow {
+    /// The name of the algorithm.
+    pub algorithm: String,
+    /// The name of the dataset.
+    pub dataset: String,
+    /// The number of points in the dataset.
+    pub points: usize,
+    /// The wall time of the run in seconds.
+    pub seconds: f64,
+    /// The peak resident set size of the process during the run in kilobytes,
+    /// or None if it cannot be measured on this platform.
+    pub peak_rss_kb: Option<u64>,
+    /// The number of output TECs (or MTPs for MTP algorithms).
+    pub patterns: usize,
+    /// The number of points of the dataset covered by the output patterns.
+    pub coverage: usize,
+}
+
+/// Runs each of the algorithms on each of the datasets once and returns the measurements
+/// in the order of the datasets and then the algorithms. The algorithms are created by name
+/// with `create_algorithm` using the given parameters.
+///
+/// # Arguments
+///
+/// * `algorithms` - The names of the algorithms to run
+/// * `datasets` - The datasets with their names
+/// * `parameters` - The parameters of the algorithms by parameter name
+pub fn run_matrix(
+    algorithms: &[String],
+    datasets: &[(String, PointSet<Point2Df64>)],
+    parameters: &HashMap<String, f64>,
+) -> Result<Vec<ReportRow>, PoSemirError> {
+    let mut rows = Vec::with_capacity(algorithms.len() * datasets.len());
+
+    for (dataset, point_set) in datasets {
+        for name in algorithms {
+            let algorithm = create_algorithm::<Point2Df64>(name, parameters)?;
+            reset_peak_rss();
+
+            let mut covered: Vec<Point2Df64> = Vec::new();
+            let mut patterns = 0;
+            let start = Instant::now();
+            match algorithm {
+                DiscoveryAlgorithm::Mtp(algorithm) => {
+                    algorithm.compute_mtps_to_output(point_set, |mtp| {
+                        patterns += 1;
+                        collect_covered(&mtp.to_tec(), &mut covered);
+                        ControlFlow::Continue(())
+                    });
+                }
+                DiscoveryAlgorithm::Tec(algorithm) => {
+                    algorithm.compute_tecs_to_output(point_set, |tec| {
+                        patterns += 1;
+                        collect_covered(&tec, &mut covered);
+                        ControlFlow::Continue(())
+                    });
+                }
+            }
+            let seconds = start.elapsed().as_secs_f64();
+
+            covered.sort();
+            covered.dedup();
+            rows.push(ReportRow {
+                algorithm: name.to_uppercase(),
+                dataset: dataset.clone(),
+                points: point_set.len(),
+                seconds,
+                peak_rss_kb: peak_rss_kb(),
+                patterns,
+                coverage: covered.len(),
+            });
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Writes the rows as CSV with a header row.
+///
+/// # Arguments
+///
+/// * `rows` - The measurements to write
+/// * `writer` - The writer to which the CSV is written
+pub fn write_csv<W: Write>(rows: &[ReportRow], writer: &mut W) -> Result<(), PoSemirError> {
+    writeln!(
+        writer,
+        "algorithm,dataset,points,seconds,peak_rss_kb,patterns,coverage"
+    )?;
+    for row in rows {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{}",
+            row.algorithm,
+            row.dataset,
+            row.points,
+            row.seconds,
+            row.peak_rss_kb.map(|kb| kb.to_string()).unwrap_or_default(),
+            row.patterns,
+            row.coverage
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes the rows as a JSON list of objects.
+///
+/// # Arguments
+///
+/// * `rows` - The measurements to write
+/// * `writer` - The writer to which the JSON is written
+pub fn write_json<W: Write>(rows: &[ReportRow], writer: &mut W) -> Result<(), PoSemirError> {
+    let rows: Vec<serde_json::Value> = rows
+        .iter()
+        .map(|row| {
+            json!({
+                "algorithm": row.algorithm,
+                "dataset": row.dataset,
+                "points": row.points,
+                "seconds": row.seconds,
+                "peak_rss_kb": row.peak_rss_kb,
+                "patterns": row.patterns,
+                "coverage": row.coverage,
+            })
+        })
+        .collect();
+    serde_json::to_writer_pretty(&mut *writer, &rows)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Adds the points covered by the TEC to the covered points. The points are deduplicated
+/// once all TECs have been added.
+fn collect_covered(tec: &Tec<Point2Df64>, covered: &mut Vec<Point2Df64>) {
+    for occurrence in tec.occurrences_iter() {
+        covered.extend(occurrence.as_ref());
+    }
+}
+
+/// Resets the peak resident set size of the process, so that the peak of the next run can
+/// be measured. This is only supported on Linux, elsewhere the peak is not measured.
+fn reset_peak_rss() {
+    // Writing 5 to clear_refs resets the peak RSS (VmHWM) to the current RSS.
+    let _ = fs::write("/proc/self/clear_refs", "5");
+}
+
+/// Returns the peak resident set size of the process in kilobytes on Linux.
+fn peak_rss_kb() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+}
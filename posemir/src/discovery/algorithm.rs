@@ -2,7 +2,11 @@
  * (c) Otso Björklund (2021)
  * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
  */
-use crate::point_set::mtp::Mtp;
+use std::ops::ControlFlow;
+
+use crate::discovery::progress::ProgressObserver;
+use crate::error::PoSemirError;
+use crate::point_set::mtp::{IndexedMtp, Mtp};
 use crate::point_set::point::Point;
 use crate::point_set::set::PointSet;
 use crate::point_set::tec::Tec;
@@ -21,13 +25,67 @@ pub trait MtpAlgorithm<T: Point> {
 
     /// Computes MTPs in the given point set and executes on_output for
     /// each produced MTP. For large outputs that should not be kept in
-    /// memory this function should be used.
+    /// memory this function should be used. The computation is stopped
+    /// as soon as on_output returns `ControlFlow::Break`.
     ///
     /// # Arguments
     ///
     /// * `point_set` - the set of points for which MTPs are computed
     /// * `on_output` - a function to execute whenever the algorithm can produce output
-    fn compute_mtps_to_output(&self, point_set: &PointSet<T>, on_output: impl FnMut(Mtp<T>));
+    fn compute_mtps_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        on_output: impl FnMut(Mtp<T>) -> ControlFlow<()>,
+    );
+
+    /// Returns the MTPs in the given point set as `compute_mtps`, but returns an error
+    /// instead of computing the MTPs if the point set is not valid (see `PointSet::validate`).
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - the set of points for which MTPs are computed
+    fn try_compute_mtps(&self, point_set: &PointSet<T>) -> Result<Vec<Mtp<T>>, PoSemirError> {
+        point_set.validate()?;
+        Ok(self.compute_mtps(point_set))
+    }
+
+    /// Returns the MTPs in the given point set with the indices of their points
+    /// in the point set. See `compute_mtps`.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - the set of points for which MTPs are computed
+    fn compute_indexed_mtps(&self, point_set: &PointSet<T>) -> Vec<IndexedMtp<T>> {
+        let mut mtps = Vec::new();
+        self.compute_indexed_mtps_to_output(point_set, |mtp| {
+            mtps.push(mtp);
+            ControlFlow::Continue(())
+        });
+        mtps
+    }
+
+    /// Computes MTPs with the indices of their points in the given point set and executes
+    /// on_output for each produced MTP. See `compute_mtps_to_output`.
+    ///
+    /// The default implementation searches for the indices of the points in the point set,
+    /// so algorithms that already know the indices should override this.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - the set of points for which MTPs are computed
+    /// * `on_output` - a function to execute whenever the algorithm can produce output
+    fn compute_indexed_mtps_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(IndexedMtp<T>) -> ControlFlow<()>,
+    ) {
+        self.compute_mtps_to_output(point_set, |mtp| {
+            let indices = point_set
+                .find_indices(&mtp.pattern)
+                .expect("MTP pattern must be in the point set");
+            on_output(IndexedMtp { mtp, indices })
+        });
+    }
 }
 
 /// Trait for algorithms that compute TECs in a point set.
@@ -45,11 +103,160 @@ pub trait TecAlgorithm<T: Point> {
 
     /// Computes TECs in the given point set and executes on_output for
     /// each produced TEC. For large outputs that should not be kept in
-    /// memory this function should be used.
+    /// memory this function should be used. The computation is stopped
+    /// as soon as on_output returns `ControlFlow::Break`.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - the set of points for which TECs are computed
+    /// * `on_output` - a function to execute whenever the algorithm can produce output
+    fn compute_tecs_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    );
+
+    /// Computes TECs as `compute_tecs_to_output` and reports the progress of the
+    /// computation to the observer. The default implementation does not report any
+    /// progress, so algorithms that can estimate their progress override this.
     ///
     /// # Arguments
     ///
     /// * `point_set` - the set of points for which TECs are computed
+    /// * `observer` - the observer to which the progress is reported
     /// * `on_output` - a function to execute whenever the algorithm can produce output
-    fn compute_tecs_to_output(&self, point_set: &PointSet<T>, on_output: impl FnMut(Tec<T>));
+    fn compute_tecs_to_output_observed(
+        &self,
+        point_set: &PointSet<T>,
+        _observer: &dyn ProgressObserver,
+        on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        self.compute_tecs_to_output(point_set, on_output)
+    }
+
+    /// Returns the TECs in the given point set as `compute_tecs`, but returns an error
+    /// instead of computing the TECs if the point set is not valid (see `PointSet::validate`).
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - the set of points for which TECs are computed
+    fn try_compute_tecs(&self, point_set: &PointSet<T>) -> Result<Vec<Tec<T>>, PoSemirError> {
+        point_set.validate()?;
+        Ok(self.compute_tecs(point_set))
+    }
+}
+
+/// Object-safe version of `MtpAlgorithm`. It is implemented for all MTP algorithms,
+/// so that algorithms can be selected at runtime and used as trait objects,
+/// e.g., `Box<dyn DynMtpAlgorithm<T>>`. Boxed algorithms implement `MtpAlgorithm`.
+pub trait DynMtpAlgorithm<T: Point> {
+    /// See `MtpAlgorithm::compute_mtps`.
+    fn compute_mtps_dyn(&self, point_set: &PointSet<T>) -> Vec<Mtp<T>>;
+
+    /// See `MtpAlgorithm::compute_mtps_to_output`.
+    fn compute_mtps_to_output_dyn(
+        &self,
+        point_set: &PointSet<T>,
+        on_output: &mut dyn FnMut(Mtp<T>) -> ControlFlow<()>,
+    );
+}
+
+impl<T: Point, A: MtpAlgorithm<T>> DynMtpAlgorithm<T> for A {
+    fn compute_mtps_dyn(&self, point_set: &PointSet<T>) -> Vec<Mtp<T>> {
+        self.compute_mtps(point_set)
+    }
+
+    fn compute_mtps_to_output_dyn(
+        &self,
+        point_set: &PointSet<T>,
+        on_output: &mut dyn FnMut(Mtp<T>) -> ControlFlow<()>,
+    ) {
+        self.compute_mtps_to_output(point_set, on_output)
+    }
+}
+
+impl<T: Point> MtpAlgorithm<T> for Box<dyn DynMtpAlgorithm<T>> {
+    fn compute_mtps(&self, point_set: &PointSet<T>) -> Vec<Mtp<T>> {
+        self.as_ref().compute_mtps_dyn(point_set)
+    }
+
+    fn compute_mtps_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(Mtp<T>) -> ControlFlow<()>,
+    ) {
+        self.as_ref()
+            .compute_mtps_to_output_dyn(point_set, &mut on_output)
+    }
+}
+
+/// Object-safe version of `TecAlgorithm`. It is implemented for all TEC algorithms,
+/// so that algorithms can be selected at runtime and used as trait objects,
+/// e.g., `Box<dyn DynTecAlgorithm<T>>`. Boxed algorithms implement `TecAlgorithm`.
+pub trait DynTecAlgorithm<T: Point> {
+    /// See `TecAlgorithm::compute_tecs`.
+    fn compute_tecs_dyn(&self, point_set: &PointSet<T>) -> Vec<Tec<T>>;
+
+    /// See `TecAlgorithm::compute_tecs_to_output`.
+    fn compute_tecs_to_output_dyn(
+        &self,
+        point_set: &PointSet<T>,
+        on_output: &mut dyn FnMut(Tec<T>) -> ControlFlow<()>,
+    );
+
+    /// See `TecAlgorithm::compute_tecs_to_output_observed`.
+    fn compute_tecs_to_output_observed_dyn(
+        &self,
+        point_set: &PointSet<T>,
+        observer: &dyn ProgressObserver,
+        on_output: &mut dyn FnMut(Tec<T>) -> ControlFlow<()>,
+    );
+}
+
+impl<T: Point, A: TecAlgorithm<T>> DynTecAlgorithm<T> for A {
+    fn compute_tecs_dyn(&self, point_set: &PointSet<T>) -> Vec<Tec<T>> {
+        self.compute_tecs(point_set)
+    }
+
+    fn compute_tecs_to_output_dyn(
+        &self,
+        point_set: &PointSet<T>,
+        on_output: &mut dyn FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        self.compute_tecs_to_output(point_set, on_output)
+    }
+
+    fn compute_tecs_to_output_observed_dyn(
+        &self,
+        point_set: &PointSet<T>,
+        observer: &dyn ProgressObserver,
+        on_output: &mut dyn FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        self.compute_tecs_to_output_observed(point_set, observer, on_output)
+    }
+}
+
+impl<T: Point> TecAlgorithm<T> for Box<dyn DynTecAlgorithm<T>> {
+    fn compute_tecs(&self, point_set: &PointSet<T>) -> Vec<Tec<T>> {
+        self.as_ref().compute_tecs_dyn(point_set)
+    }
+
+    fn compute_tecs_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        self.as_ref()
+            .compute_tecs_to_output_dyn(point_set, &mut on_output)
+    }
+
+    fn compute_tecs_to_output_observed(
+        &self,
+        point_set: &PointSet<T>,
+        observer: &dyn ProgressObserver,
+        mut on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        self.as_ref()
+            .compute_tecs_to_output_observed_dyn(point_set, observer, &mut on_output)
+    }
 }
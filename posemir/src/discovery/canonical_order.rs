@@ -0,0 +1,168 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::ops::ControlFlow;
+
+use crate::discovery::algorithm::{MtpAlgorithm, TecAlgorithm};
+use crate::discovery::progress::ProgressObserver;
+use crate::point_set::mtp::Mtp;
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// Outputs the results of an MTP or TEC algorithm in a canonical order, so that the output
+/// does not depend on, e.g., the iteration order of hash maps (as in SIATEC-CH) or the number
+/// of threads. This makes the outputs of different runs directly comparable, e.g., in
+/// regression tests.
+///
+/// The TECs are output in their canonical forms (see `Tec::canonical`) sorted lexicographically
+/// by the patterns and then by the translators. The MTPs are sorted lexicographically by the
+/// patterns and then by the translators. The output of the algorithm is buffered, so the
+/// results are output only after the algorithm has finished.
+pub struct CanonicalOrder<A> {
+    algorithm: A,
+}
+
+impl<A> CanonicalOrder<A> {
+    /// Creates a new instance that outputs the results of the given algorithm
+    /// in canonical order.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - The MTP or TEC algorithm whose output is ordered
+    pub fn with(algorithm: A) -> CanonicalOrder<A> {
+        CanonicalOrder { algorithm }
+    }
+}
+
+/// Converts the TECs into their canonical forms and sorts them into the canonical order
+/// (see `CanonicalOrder`).
+///
+/// # Arguments
+///
+/// * `tecs` - The TECs to sort
+pub fn sort_canonically<T: Point>(tecs: &mut [Tec<T>]) {
+    for tec in tecs.iter_mut() {
+        if !tec.is_canonical() {
+            *tec = tec.canonical();
+        }
+    }
+    tecs.sort();
+}
+
+impl<T: Point, A: TecAlgorithm<T>> TecAlgorithm<T> for CanonicalOrder<A> {
+    fn compute_tecs(&self, point_set: &PointSet<T>) -> Vec<Tec<T>> {
+        let mut tecs = self.algorithm.compute_tecs(point_set);
+        sort_canonically(&mut tecs);
+        tecs
+    }
+
+    fn compute_tecs_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        output_all(self.compute_tecs(point_set), on_output);
+    }
+
+    fn compute_tecs_to_output_observed(
+        &self,
+        point_set: &PointSet<T>,
+        observer: &dyn ProgressObserver,
+        on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        let mut tecs = Vec::new();
+        self.algorithm
+            .compute_tecs_to_output_observed(point_set, observer, |tec| {
+                tecs.push(tec);
+                ControlFlow::Continue(())
+            });
+        sort_canonically(&mut tecs);
+        output_all(tecs, on_output);
+    }
+}
+
+impl<T: Point, A: MtpAlgorithm<T>> MtpAlgorithm<T> for CanonicalOrder<A> {
+    fn compute_mtps(&self, point_set: &PointSet<T>) -> Vec<Mtp<T>> {
+        let mut mtps = self.algorithm.compute_mtps(point_set);
+        mtps.sort_by(|a, b| {
+            a.pattern
+                .cmp(&b.pattern)
+                .then_with(|| a.translator.cmp(&b.translator))
+        });
+        mtps
+    }
+
+    fn compute_mtps_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        on_output: impl FnMut(Mtp<T>) -> ControlFlow<()>,
+    ) {
+        output_all(self.compute_mtps(point_set), on_output);
+    }
+}
+
+fn output_all<R>(results: Vec<R>, mut on_output: impl FnMut(R) -> ControlFlow<()>) {
+    for result in results {
+        if on_output(result).is_break() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+
+    use crate::discovery::algorithm::{MtpAlgorithm, TecAlgorithm};
+    use crate::discovery::canonical_order::CanonicalOrder;
+    use crate::discovery::sia::Sia;
+    use crate::discovery::siatec_ch::SiatecCH;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+
+    fn test_point_set() -> PointSet<Point2Df64> {
+        let mut points = Vec::new();
+        for i in 0..20 {
+            points.push(Point2Df64 {
+                x: ((i * 7) % 11) as f64,
+                y: ((i * 5) % 13) as f64,
+            });
+        }
+        PointSet::new(points)
+    }
+
+    #[test]
+    fn test_tecs_in_canonical_order() {
+        let point_set = test_point_set();
+        let siatec_ch = CanonicalOrder::with(SiatecCH::new(3.0));
+
+        let tecs = siatec_ch.compute_tecs(&point_set);
+        assert!(!tecs.is_empty());
+        assert!(tecs.iter().all(|tec| tec.is_canonical()));
+        assert!(tecs.windows(2).all(|w| w[0] <= w[1]));
+
+        let mut unordered = SiatecCH::new(3.0).compute_tecs(&point_set);
+        unordered.sort();
+        assert_eq!(unordered, tecs);
+
+        let mut streamed = Vec::new();
+        siatec_ch.compute_tecs_to_output(&point_set, |tec| {
+            streamed.push(tec);
+            ControlFlow::Continue(())
+        });
+        assert_eq!(tecs, streamed);
+    }
+
+    #[test]
+    fn test_mtps_in_canonical_order() {
+        let point_set = test_point_set();
+        let mtps = CanonicalOrder::with(Sia::new()).compute_mtps(&point_set);
+
+        assert_eq!(Sia::new().compute_mtps(&point_set).len(), mtps.len());
+        assert!(mtps
+            .windows(2)
+            .all(|w| (&w[0].pattern, w[0].translator) <= (&w[1].pattern, w[1].translator)));
+    }
+}
@@ -0,0 +1,261 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::marker::PhantomData;
+use std::ops::ControlFlow;
+
+use crate::discovery::algorithm::TecAlgorithm;
+use crate::discovery::progress::ProgressObserver;
+use crate::point_set::geometry::{BoundingBox, ConvexHull};
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// The region of an occurrence of a pattern in which the points of the point set are
+/// counted when computing the compactness of the pattern (see [Meredith2013]).
+/// Time is assumed to be the first and pitch the second component of the points.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CompactnessRegion {
+    /// The smallest axis-aligned rectangle that contains the occurrence.
+    BoundingBox,
+    /// The convex hull of the occurrence.
+    ConvexHull,
+    /// The time span of the occurrence, i.e., all points between the first and last onset.
+    TemporalSegment,
+}
+
+/// Returns the compactness of the TEC in the point set, that is, the largest ratio of the
+/// pattern size to the number of points of the point set in the region of an occurrence.
+///
+/// # Arguments
+///
+/// * `tec` - The TEC whose compactness is computed
+/// * `point_set` - The point set in which the TEC occurs
+/// * `region` - The region of the occurrences in which the points are counted
+pub fn compactness<T: Point>(
+    tec: &Tec<T>,
+    point_set: &PointSet<T>,
+    region: CompactnessRegion,
+) -> f64 {
+    let pattern_size = tec.pattern.len() as f64;
+    let mut best_compactness = 0.0;
+
+    for occurrence in tec.occurrences_iter() {
+        let region = Region::of(&occurrence, region);
+        let contained = point_set
+            .into_iter()
+            .filter(|point| region.contains(*point))
+            .count();
+
+        if contained > 0 {
+            let compactness = pattern_size / contained as f64;
+            if compactness > best_compactness {
+                best_compactness = compactness;
+            }
+        }
+    }
+
+    best_compactness
+}
+
+/// Filters the output of a TEC algorithm to the TECs whose compactness in the point set
+/// is at least the given minimum (see `compactness`).
+pub struct CompactnessFilter<T: Point, A: TecAlgorithm<T>> {
+    tec_algorithm: A,
+    region: CompactnessRegion,
+    min_compactness: f64,
+    _t: PhantomData<T>,
+}
+
+impl<T: Point, A: TecAlgorithm<T>> TecAlgorithm<T> for CompactnessFilter<T, A> {
+    fn compute_tecs(&self, point_set: &PointSet<T>) -> Vec<Tec<T>> {
+        let mut tecs = Vec::new();
+        self.compute_tecs_to_output(point_set, |tec| {
+            tecs.push(tec);
+            ControlFlow::Continue(())
+        });
+        tecs
+    }
+
+    fn compute_tecs_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        self.tec_algorithm
+            .compute_tecs_to_output(point_set, |tec| self.output(tec, point_set, &mut on_output));
+    }
+
+    fn compute_tecs_to_output_observed(
+        &self,
+        point_set: &PointSet<T>,
+        observer: &dyn ProgressObserver,
+        mut on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        self.tec_algorithm
+            .compute_tecs_to_output_observed(point_set, observer, |tec| {
+                self.output(tec, point_set, &mut on_output)
+            });
+    }
+}
+
+impl<T: Point, A: TecAlgorithm<T>> CompactnessFilter<T, A> {
+    /// Creates a new compactness filter for the output of the given TEC algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// * `tec_algorithm` - The algorithm whose output is filtered
+    /// * `region` - The region of the occurrences in which the points are counted
+    /// * `min_compactness` - The minimum compactness of the retained TECs
+    pub fn with(
+        tec_algorithm: A,
+        region: CompactnessRegion,
+        min_compactness: f64,
+    ) -> CompactnessFilter<T, A> {
+        CompactnessFilter {
+            tec_algorithm,
+            region,
+            min_compactness,
+            _t: Default::default(),
+        }
+    }
+
+    /// Returns true if the compactness of the TEC in the point set is at least the minimum.
+    ///
+    /// # Arguments
+    ///
+    /// * `tec` - The TEC to test
+    /// * `point_set` - The point set in which the TEC occurs
+    pub fn accepts(&self, tec: &Tec<T>, point_set: &PointSet<T>) -> bool {
+        compactness(tec, point_set, self.region) >= self.min_compactness
+    }
+
+    fn output(
+        &self,
+        tec: Tec<T>,
+        point_set: &PointSet<T>,
+        on_output: &mut impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) -> ControlFlow<()> {
+        if self.accepts(&tec, point_set) {
+            on_output(tec)
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+/// The region of a single occurrence of a pattern.
+enum Region {
+    BoundingBox(BoundingBox),
+    ConvexHull(ConvexHull),
+    TemporalSegment(f64, f64),
+}
+
+impl Region {
+    fn of<T: Point>(occurrence: &Pattern<T>, region: CompactnessRegion) -> Region {
+        match region {
+            CompactnessRegion::BoundingBox => Region::BoundingBox(occurrence.bounding_box()),
+            CompactnessRegion::ConvexHull => Region::ConvexHull(occurrence.convex_hull()),
+            CompactnessRegion::TemporalSegment => {
+                let bb = occurrence.bounding_box();
+                Region::TemporalSegment(bb.lower_x, bb.upper_x)
+            }
+        }
+    }
+
+    fn contains<T: Point>(&self, point: &T) -> bool {
+        match self {
+            Region::BoundingBox(bb) => bb.contains(point),
+            Region::ConvexHull(hull) => hull.contains(point),
+            Region::TemporalSegment(start, end) => {
+                let onset = point.component_f64(0).unwrap();
+                *start <= onset && onset <= *end
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::algorithm::TecAlgorithm;
+    use crate::discovery::compactness::{compactness, CompactnessFilter, CompactnessRegion};
+    use crate::discovery::siatec::Siatec;
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+    use crate::point_set::tec::Tec;
+
+    #[test]
+    fn test_compactness() {
+        let a = Point2Df64 { x: 0.0, y: 0.0 };
+        let b = Point2Df64 { x: 2.0, y: 0.0 };
+        let c = Point2Df64 { x: 0.0, y: 2.0 };
+        let point_set = PointSet::new(vec![
+            a,
+            b,
+            c,
+            // On the boundary of the hull.
+            Point2Df64 { x: 1.0, y: 1.0 },
+            // In the bounding box, but outside the hull.
+            Point2Df64 { x: 1.5, y: 1.5 },
+            // In the temporal segment only.
+            Point2Df64 { x: 1.0, y: 5.0 },
+            Point2Df64 { x: 5.0, y: 5.0 },
+        ]);
+        let tec = Tec {
+            pattern: Pattern::new(&vec![&a, &b, &c]),
+            translators: vec![],
+        };
+
+        assert_eq!(
+            3.0 / 5.0,
+            compactness(&tec, &point_set, CompactnessRegion::BoundingBox)
+        );
+        assert_eq!(
+            3.0 / 4.0,
+            compactness(&tec, &point_set, CompactnessRegion::ConvexHull)
+        );
+        assert_eq!(
+            3.0 / 6.0,
+            compactness(&tec, &point_set, CompactnessRegion::TemporalSegment)
+        );
+
+        // The hull of collinear points is a line segment.
+        let diagonal = Tec {
+            pattern: Pattern::new(&vec![&a, &Point2Df64 { x: 1.5, y: 1.5 }]),
+            translators: vec![],
+        };
+        assert_eq!(
+            2.0 / 3.0,
+            compactness(&diagonal, &point_set, CompactnessRegion::ConvexHull)
+        );
+    }
+
+    #[test]
+    fn test_filter_siatec_output() {
+        let mut points = Vec::new();
+        for i in 0..10 {
+            points.push(Point2Df64 {
+                x: i as f64,
+                y: ((i * 3) % 5) as f64,
+            });
+        }
+        let point_set = PointSet::new(points);
+
+        for region in [
+            CompactnessRegion::BoundingBox,
+            CompactnessRegion::ConvexHull,
+            CompactnessRegion::TemporalSegment,
+        ] {
+            let expected: Vec<Tec<Point2Df64>> = Siatec {}
+                .compute_tecs(&point_set)
+                .into_iter()
+                .filter(|tec| compactness(tec, &point_set, region) >= 0.5)
+                .collect();
+            let filter = CompactnessFilter::with(Siatec {}, region, 0.5);
+            assert_eq!(expected, filter.compute_tecs(&point_set));
+        }
+    }
+}
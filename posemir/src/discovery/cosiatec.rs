@@ -3,37 +3,104 @@
  * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
  */
 use std::marker::PhantomData;
+use std::ops::ControlFlow;
 
 use crate::discovery::algorithm::TecAlgorithm;
 use crate::discovery::heuristic::{stats_of, TecStats};
+use crate::discovery::progress::{IgnoreProgress, ProgressObserver, ProgressTracker, ProgressUnit};
+use crate::discovery::scored_tec::{ScoredTec, TecScores};
+use crate::discovery::tec_ranker::{DefaultRanker, TecRanker};
+use crate::error::PoSemirError;
 use crate::point_set::pattern::Pattern;
 use crate::point_set::point::Point;
 use crate::point_set::set::PointSet;
 use crate::point_set::tec::Tec;
 
 /// Implements the COSIATEC algorithm as described in [Meredith2013].
+///
+/// The computation stops when the base algorithm finds no TECs for the remaining points,
+/// e.g., when a single point remains, in which case the remaining points are left uncovered.
+///
+/// The points covered by the selected TECs are tracked with a mask over the original point set,
+/// and the points that are not yet covered are collected into a buffer that is reused
+/// in each iteration.
 pub struct Cosiatec<T: Point, A: TecAlgorithm<T>> {
     tec_algorithm: A,
+    ranker: Box<dyn TecRanker>,
     _t: PhantomData<T>,
 }
 
+/// The progress of COSIATEC after selecting a TEC.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CosiatecProgress {
+    /// The number of completed iterations, that is, the number of selected TECs.
+    pub iteration: usize,
+    /// The number of points not covered by any of the selected TECs.
+    pub remaining: usize,
+    /// The number of points in the point set.
+    pub total: usize,
+}
+
+/// The intermediate state of COSIATEC: the TECs selected so far and the points that are not
+/// covered by them. Long computations can be resumed from a checkpoint, e.g., after an
+/// interruption, with `Cosiatec::compute_tecs_from_checkpoint` (see also
+/// `io::json::write_checkpoint_to_json`).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CosiatecCheckpoint<T: Point> {
+    /// The selected TECs in the order in which they were selected.
+    pub selected: Vec<Tec<T>>,
+    /// The points of the point set that are not covered by the selected TECs.
+    pub remaining: PointSet<T>,
+}
+
+impl<T: Point> CosiatecCheckpoint<T> {
+    /// Returns the checkpoint at the start of the computation, where no TECs have been
+    /// selected and none of the points are covered.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set for which the TECs are computed
+    pub fn new(point_set: &PointSet<T>) -> CosiatecCheckpoint<T> {
+        CosiatecCheckpoint {
+            selected: Vec::new(),
+            remaining: point_set.clone(),
+        }
+    }
+}
+
 impl<T: Point, A: TecAlgorithm<T>> TecAlgorithm<T> for Cosiatec<T, A> {
     fn compute_tecs(&self, point_set: &PointSet<T>) -> Vec<Tec<T>> {
         let mut tecs = Vec::new();
-        let on_output = |tec: Tec<T>| tecs.push(tec);
+        let on_output = |tec: Tec<T>| {
+            tecs.push(tec);
+            ControlFlow::Continue(())
+        };
         self.compute_tecs_to_output(point_set, on_output);
         tecs
     }
 
-    fn compute_tecs_to_output(&self, point_set: &PointSet<T>, mut on_output: impl FnMut(Tec<T>)) {
-        let mut point_set_clone = point_set.clone();
-        let mut iterations = 0;
-        while !point_set_clone.is_empty() && iterations < point_set.len() {
-            let best = self.get_best_tec(&point_set_clone);
-            point_set_clone = point_set_clone.difference(&best.covered_set);
-            on_output(best.tec);
-            iterations += 1;
-        }
+    fn compute_tecs_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        self.compute_tecs_to_output_observed(point_set, &IgnoreProgress {}, on_output)
+    }
+
+    /// Computes the TECs as `compute_tecs_to_output` and reports the number of points
+    /// covered by the selected TECs as progress.
+    fn compute_tecs_to_output_observed(
+        &self,
+        point_set: &PointSet<T>,
+        observer: &dyn ProgressObserver,
+        mut on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        let tracker = ProgressTracker::new(observer, ProgressUnit::Points, point_set.len());
+        self.compute_stats_to_output(point_set, |best, progress| {
+            tracker.report(progress.total - progress.remaining);
+            on_output(best.tec)
+        });
     }
 }
 
@@ -41,12 +108,182 @@ impl<T: Point, A: TecAlgorithm<T>> Cosiatec<T, A> {
     /// Creates a new instance of COSIATEC that uses the given TEC-algorithm
     /// for computing the TEC candidates.
     pub fn with(tec_algorithm: A) -> Cosiatec<T, A> {
+        Cosiatec::with_ranker(tec_algorithm, DefaultRanker {})
+    }
+
+    /// Creates a new instance of COSIATEC that uses the given TEC-algorithm
+    /// for computing the TEC candidates and the given ranker for selecting the best TECs.
+    ///
+    /// # Arguments
+    ///
+    /// * `tec_algorithm` - The algorithm that computes the TEC candidates
+    /// * `ranker` - The ranker that defines which TECs are better, e.g., `MeredithRanker`
+    ///   for the heuristics of [Meredith2013] or a custom ranker built with `TecRankerBuilder`
+    pub fn with_ranker(tec_algorithm: A, ranker: impl TecRanker + 'static) -> Cosiatec<T, A> {
         Cosiatec {
             tec_algorithm,
+            ranker: Box::new(ranker),
             _t: Default::default(),
         }
     }
 
+    fn is_better_than(&self, a: &TecStats<T>, b: &TecStats<T>) -> bool {
+        self.ranker
+            .is_better_than(&TecScores::from(a), &TecScores::from(b))
+    }
+
+    /// Computes the TECs as `compute_tecs` but returns them with the heuristic scores
+    /// they had when they were selected.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set for which the TECs are computed
+    pub fn compute_scored_tecs(&self, point_set: &PointSet<T>) -> Vec<ScoredTec<T>> {
+        let mut scored_tecs = Vec::new();
+        self.compute_stats_to_output(point_set, |best, _| {
+            scored_tecs.push(best.into());
+            ControlFlow::Continue(())
+        });
+        scored_tecs
+    }
+
+    /// Computes the TECs as `compute_scored_tecs`, and executes on_iteration on each selected
+    /// TEC with the progress of the computation. The computation is stopped if on_iteration
+    /// returns `ControlFlow::Break`, so the caller can terminate it early, e.g., once enough of
+    /// the point set has been covered.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set for which the TECs are computed
+    /// * `on_iteration` - A function to execute on each selected TEC
+    pub fn compute_tecs_with_progress(
+        &self,
+        point_set: &PointSet<T>,
+        mut on_iteration: impl FnMut(ScoredTec<T>, CosiatecProgress) -> ControlFlow<()>,
+    ) {
+        self.compute_stats_to_output(point_set, |best, progress| {
+            on_iteration(best.into(), progress)
+        });
+    }
+
+    /// Computes the TECs as `compute_tecs`, but starts from the given checkpoint and executes
+    /// on_checkpoint with the updated checkpoint after each selected TEC, e.g., for saving
+    /// the checkpoint to disk. The computation is stopped if on_checkpoint returns
+    /// `ControlFlow::Break`. Returns the last checkpoint, whose selected TECs include those
+    /// of the given checkpoint, or an error if the remaining points of the given checkpoint
+    /// are not in the point set.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set for which the TECs are computed
+    /// * `checkpoint` - The checkpoint from which the computation is resumed, or
+    ///   `CosiatecCheckpoint::new` for starting from the beginning
+    /// * `on_checkpoint` - A function to execute on the checkpoint after each selected TEC
+    pub fn compute_tecs_from_checkpoint(
+        &self,
+        point_set: &PointSet<T>,
+        mut checkpoint: CosiatecCheckpoint<T>,
+        mut on_checkpoint: impl FnMut(&CosiatecCheckpoint<T>) -> ControlFlow<()>,
+    ) -> Result<CosiatecCheckpoint<T>, PoSemirError> {
+        let mut is_covered = vec![true; point_set.len()];
+        for point in &checkpoint.remaining {
+            match point_set.find_index(point) {
+                Ok(index) => is_covered[index] = false,
+                Err(_) => {
+                    return Err(PoSemirError::InvalidPointSet(String::from(
+                        "the checkpoint has points that are not in the point set",
+                    )))
+                }
+            }
+        }
+
+        let iteration = checkpoint.selected.len();
+        self.compute_stats_from(point_set, is_covered, iteration, |best, _| {
+            checkpoint.remaining.difference_with(&best.covered_set);
+            checkpoint.selected.push(best.tec);
+            on_checkpoint(&checkpoint)
+        });
+
+        Ok(checkpoint)
+    }
+
+    fn compute_stats_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        on_output: impl FnMut(TecStats<T>, CosiatecProgress) -> ControlFlow<()>,
+    ) {
+        self.compute_stats_from(point_set, vec![false; point_set.len()], 0, on_output)
+    }
+
+    /// Selects the TECs for the points that are not covered yet, starting from the given
+    /// number of completed iterations.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "cosiatec", skip_all, fields(n = point_set.len())))]
+    fn compute_stats_from(
+        &self,
+        point_set: &PointSet<T>,
+        mut is_covered: Vec<bool>,
+        mut iteration: usize,
+        mut on_output: impl FnMut(TecStats<T>, CosiatecProgress) -> ControlFlow<()>,
+    ) {
+        let total = point_set.len();
+        let mut remaining = is_covered.iter().filter(|covered| !**covered).count();
+        // Reused buffer for the points that are not yet covered.
+        let mut remaining_points: Vec<T> = Vec::with_capacity(total);
+
+        while remaining > 0 && iteration < total {
+            let best = if remaining == total {
+                self.get_best_tec(point_set)
+            } else {
+                remaining_points.clear();
+                remaining_points.extend(
+                    point_set
+                        .into_iter()
+                        .zip(&is_covered)
+                        .filter(|(_, covered)| !**covered)
+                        .map(|(point, _)| *point),
+                );
+                let remaining_set = PointSet::from_sorted(std::mem::take(&mut remaining_points));
+                let best = self.get_best_tec(&remaining_set);
+                // The points are not copied unless the base algorithm keeps them shared.
+                remaining_points = remaining_set.points();
+                best
+            };
+            // The base algorithm finds no TECs, e.g., for a single remaining point,
+            // so the remaining points cannot be covered.
+            if best.covered_set.is_empty() {
+                break;
+            }
+
+            for point in &best.covered_set {
+                if let Ok(index) = point_set.find_index(point) {
+                    if !is_covered[index] {
+                        is_covered[index] = true;
+                        remaining -= 1;
+                    }
+                }
+            }
+            iteration += 1;
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                iteration = iteration - 1,
+                pattern_size = best.tec.pattern.len(),
+                covered = best.covered_set.len(),
+                remaining = remaining,
+                "selected best TEC"
+            );
+
+            let progress = CosiatecProgress {
+                iteration,
+                remaining,
+                total,
+            };
+            if on_output(best, progress).is_break() {
+                break;
+            }
+        }
+    }
+
     fn get_best_tec(&self, point_set: &PointSet<T>) -> TecStats<T> {
         let mut best: TecStats<T> = TecStats {
             tec: Tec {
@@ -56,20 +293,22 @@ impl<T: Point, A: TecAlgorithm<T>> Cosiatec<T, A> {
             comp_ratio: -1.0,
             compactness: 0.0,
             covered_set: PointSet::new(Vec::new()),
+            weighted_coverage: 0.0,
             pattern_width: 0.0,
             pattern_area: 0.0,
         };
 
         let replace_best = |tec: Tec<T>| {
             let candidate = stats_of(tec.remove_redundant_translators(), point_set);
-            if candidate.is_better_than(&best) {
+            if self.is_better_than(&candidate, &best) {
                 best = candidate;
             }
 
             let conjugate = stats_of(tec.conjugate().remove_redundant_translators(), point_set);
-            if conjugate.is_better_than(&best) {
+            if self.is_better_than(&conjugate, &best) {
                 best = conjugate;
             }
+            ControlFlow::Continue(())
         };
 
         self.tec_algorithm
@@ -81,11 +320,16 @@ impl<T: Point, A: TecAlgorithm<T>> Cosiatec<T, A> {
 
 #[cfg(test)]
 mod tests {
+    use std::ops::ControlFlow;
+
     use crate::discovery::algorithm::TecAlgorithm;
-    use crate::discovery::cosiatec::Cosiatec;
+    use crate::discovery::cosiatec::{Cosiatec, CosiatecCheckpoint, CosiatecProgress};
     use crate::discovery::siatec::Siatec;
+    use crate::discovery::siatec_c::SiatecC;
+    use crate::discovery::tec_ranker::{RankingCriterion, TecRankerBuilder};
     use crate::point_set::pattern::Pattern;
     use crate::point_set::point::Point2Df64;
+    use crate::point_set::point_nd::PointNdf64;
     use crate::point_set::set::PointSet;
 
     #[test]
@@ -113,4 +357,168 @@ mod tests {
         );
         assert_eq!(vec![Point2Df64 { x: 2.0, y: 0.0 }], best_tec.translators);
     }
+
+    #[test]
+    fn test_uncovered_point() {
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 0.0, y: 0.0 },
+            Point2Df64 { x: 1.0, y: 0.0 },
+            Point2Df64 { x: 2.0, y: 0.0 },
+            Point2Df64 { x: 3.0, y: 0.0 },
+            Point2Df64 { x: 10.0, y: 7.0 },
+        ]);
+
+        let tecs = Cosiatec::with(Siatec {}).compute_tecs(&point_set);
+        assert_eq!(1, tecs.len());
+        assert_eq!(4, tecs[0].coverage_size());
+
+        // The computation also terminates when SIATEC-C finds no TECs for the remaining points.
+        let tecs = Cosiatec::with(SiatecC::new(2.0)).compute_tecs(&point_set);
+        assert!(!tecs.is_empty());
+        assert!(tecs.iter().all(|tec| !tec.pattern.is_empty()));
+    }
+
+    #[test]
+    fn test_one_dimensional_points() {
+        let point_set = PointSet::new(
+            [0.0, 1.0, 2.0, 3.0]
+                .iter()
+                .map(|&x| PointNdf64 { components: [x] })
+                .collect(),
+        );
+
+        let tecs = Cosiatec::with(Siatec {}).compute_tecs(&point_set);
+        assert_eq!(1, tecs.len());
+        assert_eq!(4, tecs[0].coverage_size());
+    }
+
+    #[test]
+    fn test_custom_ranker() {
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 0.0, y: 0.0 },
+            Point2Df64 { x: 1.0, y: 0.0 },
+            Point2Df64 { x: 2.0, y: 0.0 },
+            Point2Df64 { x: 3.0, y: 0.0 },
+        ]);
+
+        // Prioritizing narrow patterns selects the single point pattern instead of the
+        // two point pattern selected by the default ranker.
+        let ranker = TecRankerBuilder::new()
+            .criterion(RankingCriterion::PatternWidth, 1.0)
+            .criterion(RankingCriterion::Coverage, 1.0)
+            .lexicographic();
+        let tecs = Cosiatec::with_ranker(Siatec {}, ranker).compute_tecs(&point_set);
+
+        assert_eq!(1, tecs.len());
+        assert_eq!(1, tecs[0].pattern.len());
+        assert_eq!(4, tecs[0].coverage_size());
+    }
+
+    #[test]
+    fn test_scored_tecs_equal_tecs() {
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 0.0, y: 0.0 },
+            Point2Df64 { x: 1.0, y: 1.0 },
+            Point2Df64 { x: 2.0, y: 0.0 },
+            Point2Df64 { x: 3.0, y: 1.0 },
+            Point2Df64 { x: 5.0, y: 5.0 },
+            Point2Df64 { x: 6.0, y: 7.0 },
+        ]);
+
+        let cosiatec = Cosiatec::with(Siatec {});
+        let tecs = cosiatec.compute_tecs(&point_set);
+        let scored_tecs = cosiatec.compute_scored_tecs(&point_set);
+
+        assert_eq!(tecs.len(), scored_tecs.len());
+        for (tec, scored) in tecs.iter().zip(&scored_tecs) {
+            assert_eq!(tec, &scored.tec);
+            assert_eq!(tec.covered_set().len(), scored.scores.coverage);
+        }
+    }
+
+    #[test]
+    fn test_progress_and_early_termination() {
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 0.0, y: 0.0 },
+            Point2Df64 { x: 1.0, y: 1.0 },
+            Point2Df64 { x: 2.0, y: 0.0 },
+            Point2Df64 { x: 3.0, y: 1.0 },
+            Point2Df64 { x: 5.0, y: 5.0 },
+            Point2Df64 { x: 6.0, y: 7.0 },
+        ]);
+        let cosiatec = Cosiatec::with(Siatec {});
+        let tecs = cosiatec.compute_tecs(&point_set);
+
+        let mut progresses = Vec::new();
+        cosiatec.compute_tecs_with_progress(&point_set, |scored, progress| {
+            assert_eq!(tecs[progress.iteration - 1], scored.tec);
+            progresses.push(progress);
+            ControlFlow::Continue(())
+        });
+        assert_eq!(tecs.len(), progresses.len());
+        assert_eq!(
+            CosiatecProgress {
+                iteration: tecs.len(),
+                remaining: 0,
+                total: point_set.len()
+            },
+            progresses[progresses.len() - 1]
+        );
+        assert!(progresses
+            .windows(2)
+            .all(|pair| pair[1].remaining < pair[0].remaining));
+
+        let mut iterations = 0;
+        cosiatec.compute_tecs_with_progress(&point_set, |_, _| {
+            iterations += 1;
+            ControlFlow::Break(())
+        });
+        assert_eq!(1, iterations);
+    }
+
+    #[test]
+    fn test_resume_from_checkpoint() {
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 0.0, y: 0.0 },
+            Point2Df64 { x: 1.0, y: 1.0 },
+            Point2Df64 { x: 2.0, y: 0.0 },
+            Point2Df64 { x: 3.0, y: 1.0 },
+            Point2Df64 { x: 5.0, y: 5.0 },
+            Point2Df64 { x: 6.0, y: 7.0 },
+            Point2Df64 { x: 8.0, y: 2.0 },
+        ]);
+        let cosiatec = Cosiatec::with(Siatec {});
+        let tecs = cosiatec.compute_tecs(&point_set);
+        assert!(tecs.len() > 1);
+
+        // Interrupt the computation after the first selected TEC.
+        let interrupted = cosiatec
+            .compute_tecs_from_checkpoint(&point_set, CosiatecCheckpoint::new(&point_set), |_| {
+                ControlFlow::Break(())
+            })
+            .unwrap();
+        assert_eq!(&tecs[..1], &interrupted.selected[..]);
+        assert_eq!(
+            point_set.len() - tecs[0].covered_set().len(),
+            interrupted.remaining.len()
+        );
+
+        let mut checkpoints = 0;
+        let resumed = cosiatec
+            .compute_tecs_from_checkpoint(&point_set, interrupted, |_| {
+                checkpoints += 1;
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        assert_eq!(tecs, resumed.selected);
+        assert_eq!(tecs.len() - 1, checkpoints);
+        assert!(resumed.remaining.is_empty());
+
+        let other = PointSet::new(vec![Point2Df64 { x: 9.0, y: 9.0 }]);
+        assert!(cosiatec
+            .compute_tecs_from_checkpoint(&point_set, CosiatecCheckpoint::new(&other), |_| {
+                ControlFlow::Continue(())
+            })
+            .is_err());
+    }
 }
@@ -0,0 +1,316 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use crate::discovery::algorithm::TecAlgorithm;
+use crate::error::PoSemirError;
+use crate::point_set::point::{Point, Point2Di64};
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// Defines how the outputs of two algorithms are compared when cross-checking them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Agreement {
+    /// The algorithms must produce the same translationally distinct TECs, that is,
+    /// the outputs are equal after merging translationally equivalent TECs
+    /// (see `Tec::merge_translationally_equivalent`).
+    DistinctTecs,
+    /// The TECs produced by the algorithms must cover the same points, that is,
+    /// the unions of the covered sets of the TECs must be equal. This is useful for
+    /// algorithms whose output depends on the order in which the patterns are processed,
+    /// such as SIATEC-C and SIATEC-CH.
+    CoveredSets,
+}
+
+/// Describes a point set on which two algorithms disagree.
+#[derive(Debug)]
+pub struct Divergence<T: Point> {
+    /// The point set on which the algorithms disagree.
+    pub point_set: PointSet<T>,
+    /// The number of compared items (TECs or covered points) found only by the first algorithm.
+    pub only_in_first: usize,
+    /// The number of compared items (TECs or covered points) found only by the second algorithm.
+    pub only_in_second: usize,
+}
+
+impl<T: Point> Display for Divergence<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Algorithms disagree on {:?}: {} results only in first, {} results only in second",
+            self.point_set.clone().points(),
+            self.only_in_first,
+            self.only_in_second
+        )
+    }
+}
+
+impl<T: Point> Error for Divergence<T> {}
+
+/// Runs both algorithms on the point set and checks that their outputs agree.
+///
+/// # Arguments
+///
+/// * `first` - The first algorithm
+/// * `second` - The second algorithm
+/// * `point_set` - The point set on which the algorithms are run
+/// * `agreement` - How the outputs of the algorithms are compared
+pub fn cross_check<T: Point, A: TecAlgorithm<T>, B: TecAlgorithm<T>>(
+    first: &A,
+    second: &B,
+    point_set: &PointSet<T>,
+    agreement: Agreement,
+) -> Result<(), Divergence<T>> {
+    let first_tecs = first.compute_tecs(point_set);
+    let second_tecs = second.compute_tecs(point_set);
+
+    let (only_in_first, only_in_second) = match agreement {
+        Agreement::DistinctTecs => {
+            count_differences(distinct_tecs(&first_tecs), distinct_tecs(&second_tecs))
+        }
+        Agreement::CoveredSets => {
+            count_differences(covered_points(&first_tecs), covered_points(&second_tecs))
+        }
+    };
+
+    if only_in_first == 0 && only_in_second == 0 {
+        Ok(())
+    } else {
+        Err(Divergence {
+            point_set: point_set.clone(),
+            only_in_first,
+            only_in_second,
+        })
+    }
+}
+
+/// Cross-checks the algorithms (see `cross_check`) on the given number of point sets produced
+/// by the generator. Returns the first point set on which the algorithms disagree.
+///
+/// # Arguments
+///
+/// * `first` - The first algorithm
+/// * `second` - The second algorithm
+/// * `generator` - The generator for the point sets
+/// * `count` - The number of point sets on which the algorithms are run
+/// * `agreement` - How the outputs of the algorithms are compared
+pub fn cross_check_random<A, B>(
+    first: &A,
+    second: &B,
+    generator: &mut RandomPointSets,
+    count: usize,
+    agreement: Agreement,
+) -> Result<(), Divergence<Point2Di64>>
+where
+    A: TecAlgorithm<Point2Di64>,
+    B: TecAlgorithm<Point2Di64>,
+{
+    for point_set in generator.take(count) {
+        cross_check(first, second, &point_set, agreement)?;
+    }
+
+    Ok(())
+}
+
+fn distinct_tecs<T: Point>(tecs: &[Tec<T>]) -> HashSet<Tec<T>> {
+    Tec::merge_translationally_equivalent(tecs)
+        .into_iter()
+        .collect()
+}
+
+fn covered_points<T: Point>(tecs: &[Tec<T>]) -> HashSet<T> {
+    tecs.iter()
+        .flat_map(|tec| tec.covered_set().points())
+        .collect()
+}
+
+fn count_differences<V: Eq + std::hash::Hash>(
+    first: HashSet<V>,
+    second: HashSet<V>,
+) -> (usize, usize) {
+    (
+        first.difference(&second).count(),
+        second.difference(&first).count(),
+    )
+}
+
+/// Generates random point sets of integer points for cross-checking algorithms.
+/// The generator is seeded, so the same seed always produces the same point sets.
+/// The first component of the points is the onset and the second the pitch.
+pub struct RandomPointSets {
+    /// The number of points in each point set.
+    pub size: usize,
+    /// The onsets of the points are in the range [0, max_onset).
+    pub max_onset: i64,
+    /// The pitches of the points are in the range [0, max_pitch).
+    pub max_pitch: i64,
+    state: u64,
+}
+
+impl RandomPointSets {
+    /// Returns a new generator of point sets. Returns an error if the upper bounds are
+    /// not positive or if the ranges do not have room for the given number of points.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed of the generator
+    /// * `size` - The number of points in each point set, can also be 0 or 1
+    /// * `max_onset` - The upper bound (exclusive) for the onsets of the points
+    /// * `max_pitch` - The upper bound (exclusive) for the pitches of the points
+    pub fn new(
+        seed: u64,
+        size: usize,
+        max_onset: i64,
+        max_pitch: i64,
+    ) -> Result<RandomPointSets, PoSemirError> {
+        if max_onset <= 0 || max_pitch <= 0 {
+            return Err(PoSemirError::InvalidConfiguration(String::from(
+                "the upper bounds of the onsets and pitches must be positive",
+            )));
+        }
+        let has_room = max_onset
+            .checked_mul(max_pitch)
+            .is_none_or(|room| size as u64 <= room as u64);
+        if !has_room {
+            return Err(PoSemirError::InvalidConfiguration(format!(
+                "the ranges do not have room for {} points",
+                size
+            )));
+        }
+
+        Ok(RandomPointSets {
+            size,
+            max_onset,
+            max_pitch,
+            // The state of the xorshift generator must not be zero.
+            state: seed | 1,
+        })
+    }
+
+    /// Returns the next pseudorandom number (xorshift64*).
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+
+    fn next_in_range(&mut self, upper: i64) -> i64 {
+        (self.next_u64() % upper as u64) as i64
+    }
+}
+
+impl Iterator for RandomPointSets {
+    type Item = PointSet<Point2Di64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut points = HashSet::with_capacity(self.size);
+        while points.len() < self.size {
+            points.insert(Point2Di64 {
+                x: self.next_in_range(self.max_onset),
+                y: self.next_in_range(self.max_pitch),
+            });
+        }
+
+        Some(PointSet::new(points.into_iter().collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::cross_check::{
+        cross_check, cross_check_random, Agreement, RandomPointSets,
+    };
+    use crate::discovery::mtp_to_tec::MtpToTec;
+    use crate::discovery::sia::Sia;
+    use crate::discovery::siatec::Siatec;
+    use crate::discovery::siatec_c::SiatecC;
+    use crate::discovery::siatec_ch::SiatecCH;
+    use crate::point_set::point::Point2Di64;
+    use crate::point_set::set::PointSet;
+
+    #[test]
+    fn test_siatec_agrees_with_sia_tecs() {
+        let mut generator = RandomPointSets::new(1, 12, 16, 8).unwrap();
+        let result = cross_check_random(
+            &Siatec {},
+            &MtpToTec::with(Sia::new()),
+            &mut generator,
+            20,
+            Agreement::DistinctTecs,
+        );
+
+        assert!(result.is_ok(), "{}", result.unwrap_err());
+    }
+
+    #[test]
+    fn test_siatec_c_agrees_with_siatec_ch() {
+        let mut generator = RandomPointSets::new(2, 12, 16, 8).unwrap();
+        let result = cross_check_random(
+            &SiatecC::new(3.0),
+            &SiatecCH::new(3.0),
+            &mut generator,
+            20,
+            Agreement::CoveredSets,
+        );
+
+        assert!(result.is_ok(), "{}", result.unwrap_err());
+    }
+
+    #[test]
+    fn test_cross_check_small_point_sets() {
+        for size in 0..2 {
+            let mut generator = RandomPointSets::new(3, size, 16, 8).unwrap();
+            let result = cross_check_random(
+                &Siatec {},
+                &MtpToTec::with(Sia::new()),
+                &mut generator,
+                5,
+                Agreement::DistinctTecs,
+            );
+            assert!(result.is_ok(), "{}", result.unwrap_err());
+
+            let result = cross_check_random(
+                &SiatecC::new(3.0),
+                &SiatecCH::new(3.0),
+                &mut generator,
+                5,
+                Agreement::CoveredSets,
+            );
+            assert!(result.is_ok(), "{}", result.unwrap_err());
+        }
+    }
+
+    #[test]
+    fn test_invalid_generator() {
+        assert!(RandomPointSets::new(1, 12, 0, 8).is_err());
+        assert!(RandomPointSets::new(1, 12, 3, 3).is_err());
+        assert!(RandomPointSets::new(1, 9, 3, 3).is_ok());
+    }
+
+    #[test]
+    fn test_divergence() {
+        let point_set = PointSet::new(vec![
+            Point2Di64 { x: 0, y: 0 },
+            Point2Di64 { x: 1, y: 0 },
+            Point2Di64 { x: 5, y: 0 },
+            Point2Di64 { x: 6, y: 0 },
+        ]);
+
+        // SIATEC-C splits the repeated two-point pattern on the IOI gap.
+        let divergence = cross_check(
+            &Siatec {},
+            &SiatecC::new(0.5),
+            &point_set,
+            Agreement::DistinctTecs,
+        )
+        .unwrap_err();
+
+        assert_eq!(point_set, divergence.point_set);
+        assert!(divergence.only_in_first > 0);
+    }
+}
@@ -0,0 +1,154 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::collections::HashMap;
+use std::hash::{BuildHasher, BuildHasherDefault};
+
+use hashers::fx_hash::FxHasher64;
+
+use crate::discovery::siatec_c::SiatecC;
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+
+/// The indices of the points from and to which a difference vector is computed.
+pub type IndPair = [usize; 2];
+
+/// Trait for the difference index used by SIATEC-C for finding the translators of patterns.
+/// The index maps the forward differences between the points whose inter-onset interval (IOI)
+/// is at most the maximum IOI to the index pairs of the points between which they occur.
+pub trait DiffIndex<T: Point> {
+    /// Returns the index of the forward differences in the point set with an IOI of
+    /// at most max_ioi.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set whose differences are indexed
+    /// * `max_ioi` - The maximum IOI of the indexed differences
+    fn build(point_set: &PointSet<T>, max_ioi: f64) -> Self
+    where
+        Self: Sized;
+
+    /// Returns the index pairs of the points between which the difference occurs
+    /// in ascending order, or None if the difference is not in the index.
+    ///
+    /// # Arguments
+    ///
+    /// * `diff` - The difference vector
+    fn get(&self, diff: &T) -> Option<&[IndPair]>;
+}
+
+/// A difference index that keeps the differences in a vector in ascending lexicographical
+/// order, and finds them with binary search.
+pub struct SortedDiffIndex<T: Point> {
+    diffs: Vec<(T, Vec<IndPair>)>,
+}
+
+impl<T: Point> DiffIndex<T> for SortedDiffIndex<T> {
+    fn build(point_set: &PointSet<T>, max_ioi: f64) -> Self {
+        let mut forward_diffs: Vec<(T, IndPair)> = Vec::new();
+        for_each_forward_diff(point_set, max_ioi, |diff, ind_pair| {
+            forward_diffs.push((diff, ind_pair))
+        });
+        // The sort is stable, so the index pairs of each difference stay in ascending order.
+        forward_diffs.sort_by_key(|forward_diff| forward_diff.0);
+
+        let mut diffs: Vec<(T, Vec<IndPair>)> = Vec::new();
+        for (diff, ind_pair) in forward_diffs {
+            match diffs.last_mut() {
+                Some((last, ind_pairs)) if *last == diff => ind_pairs.push(ind_pair),
+                _ => diffs.push((diff, vec![ind_pair])),
+            }
+        }
+
+        SortedDiffIndex { diffs }
+    }
+
+    fn get(&self, diff: &T) -> Option<&[IndPair]> {
+        let index = self.diffs.binary_search_by(|d| d.0.cmp(diff)).ok()?;
+        Some(&self.diffs[index].1)
+    }
+}
+
+/// A difference index that keeps the differences in a hash map that uses the hasher
+/// built by S. By default, the fast non-cryptographic FxHasher is used.
+pub struct HashedDiffIndex<T: Point, S: BuildHasher = BuildHasherDefault<FxHasher64>> {
+    diffs: HashMap<T, Vec<IndPair>, S>,
+}
+
+impl<T: Point, S: BuildHasher + Default> DiffIndex<T> for HashedDiffIndex<T, S> {
+    fn build(point_set: &PointSet<T>, max_ioi: f64) -> Self {
+        let mut diffs: HashMap<T, Vec<IndPair>, S> = HashMap::with_hasher(S::default());
+        for_each_forward_diff(point_set, max_ioi, |diff, ind_pair| {
+            diffs.entry(diff).or_default().push(ind_pair)
+        });
+
+        HashedDiffIndex { diffs }
+    }
+
+    fn get(&self, diff: &T) -> Option<&[IndPair]> {
+        self.diffs.get(diff).map(|ind_pairs| ind_pairs.as_slice())
+    }
+}
+
+/// Executes on_diff for each forward difference with an IOI of at most max_ioi
+/// in ascending order of the index pairs.
+fn for_each_forward_diff<T: Point>(
+    point_set: &PointSet<T>,
+    max_ioi: f64,
+    mut on_diff: impl FnMut(T, IndPair),
+) {
+    let n = point_set.len();
+
+    for i in 0..n {
+        let from = &point_set[i];
+
+        for j in (i + 1)..n {
+            let to = &point_set[j];
+            // The IOI is computed from the onsets of the points in the same way as when
+            // splitting the patterns on IOI gaps. This guarantees that the difference
+            // index contains all the differences in the vectorized split patterns.
+            if SiatecC::ioi(from, to) > max_ioi {
+                break;
+            }
+
+            on_diff(*to - *from, [i, j]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::RandomState;
+
+    use crate::discovery::diff_index::{DiffIndex, HashedDiffIndex, SortedDiffIndex};
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+
+    #[test]
+    fn test_indices_agree() {
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 0.0, y: 60.0 },
+            Point2Df64 { x: 1.0, y: 62.0 },
+            Point2Df64 { x: 2.0, y: 60.0 },
+            Point2Df64 { x: 3.0, y: 62.0 },
+            Point2Df64 { x: 6.0, y: 60.0 },
+        ]);
+
+        let sorted: SortedDiffIndex<Point2Df64> = SortedDiffIndex::build(&point_set, 2.0);
+        let hashed: HashedDiffIndex<Point2Df64> = HashedDiffIndex::build(&point_set, 2.0);
+        let std_hashed: HashedDiffIndex<Point2Df64, RandomState> =
+            HashedDiffIndex::build(&point_set, 2.0);
+
+        let step = Point2Df64 { x: 1.0, y: 2.0 };
+        let expected: &[[usize; 2]] = &[[0, 1], [2, 3]];
+        assert_eq!(Some(expected), sorted.get(&step));
+        assert_eq!(Some(expected), hashed.get(&step));
+        assert_eq!(Some(expected), std_hashed.get(&step));
+
+        // The IOI between the points 3 and 4 exceeds the maximum.
+        let gap = Point2Df64 { x: 3.0, y: -2.0 };
+        assert_eq!(None, sorted.get(&gap));
+        assert_eq!(None, hashed.get(&gap));
+    }
+}
@@ -0,0 +1,198 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+use crate::point_set::tec::Tec;
+
+/// The strategies for removing translationally equivalent duplicates from the output of a TEC
+/// algorithm. For example, SIATEC-C can find the same pattern in several windows, so that it
+/// outputs several TECs whose patterns are translations of each other.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DuplicateStrategy {
+    /// Keeps the first TEC of the translationally equivalent TECs.
+    KeepFirst,
+    /// Keeps the TEC that covers the most points of the translationally equivalent TECs,
+    /// or the first of them if several cover equally many points.
+    KeepLargestCover,
+    /// Merges the translationally equivalent TECs into one TEC that has the occurrences of
+    /// all of them (see `Tec::merge_translationally_equivalent`).
+    MergeTranslators,
+}
+
+impl DuplicateStrategy {
+    /// Returns the value of the `DUPLICATE_STRATEGY` parameter of the algorithm registry
+    /// that selects this strategy (see `registry::create_algorithm`).
+    pub fn parameter_value(self) -> f64 {
+        match self {
+            DuplicateStrategy::KeepFirst => 1.0,
+            DuplicateStrategy::KeepLargestCover => 2.0,
+            DuplicateStrategy::MergeTranslators => 3.0,
+        }
+    }
+
+    /// Returns the strategy selected by the value of the `DUPLICATE_STRATEGY` parameter of
+    /// the algorithm registry, or None if the value does not select a strategy.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value of the parameter, see `parameter_value`
+    pub fn from_parameter_value(value: f64) -> Option<DuplicateStrategy> {
+        [
+            DuplicateStrategy::KeepFirst,
+            DuplicateStrategy::KeepLargestCover,
+            DuplicateStrategy::MergeTranslators,
+        ]
+        .into_iter()
+        .find(|strategy| strategy.parameter_value() == value)
+    }
+}
+
+/// Removes the translationally equivalent duplicates from the TECs with the given strategy.
+/// The remaining TECs are in the order of the first TEC of each class of translationally
+/// equivalent TECs.
+///
+/// # Arguments
+///
+/// * `tecs` - The TECs from which the duplicates are removed
+/// * `strategy` - The strategy that selects the TEC of each class of duplicates
+pub fn remove_translational_duplicates<T: Point>(
+    tecs: &mut Vec<Tec<T>>,
+    strategy: DuplicateStrategy,
+) {
+    if strategy == DuplicateStrategy::MergeTranslators {
+        *tecs = Tec::merge_translationally_equivalent(tecs);
+        return;
+    }
+
+    let mut class_indices: HashMap<Pattern<T>, usize> = HashMap::new();
+    let mut retained: Vec<Tec<T>> = Vec::with_capacity(tecs.len());
+    for tec in tecs.drain(..) {
+        let key = tec.sorted_pattern().vectorize();
+        match class_indices.get(&key) {
+            Some(i) => {
+                if strategy == DuplicateStrategy::KeepLargestCover
+                    && tec.covered_set().len() > retained[*i].covered_set().len()
+                {
+                    retained[*i] = tec;
+                }
+            }
+            None => {
+                class_indices.insert(key, retained.len());
+                retained.push(tec);
+            }
+        }
+    }
+
+    *tecs = retained;
+}
+
+/// Runs compute with on_output as its output. If a strategy is given, the output of compute
+/// is buffered and output only after the duplicates have been removed with the strategy.
+pub(crate) fn output_without_duplicates<T: Point>(
+    strategy: Option<DuplicateStrategy>,
+    compute: impl FnOnce(&mut dyn FnMut(Tec<T>) -> ControlFlow<()>),
+    mut on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+) {
+    let strategy = match strategy {
+        Some(strategy) => strategy,
+        None => {
+            compute(&mut on_output);
+            return;
+        }
+    };
+
+    let mut tecs = Vec::new();
+    compute(&mut |tec| {
+        tecs.push(tec);
+        ControlFlow::Continue(())
+    });
+
+    remove_translational_duplicates(&mut tecs, strategy);
+    for tec in tecs {
+        if on_output(tec).is_break() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::algorithm::TecAlgorithm;
+    use crate::discovery::duplicates::{remove_translational_duplicates, DuplicateStrategy};
+    use crate::discovery::siatec_c::SiatecC;
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+    use crate::point_set::tec::Tec;
+
+    fn tec(points: &[(f64, f64)], translators: &[(f64, f64)]) -> Tec<Point2Df64> {
+        let points: Vec<Point2Df64> = points
+            .iter()
+            .map(|(x, y)| Point2Df64 { x: *x, y: *y })
+            .collect();
+        Tec {
+            pattern: Pattern::new(&points.iter().collect()),
+            translators: translators
+                .iter()
+                .map(|(x, y)| Point2Df64 { x: *x, y: *y })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_strategies() {
+        let tecs = vec![
+            tec(&[(0.0, 0.0), (1.0, 0.0)], &[(4.0, 0.0)]),
+            tec(&[(0.0, 0.0)], &[(1.0, 0.0)]),
+            tec(&[(4.0, 0.0), (5.0, 0.0)], &[(2.0, 0.0), (4.0, 0.0)]),
+        ];
+
+        let mut first = tecs.clone();
+        remove_translational_duplicates(&mut first, DuplicateStrategy::KeepFirst);
+        assert_eq!(vec![tecs[0].clone(), tecs[1].clone()], first);
+
+        let mut largest = tecs.clone();
+        remove_translational_duplicates(&mut largest, DuplicateStrategy::KeepLargestCover);
+        assert_eq!(vec![tecs[2].clone(), tecs[1].clone()], largest);
+
+        let mut merged = tecs.clone();
+        remove_translational_duplicates(&mut merged, DuplicateStrategy::MergeTranslators);
+        assert_eq!(2, merged.len());
+        assert_eq!(
+            tec(
+                &[(0.0, 0.0), (1.0, 0.0)],
+                &[(4.0, 0.0), (6.0, 0.0), (8.0, 0.0)]
+            ),
+            merged[0]
+        );
+    }
+
+    #[test]
+    fn test_siatec_c_without_duplicates() {
+        let point_set = PointSet::new(
+            [1.0, 2.0, 5.0, 6.0, 9.0, 10.0]
+                .iter()
+                .map(|x| Point2Df64 { x: *x, y: 1.0 })
+                .collect(),
+        );
+        let siatec_c = SiatecC::new(2.0);
+
+        for strategy in [
+            DuplicateStrategy::KeepFirst,
+            DuplicateStrategy::KeepLargestCover,
+            DuplicateStrategy::MergeTranslators,
+        ] {
+            let mut expected = siatec_c.compute_tecs(&point_set);
+            remove_translational_duplicates(&mut expected, strategy);
+            assert!(expected.len() < siatec_c.compute_tecs(&point_set).len());
+
+            let direct = SiatecC::new(2.0).with_duplicate_strategy(strategy);
+            assert_eq!(expected, direct.compute_tecs(&point_set));
+        }
+    }
+}
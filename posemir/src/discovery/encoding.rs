@@ -0,0 +1,160 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use crate::discovery::algorithm::TecAlgorithm;
+use crate::error::PoSemirError;
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// A lossless encoding of a point set as a list of TECs and the residual points that are not
+/// covered by any of the TECs, e.g., the output of COSIATEC or SIATECCompress as in
+/// [Meredith2013]. The point set is reconstructed from the encoding with `decode`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Encoding<T: Point> {
+    /// The TECs whose covered sets are subsets of the encoded point set.
+    pub tecs: Vec<Tec<T>>,
+    /// The points of the encoded point set not covered by the TECs.
+    pub residual: PointSet<T>,
+}
+
+impl<T: Point> Encoding<T> {
+    /// Returns the encoding of the point set by the given TECs, where the points not covered
+    /// by the TECs are the residual. Returns an error if the TECs cover points that are not
+    /// in the point set, as the point set could then not be decoded from the encoding.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The encoded point set
+    /// * `tecs` - The TECs that encode the point set
+    pub fn with_tecs(
+        point_set: &PointSet<T>,
+        tecs: Vec<Tec<T>>,
+    ) -> Result<Encoding<T>, PoSemirError> {
+        let mut residual = point_set.clone();
+        for tec in &tecs {
+            let covered_set = tec.covered_set();
+            if !covered_set
+                .into_iter()
+                .all(|point| point_set.contains(point))
+            {
+                return Err(PoSemirError::InvalidPointSet(String::from(
+                    "a TEC covers points that are not in the encoded point set",
+                )));
+            }
+            residual.difference_with(&covered_set);
+        }
+
+        Ok(Encoding { tecs, residual })
+    }
+
+    /// Returns the size of the encoding, i.e., the total number of points and translators
+    /// in the TECs and the number of residual points.
+    pub fn size(&self) -> usize {
+        let tec_size: usize = self
+            .tecs
+            .iter()
+            .map(|tec| tec.pattern.len() + tec.translators.len())
+            .sum();
+        tec_size + self.residual.len()
+    }
+
+    /// Returns the compression ratio of the encoding, i.e., the size of the decoded point set
+    /// divided by the size of the encoding, or 1 if the encoded point set is empty.
+    pub fn compression_ratio(&self) -> f64 {
+        let size = self.size();
+        if size == 0 {
+            return 1.0;
+        }
+        decode(self).len() as f64 / size as f64
+    }
+}
+
+/// Returns the lossless encoding of the point set by the TECs computed with the given
+/// algorithm, e.g., COSIATEC or SIATECCompress (see `Encoding`). Returns an error if the
+/// algorithm outputs TECs that cover points that are not in the point set.
+///
+/// # Arguments
+///
+/// * `algorithm` - The algorithm that computes the TECs of the encoding
+/// * `point_set` - The point set that is encoded
+pub fn encode<T: Point, A: TecAlgorithm<T>>(
+    algorithm: &A,
+    point_set: &PointSet<T>,
+) -> Result<Encoding<T>, PoSemirError> {
+    Encoding::with_tecs(point_set, algorithm.compute_tecs(point_set))
+}
+
+/// Returns the point set reconstructed from the encoding, i.e., the union of the covered sets
+/// of the TECs and the residual points.
+///
+/// # Arguments
+///
+/// * `encoding` - The encoding of the point set
+pub fn decode<T: Point>(encoding: &Encoding<T>) -> PointSet<T> {
+    let mut point_set = encoding.residual.clone();
+    for tec in &encoding.tecs {
+        point_set.union_with(&tec.covered_set());
+    }
+    point_set
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::cosiatec::Cosiatec;
+    use crate::discovery::encoding::{decode, encode, Encoding};
+    use crate::discovery::siatec::Siatec;
+    use crate::discovery::siatec_compress::SiatecCompress;
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+    use crate::point_set::tec::Tec;
+    use crate::synthetic::{PlantedConfig, SyntheticGenerator};
+
+    #[test]
+    fn test_encode_and_decode() {
+        let config = PlantedConfig {
+            pattern_size: 5,
+            pattern_span: 8,
+            copies: 4,
+            noise_size: 10,
+            max_pitch: 40,
+        };
+        let point_set = SyntheticGenerator::new(5).planted(&config).point_set;
+
+        let encoding = encode(&Cosiatec::with(Siatec {}), &point_set).unwrap();
+        assert_eq!(point_set, decode(&encoding));
+        assert!(encoding.compression_ratio() > 1.0);
+
+        let encoding = encode(&SiatecCompress::with(Siatec {}), &point_set).unwrap();
+        assert_eq!(point_set, decode(&encoding));
+        assert_eq!(
+            point_set.len() as f64 / encoding.size() as f64,
+            encoding.compression_ratio()
+        );
+    }
+
+    #[test]
+    fn test_residual() {
+        let a = Point2Df64 { x: 0.0, y: 0.0 };
+        let b = Point2Df64 { x: 1.0, y: 2.0 };
+        let c = Point2Df64 { x: 5.0, y: 5.0 };
+        let translator = Point2Df64 { x: 2.0, y: 0.0 };
+        let point_set = PointSet::new(vec![a, b, c, a + translator, b + translator]);
+        let tec = Tec {
+            pattern: Pattern::new(&vec![&a, &b]),
+            translators: vec![translator],
+        };
+
+        let encoding = Encoding::with_tecs(&point_set, vec![tec.clone()]).unwrap();
+        assert_eq!(PointSet::new(vec![c]), encoding.residual);
+        assert_eq!(4, encoding.size());
+        assert_eq!(5.0 / 4.0, encoding.compression_ratio());
+        assert_eq!(point_set, decode(&encoding));
+
+        let partial = PointSet::new(vec![a, b]);
+        assert!(Encoding::with_tecs(&partial, vec![tec]).is_err());
+    }
+}
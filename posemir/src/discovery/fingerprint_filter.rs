@@ -0,0 +1,219 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::cmp::Ordering;
+
+use crate::point_set::mtp::Mtp;
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+use crate::point_set::tec::Tec;
+
+/// Implements a categorization stage for the output of SIAR and SIACT in the style of
+/// SIARCT-CFP [Collins et al. 2013]. The patterns are clustered into categories of
+/// rhythmically and intervallically similar patterns and only one representative of each
+/// category is retained.
+///
+/// The fingerprint of a pattern is the multiset of the difference vectors between all pairs of
+/// its points, so it is invariant under translation. The similarity of two patterns is the size
+/// of the intersection of their fingerprints divided by the size of the larger fingerprint.
+/// The patterns are categorized in descending order of size: the largest uncategorized pattern
+/// is the representative of a new category that contains all of the uncategorized patterns
+/// whose similarity to the representative is at least the minimum similarity.
+pub struct FingerprintFilter {
+    /// The minimum similarity of the patterns in a category to its representative,
+    /// in the range [0, 1].
+    pub min_similarity: f64,
+}
+
+impl FingerprintFilter {
+    /// Returns the categories of the patterns as indices to the patterns. The first index
+    /// of each category is the index of its representative.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns` - The patterns to categorize
+    pub fn categorize<T: Point>(&self, patterns: &[&Pattern<T>]) -> Vec<Vec<usize>> {
+        let fingerprints: Vec<Vec<T>> = patterns
+            .iter()
+            .map(|pattern| fingerprint(pattern))
+            .collect();
+
+        let mut order: Vec<usize> = (0..patterns.len()).collect();
+        order.sort_by(|a, b| patterns[*b].len().cmp(&patterns[*a].len()));
+
+        let mut is_categorized = vec![false; patterns.len()];
+        let mut categories = Vec::new();
+
+        for &representative in &order {
+            if is_categorized[representative] {
+                continue;
+            }
+
+            is_categorized[representative] = true;
+            let mut category = vec![representative];
+
+            for &candidate in &order {
+                if is_categorized[candidate] {
+                    continue;
+                }
+
+                let similarity =
+                    similarity(&fingerprints[representative], &fingerprints[candidate]);
+                if similarity >= self.min_similarity {
+                    is_categorized[candidate] = true;
+                    category.push(candidate);
+                }
+            }
+
+            categories.push(category);
+        }
+
+        categories
+    }
+
+    /// Returns the representative TECs of the categories of the given TECs.
+    ///
+    /// # Arguments
+    ///
+    /// * `tecs` - The TECs to filter, e.g., the output of SIACT
+    pub fn filter_tecs<T: Point>(&self, tecs: Vec<Tec<T>>) -> Vec<Tec<T>> {
+        let patterns: Vec<&Pattern<T>> = tecs.iter().map(|tec| &tec.pattern).collect();
+        let categories = self.categorize(&patterns);
+        FingerprintFilter::representatives(tecs, &categories)
+    }
+
+    /// Returns the representative MTPs of the categories of the given MTPs.
+    ///
+    /// # Arguments
+    ///
+    /// * `mtps` - The MTPs to filter, e.g., the output of SIAR
+    pub fn filter_mtps<T: Point>(&self, mtps: Vec<Mtp<T>>) -> Vec<Mtp<T>> {
+        let patterns: Vec<&Pattern<T>> = mtps.iter().map(|mtp| &mtp.pattern).collect();
+        let categories = self.categorize(&patterns);
+        FingerprintFilter::representatives(mtps, &categories)
+    }
+
+    /// Returns the items at the indices of the representatives, in the order of the categories.
+    fn representatives<I>(items: Vec<I>, categories: &[Vec<usize>]) -> Vec<I> {
+        let mut items: Vec<Option<I>> = items.into_iter().map(Some).collect();
+        categories
+            .iter()
+            .filter_map(|category| items[category[0]].take())
+            .collect()
+    }
+}
+
+/// Returns the fingerprint of the pattern as a sorted vector of the differences
+/// between all pairs of its points.
+fn fingerprint<T: Point>(pattern: &Pattern<T>) -> Vec<T> {
+    let n = pattern.len();
+    let mut diffs = Vec::with_capacity(n * n.saturating_sub(1) / 2);
+    for i in 0..n {
+        for j in (i + 1)..n {
+            diffs.push(pattern[j] - pattern[i]);
+        }
+    }
+
+    diffs.sort();
+    diffs
+}
+
+/// Returns the size of the multiset intersection of the sorted fingerprints divided by the size
+/// of the larger fingerprint. Two single point patterns, with empty fingerprints, are identical.
+fn similarity<T: Point>(a: &[T], b: &[T]) -> f64 {
+    let max_len = a.len().max(b.len());
+    if max_len == 0 {
+        return 1.0;
+    }
+
+    let mut common = 0;
+    let mut i = 0;
+    let mut j = 0;
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                common += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+
+    common as f64 / max_len as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::algorithm::TecAlgorithm;
+    use crate::discovery::fingerprint_filter::FingerprintFilter;
+    use crate::discovery::siact::Siact;
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+
+    fn pattern(points: &[(f64, f64)]) -> Pattern<Point2Df64> {
+        let points: Vec<Point2Df64> = points
+            .iter()
+            .map(|(x, y)| Point2Df64 { x: *x, y: *y })
+            .collect();
+        Pattern::new(&points.iter().collect())
+    }
+
+    #[test]
+    fn test_categorize() {
+        let motif = pattern(&[(0.0, 60.0), (1.0, 62.0), (2.0, 64.0), (3.0, 65.0)]);
+        // The motif transposed with the last note altered.
+        let variant = pattern(&[(8.0, 67.0), (9.0, 69.0), (10.0, 71.0), (11.0, 71.0)]);
+        // A subpattern of the motif.
+        let fragment = pattern(&[(4.0, 60.0), (5.0, 62.0), (6.0, 64.0)]);
+        let other = pattern(&[(0.0, 40.0), (4.0, 30.0), (5.0, 50.0)]);
+
+        let filter = FingerprintFilter {
+            min_similarity: 0.5,
+        };
+        let categories = filter.categorize(&[&fragment, &motif, &other, &variant]);
+        assert_eq!(vec![vec![1, 3, 0], vec![2]], categories);
+
+        let strict = FingerprintFilter {
+            min_similarity: 1.0,
+        };
+        assert_eq!(
+            4,
+            strict
+                .categorize(&[&fragment, &motif, &other, &variant])
+                .len()
+        );
+    }
+
+    #[test]
+    fn test_filter_siact_output() {
+        // A motif repeated three times with transpositions.
+        let mut points = Vec::new();
+        for (onset, transposition) in [(0.0, 0.0), (8.0, 3.0), (16.0, -2.0)] {
+            for (i, pitch) in [60.0, 64.0, 62.0, 67.0, 65.0].iter().enumerate() {
+                points.push(Point2Df64 {
+                    x: onset + i as f64,
+                    y: pitch + transposition,
+                });
+            }
+        }
+        let point_set = PointSet::new(points);
+
+        let tecs = Siact {
+            compactness_threshold: 0.5,
+            min_pattern_size: 3,
+        }
+        .compute_tecs(&point_set);
+        let filtered = FingerprintFilter {
+            min_similarity: 0.3,
+        }
+        .filter_tecs(tecs.clone());
+
+        assert!(!filtered.is_empty());
+        assert!(filtered.len() < tecs.len());
+        assert_eq!(5, filtered[0].pattern.len());
+    }
+}
@@ -0,0 +1,219 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::marker::PhantomData;
+use std::ops::ControlFlow;
+
+use crate::discovery::algorithm::TecAlgorithm;
+use crate::discovery::heuristic::{stats_of, TecStats};
+use crate::discovery::scored_tec::{ScoredTec, TecScores};
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// Implements Forth's algorithm for selecting perceptually salient TECs [Forth and Wiggins 2009].
+///
+/// The salience of each candidate TEC is the product of its compression ratio, normalized by the
+/// greatest compression ratio of the candidates, and its compactness. The TECs are then selected
+/// iteratively: on each iteration the TEC with the greatest salience-weighted coverage, i.e., the
+/// salience multiplied by the number of points covered by the TEC that are not yet covered by the
+/// selected TECs, is selected. TECs with a salience below the minimum salience, or that cover
+/// fewer new points than the minimum, are never selected, so the selected TECs are non-redundant.
+/// Forth and Wiggins used the minimum salience 0.5 and the minimum of 15 new points.
+pub struct Forth<T: Point, A: TecAlgorithm<T>> {
+    tec_algorithm: A,
+    min_salience: f64,
+    min_new_points: usize,
+    _t: PhantomData<T>,
+}
+
+impl<T: Point, A: TecAlgorithm<T>> TecAlgorithm<T> for Forth<T, A> {
+    fn compute_tecs(&self, point_set: &PointSet<T>) -> Vec<Tec<T>> {
+        self.compute_scored_tecs(point_set)
+            .into_iter()
+            .map(|scored| scored.tec)
+            .collect()
+    }
+
+    fn compute_tecs_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        let tecs = self.compute_tecs(point_set);
+        for tec in tecs {
+            if on_output(tec).is_break() {
+                return;
+            }
+        }
+    }
+}
+
+impl<T: Point, A: TecAlgorithm<T>> Forth<T, A> {
+    /// Creates a new instance of Forth's algorithm that uses the given TEC-algorithm
+    /// for computing the TEC candidates.
+    ///
+    /// # Arguments
+    ///
+    /// * `tec_algorithm` - The algorithm that computes the TEC candidates
+    /// * `min_salience` - The minimum salience of the selected TECs, in the range [0, 1]
+    /// * `min_new_points` - The minimum number of new points covered by each selected TEC
+    pub fn with(tec_algorithm: A, min_salience: f64, min_new_points: usize) -> Forth<T, A> {
+        Forth {
+            tec_algorithm,
+            min_salience,
+            min_new_points: min_new_points.max(1),
+            _t: Default::default(),
+        }
+    }
+
+    /// Computes the TECs as `compute_tecs` but returns them with their heuristic scores.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set for which the TECs are computed
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "forth", skip_all, fields(n = point_set.len())))]
+    pub fn compute_scored_tecs(&self, point_set: &PointSet<T>) -> Vec<ScoredTec<T>> {
+        let candidates: Vec<TecStats<T>> = self
+            .tec_algorithm
+            .compute_tecs(point_set)
+            .into_iter()
+            .map(|tec| stats_of(tec.remove_redundant_translators(), point_set))
+            .collect();
+        let saliences = Forth::<T, A>::saliences(&candidates);
+
+        let mut is_covered = vec![false; point_set.len()];
+        let mut is_selected = vec![false; candidates.len()];
+        let mut selected = Vec::new();
+
+        loop {
+            let mut best: Option<(usize, f64)> = None;
+            for (i, candidate) in candidates.iter().enumerate() {
+                if is_selected[i] || saliences[i] < self.min_salience {
+                    continue;
+                }
+
+                let new_points = candidate
+                    .covered_set
+                    .into_iter()
+                    .filter(|point| {
+                        point_set
+                            .find_index(point)
+                            .is_ok_and(|index| !is_covered[index])
+                    })
+                    .count();
+                if new_points < self.min_new_points {
+                    continue;
+                }
+
+                let weighted_coverage = saliences[i] * new_points as f64;
+                if best.is_none_or(|(_, best_coverage)| weighted_coverage > best_coverage) {
+                    best = Some((i, weighted_coverage));
+                }
+            }
+
+            let index = match best {
+                Some((index, _)) => index,
+                None => break,
+            };
+
+            is_selected[index] = true;
+            for point in &candidates[index].covered_set {
+                if let Ok(i) = point_set.find_index(point) {
+                    is_covered[i] = true;
+                }
+            }
+            selected.push(ScoredTec {
+                tec: candidates[index].tec.clone(),
+                scores: TecScores::from(&candidates[index]),
+            });
+        }
+
+        selected
+    }
+
+    /// Returns the salience of each candidate: the normalized compression ratio
+    /// multiplied by the compactness.
+    fn saliences(candidates: &[TecStats<T>]) -> Vec<f64> {
+        let max_ratio = candidates
+            .iter()
+            .map(|candidate| candidate.comp_ratio)
+            .fold(0.0, f64::max);
+
+        candidates
+            .iter()
+            .map(|candidate| {
+                if max_ratio > 0.0 {
+                    candidate.comp_ratio / max_ratio * candidate.compactness
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::algorithm::TecAlgorithm;
+    use crate::discovery::forth::Forth;
+    use crate::discovery::siatec::Siatec;
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+
+    fn test_point_set() -> PointSet<Point2Df64> {
+        // A four note motif repeated three times with transpositions, and two unrelated points.
+        let mut points = Vec::new();
+        for (onset, transposition) in [(0.0, 0.0), (4.0, 5.0), (8.0, -3.0)] {
+            for (i, pitch) in [60.0, 62.0, 64.0, 65.0].iter().enumerate() {
+                points.push(Point2Df64 {
+                    x: onset + i as f64 * 0.5,
+                    y: pitch + transposition,
+                });
+            }
+        }
+        points.push(Point2Df64 { x: 13.0, y: 40.0 });
+        points.push(Point2Df64 { x: 15.0, y: 90.0 });
+        PointSet::new(points)
+    }
+
+    #[test]
+    fn test_selects_salient_motif() {
+        let point_set = test_point_set();
+        let tecs = Forth::with(Siatec {}, 0.5, 4).compute_tecs(&point_set);
+
+        assert_eq!(1, tecs.len());
+        assert_eq!(
+            Pattern::new(&vec![
+                &Point2Df64 { x: 0.0, y: 60.0 },
+                &Point2Df64 { x: 0.5, y: 62.0 },
+                &Point2Df64 { x: 1.0, y: 64.0 },
+                &Point2Df64 { x: 1.5, y: 65.0 },
+            ])
+            .vectorize(),
+            tecs[0].pattern.vectorize()
+        );
+        assert_eq!(2, tecs[0].translators.len());
+        assert_eq!(12, tecs[0].covered_set().len());
+    }
+
+    #[test]
+    fn test_selected_tecs_are_non_redundant() {
+        let point_set = test_point_set();
+        let forth = Forth::with(Siatec {}, 0.0, 1);
+        let scored_tecs = forth.compute_scored_tecs(&point_set);
+
+        // Every selected TEC covers new points, so all points are covered with at most
+        // as many TECs as there are points.
+        let mut covered = PointSet::new(Vec::new());
+        for scored in &scored_tecs {
+            let covered_set = scored.tec.covered_set();
+            assert!(!covered_set.difference(&covered).is_empty());
+            covered = covered.union(&covered_set);
+        }
+        assert_eq!(point_set, covered);
+        assert!(scored_tecs.len() <= point_set.len());
+    }
+}
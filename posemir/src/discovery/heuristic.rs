@@ -2,124 +2,91 @@
  * (c) Otso Björklund (2021)
  * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
  */
-use crate::point_set::pattern::Pattern;
+use crate::discovery::compactness::{compactness, CompactnessRegion};
 use crate::point_set::point::Point;
 use crate::point_set::set::PointSet;
 use crate::point_set::tec::Tec;
 
+/// The heuristic scores of a TEC together with the TEC and its covered set, as computed
+/// with `stats_of`. See `scored_tec::TecScores` for the descriptions of the scores.
 #[derive(Debug)]
 pub struct TecStats<T: Point> {
     pub tec: Tec<T>,
     pub comp_ratio: f64,
     pub compactness: f64,
     pub covered_set: PointSet<T>,
+    pub weighted_coverage: f64,
     pub pattern_width: f64,
     pub pattern_area: f64,
 }
 
+/// Returns all of the heuristic scores of the TEC in the point set.
+///
+/// # Arguments
+///
+/// * `tec` - The TEC whose scores are computed
+/// * `point_set` - The point set in which the TEC occurs
 pub fn stats_of<T: Point>(tec: Tec<T>, point_set: &PointSet<T>) -> TecStats<T> {
     let covered_set = tec.covered_set();
     let comp_ratio = compr_ratio_with_cov(&tec, &covered_set);
-    let bb = bounding_box(&tec.pattern);
+    let weighted_coverage = weighted_coverage(&covered_set, point_set);
+    let bb = tec.pattern.bounding_box();
     let compactness = bb_compactness(&tec, point_set);
 
-    let pattern_width = bb.upper_x - bb.lower_x;
-    let pattern_area = (bb.upper_x - bb.lower_x) * (bb.upper_y - bb.lower_y);
+    let pattern_width = bb.width();
+    let pattern_area = bb.area();
 
     TecStats {
         tec,
         comp_ratio,
         compactness,
         covered_set,
+        weighted_coverage,
         pattern_width,
         pattern_area,
     }
 }
 
-impl<T: Point> TecStats<T> {
-    pub fn is_better_than(&self, other: &TecStats<T>) -> bool {
-        if self.comp_ratio > other.comp_ratio {
-            return true;
-        }
-        if self.compactness > other.compactness {
-            return true;
-        }
-        if self.covered_set.len() > other.covered_set.len() {
-            return true;
-        }
-        if self.tec.pattern.len() > other.tec.pattern.len() {
-            return true;
-        }
-        if self.pattern_width < other.pattern_width {
-            return true;
-        }
-        if self.pattern_area < other.pattern_area {
-            return true;
-        }
-
-        false
-    }
+/// Returns the compression ratio of the TEC, i.e., the number of covered points divided
+/// by the size of the TEC representation (pattern size + number of translators).
+///
+/// # Arguments
+///
+/// * `tec` - The TEC whose compression ratio is computed
+pub fn compression_ratio<T: Point>(tec: &Tec<T>) -> f64 {
+    compr_ratio_with_cov(tec, &tec.covered_set())
 }
 
-struct BoundingBox {
-    lower_x: f64,
-    lower_y: f64,
-    upper_x: f64,
-    upper_y: f64,
+/// Returns the number of points covered by the TEC.
+///
+/// # Arguments
+///
+/// * `tec` - The TEC whose coverage is computed
+pub fn coverage<T: Point>(tec: &Tec<T>) -> usize {
+    tec.covered_set().len()
 }
 
-impl BoundingBox {
-    fn contains<T: Point>(&self, point: &T) -> bool {
-        let x = point.component_f64(0).unwrap();
-        let y = point.component_f64(1).unwrap();
-
-        if x < self.lower_x {
-            return false;
-        }
-
-        if x > self.upper_x {
-            return false;
-        }
-
-        if y < self.lower_y {
-            return false;
-        }
-
-        if y > self.upper_y {
-            return false;
-        }
-
-        true
-    }
+/// Returns the bounding box compactness of the TEC in the point set as in [Meredith2013]
+/// (see `compactness::compactness` for other regions).
+///
+/// # Arguments
+///
+/// * `tec` - The TEC whose compactness is computed
+/// * `point_set` - The point set in which the TEC occurs
+pub fn bb_compactness<T: Point>(tec: &Tec<T>, point_set: &PointSet<T>) -> f64 {
+    compactness(tec, point_set, CompactnessRegion::BoundingBox)
 }
 
-fn bounding_box<T: Point>(pattern: &Pattern<T>) -> BoundingBox {
-    let mut bb = BoundingBox {
-        lower_x: f64::MAX,
-        lower_y: f64::MAX,
-        upper_x: f64::MIN,
-        upper_y: f64::MIN,
-    };
-
-    for point in pattern {
-        let point_x = point.component_f64(0).unwrap();
-        let point_y = point.component_f64(1).unwrap();
-
-        if point_x < bb.lower_x {
-            bb.lower_x = point_x;
-        }
-        if point_x > bb.upper_x {
-            bb.upper_x = point_x;
-        }
-        if point_y < bb.lower_y {
-            bb.lower_y = point_y;
-        }
-        if point_y < bb.upper_y {
-            bb.upper_y = point_y;
-        }
-    }
-
-    bb
+/// Returns the sum of the weights of the covered points. The weights are taken from the point
+/// set, as the weights of the covered points are those of the points of the pattern.
+fn weighted_coverage<T: Point>(covered_set: &PointSet<T>, point_set: &PointSet<T>) -> f64 {
+    covered_set
+        .into_iter()
+        .map(|point| match point_set.find_index(point) {
+            Ok(index) => point_set[index].weight(),
+            Err(_) => point.weight(),
+        })
+        .sum()
 }
 
 fn compr_ratio_with_cov<T: Point>(tec: &Tec<T>, cov: &PointSet<T>) -> f64 {
@@ -132,27 +99,33 @@ fn compr_ratio_with_cov<T: Point>(tec: &Tec<T>, cov: &PointSet<T>) -> f64 {
     cov_size / (pat_size + transl_size)
 }
 
-fn bb_compactness<T: Point>(tec: &Tec<T>, point_set: &PointSet<T>) -> f64 {
-    let mut best_compactness = 0.0;
-    let expanded = tec.expand();
-
-    for pattern in &expanded {
-        let bb = bounding_box(pattern);
-        let mut contained: f64 = 0.0;
-
-        for point in point_set {
-            if bb.contains(point) {
-                contained += 1.0;
-            }
-        }
-
-        let pat_size = tec.pattern.len() as f64;
-
-        let compactness = pat_size / contained;
-        if compactness > best_compactness {
-            best_compactness = compactness;
-        }
+#[cfg(test)]
+mod tests {
+    use crate::discovery::heuristic::{bb_compactness, compression_ratio, coverage, stats_of};
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+    use crate::point_set::tec::Tec;
+
+    #[test]
+    fn test_heuristics() {
+        let a = Point2Df64 { x: 0.0, y: 0.0 };
+        let b = Point2Df64 { x: 1.0, y: 2.0 };
+        let c = Point2Df64 { x: 0.5, y: 5.0 };
+        let translator = Point2Df64 { x: 2.0, y: 0.0 };
+        let point_set = PointSet::new(vec![a, b, c, a + translator, b + translator]);
+        let tec = Tec {
+            pattern: Pattern::new(&vec![&a, &b]),
+            translators: vec![translator],
+        };
+
+        assert_eq!(4.0 / 3.0, compression_ratio(&tec));
+        assert_eq!(4, coverage(&tec));
+        assert_eq!(1.0, bb_compactness(&tec, &point_set));
+
+        let stats = stats_of(tec, &point_set);
+        assert_eq!(1.0, stats.pattern_width);
+        assert_eq!(2.0, stats.pattern_area);
+        assert_eq!(4.0, stats.weighted_coverage);
     }
-
-    best_compactness
 }
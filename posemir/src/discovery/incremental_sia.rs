@@ -0,0 +1,180 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::collections::HashMap;
+
+use crate::point_set::mtp::Mtp;
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+
+/// Computes MTPs incrementally as points are inserted one at a time, e.g., from a live MIDI
+/// stream. Instead of re-running SIA after each insertion, an index from each forward
+/// difference vector (translator) to the points of its MTP is maintained. Inserting a point
+/// into a set of n points updates the MTPs of its n difference vectors by inserting into their
+/// sorted point lists, so the insertion takes time proportional to the total size of the updated
+/// MTPs, which is O(n^2) in the worst case, e.g., for evenly spaced points. The MTPs in the
+/// index are always the same as the MTPs computed by SIA for the inserted points.
+pub struct IncrementalSia<T: Point> {
+    points: Vec<T>,
+    /// The points of the MTP of each forward difference vector in ascending order.
+    mtp_index: HashMap<T, Vec<T>>,
+}
+
+impl<T: Point> Default for IncrementalSia<T> {
+    fn default() -> Self {
+        IncrementalSia::new()
+    }
+}
+
+impl<T: Point> IncrementalSia<T> {
+    /// Returns a new instance with no points.
+    pub fn new() -> IncrementalSia<T> {
+        IncrementalSia {
+            points: Vec::new(),
+            mtp_index: HashMap::new(),
+        }
+    }
+
+    /// Returns the number of inserted points.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Returns true if no points have been inserted.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Returns the inserted points as a point set.
+    pub fn point_set(&self) -> PointSet<T> {
+        PointSet::new(self.points.clone())
+    }
+
+    /// Inserts the point and executes the callback on each MTP that gained a point due to
+    /// the insertion. Returns false if the point had already been inserted, in which case
+    /// no MTPs change.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to insert
+    /// * `on_update` - The callback executed on each updated MTP
+    pub fn insert(&mut self, point: T, mut on_update: impl FnMut(Mtp<T>)) -> bool {
+        let position = match self.points.binary_search(&point) {
+            Ok(_) => return false,
+            Err(position) => position,
+        };
+
+        // The new point is the target of the forward differences from the preceding points,
+        // and the source of the forward differences to the following points.
+        for (i, other) in self.points.iter().enumerate() {
+            let (translator, source) = if i < position {
+                (point - *other, *other)
+            } else {
+                (*other - point, point)
+            };
+
+            let mtp_points = self.mtp_index.entry(translator).or_default();
+            if let Err(index) = mtp_points.binary_search(&source) {
+                mtp_points.insert(index, source);
+            }
+
+            on_update(IncrementalSia::to_mtp(translator, mtp_points));
+        }
+
+        self.points.insert(position, point);
+        true
+    }
+
+    /// Returns the MTP for the translator, or empty if no points can be translated by it.
+    ///
+    /// # Arguments
+    ///
+    /// * `translator` - The forward difference vector of the MTP
+    pub fn mtp(&self, translator: &T) -> Option<Mtp<T>> {
+        self.mtp_index
+            .get(translator)
+            .map(|mtp_points| IncrementalSia::to_mtp(*translator, mtp_points))
+    }
+
+    /// Returns all MTPs of the inserted points in ascending order of translators.
+    pub fn mtps(&self) -> Vec<Mtp<T>> {
+        let mut translators: Vec<&T> = self.mtp_index.keys().collect();
+        translators.sort();
+
+        translators
+            .into_iter()
+            .map(|translator| IncrementalSia::to_mtp(*translator, &self.mtp_index[translator]))
+            .collect()
+    }
+
+    fn to_mtp(translator: T, mtp_points: &[T]) -> Mtp<T> {
+        Mtp {
+            translator,
+            pattern: Pattern::new(&mtp_points.iter().collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::algorithm::MtpAlgorithm;
+    use crate::discovery::incremental_sia::IncrementalSia;
+    use crate::discovery::sia::Sia;
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+
+    #[test]
+    fn test_incremental_mtps_equal_sia_mtps() {
+        // Points in non-lexicographic order, as they could arrive from a stream.
+        let points = [
+            Point2Df64 { x: 3.0, y: 3.0 },
+            Point2Df64 { x: 1.0, y: 1.0 },
+            Point2Df64 { x: 2.0, y: 3.0 },
+            Point2Df64 { x: 4.0, y: 1.0 },
+            Point2Df64 { x: 2.0, y: 1.0 },
+            Point2Df64 { x: 5.0, y: 2.0 },
+            Point2Df64 { x: 4.0, y: 2.0 },
+        ];
+
+        let mut incremental_sia = IncrementalSia::new();
+        for (i, point) in points.iter().enumerate() {
+            let mut update_count = 0;
+            assert!(incremental_sia.insert(*point, |_| update_count += 1));
+            assert_eq!(i, update_count);
+
+            let mut expected = Sia::new().compute_mtps(&PointSet::new(points[..=i].to_vec()));
+            expected.sort_by_key(|mtp| mtp.translator);
+            assert_eq!(expected, incremental_sia.mtps());
+        }
+
+        assert!(!incremental_sia.insert(points[0], |_| panic!("no MTPs should change")));
+        assert_eq!(points.len(), incremental_sia.len());
+    }
+
+    #[test]
+    fn test_updated_mtps() {
+        let a = Point2Df64 { x: 1.0, y: 1.0 };
+        let b = Point2Df64 { x: 2.0, y: 1.0 };
+        let c = Point2Df64 { x: 3.0, y: 1.0 };
+        let translator = Point2Df64 { x: 1.0, y: 0.0 };
+
+        let mut incremental_sia = IncrementalSia::new();
+        incremental_sia.insert(a, |_| {});
+        incremental_sia.insert(b, |_| {});
+
+        let mut updated = Vec::new();
+        incremental_sia.insert(c, |mtp| updated.push(mtp));
+
+        assert_eq!(2, updated.len());
+        assert!(updated
+            .iter()
+            .any(|mtp| mtp.translator == translator && mtp.pattern == Pattern::new(&vec![&a, &b])));
+        assert_eq!(
+            updated[0],
+            incremental_sia.mtp(&updated[0].translator).unwrap()
+        );
+    }
+}
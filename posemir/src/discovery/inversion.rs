@@ -0,0 +1,234 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+
+use crate::discovery::algorithm::TecAlgorithm;
+use crate::discovery::mtp_to_tec::MtpToTec;
+use crate::discovery::sia::Sia;
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// The transformation that maps the pattern of a `TransformTec` onto one of its occurrences.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OccurrenceTransform {
+    /// The occurrence is a translation of the pattern.
+    Translation,
+    /// The occurrence is a translation of the inversion of the pattern, i.e., of the pattern
+    /// with its pitches (the second component) mirrored about some axis.
+    Inversion,
+}
+
+/// A translational equivalence class extended with the occurrences of the inversion of
+/// the pattern, so that patterns related by inversion are in the same class.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransformTec<T: Point> {
+    /// The pattern of the class.
+    pub pattern: Pattern<T>,
+    /// The non-zero translators that map the pattern onto its translated occurrences.
+    pub translators: Vec<T>,
+    /// The non-zero translators that map the inverted pattern (see `TransformTec::inverted_pattern`)
+    /// onto the inverted occurrences.
+    pub inversion_translators: Vec<T>,
+}
+
+impl<T: Point> TransformTec<T> {
+    /// Returns the inversion of the pattern, where the pitches (the second components)
+    /// of the points are negated.
+    pub fn inverted_pattern(&self) -> Pattern<T> {
+        invert(&self.pattern)
+    }
+
+    /// Returns the occurrences of the pattern with the transforms that map the pattern
+    /// onto them, starting with the pattern itself.
+    pub fn occurrences(&self) -> Vec<(OccurrenceTransform, Pattern<T>)> {
+        let mut occurrences =
+            Vec::with_capacity(1 + self.translators.len() + self.inversion_translators.len());
+        occurrences.push((OccurrenceTransform::Translation, self.pattern.clone()));
+        for translator in &self.translators {
+            occurrences.push((
+                OccurrenceTransform::Translation,
+                self.pattern.translate(translator),
+            ));
+        }
+
+        let inverted = self.inverted_pattern();
+        for translator in &self.inversion_translators {
+            occurrences.push((
+                OccurrenceTransform::Inversion,
+                inverted.translate(translator),
+            ));
+        }
+        occurrences
+    }
+
+    /// Returns the set of points covered by all occurrences of the pattern.
+    pub fn covered_set(&self) -> PointSet<T> {
+        let mut points = Vec::new();
+        for (_, occurrence) in self.occurrences() {
+            points.extend(&occurrence);
+        }
+        PointSet::new(points)
+    }
+
+    /// Returns the TEC of the translated occurrences of the pattern.
+    pub fn to_tec(&self) -> Tec<T> {
+        Tec {
+            pattern: self.pattern.clone(),
+            translators: self.translators.clone(),
+        }
+    }
+}
+
+/// Extends the TECs computed by a TEC algorithm with the occurrences of the inversions of
+/// their patterns. The TEC of a pattern that is translationally equivalent to the inversion
+/// of the pattern of an earlier TEC is merged into the class of the earlier TEC, so each class
+/// of patterns related by translation and inversion is output once, as found first by the
+/// TEC algorithm. The pitch is assumed to be the second component of the points.
+pub struct InversionTec<A> {
+    algorithm: A,
+}
+
+impl<A> InversionTec<A> {
+    /// Returns an instance that extends the TECs computed with the given algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - The algorithm that computes the TECs, e.g., SIATEC
+    pub fn with(algorithm: A) -> InversionTec<A> {
+        InversionTec { algorithm }
+    }
+
+    /// Computes the classes of patterns related by translation and inversion in the point set.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set in which the patterns are found
+    pub fn compute_transform_tecs<T: Point>(&self, point_set: &PointSet<T>) -> Vec<TransformTec<T>>
+    where
+        A: TecAlgorithm<T>,
+    {
+        let mut transform_tecs = Vec::new();
+        self.compute_transform_tecs_to_output(point_set, |transform_tec| {
+            transform_tecs.push(transform_tec);
+            ControlFlow::Continue(())
+        });
+        transform_tecs
+    }
+
+    /// Computes the classes as `compute_transform_tecs`, and outputs each class with the
+    /// given callback as soon as it has been computed.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set in which the patterns are found
+    /// * `on_output` - The callback for the classes, which can stop the computation
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "inversion_tec", skip_all, fields(n = point_set.len())))]
+    pub fn compute_transform_tecs_to_output<T: Point>(
+        &self,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(TransformTec<T>) -> ControlFlow<()>,
+    ) where
+        A: TecAlgorithm<T>,
+    {
+        // The vectorized patterns of the inversions of the output classes.
+        let mut inversions: HashSet<Pattern<T>> = HashSet::new();
+
+        self.algorithm.compute_tecs_to_output(point_set, |tec| {
+            if tec.pattern.is_empty() || inversions.contains(&tec.pattern.vectorize()) {
+                return ControlFlow::Continue(());
+            }
+
+            let inverted = invert(&tec.pattern);
+            inversions.insert(inverted.vectorize());
+            let inversion_translators = MtpToTec::<T, Sia>::find_translators(&inverted, point_set);
+
+            on_output(TransformTec {
+                pattern: tec.pattern,
+                translators: tec.translators,
+                inversion_translators,
+            })
+        });
+    }
+}
+
+/// Returns the pattern with the pitches negated. Points without a pitch component are kept as is.
+fn invert<T: Point>(pattern: &Pattern<T>) -> Pattern<T> {
+    let points = pattern
+        .into_iter()
+        .map(|point| {
+            point
+                .component_f64(1)
+                .and_then(|pitch| point.with_component_f64(1, -pitch))
+                .unwrap_or(*point)
+        })
+        .collect();
+    PointSet::new(points).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::inversion::{InversionTec, OccurrenceTransform};
+    use crate::discovery::siatec::Siatec;
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+
+    fn point(x: f64, y: f64) -> Point2Df64 {
+        Point2Df64 { x, y }
+    }
+
+    #[test]
+    fn test_inverted_occurrences_are_in_the_same_class() {
+        // The subject C-D-G, its answer a fourth lower, and its inversion starting on G.
+        let subject = [point(0.0, 60.0), point(1.0, 62.0), point(2.0, 67.0)];
+        let point_set = PointSet::new(vec![
+            subject[0],
+            subject[1],
+            subject[2],
+            point(4.0, 55.0),
+            point(5.0, 57.0),
+            point(6.0, 62.0),
+            point(8.0, 67.0),
+            point(9.0, 65.0),
+            point(10.0, 60.0),
+        ]);
+
+        let transform_tecs = InversionTec::with(Siatec {}).compute_transform_tecs(&point_set);
+        let subject_class = transform_tecs
+            .iter()
+            .find(|transform_tec| transform_tec.pattern == Pattern::new(&subject.iter().collect()))
+            .unwrap();
+
+        assert_eq!(vec![point(4.0, -5.0)], subject_class.translators);
+        assert_eq!(vec![point(8.0, 127.0)], subject_class.inversion_translators);
+        assert_eq!(point_set, subject_class.covered_set());
+
+        let occurrences = subject_class.occurrences();
+        assert_eq!(3, occurrences.len());
+        assert_eq!(OccurrenceTransform::Inversion, occurrences[2].0);
+        assert_eq!(
+            Pattern::new(&vec![
+                &point(8.0, 67.0),
+                &point(9.0, 65.0),
+                &point(10.0, 60.0)
+            ]),
+            occurrences[2].1
+        );
+
+        // The inverted subject is not a class of its own.
+        let inverted_subject = Pattern::new(&point_set.into_iter().skip(6).collect());
+        assert!(
+            transform_tecs
+                .iter()
+                .all(|transform_tec| transform_tec.pattern.vectorize()
+                    != inverted_subject.vectorize())
+        );
+    }
+}
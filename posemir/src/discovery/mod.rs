@@ -5,14 +5,37 @@
 extern crate core;
 
 pub mod algorithm;
+pub mod canonical_order;
+pub mod compactness;
 pub mod cosiatec;
+pub mod cross_check;
+pub mod diff_index;
+pub mod duplicates;
+pub mod encoding;
+pub mod fingerprint_filter;
+pub mod forth;
+pub mod incremental_sia;
+pub mod inversion;
+pub mod mtp_to_tec;
+pub mod progress;
+pub mod quantized;
+pub mod registry;
+pub mod scaling_siatec;
+pub mod scored_tec;
 pub mod sia;
+pub mod sia_chunked;
+pub mod siact;
 pub mod siar;
 pub mod siatec;
 pub mod siatec_c;
 pub mod siatec_ch;
 pub mod siatec_compress;
+pub mod siatec_r;
+pub mod sliding_window;
+pub mod statistics;
+pub mod subsumption_filter;
+pub mod tec_filter;
+pub mod tec_ranker;
 
-pub(crate) mod utilities;
 //noinspection RsExternalLinter
-pub(crate) mod heuristic;
+pub mod heuristic;
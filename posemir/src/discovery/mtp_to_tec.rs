@@ -0,0 +1,122 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::collections::HashSet;
+use std::marker::PhantomData;
+use std::ops::ControlFlow;
+
+use crate::discovery::algorithm::{MtpAlgorithm, TecAlgorithm};
+use crate::point_set::mtp::Mtp;
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// Adapts an MTP algorithm into a TEC algorithm by computing the full set of translators
+/// for each MTP produced by the MTP algorithm. Translationally equivalent MTPs are output only once.
+/// This makes it possible to, e.g., use SIA or SIAR for computing the TEC candidates for COSIATEC.
+pub struct MtpToTec<T: Point, A: MtpAlgorithm<T>> {
+    mtp_algorithm: A,
+    _t: PhantomData<T>,
+}
+
+impl<T: Point, A: MtpAlgorithm<T>> TecAlgorithm<T> for MtpToTec<T, A> {
+    fn compute_tecs(&self, point_set: &PointSet<T>) -> Vec<Tec<T>> {
+        let mut tecs = Vec::new();
+        let on_output = |tec: Tec<T>| {
+            tecs.push(tec);
+            ControlFlow::Continue(())
+        };
+        self.compute_tecs_to_output(point_set, on_output);
+        tecs
+    }
+
+    fn compute_tecs_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        let mut seen_patterns: HashSet<Pattern<T>> = HashSet::new();
+
+        let on_mtp = |mtp: Mtp<T>| {
+            if mtp.pattern.is_empty() || !seen_patterns.insert(mtp.pattern.vectorize()) {
+                return ControlFlow::Continue(());
+            }
+
+            let translators = MtpToTec::<T, A>::find_translators(&mtp.pattern, point_set);
+            on_output(Tec {
+                pattern: mtp.pattern,
+                translators,
+            })
+        };
+
+        self.mtp_algorithm.compute_mtps_to_output(point_set, on_mtp);
+    }
+}
+
+impl<T: Point, A: MtpAlgorithm<T>> MtpToTec<T, A> {
+    /// Creates a new instance of the adapter that uses the given MTP-algorithm
+    /// for computing the patterns for the TECs.
+    pub fn with(mtp_algorithm: A) -> MtpToTec<T, A> {
+        MtpToTec {
+            mtp_algorithm,
+            _t: Default::default(),
+        }
+    }
+
+    /// Finds all non-zero translators by which the pattern can be translated
+    /// so that the translated pattern is within the point set.
+    pub(crate) fn find_translators(pattern: &Pattern<T>, point_set: &PointSet<T>) -> Vec<T> {
+        let first = pattern[0];
+        let mut translators = Vec::new();
+
+        for point in point_set {
+            let translator = *point - first;
+            if translator.is_zero() {
+                continue;
+            }
+
+            let is_translatable = pattern
+                .into_iter()
+                .skip(1)
+                .all(|p| point_set.contains(&(*p + translator)));
+
+            if is_translatable {
+                translators.push(translator);
+            }
+        }
+
+        translators
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::algorithm::TecAlgorithm;
+    use crate::discovery::mtp_to_tec::MtpToTec;
+    use crate::discovery::sia::Sia;
+    use crate::discovery::siatec::Siatec;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+
+    #[test]
+    fn test_sia_tecs_equal_siatec_tecs() {
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 1.0, y: 1.0 },
+            Point2Df64 { x: 2.0, y: 3.0 },
+            Point2Df64 { x: 2.0, y: 1.0 },
+            Point2Df64 { x: 3.0, y: 3.0 },
+            Point2Df64 { x: 4.0, y: 1.0 },
+            Point2Df64 { x: 4.0, y: 2.0 },
+            Point2Df64 { x: 5.0, y: 2.0 },
+        ]);
+
+        let mut tecs = MtpToTec::with(Sia::new()).compute_tecs(&point_set);
+        let mut siatec_tecs = Siatec {}.compute_tecs(&point_set);
+        tecs.sort();
+        siatec_tecs.sort();
+
+        assert_eq!(siatec_tecs, tecs);
+    }
+}
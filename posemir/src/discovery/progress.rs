@@ -0,0 +1,177 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::time::{Duration, Instant};
+
+/// The unit of the work whose progress is reported.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ProgressUnit {
+    /// Points covered, e.g., by the TECs selected by COSIATEC.
+    Points,
+    /// Windows of onset times processed, e.g., by SIATEC-C.
+    Windows,
+    /// Patterns processed, e.g., the MTPs for which SIATEC has found the translators.
+    Patterns,
+}
+
+impl ProgressUnit {
+    /// Returns the name of the unit in plural.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProgressUnit::Points => "points",
+            ProgressUnit::Windows => "windows",
+            ProgressUnit::Patterns => "patterns",
+        }
+    }
+}
+
+/// The progress of a long-running algorithm.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Progress {
+    /// The unit of the completed and total work.
+    pub unit: ProgressUnit,
+    /// The amount of completed work.
+    pub completed: usize,
+    /// The total amount of work, which may be an estimate, but is never less than
+    /// the amount of completed work.
+    pub total: usize,
+    /// The estimated remaining time, extrapolated from the time taken by the completed work.
+    /// None if no work has been completed yet.
+    pub eta: Option<Duration>,
+}
+
+/// Trait for observing the progress of long-running algorithms, e.g., for showing
+/// a progress bar. See `TecAlgorithm::compute_tecs_to_output_observed`.
+pub trait ProgressObserver {
+    /// Called whenever the algorithm has completed some work.
+    ///
+    /// # Arguments
+    ///
+    /// * `progress` - The progress of the algorithm
+    fn on_progress(&self, progress: Progress);
+}
+
+/// An observer that ignores the progress.
+pub struct IgnoreProgress {}
+
+impl ProgressObserver for IgnoreProgress {
+    fn on_progress(&self, _progress: Progress) {}
+}
+
+/// Reports the progress of an algorithm with the estimated remaining time to an observer.
+pub(crate) struct ProgressTracker<'a> {
+    observer: &'a dyn ProgressObserver,
+    unit: ProgressUnit,
+    total: usize,
+    start: Instant,
+}
+
+impl<'a> ProgressTracker<'a> {
+    pub(crate) fn new(
+        observer: &'a dyn ProgressObserver,
+        unit: ProgressUnit,
+        total: usize,
+    ) -> ProgressTracker<'a> {
+        ProgressTracker {
+            observer,
+            unit,
+            total,
+            start: Instant::now(),
+        }
+    }
+
+    /// Reports the given amount of completed work.
+    pub(crate) fn report(&self, completed: usize) {
+        let total = self.total.max(completed);
+        let eta = if completed > 0 {
+            let per_unit = self.start.elapsed().as_secs_f64() / completed as f64;
+            Some(Duration::from_secs_f64(
+                per_unit * (total - completed) as f64,
+            ))
+        } else {
+            None
+        };
+
+        self.observer.on_progress(Progress {
+            unit: self.unit,
+            completed,
+            total,
+            eta,
+        });
+    }
+
+    /// Reports the given amount of completed work as all of the work, e.g., when
+    /// the total work has been overestimated.
+    pub(crate) fn finish(&self, completed: usize) {
+        self.observer.on_progress(Progress {
+            unit: self.unit,
+            completed,
+            total: completed,
+            eta: Some(Duration::ZERO),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::ops::ControlFlow;
+
+    use crate::discovery::algorithm::TecAlgorithm;
+    use crate::discovery::cosiatec::Cosiatec;
+    use crate::discovery::progress::{Progress, ProgressObserver, ProgressUnit};
+    use crate::discovery::siatec::Siatec;
+    use crate::discovery::siatec_c::SiatecC;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+    use crate::point_set::tec::Tec;
+
+    #[derive(Default)]
+    struct Recorder {
+        progresses: RefCell<Vec<Progress>>,
+    }
+
+    impl ProgressObserver for Recorder {
+        fn on_progress(&self, progress: Progress) {
+            self.progresses.borrow_mut().push(progress);
+        }
+    }
+
+    fn assert_reports_progress<A: TecAlgorithm<Point2Df64>>(algorithm: &A, unit: ProgressUnit) {
+        let mut points = Vec::new();
+        for i in 0..12 {
+            points.push(Point2Df64 {
+                x: i as f64,
+                y: (i % 4) as f64,
+            });
+        }
+        let point_set = PointSet::new(points);
+
+        let recorder = Recorder::default();
+        let mut tecs: Vec<Tec<Point2Df64>> = Vec::new();
+        algorithm.compute_tecs_to_output_observed(&point_set, &recorder, |tec| {
+            tecs.push(tec);
+            ControlFlow::Continue(())
+        });
+        assert_eq!(algorithm.compute_tecs(&point_set), tecs);
+
+        let progresses = recorder.progresses.borrow();
+        assert!(!progresses.is_empty());
+        assert!(progresses.iter().all(|progress| progress.unit == unit
+            && progress.completed <= progress.total
+            && progress.eta.is_some()));
+        assert!(progresses
+            .windows(2)
+            .all(|pair| pair[0].completed <= pair[1].completed));
+        let last = progresses[progresses.len() - 1];
+        assert_eq!(last.total, last.completed);
+    }
+
+    #[test]
+    fn test_progress_is_reported() {
+        assert_reports_progress(&Siatec {}, ProgressUnit::Patterns);
+        assert_reports_progress(&SiatecC::new(3.0), ProgressUnit::Windows);
+        assert_reports_progress(&Cosiatec::with(Siatec {}), ProgressUnit::Points);
+    }
+}
@@ -0,0 +1,177 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::ops::ControlFlow;
+
+use crate::discovery::algorithm::TecAlgorithm;
+use crate::discovery::progress::{IgnoreProgress, ProgressObserver};
+use crate::error::PoSemirError;
+use crate::point_set::point::{Point, Point2Di64};
+use crate::point_set::quantization::Quantizer;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// Runs a TEC algorithm on the quantized points of a point set (see `Quantizer`), and maps
+/// the computed TECs back to the original points. The algorithm compares and hashes integer
+/// points, so onsets that differ only by floating point errors, e.g., triplets that do not sum
+/// exactly to a beat, are equal on the grid.
+///
+/// The point set must be quantizable, see `Quantizer::quantize`: a point must have an onset and
+/// a pitch, and they must be finite. The methods of `TecAlgorithm` output no TECs for a point set
+/// that cannot be quantized, whereas `Quantized::try_compute_tecs` returns the error.
+pub struct Quantized<A> {
+    algorithm: A,
+    quantizer: Quantizer,
+}
+
+impl<A> Quantized<A> {
+    /// Returns an instance that runs the algorithm on the points quantized with the quantizer.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - The algorithm that computes the TECs of the quantized points
+    /// * `quantizer` - The quantizer that maps the points onto the integer grid
+    pub fn with(algorithm: A, quantizer: Quantizer) -> Quantized<A> {
+        Quantized {
+            algorithm,
+            quantizer,
+        }
+    }
+
+    /// Computes the TECs of the point set, or returns an error if the point set cannot be
+    /// quantized.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set for which the TECs are computed
+    pub fn try_compute_tecs<T: Point>(
+        &self,
+        point_set: &PointSet<T>,
+    ) -> Result<Vec<Tec<T>>, PoSemirError>
+    where
+        A: TecAlgorithm<Point2Di64>,
+    {
+        let mut tecs = Vec::new();
+        self.try_compute_tecs_to_output_observed(point_set, &IgnoreProgress {}, |tec| {
+            tecs.push(tec);
+            ControlFlow::Continue(())
+        })?;
+        Ok(tecs)
+    }
+
+    /// Computes the TECs as `try_compute_tecs`, and outputs each TEC with the given callback
+    /// as soon as it has been computed. Returns an error if the point set cannot be quantized,
+    /// or if the algorithm outputs a TEC that cannot be mapped back to the original points.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set for which the TECs are computed
+    /// * `observer` - The observer that is notified of the progress of the algorithm
+    /// * `on_output` - The callback for the TECs, which can stop the computation
+    pub fn try_compute_tecs_to_output_observed<T: Point>(
+        &self,
+        point_set: &PointSet<T>,
+        observer: &dyn ProgressObserver,
+        mut on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) -> Result<(), PoSemirError>
+    where
+        A: TecAlgorithm<Point2Di64>,
+    {
+        let quantized = self.quantizer.quantize_point_set(point_set)?;
+
+        let mut unmapped = false;
+        self.algorithm
+            .compute_tecs_to_output_observed(&quantized.point_set, observer, |tec| match quantized
+                .original_tec(&tec)
+            {
+                Some(original) => on_output(original),
+                None => {
+                    unmapped = true;
+                    ControlFlow::Break(())
+                }
+            });
+
+        if unmapped {
+            return Err(PoSemirError::InvalidPointSet(String::from(
+                "the algorithm output a TEC that is not in the quantized point set",
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl<T: Point, A: TecAlgorithm<Point2Di64>> TecAlgorithm<T> for Quantized<A> {
+    fn compute_tecs(&self, point_set: &PointSet<T>) -> Vec<Tec<T>> {
+        let mut tecs = Vec::new();
+        self.compute_tecs_to_output(point_set, |tec| {
+            tecs.push(tec);
+            ControlFlow::Continue(())
+        });
+        tecs
+    }
+
+    fn compute_tecs_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        self.compute_tecs_to_output_observed(point_set, &IgnoreProgress {}, on_output)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "quantized", skip_all, fields(n = point_set.len())))]
+    fn compute_tecs_to_output_observed(
+        &self,
+        point_set: &PointSet<T>,
+        observer: &dyn ProgressObserver,
+        on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        // The error is available through `try_compute_tecs_to_output_observed`.
+        let _ = self.try_compute_tecs_to_output_observed(point_set, observer, on_output);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::algorithm::TecAlgorithm;
+    use crate::discovery::quantized::Quantized;
+    use crate::discovery::siatec::Siatec;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::quantization::Quantizer;
+    use crate::point_set::set::PointSet;
+
+    #[test]
+    fn test_triplets_with_rounding_errors() {
+        // Two triplet motifs, whose onsets have different rounding errors.
+        let mut points = Vec::new();
+        for start in [0.0, 1.0] {
+            let mut onset: f64 = start;
+            for pitch in [60.0, 62.0, 64.0] {
+                points.push(Point2Df64 { x: onset, y: pitch });
+                onset += 1.0 / 3.0;
+            }
+        }
+        let point_set = PointSet::new(points);
+
+        let quantized = Quantized::with(Siatec {}, Quantizer::with(12.0));
+        let tecs = quantized.compute_tecs(&point_set);
+        let motif = tecs.iter().find(|tec| tec.pattern.len() == 3).unwrap();
+        assert_eq!(point_set.get_pattern(&[0, 1, 2]), motif.pattern);
+        assert_eq!(point_set, motif.covered_set());
+    }
+
+    #[test]
+    fn test_point_set_that_cannot_be_quantized() {
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 0.0, y: 60.0 },
+            Point2Df64 {
+                x: f64::NAN,
+                y: 62.0,
+            },
+        ]);
+
+        let quantized = Quantized::with(Siatec {}, Quantizer::with(12.0));
+        assert!(quantized.try_compute_tecs(&point_set).is_err());
+        assert!(quantized.compute_tecs(&point_set).is_empty());
+    }
+}
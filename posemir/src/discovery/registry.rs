@@ -0,0 +1,690 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::collections::HashMap;
+
+use crate::discovery::algorithm::{DynMtpAlgorithm, DynTecAlgorithm};
+use crate::discovery::cosiatec::Cosiatec;
+use crate::discovery::duplicates::DuplicateStrategy;
+use crate::discovery::sia::Sia;
+use crate::discovery::sia_chunked::SiaChunked;
+use crate::discovery::siar::SiaR;
+use crate::discovery::siatec::Siatec;
+use crate::discovery::siatec_c::SiatecC;
+use crate::discovery::siatec_ch::SiatecCH;
+use crate::discovery::siatec_compress::SiatecCompress;
+use crate::discovery::siatec_r::SiatecR;
+use crate::discovery::sliding_window::SlidingWindow;
+use crate::error::PoSemirError;
+use crate::point_set::point::Point;
+
+/// The names of the algorithms that can be created with `create_algorithm`.
+pub const ALGORITHM_NAMES: [&str; 11] = [
+    "SIA",
+    "SIA-CHUNKED",
+    "SIAR",
+    "SIATEC",
+    "SIATEC-C",
+    "SIATEC-CH",
+    "SIATEC-R",
+    "COSIATEC",
+    "COSIATEC-C",
+    "SIATECCOMPRESS",
+    "SIATEC-CCOMPRESS",
+];
+
+/// The names of the algorithms that can be created with `create_algorithm_with_base`.
+pub const BASE_WRAPPING_ALGORITHM_NAMES: [&str; 3] =
+    ["COSIATEC", "SIATEC-COMPRESS", "SLIDING-WINDOW"];
+
+/// The parameter for the maximum inter-onset interval of SIATEC-C based algorithms.
+pub const MAX_IOI: &str = "max_ioi";
+/// The parameter for the number of subdiagonals of SIAR and SIATEC-R.
+pub const SUB_DIAGONALS: &str = "r";
+/// The parameter for the memory budget of SIA-CHUNKED in megabytes.
+pub const MEMORY_BUDGET: &str = "memory_budget";
+/// The optional parameter for the maximum number of points in a pattern of SIATEC-C.
+pub const MAX_PATTERN_SIZE: &str = "max_pattern_size";
+/// The optional parameter for the maximum number of gaps in a pattern of SIATEC-C.
+pub const MAX_GAP_COUNT: &str = "max_gap_count";
+/// The parameter for the length of the time windows of SLIDING-WINDOW.
+pub const WINDOW_LENGTH: &str = "window_length";
+/// The parameter for the time between the starts of successive windows of SLIDING-WINDOW.
+pub const HOP: &str = "hop";
+/// The optional parameter for the strategy for removing translationally equivalent duplicates
+/// from the output of SIATEC-C and SIATEC-CH (see `DuplicateStrategy::parameter_value`).
+/// The duplicates are kept if the value is zero or the parameter is not present.
+pub const DUPLICATE_STRATEGY: &str = "duplicate_strategy";
+/// The optional parameter for merging the TECs across the windows of SLIDING-WINDOW,
+/// enabled by a non-zero value.
+pub const MERGE_WINDOWS: &str = "merge_windows";
+
+/// An algorithm created by name with `create_algorithm`.
+pub enum DiscoveryAlgorithm<T: Point> {
+    /// An algorithm that computes MTPs.
+    Mtp(Box<dyn DynMtpAlgorithm<T>>),
+    /// An algorithm that computes TECs.
+    Tec(Box<dyn DynTecAlgorithm<T>>),
+}
+
+/// Returns the names of the parameters used by the algorithm with the given name,
+/// or empty if there is no such algorithm.
+///
+/// # Arguments
+///
+/// * `name` - The name of the algorithm (case insensitive), see `ALGORITHM_NAMES`
+pub fn parameter_names(name: &str) -> Option<&'static [&'static str]> {
+    match name.to_uppercase().as_str() {
+        "SIA" | "SIATEC" | "COSIATEC" | "SIATECCOMPRESS" | "SIATEC-COMPRESS" => Some(&[]),
+        "SIA-CHUNKED" => Some(&[MEMORY_BUDGET]),
+        "SIAR" | "SIATEC-R" => Some(&[SUB_DIAGONALS]),
+        "SIATEC-C" | "SIATEC-CH" | "COSIATEC-C" | "SIATEC-CCOMPRESS" => Some(&[MAX_IOI]),
+        "SLIDING-WINDOW" => Some(&[WINDOW_LENGTH, HOP]),
+        _ => None,
+    }
+}
+
+/// Creates the algorithm with the given name. The parameters of the algorithm are read
+/// from the parameter map (see `parameter_names`), and all parameters used by the
+/// algorithm must be present. The SIATEC-C based algorithms also read the optional
+/// parameters `MAX_PATTERN_SIZE`, `MAX_GAP_COUNT` and `DUPLICATE_STRATEGY` if they are present,
+/// and SIATEC-CH reads `DUPLICATE_STRATEGY`.
+///
+/// # Arguments
+///
+/// * `name` - The name of the algorithm (case insensitive), see `ALGORITHM_NAMES`
+/// * `parameters` - The parameter values by parameter name
+pub fn create_algorithm<T: Point + 'static>(
+    name: &str,
+    parameters: &HashMap<String, f64>,
+) -> Result<DiscoveryAlgorithm<T>, PoSemirError> {
+    let algorithm = match name.to_uppercase().as_str() {
+        "SIA" => DiscoveryAlgorithm::Mtp(Box::new(Sia::new())),
+        "SIA-CHUNKED" => {
+            let megabytes = parameter(parameters, MEMORY_BUDGET)?;
+            if megabytes <= 0.0 {
+                return Err(PoSemirError::InvalidConfiguration(format!(
+                    "{} must be positive",
+                    MEMORY_BUDGET
+                )));
+            }
+            DiscoveryAlgorithm::Mtp(Box::new(SiaChunked {
+                memory_budget: (megabytes * 1024.0 * 1024.0) as usize,
+            }))
+        }
+        "SIAR" => DiscoveryAlgorithm::Mtp(Box::new(SiaR::new(sub_diagonals(parameters)?))),
+        "SIATEC" => DiscoveryAlgorithm::Tec(Box::new(Siatec {})),
+        "SIATEC-C" => DiscoveryAlgorithm::Tec(Box::new(siatec_c(parameters)?)),
+        "SIATEC-CH" => DiscoveryAlgorithm::Tec(Box::new(siatec_ch(parameters)?)),
+        "SIATEC-R" => DiscoveryAlgorithm::Tec(Box::new(SiatecR {
+            r: sub_diagonals(parameters)?,
+            min_pattern_size: 0,
+        })),
+        "COSIATEC" => DiscoveryAlgorithm::Tec(Box::new(Cosiatec::with(Siatec {}))),
+        "COSIATEC-C" => DiscoveryAlgorithm::Tec(Box::new(Cosiatec::with(siatec_c(parameters)?))),
+        "SIATECCOMPRESS" | "SIATEC-COMPRESS" => {
+            DiscoveryAlgorithm::Tec(Box::new(SiatecCompress::with(Siatec {})))
+        }
+        "SIATEC-CCOMPRESS" => {
+            DiscoveryAlgorithm::Tec(Box::new(SiatecCompress::with(siatec_c(parameters)?)))
+        }
+        _ => {
+            return Err(PoSemirError::InvalidConfiguration(format!(
+                "unrecognized algorithm {}",
+                name
+            )))
+        }
+    };
+
+    Ok(algorithm)
+}
+
+/// Creates the algorithm with the given name that wraps the given base TEC algorithm, e.g.,
+/// COSIATEC with SIATEC-C for computing the TEC candidates. The parameters of the base
+/// algorithm are read from the parameter map as in `create_algorithm`, and those of the
+/// wrapping algorithm as given by `parameter_names`. SLIDING-WINDOW also reads the optional
+/// parameter `MERGE_WINDOWS` if it is present.
+///
+/// # Arguments
+///
+/// * `name` - The name of the wrapping algorithm (case insensitive),
+///   see `BASE_WRAPPING_ALGORITHM_NAMES`
+/// * `base_name` - The name of the base algorithm (case insensitive), which must be
+///   one of `ALGORITHM_NAMES` that computes TECs
+/// * `parameters` - The parameter values by parameter name
+pub fn create_algorithm_with_base<T: Point + 'static>(
+    name: &str,
+    base_name: &str,
+    parameters: &HashMap<String, f64>,
+) -> Result<DiscoveryAlgorithm<T>, PoSemirError> {
+    let base = match create_algorithm(base_name, parameters)? {
+        DiscoveryAlgorithm::Tec(base) => base,
+        DiscoveryAlgorithm::Mtp(_) => {
+            return Err(PoSemirError::InvalidConfiguration(format!(
+                "base algorithm {} does not compute TECs",
+                base_name
+            )))
+        }
+    };
+
+    let algorithm = match name.to_uppercase().as_str() {
+        "COSIATEC" => DiscoveryAlgorithm::Tec(Box::new(Cosiatec::with(base))),
+        "SIATECCOMPRESS" | "SIATEC-COMPRESS" => {
+            DiscoveryAlgorithm::Tec(Box::new(SiatecCompress::with(base)))
+        }
+        "SLIDING-WINDOW" => DiscoveryAlgorithm::Tec(Box::new(sliding_window(base, parameters)?)),
+        _ => {
+            return Err(PoSemirError::InvalidConfiguration(format!(
+                "algorithm {} does not take a base algorithm",
+                name
+            )))
+        }
+    };
+
+    Ok(algorithm)
+}
+
+/// Creates COSIATEC as `create_algorithm` or `create_algorithm_with_base`, but returns it
+/// as `Cosiatec` instead of a trait object, so that its own methods can be used, e.g.,
+/// `compute_tecs_from_checkpoint` for resuming long computations.
+///
+/// # Arguments
+///
+/// * `name` - The name of the algorithm (case insensitive), COSIATEC or COSIATEC-C
+/// * `base_name` - The name of the base algorithm of COSIATEC (case insensitive) as in
+///   `create_algorithm_with_base`, or None for the default base algorithm
+/// * `parameters` - The parameter values by parameter name
+pub fn create_cosiatec<T: Point + 'static>(
+    name: &str,
+    base_name: Option<&str>,
+    parameters: &HashMap<String, f64>,
+) -> Result<Cosiatec<T, Box<dyn DynTecAlgorithm<T>>>, PoSemirError> {
+    let base_name = match (name.to_uppercase().as_str(), base_name) {
+        ("COSIATEC", Some(base_name)) => base_name,
+        ("COSIATEC", None) => "SIATEC",
+        ("COSIATEC-C", None) => "SIATEC-C",
+        _ => {
+            return Err(PoSemirError::InvalidConfiguration(format!(
+                "algorithm {} is not COSIATEC with a base algorithm",
+                name
+            )))
+        }
+    };
+
+    match create_algorithm(base_name, parameters)? {
+        DiscoveryAlgorithm::Tec(base) => Ok(Cosiatec::with(base)),
+        DiscoveryAlgorithm::Mtp(_) => Err(PoSemirError::InvalidConfiguration(format!(
+            "base algorithm {} does not compute TECs",
+            base_name
+        ))),
+    }
+}
+
+/// Creates the sliding window driver for the TEC algorithm with the given name as in
+/// `create_algorithm_with_base`, but returns it as `SlidingWindow` instead of a trait object,
+/// so that the windows in which each TEC was found can be reported with
+/// `compute_windowed_tecs`.
+///
+/// # Arguments
+///
+/// * `base_name` - The name of the TEC algorithm that is run in each window (case insensitive)
+/// * `parameters` - The parameter values by parameter name
+pub fn create_sliding_window<T: Point + 'static>(
+    base_name: &str,
+    parameters: &HashMap<String, f64>,
+) -> Result<SlidingWindow<T, Box<dyn DynTecAlgorithm<T>>>, PoSemirError> {
+    match create_algorithm(base_name, parameters)? {
+        DiscoveryAlgorithm::Tec(base) => sliding_window(base, parameters),
+        DiscoveryAlgorithm::Mtp(_) => Err(PoSemirError::InvalidConfiguration(format!(
+            "base algorithm {} does not compute TECs",
+            base_name
+        ))),
+    }
+}
+
+/// Returns the sliding window driver for the base algorithm, whose window length
+/// and hop must be positive. The TECs are merged across the windows if the optional
+/// merge parameter is non-zero.
+fn sliding_window<T: Point>(
+    base: Box<dyn DynTecAlgorithm<T>>,
+    parameters: &HashMap<String, f64>,
+) -> Result<SlidingWindow<T, Box<dyn DynTecAlgorithm<T>>>, PoSemirError> {
+    let window_length = parameter(parameters, WINDOW_LENGTH)?;
+    let hop = parameter(parameters, HOP)?;
+    let sliding_window = SlidingWindow::with(base, window_length, hop)?;
+    match parameters.get(MERGE_WINDOWS) {
+        Some(merge) if *merge != 0.0 => Ok(sliding_window.with_merged_windows()),
+        _ => Ok(sliding_window),
+    }
+}
+
+fn siatec_c(parameters: &HashMap<String, f64>) -> Result<SiatecC, PoSemirError> {
+    Ok(SiatecC {
+        max_pattern_size: optional_count(parameters, MAX_PATTERN_SIZE, 1)?,
+        max_gap_count: optional_count(parameters, MAX_GAP_COUNT, 0)?,
+        duplicate_strategy: duplicate_strategy(parameters)?,
+        ..SiatecC::new(parameter(parameters, MAX_IOI)?)
+    })
+}
+
+fn siatec_ch(parameters: &HashMap<String, f64>) -> Result<SiatecCH, PoSemirError> {
+    Ok(SiatecCH {
+        duplicate_strategy: duplicate_strategy(parameters)?,
+        ..SiatecCH::new(parameter(parameters, MAX_IOI)?)
+    })
+}
+
+/// Returns the duplicate strategy of SIATEC-C and SIATEC-CH, or None if the duplicates are kept.
+fn duplicate_strategy(
+    parameters: &HashMap<String, f64>,
+) -> Result<Option<DuplicateStrategy>, PoSemirError> {
+    match parameters.get(DUPLICATE_STRATEGY) {
+        None => Ok(None),
+        Some(value) if *value == 0.0 => Ok(None),
+        Some(value) => match DuplicateStrategy::from_parameter_value(*value) {
+            Some(strategy) => Ok(Some(strategy)),
+            None => Err(PoSemirError::InvalidConfiguration(format!(
+                "unrecognized {} {}",
+                DUPLICATE_STRATEGY, value
+            ))),
+        },
+    }
+}
+
+/// Returns the number of subdiagonals of SIAR and SIATEC-R, which must be a positive integer.
+fn sub_diagonals(parameters: &HashMap<String, f64>) -> Result<usize, PoSemirError> {
+    let r = parameter(parameters, SUB_DIAGONALS)?;
+    if r < 1.0 || r.fract() != 0.0 {
+        return Err(PoSemirError::InvalidConfiguration(format!(
+            "{} must be a positive integer",
+            SUB_DIAGONALS
+        )));
+    }
+    Ok(r as usize)
+}
+
+/// Returns the value of the optional parameter, which must be an integer of at least min.
+fn optional_count(
+    parameters: &HashMap<String, f64>,
+    name: &str,
+    min: usize,
+) -> Result<Option<usize>, PoSemirError> {
+    match parameters.get(name) {
+        Some(value) if *value < min as f64 || value.fract() != 0.0 => {
+            Err(PoSemirError::InvalidConfiguration(format!(
+                "{} must be an integer of at least {}",
+                name, min
+            )))
+        }
+        Some(value) => Ok(Some(*value as usize)),
+        None => Ok(None),
+    }
+}
+
+fn parameter(parameters: &HashMap<String, f64>, name: &str) -> Result<f64, PoSemirError> {
+    match parameters.get(name) {
+        Some(value) => Ok(*value),
+        None => Err(PoSemirError::InvalidConfiguration(format!(
+            "missing parameter {}",
+            name
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::discovery::algorithm::{MtpAlgorithm, TecAlgorithm};
+    use crate::discovery::cosiatec::Cosiatec;
+    use crate::discovery::duplicates::DuplicateStrategy;
+    use crate::discovery::registry::{
+        create_algorithm, create_algorithm_with_base, create_cosiatec, create_sliding_window,
+        parameter_names, DiscoveryAlgorithm, ALGORITHM_NAMES, BASE_WRAPPING_ALGORITHM_NAMES,
+        DUPLICATE_STRATEGY, HOP, MAX_GAP_COUNT, MAX_IOI, MAX_PATTERN_SIZE, MEMORY_BUDGET,
+        MERGE_WINDOWS, SUB_DIAGONALS, WINDOW_LENGTH,
+    };
+    use crate::discovery::sia::Sia;
+    use crate::discovery::siatec::Siatec;
+    use crate::discovery::siatec_c::SiatecC;
+    use crate::discovery::siatec_ch::SiatecCH;
+    use crate::discovery::sliding_window::SlidingWindow;
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::{Point2Df64, Point2Di64};
+    use crate::point_set::point_nd::PointNdi64;
+    use crate::point_set::set::PointSet;
+    use crate::point_set::tec::Tec;
+
+    fn test_point_set() -> PointSet<Point2Df64> {
+        PointSet::new(vec![
+            Point2Df64 { x: 1.0, y: 1.0 },
+            Point2Df64 { x: 2.0, y: 3.0 },
+            Point2Df64 { x: 2.0, y: 1.0 },
+            Point2Df64 { x: 3.0, y: 3.0 },
+        ])
+    }
+
+    #[test]
+    fn test_create_all_algorithms() {
+        let parameters = HashMap::from([
+            (MAX_IOI.to_string(), 2.0),
+            (SUB_DIAGONALS.to_string(), 2.0),
+            (MEMORY_BUDGET.to_string(), 1.0),
+        ]);
+        let point_set = test_point_set();
+
+        for name in ALGORITHM_NAMES {
+            assert!(parameter_names(name).is_some());
+            match create_algorithm::<Point2Df64>(name, &parameters).unwrap() {
+                DiscoveryAlgorithm::Mtp(algorithm) => {
+                    assert!(!algorithm.compute_mtps(&point_set).is_empty())
+                }
+                DiscoveryAlgorithm::Tec(algorithm) => {
+                    assert!(!algorithm.compute_tecs(&point_set).is_empty())
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_all_algorithms_with_small_point_sets() {
+        let parameters = HashMap::from([
+            (MAX_IOI.to_string(), 2.0),
+            (SUB_DIAGONALS.to_string(), 2.0),
+            (MEMORY_BUDGET.to_string(), 1.0),
+            (WINDOW_LENGTH.to_string(), 4.0),
+            (HOP.to_string(), 2.0),
+        ]);
+        let points = test_point_set().points();
+
+        for size in 0..3 {
+            let point_set = PointSet::new(points[..size].to_vec());
+
+            for name in ALGORITHM_NAMES {
+                match create_algorithm::<Point2Df64>(name, &parameters).unwrap() {
+                    DiscoveryAlgorithm::Mtp(algorithm) => {
+                        let mtps = algorithm.try_compute_mtps(&point_set).unwrap();
+                        assert!(size >= 2 || mtps.is_empty(), "{}", name);
+                    }
+                    DiscoveryAlgorithm::Tec(algorithm) => {
+                        let tecs = algorithm.try_compute_tecs(&point_set).unwrap();
+                        assert!(tecs.iter().all(|tec| !tec.pattern.is_empty()), "{}", name);
+                    }
+                }
+            }
+
+            for name in BASE_WRAPPING_ALGORITHM_NAMES {
+                for base_name in ["SIATEC", "SIATEC-C", "SIATEC-CH"] {
+                    match create_algorithm_with_base::<Point2Df64>(name, base_name, &parameters)
+                        .unwrap()
+                    {
+                        DiscoveryAlgorithm::Tec(algorithm) => {
+                            let tecs = algorithm.try_compute_tecs(&point_set).unwrap();
+                            assert!(tecs.iter().all(|tec| !tec.pattern.is_empty()), "{}", name);
+                        }
+                        _ => panic!("{} should be a TEC algorithm", name),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_created_algorithms_equal_direct_instances() {
+        let parameters = HashMap::from([(MAX_IOI.to_string(), 2.0)]);
+        let point_set = test_point_set();
+
+        match create_algorithm("siatec-c", &parameters).unwrap() {
+            DiscoveryAlgorithm::Tec(algorithm) => assert_eq!(
+                SiatecC::new(2.0).compute_tecs(&point_set),
+                algorithm.compute_tecs(&point_set)
+            ),
+            _ => panic!("SIATEC-C should be a TEC algorithm"),
+        }
+
+        let mut parameters = parameters;
+        parameters.insert(
+            DUPLICATE_STRATEGY.to_string(),
+            DuplicateStrategy::MergeTranslators.parameter_value(),
+        );
+        match create_algorithm("SIATEC-CH", &parameters).unwrap() {
+            DiscoveryAlgorithm::Tec(algorithm) => assert_eq!(
+                SiatecCH::new(2.0)
+                    .with_duplicate_strategy(DuplicateStrategy::MergeTranslators)
+                    .compute_tecs(&point_set),
+                algorithm.compute_tecs(&point_set)
+            ),
+            _ => panic!("SIATEC-CH should be a TEC algorithm"),
+        }
+        match create_algorithm("SIATEC-C", &parameters).unwrap() {
+            DiscoveryAlgorithm::Tec(algorithm) => assert_eq!(
+                SiatecC::new(2.0)
+                    .with_duplicate_strategy(DuplicateStrategy::MergeTranslators)
+                    .compute_tecs(&point_set),
+                algorithm.compute_tecs(&point_set)
+            ),
+            _ => panic!("SIATEC-C should be a TEC algorithm"),
+        }
+        parameters.insert(DUPLICATE_STRATEGY.to_string(), 4.0);
+        assert!(create_algorithm::<Point2Df64>("SIATEC-C", &parameters).is_err());
+
+        match create_algorithm("SIA", &parameters).unwrap() {
+            DiscoveryAlgorithm::Mtp(algorithm) => assert_eq!(
+                Sia::new().compute_mtps(&point_set),
+                algorithm.compute_mtps(&point_set)
+            ),
+            _ => panic!("SIA should be an MTP algorithm"),
+        }
+    }
+
+    #[test]
+    fn test_create_algorithms_with_base() {
+        let parameters = HashMap::from([
+            (MAX_IOI.to_string(), 2.0),
+            (WINDOW_LENGTH.to_string(), 4.0),
+            (HOP.to_string(), 2.0),
+        ]);
+        let point_set = test_point_set();
+
+        for name in BASE_WRAPPING_ALGORITHM_NAMES {
+            assert!(parameter_names(name).is_some());
+            assert!(create_algorithm_with_base::<Point2Df64>(name, "SIA", &parameters).is_err());
+            match create_algorithm_with_base(name, "siatec-ch", &parameters).unwrap() {
+                DiscoveryAlgorithm::Tec(algorithm) => {
+                    assert!(!algorithm.compute_tecs(&point_set).is_empty())
+                }
+                _ => panic!("{} should be a TEC algorithm", name),
+            }
+        }
+
+        match create_algorithm_with_base("COSIATEC", "SIATEC-C", &parameters).unwrap() {
+            DiscoveryAlgorithm::Tec(algorithm) => assert_eq!(
+                Cosiatec::with(SiatecC::new(2.0)).compute_tecs(&point_set),
+                algorithm.compute_tecs(&point_set)
+            ),
+            _ => panic!("COSIATEC should be a TEC algorithm"),
+        }
+        assert!(create_algorithm_with_base::<Point2Df64>("SIATEC", "SIATEC", &parameters).is_err());
+
+        let cosiatec = create_cosiatec("cosiatec", Some("SIATEC-C"), &parameters).unwrap();
+        let cosiatec_c = create_cosiatec("COSIATEC-C", None, &parameters).unwrap();
+        assert_eq!(
+            cosiatec.compute_tecs(&point_set),
+            cosiatec_c.compute_tecs(&point_set)
+        );
+        assert!(create_cosiatec::<Point2Df64>("COSIATEC", Some("SIA"), &parameters).is_err());
+        assert!(create_cosiatec::<Point2Df64>("COSIATEC-C", Some("SIATEC"), &parameters).is_err());
+        assert!(create_cosiatec::<Point2Df64>("SIATEC", None, &parameters).is_err());
+    }
+
+    #[test]
+    fn test_create_sliding_window() {
+        let mut parameters =
+            HashMap::from([(WINDOW_LENGTH.to_string(), 2.0), (HOP.to_string(), 1.0)]);
+        let point_set = test_point_set();
+
+        let sliding_window = create_sliding_window("siatec", &parameters).unwrap();
+        let expected = SlidingWindow::with(Siatec {}, 2.0, 1.0).unwrap();
+        assert_eq!(
+            expected.windows(&point_set),
+            sliding_window.windows(&point_set)
+        );
+        assert_eq!(
+            expected.compute_tecs(&point_set),
+            sliding_window.compute_tecs(&point_set)
+        );
+        assert!(create_sliding_window::<Point2Df64>("SIA", &parameters).is_err());
+
+        parameters.insert(MERGE_WINDOWS.to_string(), 1.0);
+        let merged = create_sliding_window("siatec", &parameters).unwrap();
+        assert_eq!(
+            expected.with_merged_windows().compute_tecs(&point_set),
+            merged.compute_tecs(&point_set)
+        );
+
+        parameters.insert(HOP.to_string(), 0.0);
+        assert!(create_sliding_window::<Point2Df64>("SIATEC", &parameters).is_err());
+        parameters.remove(HOP);
+        assert!(
+            create_algorithm_with_base::<Point2Df64>("SLIDING-WINDOW", "SIATEC", &parameters)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_algorithms_with_n_dimensional_points() {
+        let points = vec![
+            Point2Di64 { x: 0, y: 60 },
+            Point2Di64 { x: 1, y: 62 },
+            Point2Di64 { x: 1, y: 48 },
+            Point2Di64 { x: 2, y: 64 },
+            Point2Di64 { x: 4, y: 62 },
+            Point2Di64 { x: 5, y: 64 },
+            Point2Di64 { x: 5, y: 50 },
+            Point2Di64 { x: 6, y: 66 },
+        ];
+        // The third dimension follows the pitch, so the results must be the same as in 2-D.
+        let embed = |p: &Point2Di64| PointNdi64 {
+            components: [p.x, p.y, p.y],
+        };
+        let project = |p: &PointNdi64<3>| Point2Di64 {
+            x: p.components[0],
+            y: p.components[1],
+        };
+
+        let point_set = PointSet::new(points.clone());
+        let point_set_nd = PointSet::new(points.iter().map(embed).collect());
+        let parameters = HashMap::from([
+            (MAX_IOI.to_string(), 2.0),
+            (SUB_DIAGONALS.to_string(), 2.0),
+            (MEMORY_BUDGET.to_string(), 1.0),
+        ]);
+
+        for name in ALGORITHM_NAMES {
+            let (mut expected, mut tecs) = match (
+                create_algorithm::<Point2Di64>(name, &parameters).unwrap(),
+                create_algorithm::<PointNdi64<3>>(name, &parameters).unwrap(),
+            ) {
+                (DiscoveryAlgorithm::Mtp(algorithm), DiscoveryAlgorithm::Mtp(algorithm_nd)) => (
+                    algorithm
+                        .compute_mtps(&point_set)
+                        .into_iter()
+                        .map(|mtp| Tec {
+                            pattern: mtp.pattern,
+                            translators: vec![mtp.translator],
+                        })
+                        .collect(),
+                    algorithm_nd
+                        .compute_mtps(&point_set_nd)
+                        .into_iter()
+                        .map(|mtp| Tec {
+                            pattern: mtp.pattern,
+                            translators: vec![mtp.translator],
+                        })
+                        .collect(),
+                ),
+                (DiscoveryAlgorithm::Tec(algorithm), DiscoveryAlgorithm::Tec(algorithm_nd)) => (
+                    algorithm.compute_tecs(&point_set),
+                    algorithm_nd.compute_tecs(&point_set_nd),
+                ),
+                _ => panic!(
+                    "{} should be the same kind of algorithm in 2-D and 3-D",
+                    name
+                ),
+            };
+
+            let mut projected: Vec<Tec<Point2Di64>> = tecs
+                .drain(..)
+                .map(|tec| Tec {
+                    pattern: Pattern::new(
+                        &tec.pattern
+                            .into_iter()
+                            .map(project)
+                            .collect::<Vec<_>>()
+                            .iter()
+                            .collect(),
+                    ),
+                    translators: tec.translators.iter().map(project).collect(),
+                })
+                .collect();
+            if name == "SIATEC-CH" {
+                // The greedy cover of SIATEC-CH depends on the hash order of the points.
+                let covered = |tecs: &Vec<Tec<Point2Di64>>| {
+                    let mut points: Vec<Point2Di64> = tecs
+                        .iter()
+                        .flat_map(|tec| tec.covered_set().points())
+                        .collect();
+                    points.sort();
+                    points.dedup();
+                    points
+                };
+                assert_eq!(covered(&expected), covered(&projected), "{}", name);
+            } else {
+                expected.sort();
+                projected.sort();
+                assert_eq!(expected, projected, "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_configurations() {
+        let parameters = HashMap::from([(SUB_DIAGONALS.to_string(), 0.5)]);
+
+        assert!(create_algorithm::<Point2Df64>("SIATEC-X", &parameters).is_err());
+        assert!(create_algorithm::<Point2Df64>("SIATEC-C", &parameters).is_err());
+        assert!(create_algorithm::<Point2Df64>("SIAR", &parameters).is_err());
+        assert!(create_algorithm::<Point2Df64>("SIATEC-R", &parameters).is_err());
+        assert!(parameter_names("SIATEC-X").is_none());
+
+        let parameters = HashMap::from([
+            (MAX_IOI.to_string(), 2.0),
+            (MAX_PATTERN_SIZE.to_string(), 0.0),
+        ]);
+        assert!(create_algorithm::<Point2Df64>("SIATEC-C", &parameters).is_err());
+        let parameters =
+            HashMap::from([(MAX_IOI.to_string(), 2.0), (MAX_GAP_COUNT.to_string(), 1.5)]);
+        assert!(create_algorithm::<Point2Df64>("COSIATEC-C", &parameters).is_err());
+    }
+
+    #[test]
+    fn test_try_compute_with_invalid_point_set() {
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 0.0, y: 1.0 },
+            Point2Df64 {
+                x: f64::INFINITY,
+                y: 1.0,
+            },
+        ]);
+
+        match create_algorithm("SIATEC", &HashMap::new()).unwrap() {
+            DiscoveryAlgorithm::Tec(algorithm) => {
+                assert!(algorithm.try_compute_tecs(&point_set).is_err());
+                assert!(algorithm.try_compute_tecs(&test_point_set()).is_ok());
+            }
+            _ => panic!("SIATEC should be a TEC algorithm"),
+        }
+        assert!(Sia::new().try_compute_mtps(&point_set).is_err());
+    }
+}
@@ -0,0 +1,238 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec_with_scaling::{scale_onset, TecWithScaling};
+use crate::utilities::sort;
+
+/// Implements a SIATEC-like algorithm that finds patterns that are repeated under time-scaling
+/// as well as translation, e.g., augmented and diminished restatements of a theme.
+///
+/// For each scale factor s, the vectors q - scale(p, s) are computed for all pairs of points p, q,
+/// where scale(p, s) multiplies the onset of p by s. Partitioning the sorted vectors gives the
+/// maximal patterns that are mapped into the point set by scaling with s and translating by the
+/// vector, in the same way as SIA computes MTPs. The occurrences of each found pattern are then
+/// searched for with all of the scale factors. The scale factor 1 (pure translation) is always
+/// used, so the found patterns include the patterns found by SIATEC.
+pub struct ScalingSiatec {
+    /// The scale factors used in addition to 1, e.g., 2.0 for augmentation
+    /// and 0.5 for diminution. The scale factors must be positive.
+    pub scale_factors: Vec<f64>,
+}
+
+impl ScalingSiatec {
+    /// Returns the TECs with scaling in the given point set.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The set of points for which the TECs are computed
+    pub fn compute_tecs_with_scaling<T: Point>(
+        &self,
+        point_set: &PointSet<T>,
+    ) -> Vec<TecWithScaling<T>> {
+        let mut tecs = Vec::new();
+        self.compute_tecs_with_scaling_to_output(point_set, |tec| {
+            tecs.push(tec);
+            ControlFlow::Continue(())
+        });
+        tecs
+    }
+
+    /// Computes the TECs with scaling in the given point set and executes on_output for
+    /// each produced TEC. The computation is stopped as soon as on_output returns
+    /// `ControlFlow::Break`.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The set of points for which the TECs are computed
+    /// * `on_output` - A function to execute whenever the algorithm can produce output
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "scaling_siatec", skip_all, fields(n = point_set.len())))]
+    pub fn compute_tecs_with_scaling_to_output<T: Point>(
+        &self,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(TecWithScaling<T>) -> ControlFlow<()>,
+    ) {
+        let scale_factors = self.all_scale_factors();
+        let mut seen_patterns: HashSet<Pattern<T>> = HashSet::new();
+
+        for scale in &scale_factors {
+            let diffs = ScalingSiatec::compute_differences(point_set, *scale);
+
+            let mut i = 0;
+            while i < diffs.len() {
+                let mut indices = Vec::new();
+                let mut j = i;
+                while j < diffs.len() && diffs[i].0 == diffs[j].0 {
+                    indices.push(diffs[j].1);
+                    j += 1;
+                }
+                i = j;
+
+                let pattern = point_set.get_pattern(&indices);
+                if !seen_patterns.insert(pattern.vectorize()) {
+                    continue;
+                }
+
+                let transformations =
+                    ScalingSiatec::find_transformations(&pattern, point_set, &scale_factors);
+                let flow = on_output(TecWithScaling {
+                    pattern,
+                    transformations,
+                });
+                if flow.is_break() {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn all_scale_factors(&self) -> Vec<f64> {
+        let mut scale_factors = vec![1.0];
+        for scale in &self.scale_factors {
+            if !scale_factors.contains(scale) {
+                scale_factors.push(*scale);
+            }
+        }
+        scale_factors
+    }
+
+    /// Computes the differences between the points and the scaled points with the indices of
+    /// the scaled points. With the scale factor 1 only the forward differences are computed,
+    /// as in SIA. The differences are sorted in ascending lexicographical order.
+    fn compute_differences<T: Point>(point_set: &PointSet<T>, scale: f64) -> Vec<(T, usize)> {
+        let n = point_set.len();
+        let mut diffs: Vec<(T, usize)> = Vec::with_capacity(n * n);
+
+        for i in 0..n {
+            let scaled = scale_onset(&point_set[i], scale);
+            let first_target = if scale == 1.0 { i + 1 } else { 0 };
+            for j in first_target..n {
+                diffs.push((point_set[j] - scaled, i));
+            }
+        }
+
+        sort(&mut diffs);
+        diffs
+    }
+
+    /// Finds all (translator, scale factor) pairs, other than the identity, by which the
+    /// pattern is mapped into the point set.
+    fn find_transformations<T: Point>(
+        pattern: &Pattern<T>,
+        point_set: &PointSet<T>,
+        scale_factors: &[f64],
+    ) -> Vec<(T, f64)> {
+        let mut transformations = Vec::new();
+
+        for scale in scale_factors {
+            let scaled: Vec<T> = pattern
+                .into_iter()
+                .map(|point| scale_onset(point, *scale))
+                .collect();
+
+            for point in point_set {
+                let translator = *point - scaled[0];
+                if *scale == 1.0 && translator.is_zero() {
+                    continue;
+                }
+
+                let is_mapped = scaled
+                    .iter()
+                    .skip(1)
+                    .all(|p| point_set.contains(&(*p + translator)));
+
+                if is_mapped {
+                    transformations.push((translator, *scale));
+                }
+            }
+        }
+
+        transformations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::algorithm::TecAlgorithm;
+    use crate::discovery::scaling_siatec::ScalingSiatec;
+    use crate::discovery::siatec::Siatec;
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+    use crate::point_set::tec_with_scaling::TecWithScaling;
+
+    #[test]
+    fn test_finds_augmented_occurrence() {
+        // A motif and its augmentation transposed up a fifth.
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 0.0, y: 60.0 },
+            Point2Df64 { x: 1.0, y: 62.0 },
+            Point2Df64 { x: 1.5, y: 64.0 },
+            Point2Df64 { x: 4.0, y: 67.0 },
+            Point2Df64 { x: 6.0, y: 69.0 },
+            Point2Df64 { x: 7.0, y: 71.0 },
+        ]);
+        let motif = Pattern::new(&vec![&point_set[0], &point_set[1], &point_set[2]]);
+
+        let tecs = ScalingSiatec {
+            scale_factors: vec![2.0, 0.5],
+        }
+        .compute_tecs_with_scaling(&point_set);
+
+        let motif_tec = tecs
+            .iter()
+            .find(|tec| tec.pattern.vectorize() == motif.vectorize())
+            .unwrap();
+        assert_eq!(2, motif_tec.occurrence_count());
+
+        let expanded = motif_tec.expand();
+        let other = if expanded[0] == motif {
+            &expanded[1]
+        } else {
+            &expanded[0]
+        };
+        assert_eq!(
+            Pattern::new(&vec![&point_set[3], &point_set[4], &point_set[5]]),
+            *other
+        );
+
+        // SIATEC does not find the motif, as its occurrences are not translationally equivalent.
+        assert!(Siatec {}
+            .compute_tecs(&point_set)
+            .iter()
+            .all(|tec| tec.pattern.vectorize() != motif.vectorize()));
+    }
+
+    #[test]
+    fn test_without_scale_factors_equals_siatec_patterns() {
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 1.0, y: 1.0 },
+            Point2Df64 { x: 2.0, y: 3.0 },
+            Point2Df64 { x: 2.0, y: 1.0 },
+            Point2Df64 { x: 3.0, y: 3.0 },
+            Point2Df64 { x: 4.0, y: 1.0 },
+        ]);
+
+        let tecs: Vec<TecWithScaling<Point2Df64>> = ScalingSiatec {
+            scale_factors: vec![],
+        }
+        .compute_tecs_with_scaling(&point_set);
+        let siatec_tecs = Siatec {}.compute_tecs(&point_set);
+
+        assert_eq!(siatec_tecs.len(), tecs.len());
+        for tec in &tecs {
+            assert!(tec.transformations.iter().all(|(_, scale)| *scale == 1.0));
+            let siatec_tec = siatec_tecs
+                .iter()
+                .find(|siatec_tec| siatec_tec.pattern.vectorize() == tec.pattern.vectorize())
+                .unwrap();
+            assert_eq!(siatec_tec.occurrence_count(), tec.occurrence_count());
+        }
+    }
+}
@@ -0,0 +1,142 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use crate::discovery::heuristic::{stats_of, TecStats};
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// The heuristic scores of a TEC in a point set, as used by COSIATEC and SIATECCompress
+/// for selecting the best TECs (see [Meredith2013]).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TecScores {
+    /// The compression ratio of the TEC, i.e., the size of the covered set divided by the
+    /// size of the TEC representation (pattern size + number of translators).
+    pub compression_ratio: f64,
+    /// The bounding box compactness of the TEC in the point set.
+    pub compactness: f64,
+    /// The number of points covered by the TEC.
+    pub coverage: usize,
+    /// The sum of the weights of the points covered by the TEC (see `Point::weight`),
+    /// which equals the coverage if the points are not weighted.
+    pub weighted_coverage: f64,
+    /// The number of points in the pattern of the TEC.
+    pub pattern_size: usize,
+    /// The width (extent in the first component) of the bounding box of the pattern.
+    pub pattern_width: f64,
+    /// The area of the bounding box of the pattern.
+    pub pattern_area: f64,
+}
+
+/// A TEC together with its heuristic scores in the point set it was discovered in.
+#[derive(Debug, Clone)]
+pub struct ScoredTec<T: Point> {
+    pub tec: Tec<T>,
+    pub scores: TecScores,
+}
+
+impl<T: Point> ScoredTec<T> {
+    /// Returns the TEC scored in the given point set.
+    ///
+    /// # Arguments
+    ///
+    /// * `tec` - The TEC to score
+    /// * `point_set` - The point set in which the TEC occurs
+    pub fn new(tec: Tec<T>, point_set: &PointSet<T>) -> ScoredTec<T> {
+        ScoredTec::from(stats_of(tec, point_set))
+    }
+}
+
+impl<T: Point> From<&TecStats<T>> for TecScores {
+    fn from(stats: &TecStats<T>) -> Self {
+        TecScores {
+            compression_ratio: stats.comp_ratio,
+            compactness: stats.compactness,
+            coverage: stats.covered_set.len(),
+            weighted_coverage: stats.weighted_coverage,
+            pattern_size: stats.tec.pattern.len(),
+            pattern_width: stats.pattern_width,
+            pattern_area: stats.pattern_area,
+        }
+    }
+}
+
+impl<T: Point> From<TecStats<T>> for ScoredTec<T> {
+    fn from(stats: TecStats<T>) -> Self {
+        let scores = TecScores::from(&stats);
+        ScoredTec {
+            tec: stats.tec,
+            scores,
+        }
+    }
+}
+
+/// Scores all of the given TECs in the point set. This can be used as an optional pass
+/// after any TEC algorithm, so that the scores can be written out with the TECs.
+/// The order of the TECs is retained.
+///
+/// # Arguments
+///
+/// * `tecs` - The TECs to score
+/// * `point_set` - The point set in which the TECs occur
+pub fn score_tecs<T: Point>(tecs: Vec<Tec<T>>, point_set: &PointSet<T>) -> Vec<ScoredTec<T>> {
+    tecs.into_iter()
+        .map(|tec| ScoredTec::new(tec, point_set))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::scored_tec::score_tecs;
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::{Point2DWeighted, Point2Df64};
+    use crate::point_set::set::PointSet;
+    use crate::point_set::tec::Tec;
+
+    #[test]
+    fn test_score_tecs() {
+        let a = Point2Df64 { x: 0.0, y: 0.0 };
+        let b = Point2Df64 { x: 1.0, y: 2.0 };
+        let c = Point2Df64 { x: 2.0, y: 0.0 };
+        let d = Point2Df64 { x: 3.0, y: 2.0 };
+        let point_set = PointSet::new(vec![a, b, c, d]);
+
+        let tec = Tec {
+            pattern: Pattern::new(&vec![&a, &b]),
+            translators: vec![c - a],
+        };
+
+        let scored = score_tecs(vec![tec.clone()], &point_set);
+        assert_eq!(1, scored.len());
+        assert_eq!(tec, scored[0].tec);
+
+        let scores = scored[0].scores;
+        assert_eq!(4, scores.coverage);
+        assert_eq!(4.0, scores.weighted_coverage);
+        assert_eq!(4.0 / 3.0, scores.compression_ratio);
+        assert_eq!(1.0, scores.compactness);
+        assert_eq!(1.0, scores.pattern_width);
+        assert_eq!(2.0, scores.pattern_area);
+    }
+
+    #[test]
+    fn test_weighted_coverage() {
+        let point = |x: f64, y: f64, weight: f64| Point2DWeighted { x, y, weight };
+        let a = point(0.0, 0.0, 2.0);
+        let b = point(1.0, 2.0, 1.0);
+        let c = point(2.0, 0.0, 0.5);
+        let d = point(3.0, 2.0, 1.5);
+        let point_set = PointSet::new(vec![a, b, c, d]);
+
+        let tec = Tec {
+            pattern: Pattern::new(&vec![&a, &b]),
+            translators: vec![c - a],
+        };
+
+        // The weights of the covered points are those in the point set.
+        let scores = score_tecs(vec![tec], &point_set)[0].scores;
+        assert_eq!(4, scores.coverage);
+        assert_eq!(5.0, scores.weighted_coverage);
+    }
+}
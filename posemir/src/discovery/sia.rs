@@ -2,16 +2,32 @@
  * (c) Otso Björklund (2021)
  * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
  */
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
 use crate::discovery::algorithm::MtpAlgorithm;
-use crate::discovery::utilities::sort;
-use crate::point_set::mtp::Mtp;
+use crate::point_set::mtp::{IndexedMtp, Mtp};
 use crate::point_set::point::Point;
 use crate::point_set::set::PointSet;
+use crate::utilities::sort;
 
 /// Implements the SIA algorithm [Meredith et al. 2002].
 /// The SIA algorithm computes all Maximal Translatable Patterns (MTP) in a
 /// given point set.
-pub struct Sia {}
+///
+/// The MTPs can be filtered by their size and number of occurrences while partitioning the
+/// difference vectors, so that the patterns of the filtered MTPs are never created. The default
+/// values of zero compute all MTPs.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Sia {
+    /// The minimum number of points in the output MTPs.
+    pub min_pattern_size: usize,
+    /// The minimum number of occurrences of the patterns of the output MTPs found by SIA,
+    /// i.e., the pattern itself and its translations by the translators for which it is the MTP.
+    /// This is a lower bound of the number of occurrences of the pattern in the point set.
+    /// Values above two require counting the translators of all patterns before output.
+    pub min_occurrences: usize,
+}
 
 impl<T: Point> MtpAlgorithm<T> for Sia {
     /// Computes and returns all MTPs in the given point set.
@@ -19,30 +35,60 @@ impl<T: Point> MtpAlgorithm<T> for Sia {
     /// # Arguments
     ///
     /// * `point_set` - The point set for which all MTPs are computed
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "sia", skip_all, fields(n = point_set.len())))]
     fn compute_mtps(&self, point_set: &PointSet<T>) -> Vec<Mtp<T>> {
         let forward_diffs = Sia::compute_differences(point_set);
 
         let mut mtps = Vec::new();
-        let on_output = |mtp: Mtp<T>| mtps.push(mtp);
-        Sia::partition(point_set, &forward_diffs, on_output);
+        let on_output = |indexed: IndexedMtp<T>| {
+            mtps.push(indexed.mtp);
+            ControlFlow::Continue(())
+        };
+        self.partition_filtered(point_set, &forward_diffs, on_output);
         mtps
     }
 
-    fn compute_mtps_to_output(&self, point_set: &PointSet<T>, on_output: impl FnMut(Mtp<T>)) {
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "sia", skip_all, fields(n = point_set.len())))]
+    fn compute_mtps_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(Mtp<T>) -> ControlFlow<()>,
+    ) {
+        let forward_diffs = Sia::compute_differences(point_set);
+        self.partition_filtered(point_set, &forward_diffs, |indexed| on_output(indexed.mtp));
+    }
+
+    /// Computes MTPs with the indices of their points as in `compute_mtps_to_output`.
+    /// The indices are retained from the partitioning of the difference vectors, so no searching
+    /// is required.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "sia", skip_all, fields(n = point_set.len())))]
+    fn compute_indexed_mtps_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        on_output: impl FnMut(IndexedMtp<T>) -> ControlFlow<()>,
+    ) {
         let forward_diffs = Sia::compute_differences(point_set);
-        Sia::partition(point_set, &forward_diffs, on_output);
+        self.partition_filtered(point_set, &forward_diffs, on_output);
     }
 }
 
 impl Sia {
+    /// Creates SIA that outputs all of the MTPs it finds.
+    pub const fn new() -> Sia {
+        Sia {
+            min_pattern_size: 0,
+            min_occurrences: 0,
+        }
+    }
+
     /// Computes the forward differences with the indices required
     /// for MTP computation.
     /// The forward differences are sorted in ascending lexicographical order.
     fn compute_differences<T: Point>(point_set: &PointSet<T>) -> Vec<(T, usize)> {
         let n = point_set.len();
-        let mut diffs: Vec<(T, usize)> = Vec::with_capacity(n * (n - 1) / 2);
+        let mut diffs: Vec<(T, usize)> = Vec::with_capacity(n * n.saturating_sub(1) / 2);
 
-        for i in 0..n - 1 {
+        for i in 0..n.saturating_sub(1) {
             let from = &point_set[i];
             for j in i + 1..n {
                 let to = &point_set[j];
@@ -55,42 +101,103 @@ impl Sia {
     }
 
     /// Partitions the sorted list of difference-index pairs into MTPs.
-    fn partition<T: Point>(
+    pub(crate) fn partition<T: Point>(
         point_set: &PointSet<T>,
-        forward_diffs: &Vec<(T, usize)>,
-        mut on_output: impl FnMut(Mtp<T>),
+        forward_diffs: &[(T, usize)],
+        mut on_output: impl FnMut(IndexedMtp<T>) -> ControlFlow<()>,
+    ) {
+        Sia::partition_indices(forward_diffs, 0, |translator, indices| {
+            on_output(Sia::indexed_mtp(point_set, translator, indices))
+        });
+    }
+
+    /// Partitions the sorted list of difference-index pairs into MTPs, and outputs the MTPs
+    /// that pass the size and occurrence filters.
+    fn partition_filtered<T: Point>(
+        &self,
+        point_set: &PointSet<T>,
+        forward_diffs: &[(T, usize)],
+        mut on_output: impl FnMut(IndexedMtp<T>) -> ControlFlow<()>,
+    ) {
+        let min_size = self.min_pattern_size;
+        // Every MTP has at least two occurrences, so the translators only need to be
+        // counted for greater minimums.
+        if self.min_occurrences <= 2 {
+            Sia::partition_indices(forward_diffs, min_size, |translator, indices| {
+                on_output(Sia::indexed_mtp(point_set, translator, indices))
+            });
+            return;
+        }
+
+        let mut translator_counts: HashMap<Vec<usize>, usize> = HashMap::new();
+        Sia::partition_indices(forward_diffs, min_size, |_, indices| {
+            *translator_counts.entry(indices).or_default() += 1;
+            ControlFlow::Continue(())
+        });
+
+        Sia::partition_indices(forward_diffs, min_size, |translator, indices| {
+            if translator_counts[&indices] + 1 < self.min_occurrences {
+                return ControlFlow::Continue(());
+            }
+            on_output(Sia::indexed_mtp(point_set, translator, indices))
+        });
+    }
+
+    /// Partitions the sorted list of difference-index pairs into the translators and the
+    /// indices of the points of their MTPs, and executes on_indices for the MTPs with at
+    /// least min_size points.
+    fn partition_indices<T: Point>(
+        forward_diffs: &[(T, usize)],
+        min_size: usize,
+        mut on_indices: impl FnMut(T, Vec<usize>) -> ControlFlow<()>,
     ) {
         let m = forward_diffs.len();
         let mut i = 0;
         while i < m {
-            let mut indices: Vec<usize> = Vec::new();
-            let translator = &forward_diffs[i].0;
+            let translator = forward_diffs[i].0;
 
             let mut j = i;
-            while j < m && *translator == forward_diffs[j].0 {
-                indices.push(forward_diffs[j].1);
+            while j < m && translator == forward_diffs[j].0 {
                 j += 1;
             }
 
+            if j - i >= min_size {
+                let indices: Vec<usize> = forward_diffs[i..j].iter().map(|diff| diff.1).collect();
+                if on_indices(translator, indices).is_break() {
+                    return;
+                }
+            }
             i = j;
-            on_output(Mtp {
-                translator: *translator,
+        }
+    }
+
+    fn indexed_mtp<T: Point>(
+        point_set: &PointSet<T>,
+        translator: T,
+        indices: Vec<usize>,
+    ) -> IndexedMtp<T> {
+        IndexedMtp {
+            mtp: Mtp {
+                translator,
                 pattern: point_set.get_pattern(&indices),
-            });
+            },
+            indices,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::ops::ControlFlow;
+
     use crate::discovery::algorithm::MtpAlgorithm;
+    use crate::discovery::sia::Sia;
     use crate::point_set::mtp::Mtp;
     use crate::point_set::pattern::Pattern;
     use crate::point_set::point::Point2Df64;
     use crate::point_set::set::PointSet;
-    use crate::discovery::sia::Sia;
 
-    const ALGORITHM: Sia = Sia {};
+    const ALGORITHM: Sia = Sia::new();
 
     #[test]
     fn test_minimal_number_of_mtps() {
@@ -107,7 +214,7 @@ mod tests {
 
         let point_set = PointSet::new(points);
         let mut mtps = ALGORITHM.compute_mtps(&point_set);
-        mtps.sort_by(|a, b| a.translator.cmp(&b.translator));
+        mtps.sort_by_key(|mtp| mtp.translator);
 
         assert_eq!(3, mtps.len());
         assert_eq!(
@@ -146,7 +253,7 @@ mod tests {
 
         let point_set = PointSet::new(points);
         let mut mtps = ALGORITHM.compute_mtps(&point_set);
-        mtps.sort_by(|a, b| a.translator.cmp(&b.translator));
+        mtps.sort_by_key(|mtp| mtp.translator);
 
         assert_eq!(3, mtps.len());
         assert_eq!(
@@ -171,4 +278,82 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_indexed_mtps() {
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 1.0, y: 1.0 },
+            Point2Df64 { x: 2.0, y: 1.0 },
+            Point2Df64 { x: 2.0, y: 3.0 },
+            Point2Df64 { x: 3.0, y: 3.0 },
+        ]);
+
+        let indexed_mtps = ALGORITHM.compute_indexed_mtps(&point_set);
+        assert_eq!(ALGORITHM.compute_mtps(&point_set).len(), indexed_mtps.len());
+        for indexed in &indexed_mtps {
+            assert_eq!(indexed.mtp.pattern, point_set.get_pattern(&indexed.indices));
+        }
+    }
+
+    #[test]
+    fn test_filters() {
+        // A motif with three occurrences, so that the motif is the MTP of two translators.
+        let motif = [(0.0, 0.0), (1.0, 2.0), (2.0, 1.0)];
+        let mut points = Vec::new();
+        for (dx, dy) in [(0.0, 0.0), (10.0, 5.0), (25.0, -3.0)] {
+            for (x, y) in motif {
+                points.push(Point2Df64 {
+                    x: x + dx,
+                    y: y + dy,
+                });
+            }
+        }
+        let point_set = PointSet::new(points);
+        let mtps = ALGORITHM.compute_mtps(&point_set);
+
+        let min_size = Sia {
+            min_pattern_size: 3,
+            ..Sia::new()
+        };
+        let expected: Vec<Mtp<Point2Df64>> = ALGORITHM
+            .compute_mtps(&point_set)
+            .into_iter()
+            .filter(|mtp| mtp.pattern.len() >= 3)
+            .collect();
+        assert!(!expected.is_empty() && expected.len() < mtps.len());
+        assert_eq!(expected, min_size.compute_mtps(&point_set));
+
+        let min_occurrences = Sia {
+            min_pattern_size: 2,
+            min_occurrences: 3,
+        };
+        let expected: Vec<Mtp<Point2Df64>> = ALGORITHM
+            .compute_mtps(&point_set)
+            .into_iter()
+            .filter(|mtp| {
+                let translators = mtps.iter().filter(|m| m.pattern == mtp.pattern).count();
+                mtp.pattern.len() >= 2 && translators >= 2
+            })
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(expected, min_occurrences.compute_mtps(&point_set));
+    }
+
+    #[test]
+    fn test_terminate_early() {
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 1.0, y: 1.0 },
+            Point2Df64 { x: 2.0, y: 1.0 },
+            Point2Df64 { x: 2.0, y: 3.0 },
+            Point2Df64 { x: 3.0, y: 3.0 },
+        ]);
+
+        let mut mtps = Vec::new();
+        ALGORITHM.compute_mtps_to_output(&point_set, |mtp| {
+            mtps.push(mtp);
+            ControlFlow::Break(())
+        });
+
+        assert_eq!(vec![ALGORITHM.compute_mtps(&point_set).remove(0)], mtps);
+    }
 }
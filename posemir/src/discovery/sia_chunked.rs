@@ -0,0 +1,193 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::mem::size_of;
+use std::ops::{Bound, ControlFlow, RangeBounds};
+
+use crate::discovery::algorithm::MtpAlgorithm;
+use crate::discovery::sia::Sia;
+use crate::point_set::mtp::{IndexedMtp, Mtp};
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::utilities::sort;
+
+/// Implements SIA with a memory budget for the forward difference vectors.
+///
+/// SIA keeps all n(n - 1)/2 forward differences in memory, which becomes infeasible for large
+/// point sets (e.g., a full orchestral movement). This implementation processes the differences
+/// in chunks of consecutive translators: each pass over the point set collects only the smallest
+/// translators that have not yet been processed and fit in the budget, and partitions them into
+/// MTPs. The output is the same as that of `Sia` and in the same order, at the cost of computing
+/// the differences once per pass.
+///
+/// All differences of a single translator are always processed in the same pass, so an MTP
+/// that is larger than the budget is kept in memory regardless of the budget.
+pub struct SiaChunked {
+    /// The maximum number of bytes used for the forward differences.
+    pub memory_budget: usize,
+}
+
+impl<T: Point> MtpAlgorithm<T> for SiaChunked {
+    fn compute_mtps(&self, point_set: &PointSet<T>) -> Vec<Mtp<T>> {
+        let mut mtps = Vec::new();
+        self.compute_mtps_to_output(point_set, |mtp| {
+            mtps.push(mtp);
+            ControlFlow::Continue(())
+        });
+        mtps
+    }
+
+    fn compute_mtps_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(Mtp<T>) -> ControlFlow<()>,
+    ) {
+        self.compute_indexed_mtps_to_output(point_set, |indexed| on_output(indexed.mtp));
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "sia_chunked", skip_all, fields(n = point_set.len(), memory_budget = self.memory_budget)))]
+    fn compute_indexed_mtps_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(IndexedMtp<T>) -> ControlFlow<()>,
+    ) {
+        let capacity = self.chunk_capacity::<T>();
+        let mut lower: Bound<T> = Bound::Unbounded;
+        let mut stopped = false;
+
+        while !stopped {
+            let (mut chunk, upper) = SiaChunked::collect_chunk(point_set, lower, capacity);
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(differences = chunk.len(), "collected chunk");
+
+            sort(&mut chunk);
+            Sia::partition(point_set, &chunk, |indexed| {
+                let flow = on_output(indexed);
+                stopped = flow.is_break();
+                flow
+            });
+
+            // The next chunk starts from the translators after the ones in this chunk.
+            lower = match upper {
+                Bound::Included(translator) => Bound::Excluded(translator),
+                Bound::Excluded(translator) => Bound::Included(translator),
+                Bound::Unbounded => break,
+            };
+        }
+    }
+}
+
+impl SiaChunked {
+    /// Returns the number of differences that are collected before the collected
+    /// differences are reduced to the smallest translators. The collected differences
+    /// are reduced to half of this, so that they always fit in the budget.
+    fn chunk_capacity<T: Point>(&self) -> usize {
+        (self.memory_budget / size_of::<(T, usize)>()).max(2)
+    }
+
+    /// Collects the forward differences (with the indices of their origins) whose translators
+    /// are above the lower bound, and at most half of the capacity of the smallest ones among
+    /// them unless a single translator has more differences. Returns the collected differences
+    /// and the upper bound of their translators.
+    fn collect_chunk<T: Point>(
+        point_set: &PointSet<T>,
+        lower: Bound<T>,
+        capacity: usize,
+    ) -> (Vec<(T, usize)>, Bound<T>) {
+        let n = point_set.len();
+        let kept = capacity / 2;
+        let mut upper: Bound<T> = Bound::Unbounded;
+        let mut limit = capacity;
+        let mut chunk: Vec<(T, usize)> =
+            Vec::with_capacity(capacity.min(n * n.saturating_sub(1) / 2));
+
+        for i in 0..n {
+            let from = &point_set[i];
+            for j in i + 1..n {
+                let diff = point_set[j] - *from;
+                if !(lower, upper).contains(&diff) {
+                    continue;
+                }
+
+                chunk.push((diff, i));
+                if chunk.len() >= limit {
+                    upper = SiaChunked::reduce(&mut chunk, kept);
+                    limit = chunk.len() + kept;
+                }
+            }
+        }
+
+        (chunk, upper)
+    }
+
+    /// Reduces the differences to the ones with the smallest translators, so that at most
+    /// `kept` differences remain unless the smallest translator has more differences than that.
+    /// Returns the upper bound of the remaining translators.
+    fn reduce<T: Point>(chunk: &mut Vec<(T, usize)>, kept: usize) -> Bound<T> {
+        chunk.select_nth_unstable_by_key(kept, |diff| diff.0);
+        let cutoff = chunk[kept].0;
+
+        let upper = if chunk[..kept].iter().any(|diff| diff.0 < cutoff) {
+            Bound::Excluded(cutoff)
+        } else {
+            Bound::Included(cutoff)
+        };
+        chunk.retain(|diff| (Bound::Unbounded, upper).contains(&diff.0));
+        upper
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+
+    use crate::discovery::algorithm::MtpAlgorithm;
+    use crate::discovery::sia::Sia;
+    use crate::discovery::sia_chunked::SiaChunked;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+
+    fn test_point_set() -> PointSet<Point2Df64> {
+        let mut points = Vec::new();
+        for i in 0..20 {
+            points.push(Point2Df64 {
+                x: (i / 2) as f64,
+                y: ((i * 7) % 5) as f64,
+            });
+        }
+        PointSet::new(points)
+    }
+
+    #[test]
+    fn test_same_output_as_sia() {
+        let point_set = test_point_set();
+        let expected = Sia::new().compute_indexed_mtps(&point_set);
+
+        for differences in [1, 2, 5, 16, 1000] {
+            let algorithm = SiaChunked {
+                memory_budget: differences * std::mem::size_of::<(Point2Df64, usize)>(),
+            };
+            assert_eq!(expected, algorithm.compute_indexed_mtps(&point_set));
+        }
+    }
+
+    #[test]
+    fn test_terminate_early() {
+        let point_set = test_point_set();
+        let algorithm = SiaChunked { memory_budget: 0 };
+
+        let mut mtps = Vec::new();
+        algorithm.compute_mtps_to_output(&point_set, |mtp| {
+            mtps.push(mtp);
+            if mtps.len() == 3 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+
+        assert_eq!(Sia::new().compute_mtps(&point_set)[..3], mtps);
+    }
+}
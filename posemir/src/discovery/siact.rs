@@ -0,0 +1,186 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+
+use crate::discovery::algorithm::{MtpAlgorithm, TecAlgorithm};
+use crate::discovery::mtp_to_tec::MtpToTec;
+use crate::discovery::sia::Sia;
+use crate::point_set::mtp::IndexedMtp;
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// Implements the SIACT algorithm (SIA with compactness trawling) for finding compact
+/// patterns and their TECs in a point set representation of music (see [Collins et al. 2010]).
+/// The MTPs computed by SIA are trawled for compact subpatterns, which makes the algorithm
+/// better suited for noisy polyphonic data where the MTPs are often too sparse.
+///
+/// The compactness of a subpattern is the ratio of the number of points in the subpattern to
+/// the number of points in the lexicographically ordered segment of the point set spanned
+/// by the subpattern.
+pub struct Siact {
+    /// The minimum compactness of the trawled subpatterns, in the range (0, 1].
+    pub compactness_threshold: f64,
+    /// The minimum number of points in the trawled subpatterns.
+    pub min_pattern_size: usize,
+}
+
+impl<T: Point> TecAlgorithm<T> for Siact {
+    fn compute_tecs(&self, point_set: &PointSet<T>) -> Vec<Tec<T>> {
+        let mut tecs = Vec::new();
+        let on_output = |tec: Tec<T>| {
+            tecs.push(tec);
+            ControlFlow::Continue(())
+        };
+        self.compute_tecs_to_output(point_set, on_output);
+        tecs
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "siact", skip_all, fields(n = point_set.len())))]
+    fn compute_tecs_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        let mut seen_patterns: HashSet<Pattern<T>> = HashSet::new();
+
+        let on_mtp = |mtp: IndexedMtp<T>| {
+            for pattern in self.trawl(&mtp) {
+                if !seen_patterns.insert(pattern.vectorize()) {
+                    continue;
+                }
+
+                let translators = MtpToTec::<T, Sia>::find_translators(&pattern, point_set);
+                on_output(Tec {
+                    pattern,
+                    translators,
+                })?;
+            }
+
+            ControlFlow::Continue(())
+        };
+
+        Sia::new().compute_indexed_mtps_to_output(point_set, on_mtp);
+    }
+}
+
+impl Siact {
+    /// Returns the compact subpatterns of the MTP. The points of the MTP are scanned in
+    /// lexicographic order, and a subpattern is extended with the next point as long as its
+    /// compactness stays at or above the threshold. When the compactness would drop below the
+    /// threshold, the subpattern is output if it is large enough and a new subpattern is
+    /// started from the next point.
+    ///
+    /// # Arguments
+    ///
+    /// * `mtp` - The MTP with the indices of its points in the point set
+    fn trawl<T: Point>(&self, mtp: &IndexedMtp<T>) -> Vec<Pattern<T>> {
+        let indices = &mtp.indices;
+        let size = indices.len();
+        let mut patterns = Vec::new();
+
+        let mut start = 0;
+        for end in 1..=size {
+            if end < size {
+                let point_count = end - start + 1;
+                let segment_size = indices[end] - indices[start] + 1;
+                if point_count as f64 / segment_size as f64 >= self.compactness_threshold {
+                    continue;
+                }
+            }
+
+            if end - start >= self.min_pattern_size {
+                let points: Vec<&T> = (start..end).map(|i| &mtp.mtp.pattern[i]).collect();
+                patterns.push(Pattern::new(&points));
+            }
+            start = end;
+        }
+
+        patterns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::algorithm::TecAlgorithm;
+    use crate::discovery::siact::Siact;
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+
+    #[test]
+    fn test_trawls_compact_subpatterns() {
+        // A three-note motif repeated with a distant point that translates along with it,
+        // and unrelated points between the motif and the distant point in the first occurrence.
+        let mut points = Vec::new();
+        for onset in [0.0, 20.0] {
+            points.push(Point2Df64 { x: onset, y: 60.0 });
+            points.push(Point2Df64 {
+                x: onset + 1.0,
+                y: 62.0,
+            });
+            points.push(Point2Df64 {
+                x: onset + 2.0,
+                y: 64.0,
+            });
+            points.push(Point2Df64 {
+                x: onset + 10.0,
+                y: 70.0,
+            });
+        }
+        for i in 3..10 {
+            points.push(Point2Df64 {
+                x: i as f64,
+                y: 30.0 + i as f64,
+            });
+        }
+        let point_set = PointSet::new(points);
+
+        let siact = Siact {
+            compactness_threshold: 0.6,
+            min_pattern_size: 3,
+        };
+        let tecs = siact.compute_tecs(&point_set);
+
+        let motif = Pattern::new(&vec![
+            &Point2Df64 { x: 0.0, y: 60.0 },
+            &Point2Df64 { x: 1.0, y: 62.0 },
+            &Point2Df64 { x: 2.0, y: 64.0 },
+        ]);
+        let motif_tec = tecs.iter().find(|tec| tec.pattern == motif).unwrap();
+        assert_eq!(vec![Point2Df64 { x: 20.0, y: 0.0 }], motif_tec.translators);
+
+        // The sparse MTP with the distant point is split, and the remainder is too small.
+        assert!(tecs
+            .iter()
+            .all(|tec| tec.pattern.len() >= siact.min_pattern_size));
+        assert!(tecs
+            .iter()
+            .all(|tec| tec.pattern.len() < 4 || tec.pattern[0].y != 60.0));
+    }
+
+    #[test]
+    fn test_full_compactness_threshold_with_dense_mtps() {
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 0.0, y: 0.0 },
+            Point2Df64 { x: 1.0, y: 0.0 },
+            Point2Df64 { x: 2.0, y: 0.0 },
+            Point2Df64 { x: 3.0, y: 0.0 },
+        ]);
+
+        let tecs = Siact {
+            compactness_threshold: 1.0,
+            min_pattern_size: 1,
+        }
+        .compute_tecs(&point_set);
+
+        // All MTPs of evenly spaced points are contiguous, so nothing is split.
+        let mut patterns: Vec<usize> = tecs.iter().map(|tec| tec.pattern.len()).collect();
+        patterns.sort();
+        assert_eq!(vec![1, 2, 3], patterns);
+    }
+}
@@ -2,14 +2,16 @@
  * (c) Otso Björklund (2021)
  * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
  */
-use std::cmp::min;
+use std::cmp::{min, Reverse};
+use std::collections::HashMap;
+use std::ops::ControlFlow;
 
 use crate::discovery::algorithm::MtpAlgorithm;
-use crate::discovery::utilities::sort;
 use crate::point_set::mtp::Mtp;
 use crate::point_set::pattern::Pattern;
 use crate::point_set::point::Point;
 use crate::point_set::set::PointSet;
+use crate::utilities::sort;
 
 /// Implements the SIAR algorithm (SIA for R subdiagonals) for finding a restricted set of MTPs from
 /// a point set representation of music (see [Collins 2011]). The implementation
@@ -18,6 +20,25 @@ pub struct SiaR {
     /// The r parameter of algorithm. This defines the number of subdiagonals
     /// computed by the algorithm, i.e., the size of the sliding window.
     pub r: usize,
+    /// The minimum number of points in the output MTPs, see `Sia::min_pattern_size`.
+    pub min_pattern_size: usize,
+    /// The minimum number of occurrences of the patterns of the output MTPs found by SIAR,
+    /// see `Sia::min_occurrences`. Values above two require keeping the MTPs in memory
+    /// until all of them have been computed.
+    pub min_occurrences: usize,
+}
+
+/// The intermediate results of SIAR together with the final MTPs, see `SiaR::compute_with_stats`.
+#[derive(Debug)]
+pub struct SiaRStats<T: Point> {
+    /// The MTPs of the difference vectors on the r subdiagonals, in ascending
+    /// order of the translators.
+    pub sub_diagonal_mtps: Vec<Mtp<T>>,
+    /// The intra-pattern difference vectors of the subdiagonal MTPs with their
+    /// frequencies, in descending order of frequency.
+    pub intra_diff_frequencies: Vec<(T, u64)>,
+    /// The MTPs of the intra-pattern difference vectors, in the order of the frequencies.
+    pub mtps: Vec<Mtp<T>>,
 }
 
 impl<T: Point> MtpAlgorithm<T> for SiaR {
@@ -27,35 +48,85 @@ impl<T: Point> MtpAlgorithm<T> for SiaR {
     ///
     /// * `point_set` - The point set for which restricted MTPs are computed
     /// * `window` - the size of the window used for restricting the scope of difference vectors
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "siar", skip_all, fields(n = point_set.len(), r = self.r)))]
     fn compute_mtps(&self, point_set: &PointSet<T>) -> Vec<Mtp<T>> {
-        let forward_diffs = self.compute_differences(point_set);
+        let (_, intra_diff_frequencies) = self.compute_intra_diff_frequencies(point_set);
 
-        let mtp_patterns = SiaR::partition(point_set, &forward_diffs);
+        let mut mtps = Vec::new();
+        let on_output = |mtp: Mtp<T>| {
+            mtps.push(mtp);
+            ControlFlow::Continue(())
+        };
+        self.compute_filtered_mtps(point_set, &intra_diff_frequencies, on_output);
+        mtps
+    }
 
-        let intra_pattern_diffs = SiaR::compute_intra_pattern_diffs(&mtp_patterns);
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "siar", skip_all, fields(n = point_set.len(), r = self.r)))]
+    fn compute_mtps_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        on_output: impl FnMut(Mtp<T>) -> ControlFlow<()>,
+    ) {
+        let (_, intra_diff_frequencies) = self.compute_intra_diff_frequencies(point_set);
+        self.compute_filtered_mtps(point_set, &intra_diff_frequencies, on_output);
+    }
+}
 
-        let intra_diff_frequencies = SiaR::compute_diff_frequencies(&intra_pattern_diffs);
+impl SiaR {
+    /// Creates SIAR for the given number of subdiagonals, which outputs all of
+    /// the MTPs it finds.
+    ///
+    /// # Arguments
+    ///
+    /// * `r` - The number of subdiagonals, see `SiaR::r`
+    pub fn new(r: usize) -> SiaR {
+        SiaR {
+            r,
+            min_pattern_size: 0,
+            min_occurrences: 0,
+        }
+    }
+
+    /// Computes the MTPs as `compute_mtps`, and returns them together with the intermediate
+    /// results of the algorithm: the MTPs on the subdiagonals and the frequencies of their
+    /// intra-pattern difference vectors. These can be used, e.g., for tuning the r parameter.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set for which restricted MTPs are computed
+    pub fn compute_with_stats<T: Point>(&self, point_set: &PointSet<T>) -> SiaRStats<T> {
+        let (sub_diagonal_mtps, intra_diff_frequencies) =
+            self.compute_intra_diff_frequencies(point_set);
 
         let mut mtps = Vec::new();
-        let on_output = |mtp: Mtp<T>| mtps.push(mtp);
-        SiaR::compute_mtps(point_set, &intra_diff_frequencies, on_output);
-        mtps
+        self.compute_filtered_mtps(point_set, &intra_diff_frequencies, |mtp| {
+            mtps.push(mtp);
+            ControlFlow::Continue(())
+        });
+
+        SiaRStats {
+            sub_diagonal_mtps,
+            intra_diff_frequencies,
+            mtps,
+        }
     }
 
-    fn compute_mtps_to_output(&self, point_set: &PointSet<T>, on_output: impl FnMut(Mtp<T>)) {
+    /// Computes the subdiagonal MTPs and the frequencies of their intra-pattern differences.
+    fn compute_intra_diff_frequencies<T: Point>(
+        &self,
+        point_set: &PointSet<T>,
+    ) -> (Vec<Mtp<T>>, Vec<(T, u64)>) {
         let forward_diffs = self.compute_differences(point_set);
 
-        let mtp_patterns = SiaR::partition(point_set, &forward_diffs);
+        let sub_diagonal_mtps = SiaR::partition(point_set, &forward_diffs);
 
-        let intra_pattern_diffs = SiaR::compute_intra_pattern_diffs(&mtp_patterns);
+        let intra_pattern_diffs = SiaR::compute_intra_pattern_diffs(&sub_diagonal_mtps);
 
         let intra_diff_frequencies = SiaR::compute_diff_frequencies(&intra_pattern_diffs);
 
-        SiaR::compute_mtps(point_set, &intra_diff_frequencies, on_output);
+        (sub_diagonal_mtps, intra_diff_frequencies)
     }
-}
 
-impl SiaR {
     /// Computes the forward differences with the indices required
     /// for MTP computation.
     /// The forward differences are sorted in ascending lexicographical order.
@@ -66,7 +137,7 @@ impl SiaR {
         // Add one to window index for convenience in indexing
         let window = self.r + 1;
 
-        for i in 0..n - 1 {
+        for i in 0..n.saturating_sub(1) {
             let from = &point_set[i];
             for j in i + 1..min(n, i + window) {
                 let to = &point_set[j];
@@ -78,13 +149,10 @@ impl SiaR {
         diffs
     }
 
-    /// Partitions the sorted list of difference-index pairs into a MTP patterns. The MTP
-    /// difference vectors are not needed in SIAR at this stage.
-    fn partition<T: Point>(
-        point_set: &PointSet<T>,
-        forward_diffs: &Vec<(T, usize)>,
-    ) -> Vec<Pattern<T>> {
-        let mut mtp_patterns: Vec<Pattern<T>> = Vec::new();
+    /// Partitions the sorted list of difference-index pairs into MTPs. Only the patterns
+    /// of the MTPs are needed by SIAR, but the translators are kept for `compute_with_stats`.
+    fn partition<T: Point>(point_set: &PointSet<T>, forward_diffs: &[(T, usize)]) -> Vec<Mtp<T>> {
+        let mut mtps: Vec<Mtp<T>> = Vec::new();
         let m = forward_diffs.len();
         let mut i = 0;
         while i < m {
@@ -98,16 +166,20 @@ impl SiaR {
             }
 
             i = j;
-            mtp_patterns.push(point_set.get_pattern(&indices));
+            mtps.push(Mtp {
+                translator: *translator,
+                pattern: point_set.get_pattern(&indices),
+            });
         }
-        mtp_patterns
+        mtps
     }
 
     /// Computes the intrapattern diffence vectors (forward differences between points belonging to
     /// same pattern) and sorts them in ascending order.
-    fn compute_intra_pattern_diffs<T: Point>(mtp_patterns: &Vec<Pattern<T>>) -> Vec<T> {
+    fn compute_intra_pattern_diffs<T: Point>(mtps: &[Mtp<T>]) -> Vec<T> {
         let mut intra_diffs: Vec<T> = Vec::new();
-        for pattern in mtp_patterns {
+        for mtp in mtps {
+            let pattern = &mtp.pattern;
             let p = pattern.len();
 
             for i in 0..p - 1 {
@@ -119,6 +191,14 @@ impl SiaR {
         }
 
         intra_diffs.sort();
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            patterns = mtps.len(),
+            intra_diffs = intra_diffs.len(),
+            "computed intra-pattern differences"
+        );
+
         intra_diffs
     }
 
@@ -152,23 +232,64 @@ impl SiaR {
         intra_diff_freqs.push((*current, freq));
 
         // Sort by descending frequency
-        intra_diff_freqs.sort_by(|a, b| b.1.cmp(&a.1));
+        intra_diff_freqs.sort_by_key(|diff_freq| Reverse(diff_freq.1));
         intra_diff_freqs
     }
 
+    /// Computes the MTPs for the intra pattern differences as `compute_mtps`, and outputs
+    /// the MTPs that pass the size and occurrence filters.
+    fn compute_filtered_mtps<T: Point>(
+        &self,
+        point_set: &PointSet<T>,
+        intra_diff_freqs: &Vec<(T, u64)>,
+        mut on_output: impl FnMut(Mtp<T>) -> ControlFlow<()>,
+    ) {
+        let min_size = self.min_pattern_size;
+        if self.min_occurrences <= 2 {
+            SiaR::compute_mtps(point_set, intra_diff_freqs, |mtp| {
+                if mtp.pattern.len() < min_size {
+                    return ControlFlow::Continue(());
+                }
+                on_output(mtp)
+            });
+            return;
+        }
+
+        let mut mtps: Vec<Mtp<T>> = Vec::new();
+        let mut translator_counts: HashMap<Pattern<T>, usize> = HashMap::new();
+        SiaR::compute_mtps(point_set, intra_diff_freqs, |mtp| {
+            if mtp.pattern.len() >= min_size {
+                *translator_counts.entry(mtp.pattern.clone()).or_default() += 1;
+                mtps.push(mtp);
+            }
+            ControlFlow::Continue(())
+        });
+
+        for mtp in mtps {
+            if translator_counts[&mtp.pattern] + 1 >= self.min_occurrences
+                && on_output(mtp).is_break()
+            {
+                return;
+            }
+        }
+    }
+
     /// Computes the MTPs for the intra pattern differences in descending order of size.
     fn compute_mtps<T: Point>(
         point_set: &PointSet<T>,
         intra_diff_freqs: &Vec<(T, u64)>,
-        mut on_output: impl FnMut(Mtp<T>),
+        mut on_output: impl FnMut(Mtp<T>) -> ControlFlow<()>,
     ) {
         for diff in intra_diff_freqs {
             let translator = diff.0;
             let intersection = point_set.intersect(&point_set.translate(&(translator * -1.0)));
-            on_output(Mtp {
+            let flow = on_output(Mtp {
                 translator,
                 pattern: intersection.into(),
-            })
+            });
+            if flow.is_break() {
+                return;
+            }
         }
     }
 }
@@ -176,11 +297,11 @@ impl SiaR {
 #[cfg(test)]
 mod tests {
     use crate::discovery::algorithm::MtpAlgorithm;
+    use crate::discovery::siar::SiaR;
     use crate::point_set::mtp::Mtp;
     use crate::point_set::pattern::Pattern;
     use crate::point_set::point::Point2Di64;
     use crate::point_set::set::PointSet;
-    use crate::discovery::siar::SiaR;
 
     #[test]
     fn test_minimal_number_of_mtps() {
@@ -196,9 +317,9 @@ mod tests {
         points.push(d);
 
         let point_set = PointSet::new(points);
-        let siar = SiaR { r: 3 };
+        let siar = SiaR::new(3);
         let mut mtps = siar.compute_mtps(&point_set);
-        mtps.sort_by(|a, b| a.translator.cmp(&b.translator));
+        mtps.sort_by_key(|mtp| mtp.translator);
 
         assert_eq!(2, mtps.len());
         assert_eq!(
@@ -231,9 +352,9 @@ mod tests {
         points.push(d);
 
         let point_set = PointSet::new(points);
-        let siar = SiaR { r: 1 };
+        let siar = SiaR::new(1);
         let mut mtps = siar.compute_mtps(&point_set);
-        mtps.sort_by(|a, b| a.translator.cmp(&b.translator));
+        mtps.sort_by_key(|mtp| mtp.translator);
 
         assert_eq!(2, mtps.len());
         assert_eq!(
@@ -251,4 +372,79 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_indexed_mtps() {
+        let point_set = PointSet::new(vec![
+            Point2Di64 { x: 1, y: 1 },
+            Point2Di64 { x: 2, y: 1 },
+            Point2Di64 { x: 3, y: 1 },
+            Point2Di64 { x: 4, y: 2 },
+        ]);
+
+        let siar = SiaR::new(1);
+        let indexed_mtps = siar.compute_indexed_mtps(&point_set);
+        assert_eq!(siar.compute_mtps(&point_set).len(), indexed_mtps.len());
+        for indexed in &indexed_mtps {
+            assert_eq!(indexed.mtp.pattern, point_set.get_pattern(&indexed.indices));
+        }
+    }
+
+    #[test]
+    fn test_compute_with_stats() {
+        let a = Point2Di64 { x: 1, y: 1 };
+        let b = Point2Di64 { x: 2, y: 1 };
+        let c = Point2Di64 { x: 3, y: 1 };
+        let point_set = PointSet::new(vec![a, b, c]);
+
+        let siar = SiaR::new(1);
+        let stats = siar.compute_with_stats(&point_set);
+
+        assert_eq!(
+            vec![Mtp {
+                translator: Point2Di64 { x: 1, y: 0 },
+                pattern: Pattern::new(&vec![&a, &b])
+            }],
+            stats.sub_diagonal_mtps
+        );
+        assert_eq!(
+            vec![(Point2Di64 { x: 1, y: 0 }, 1)],
+            stats.intra_diff_frequencies
+        );
+        assert_eq!(siar.compute_mtps(&point_set), stats.mtps);
+    }
+
+    #[test]
+    fn test_filters() {
+        // A motif with three occurrences, so that the motif is the MTP of two translators.
+        let motif = [(0, 0), (1, 2), (2, 1)];
+        let mut points = Vec::new();
+        for (dx, dy) in [(0, 0), (10, 5), (25, -3)] {
+            for (x, y) in motif {
+                points.push(Point2Di64 {
+                    x: x + dx,
+                    y: y + dy,
+                });
+            }
+        }
+        let point_set = PointSet::new(points);
+        let unfiltered = SiaR::new(3);
+        let mtps = unfiltered.compute_mtps(&point_set);
+
+        let filtered = SiaR {
+            r: 3,
+            min_pattern_size: 2,
+            min_occurrences: 3,
+        };
+        let expected: Vec<Mtp<Point2Di64>> = unfiltered
+            .compute_mtps(&point_set)
+            .into_iter()
+            .filter(|mtp| {
+                let translators = mtps.iter().filter(|m| m.pattern == mtp.pattern).count();
+                mtp.pattern.len() >= 2 && translators >= 2
+            })
+            .collect();
+        assert!(!expected.is_empty() && expected.len() < mtps.len());
+        assert_eq!(expected, filtered.compute_mtps(&point_set));
+    }
 }
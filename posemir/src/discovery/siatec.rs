@@ -3,45 +3,104 @@
  * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
  */
 use std::cmp::Ordering;
+use std::ops::ControlFlow;
 
 use crate::discovery::algorithm::TecAlgorithm;
-use crate::point_set::pattern::Pattern;
+use crate::discovery::progress::{IgnoreProgress, ProgressObserver, ProgressTracker, ProgressUnit};
+use crate::point_set::pattern_ref::PatternRef;
 use crate::point_set::point::Point;
 use crate::point_set::set::PointSet;
 use crate::point_set::tec::Tec;
-use crate::discovery::utilities::sort;
+#[cfg(not(feature = "parallel"))]
+use crate::utilities::sort;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 /// Implements the SIATEC algorithm for computing all translational equivalence classes (TECs) of
 /// maximal translatable patterns (MTPs) in a point set (see [Meredith et al 2002]). The implementation
 /// is based on the pseudocode in Figure 13.7 of [Meredith 2016] and on the description in [Meredith et al 2002]
 /// that avoids computing TECs for duplicate MTPs. This implementation does not produces duplicate TECs
 /// as avoiding computing translators for duplicates is considerably faster.
+/// The MTPs are kept as references to the point set (see `PatternRef`), so their points
+/// are copied only for the output TECs.
 pub struct Siatec {}
 
 impl<T: Point> TecAlgorithm<T> for Siatec {
     /// Returns all TECs of MTPs for the given point set.
     fn compute_tecs(&self, point_set: &PointSet<T>) -> Vec<Tec<T>> {
         let mut tecs = Vec::new();
-        let on_output = |mtp: Tec<T>| tecs.push(mtp);
+        let on_output = |tec: Tec<T>| {
+            tecs.push(tec);
+            ControlFlow::Continue(())
+        };
         self.compute_tecs_to_output(point_set, on_output);
         tecs
     }
 
-    fn compute_tecs_to_output(&self, point_set: &PointSet<T>, mut on_output: impl FnMut(Tec<T>)) {
+    fn compute_tecs_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        self.compute_tecs_to_output_observed(point_set, &IgnoreProgress {}, on_output)
+    }
+
+    /// Computes the TECs as `compute_tecs_to_output` and reports the number of MTPs
+    /// for which the translators have been found as progress.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "siatec", skip_all, fields(n = point_set.len())))]
+    fn compute_tecs_to_output_observed(
+        &self,
+        point_set: &PointSet<T>,
+        observer: &dyn ProgressObserver,
+        mut on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
         let (diff_table, forward_diffs) = Siatec::compute_differences(point_set);
 
-        let mut mtps_with_indices = Siatec::partition(point_set, &forward_diffs);
-        let mtps = Siatec::remove_translational_duplicates(&mut mtps_with_indices);
+        let mut mtps = Siatec::partition(point_set, &forward_diffs);
+        Siatec::remove_translational_duplicates(&mut mtps);
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            distinct_mtps = mtps.len(),
+            "computed translationally distinct MTPs"
+        );
 
         let n = point_set.len();
+        let tracker = ProgressTracker::new(observer, ProgressUnit::Patterns, mtps.len());
 
         // Compute the TECs by finding translators for each MTP
-        for mtp_with_indices in &mtps {
-            let translators = Siatec::find_translators(n, mtp_with_indices, &diff_table);
-            on_output(Tec {
-                pattern: mtp_with_indices.0.clone(),
+        #[cfg(not(feature = "parallel"))]
+        for (i, mtp) in mtps.iter().enumerate() {
+            let translators = Siatec::find_translators(n, mtp, &diff_table);
+            tracker.report(i + 1);
+            let flow = on_output(Tec {
+                pattern: mtp.to_pattern(),
                 translators,
             });
+            if flow.is_break() {
+                return;
+            }
+        }
+
+        // The translators are collected before output so that the output order
+        // is the same as in the sequential computation.
+        #[cfg(feature = "parallel")]
+        {
+            let all_translators: Vec<Vec<T>> = mtps
+                .par_iter()
+                .map(|mtp| Siatec::find_translators(n, mtp, &diff_table))
+                .collect();
+            tracker.report(mtps.len());
+
+            for (mtp, translators) in mtps.iter().zip(all_translators) {
+                let flow = on_output(Tec {
+                    pattern: mtp.to_pattern(),
+                    translators,
+                });
+                if flow.is_break() {
+                    return;
+                }
+            }
         }
     }
 }
@@ -50,6 +109,7 @@ impl Siatec {
     /// Initializes a size x size capacity table for differences.
     /// The table holds on the differences instead of also containing
     /// the indices as in the [Meredith et al. 2002] description.
+    #[cfg(not(feature = "parallel"))]
     fn create_diff_table<T: Point>(size: usize) -> Vec<Vec<T>> {
         let mut diff_table: Vec<Vec<T>> = Vec::with_capacity(size);
         for _ in 0..size {
@@ -62,10 +122,11 @@ impl Siatec {
     /// Computes the difference table and the forward differences with the indices required
     /// for MTP and translator computation.
     /// The forward differences are sorted in ascending lexicographical order.
+    #[cfg(not(feature = "parallel"))]
     fn compute_differences<T: Point>(point_set: &PointSet<T>) -> (Vec<Vec<T>>, Vec<(T, usize)>) {
         let n = point_set.len();
         let mut diff_table = Siatec::create_diff_table(n);
-        let mut forward_diffs: Vec<(T, usize)> = Vec::with_capacity(n * (n - 1) / 2);
+        let mut forward_diffs: Vec<(T, usize)> = Vec::with_capacity(n * n.saturating_sub(1) / 2);
 
         for i in 0..n {
             let from = &point_set[i];
@@ -86,15 +147,40 @@ impl Siatec {
         (diff_table, forward_diffs)
     }
 
-    /// Partitions the sorted list of difference-index pairs into MTPs. The returned triples contain
-    /// 0. the MTP pattern,
-    /// 1. the vectorized representation of the pattern, and
-    /// 2. the indices of the points belonging to the MTP.
-    fn partition<T: Point>(
-        point_set: &PointSet<T>,
-        forward_diffs: &Vec<(T, usize)>,
-    ) -> Vec<(Pattern<T>, Pattern<T>, Vec<usize>)> {
-        let mut mtps_with_indices: Vec<(Pattern<T>, Pattern<T>, Vec<usize>)> = Vec::new();
+    /// Computes the difference table and the forward differences with the indices required
+    /// for MTP and translator computation in parallel. The rows of the table are computed
+    /// in parallel, and the result is the same as in the sequential computation.
+    /// The forward differences are sorted in ascending lexicographical order.
+    #[cfg(feature = "parallel")]
+    fn compute_differences<T: Point>(point_set: &PointSet<T>) -> (Vec<Vec<T>>, Vec<(T, usize)>) {
+        let n = point_set.len();
+
+        let diff_table: Vec<Vec<T>> = (0..n)
+            .into_par_iter()
+            .map(|i| {
+                let from = &point_set[i];
+                (0..n).map(|j| point_set[j] - *from).collect()
+            })
+            .collect();
+
+        let mut forward_diffs: Vec<(T, usize)> = diff_table
+            .par_iter()
+            .enumerate()
+            .flat_map_iter(|(i, row)| row[i + 1..].iter().map(move |diff| (*diff, i)))
+            .collect();
+
+        forward_diffs.par_sort_unstable();
+
+        (diff_table, forward_diffs)
+    }
+
+    /// Partitions the sorted list of difference-index pairs into MTPs, which are returned
+    /// as references to the indices of the points belonging to the MTPs.
+    fn partition<'a, T: Point>(
+        point_set: &'a PointSet<T>,
+        forward_diffs: &[(T, usize)],
+    ) -> Vec<PatternRef<'a, T>> {
+        let mut mtps = Vec::new();
 
         let m = forward_diffs.len();
         let mut i = 0;
@@ -109,52 +195,32 @@ impl Siatec {
             }
 
             i = j;
-            let pattern = point_set.get_pattern(&indices);
-            let vectorized = pattern.vectorize();
-            mtps_with_indices.push((pattern, vectorized, indices));
+            mtps.push(PatternRef::new(point_set, indices));
         }
-        mtps_with_indices
+        mtps
     }
 
     /// Remove duplication of translationally equivalent patterns.
-    fn remove_translational_duplicates<T: Point>(
-        mtps_with_indices: &mut Vec<(Pattern<T>, Pattern<T>, Vec<usize>)>,
-    ) -> Vec<(&Pattern<T>, &Vec<usize>)> {
+    fn remove_translational_duplicates<T: Point>(mtps: &mut Vec<PatternRef<T>>) {
         // Sort by the vectorized representations so that translationally
         // equivalent patterns are adjacent.
-        mtps_with_indices.sort_by(|a, b| {
-            let size_order = a.1.len().cmp(&b.1.len());
+        mtps.sort_by(|a, b| {
+            let size_order = a.len().cmp(&b.len());
             if size_order == Ordering::Equal {
-                return a.1.cmp(&b.1);
+                return a.cmp_vectorized(b);
             }
             size_order
         });
 
-        // Store only the translationally distinct MTPs
-        let mut distinct_mtps = Vec::new();
-        let mut vec_representation = &mtps_with_indices[0].1;
-        distinct_mtps.push((&mtps_with_indices[0].0, &mtps_with_indices[0].2));
-        // Derefence+refence of mtps_with_indices is performed to ensure immutable reference is used.
-        for mtp_triplet in &*mtps_with_indices {
-            if mtp_triplet.1 != *vec_representation {
-                distinct_mtps.push((&mtp_triplet.0, &mtp_triplet.2));
-                vec_representation = &mtp_triplet.1;
-            }
-        }
-        distinct_mtps
+        // Keep only the first of the translationally equivalent MTPs
+        mtps.dedup_by(|mtp, previous| mtp.cmp_vectorized(previous) == Ordering::Equal);
     }
 
-    /// Finds all translators for the pattern in the given pattern-indices pair by using the difference
-    /// table.
-    fn find_translators<T: Point>(
-        n: usize,
-        mtp_indices: &(&Pattern<T>, &Vec<usize>),
-        diff_table: &[Vec<T>],
-    ) -> Vec<T> {
-        let pattern = mtp_indices.0;
-        let pat_len = pattern.len();
+    /// Finds all translators for the pattern by using the difference table.
+    fn find_translators<T: Point>(n: usize, mtp: &PatternRef<T>, diff_table: &[Vec<T>]) -> Vec<T> {
+        let pat_len = mtp.len();
         // Column indices that correspond to the indices of the pattern in the point set.
-        let col_ind = mtp_indices.1;
+        let col_ind = mtp.indices();
 
         let initial_value: usize = 0;
 
@@ -198,12 +264,14 @@ impl Siatec {
 
 #[cfg(test)]
 mod tests {
+    use std::ops::ControlFlow;
+
     use crate::discovery::algorithm::TecAlgorithm;
+    use crate::discovery::siatec::Siatec;
     use crate::point_set::pattern::Pattern;
     use crate::point_set::point::Point2Df64;
     use crate::point_set::set::PointSet;
     use crate::point_set::tec::Tec;
-    use crate::discovery::siatec::Siatec;
 
     #[test]
     fn test_with_minimal_number_of_mtps() {
@@ -221,7 +289,7 @@ mod tests {
         let point_set = PointSet::new(points);
         let siatec = Siatec {};
         let mut tecs = siatec.compute_tecs(&point_set);
-        tecs.sort_by(|a, b| a.pattern.len().cmp(&b.pattern.len()));
+        tecs.sort_by_key(|tec| tec.pattern.len());
 
         assert_eq!(3, tecs.len());
         assert_eq!(
@@ -250,4 +318,28 @@ mod tests {
             tecs[2]
         );
     }
+
+    #[test]
+    fn test_terminate_early() {
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 1.0, y: 1.0 },
+            Point2Df64 { x: 2.0, y: 1.0 },
+            Point2Df64 { x: 3.0, y: 1.0 },
+            Point2Df64 { x: 4.0, y: 1.0 },
+        ]);
+        let siatec = Siatec {};
+
+        let mut tecs = Vec::new();
+        siatec.compute_tecs_to_output(&point_set, |tec| {
+            tecs.push(tec);
+            if tecs.len() == 2 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+
+        assert_eq!(2, tecs.len());
+        assert_eq!(siatec.compute_tecs(&point_set)[..2], tecs[..]);
+    }
 }
@@ -3,113 +3,184 @@
  * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
  */
 
-use std::cmp::{max, Ordering};
+use std::cmp::{max, Ordering, Reverse};
+use std::ops::ControlFlow;
 
 use crate::discovery::algorithm::TecAlgorithm;
+use crate::discovery::diff_index::{DiffIndex, HashedDiffIndex, IndPair};
+use crate::discovery::duplicates::{output_without_duplicates, DuplicateStrategy};
+use crate::discovery::progress::{IgnoreProgress, ProgressObserver, ProgressTracker, ProgressUnit};
 use crate::point_set::mtp::Mtp;
 use crate::point_set::pattern::Pattern;
 use crate::point_set::point::Point;
 use crate::point_set::set::PointSet;
 use crate::point_set::tec::Tec;
-
-type IndPair = [usize; 2];
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 /// Implements the SIATEC-C algorithm [Björklund2022].
+///
+/// The translators of the patterns are found with a difference index, which is by default
+/// a `HashedDiffIndex`. Other index implementations can be used with `compute_tecs_with_index`.
+///
+/// The same pattern can be found in several windows, so the output can contain translationally
+/// equivalent TECs. These are removed with the duplicate strategy if one is given, in which case
+/// the TECs are output only after all of them have been computed.
+///
+/// The MTPs are split into patterns on the IOIs that exceed the maximum IOI. The size and the
+/// number of gaps of the patterns can be limited further, so that long patterns spanning many
+/// onsets are split into shorter successive patterns.
 pub struct SiatecC {
     /// Maximum allowed inter-onset-interval (IOI) between successive points in a pattern.
     pub max_ioi: f64,
+    /// Maximum number of points in a pattern, or None for no limit. Larger patterns are split
+    /// into successive patterns of at most this many points.
+    pub max_pattern_size: Option<usize>,
+    /// Maximum number of gaps, i.e., non-zero IOIs between successive points, in a pattern, or
+    /// None for no limit. Patterns with more gaps are split, so that the points of each pattern
+    /// are at no more than max_gap_count + 1 distinct onsets.
+    pub max_gap_count: Option<usize>,
+    /// The strategy for removing translationally equivalent duplicates from the output,
+    /// or None for outputting all TECs as they are found.
+    pub duplicate_strategy: Option<DuplicateStrategy>,
 }
 
 impl<T: Point> TecAlgorithm<T> for SiatecC {
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "siatec_c", skip_all, fields(n = point_set.len(), max_ioi = self.max_ioi)))]
     fn compute_tecs(&self, point_set: &PointSet<T>) -> Vec<Tec<T>> {
-        let diff_index = self.compute_diff_index(point_set);
         let mut tecs = Vec::new();
-        let on_output = |mtp: Tec<T>| tecs.push(mtp);
-        self.compute_split_mtp_tecs(point_set, &diff_index, on_output);
+        let on_output = |tec: Tec<T>| {
+            tecs.push(tec);
+            ControlFlow::Continue(())
+        };
+        self.compute_tecs_with_index::<T, HashedDiffIndex<T>>(point_set, on_output);
         tecs
     }
 
-    fn compute_tecs_to_output(&self, point_set: &PointSet<T>, on_output: impl FnMut(Tec<T>)) {
-        let diff_index = self.compute_diff_index(point_set);
-        self.compute_split_mtp_tecs(point_set, &diff_index, on_output)
+    fn compute_tecs_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        self.compute_tecs_to_output_observed(point_set, &IgnoreProgress {}, on_output)
+    }
+
+    /// Computes the TECs as `compute_tecs_to_output` and reports the number of
+    /// windows processed as progress. The total number of windows is estimated
+    /// from the span of the onset times and the maximum IOI.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "siatec_c", skip_all, fields(n = point_set.len(), max_ioi = self.max_ioi)))]
+    fn compute_tecs_to_output_observed(
+        &self,
+        point_set: &PointSet<T>,
+        observer: &dyn ProgressObserver,
+        on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        let diff_index = self.build_index::<T, HashedDiffIndex<T>>(point_set);
+        let tracker = ProgressTracker::new(
+            observer,
+            ProgressUnit::Windows,
+            self.window_count(point_set),
+        );
+        output_without_duplicates(
+            self.duplicate_strategy,
+            |output| self.compute_split_mtp_tecs(point_set, &diff_index, &tracker, output),
+            on_output,
+        );
     }
 }
 
 impl SiatecC {
-    /// Computes the IOI between to points. Onset time is
-    /// assumed to be the first component of the points and all points
-    /// are assumed to have dimensionality of at least one.
-    pub(crate) fn ioi<T: Point>(a: &T, b: &T) -> f64 {
-        let a_onset = a.component_f64(0);
-        let b_onset = b.component_f64(0);
-        b_onset.unwrap() - a_onset.unwrap()
+    /// Creates SIATEC-C with the given maximum IOI, which limits neither the size nor the
+    /// number of gaps of the patterns.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_ioi` - The maximum IOI between successive points in a pattern
+    pub fn new(max_ioi: f64) -> SiatecC {
+        SiatecC {
+            max_ioi,
+            max_pattern_size: None,
+            max_gap_count: None,
+            duplicate_strategy: None,
+        }
     }
 
-    /// Returns a vector of difference - index-pair-vector pairs, sorted in ascending lexicographical
-    /// order of the difference vectors.
-    fn compute_diff_index<T: Point>(&self, point_set: &PointSet<T>) -> Vec<(T, Vec<IndPair>)> {
-        let n = point_set.len();
-
-        let forward_diffs = self.compute_forward_diffs(point_set, n);
-
-        SiatecC::partition_by_diff_vector(&forward_diffs)
+    /// Returns this algorithm with the number of points in the patterns limited
+    /// (see `SiatecC::max_pattern_size`).
+    ///
+    /// # Arguments
+    ///
+    /// * `max_pattern_size` - The maximum number of points in a pattern
+    pub fn with_max_pattern_size(self, max_pattern_size: usize) -> SiatecC {
+        SiatecC {
+            max_pattern_size: Some(max_pattern_size),
+            ..self
+        }
     }
 
-    fn partition_by_diff_vector<T: Point>(
-        forward_diffs: &Vec<(T, [usize; 2])>,
-    ) -> Vec<(T, Vec<IndPair>)> {
-        let mut diff_index: Vec<(T, Vec<IndPair>)> = Vec::new();
-        let m = forward_diffs.len();
-        let mut i = 0;
-        while i < m {
-            let mut index_pairs: Vec<IndPair> = Vec::new();
-            let translator = &forward_diffs[i].0;
-
-            let mut j = i;
-            while j < m && *translator == forward_diffs[j].0 {
-                index_pairs.push(forward_diffs[j].1);
-                j += 1;
-            }
-
-            diff_index.push((*translator, index_pairs));
-            i = j;
+    /// Returns this algorithm with the number of gaps in the patterns limited
+    /// (see `SiatecC::max_gap_count`).
+    ///
+    /// # Arguments
+    ///
+    /// * `max_gap_count` - The maximum number of gaps in a pattern
+    pub fn with_max_gap_count(self, max_gap_count: usize) -> SiatecC {
+        SiatecC {
+            max_gap_count: Some(max_gap_count),
+            ..self
         }
+    }
 
-        diff_index
+    /// Returns this algorithm with the translationally equivalent duplicates removed
+    /// from the output with the given strategy (see `SiatecC::duplicate_strategy`).
+    ///
+    /// # Arguments
+    ///
+    /// * `strategy` - The strategy for removing the duplicates
+    pub fn with_duplicate_strategy(self, strategy: DuplicateStrategy) -> SiatecC {
+        SiatecC {
+            duplicate_strategy: Some(strategy),
+            ..self
+        }
     }
 
-    /// Computes forward differences that have inter-onset-interval of at most the limit set
-    /// in this instance of SiatecC.
-    fn compute_forward_diffs<T: Point>(
+    /// Computes the TECs as `compute_tecs_to_output`, but uses a difference index of
+    /// type I for finding the translators of the patterns, e.g., a `SortedDiffIndex` or
+    /// a `HashedDiffIndex` with a different hasher.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set for which the TECs are computed
+    /// * `on_output` - A function to execute whenever the algorithm can produce output
+    pub fn compute_tecs_with_index<T: Point, I: DiffIndex<T>>(
         &self,
         point_set: &PointSet<T>,
-        n: usize,
-    ) -> Vec<(T, IndPair)> {
-        let mut forward_diffs: Vec<(T, IndPair)> = Vec::new();
-
-        for i in 0..(n - 1) {
-            let from = &point_set[i];
-
-            for j in (i + 1)..n {
-                let to = &point_set[j];
-                let diff = *to - *from;
-                let ioi_opt = diff.component_f64(0);
-                match ioi_opt {
-                    Some(ioi) => {
-                        if ioi > self.max_ioi {
-                            break;
-                        }
-                    }
-                    None => panic!("Cannot compute with points with no onset component 0"),
-                }
-
-                forward_diffs.push((diff, [i, j]));
-            }
-        }
+        on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        let diff_index = self.build_index::<T, I>(point_set);
+        let tracker = ProgressTracker::new(&IgnoreProgress {}, ProgressUnit::Windows, 0);
+        output_without_duplicates(
+            self.duplicate_strategy,
+            |output| self.compute_split_mtp_tecs(point_set, &diff_index, &tracker, output),
+            on_output,
+        );
+    }
 
-        SiatecC::sort_with_ind_pairs(&mut forward_diffs);
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "diff_index", level = "debug", skip_all)
+    )]
+    fn build_index<T: Point, I: DiffIndex<T>>(&self, point_set: &PointSet<T>) -> I {
+        I::build(point_set, self.max_ioi)
+    }
 
-        forward_diffs
+    /// Computes the IOI between to points. Onset time is
+    /// assumed to be the first component of the points and all points
+    /// are assumed to have dimensionality of at least one.
+    pub(crate) fn ioi<T: Point>(a: &T, b: &T) -> f64 {
+        let a_onset = a.component_f64(0);
+        let b_onset = b.component_f64(0);
+        b_onset.unwrap() - a_onset.unwrap()
     }
 
     pub(crate) fn init_window_upper_bounds<T: Point>(
@@ -126,13 +197,31 @@ impl SiatecC {
         window_bounds
     }
 
-    fn compute_split_mtp_tecs<T: Point>(
+    /// Returns the estimated number of windows processed by `compute_split_mtp_tecs`.
+    fn window_count<T: Point>(&self, point_set: &PointSet<T>) -> usize {
+        let n = point_set.len();
+        if n == 0 || self.max_ioi <= 0.0 {
+            return n;
+        }
+
+        let first = point_set[0].component_f64(0).unwrap();
+        let last = point_set[n - 1].component_f64(0).unwrap();
+        ((last - first) / self.max_ioi).floor() as usize + 1
+    }
+
+    fn compute_split_mtp_tecs<T: Point, I: DiffIndex<T>>(
         &self,
         point_set: &PointSet<T>,
-        diff_index: &[(T, Vec<IndPair>)],
-        mut on_output: impl FnMut(Tec<T>),
+        diff_index: &I,
+        tracker: &ProgressTracker,
+        mut on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
     ) {
         let n = point_set.len();
+        // A single point has no difference vectors, so the windows would never advance.
+        if n < 2 {
+            return;
+        }
+
         // Initialize the window beginnings to start from the points:
         // target_indices keeps track of the target indices for the translators
         // window_bounds keeps track of the upper bounds of the windows within which
@@ -141,6 +230,7 @@ impl SiatecC {
         let mut window_bounds = SiatecC::init_window_upper_bounds(self.max_ioi, point_set);
 
         let mut cover: Vec<usize> = vec![0; n];
+        let mut windows = 0;
 
         while target_indices[0] < n {
             // Compute forward diffs in restricted size window
@@ -151,7 +241,7 @@ impl SiatecC {
                 &mut window_bounds,
             );
             let mtps = SiatecC::partition_to_mtps(point_set, &mut forward_diffs);
-            let split_triples = SiatecC::split_mtps_on_ioi(&mtps, self.max_ioi);
+            let split_triples = self.split_mtps(&mtps);
 
             for split_triple in &split_triples {
                 let pattern = &split_triple.0;
@@ -162,13 +252,33 @@ impl SiatecC {
                     let translators = SiatecC::find_translators_update_cover(
                         pattern, diff_index, point_set, &mut cover,
                     );
-                    on_output(Tec {
+                    #[cfg(feature = "tracing")]
+                    tracing::trace!(
+                        pattern_size = pattern.len(),
+                        translators = translators.len(),
+                        "emitted TEC"
+                    );
+                    let flow = on_output(Tec {
                         pattern: pattern.clone(),
                         translators,
                     });
+                    if flow.is_break() {
+                        return;
+                    }
                 }
             }
+
+            windows += 1;
+            #[cfg(feature = "tracing")]
+            tracing::trace!(
+                window = windows,
+                patterns = split_triples.len(),
+                "processed window"
+            );
+            tracker.report(windows);
         }
+
+        tracker.finish(windows);
     }
 
     pub(crate) fn improves_cover(
@@ -195,6 +305,7 @@ impl SiatecC {
     /// Computes the forward difference vectors for all points, such that, the target points are all within
     /// a restricted size window. Each source point has its own window position, so that difference
     /// vectors of the same size are always computed during the same iteration.
+    #[cfg(not(feature = "parallel"))]
     fn compute_forward_diffs_within_window<T: Point>(
         &self,
         point_set: &PointSet<T>,
@@ -204,65 +315,109 @@ impl SiatecC {
     ) -> Vec<(T, IndPair)> {
         let mut forward_diffs = Vec::new();
         for i in 0..(n - 1) {
-            let from = &point_set[i];
-            let target_index = target_indices[i];
-            if target_index >= n {
-                continue;
-            }
-
-            let mut window_exceeds_data = true;
+            self.compute_forward_diffs_from(
+                point_set,
+                i,
+                &mut target_indices[i],
+                &mut window_bounds[i],
+                &mut forward_diffs,
+            );
+        }
+        forward_diffs
+    }
 
-            for j in target_index..n {
-                if i == j {
-                    continue;
-                }
+    /// Computes the forward difference vectors within the window in parallel for the source points.
+    /// The windows of the source points are independent, so the result is the same as
+    /// in the sequential computation.
+    #[cfg(feature = "parallel")]
+    fn compute_forward_diffs_within_window<T: Point>(
+        &self,
+        point_set: &PointSet<T>,
+        n: usize,
+        target_indices: &mut [usize],
+        window_bounds: &mut [f64],
+    ) -> Vec<(T, IndPair)> {
+        target_indices[..(n - 1)]
+            .par_iter_mut()
+            .zip(window_bounds[..(n - 1)].par_iter_mut())
+            .enumerate()
+            .flat_map_iter(|(i, (target_index, window_bound))| {
+                let mut forward_diffs = Vec::new();
+                self.compute_forward_diffs_from(
+                    point_set,
+                    i,
+                    target_index,
+                    window_bound,
+                    &mut forward_diffs,
+                );
+                forward_diffs
+            })
+            .collect()
+    }
 
-                let to = &point_set[j];
-                let onset = to.component_f64(0).unwrap();
-                let diff: T = *to - *from;
+    /// Computes the forward difference vectors from the source point at index i to the target
+    /// points within its current window, and moves the window forward.
+    fn compute_forward_diffs_from<T: Point>(
+        &self,
+        point_set: &PointSet<T>,
+        i: usize,
+        target_index: &mut usize,
+        window_bound: &mut f64,
+        forward_diffs: &mut Vec<(T, IndPair)>,
+    ) {
+        let n = point_set.len();
+        if *target_index >= n {
+            return;
+        }
 
-                if onset > window_bounds[i] {
-                    target_indices[i] = j;
-                    window_exceeds_data = false;
-                    window_bounds[i] += self.max_ioi;
-                    break;
-                }
+        let from = &point_set[i];
+        let mut window_exceeds_data = true;
 
-                forward_diffs.push((diff, [i, j]))
+        for j in *target_index..n {
+            if i == j {
+                continue;
             }
 
-            // If the window has not reached the IOI limit, then the end of the window
-            // extends beyond the points in the data set, so there are no mode windows
-            // to handle from the starting index.
-            if window_exceeds_data {
-                target_indices[i] = n;
+            let to = &point_set[j];
+            let onset = to.component_f64(0).unwrap();
+            let diff: T = *to - *from;
+
+            if onset > *window_bound {
+                *target_index = j;
+                window_exceeds_data = false;
+                *window_bound += self.max_ioi;
+                break;
             }
+
+            forward_diffs.push((diff, [i, j]))
+        }
+
+        // If the window has not reached the IOI limit, then the end of the window
+        // extends beyond the points in the data set, so there are no mode windows
+        // to handle from the starting index.
+        if window_exceeds_data {
+            *target_index = n;
         }
-        forward_diffs
     }
 
-    /// Split the MTPs and their associated source and target index vectors on gaps that exceed max_ioi.
+    /// Split the MTPs and their associated source and target index vectors on gaps that exceed max_ioi
+    /// and on the limits of the pattern size and the gap count.
     /// The returned vector is sorted in descendind order of pattern size.
-    pub(crate) fn split_mtps_on_ioi<T: Point>(
+    pub(crate) fn split_mtps<T: Point>(
+        &self,
         mtps: &Vec<(Mtp<T>, Vec<usize>, Vec<usize>)>,
-        max_ioi: f64,
     ) -> Vec<(Pattern<T>, Vec<usize>, Vec<usize>)> {
         let mut split_mtps = Vec::new();
 
         for mtp_triple in mtps {
             let mtp = &mtp_triple.0;
-            let split = SiatecC::split_pattern_on_ioi_gaps(
-                &mtp.pattern,
-                &mtp_triple.1,
-                &mtp_triple.2,
-                max_ioi,
-            );
+            let split = self.split_pattern(&mtp.pattern, &mtp_triple.1, &mtp_triple.2);
             for s in split {
                 split_mtps.push(s);
             }
         }
 
-        split_mtps.sort_by(|triple_a, triple_b| triple_b.0.len().cmp(&triple_a.0.len()));
+        split_mtps.sort_by_key(|triple| Reverse(triple.0.len()));
         split_mtps
     }
 
@@ -272,7 +427,7 @@ impl SiatecC {
     /// 2. target indices: the indices of the points that form the translated MTP
     fn partition_to_mtps<T: Point>(
         point_set: &PointSet<T>,
-        forward_diffs: &mut Vec<(T, IndPair)>,
+        forward_diffs: &mut [(T, IndPair)],
     ) -> Vec<(Mtp<T>, Vec<usize>, Vec<usize>)> {
         // Sort and partition the diffs to find MTPs
         SiatecC::sort_with_ind_pairs(forward_diffs);
@@ -306,21 +461,26 @@ impl SiatecC {
         mtps
     }
 
-    pub(crate) fn split_pattern_on_ioi_gaps<T: Point>(
+    pub(crate) fn split_pattern<T: Point>(
+        &self,
         pattern: &Pattern<T>,
         source_ind: &[usize],
         target_ind: &[usize],
-        max_ioi: f64,
     ) -> Vec<(Pattern<T>, Vec<usize>, Vec<usize>)> {
         let mut split_patterns = Vec::new();
         let mut split = Vec::new();
         let mut split_source_ind = Vec::new();
         let mut split_target_ind = Vec::new();
+        let mut gaps = 0;
         let mut prev = &pattern[0];
         for i in 0..pattern.len() {
             let p = &pattern[i];
             let ioi = SiatecC::ioi(prev, p);
-            if ioi > max_ioi {
+            let is_full = self
+                .max_pattern_size
+                .is_some_and(|max_size| split.len() >= max_size);
+            let has_max_gaps = ioi > 0.0 && self.max_gap_count.is_some_and(|max| gaps >= max);
+            if !split.is_empty() && (ioi > self.max_ioi || is_full || has_max_gaps) {
                 split_patterns.push((
                     Pattern::new(&split),
                     split_source_ind.clone(),
@@ -329,6 +489,9 @@ impl SiatecC {
                 split.clear();
                 split_source_ind.clear();
                 split_target_ind.clear();
+                gaps = 0;
+            } else if ioi > 0.0 {
+                gaps += 1;
             }
             split.push(p);
             split_source_ind.push(source_ind[i]);
@@ -347,23 +510,21 @@ impl SiatecC {
         split_patterns
     }
 
-    fn find_indices<'a, T: Point>(
-        diff_index: &'a [(T, Vec<IndPair>)],
+    /// Returns the index pairs of the points between which the translation is the difference.
+    /// The difference index contains all differences within the max IOI (see `DiffIndex::build`),
+    /// so the translations between successive points of the split patterns are always found.
+    /// An empty slice is returned for translations that are not in the index.
+    fn find_indices<'a, T: Point, I: DiffIndex<T>>(
+        diff_index: &'a I,
         translation: &T,
-    ) -> &'a Vec<IndPair> {
-        let index_res = diff_index.binary_search_by(|t| t.0.cmp(translation));
-        match index_res {
-            Ok(index) => &diff_index[index].1,
-            Err(index) => {
-                println!(
-                    "Could not find exact match for {:?}, returning closest to {}",
-                    translation, index
-                );
-                if index >= diff_index.len() {
-                    return &diff_index[diff_index.len() - 1].1;
-                }
-
-                &diff_index[index].1
+    ) -> &'a [IndPair] {
+        match diff_index.get(translation) {
+            Some(indices) => indices,
+            None => {
+                debug_assert!(false, "{:?} not in difference index", translation);
+                #[cfg(feature = "tracing")]
+                tracing::warn!(?translation, "difference not in difference index");
+                &[]
             }
         }
     }
@@ -392,9 +553,9 @@ impl SiatecC {
         translators
     }
 
-    fn find_translators_update_cover<T: Point>(
+    fn find_translators_update_cover<T: Point, I: DiffIndex<T>>(
         pattern: &Pattern<T>,
-        diff_index: &[(T, Vec<IndPair>)],
+        diff_index: &I,
         point_set: &PointSet<T>,
         cover: &mut [usize],
     ) -> Vec<T> {
@@ -433,9 +594,9 @@ impl SiatecC {
         translators
     }
 
-    fn update_cover<T: Point>(
+    fn update_cover<T: Point, I: DiffIndex<T>>(
         pattern: &Pattern<T>,
-        diff_index: &[(T, Vec<[usize; 2]>)],
+        diff_index: &I,
         cover: &mut [usize],
         vectorized: &Pattern<T>,
         init_cover_ind: Vec<usize>,
@@ -455,23 +616,23 @@ impl SiatecC {
     }
 
     pub(crate) fn match_index_pairs_forward(
-        target_indices: &Vec<usize>,
-        translatable_indices: &Vec<IndPair>,
+        target_indices: &[usize],
+        translatable_indices: &[IndPair],
     ) -> Vec<usize> {
         SiatecC::match_index_pairs(target_indices, translatable_indices, true)
     }
 
     pub(crate) fn match_index_pairs_backward(
-        target_indices: &Vec<usize>,
-        translatable_indices: &Vec<IndPair>,
+        target_indices: &[usize],
+        translatable_indices: &[IndPair],
     ) -> Vec<usize> {
         SiatecC::match_index_pairs(target_indices, translatable_indices, false)
     }
 
     //noinspection ALL
     fn match_index_pairs(
-        target_indices: &Vec<usize>,
-        index_pairs: &Vec<[usize; 2]>,
+        target_indices: &[usize],
+        index_pairs: &[[usize; 2]],
         forward: bool,
     ) -> Vec<usize> {
         let mut matching_ind = Vec::new();
@@ -502,6 +663,13 @@ impl SiatecC {
         matching_ind
     }
 
+    /// Removes the translationally equivalent duplicates from the TECs and sorts the remaining
+    /// TECs by the size and the vectorized representation of their patterns. For other ways of
+    /// handling the duplicates, see `duplicates::remove_translational_duplicates`.
+    ///
+    /// # Arguments
+    ///
+    /// * `tecs` - The TECs from which the duplicates are removed
     pub fn remove_translational_duplicates<T: Point>(tecs: &mut Vec<Tec<T>>) {
         tecs.sort_by(|tec_a, tec_b| {
             let a = tec_a.pattern.vectorize();
@@ -514,7 +682,7 @@ impl SiatecC {
             size_order
         });
 
-        tecs.dedup_by(|a, b| a.pattern.vectorize() == b.pattern.vectorize())
+        tecs.dedup_by(|a, b| a.translationally_equivalent(b))
     }
 
     fn sort_with_ind_pairs<T: Point>(diffs: &mut [(T, IndPair)]) {
@@ -532,11 +700,14 @@ impl SiatecC {
 
 #[cfg(test)]
 mod tests {
+    use std::ops::ControlFlow;
+
     use crate::discovery::algorithm::TecAlgorithm;
+    use crate::discovery::diff_index::SortedDiffIndex;
     use crate::discovery::siatec_c::SiatecC;
     use crate::point_set::mtp::Mtp;
     use crate::point_set::pattern::Pattern;
-    use crate::point_set::point::Point2Df64;
+    use crate::point_set::point::{Point2DRf64, Point2Df64};
     use crate::point_set::set::PointSet;
     use crate::point_set::tec::Tec;
 
@@ -554,9 +725,9 @@ mod tests {
         points.push(d);
 
         let point_set = PointSet::new(points);
-        let siatec_c = SiatecC { max_ioi: 2.0 };
+        let siatec_c = SiatecC::new(2.0);
         let mut tecs = siatec_c.compute_tecs(&point_set);
-        tecs.sort_by(|a, b| a.pattern.len().cmp(&b.pattern.len()));
+        tecs.sort_by_key(|tec| tec.pattern.len());
 
         assert_eq!(3, tecs.len());
         assert_eq!(1, tecs[0].pattern.len());
@@ -591,7 +762,7 @@ mod tests {
         points.push(d);
 
         let point_set = PointSet::new(points);
-        let siatec_c = SiatecC { max_ioi: 2.0 };
+        let siatec_c = SiatecC::new(2.0);
         let mut tecs = siatec_c.compute_tecs(&point_set);
 
         SiatecC::remove_translational_duplicates(&mut tecs);
@@ -624,7 +795,7 @@ mod tests {
         points.push(e);
 
         let point_set = PointSet::new(points);
-        let siatec_c = SiatecC { max_ioi: 2.0 };
+        let siatec_c = SiatecC::new(2.0);
         let mut tecs = siatec_c.compute_tecs(&point_set);
 
         SiatecC::remove_translational_duplicates(&mut tecs);
@@ -672,7 +843,8 @@ mod tests {
             vec![110, 111],
         ));
 
-        let split_triples = SiatecC::split_mtps_on_ioi(&mtp_triples, max_ioi);
+        let siatec_c = SiatecC::new(max_ioi);
+        let split_triples = siatec_c.split_mtps(&mtp_triples);
         assert_eq!(3, split_triples.len());
 
         assert!(split_triples.contains(&(
@@ -702,4 +874,108 @@ mod tests {
             vec![110, 111]
         )));
     }
+
+    #[test]
+    fn test_splitting_on_pattern_size_and_gaps() {
+        let points = [(0.0, 0.0), (0.0, 4.0), (1.0, 0.0), (2.0, 0.0), (3.0, 0.0)];
+        let points: Vec<Point2Df64> = points
+            .iter()
+            .map(|(x, y)| Point2Df64 { x: *x, y: *y })
+            .collect();
+        let pattern = Pattern::new(&points.iter().collect());
+        let source_ind = vec![0, 1, 2, 3, 4];
+        let target_ind = vec![10, 11, 12, 13, 14];
+        let split_indices = |siatec_c: &SiatecC| -> Vec<Vec<usize>> {
+            siatec_c
+                .split_pattern(&pattern, &source_ind, &target_ind)
+                .into_iter()
+                .map(|split| split.1)
+                .collect()
+        };
+
+        let size_limited = SiatecC::new(2.0).with_max_pattern_size(2);
+        assert_eq!(
+            vec![vec![0, 1], vec![2, 3], vec![4]],
+            split_indices(&size_limited)
+        );
+
+        let gap_limited = SiatecC::new(2.0).with_max_gap_count(1);
+        assert_eq!(vec![vec![0, 1, 2], vec![3, 4]], split_indices(&gap_limited));
+
+        let point_set = PointSet::new(points);
+        for siatec_c in [size_limited, gap_limited] {
+            let tecs = siatec_c.compute_tecs(&point_set);
+            assert!(!tecs.is_empty());
+            assert!(tecs.iter().all(|tec| tec.pattern.len() <= 3));
+        }
+    }
+
+    #[test]
+    fn test_rounded_points_at_ioi_limit() {
+        // The rounded onsets of a and b differ by exactly the max IOI, whereas the rounded
+        // difference of the raw onsets exceeds it. The difference must still be found.
+        let a = Point2DRf64::new(0.000006, 60.0);
+        let b = Point2DRf64::new(1.000014, 62.0);
+        let c = Point2DRf64::new(3.000006, 60.0);
+        let d = Point2DRf64::new(4.000014, 62.0);
+        let point_set = PointSet::new(vec![a, b, c, d]);
+
+        let siatec_c = SiatecC::new(1.0);
+        let tecs = siatec_c.compute_tecs(&point_set);
+
+        let tec = tecs.iter().find(|tec| tec.pattern.len() == 2).unwrap();
+        assert_eq!(Pattern::new(&vec![&a, &b]), tec.pattern);
+        assert_eq!(vec![c - a], tec.translators);
+    }
+
+    #[test]
+    fn test_rounded_points_with_triplets() {
+        // A triplet figure repeated in each bar. The raw onsets are not exactly representable,
+        // and they accumulate different floating point errors in each bar.
+        let mut points = Vec::new();
+        for bar in 0..4 {
+            let bar_onset = bar as f64 * 1.1;
+            for (i, pitch) in [60.0, 64.0, 67.0].iter().enumerate() {
+                let onset = bar_onset + i as f64 / 3.0;
+                points.push(Point2DRf64::new(onset, pitch + bar as f64));
+            }
+        }
+        let point_set = PointSet::new(points);
+
+        let siatec_c = SiatecC::new(1.0);
+        let tecs = siatec_c.compute_tecs(&point_set);
+
+        // All occurrences of all TECs must be in the point set, i.e., no translators
+        // may be produced from inexact matches of the differences.
+        for tec in &tecs {
+            for point in &tec.covered_set() {
+                assert!(point_set.contains(point), "{:?} not in point set", point);
+            }
+        }
+
+        let triplet = Pattern::new(&vec![&point_set[0], &point_set[1], &point_set[2]]);
+        let triplet_tec = tecs.iter().find(|tec| tec.pattern == triplet).unwrap();
+        assert_eq!(3, triplet_tec.translators.len());
+        assert_eq!(point_set, triplet_tec.covered_set());
+    }
+
+    #[test]
+    fn test_with_sorted_diff_index() {
+        let mut points = Vec::new();
+        for i in 0..16 {
+            points.push(Point2Df64 {
+                x: i as f64,
+                y: ((i * 5) % 7) as f64,
+            });
+        }
+        let point_set = PointSet::new(points);
+        let siatec_c = SiatecC::new(3.0);
+
+        let mut tecs = Vec::new();
+        siatec_c.compute_tecs_with_index::<_, SortedDiffIndex<_>>(&point_set, |tec| {
+            tecs.push(tec);
+            ControlFlow::Continue(())
+        });
+        assert_eq!(siatec_c.compute_tecs(&point_set), tecs);
+    }
 }
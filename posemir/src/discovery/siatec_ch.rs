@@ -3,286 +3,89 @@
  * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
  */
 
-use std::cmp::max;
-use std::collections::HashMap;
-use std::hash::BuildHasherDefault;
-
-use hashers::fx_hash::FxHasher64;
+use std::ops::ControlFlow;
 
 use crate::discovery::algorithm::TecAlgorithm;
+use crate::discovery::diff_index::HashedDiffIndex;
+use crate::discovery::duplicates::DuplicateStrategy;
+use crate::discovery::progress::ProgressObserver;
 use crate::discovery::siatec_c::SiatecC;
-use crate::point_set::mtp::Mtp;
-use crate::point_set::pattern::Pattern;
 use crate::point_set::point::Point;
 use crate::point_set::set::PointSet;
 use crate::point_set::tec::Tec;
 
-type IndPair = [usize; 2];
-type HMap<T> = HashMap<T, Vec<IndPair>, BuildHasherDefault<FxHasher64>>;
-
-/// Implements the SIATEC-CH algorithm (prototype).
+/// Implements the SIATEC-C algorithm with a hashed difference index (see `HashedDiffIndex`).
+/// SIATEC-C uses the hashed index by default, so this is equivalent to `SiatecC`.
 pub struct SiatecCH {
     /// Maximum allowed inter-onset-interval (IOI) between successive points in a pattern.
     pub max_ioi: f64,
+    /// The strategy for removing translationally equivalent duplicates from the output,
+    /// or None for outputting all TECs as they are found (see `SiatecC`).
+    pub duplicate_strategy: Option<DuplicateStrategy>,
 }
 
 impl<T: Point> TecAlgorithm<T> for SiatecCH {
     fn compute_tecs(&self, point_set: &PointSet<T>) -> Vec<Tec<T>> {
-        let diff_index = self.compute_diff_index(point_set);
         let mut tecs = Vec::new();
-        let on_output = |mtp: Tec<T>| tecs.push(mtp);
-        self.compute_split_mtp_tecs(point_set, &diff_index, on_output);
+        self.compute_tecs_to_output(point_set, |tec| {
+            tecs.push(tec);
+            ControlFlow::Continue(())
+        });
         tecs
     }
 
-    fn compute_tecs_to_output(&self, point_set: &PointSet<T>, on_output: impl FnMut(Tec<T>)) {
-        let diff_index = self.compute_diff_index(point_set);
-        self.compute_split_mtp_tecs(point_set, &diff_index, on_output)
-    }
-}
-
-impl SiatecCH {
-    fn new_hmap<T: Point>() -> HMap<T> {
-        HashMap::with_hasher(BuildHasherDefault::<FxHasher64>::default())
-    }
-
-    /// Returns a hashmap of difference - index-pair-vector pairs
-    fn compute_diff_index<T: Point>(&self, point_set: &PointSet<T>) -> HMap<T> {
-        let n = point_set.len();
-        let mut forward_diffs = SiatecCH::new_hmap();
-
-        for i in 0..(n - 1) {
-            let from = &point_set[i];
-
-            for j in (i + 1)..n {
-                let to = &point_set[j];
-                let diff = *to - *from;
-                let ioi_opt = diff.component_f64(0);
-                match ioi_opt {
-                    Some(ioi) => {
-                        if ioi > self.max_ioi {
-                            break;
-                        }
-                    }
-                    None => panic!("Cannot compute with points with no onset component 0"),
-                }
-
-                match forward_diffs.get_mut(&diff) {
-                    Some(indices) => {
-                        indices.push([i, j]);
-                    }
-                    None => {
-                        forward_diffs.insert(diff, vec![[i, j]]);
-                    }
-                }
-            }
-        }
-
-        forward_diffs
-    }
-
-    fn compute_split_mtp_tecs<T: Point>(
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "siatec_ch", skip_all, fields(n = point_set.len(), max_ioi = self.max_ioi)))]
+    fn compute_tecs_to_output(
         &self,
         point_set: &PointSet<T>,
-        diff_index: &HMap<T>,
-        mut on_output: impl FnMut(Tec<T>),
+        on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
     ) {
-        let n = point_set.len();
-        // Initialize the window beginnings to start from the points:
-        // target_indices keeps track of the target indices for the translators
-        // window_bounds keeps track of the upper bounds of the windows within which
-        // the target points of the translators must be.
-        let mut target_indices: Vec<usize> = (0..n).collect();
-        let mut window_bounds = SiatecC::init_window_upper_bounds(self.max_ioi, point_set);
-
-        let mut cover: Vec<usize> = vec![0; n];
-
-        while target_indices[0] < n {
-            // Compute forward diffs in restricted size window
-            let forward_diffs = self.compute_forward_diffs_within_window(
-                point_set,
-                n,
-                &mut target_indices,
-                &mut window_bounds,
-            );
-            let mtps = SiatecCH::partition_to_mtps(point_set, &forward_diffs);
-            let split_triples = SiatecC::split_mtps_on_ioi(&mtps, self.max_ioi);
-
-            for split_triple in &split_triples {
-                let pattern = &split_triple.0;
-                let source_ind = &split_triple.1;
-                let target_ind = &split_triple.2;
-
-                if SiatecC::improves_cover(&cover, source_ind, target_ind, pattern.len()) {
-                    let translators = SiatecCH::find_translators_update_cover(
-                        pattern, diff_index, point_set, &mut cover,
-                    );
-                    on_output(Tec {
-                        pattern: pattern.clone(),
-                        translators,
-                    });
-                }
-            }
-        }
+        self.siatec_c()
+            .compute_tecs_with_index::<T, HashedDiffIndex<T>>(point_set, on_output)
     }
 
-    /// Computes the forward difference vectors for all points, such that, the target points are all within
-    /// a restricted size window. Each source point has its own window position, so that difference
-    /// vectors of the same size are always computed during the same iteration.
-    fn compute_forward_diffs_within_window<T: Point>(
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "siatec_ch", skip_all, fields(n = point_set.len(), max_ioi = self.max_ioi)))]
+    fn compute_tecs_to_output_observed(
         &self,
         point_set: &PointSet<T>,
-        n: usize,
-        target_indices: &mut [usize],
-        window_bounds: &mut [f64],
-    ) -> HMap<T> {
-        let mut forward_diffs = SiatecCH::new_hmap();
-        for i in 0..(n - 1) {
-            let from = &point_set[i];
-            let target_index = target_indices[i];
-            if target_index >= n {
-                continue;
-            }
-
-            let mut window_exceeds_data = true;
-
-            for j in target_index..n {
-                if i == j {
-                    continue;
-                }
-
-                let to = &point_set[j];
-                let onset = to.component_f64(0).unwrap();
-                let diff: T = *to - *from;
-
-                if onset > window_bounds[i] {
-                    target_indices[i] = j;
-                    window_exceeds_data = false;
-                    window_bounds[i] += self.max_ioi;
-                    break;
-                }
-
-                match forward_diffs.get_mut(&diff) {
-                    Some(indices) => {
-                        indices.push([i, j]);
-                    }
-                    None => {
-                        forward_diffs.insert(diff, vec![[i, j]]);
-                    }
-                }
-            }
-
-            // If the window has not reached the IOI limit, then the end of the window
-            // extends beyond the points in the data set, so there are no mode windows
-            // to handle from the starting index.
-            if window_exceeds_data {
-                target_indices[i] = n;
-            }
-        }
-        forward_diffs
-    }
-
-    /// Partitions the forward diffs to MTPs and returns a vector of triples, where:
-    /// 0. MTP
-    /// 1. source indices: the indices that form the MTP
-    /// 2. target indices: the indices of the points that form the translated MTP
-    fn partition_to_mtps<T: Point>(
-        point_set: &PointSet<T>,
-        forward_diffs: &HMap<T>,
-    ) -> Vec<(Mtp<T>, Vec<usize>, Vec<usize>)> {
-        let mut mtps: Vec<(Mtp<T>, Vec<usize>, Vec<usize>)> = Vec::new();
-
-        for (translator, ind_pairs) in forward_diffs {
-            let m = ind_pairs.len();
-            let mut source_indices = Vec::with_capacity(m);
-            let mut target_indices = Vec::with_capacity(m);
-
-            for ind_pair in ind_pairs.iter() {
-                source_indices.push(ind_pair[0]);
-                target_indices.push(ind_pair[1]);
-            }
-
-            mtps.push((
-                Mtp {
-                    translator: *translator,
-                    pattern: point_set.get_pattern(&source_indices),
-                },
-                source_indices,
-                target_indices,
-            ));
-        }
-        mtps
+        observer: &dyn ProgressObserver,
+        on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        self.siatec_c()
+            .compute_tecs_to_output_observed(point_set, observer, on_output)
     }
+}
 
-    fn find_indices<'a, T: Point>(diff_index: &'a HMap<T>, translation: &T) -> &'a Vec<IndPair> {
-        match diff_index.get(translation) {
-            Some(indices) => indices,
-            None => {
-                println!("Could not find exact match for {:?}", translation);
-                panic!("Cannot default to any value");
-            }
+impl SiatecCH {
+    /// Creates SIATEC-CH with the given maximum IOI, which outputs all TECs as they are found.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_ioi` - The maximum IOI between successive points in a pattern
+    pub fn new(max_ioi: f64) -> SiatecCH {
+        SiatecCH {
+            max_ioi,
+            duplicate_strategy: None,
         }
     }
 
-    fn find_translators_update_cover<T: Point>(
-        pattern: &Pattern<T>,
-        diff_index: &HMap<T>,
-        point_set: &PointSet<T>,
-        cover: &mut [usize],
-    ) -> Vec<T> {
-        if pattern.len() == 1 {
-            return SiatecC::find_single_point_translators_update_cover(pattern, point_set, cover);
-        }
-
-        let vectorized = pattern.vectorize();
-        let v = &vectorized[0];
-
-        let indices = SiatecCH::find_indices(diff_index, v);
-        let mut target_indices = Vec::with_capacity(indices.len());
-        for ind_pair in indices.iter() {
-            target_indices.push(ind_pair[1]);
-        }
-
-        for i in 1..vectorized.len() {
-            let diff = &vectorized[i];
-            let translatable_indices = SiatecCH::find_indices(diff_index, diff);
-            target_indices =
-                SiatecC::match_index_pairs_forward(&target_indices, translatable_indices);
+    /// Returns this algorithm with the translationally equivalent duplicates removed
+    /// from the output with the given strategy (see `SiatecCH::duplicate_strategy`).
+    ///
+    /// # Arguments
+    ///
+    /// * `strategy` - The strategy for removing the duplicates
+    pub fn with_duplicate_strategy(self, strategy: DuplicateStrategy) -> SiatecCH {
+        SiatecCH {
+            duplicate_strategy: Some(strategy),
+            ..self
         }
-
-        let mut translators = Vec::with_capacity(target_indices.len());
-        let last_point = pattern[pattern.len() - 1];
-        for i in 0..target_indices.len() {
-            let translator = point_set[target_indices[i]] - last_point;
-            if !translator.is_zero() {
-                translators.push(translator);
-            }
-        }
-
-        // Update cover
-        SiatecCH::update_cover(pattern, diff_index, cover, &vectorized, target_indices);
-
-        translators
     }
 
-    fn update_cover<T: Point>(
-        pattern: &Pattern<T>,
-        diff_index: &HMap<T>,
-        cover: &mut [usize],
-        vectorized: &Pattern<T>,
-        init_cover_ind: Vec<usize>,
-    ) {
-        let mut cover_indices = init_cover_ind;
-
-        for i in (0..vectorized.len()).rev() {
-            let diff = &vectorized[i];
-            let translatable_indices = SiatecCH::find_indices(diff_index, diff);
-            cover_indices =
-                SiatecC::match_index_pairs_backward(&cover_indices, translatable_indices);
-
-            for c in &cover_indices {
-                cover[*c] = max(cover[*c], pattern.len());
-            }
+    fn siatec_c(&self) -> SiatecC {
+        SiatecC {
+            duplicate_strategy: self.duplicate_strategy,
+            ..SiatecC::new(self.max_ioi)
         }
     }
 }
@@ -290,12 +93,12 @@ impl SiatecCH {
 #[cfg(test)]
 mod tests {
     use crate::discovery::algorithm::TecAlgorithm;
+    use crate::discovery::siatec_c::SiatecC;
+    use crate::discovery::siatec_ch::SiatecCH;
     use crate::point_set::pattern::Pattern;
-    use crate::point_set::point::Point2Df64;
+    use crate::point_set::point::{Point2DRf64, Point2Df64};
     use crate::point_set::set::PointSet;
     use crate::point_set::tec::Tec;
-    use crate::discovery::siatec_c::SiatecC;
-    use crate::discovery::siatec_ch::SiatecCH;
 
     #[test]
     fn test_with_minimal_number_of_mtps() {
@@ -311,9 +114,9 @@ mod tests {
         points.push(d);
 
         let point_set = PointSet::new(points);
-        let siatec_ch = SiatecCH { max_ioi: 2.0 };
+        let siatec_ch = SiatecCH::new(2.0);
         let mut tecs = siatec_ch.compute_tecs(&point_set);
-        tecs.sort_by(|a, b| a.pattern.len().cmp(&b.pattern.len()));
+        tecs.sort_by_key(|tec| tec.pattern.len());
 
         assert_eq!(3, tecs.len());
         assert_eq!(1, tecs[0].pattern.len());
@@ -348,7 +151,7 @@ mod tests {
         points.push(d);
 
         let point_set = PointSet::new(points);
-        let siatec_ch = SiatecCH { max_ioi: 2.0 };
+        let siatec_ch = SiatecCH::new(2.0);
         let mut tecs = siatec_ch.compute_tecs(&point_set);
 
         SiatecC::remove_translational_duplicates(&mut tecs);
@@ -381,7 +184,7 @@ mod tests {
         points.push(e);
 
         let point_set = PointSet::new(points);
-        let siatec_ch = SiatecCH { max_ioi: 2.0 };
+        let siatec_ch = SiatecCH::new(2.0);
         let mut tecs = siatec_ch.compute_tecs(&point_set);
 
         SiatecC::remove_translational_duplicates(&mut tecs);
@@ -397,4 +200,22 @@ mod tests {
             tecs[1]
         );
     }
+
+    #[test]
+    fn test_rounded_points_at_ioi_limit() {
+        // The rounded onsets of a and b differ by exactly the max IOI, whereas the rounded
+        // difference of the raw onsets exceeds it. The difference must still be found.
+        let a = Point2DRf64::new(0.000006, 60.0);
+        let b = Point2DRf64::new(1.000014, 62.0);
+        let c = Point2DRf64::new(3.000006, 60.0);
+        let d = Point2DRf64::new(4.000014, 62.0);
+        let point_set = PointSet::new(vec![a, b, c, d]);
+
+        let siatec_ch = SiatecCH::new(1.0);
+        let tecs = siatec_ch.compute_tecs(&point_set);
+
+        let tec = tecs.iter().find(|tec| tec.pattern.len() == 2).unwrap();
+        assert_eq!(Pattern::new(&vec![&a, &b]), tec.pattern);
+        assert_eq!(vec![c - a], tec.translators);
+    }
 }
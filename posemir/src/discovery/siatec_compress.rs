@@ -2,11 +2,13 @@
  * (c) Otso Björklund (2021)
  * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
  */
-use std::cmp::Ordering;
 use std::marker::PhantomData;
+use std::ops::ControlFlow;
 
 use crate::discovery::algorithm::TecAlgorithm;
 use crate::discovery::heuristic::{stats_of, TecStats};
+use crate::discovery::scored_tec::{ScoredTec, TecScores};
+use crate::discovery::tec_ranker::{DefaultRanker, TecRanker};
 use crate::point_set::pattern::Pattern;
 use crate::point_set::point::Point;
 use crate::point_set::set::PointSet;
@@ -15,39 +17,28 @@ use crate::point_set::tec::Tec;
 /// Implements the SIATECCompress algorithm as described in [Meredith2013].
 pub struct SiatecCompress<T: Point, A: TecAlgorithm<T>> {
     tec_algorithm: A,
+    ranker: Box<dyn TecRanker>,
     _t: PhantomData<T>,
 }
 
 impl<T: Point, A: TecAlgorithm<T>> TecAlgorithm<T> for SiatecCompress<T, A> {
     fn compute_tecs(&self, point_set: &PointSet<T>) -> Vec<Tec<T>> {
-        let mut tecs = self.tec_algorithm.compute_tecs(point_set);
-        let mut conjugate_tecs: Vec<Tec<T>> = tecs.iter().map(|tec| tec.conjugate()).collect();
-        tecs.append(&mut conjugate_tecs);
-        let mut tec_stats: Vec<TecStats<T>> = tecs
-            .iter()
-            .map(|tec| stats_of(tec.remove_redundant_translators(), point_set))
-            .collect();
-
-        // Sort the tec stats so that best ones are first
-        tec_stats.sort_by(|a, b| {
-            if a.is_better_than(b) {
-                return Ordering::Less;
-            }
-
-            if b.is_better_than(a) {
-                return Ordering::Greater;
-            }
-
-            Ordering::Equal
-        });
-
-        self.compute_encoding(&tec_stats, point_set)
+        self.compute_scored_tecs(point_set)
+            .into_iter()
+            .map(|scored| scored.tec)
+            .collect()
     }
 
-    fn compute_tecs_to_output(&self, point_set: &PointSet<T>, mut on_output: impl FnMut(Tec<T>)) {
+    fn compute_tecs_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
         let tecs = self.compute_tecs(point_set);
         for tec in tecs {
-            on_output(tec);
+            if on_output(tec).is_break() {
+                return;
+            }
         }
     }
 }
@@ -56,13 +47,81 @@ impl<T: Point, A: TecAlgorithm<T>> SiatecCompress<T, A> {
     /// Creates a new instance of SIATECCompress that uses the given TEC-algorithm
     /// for computing the TEC candidates.
     pub fn with(tec_algorithm: A) -> SiatecCompress<T, A> {
+        SiatecCompress::with_ranker(tec_algorithm, DefaultRanker {})
+    }
+
+    /// Creates a new instance of SIATECCompress that uses the given TEC-algorithm
+    /// for computing the TEC candidates and the given ranker for selecting the best TECs.
+    ///
+    /// # Arguments
+    ///
+    /// * `tec_algorithm` - The algorithm that computes the TEC candidates
+    /// * `ranker` - The ranker that defines which TECs are better, see `TecRankerBuilder`
+    pub fn with_ranker(tec_algorithm: A, ranker: impl TecRanker + 'static) -> SiatecCompress<T, A> {
         SiatecCompress {
             tec_algorithm,
+            ranker: Box::new(ranker),
             _t: Default::default(),
         }
     }
 
-    fn compute_encoding(&self, tec_stats: &[TecStats<T>], point_set: &PointSet<T>) -> Vec<Tec<T>> {
+    fn is_better_than(&self, a: &TecStats<T>, b: &TecStats<T>) -> bool {
+        self.ranker
+            .is_better_than(&TecScores::from(a), &TecScores::from(b))
+    }
+
+    /// Computes the TECs as `compute_tecs` but returns them with their heuristic scores.
+    /// The residual TEC (if any) is scored in the same way as the selected TECs.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set for which the TECs are computed
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "siatec_compress", skip_all, fields(n = point_set.len())))]
+    pub fn compute_scored_tecs(&self, point_set: &PointSet<T>) -> Vec<ScoredTec<T>> {
+        let mut tecs = self.tec_algorithm.compute_tecs(point_set);
+        let mut conjugate_tecs: Vec<Tec<T>> = tecs.iter().map(|tec| tec.conjugate()).collect();
+        tecs.append(&mut conjugate_tecs);
+        let tec_stats: Vec<TecStats<T>> = tecs
+            .iter()
+            .map(|tec| stats_of(tec.remove_redundant_translators(), point_set))
+            .collect();
+
+        // Sort the tec stats so that best ones are first
+        let tec_stats = self.sort_by_rank(tec_stats);
+
+        self.compute_encoding(&tec_stats, point_set)
+    }
+
+    /// Sorts the TEC stats so that better TECs are before worse ones with a stable merge sort.
+    /// The ranker need not define a total order (e.g., `DefaultRanker` does not), for which
+    /// the sort of the standard library may panic.
+    fn sort_by_rank(&self, mut tec_stats: Vec<TecStats<T>>) -> Vec<TecStats<T>> {
+        if tec_stats.len() <= 1 {
+            return tec_stats;
+        }
+
+        let second_half = tec_stats.split_off(tec_stats.len() / 2);
+        let mut first = self.sort_by_rank(tec_stats).into_iter().peekable();
+        let mut second = self.sort_by_rank(second_half).into_iter().peekable();
+
+        let mut sorted = Vec::with_capacity(first.len() + second.len());
+        while let (Some(a), Some(b)) = (first.peek(), second.peek()) {
+            if self.is_better_than(b, a) {
+                sorted.extend(second.next());
+            } else {
+                sorted.extend(first.next());
+            }
+        }
+        sorted.extend(first);
+        sorted.extend(second);
+        sorted
+    }
+
+    fn compute_encoding(
+        &self,
+        tec_stats: &[TecStats<T>],
+        point_set: &PointSet<T>,
+    ) -> Vec<ScoredTec<T>> {
         let mut total_cover = PointSet::new(Vec::new());
         let mut tec_cover = Vec::new();
 
@@ -74,8 +133,11 @@ impl<T: Point, A: TecAlgorithm<T>> SiatecCompress<T, A> {
             let tec_repr_size = tec_stat.tec.pattern.len() + tec_stat.tec.translators.len();
 
             if new_points.len() > tec_repr_size {
-                tec_cover.push(tec_stat.tec.clone());
-                total_cover = total_cover.union(cov);
+                tec_cover.push(ScoredTec {
+                    tec: tec_stat.tec.clone(),
+                    scores: TecScores::from(tec_stat),
+                });
+                total_cover.union_with(cov);
                 if total_cover.len() == point_set.len() {
                     break;
                 }
@@ -84,6 +146,15 @@ impl<T: Point, A: TecAlgorithm<T>> SiatecCompress<T, A> {
 
         // Add any remaining residual points as a TEC
         let residual_points = point_set.difference(&total_cover);
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            candidates = tec_stats.len(),
+            selected = tec_cover.len(),
+            residual = residual_points.len(),
+            "computed encoding"
+        );
+
         if !residual_points.is_empty() {
             let first = &residual_points[0];
             let pattern = Pattern::new(&vec![first]);
@@ -93,10 +164,11 @@ impl<T: Point, A: TecAlgorithm<T>> SiatecCompress<T, A> {
                 translators.push(residual_points[i] - *first);
             }
 
-            tec_cover.push(Tec {
+            let residual_tec = Tec {
                 pattern,
                 translators,
-            });
+            };
+            tec_cover.push(ScoredTec::new(residual_tec, point_set));
         }
 
         tec_cover
@@ -106,11 +178,11 @@ impl<T: Point, A: TecAlgorithm<T>> SiatecCompress<T, A> {
 #[cfg(test)]
 mod tests {
     use crate::discovery::algorithm::TecAlgorithm;
+    use crate::discovery::siatec::Siatec;
+    use crate::discovery::siatec_compress::SiatecCompress;
     use crate::point_set::pattern::Pattern;
     use crate::point_set::point::Point2Df64;
     use crate::point_set::set::PointSet;
-    use crate::discovery::siatec::Siatec;
-    use crate::discovery::siatec_compress::SiatecCompress;
 
     #[test]
     fn test_simple_point_set() {
@@ -0,0 +1,104 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::ops::ControlFlow;
+
+use crate::discovery::algorithm::TecAlgorithm;
+use crate::discovery::mtp_to_tec::MtpToTec;
+use crate::discovery::siar::SiaR;
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// Implements SIATEC-R, i.e., SIATEC restricted to the MTPs of SIAR. The MTPs are computed
+/// from the difference vectors on the r subdiagonals of the difference table as in SIAR,
+/// and the full set of translators is found only for those MTPs. This is a middle ground
+/// between SIAR, which does not find the translators of the patterns, and SIATEC, which
+/// finds the TECs of all MTPs and is too slow for large point sets.
+pub struct SiatecR {
+    /// The number of subdiagonals of the difference table used for computing the MTPs,
+    /// see `SiaR::r`.
+    pub r: usize,
+    /// The minimum number of points in the patterns of the output TECs,
+    /// see `SiaR::min_pattern_size`.
+    pub min_pattern_size: usize,
+}
+
+impl<T: Point> TecAlgorithm<T> for SiatecR {
+    fn compute_tecs(&self, point_set: &PointSet<T>) -> Vec<Tec<T>> {
+        let mut tecs = Vec::new();
+        let on_output = |tec: Tec<T>| {
+            tecs.push(tec);
+            ControlFlow::Continue(())
+        };
+        self.compute_tecs_to_output(point_set, on_output);
+        tecs
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "siatec_r", skip_all, fields(n = point_set.len(), r = self.r)))]
+    fn compute_tecs_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        let siar = SiaR {
+            min_pattern_size: self.min_pattern_size,
+            ..SiaR::new(self.r)
+        };
+        MtpToTec::with(siar).compute_tecs_to_output(point_set, on_output);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::algorithm::{MtpAlgorithm, TecAlgorithm};
+    use crate::discovery::siar::SiaR;
+    use crate::discovery::siatec::Siatec;
+    use crate::discovery::siatec_r::SiatecR;
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+
+    fn test_point_set() -> PointSet<Point2Df64> {
+        PointSet::new(vec![
+            Point2Df64 { x: 1.0, y: 1.0 },
+            Point2Df64 { x: 2.0, y: 3.0 },
+            Point2Df64 { x: 2.0, y: 1.0 },
+            Point2Df64 { x: 3.0, y: 3.0 },
+            Point2Df64 { x: 4.0, y: 1.0 },
+            Point2Df64 { x: 4.0, y: 2.0 },
+            Point2Df64 { x: 5.0, y: 2.0 },
+        ])
+    }
+
+    #[test]
+    fn test_tecs_of_restricted_mtps() {
+        let point_set = test_point_set();
+        let algorithm = SiatecR {
+            r: 1,
+            min_pattern_size: 0,
+        };
+        let tecs = algorithm.compute_tecs(&point_set);
+        let siatec_tecs = Siatec {}.compute_tecs(&point_set);
+
+        let mut siar_patterns: Vec<Pattern<Point2Df64>> = SiaR::new(1)
+            .compute_mtps(&point_set)
+            .into_iter()
+            .map(|mtp| mtp.pattern.vectorize())
+            .collect();
+        siar_patterns.sort();
+        siar_patterns.dedup();
+
+        assert_eq!(siar_patterns.len(), tecs.len());
+        assert!(tecs.len() < siatec_tecs.len());
+        for tec in &tecs {
+            assert!(siar_patterns.contains(&tec.pattern.vectorize()));
+            // All translators are found, so the TEC is equivalent to a TEC computed by SIATEC.
+            assert!(siatec_tecs
+                .iter()
+                .any(|siatec_tec| siatec_tec.covered_set() == tec.covered_set()
+                    && siatec_tec.translationally_equivalent(tec)));
+        }
+    }
+}
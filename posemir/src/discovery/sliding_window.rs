@@ -0,0 +1,321 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::ops::ControlFlow;
+
+use crate::discovery::algorithm::TecAlgorithm;
+use crate::error::PoSemirError;
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// A TEC found by `SlidingWindow` together with the indices of the windows in which
+/// the pattern of the TEC was found (see `SlidingWindow::windows`).
+#[derive(Debug, Clone)]
+pub struct WindowedTec<T: Point> {
+    pub tec: Tec<T>,
+    pub windows: Vec<usize>,
+}
+
+/// Runs a TEC algorithm over overlapping time windows of a point set, so that long pieces
+/// can be analyzed with bounded memory and so that the discovered patterns are local.
+/// The TECs of each window are output as soon as they are computed, so only the points and
+/// the TECs of one window are kept in memory (see `compute_window_tecs_to_output`).
+///
+/// Optionally, the TECs found in the windows are merged across the windows as a post-step,
+/// so that each class of translationally equivalent patterns is output once with the
+/// translators found in all of the windows (see `with_merged_windows` and
+/// `Tec::merge_translationally_equivalent`). A class can gain translators in any later window,
+/// so the merging keeps the TECs of all windows in memory until the whole point set has been
+/// processed.
+///
+/// Time is assumed to be the first component of the points. Note that only occurrences
+/// that are within a single window can be found.
+pub struct SlidingWindow<T: Point, A: TecAlgorithm<T>> {
+    tec_algorithm: A,
+    window_length: f64,
+    hop: f64,
+    merge_windows: bool,
+    _t: PhantomData<T>,
+}
+
+impl<T: Point, A: TecAlgorithm<T>> TecAlgorithm<T> for SlidingWindow<T, A> {
+    fn compute_tecs(&self, point_set: &PointSet<T>) -> Vec<Tec<T>> {
+        let mut tecs = Vec::new();
+        self.compute_tecs_to_output(point_set, |tec| {
+            tecs.push(tec);
+            ControlFlow::Continue(())
+        });
+        tecs
+    }
+
+    fn compute_tecs_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(Tec<T>) -> ControlFlow<()>,
+    ) {
+        if !self.merge_windows {
+            self.compute_window_tecs_to_output(point_set, |_, tec| on_output(tec));
+            return;
+        }
+
+        for windowed in self.compute_windowed_tecs(point_set) {
+            if on_output(windowed.tec).is_break() {
+                return;
+            }
+        }
+    }
+}
+
+impl<T: Point, A: TecAlgorithm<T>> SlidingWindow<T, A> {
+    /// Creates a new sliding window driver for the given TEC algorithm. Returns an error
+    /// if the window length or the hop is not positive.
+    ///
+    /// # Arguments
+    ///
+    /// * `tec_algorithm` - The algorithm that is run in each window
+    /// * `window_length` - The length of the windows in time, must be positive
+    /// * `hop` - The time between the starts of successive windows, must be positive.
+    ///   The windows overlap if the hop is smaller than the window length.
+    pub fn with(
+        tec_algorithm: A,
+        window_length: f64,
+        hop: f64,
+    ) -> Result<SlidingWindow<T, A>, PoSemirError> {
+        for (name, value) in [("window length", window_length), ("hop", hop)] {
+            if value.is_nan() || value <= 0.0 {
+                return Err(PoSemirError::InvalidConfiguration(format!(
+                    "{} must be positive",
+                    name
+                )));
+            }
+        }
+
+        Ok(SlidingWindow {
+            tec_algorithm,
+            window_length,
+            hop,
+            merge_windows: false,
+            _t: Default::default(),
+        })
+    }
+
+    /// Returns the sliding window driver that merges the TECs across the windows before
+    /// outputting them as in `compute_windowed_tecs`, instead of outputting the TECs of each
+    /// window as they are computed.
+    pub fn with_merged_windows(self) -> SlidingWindow<T, A> {
+        SlidingWindow {
+            merge_windows: true,
+            ..self
+        }
+    }
+
+    /// Computes the TECs in each window and executes the callback on each TEC together with
+    /// the index of its window as soon as the TEC is computed. The TECs are not merged across
+    /// the windows, so only the points and the TECs of one window are kept in memory.
+    /// The computation stops when the callback returns `ControlFlow::Break`.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set for which the TECs are computed
+    /// * `on_output` - The function executed on each window index and TEC
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "sliding_window", skip_all, fields(n = point_set.len())))]
+    pub fn compute_window_tecs_to_output(
+        &self,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(usize, Tec<T>) -> ControlFlow<()>,
+    ) {
+        for (window_index, (start, end)) in self.windows(point_set).into_iter().enumerate() {
+            let window_points = point_set.slice_time(start, end);
+            if window_points.len() < 2 {
+                continue;
+            }
+
+            let mut stopped = false;
+            self.tec_algorithm
+                .compute_tecs_to_output(&window_points, |tec| {
+                    let flow = on_output(window_index, tec);
+                    stopped = flow.is_break();
+                    flow
+                });
+            if stopped {
+                return;
+            }
+        }
+    }
+
+    /// Returns the time windows used for the point set as half-open (start, end) ranges.
+    /// The first window starts at the first onset of the point set and the last window
+    /// contains the last onset.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set for which the windows are returned
+    pub fn windows(&self, point_set: &PointSet<T>) -> Vec<(f64, f64)> {
+        let mut windows = Vec::new();
+        if point_set.is_empty() {
+            return windows;
+        }
+
+        let first_onset = point_set[0].component_f64(0).unwrap();
+        let last_onset = point_set[point_set.len() - 1].component_f64(0).unwrap();
+
+        let mut i = 0;
+        loop {
+            let start = first_onset + i as f64 * self.hop;
+            let end = start + self.window_length;
+            windows.push((start, end));
+            if end > last_onset {
+                break;
+            }
+            i += 1;
+        }
+
+        windows
+    }
+
+    /// Computes the TECs in each window and returns the TECs merged across the windows,
+    /// tagged with the windows in which they were found. The TECs are in the order
+    /// in which they were first found. The TECs of all windows are kept in memory until
+    /// the merging.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set for which the TECs are computed
+    pub fn compute_windowed_tecs(&self, point_set: &PointSet<T>) -> Vec<WindowedTec<T>> {
+        let mut class_indices: HashMap<Pattern<T>, usize> = HashMap::new();
+        let mut classes: Vec<(Vec<Tec<T>>, Vec<usize>)> = Vec::new();
+
+        self.compute_window_tecs_to_output(point_set, |window_index, tec| {
+            if tec.pattern.is_empty() {
+                return ControlFlow::Continue(());
+            }
+
+            let key = tec.canonical().pattern.vectorize();
+            let index = *class_indices.entry(key).or_insert_with(|| {
+                classes.push((Vec::new(), Vec::new()));
+                classes.len() - 1
+            });
+
+            let (tecs, windows) = &mut classes[index];
+            tecs.push(tec);
+            if windows.last() != Some(&window_index) {
+                windows.push(window_index);
+            }
+            ControlFlow::Continue(())
+        });
+
+        classes
+            .into_iter()
+            .map(|(tecs, windows)| WindowedTec {
+                tec: Tec::merge_translationally_equivalent(&tecs).remove(0),
+                windows,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+
+    use crate::discovery::algorithm::TecAlgorithm;
+    use crate::discovery::siatec::Siatec;
+    use crate::discovery::sliding_window::SlidingWindow;
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+
+    fn test_point_set() -> PointSet<Point2Df64> {
+        // A two-note motif repeated every 4 beats.
+        let mut points = Vec::new();
+        for i in 0..4 {
+            let onset = i as f64 * 4.0;
+            points.push(Point2Df64 { x: onset, y: 60.0 });
+            points.push(Point2Df64 {
+                x: onset + 1.0,
+                y: 62.0,
+            });
+        }
+        PointSet::new(points)
+    }
+
+    #[test]
+    fn test_windows() {
+        let sliding_window = SlidingWindow::with(Siatec {}, 8.0, 4.0).unwrap();
+        assert_eq!(
+            vec![(0.0, 8.0), (4.0, 12.0), (8.0, 16.0)],
+            sliding_window.windows(&test_point_set())
+        );
+
+        assert!(SlidingWindow::<Point2Df64, _>::with(Siatec {}, 0.0, 4.0).is_err());
+        assert!(SlidingWindow::<Point2Df64, _>::with(Siatec {}, 8.0, -1.0).is_err());
+        assert!(SlidingWindow::<Point2Df64, _>::with(Siatec {}, 8.0, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_merges_tecs_across_windows() {
+        let point_set = test_point_set();
+        let sliding_window = SlidingWindow::with(Siatec {}, 8.0, 4.0).unwrap();
+        let windowed_tecs = sliding_window.compute_windowed_tecs(&point_set);
+
+        let motif = Pattern::new(&vec![
+            &Point2Df64 { x: 0.0, y: 60.0 },
+            &Point2Df64 { x: 1.0, y: 62.0 },
+        ]);
+        let motif_tec = windowed_tecs
+            .iter()
+            .find(|windowed| windowed.tec.pattern == motif)
+            .unwrap();
+
+        assert_eq!(vec![0, 1, 2], motif_tec.windows);
+        assert_eq!(point_set, motif_tec.tec.covered_set());
+        assert_eq!(
+            windowed_tecs.len(),
+            sliding_window
+                .with_merged_windows()
+                .compute_tecs(&point_set)
+                .len()
+        );
+    }
+
+    #[test]
+    fn test_streams_tecs_per_window() {
+        let point_set = test_point_set();
+        let sliding_window = SlidingWindow::with(Siatec {}, 8.0, 4.0).unwrap();
+
+        let mut window_indices = Vec::new();
+        let mut tecs = Vec::new();
+        sliding_window.compute_window_tecs_to_output(&point_set, |window_index, tec| {
+            window_indices.push(window_index);
+            tecs.push(tec);
+            ControlFlow::Continue(())
+        });
+
+        let mut expected = Vec::new();
+        for (start, end) in sliding_window.windows(&point_set) {
+            expected.extend(Siatec {}.compute_tecs(&point_set.slice_time(start, end)));
+        }
+        assert_eq!(expected, tecs);
+        assert_eq!(tecs, sliding_window.compute_tecs(&point_set));
+        assert!(window_indices.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(Some(&2), window_indices.last());
+
+        let mut first_window = Vec::new();
+        sliding_window.compute_window_tecs_to_output(&point_set, |window_index, tec| {
+            if window_index > 0 {
+                return ControlFlow::Break(());
+            }
+            first_window.push(tec);
+            ControlFlow::Continue(())
+        });
+        assert_eq!(
+            window_indices.iter().filter(|i| **i == 0).count(),
+            first_window.len()
+        );
+    }
+}
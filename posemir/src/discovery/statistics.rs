@@ -0,0 +1,252 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// Collects statistics of the TECs output by a discovery algorithm while they are streamed
+/// through `compute_tecs_to_output`, so that a run can be summarized without storing all of
+/// the TECs. The collected statistics are the number of TECs, histograms of the pattern sizes
+/// and occurrence counts, the growth of the coverage of the point set, and the distribution
+/// of the translators.
+///
+/// TECs are added with `record`, which can be called in the output callback
+/// of the algorithm, e.g., `|tec| statistics.record(&tec)`.
+#[derive(Debug, Clone)]
+pub struct DiscoveryStatistics<'a, T: Point> {
+    point_set: &'a PointSet<T>,
+    pattern_sizes: BTreeMap<usize, usize>,
+    occurrence_counts: BTreeMap<usize, usize>,
+    translator_counts: HashMap<T, usize>,
+    is_covered: Vec<bool>,
+    covered_count: usize,
+    coverage_growth: Vec<usize>,
+}
+
+impl<'a, T: Point> DiscoveryStatistics<'a, T> {
+    /// Returns a new collector for TECs in the given point set.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set in which the recorded TECs occur
+    pub fn new(point_set: &'a PointSet<T>) -> DiscoveryStatistics<'a, T> {
+        DiscoveryStatistics {
+            point_set,
+            pattern_sizes: BTreeMap::new(),
+            occurrence_counts: BTreeMap::new(),
+            translator_counts: HashMap::new(),
+            is_covered: vec![false; point_set.len()],
+            covered_count: 0,
+            coverage_growth: Vec::new(),
+        }
+    }
+
+    /// Adds the TEC to the statistics.
+    ///
+    /// # Arguments
+    ///
+    /// * `tec` - The TEC to add
+    pub fn record(&mut self, tec: &Tec<T>) {
+        *self.pattern_sizes.entry(tec.pattern.len()).or_insert(0) += 1;
+        *self
+            .occurrence_counts
+            .entry(tec.occurrence_count())
+            .or_insert(0) += 1;
+
+        for translator in &tec.translators {
+            *self.translator_counts.entry(*translator).or_insert(0) += 1;
+        }
+
+        for point in &tec.covered_set() {
+            if let Ok(i) = self.point_set.find_index(point) {
+                if !self.is_covered[i] {
+                    self.is_covered[i] = true;
+                    self.covered_count += 1;
+                }
+            }
+        }
+        self.coverage_growth.push(self.covered_count);
+    }
+
+    /// Returns the number of recorded TECs.
+    pub fn tec_count(&self) -> usize {
+        self.coverage_growth.len()
+    }
+
+    /// Returns the number of recorded TECs by pattern size.
+    pub fn pattern_size_histogram(&self) -> &BTreeMap<usize, usize> {
+        &self.pattern_sizes
+    }
+
+    /// Returns the number of recorded TECs by number of occurrences.
+    pub fn occurrence_count_histogram(&self) -> &BTreeMap<usize, usize> {
+        &self.occurrence_counts
+    }
+
+    /// Returns the number of points in the point set covered by the TECs recorded so far,
+    /// after each recorded TEC.
+    pub fn coverage_growth(&self) -> &[usize] {
+        &self.coverage_growth
+    }
+
+    /// Returns the fraction of the point set covered by the recorded TECs.
+    pub fn coverage_ratio(&self) -> f64 {
+        if self.point_set.is_empty() {
+            return 0.0;
+        }
+        self.covered_count as f64 / self.point_set.len() as f64
+    }
+
+    /// Returns the number of distinct translators in the recorded TECs.
+    pub fn distinct_translator_count(&self) -> usize {
+        self.translator_counts.len()
+    }
+
+    /// Returns at most the given number of the most common translators in the recorded TECs
+    /// with the number of TECs in which they occur. The translators are in descending order
+    /// of frequency, and translators with equal frequencies are in ascending order.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The maximum number of translators to return
+    pub fn most_common_translators(&self, count: usize) -> Vec<(T, usize)> {
+        let mut translators: Vec<(T, usize)> = self
+            .translator_counts
+            .iter()
+            .map(|(translator, frequency)| (*translator, *frequency))
+            .collect();
+        translators.sort_by_key(|(translator, frequency)| (Reverse(*frequency), *translator));
+        translators.truncate(count);
+        translators
+    }
+
+    /// Returns the statistics as JSON, with the translators limited to the given number
+    /// of most common ones:
+    /// ```json
+    /// {
+    ///   "tec_count": 3,
+    ///   "coverage_ratio": 1.0,
+    ///   "pattern_sizes": { "1": 1, "2": 2 },
+    ///   "occurrence_counts": { "2": 2, "4": 1 },
+    ///   "coverage_growth": [4, 4, 6],
+    ///   "distinct_translators": 5,
+    ///   "translators": [ { "translator": [1.0, 0.0], "count": 2 }, ... ]
+    /// }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `translator_count` - The maximum number of translators to include
+    #[cfg(feature = "io")]
+    pub fn to_json(&self, translator_count: usize) -> serde_json::Value {
+        use serde_json::json;
+
+        let translators: Vec<serde_json::Value> = self
+            .most_common_translators(translator_count)
+            .iter()
+            .map(|(translator, count)| {
+                let components: Vec<f64> = (0..translator.dimensionality())
+                    .map(|i| translator.component_f64(i).unwrap())
+                    .collect();
+                json!({ "translator": components, "count": count })
+            })
+            .collect();
+
+        json!({
+            "tec_count": self.tec_count(),
+            "coverage_ratio": self.coverage_ratio(),
+            "pattern_sizes": self.pattern_sizes,
+            "occurrence_counts": self.occurrence_counts,
+            "coverage_growth": self.coverage_growth,
+            "distinct_translators": self.distinct_translator_count(),
+            "translators": translators
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+
+    use crate::discovery::algorithm::TecAlgorithm;
+    use crate::discovery::siatec::Siatec;
+    use crate::discovery::statistics::DiscoveryStatistics;
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+    use crate::point_set::tec::Tec;
+
+    #[test]
+    fn test_record() {
+        let a = Point2Df64 { x: 0.0, y: 0.0 };
+        let b = Point2Df64 { x: 1.0, y: 0.0 };
+        let c = Point2Df64 { x: 2.0, y: 0.0 };
+        let d = Point2Df64 { x: 5.0, y: 1.0 };
+        let point_set = PointSet::new(vec![a, b, c, d]);
+
+        let mut statistics = DiscoveryStatistics::new(&point_set);
+        statistics.record(&Tec {
+            pattern: Pattern::new(&vec![&a, &b]),
+            translators: vec![b - a],
+        });
+        statistics.record(&Tec {
+            pattern: Pattern::new(&vec![&a]),
+            translators: vec![b - a, c - a, d - a],
+        });
+
+        assert_eq!(2, statistics.tec_count());
+        assert_eq!(&[3, 4], statistics.coverage_growth());
+        assert_eq!(1.0, statistics.coverage_ratio());
+        assert_eq!(Some(&1), statistics.pattern_size_histogram().get(&2));
+        assert_eq!(Some(&1), statistics.occurrence_count_histogram().get(&4));
+        assert_eq!(3, statistics.distinct_translator_count());
+        assert_eq!(vec![(b - a, 2)], statistics.most_common_translators(1));
+    }
+
+    #[test]
+    fn test_record_streamed_tecs() {
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 0.0, y: 0.0 },
+            Point2Df64 { x: 1.0, y: 0.0 },
+            Point2Df64 { x: 2.0, y: 0.0 },
+        ]);
+
+        let mut tecs = Vec::new();
+        let mut statistics = DiscoveryStatistics::new(&point_set);
+        Siatec {}.compute_tecs_to_output(&point_set, |tec| {
+            statistics.record(&tec);
+            tecs.push(tec);
+            ControlFlow::Continue(())
+        });
+
+        assert_eq!(tecs.len(), statistics.tec_count());
+        assert_eq!(1.0, statistics.coverage_ratio());
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn test_to_json() {
+        let a = Point2Df64 { x: 0.0, y: 0.0 };
+        let b = Point2Df64 { x: 1.0, y: 0.0 };
+        let point_set = PointSet::new(vec![a, b]);
+
+        let mut statistics = DiscoveryStatistics::new(&point_set);
+        statistics.record(&Tec {
+            pattern: Pattern::new(&vec![&a]),
+            translators: vec![b - a],
+        });
+
+        let json = statistics.to_json(10);
+        assert_eq!(1, json["tec_count"]);
+        assert_eq!(1, json["pattern_sizes"]["1"]);
+        assert_eq!(
+            serde_json::json!([1.0, 0.0]),
+            json["translators"][0]["translator"]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::point_set::mtp::Mtp;
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// Removes the patterns that are subsumed by other patterns, that is, patterns that have a
+/// translated copy that is a subset of another pattern. For example, the output of SIA is
+/// dominated by such patterns, as the subsets of large MTPs are often MTPs for other translators.
+/// Of translationally equivalent patterns, only the first one is retained.
+///
+/// The patterns are processed in descending order of size, and each pattern is compared only
+/// to the retained patterns that have the difference between its first two points. For this, the
+/// differences between all pairs of points in the retained patterns are indexed, so the index
+/// grows quadratically with the size of the retained patterns.
+pub struct SubsumptionFilter {}
+
+impl SubsumptionFilter {
+    /// Returns the indices of the patterns that are not subsumed by any other pattern,
+    /// in ascending order.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns` - The patterns to filter
+    pub fn maximal<T: Point>(&self, patterns: &[&Pattern<T>]) -> Vec<usize> {
+        let point_sets: Vec<PointSet<T>> = patterns
+            .iter()
+            .map(|pattern| pattern.to_point_set())
+            .collect();
+
+        let mut order: Vec<usize> = (0..patterns.len()).collect();
+        order.sort_by_key(|i| Reverse(point_sets[*i].len()));
+
+        // The differences between the points of the retained patterns, with the index of
+        // the pattern and the index of the point from which the difference is computed.
+        let mut diff_index: HashMap<T, Vec<(usize, usize)>> = HashMap::new();
+        let mut maximal = Vec::new();
+
+        for i in order {
+            let point_set = &point_sets[i];
+            if SubsumptionFilter::is_subsumed(point_set, &point_sets, &maximal, &diff_index) {
+                continue;
+            }
+
+            for k in 0..point_set.len() {
+                for j in (k + 1)..point_set.len() {
+                    diff_index
+                        .entry(point_set[j] - point_set[k])
+                        .or_default()
+                        .push((i, k));
+                }
+            }
+            maximal.push(i);
+        }
+
+        maximal.sort();
+        maximal
+    }
+
+    /// Returns the TECs whose patterns are not subsumed by the patterns of other TECs.
+    /// The order of the TECs is retained.
+    ///
+    /// # Arguments
+    ///
+    /// * `tecs` - The TECs to filter
+    pub fn filter_tecs<T: Point>(&self, tecs: Vec<Tec<T>>) -> Vec<Tec<T>> {
+        let patterns: Vec<&Pattern<T>> = tecs.iter().map(|tec| &tec.pattern).collect();
+        let maximal = self.maximal(&patterns);
+        SubsumptionFilter::retain(tecs, &maximal)
+    }
+
+    /// Returns the MTPs whose patterns are not subsumed by the patterns of other MTPs.
+    /// The order of the MTPs is retained.
+    ///
+    /// # Arguments
+    ///
+    /// * `mtps` - The MTPs to filter, e.g., the output of SIA
+    pub fn filter_mtps<T: Point>(&self, mtps: Vec<Mtp<T>>) -> Vec<Mtp<T>> {
+        let patterns: Vec<&Pattern<T>> = mtps.iter().map(|mtp| &mtp.pattern).collect();
+        let maximal = self.maximal(&patterns);
+        SubsumptionFilter::retain(mtps, &maximal)
+    }
+
+    /// Returns true if a translated copy of the pattern is a subset of one of the retained
+    /// patterns. The retained patterns are at least as large as the pattern.
+    fn is_subsumed<T: Point>(
+        pattern: &PointSet<T>,
+        patterns: &[PointSet<T>],
+        retained: &[usize],
+        diff_index: &HashMap<T, Vec<(usize, usize)>>,
+    ) -> bool {
+        if pattern.len() < 2 {
+            return !retained.is_empty();
+        }
+
+        let origin = pattern[0];
+        let candidates = match diff_index.get(&(pattern[1] - origin)) {
+            Some(candidates) => candidates,
+            None => return false,
+        };
+
+        candidates.iter().any(|(other_index, k)| {
+            let other = &patterns[*other_index];
+            let translator = other[*k] - origin;
+            pattern
+                .into_iter()
+                .skip(2)
+                .all(|point| other.contains(&(*point + translator)))
+        })
+    }
+
+    /// Returns the items at the given ascending indices.
+    fn retain<I>(items: Vec<I>, indices: &[usize]) -> Vec<I> {
+        let mut indices = indices.iter().peekable();
+        items
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| indices.next_if_eq(&i).is_some())
+            .map(|(_, item)| item)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::algorithm::MtpAlgorithm;
+    use crate::discovery::sia::Sia;
+    use crate::discovery::subsumption_filter::SubsumptionFilter;
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+
+    fn pattern(points: &[(f64, f64)]) -> Pattern<Point2Df64> {
+        let points: Vec<Point2Df64> = points
+            .iter()
+            .map(|(x, y)| Point2Df64 { x: *x, y: *y })
+            .collect();
+        Pattern::new(&points.iter().collect())
+    }
+
+    #[test]
+    fn test_maximal() {
+        let motif = pattern(&[(0.0, 60.0), (1.0, 62.0), (2.0, 64.0), (3.0, 65.0)]);
+        // A transposed subset of the motif.
+        let fragment = pattern(&[(9.0, 69.0), (8.0, 67.0), (11.0, 72.0)]);
+        let other = pattern(&[(0.0, 40.0), (4.0, 30.0)]);
+        let duplicate = pattern(&[(4.0, 40.0), (8.0, 30.0)]);
+        let single = pattern(&[(5.0, 50.0)]);
+
+        let filter = SubsumptionFilter {};
+        assert_eq!(
+            vec![1, 2],
+            filter.maximal(&[&fragment, &motif, &other, &duplicate, &single])
+        );
+        assert_eq!(vec![0], filter.maximal(&[&single]));
+    }
+
+    #[test]
+    fn test_filter_sia_output() {
+        let mut points = Vec::new();
+        for i in 0..12 {
+            points.push(Point2Df64 {
+                x: i as f64,
+                y: ((i * 5) % 7) as f64,
+            });
+        }
+        let point_set = PointSet::new(points);
+        let mtps = Sia::new().compute_mtps(&point_set);
+        let filtered = SubsumptionFilter {}.filter_mtps(Sia::new().compute_mtps(&point_set));
+        assert!(!filtered.is_empty() && filtered.len() < mtps.len());
+
+        // Compare to the pairwise check of the subsumption.
+        let is_subsumed_by = |a: &Pattern<Point2Df64>, b: &Pattern<Point2Df64>| {
+            let b = b.to_point_set();
+            b.into_iter().any(|origin| {
+                let translator = *origin - a[0];
+                a.into_iter().all(|p| b.contains(&(*p + translator)))
+            })
+        };
+        for (i, mtp) in mtps.iter().enumerate() {
+            let subsumed = mtps.iter().enumerate().any(|(j, other)| {
+                i != j
+                    && is_subsumed_by(&mtp.pattern, &other.pattern)
+                    && (other.pattern.len() > mtp.pattern.len() || j < i)
+            });
+            assert_eq!(!subsumed, filtered.contains(mtp));
+        }
+    }
+}
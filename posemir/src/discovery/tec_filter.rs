@@ -0,0 +1,121 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use crate::discovery::compactness::{compactness, CompactnessRegion};
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// Predicates for filtering TECs. Filters can be combined by applying several of them
+/// with `apply_filters`, in which case a TEC must be accepted by all of the filters.
+#[derive(Debug, Clone)]
+pub enum TecFilter<'a, T: Point> {
+    /// Accepts TECs whose pattern has at least the given number of points.
+    MinPatternSize(usize),
+    /// Accepts TECs with at least the given number of occurrences (including the pattern itself).
+    MinOccurrences(usize),
+    /// Accepts TECs whose pattern spans at most the given time (see `Tec::time_span`).
+    MaxTimeSpan(f64),
+    /// Accepts TECs whose bounding box compactness in the point set is at least
+    /// the given minimum (see `compactness::compactness`).
+    MinCompactness {
+        min: f64,
+        point_set: &'a PointSet<T>,
+    },
+}
+
+impl<'a, T: Point> TecFilter<'a, T> {
+    /// Returns true if the TEC is accepted by this filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `tec` - The TEC to test
+    pub fn accepts(&self, tec: &Tec<T>) -> bool {
+        match self {
+            TecFilter::MinPatternSize(min) => tec.pattern.len() >= *min,
+            TecFilter::MinOccurrences(min) => tec.occurrence_count() >= *min,
+            TecFilter::MaxTimeSpan(max) => tec.time_span() <= *max,
+            TecFilter::MinCompactness { min, point_set } => {
+                compactness(tec, point_set, CompactnessRegion::BoundingBox) >= *min
+            }
+        }
+    }
+}
+
+/// Returns true if the TEC is accepted by all of the given filters.
+///
+/// # Arguments
+///
+/// * `tec` - The TEC to test
+/// * `filters` - The filters that must all accept the TEC
+pub fn accepts_all<T: Point>(tec: &Tec<T>, filters: &[TecFilter<T>]) -> bool {
+    filters.iter().all(|filter| filter.accepts(tec))
+}
+
+/// Returns the TECs that are accepted by all of the given filters.
+/// The order of the TECs is retained.
+///
+/// # Arguments
+///
+/// * `tecs` - The TECs to filter
+/// * `filters` - The filters that must all accept a TEC for it to be retained
+pub fn apply_filters<T: Point>(tecs: Vec<Tec<T>>, filters: &[TecFilter<T>]) -> Vec<Tec<T>> {
+    tecs.into_iter()
+        .filter(|tec| accepts_all(tec, filters))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::tec_filter::{apply_filters, TecFilter};
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+    use crate::point_set::tec::Tec;
+
+    #[test]
+    fn test_apply_filters() {
+        let a = Point2Df64 { x: 0.0, y: 0.0 };
+        let b = Point2Df64 { x: 1.0, y: 0.0 };
+        let c = Point2Df64 { x: 1.0, y: 1.0 };
+        let d = Point2Df64 { x: 4.0, y: 0.0 };
+        let point_set = PointSet::new(vec![a, b, c, d]);
+
+        let small = Tec {
+            pattern: Pattern::new(&vec![&a]),
+            translators: vec![b - a, d - a],
+        };
+        let wide = Tec {
+            pattern: Pattern::new(&vec![&a, &d]),
+            translators: vec![],
+        };
+        let compact = Tec {
+            pattern: Pattern::new(&vec![&a, &b]),
+            translators: vec![],
+        };
+        let tecs = vec![small.clone(), wide.clone(), compact.clone()];
+
+        let filtered = apply_filters(tecs.clone(), &[TecFilter::MinPatternSize(2)]);
+        assert_eq!(vec![wide.clone(), compact.clone()], filtered);
+
+        let filtered = apply_filters(
+            tecs.clone(),
+            &[TecFilter::MinPatternSize(2), TecFilter::MaxTimeSpan(2.0)],
+        );
+        assert_eq!(vec![compact.clone()], filtered);
+
+        let filtered = apply_filters(tecs.clone(), &[TecFilter::MinOccurrences(2)]);
+        assert_eq!(vec![small], filtered);
+
+        let filtered = apply_filters(
+            tecs,
+            &[TecFilter::MinCompactness {
+                min: 1.0,
+                point_set: &point_set,
+            }],
+        );
+        assert!(filtered.contains(&compact));
+        assert!(!filtered.contains(&wide));
+    }
+}
@@ -0,0 +1,261 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::cmp::Ordering;
+
+use crate::discovery::scored_tec::TecScores;
+
+/// Trait for the selection criteria of COSIATEC and SIATECCompress, which select the
+/// TECs that are better than the other TECs according to the ranker.
+pub trait TecRanker {
+    /// Returns true if the TEC with the scores `a` should be selected over the TEC
+    /// with the scores `b`.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The scores of the first TEC
+    /// * `b` - The scores of the second TEC
+    fn is_better_than(&self, a: &TecScores, b: &TecScores) -> bool;
+}
+
+/// The ranker used by default in COSIATEC and SIATECCompress. A TEC is better than the other
+/// if any of its compression ratio, compactness, coverage or pattern size is greater, or if
+/// its pattern width or pattern area is smaller, checked in that order (see [Meredith2013]).
+/// The coverage is weighted by the weights of the points, so with weighted points, e.g.,
+/// `Point2DWeighted` with note durations, TECs covering long notes are preferred. Similarly,
+/// with note velocities as weights (see `midi::midi_to_weighted_point_set`), TECs covering
+/// accented notes are preferred.
+pub struct DefaultRanker {}
+
+impl TecRanker for DefaultRanker {
+    fn is_better_than(&self, a: &TecScores, b: &TecScores) -> bool {
+        a.compression_ratio > b.compression_ratio
+            || a.compactness > b.compactness
+            || a.weighted_coverage > b.weighted_coverage
+            || a.pattern_size > b.pattern_size
+            || a.pattern_width < b.pattern_width
+            || a.pattern_area < b.pattern_area
+    }
+}
+
+/// The ranker of COSIATEC in [Meredith2013], which compares the compression ratio, the
+/// compactness and the coverage of the TECs in that order, so that the first of them by which
+/// the TECs differ decides which one is better. Unlike `DefaultRanker`, this ranker is
+/// asymmetric, so the selected TECs do not depend on the order in which they are found.
+pub struct MeredithRanker {}
+
+impl MeredithRanker {
+    const PRIORITIES: [RankingCriterion; 3] = [
+        RankingCriterion::CompressionRatio,
+        RankingCriterion::Compactness,
+        RankingCriterion::Coverage,
+    ];
+}
+
+impl TecRanker for MeredithRanker {
+    fn is_better_than(&self, a: &TecScores, b: &TecScores) -> bool {
+        is_lexicographically_better(&MeredithRanker::PRIORITIES, a, b)
+    }
+}
+
+/// The criteria by which TECs can be ranked.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RankingCriterion {
+    /// Greater compression ratio is better.
+    CompressionRatio,
+    /// Greater bounding box compactness is better.
+    Compactness,
+    /// Greater number of covered points is better.
+    Coverage,
+    /// Greater sum of the weights, e.g., the saliences, of the covered points is better.
+    WeightedCoverage,
+    /// Greater number of points in the pattern is better.
+    PatternSize,
+    /// Smaller pattern width, i.e., greater temporal compactness, is better.
+    PatternWidth,
+    /// Smaller pattern bounding box area is better.
+    PatternArea,
+}
+
+impl RankingCriterion {
+    /// Returns the value of this criterion in the scores, so that greater values are better.
+    fn value(&self, scores: &TecScores) -> f64 {
+        match self {
+            RankingCriterion::CompressionRatio => scores.compression_ratio,
+            RankingCriterion::Compactness => scores.compactness,
+            RankingCriterion::Coverage => scores.coverage as f64,
+            RankingCriterion::WeightedCoverage => scores.weighted_coverage,
+            RankingCriterion::PatternSize => scores.pattern_size as f64,
+            RankingCriterion::PatternWidth => -scores.pattern_width,
+            RankingCriterion::PatternArea => -scores.pattern_area,
+        }
+    }
+}
+
+/// Ranks TECs by a priority list of criteria: the first criterion by which the TECs differ
+/// decides which of them is better.
+pub struct LexicographicRanker {
+    priorities: Vec<RankingCriterion>,
+}
+
+impl TecRanker for LexicographicRanker {
+    fn is_better_than(&self, a: &TecScores, b: &TecScores) -> bool {
+        is_lexicographically_better(&self.priorities, a, b)
+    }
+}
+
+fn is_lexicographically_better(
+    priorities: &[RankingCriterion],
+    a: &TecScores,
+    b: &TecScores,
+) -> bool {
+    for criterion in priorities {
+        match criterion.value(a).partial_cmp(&criterion.value(b)) {
+            Some(Ordering::Greater) => return true,
+            Some(Ordering::Less) => return false,
+            _ => {}
+        }
+    }
+
+    false
+}
+
+/// Ranks TECs by the weighted sum of the values of the criteria, where the pattern width
+/// and area are negated so that greater sums are always better.
+pub struct WeightedRanker {
+    weights: Vec<(RankingCriterion, f64)>,
+}
+
+impl WeightedRanker {
+    fn score(&self, scores: &TecScores) -> f64 {
+        self.weights
+            .iter()
+            .map(|(criterion, weight)| weight * criterion.value(scores))
+            .sum()
+    }
+}
+
+impl TecRanker for WeightedRanker {
+    fn is_better_than(&self, a: &TecScores, b: &TecScores) -> bool {
+        self.score(a) > self.score(b)
+    }
+}
+
+/// Builder for custom TEC rankers. The criteria are added in priority order together with
+/// their weights, and the ranker is built either as a lexicographic ranker, which uses only
+/// the priority order, or as a weighted ranker, which uses only the weights.
+///
+/// For example, temporally compact patterns can be prioritized for motif discovery with:
+/// `TecRankerBuilder::new().criterion(RankingCriterion::PatternWidth, 1.0)
+///     .criterion(RankingCriterion::CompressionRatio, 1.0).lexicographic()`.
+#[derive(Debug, Clone, Default)]
+pub struct TecRankerBuilder {
+    criteria: Vec<(RankingCriterion, f64)>,
+}
+
+impl TecRankerBuilder {
+    /// Returns a builder with no criteria.
+    pub fn new() -> TecRankerBuilder {
+        TecRankerBuilder::default()
+    }
+
+    /// Adds a criterion with the given weight after the already added criteria.
+    ///
+    /// # Arguments
+    ///
+    /// * `criterion` - The criterion that is added
+    /// * `weight` - The weight of the criterion in weighted rankers
+    pub fn criterion(mut self, criterion: RankingCriterion, weight: f64) -> TecRankerBuilder {
+        self.criteria.push((criterion, weight));
+        self
+    }
+
+    /// Returns a ranker that compares the criteria in the order they were added.
+    pub fn lexicographic(self) -> LexicographicRanker {
+        LexicographicRanker {
+            priorities: self
+                .criteria
+                .into_iter()
+                .map(|(criterion, _)| criterion)
+                .collect(),
+        }
+    }
+
+    /// Returns a ranker that compares the weighted sums of the criteria.
+    pub fn weighted(self) -> WeightedRanker {
+        WeightedRanker {
+            weights: self.criteria,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::scored_tec::TecScores;
+    use crate::discovery::tec_ranker::{
+        DefaultRanker, MeredithRanker, RankingCriterion, TecRanker, TecRankerBuilder,
+    };
+
+    fn scores(compression_ratio: f64, pattern_width: f64) -> TecScores {
+        TecScores {
+            compression_ratio,
+            compactness: 1.0,
+            coverage: 6,
+            weighted_coverage: 6.0,
+            pattern_size: 3,
+            pattern_width,
+            pattern_area: pattern_width * 2.0,
+        }
+    }
+
+    #[test]
+    fn test_lexicographic_ranker() {
+        let wide = scores(2.0, 8.0);
+        let narrow = scores(1.5, 2.0);
+
+        let ranker = TecRankerBuilder::new()
+            .criterion(RankingCriterion::PatternWidth, 1.0)
+            .criterion(RankingCriterion::CompressionRatio, 1.0)
+            .lexicographic();
+        assert!(ranker.is_better_than(&narrow, &wide));
+        assert!(!ranker.is_better_than(&wide, &narrow));
+        assert!(!ranker.is_better_than(&wide, &wide));
+
+        assert!(DefaultRanker {}.is_better_than(&wide, &narrow));
+    }
+
+    #[test]
+    fn test_meredith_ranker() {
+        let wide = scores(2.0, 8.0);
+        let narrow = scores(1.5, 2.0);
+        assert!(MeredithRanker {}.is_better_than(&wide, &narrow));
+        assert!(!MeredithRanker {}.is_better_than(&narrow, &wide));
+        // The default ranker prefers either TEC over the other.
+        assert!(DefaultRanker {}.is_better_than(&narrow, &wide));
+
+        let mut less_compact = wide;
+        less_compact.compactness = 0.5;
+        less_compact.coverage = 10;
+        assert!(MeredithRanker {}.is_better_than(&wide, &less_compact));
+        assert!(!MeredithRanker {}.is_better_than(&wide, &wide));
+    }
+
+    #[test]
+    fn test_weighted_ranker() {
+        let wide = scores(2.0, 8.0);
+        let narrow = scores(1.5, 2.0);
+
+        let compression = TecRankerBuilder::new()
+            .criterion(RankingCriterion::CompressionRatio, 10.0)
+            .criterion(RankingCriterion::PatternWidth, 0.1)
+            .weighted();
+        assert!(compression.is_better_than(&wide, &narrow));
+
+        let width = TecRankerBuilder::new()
+            .criterion(RankingCriterion::CompressionRatio, 1.0)
+            .criterion(RankingCriterion::PatternWidth, 1.0)
+            .weighted();
+        assert!(width.is_better_than(&narrow, &wide));
+    }
+}
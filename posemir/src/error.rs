@@ -0,0 +1,130 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::num::{ParseFloatError, ParseIntError};
+
+use crate::point_set::point::Point;
+
+/// The error type of the fallible operations of the crate, e.g., reading and writing files,
+/// creating algorithms by name and validating point sets before computing patterns.
+#[derive(Debug)]
+pub enum PoSemirError {
+    /// Reading or writing a file failed.
+    Io(std::io::Error),
+    /// Reading a CSV file failed.
+    #[cfg(feature = "io")]
+    Csv(csv::Error),
+    /// Reading or writing JSON failed.
+    #[cfg(feature = "io")]
+    Json(serde_json::Error),
+    /// A value could not be parsed as a number.
+    InvalidNumber(String),
+    /// A value is missing at the given column of a row.
+    MissingValue(usize),
+    /// The CSV header has no column with the given name.
+    MissingColumn(String),
+    /// The pattern JSON does not have the expected structure.
+    InvalidJson(String),
+    /// The pattern CSV does not have the expected structure.
+    InvalidCsv(String),
+    /// The MIDI data is malformed or uses unsupported features.
+    InvalidMidi(String),
+    /// The MEI data is malformed or uses unsupported features.
+    InvalidMei(String),
+    /// The ABC data is malformed or uses unsupported features.
+    InvalidAbc(String),
+    /// The analysis session file is malformed or written with an unsupported version.
+    InvalidSession(String),
+    /// A point does not have the component at the given index.
+    MissingComponent(usize),
+    /// The point set cannot be processed, e.g., because a point has a NaN component.
+    InvalidPointSet(String),
+    /// The name or the parameters of an algorithm are invalid.
+    InvalidConfiguration(String),
+}
+
+impl Display for PoSemirError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PoSemirError::Io(error) => write!(f, "IO error: {}", error),
+            #[cfg(feature = "io")]
+            PoSemirError::Csv(error) => write!(f, "CSV error: {}", error),
+            #[cfg(feature = "io")]
+            PoSemirError::Json(error) => write!(f, "JSON error: {}", error),
+            PoSemirError::InvalidNumber(message) => write!(f, "Invalid number: {}", message),
+            PoSemirError::MissingValue(column) => write!(f, "Value missing at column {}", column),
+            PoSemirError::MissingColumn(name) => write!(f, "No column named {} in header", name),
+            PoSemirError::InvalidJson(message) => write!(f, "Invalid pattern JSON: {}", message),
+            PoSemirError::InvalidCsv(message) => write!(f, "Invalid pattern CSV: {}", message),
+            PoSemirError::InvalidMidi(message) => write!(f, "Invalid MIDI file: {}", message),
+            PoSemirError::InvalidMei(message) => write!(f, "Invalid MEI file: {}", message),
+            PoSemirError::InvalidAbc(message) => write!(f, "Invalid ABC file: {}", message),
+            PoSemirError::InvalidSession(message) => {
+                write!(f, "Invalid session file: {}", message)
+            }
+            PoSemirError::MissingComponent(index) => {
+                write!(f, "Point has no component at index {}", index)
+            }
+            PoSemirError::InvalidPointSet(message) => write!(f, "Invalid point set: {}", message),
+            PoSemirError::InvalidConfiguration(message) => {
+                write!(f, "Invalid algorithm configuration: {}", message)
+            }
+        }
+    }
+}
+
+impl Error for PoSemirError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PoSemirError::Io(error) => Some(error),
+            #[cfg(feature = "io")]
+            PoSemirError::Csv(error) => Some(error),
+            #[cfg(feature = "io")]
+            PoSemirError::Json(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the component of the point at the given index, or an error if the point
+/// has no such component.
+pub(crate) fn component_f64<T: Point>(point: &T, index: usize) -> Result<f64, PoSemirError> {
+    point
+        .component_f64(index)
+        .ok_or(PoSemirError::MissingComponent(index))
+}
+
+impl From<std::io::Error> for PoSemirError {
+    fn from(error: std::io::Error) -> Self {
+        PoSemirError::Io(error)
+    }
+}
+
+#[cfg(feature = "io")]
+impl From<csv::Error> for PoSemirError {
+    fn from(error: csv::Error) -> Self {
+        PoSemirError::Csv(error)
+    }
+}
+
+#[cfg(feature = "io")]
+impl From<serde_json::Error> for PoSemirError {
+    fn from(error: serde_json::Error) -> Self {
+        PoSemirError::Json(error)
+    }
+}
+
+impl From<ParseFloatError> for PoSemirError {
+    fn from(error: ParseFloatError) -> Self {
+        PoSemirError::InvalidNumber(error.to_string())
+    }
+}
+
+impl From<ParseIntError> for PoSemirError {
+    fn from(error: ParseIntError) -> Self {
+        PoSemirError::InvalidNumber(error.to_string())
+    }
+}
@@ -0,0 +1,364 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::collections::HashSet;
+#[cfg(feature = "io")]
+use std::path::Path;
+
+#[cfg(feature = "io")]
+use crate::error::PoSemirError;
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+#[cfg(feature = "io")]
+use crate::point_set::point::Point2DRf64;
+use crate::point_set::tec::Tec;
+
+/// The occurrence threshold used in the MIREX Discovery of Repeated Themes & Sections task.
+pub const DEFAULT_OCCURRENCE_THRESHOLD: f64 = 0.75;
+
+/// An annotated repeated pattern with all of its occurrences.
+#[derive(Debug, Clone)]
+pub struct GroundTruthPattern<T: Point> {
+    /// The name of the pattern in the annotations.
+    pub name: String,
+    /// The annotated occurrences of the pattern.
+    pub occurrences: Vec<Pattern<T>>,
+}
+
+/// The evaluation measures for discovered patterns (see [Collins 2013] and the MIREX
+/// Discovery of Repeated Themes & Sections task).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EvaluationScores {
+    /// How well the discovered patterns correspond to some ground truth pattern.
+    pub establishment_precision: f64,
+    /// How well the ground truth patterns are established by some discovered pattern.
+    pub establishment_recall: f64,
+    /// The harmonic mean of the establishment precision and recall.
+    pub establishment_f1: f64,
+    /// How well the occurrences of the discovered patterns correspond to the occurrences
+    /// of the ground truth patterns they establish.
+    pub occurrence_precision: f64,
+    /// How well the occurrences of the established ground truth patterns are found.
+    pub occurrence_recall: f64,
+    /// The harmonic mean of the occurrence precision and recall.
+    pub occurrence_f1: f64,
+}
+
+/// Computes the establishment and occurrence precision, recall and F1 scores of the
+/// discovered TECs against the ground truth patterns. The similarity of two occurrences
+/// is measured with the cardinality score, that is, the size of their intersection divided
+/// by the size of the larger one. A ground truth pattern is established by a TEC if the best
+/// score between their occurrences is at least the threshold, and the occurrence scores are
+/// computed over the established pairs of patterns. The scores are zero if there are no TECs,
+/// no ground truth patterns or no established pairs.
+///
+/// # Arguments
+///
+/// * `ground_truth` - The annotated patterns
+/// * `tecs` - The discovered TECs
+/// * `occurrence_threshold` - The threshold for establishment used in the occurrence scores,
+///   see `DEFAULT_OCCURRENCE_THRESHOLD`
+pub fn evaluate<T: Point>(
+    ground_truth: &[GroundTruthPattern<T>],
+    tecs: &[Tec<T>],
+    occurrence_threshold: f64,
+) -> EvaluationScores {
+    let ground_truth_occurrences: Vec<Vec<HashSet<T>>> = ground_truth
+        .iter()
+        .map(|pattern| pattern.occurrences.iter().map(to_set).collect())
+        .collect();
+    let discovered_occurrences: Vec<Vec<HashSet<T>>> = tecs
+        .iter()
+        .map(|tec| tec.occurrences_iter().map(|p| to_set(&p)).collect())
+        .collect();
+
+    // The occurrence score matrices for each pair of ground truth and discovered patterns.
+    let score_matrices: Vec<Vec<Vec<Vec<f64>>>> = ground_truth_occurrences
+        .iter()
+        .map(|truth| {
+            discovered_occurrences
+                .iter()
+                .map(|discovered| score_matrix(truth, discovered))
+                .collect()
+        })
+        .collect();
+
+    let establishment: Vec<Vec<f64>> = score_matrices
+        .iter()
+        .map(|row| row.iter().map(|matrix| max_score(matrix)).collect())
+        .collect();
+
+    let establishment_recall = mean(establishment.iter().map(|row| max(row.iter())));
+    let establishment_precision =
+        mean((0..tecs.len()).map(|j| max(establishment.iter().map(|row| &row[j]))));
+
+    let mut occurrence_precisions = Vec::new();
+    let mut occurrence_recalls = Vec::new();
+    for (i, row) in establishment.iter().enumerate() {
+        for (j, score) in row.iter().enumerate() {
+            if *score < occurrence_threshold {
+                continue;
+            }
+
+            let matrix = &score_matrices[i][j];
+            occurrence_recalls.push(mean(matrix.iter().map(|scores| max(scores.iter()))));
+            occurrence_precisions.push(mean(
+                (0..discovered_occurrences[j].len())
+                    .map(|l| max(matrix.iter().map(|scores| &scores[l]))),
+            ));
+        }
+    }
+
+    let occurrence_precision = mean(occurrence_precisions.into_iter());
+    let occurrence_recall = mean(occurrence_recalls.into_iter());
+
+    EvaluationScores {
+        establishment_precision,
+        establishment_recall,
+        establishment_f1: f1(establishment_precision, establishment_recall),
+        occurrence_precision,
+        occurrence_recall,
+        occurrence_f1: f1(occurrence_precision, occurrence_recall),
+    }
+}
+
+/// Returns the cardinality scores between all ground truth and discovered occurrences.
+fn score_matrix<T: Point>(truth: &[HashSet<T>], discovered: &[HashSet<T>]) -> Vec<Vec<f64>> {
+    truth
+        .iter()
+        .map(|p| discovered.iter().map(|q| cardinality_score(p, q)).collect())
+        .collect()
+}
+
+fn cardinality_score<T: Point>(p: &HashSet<T>, q: &HashSet<T>) -> f64 {
+    let larger = p.len().max(q.len());
+    if larger == 0 {
+        return 0.0;
+    }
+    p.intersection(q).count() as f64 / larger as f64
+}
+
+fn to_set<T: Point>(pattern: &Pattern<T>) -> HashSet<T> {
+    pattern.into_iter().copied().collect()
+}
+
+fn max_score(matrix: &[Vec<f64>]) -> f64 {
+    max(matrix.iter().flat_map(|scores| scores.iter()))
+}
+
+fn max<'a>(scores: impl Iterator<Item = &'a f64>) -> f64 {
+    scores.fold(0.0, |a, b| a.max(*b))
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+    if count == 0 {
+        return 0.0;
+    }
+    sum / count as f64
+}
+
+fn f1(precision: f64, recall: f64) -> f64 {
+    if precision + recall == 0.0 {
+        return 0.0;
+    }
+    2.0 * precision * recall / (precision + recall)
+}
+
+/// Returns the ground truth patterns read from a directory in the format of the JKU Patterns
+/// Development Database (JKU PDD). The directory is the `repeatedPatterns` directory of a piece,
+/// where each subdirectory is a pattern and each subdirectory of a pattern is an occurrence.
+/// The points of each occurrence are read from the CSV file in the `csv` subdirectory of the
+/// occurrence, where the first column is the onset and the second column the MIDI note number.
+/// The patterns and occurrences are in the alphabetical order of their directory names.
+///
+/// # Arguments
+///
+/// * `path` - The path to the directory of the repeated patterns of a piece
+#[cfg(feature = "io")]
+pub fn load_jku_ground_truth(
+    path: &Path,
+) -> Result<Vec<GroundTruthPattern<Point2DRf64>>, PoSemirError> {
+    let mut patterns = Vec::new();
+
+    for pattern_dir in sorted_subdirectories(path)? {
+        let mut occurrences = Vec::new();
+        for occurrence_dir in sorted_subdirectories(&pattern_dir)? {
+            let csv_dir = occurrence_dir.join("csv");
+            if !csv_dir.is_dir() {
+                continue;
+            }
+
+            let mut csv_files: Vec<_> = std::fs::read_dir(&csv_dir)?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|file| file.extension().is_some_and(|ext| ext == "csv"))
+                .collect();
+            csv_files.sort();
+
+            if let Some(csv_file) = csv_files.first() {
+                occurrences.push(read_jku_occurrence(csv_file)?);
+            }
+        }
+
+        let name = pattern_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        patterns.push(GroundTruthPattern { name, occurrences });
+    }
+
+    Ok(patterns)
+}
+
+#[cfg(feature = "io")]
+fn sorted_subdirectories(path: &Path) -> Result<Vec<std::path::PathBuf>, PoSemirError> {
+    let mut directories: Vec<_> = std::fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|entry| entry.is_dir())
+        .collect();
+    directories.sort();
+    Ok(directories)
+}
+
+/// Reads an occurrence from a JKU PDD CSV file, which has no header row.
+#[cfg(feature = "io")]
+fn read_jku_occurrence(path: &Path) -> Result<Pattern<Point2DRf64>, PoSemirError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_path(path)?;
+
+    let mut points = Vec::new();
+    for result in reader.records() {
+        let record = result?;
+        let onset = value_at(&record, 0)?;
+        let pitch = value_at(&record, 1)?;
+        points.push(Point2DRf64::new(onset, pitch));
+    }
+
+    Ok(Pattern::new(&points.iter().collect()))
+}
+
+#[cfg(feature = "io")]
+fn value_at(record: &csv::StringRecord, i: usize) -> Result<f64, PoSemirError> {
+    match record.get(i) {
+        None => Err(PoSemirError::MissingValue(i)),
+        Some(str) => Ok(str.trim().parse::<f64>()?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluation::{evaluate, GroundTruthPattern, DEFAULT_OCCURRENCE_THRESHOLD};
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::tec::Tec;
+
+    fn motif(onset: f64) -> Vec<Point2Df64> {
+        vec![
+            Point2Df64 { x: onset, y: 60.0 },
+            Point2Df64 {
+                x: onset + 1.0,
+                y: 62.0,
+            },
+            Point2Df64 {
+                x: onset + 2.0,
+                y: 64.0,
+            },
+            Point2Df64 {
+                x: onset + 3.0,
+                y: 65.0,
+            },
+        ]
+    }
+
+    fn ground_truth() -> Vec<GroundTruthPattern<Point2Df64>> {
+        vec![GroundTruthPattern {
+            name: "A".to_string(),
+            occurrences: [0.0, 8.0]
+                .iter()
+                .map(|onset| Pattern::new(&motif(*onset).iter().collect()))
+                .collect(),
+        }]
+    }
+
+    #[test]
+    fn test_perfect_discovery() {
+        let tec = Tec {
+            pattern: Pattern::new(&motif(0.0).iter().collect()),
+            translators: vec![Point2Df64 { x: 8.0, y: 0.0 }],
+        };
+
+        let scores = evaluate(&ground_truth(), &[tec], DEFAULT_OCCURRENCE_THRESHOLD);
+        assert_eq!(1.0, scores.establishment_precision);
+        assert_eq!(1.0, scores.establishment_recall);
+        assert_eq!(1.0, scores.occurrence_f1);
+    }
+
+    #[test]
+    fn test_partial_discovery() {
+        // Three of the four points, with one spurious occurrence.
+        let points = motif(0.0);
+        let tec = Tec {
+            pattern: Pattern::new(&points[..3].iter().collect()),
+            translators: vec![
+                Point2Df64 { x: 8.0, y: 0.0 },
+                Point2Df64 { x: 20.0, y: 0.0 },
+            ],
+        };
+        let unrelated = Tec {
+            pattern: Pattern::new(&vec![&Point2Df64 { x: 30.0, y: 40.0 }]),
+            translators: vec![Point2Df64 { x: 1.0, y: 0.0 }],
+        };
+
+        let scores = evaluate(
+            &ground_truth(),
+            &[tec, unrelated],
+            DEFAULT_OCCURRENCE_THRESHOLD,
+        );
+        assert_eq!(0.75, scores.establishment_recall);
+        assert_eq!(0.375, scores.establishment_precision);
+        assert_eq!(0.75, scores.occurrence_recall);
+        assert_eq!(0.5, scores.occurrence_precision);
+        assert_eq!(0.6, scores.occurrence_f1);
+
+        let scores = evaluate(&ground_truth(), &[], DEFAULT_OCCURRENCE_THRESHOLD);
+        assert_eq!(0.0, scores.establishment_f1);
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn test_load_jku_ground_truth() {
+        use crate::evaluation::load_jku_ground_truth;
+        use crate::point_set::point::Point2DRf64;
+
+        let tmp_dir = tempfile::tempdir().unwrap();
+        for (pattern, occurrence, content) in [
+            (
+                "patternA",
+                "occ1",
+                "0.00000, 60, 60, 1.00000, 0\n1.00000, 62, 61, 1.00000, 0\n",
+            ),
+            (
+                "patternA",
+                "occ2",
+                "8.00000, 60, 60, 1.00000, 0\n9.00000, 62, 61, 1.00000, 0\n",
+            ),
+            ("patternB", "occ1", "-1.00000, 55, 57, 0.50000, 1\n"),
+        ] {
+            let csv_dir = tmp_dir.path().join(pattern).join(occurrence).join("csv");
+            std::fs::create_dir_all(&csv_dir).unwrap();
+            std::fs::write(csv_dir.join(format!("{}.csv", occurrence)), content).unwrap();
+        }
+
+        let patterns = load_jku_ground_truth(tmp_dir.path()).unwrap();
+        assert_eq!(2, patterns.len());
+        assert_eq!("patternA", patterns[0].name);
+        assert_eq!(2, patterns[0].occurrences.len());
+        assert_eq!(Point2DRf64::new(8.0, 60.0), patterns[0].occurrences[1][0]);
+        assert_eq!(
+            Pattern::new(&vec![&Point2DRf64::new(-1.0, 55.0)]),
+            patterns[1].occurrences[0]
+        );
+    }
+}
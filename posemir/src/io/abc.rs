@@ -0,0 +1,918 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use num_rational::Rational64;
+
+use crate::error::PoSemirError;
+use crate::point_set::point::Point2DRf64;
+use crate::point_set::point_pitch::chromatic_pitch;
+use crate::point_set::point_rational::to_f64;
+use crate::point_set::set::PointSet;
+
+/// How the repeats of ABC tunes are read.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Repeats {
+    /// The music is read once in the order in which it is written, so the repeated sections
+    /// are not repeated, and all endings (e.g., `[1` and `[2`) are read one after another.
+    AsWritten,
+    /// The music is read in the order in which it is played: each repeated section is read
+    /// twice, and on each pass only the endings marked for the pass are read.
+    Expand,
+}
+
+/// Options for reading point sets from ABC files.
+#[derive(Debug, Clone)]
+pub struct AbcOptions {
+    /// How the repeats are read.
+    pub repeats: Repeats,
+}
+
+impl Default for AbcOptions {
+    fn default() -> Self {
+        AbcOptions {
+            repeats: Repeats::AsWritten,
+        }
+    }
+}
+
+/// A tune read from an ABC file.
+#[derive(Debug, Clone)]
+pub struct AbcTune {
+    /// The reference number of the tune (the X: field).
+    pub number: Option<usize>,
+    /// The first title of the tune (the T: field).
+    pub title: Option<String>,
+    /// The notes of the tune, see `abc_to_point_set`.
+    pub point_set: PointSet<Point2DRf64>,
+}
+
+fn format_error<V>(message: &str) -> Result<V, PoSemirError> {
+    Err(PoSemirError::InvalidAbc(message.to_string()))
+}
+
+/// Returns a point set read from the first tune of the ABC file at the given path.
+/// Each note is a point, where the x-coordinate is the onset time in quarter notes
+/// and the y-coordinate is the chromatic pitch as a MIDI note number. Tied notes are
+/// a single point, and grace notes are skipped. The voices of multi-voice tunes
+/// (the V: field) all start at time zero.
+///
+/// # Arguments
+///
+/// * `path` - The path to the ABC file
+/// * `options` - The options for reading the notes
+pub fn abc_to_point_set(
+    path: &Path,
+    options: &AbcOptions,
+) -> Result<PointSet<Point2DRf64>, PoSemirError> {
+    let abc = fs::read_to_string(path)?;
+    abc_str_to_point_set(&abc, options)
+}
+
+/// Returns a point set read from the first tune of the contents of an ABC file
+/// (see `abc_to_point_set`).
+///
+/// # Arguments
+///
+/// * `abc` - The contents of the ABC file
+/// * `options` - The options for reading the notes
+pub fn abc_str_to_point_set(
+    abc: &str,
+    options: &AbcOptions,
+) -> Result<PointSet<Point2DRf64>, PoSemirError> {
+    match abc_str_to_tunes(abc, options)?.into_iter().next() {
+        Some(tune) => Ok(tune.point_set),
+        None => format_error("no tunes"),
+    }
+}
+
+/// Returns all tunes of the ABC file at the given path, e.g., of a tune collection,
+/// in the order in which they are in the file. The notes are read as in `abc_to_point_set`.
+///
+/// # Arguments
+///
+/// * `path` - The path to the ABC file
+/// * `options` - The options for reading the notes
+pub fn abc_to_tunes(path: &Path, options: &AbcOptions) -> Result<Vec<AbcTune>, PoSemirError> {
+    let abc = fs::read_to_string(path)?;
+    abc_str_to_tunes(&abc, options)
+}
+
+/// Returns all tunes of the contents of an ABC file (see `abc_to_tunes`).
+///
+/// # Arguments
+///
+/// * `abc` - The contents of the ABC file
+/// * `options` - The options for reading the notes
+pub fn abc_str_to_tunes(abc: &str, options: &AbcOptions) -> Result<Vec<AbcTune>, PoSemirError> {
+    let mut tunes = Vec::new();
+    let mut tune: Option<TuneReader> = None;
+
+    for line in abc.lines() {
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            // Tunes are separated by empty lines.
+            if let Some(finished) = tune.take() {
+                tunes.push(finished.finish(options));
+            }
+            continue;
+        }
+
+        let field = field(line);
+        if let Some(('X', value)) = field {
+            if let Some(finished) = tune.take() {
+                tunes.push(finished.finish(options));
+            }
+            tune = Some(TuneReader::new(value.trim().parse().ok()));
+            continue;
+        }
+
+        // Files with a single tune may omit the X: field. Text between tunes is skipped.
+        let reader = match (&mut tune, field) {
+            (Some(reader), _) => reader,
+            (None, Some(_)) => tune.insert(TuneReader::new(None)),
+            (None, None) => continue,
+        };
+        match field {
+            Some((name, value)) => reader.read_field(name, value)?,
+            None if line.starts_with('%') => {}
+            None => reader.read_music(line)?,
+        }
+    }
+
+    if let Some(finished) = tune {
+        tunes.push(finished.finish(options));
+    }
+    Ok(tunes)
+}
+
+/// Returns the name and the value of an information field line, e.g., `K:G`.
+fn field(line: &str) -> Option<(char, &str)> {
+    let mut chars = line.chars();
+    match (chars.next(), chars.next()) {
+        (Some(name), Some(':')) if name.is_ascii_alphabetic() => Some((name, &line[2..])),
+        _ => None,
+    }
+}
+
+/// An element of the music of a voice, whose notes have been resolved into pitches
+/// and durations in quarter notes.
+enum Item {
+    /// Notes that start at the same time, and the duration after which the next item starts.
+    /// The pitches do not include the notes that continue a tie.
+    Notes(Vec<f64>, Rational64),
+    Rest(Rational64),
+    RepeatStart,
+    RepeatEnd,
+    /// The start of an ending for the given passes of a repeated section.
+    Ending(Vec<usize>),
+}
+
+/// Reads the fields and the music of a tune.
+struct TuneReader {
+    number: Option<usize>,
+    title: Option<String>,
+    in_header: bool,
+    /// The unit note length in whole notes (the L: field).
+    unit: Option<Rational64>,
+    /// The meter as (count, unit), e.g., (6, 8), or empty for free meter.
+    meter: Option<(i64, i64)>,
+    /// The alterations of the steps C to B given by the key signature.
+    key: [i32; 7],
+    /// The alterations of the accidentals of the current measure by step and octave.
+    accidentals: HashMap<(char, i32), i32>,
+    /// The duration ratio of the current tuplet and the number of its remaining notes.
+    tuplet: Option<(Rational64, usize)>,
+    /// The factor of the duration of the next note given by a broken rhythm, e.g., `a>b`.
+    broken: Option<Rational64>,
+    /// The pitches of the previous notes, and the pitches that are tied to the next notes.
+    previous: Vec<f64>,
+    tied: Vec<f64>,
+    voices: Vec<(String, Vec<Item>)>,
+    voice: usize,
+}
+
+impl TuneReader {
+    fn new(number: Option<usize>) -> TuneReader {
+        TuneReader {
+            number,
+            title: None,
+            in_header: true,
+            unit: None,
+            meter: Some((4, 4)),
+            key: [0; 7],
+            accidentals: HashMap::new(),
+            tuplet: None,
+            broken: None,
+            previous: Vec::new(),
+            tied: Vec::new(),
+            voices: vec![(String::new(), Vec::new())],
+            voice: 0,
+        }
+    }
+
+    fn read_field(&mut self, name: char, value: &str) -> Result<(), PoSemirError> {
+        let value = value.split('%').next().unwrap_or("").trim();
+        match name {
+            'T' if self.title.is_none() => self.title = Some(value.to_string()),
+            'M' => self.meter = parse_meter(value)?,
+            'L' => self.unit = Some(parse_fraction(value)?),
+            'K' => {
+                self.key = parse_key(value)?;
+                self.in_header = false;
+            }
+            'V' => self.select_voice(value.split_whitespace().next().unwrap_or("")),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn select_voice(&mut self, id: &str) {
+        self.voice = match self.voices.iter().position(|(voice, _)| voice == id) {
+            Some(index) => index,
+            // The music before the first V: field belongs to the first voice.
+            None if self.voices.len() == 1 && self.voices[0].1.is_empty() => {
+                self.voices[0].0 = id.to_string();
+                0
+            }
+            None => {
+                self.voices.push((id.to_string(), Vec::new()));
+                self.voices.len() - 1
+            }
+        };
+        self.accidentals.clear();
+        self.tuplet = None;
+        self.broken = None;
+        self.tied.clear();
+    }
+
+    /// Returns the unit note length, which defaults to an eighth, or to a sixteenth
+    /// in meters shorter than 3/4.
+    fn unit(&self) -> Rational64 {
+        self.unit.unwrap_or(match self.meter {
+            Some((count, unit)) if 4 * count < 3 * unit => Rational64::new(1, 16),
+            _ => Rational64::new(1, 8),
+        })
+    }
+
+    fn measure_length(&self) -> Rational64 {
+        let (count, unit) = self.meter.unwrap_or((4, 4));
+        Rational64::new(4 * count, unit)
+    }
+
+    fn push(&mut self, item: Item) {
+        self.voices[self.voice].1.push(item);
+    }
+
+    /// Reads a line of music, which is read as the header if the K: field has not been read.
+    fn read_music(&mut self, line: &str) -> Result<(), PoSemirError> {
+        if self.in_header {
+            return Ok(());
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            match c {
+                '%' => break,
+                '"' => i = skip_past(&chars, i + 1, '"'),
+                '!' | '+' => i = skip_past(&chars, i + 1, c),
+                '{' => i = skip_past(&chars, i + 1, '}'),
+                '[' => match (chars.get(i + 1), chars.get(i + 2)) {
+                    (Some(name), Some(':')) if name.is_ascii_alphabetic() => {
+                        let end = skip_past(&chars, i + 3, ']');
+                        let value: String = chars[i + 3..end]
+                            .iter()
+                            .take_while(|c| **c != ']')
+                            .collect();
+                        self.read_field(*name, &value)?;
+                        i = end;
+                    }
+                    (Some(digit), _) if digit.is_ascii_digit() => {
+                        i = self.read_ending(&chars, i + 1);
+                    }
+                    (Some('|'), _) => i = self.read_bar(&chars, i),
+                    _ => i = self.read_chord(&chars, i + 1)?,
+                },
+                '|' | ':' => i = self.read_bar(&chars, i),
+                '(' => match chars.get(i + 1) {
+                    Some(digit) if digit.is_ascii_digit() => i = self.read_tuplet(&chars, i + 1),
+                    _ => i += 1,
+                },
+                '^' | '_' | '=' | 'A'..='G' | 'a'..='g' => {
+                    let (pitch, length, end) = self.read_note(&chars, i)?;
+                    let mut tied = false;
+                    i = end;
+                    if chars.get(i) == Some(&'-') {
+                        tied = true;
+                        i += 1;
+                    }
+                    self.add_notes(vec![pitch], length, if tied { vec![pitch] } else { vec![] });
+                }
+                'z' | 'x' => {
+                    let (length, end) = read_length(&chars, i + 1)?;
+                    let duration = self.duration(length);
+                    self.push(Item::Rest(duration));
+                    self.tied.clear();
+                    i = end;
+                }
+                'Z' | 'X' => {
+                    let end = skip_digits(&chars, i + 1);
+                    let measures: i64 = if end > i + 1 {
+                        parse_digits(&chars[i + 1..end])?
+                    } else {
+                        1
+                    };
+                    let duration = self.measure_length() * Rational64::from_integer(measures);
+                    self.push(Item::Rest(duration));
+                    i = end;
+                }
+                '>' | '<' => {
+                    let end = skip_while(&chars, i, c);
+                    self.read_broken_rhythm(c, (end - i) as u32);
+                    i = end;
+                }
+                '-' => {
+                    self.tied = self.previous.clone();
+                    i += 1;
+                }
+                _ => i += 1,
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a bar line starting at the given index, e.g., `|`, `||`, `|:`, `:|`, `::`
+    /// or `:|2`, and returns the index after it.
+    fn read_bar(&mut self, chars: &[char], start: usize) -> usize {
+        let mut end = start;
+        while let Some(c) = chars.get(end) {
+            match c {
+                '|' | ':' | ']' => end += 1,
+                '[' if chars.get(end + 1) == Some(&'|') => end += 1,
+                _ => break,
+            }
+        }
+
+        let bar = &chars[start..end];
+        if bar.first() == Some(&':') {
+            self.push(Item::RepeatEnd);
+        }
+        if bar.len() > 1 && bar.last() == Some(&':') {
+            self.push(Item::RepeatStart);
+        }
+        self.accidentals.clear();
+
+        match chars.get(end) {
+            Some(digit) if digit.is_ascii_digit() => self.read_ending(chars, end),
+            _ => end,
+        }
+    }
+
+    /// Reads the passes of an ending, e.g., `1`, `2` or `1,3` or `1-2`, starting at
+    /// the given index, and returns the index after them.
+    fn read_ending(&mut self, chars: &[char], start: usize) -> usize {
+        let mut end = start;
+        while chars
+            .get(end)
+            .is_some_and(|c| c.is_ascii_digit() || *c == ',' || *c == '-')
+        {
+            end += 1;
+        }
+
+        let text: String = chars[start..end].iter().collect();
+        let mut passes = Vec::new();
+        for part in text.split(',') {
+            let mut bounds = part
+                .split('-')
+                .filter_map(|bound| bound.parse::<usize>().ok());
+            if let Some(first) = bounds.next() {
+                let last = bounds.next().unwrap_or(first);
+                passes.extend(first..=last);
+            }
+        }
+        self.push(Item::Ending(passes));
+        end
+    }
+
+    /// Reads a tuplet, e.g., `3`, `3:2` or `3:2:3` after the opening parenthesis,
+    /// and returns the index after it.
+    fn read_tuplet(&mut self, chars: &[char], start: usize) -> usize {
+        let mut numbers: Vec<Option<i64>> = Vec::new();
+        let mut end = start;
+        loop {
+            let digits_end = skip_digits(chars, end);
+            numbers.push(parse_digits(&chars[end..digits_end]).ok());
+            end = digits_end;
+            if numbers.len() < 3 && chars.get(end) == Some(&':') {
+                end += 1;
+            } else {
+                break;
+            }
+        }
+
+        let notes = numbers[0].unwrap_or(3).max(1);
+        let compound = matches!(self.meter, Some((count, _)) if count % 3 == 0 && count > 3);
+        let default_time = match notes {
+            2 | 4 | 8 => 3,
+            3 | 6 => 2,
+            _ if compound => 3,
+            _ => 2,
+        };
+        let time = numbers.get(1).copied().flatten().unwrap_or(default_time);
+        let count = numbers.get(2).copied().flatten().unwrap_or(notes);
+        if time > 0 && count > 0 {
+            self.tuplet = Some((Rational64::new(time, notes), count as usize));
+        }
+        end
+    }
+
+    fn read_broken_rhythm(&mut self, direction: char, count: u32) {
+        let count = count.min(3);
+        let short = Rational64::new(1, 1 << count);
+        let long = Rational64::from_integer(2) - short;
+        let (previous, next) = if direction == '>' {
+            (long, short)
+        } else {
+            (short, long)
+        };
+
+        if let Some(Item::Notes(_, duration) | Item::Rest(duration)) =
+            self.voices[self.voice].1.last_mut()
+        {
+            *duration *= previous;
+        }
+        self.broken = Some(next);
+    }
+
+    /// Reads a chord after the opening bracket, e.g., `CEG]2`, and returns the index after it.
+    fn read_chord(&mut self, chars: &[char], start: usize) -> Result<usize, PoSemirError> {
+        let mut pitches = Vec::new();
+        let mut tied = Vec::new();
+        let mut length = None;
+        let mut i = start;
+        loop {
+            match chars.get(i) {
+                Some(']') => break,
+                Some('^' | '_' | '=' | 'A'..='G' | 'a'..='g') => {
+                    let (pitch, note_length, end) = self.read_note(chars, i)?;
+                    pitches.push(pitch);
+                    // The chord has the length of its first note.
+                    length.get_or_insert(note_length);
+                    i = end;
+                    if chars.get(i) == Some(&'-') {
+                        tied.push(pitch);
+                        i += 1;
+                    }
+                }
+                Some(_) => i += 1,
+                None => return format_error("unterminated chord"),
+            }
+        }
+
+        let (chord_length, end) = read_length(chars, i + 1)?;
+        let mut end = end;
+        if chars.get(end) == Some(&'-') {
+            tied = pitches.clone();
+            end += 1;
+        }
+        if !pitches.is_empty() {
+            let length = length.unwrap_or(Rational64::from_integer(1)) * chord_length;
+            self.add_notes(pitches, length, tied);
+        }
+        Ok(end)
+    }
+
+    /// Reads a note, e.g., `^c'2`, and returns its pitch, its length in unit note lengths,
+    /// and the index after it.
+    fn read_note(
+        &mut self,
+        chars: &[char],
+        start: usize,
+    ) -> Result<(f64, Rational64, usize), PoSemirError> {
+        let mut i = start;
+        let mut accidental = None;
+        while let Some(c @ ('^' | '_' | '=')) = chars.get(i) {
+            accidental = Some(match c {
+                '^' => accidental.unwrap_or(0).max(0) + 1,
+                '_' => accidental.unwrap_or(0).min(0) - 1,
+                _ => 0,
+            });
+            i += 1;
+        }
+
+        let letter = match chars.get(i) {
+            Some(letter @ ('A'..='G' | 'a'..='g')) => *letter,
+            _ => return format_error("accidental without a note"),
+        };
+        i += 1;
+        let step = letter.to_ascii_uppercase();
+        let mut octave = if letter.is_ascii_uppercase() { 4 } else { 5 };
+        while let Some(mark @ ('\'' | ',')) = chars.get(i) {
+            octave += if *mark == '\'' { 1 } else { -1 };
+            i += 1;
+        }
+
+        let alter = match accidental {
+            Some(alter) => {
+                self.accidentals.insert((step, octave), alter);
+                alter
+            }
+            None => match self.accidentals.get(&(step, octave)) {
+                Some(alter) => *alter,
+                None => self.key[step_index(step)],
+            },
+        };
+        // The step is always a note name.
+        let pitch = chromatic_pitch(step, alter, octave).unwrap();
+
+        let (length, end) = read_length(chars, i)?;
+        Ok((pitch, length, end))
+    }
+
+    /// Adds notes that start at the same time, where the pitches that continue a tie
+    /// from the previous notes do not start a new note.
+    fn add_notes(&mut self, pitches: Vec<f64>, length: Rational64, tied: Vec<f64>) {
+        let duration = self.duration(length);
+        let onsets: Vec<f64> = pitches
+            .iter()
+            .filter(|pitch| !self.tied.contains(pitch))
+            .copied()
+            .collect();
+        self.push(Item::Notes(onsets, duration));
+        self.previous = pitches;
+        self.tied = tied;
+    }
+
+    /// Returns the duration in quarter notes of the length in unit note lengths,
+    /// taking into account the current tuplet and broken rhythm.
+    fn duration(&mut self, length: Rational64) -> Rational64 {
+        let mut duration = Rational64::from_integer(4) * self.unit() * length;
+        if let Some((ratio, remaining)) = self.tuplet {
+            duration *= ratio;
+            self.tuplet = if remaining > 1 {
+                Some((ratio, remaining - 1))
+            } else {
+                None
+            };
+        }
+        if let Some(factor) = self.broken.take() {
+            duration *= factor;
+        }
+        duration
+    }
+
+    fn finish(self, options: &AbcOptions) -> AbcTune {
+        let mut points = Vec::new();
+        for (_, items) in &self.voices {
+            let mut time = Rational64::ZERO;
+            for index in order(items, options.repeats) {
+                match &items[index] {
+                    Item::Notes(pitches, duration) => {
+                        for pitch in pitches {
+                            points.push(Point2DRf64::new(to_f64(&time), *pitch));
+                        }
+                        time += *duration;
+                    }
+                    Item::Rest(duration) => time += *duration,
+                    _ => {}
+                }
+            }
+        }
+
+        AbcTune {
+            number: self.number,
+            title: self.title,
+            point_set: PointSet::new(points),
+        }
+    }
+}
+
+/// Returns the indices of the items in the order in which they are read.
+fn order(items: &[Item], repeats: Repeats) -> Vec<usize> {
+    if repeats == Repeats::AsWritten {
+        return (0..items.len()).collect();
+    }
+
+    let mut indices = Vec::new();
+    let mut repeat_start = 0;
+    let mut pass = 1;
+    // The pass of the endings after a skipped first ending, which is read after the end
+    // of the repeated section.
+    let mut ending_pass = None;
+    let mut i = 0;
+    while i < items.len() {
+        match &items[i] {
+            Item::RepeatStart => {
+                repeat_start = i + 1;
+                pass = 1;
+                ending_pass = None;
+            }
+            Item::RepeatEnd if pass == 1 => {
+                pass = 2;
+                i = repeat_start;
+                continue;
+            }
+            Item::RepeatEnd => {
+                repeat_start = i + 1;
+                pass = 1;
+                ending_pass = None;
+            }
+            Item::Ending(passes) if !passes.contains(&ending_pass.unwrap_or(pass)) => {
+                // Skip the ending, which lasts until the next ending or the end of
+                // the repeated section.
+                let skipped_pass = ending_pass.unwrap_or(pass);
+                i += 1;
+                while i < items.len() {
+                    match &items[i] {
+                        Item::Ending(_) | Item::RepeatStart => break,
+                        Item::RepeatEnd => {
+                            i += 1;
+                            repeat_start = i;
+                            pass = 1;
+                            ending_pass = Some(skipped_pass);
+                            break;
+                        }
+                        _ => i += 1,
+                    }
+                }
+                continue;
+            }
+            _ => indices.push(i),
+        }
+        i += 1;
+    }
+    indices
+}
+
+fn step_index(step: char) -> usize {
+    "CDEFGAB".find(step).unwrap()
+}
+
+/// Returns the alterations of the steps C to B given by a key, e.g., `G`, `Dmix`, `F#m`,
+/// `Bb` or `none`, which may be followed by explicit accidentals, e.g., `D ^g`.
+fn parse_key(value: &str) -> Result<[i32; 7], PoSemirError> {
+    let mut key = [0; 7];
+    let mut tokens = value.split_whitespace();
+    let Some(tonic) = tokens.next() else {
+        return Ok(key);
+    };
+    if tonic.eq_ignore_ascii_case("none") || tonic.contains('=') {
+        return Ok(key);
+    }
+
+    let fifths = if tonic == "HP" || tonic == "Hp" {
+        // Highland bagpipe music has F and C sharp.
+        2
+    } else {
+        let mut chars = tonic.chars();
+        let letter = chars.next().unwrap().to_ascii_uppercase();
+        let Some(major_fifths) = "FCGDAEB".find(letter) else {
+            return format_error(&format!("invalid key {}", value));
+        };
+        let mut rest = chars.as_str();
+        let mut fifths = major_fifths as i32 - 1;
+        if let Some(stripped) = rest.strip_prefix('#') {
+            fifths += 7;
+            rest = stripped;
+        } else if let Some(stripped) = rest.strip_prefix('b') {
+            fifths -= 7;
+            rest = stripped;
+        }
+        let mode = rest.to_ascii_lowercase();
+        fifths
+            + match mode.get(..3.min(mode.len())).unwrap_or("") {
+                "" | "maj" | "ion" => 0,
+                "m" | "min" | "aeo" => -3,
+                "mix" => -1,
+                "dor" => -2,
+                "phr" => -4,
+                "lyd" => 1,
+                "loc" => -5,
+                _ => return format_error(&format!("invalid mode in key {}", value)),
+            }
+    };
+
+    if fifths > 0 {
+        for step in "FCGDAEB".chars().take(fifths.min(7) as usize) {
+            key[step_index(step)] = 1;
+        }
+    } else {
+        for step in "BEADGCF".chars().take((-fifths).min(7) as usize) {
+            key[step_index(step)] = -1;
+        }
+    }
+
+    // Explicit accidentals, e.g., ^f, _b or =c.
+    for token in tokens {
+        let mut chars = token.chars();
+        let alter = match chars.next() {
+            Some('^') => 1,
+            Some('_') => -1,
+            Some('=') => 0,
+            _ => continue,
+        };
+        if let Some(letter @ ('A'..='G' | 'a'..='g')) = chars.last() {
+            key[step_index(letter.to_ascii_uppercase())] = alter;
+        }
+    }
+    Ok(key)
+}
+
+/// Returns the meter as (count, unit), e.g., `6/8`, `C`, `C|` or `(2+3)/8`,
+/// or empty for free meter.
+fn parse_meter(value: &str) -> Result<Option<(i64, i64)>, PoSemirError> {
+    match value {
+        "" | "none" => Ok(None),
+        "C" => Ok(Some((4, 4))),
+        "C|" => Ok(Some((2, 2))),
+        _ => {
+            let Some((count, unit)) = value.split_once('/') else {
+                return format_error(&format!("invalid meter {}", value));
+            };
+            let mut sum = 0;
+            for term in count.trim_matches(|c| c == '(' || c == ')').split('+') {
+                sum += parse_number(term)?;
+            }
+            let unit = parse_number(unit)?;
+            if sum <= 0 || unit <= 0 {
+                return format_error(&format!("invalid meter {}", value));
+            }
+            Ok(Some((sum, unit)))
+        }
+    }
+}
+
+/// Returns the value of a fraction, e.g., `1/8`.
+fn parse_fraction(value: &str) -> Result<Rational64, PoSemirError> {
+    let (numer, denom) = value.split_once('/').unwrap_or((value, "1"));
+    let (numer, denom) = (parse_number(numer)?, parse_number(denom)?);
+    if numer <= 0 || denom <= 0 {
+        return format_error(&format!("invalid note length {}", value));
+    }
+    Ok(Rational64::new(numer, denom))
+}
+
+fn parse_number(value: &str) -> Result<i64, PoSemirError> {
+    value
+        .trim()
+        .parse()
+        .or_else(|_| format_error(&format!("invalid number {}", value)))
+}
+
+fn parse_digits(digits: &[char]) -> Result<i64, PoSemirError> {
+    parse_number(&digits.iter().collect::<String>())
+}
+
+/// Reads a note length, e.g., `2`, `/`, `//`, `3/2` or `/4`, and returns the length
+/// in unit note lengths and the index after it.
+fn read_length(chars: &[char], start: usize) -> Result<(Rational64, usize), PoSemirError> {
+    let mut end = skip_digits(chars, start);
+    let numer = if end > start {
+        parse_digits(&chars[start..end])?
+    } else {
+        1
+    };
+
+    let mut denom = 1;
+    if chars.get(end) == Some(&'/') {
+        let slashes_end = skip_while(chars, end, '/');
+        let digits_end = skip_digits(chars, slashes_end);
+        denom = if digits_end > slashes_end {
+            parse_digits(&chars[slashes_end..digits_end])?
+        } else {
+            1 << (slashes_end - end).min(8)
+        };
+        end = digits_end;
+    }
+
+    if numer == 0 || denom == 0 {
+        return format_error("zero note length");
+    }
+    Ok((Rational64::new(numer, denom), end))
+}
+
+fn skip_digits(chars: &[char], start: usize) -> usize {
+    let mut end = start;
+    while chars.get(end).is_some_and(|c| c.is_ascii_digit()) {
+        end += 1;
+    }
+    end
+}
+
+fn skip_while(chars: &[char], start: usize, skipped: char) -> usize {
+    let mut end = start;
+    while chars.get(end) == Some(&skipped) {
+        end += 1;
+    }
+    end
+}
+
+/// Returns the index after the next occurrence of the character, or the end of the line.
+fn skip_past(chars: &[char], start: usize, end: char) -> usize {
+    match chars[start.min(chars.len())..]
+        .iter()
+        .position(|c| *c == end)
+    {
+        Some(index) => start + index + 1,
+        None => chars.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::abc::{abc_str_to_point_set, abc_str_to_tunes, AbcOptions, Repeats};
+    use crate::point_set::point::Point2DRf64;
+
+    fn onsets_and_pitches(abc: &str, repeats: Repeats) -> Vec<(f64, f64)> {
+        abc_str_to_point_set(abc, &AbcOptions { repeats })
+            .unwrap()
+            .into_iter()
+            .map(|point| (point.rounded_x, point.y))
+            .collect()
+    }
+
+    #[test]
+    fn test_read_notes() {
+        let abc = "X:1\nT:Test\nM:4/4\nL:1/8\nK:G\n\
+                   F2 ^c=c c2 z2 | (3ABc d>e [CEG]2 z2 | G4- G4 | _B,/ C'/ %comment\n";
+        let points = onsets_and_pitches(abc, Repeats::AsWritten);
+        assert_eq!(
+            vec![
+                // F is sharp in G major, and the sharp of c lasts until the natural.
+                (0.0, 66.0),
+                (1.0, 73.0),
+                (1.5, 72.0),
+                (2.0, 72.0),
+                // The triplet and the broken rhythm.
+                (4.0, 69.0),
+                (4.33333, 71.0),
+                (4.66667, 72.0),
+                (5.0, 74.0),
+                (5.75, 76.0),
+                (6.0, 60.0),
+                (6.0, 64.0),
+                (6.0, 67.0),
+                // The tied note is a single note.
+                (8.0, 67.0),
+                (12.0, 58.0),
+                (12.25, 72.0),
+            ],
+            points
+        );
+    }
+
+    #[test]
+    fn test_expand_repeats() {
+        let abc = "X:1\nM:2/4\nL:1/4\nK:C\n|: C D |1 E F :|2 G A || B c |: d e :: f g :|\n";
+        let pitches = |repeats| -> Vec<f64> {
+            onsets_and_pitches(abc, repeats)
+                .iter()
+                .map(|point| point.1)
+                .collect()
+        };
+
+        assert_eq!(
+            vec![60.0, 62.0, 64.0, 65.0, 67.0, 69.0, 71.0, 72.0, 74.0, 76.0, 77.0, 79.0],
+            pitches(Repeats::AsWritten)
+        );
+        assert_eq!(
+            vec![
+                60.0, 62.0, 64.0, 65.0, 60.0, 62.0, 67.0, 69.0, 71.0, 72.0, 74.0, 76.0, 74.0, 76.0,
+                77.0, 79.0, 77.0, 79.0
+            ],
+            pitches(Repeats::Expand)
+        );
+    }
+
+    #[test]
+    fn test_read_tunes() {
+        let abc = "Collection header text\n\n\
+                   X:1\nT:First\nK:D\nd2 f2|\n\n\
+                   X:2\nT:Second\nT:Alternative title\nM:6/8\nK:Am\nV:1\nABc\nV:2\nA,3\n";
+        let tunes = abc_str_to_tunes(abc, &AbcOptions::default()).unwrap();
+        assert_eq!(2, tunes.len());
+        assert_eq!(Some(1), tunes[0].number);
+        assert_eq!(Some("First".to_string()), tunes[0].title);
+        assert_eq!(Point2DRf64::new(0.0, 74.0), tunes[0].point_set[0]);
+        assert_eq!(Point2DRf64::new(1.0, 78.0), tunes[0].point_set[1]);
+
+        assert_eq!(Some("Second".to_string()), tunes[1].title);
+        assert_eq!(
+            vec![
+                Point2DRf64::new(0.0, 57.0),
+                Point2DRf64::new(0.0, 69.0),
+                Point2DRf64::new(0.5, 71.0),
+                Point2DRf64::new(1.0, 72.0)
+            ],
+            tunes[1].point_set.clone().points()
+        );
+
+        assert!(abc_str_to_tunes("X:1\nK:Q\nABC\n", &AbcOptions::default()).is_err());
+    }
+}
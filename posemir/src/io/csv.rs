@@ -2,43 +2,63 @@
  * (c) Otso Björklund (2021)
  * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
  */
-use std::error::Error;
-use std::fmt::{Debug, Display, Formatter};
+use std::io::{Read, Write};
 use std::path::Path;
 
 use csv::StringRecord;
+use num_rational::Rational64;
 
-use crate::point_set::point::{Point2DRf64, Point2Df64, Point2Di64};
+use crate::error::{component_f64, PoSemirError};
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::{Point, Point2DRf64, Point2DWeighted, Point2Df64, Point2Di64};
+use crate::point_set::point_nd::PointDynf64;
+use crate::point_set::point_rational::{approximate, Point2DRational};
+use crate::point_set::tec::Tec;
 
-#[derive(Debug)]
-struct MissingValueError(usize);
-
-impl Display for MissingValueError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Value missing at column {}", self.0)
-    }
-}
-
-impl Error for MissingValueError {}
-
-fn get_f64_value_at(record: &StringRecord, i: usize) -> Result<f64, Box<dyn Error>> {
+fn get_f64_value_at(record: &StringRecord, i: usize) -> Result<f64, PoSemirError> {
     let str_opt = record.get(i);
 
     match str_opt {
-        None => Err(Box::new(MissingValueError(i))),
+        None => Err(PoSemirError::MissingValue(i)),
         Some(str) => Ok(str.trim().parse::<f64>()?),
     }
 }
 
-fn get_i64_value_at(record: &StringRecord, i: usize) -> Result<i64, Box<dyn Error>> {
+fn get_i64_value_at(record: &StringRecord, i: usize) -> Result<i64, PoSemirError> {
     let str_opt = record.get(i);
 
     match str_opt {
-        None => Err(Box::new(MissingValueError(i))),
+        None => Err(PoSemirError::MissingValue(i)),
         Some(str) => Ok(str.trim().parse::<i64>()?),
     }
 }
 
+/// Reads a rational number written either as a fraction, e.g., 1/3, or as a decimal number,
+/// which is approximated with a denominator of at most max_denom.
+fn get_rational_value_at(
+    record: &StringRecord,
+    i: usize,
+    max_denom: i64,
+) -> Result<Rational64, PoSemirError> {
+    let str = record.get(i).ok_or(PoSemirError::MissingValue(i))?.trim();
+
+    match str.split_once('/') {
+        Some((numer, denom)) => {
+            let numer = numer.trim().parse::<i64>()?;
+            let denom = denom.trim().parse::<i64>()?;
+            if denom == 0 {
+                return Err(PoSemirError::InvalidNumber(format!(
+                    "{} has a zero denominator",
+                    str
+                )));
+            }
+            Ok(Rational64::new(numer, denom))
+        }
+        None => approximate(str.parse::<f64>()?, max_denom)
+            .ok_or_else(|| PoSemirError::InvalidNumber(format!("{} is not a finite number", str))),
+    }
+}
+
 /// Returns a vector of points with floating point components read from
 /// the CSV file at the given path.
 /// The CSV file is expected to:
@@ -52,7 +72,7 @@ fn get_i64_value_at(record: &StringRecord, i: usize) -> Result<i64, Box<dyn Erro
 ///
 /// * `path` - The path to the CSV file
 ///
-pub fn csv_to_2d_point_f64(path: &Path) -> Result<Vec<Point2Df64>, Box<dyn Error>> {
+pub fn csv_to_2d_point_f64(path: &Path) -> Result<Vec<Point2Df64>, PoSemirError> {
     let mut points = Vec::new();
     let mut reader = csv::Reader::from_path(path)?;
 
@@ -83,9 +103,28 @@ pub fn csv_to_2d_point_f64(path: &Path) -> Result<Vec<Point2Df64>, Box<dyn Error
 ///
 /// * `path` - The path to the CSV file
 ///
-pub fn csv_to_rounded_2d_point_f64(path: &Path) -> Result<Vec<Point2DRf64>, Box<dyn Error>> {
+pub fn csv_to_rounded_2d_point_f64(path: &Path) -> Result<Vec<Point2DRf64>, PoSemirError> {
+    read_rounded_2d_point_f64(csv::Reader::from_path(path)?)
+}
+
+/// Returns a vector of points with floating point components read as CSV
+/// from the given reader, e.g., the standard input. The points are read as in
+/// `csv_to_rounded_2d_point_f64`.
+///
+/// # Arguments
+///
+/// * `reader` - The reader from which the CSV data is read
+///
+pub fn csv_reader_to_rounded_2d_point_f64<R: Read>(
+    reader: R,
+) -> Result<Vec<Point2DRf64>, PoSemirError> {
+    read_rounded_2d_point_f64(csv::Reader::from_reader(reader))
+}
+
+fn read_rounded_2d_point_f64<R: Read>(
+    mut reader: csv::Reader<R>,
+) -> Result<Vec<Point2DRf64>, PoSemirError> {
     let mut points = Vec::new();
-    let mut reader = csv::Reader::from_path(path)?;
 
     for result in reader.records() {
         let record = result?;
@@ -112,7 +151,7 @@ pub fn csv_to_rounded_2d_point_f64(path: &Path) -> Result<Vec<Point2DRf64>, Box<
 ///
 /// * `path` - The path to the CSV file
 ///
-pub fn csv_to_2d_point_i64(path: &Path) -> Result<Vec<Point2Di64>, Box<dyn Error>> {
+pub fn csv_to_2d_point_i64(path: &Path) -> Result<Vec<Point2Di64>, PoSemirError> {
     let mut points = Vec::new();
     let mut reader = csv::Reader::from_path(path)?;
 
@@ -128,12 +167,277 @@ pub fn csv_to_2d_point_i64(path: &Path) -> Result<Vec<Point2Di64>, Box<dyn Error
     Ok(points)
 }
 
+/// Returns a vector of points with exact rational onsets and integer pitches read from
+/// the CSV file at the given path. The onsets can be written as fractions, e.g., 4/3,
+/// or as decimal numbers, which are approximated with the nearest fractions whose
+/// denominators are at most the given maximum, e.g., 0.333 is read as 1/3 with a maximum
+/// denominator of 100 (see `approximate`).
+/// The CSV file is expected to:
+/// - have a header row
+/// - contain onsets in the first column
+/// - contain integer pitches in the second column
+///
+/// The rest of the columns are ignored.
+///
+/// # Arguments
+///
+/// * `path` - The path to the CSV file
+/// * `max_denominator` - The largest denominator of the onsets written as decimal numbers
+///
+pub fn csv_to_rational_2d_point(
+    path: &Path,
+    max_denominator: i64,
+) -> Result<Vec<Point2DRational>, PoSemirError> {
+    let mut points = Vec::new();
+    let mut reader = csv::Reader::from_path(path)?;
+
+    for result in reader.records() {
+        let record = result?;
+
+        let onset = get_rational_value_at(&record, 0, max_denominator)?;
+        let pitch = get_f64_value_at(&record, 1)?;
+        if pitch.fract() != 0.0 {
+            return Err(PoSemirError::InvalidNumber(format!(
+                "pitch {} is not an integer",
+                pitch
+            )));
+        }
+
+        points.push(Point2DRational {
+            onset,
+            pitch: pitch as i64,
+        });
+    }
+
+    Ok(points)
+}
+
+/// Returns a vector of weighted points read from the CSV file at the given path, where
+/// the weight of each point is its salience, e.g., the MIDI velocity of the note. Accented
+/// notes then count more in the weighted coverage of TECs (see `heuristic::TecStats`).
+/// The CSV file is expected to:
+/// - have a header row
+/// - contain x-coordinates in the first column
+/// - contain y-coordinates in the second column
+/// - contain the weights in the given column
+///
+/// The rest of the columns are ignored.
+///
+/// # Arguments
+///
+/// * `path` - The path to the CSV file
+/// * `weight_column` - The (zero-based) index of the column that contains the weights
+///
+pub fn csv_to_weighted_2d_point_f64(
+    path: &Path,
+    weight_column: usize,
+) -> Result<Vec<Point2DWeighted>, PoSemirError> {
+    let mut points = Vec::new();
+    let mut reader = csv::Reader::from_path(path)?;
+
+    for result in reader.records() {
+        let record = result?;
+
+        let x = get_f64_value_at(&record, 0)?;
+        let y = get_f64_value_at(&record, 1)?;
+        let weight = get_f64_value_at(&record, weight_column)?;
+
+        points.push(Point2DWeighted { x, y, weight });
+    }
+
+    Ok(points)
+}
+
+/// Returns a vector of points with floating point components read from the given
+/// columns of the CSV file at the given path. The i-th component of each point is read
+/// from the i-th column in `columns`, so the columns can be selected in any order.
+/// The CSV file is expected to have a header row. The rest of the columns are ignored.
+///
+/// # Arguments
+///
+/// * `path` - The path to the CSV file
+/// * `columns` - The (zero-based) indices of the columns from which the components are read,
+///   at most `MAX_DYNAMIC_DIMENSIONALITY`
+///
+pub fn csv_to_points_nd(path: &Path, columns: &[usize]) -> Result<Vec<PointDynf64>, PoSemirError> {
+    read_points_nd(csv::Reader::from_path(path)?, columns)
+}
+
+/// Returns a vector of points with floating point components read from the columns with
+/// the given names in the header row of the CSV file at the given path. The points are read
+/// as in `csv_to_points_nd`, e.g., the names `["onset", "pitch", "channel"]` select those
+/// columns regardless of their order in the file. Leading and trailing whitespace in the
+/// header is ignored.
+///
+/// # Arguments
+///
+/// * `path` - The path to the CSV file
+/// * `names` - The names of the columns from which the components are read
+///
+pub fn csv_to_points_nd_by_name(
+    path: &Path,
+    names: &[&str],
+) -> Result<Vec<PointDynf64>, PoSemirError> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let headers = reader.headers()?;
+    let columns = names
+        .iter()
+        .map(|name| {
+            headers
+                .iter()
+                .position(|header| header.trim() == *name)
+                .ok_or_else(|| PoSemirError::MissingColumn(name.to_string()))
+        })
+        .collect::<Result<Vec<usize>, PoSemirError>>()?;
+
+    read_points_nd(reader, &columns)
+}
+
+fn read_points_nd<R: Read>(
+    mut reader: csv::Reader<R>,
+    columns: &[usize],
+) -> Result<Vec<PointDynf64>, PoSemirError> {
+    let mut points = Vec::new();
+    let mut components = Vec::with_capacity(columns.len());
+
+    for result in reader.records() {
+        let record = result?;
+
+        components.clear();
+        for &column in columns {
+            components.push(get_f64_value_at(&record, column)?);
+        }
+
+        points.push(PointDynf64::new(&components)?);
+    }
+
+    Ok(points)
+}
+
+/// Writes the occurrences of the TECs as CSV with a row for each point of each occurrence.
+/// The patterns and their occurrences are numbered from 1 as in the MIREX format
+/// (see `write_tecs_to_mirex`):
+/// ```text
+/// pattern,occurrence,onset,pitch
+/// 1,1,1.0,64.0
+/// 1,1,2.0,60.0
+/// 1,2,5.0,64.0
+/// ...
+/// ```
+/// The first component of the points is written as the onset and the second as the pitch.
+///
+/// # Arguments
+///
+/// * `tecs` - The TECs that are written
+/// * `writer` - The writer into which the CSV is written
+pub fn write_tecs_to_csv<T: Point, W: Write>(
+    tecs: &[Tec<T>],
+    writer: W,
+) -> Result<(), PoSemirError> {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(["pattern", "occurrence", "onset", "pitch"])?;
+
+    for (i, tec) in tecs.iter().enumerate() {
+        for (j, occurrence) in tec.occurrences_iter().enumerate() {
+            for point in occurrence.as_ref() {
+                writer.serialize((
+                    i + 1,
+                    j + 1,
+                    component_f64(point, 0)?,
+                    component_f64(point, 1)?,
+                ))?;
+            }
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads TECs from a CSV file written with `write_tecs_to_csv`. The consecutive rows with the
+/// same pattern number form a TEC, and the first occurrence of each TEC is read as its
+/// pattern. The translators of the TECs are computed from the first points of the occurrences.
+///
+/// # Arguments
+///
+/// * `path` - The path to the CSV file
+pub fn read_tecs_from_csv(path: &Path) -> Result<Vec<Tec<Point2DRf64>>, PoSemirError> {
+    let mut reader = csv::Reader::from_path(path)?;
+    // The points of the occurrences of each TEC.
+    let mut tec_occurrences: Vec<Vec<Vec<Point2DRf64>>> = Vec::new();
+    let mut previous = None;
+
+    for result in reader.records() {
+        let record = result?;
+
+        let pattern = get_i64_value_at(&record, 0)?;
+        let occurrence = get_i64_value_at(&record, 1)?;
+        let point = Point2DRf64::new(get_f64_value_at(&record, 2)?, get_f64_value_at(&record, 3)?);
+
+        match previous {
+            Some((p, o)) if p == pattern && o == occurrence => {}
+            Some((p, _)) if p == pattern => {
+                if let Some(occurrences) = tec_occurrences.last_mut() {
+                    occurrences.push(Vec::new());
+                }
+            }
+            _ => tec_occurrences.push(vec![Vec::new()]),
+        }
+        previous = Some((pattern, occurrence));
+
+        if let Some(points) = tec_occurrences.last_mut().and_then(|o| o.last_mut()) {
+            points.push(point);
+        }
+    }
+
+    tec_occurrences
+        .iter()
+        .map(|occurrences| tec_from_occurrences(occurrences))
+        .collect()
+}
+
+fn tec_from_occurrences(
+    occurrences: &[Vec<Point2DRf64>],
+) -> Result<Tec<Point2DRf64>, PoSemirError> {
+    let patterns: Vec<Pattern<Point2DRf64>> = occurrences
+        .iter()
+        .map(|points| Pattern::new(&points.iter().collect()))
+        .collect();
+    let pattern = &patterns[0];
+
+    let mut translators = Vec::with_capacity(patterns.len() - 1);
+    for occurrence in &patterns[1..] {
+        if occurrence.len() != pattern.len() {
+            return Err(PoSemirError::InvalidCsv(String::from(
+                "occurrence size does not match pattern size",
+            )));
+        }
+        translators.push(occurrence[0] - pattern[0]);
+    }
+
+    Ok(Tec {
+        pattern: pattern.clone(),
+        translators,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use std::io::Write;
 
-    use crate::io::csv::{csv_to_2d_point_f64, csv_to_2d_point_i64, csv_to_rounded_2d_point_f64};
-    use crate::point_set::point::{Point2DRf64, Point2Df64, Point2Di64};
+    use num_rational::Rational64;
+
+    use crate::error::PoSemirError;
+    use crate::io::csv::{
+        csv_reader_to_rounded_2d_point_f64, csv_to_2d_point_f64, csv_to_2d_point_i64,
+        csv_to_points_nd, csv_to_points_nd_by_name, csv_to_rational_2d_point,
+        csv_to_rounded_2d_point_f64, csv_to_weighted_2d_point_f64, read_tecs_from_csv,
+        write_tecs_to_csv,
+    };
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::{Point, Point2DRf64, Point2Df64, Point2Di64};
+    use crate::point_set::point_rational::Point2DRational;
+    use crate::point_set::tec::Tec;
 
     #[test]
     fn test_csv_to_float_points() {
@@ -163,6 +467,10 @@ mod tests {
         assert_eq!(Point2DRf64::new(-1.0, 2.0), points[0]);
         assert_eq!(Point2DRf64::new(0.0, 3.0), points[1]);
         assert_eq!(Point2DRf64::new(2.1, 1.1), points[2]);
+
+        let from_reader = csv_reader_to_rounded_2d_point_f64(content.as_bytes()).unwrap();
+        assert_eq!(3, from_reader.len());
+        assert!(from_reader.contains(&Point2DRf64::new(-1.0, 2.0)));
     }
 
     #[test]
@@ -179,4 +487,126 @@ mod tests {
         assert_eq!(Point2Di64 { x: 0, y: 3 }, points[1]);
         assert_eq!(Point2Di64 { x: 2, y: 1 }, points[2]);
     }
+
+    #[test]
+    fn test_csv_to_rational_points() {
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        let content = "onset, pitch \n 0, 60 \n 1/3, 62.0 \n 0.667, 64 \n";
+        tmp_file.write_all(content.as_bytes()).unwrap();
+
+        let points = csv_to_rational_2d_point(tmp_file.path(), 12).unwrap();
+        assert_eq!(
+            vec![
+                Point2DRational {
+                    onset: Rational64::ZERO,
+                    pitch: 60
+                },
+                Point2DRational {
+                    onset: Rational64::new(1, 3),
+                    pitch: 62
+                },
+                Point2DRational {
+                    onset: Rational64::new(2, 3),
+                    pitch: 64
+                },
+            ],
+            points
+        );
+
+        let mut invalid = tempfile::NamedTempFile::new().unwrap();
+        invalid.write_all(b"onset, pitch \n 1/0, 60 \n").unwrap();
+        assert!(matches!(
+            csv_to_rational_2d_point(invalid.path(), 12),
+            Err(PoSemirError::InvalidNumber(_))
+        ));
+    }
+
+    #[test]
+    fn test_csv_to_weighted_points() {
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        let content =
+            "onset, pitch, duration, velocity \n 0.0, 60, 1.0, 80 \n 1.0, 62, 1.0, 110 \n";
+        tmp_file.write_all(content.as_bytes()).unwrap();
+
+        let points = csv_to_weighted_2d_point_f64(tmp_file.path(), 3).unwrap();
+        assert_eq!(2, points.len());
+        assert_eq!(80.0, points[0].weight());
+        assert_eq!(62.0, points[1].y);
+        assert_eq!(110.0, points[1].weight());
+    }
+
+    #[test]
+    fn test_csv_to_selected_columns() {
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        let content = "pitch, onset, duration, velocity, channel \n 60, 0.0, 1.0, 80, 1 \n 62, 1.5, 0.5, 90, 2 \n";
+        tmp_file.write_all(content.as_bytes()).unwrap();
+
+        let points = csv_to_points_nd(tmp_file.path(), &[1, 0, 4]).unwrap();
+        assert_eq!(2, points.len());
+        assert_eq!(&[0.0, 60.0, 1.0], points[0].components());
+        assert_eq!(&[1.5, 62.0, 2.0], points[1].components());
+
+        let by_name =
+            csv_to_points_nd_by_name(tmp_file.path(), &["onset", "pitch", "channel"]).unwrap();
+        assert_eq!(points, by_name);
+
+        assert!(matches!(
+            csv_to_points_nd_by_name(tmp_file.path(), &["onset", "time"]),
+            Err(PoSemirError::MissingColumn(name)) if name == "time"
+        ));
+        assert!(matches!(
+            csv_to_points_nd(tmp_file.path(), &[0, 5]),
+            Err(PoSemirError::MissingValue(5))
+        ));
+    }
+
+    #[test]
+    fn test_write_tecs_to_csv() {
+        let tecs = vec![Tec {
+            pattern: Pattern::new(&vec![
+                &Point2DRf64::new(1.0, 64.0),
+                &Point2DRf64::new(2.5, 60.0),
+            ]),
+            translators: vec![Point2DRf64::new(4.0, -2.0)],
+        }];
+
+        let mut bytes = Vec::new();
+        write_tecs_to_csv(&tecs, &mut bytes).unwrap();
+
+        let expected = "pattern,occurrence,onset,pitch\n\
+                        1,1,1.0,64.0\n\
+                        1,1,2.5,60.0\n\
+                        1,2,5.0,62.0\n\
+                        1,2,6.5,58.0\n";
+        assert_eq!(expected, String::from_utf8(bytes).unwrap());
+    }
+
+    #[test]
+    fn test_read_tecs_from_csv() {
+        let tecs = vec![
+            Tec {
+                pattern: Pattern::new(&vec![
+                    &Point2DRf64::new(1.0, 64.0),
+                    &Point2DRf64::new(2.5, 60.0),
+                ]),
+                translators: vec![Point2DRf64::new(4.0, -2.0), Point2DRf64::new(8.0, 0.0)],
+            },
+            Tec {
+                pattern: Pattern::new(&vec![&Point2DRf64::new(3.0, 62.0)]),
+                translators: vec![],
+            },
+        ];
+
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        write_tecs_to_csv(&tecs, &mut tmp_file).unwrap();
+        assert_eq!(tecs, read_tecs_from_csv(tmp_file.path()).unwrap());
+
+        let mut invalid_file = tempfile::NamedTempFile::new().unwrap();
+        let content = "pattern,occurrence,onset,pitch\n1,1,1.0,64.0\n1,1,2.0,60.0\n1,2,5.0,64.0\n";
+        invalid_file.write_all(content.as_bytes()).unwrap();
+        assert!(matches!(
+            read_tecs_from_csv(invalid_file.path()),
+            Err(PoSemirError::InvalidCsv(_))
+        ));
+    }
 }
@@ -1,12 +1,18 @@
 use std::fs::File;
-use std::io::BufWriter;
+use std::io::{BufReader, BufWriter, Write};
 use std::path::Path;
 
 use serde_json::{json, Value};
 
+#[cfg(feature = "discovery")]
+use crate::discovery::cosiatec::CosiatecCheckpoint;
+#[cfg(feature = "discovery")]
+use crate::discovery::scored_tec::ScoredTec;
+use crate::error::{component_f64, PoSemirError};
 use crate::point_set::pattern::Pattern;
-use crate::point_set::point::Point;
 use crate::point_set::point::Point2DRf64;
+#[cfg(feature = "discovery")]
+use crate::point_set::set::PointSet;
 use crate::point_set::tec::Tec;
 
 /// Write a set of TECs into separate JSON files, following the following format for each TEC:
@@ -40,28 +46,24 @@ use crate::point_set::tec::Tec;
 /// * `source` - The source of the TECs, e.g, algorithm or analysts name.
 /// * `tecs` - The TECs that are written to JSON
 /// * `path` - Output directory path
-pub fn write_tecs_to_json_files(piece: &str, source: &str, tecs: &[Tec<Point2DRf64>], path: &Path) {
+pub fn write_tecs_to_json_files(
+    piece: &str,
+    source: &str,
+    tecs: &[Tec<Point2DRf64>],
+    path: &Path,
+) -> Result<(), PoSemirError> {
     for (i, tec) in tecs.iter().enumerate() {
         let label = &format!("P{}", i);
-        let expanded = tec.expand();
-        let pattern = pattern_to_json(label, source, &expanded[0]);
-        let occurrences: Vec<Value> = expanded[1..]
-            .iter()
-            .map(|p| pattern_to_json(label, source, p))
-            .collect();
-
-        let json_value = json!({
-            "piece": piece,
-            "pattern": pattern,
-            "occurrences": occurrences
-        });
+        let json_value = tec_to_json(piece, label, source, tec)?;
 
         let file_name = format!("{}{}", label, ".json");
         let pattern_path = path.join(Path::new(&file_name));
 
-        let mut buffered_writer = BufWriter::new(File::create(pattern_path).unwrap());
-        serde_json::to_writer_pretty(&mut buffered_writer, &json_value).unwrap()
+        let mut buffered_writer = BufWriter::new(File::create(pattern_path)?);
+        serde_json::to_writer_pretty(&mut buffered_writer, &json_value)?;
     }
+
+    Ok(())
 }
 
 /// Write a set of TECs into a single JSON file. The TECs are written into a JSON list
@@ -72,44 +74,477 @@ pub fn write_tecs_to_json_files(piece: &str, source: &str, tecs: &[Tec<Point2DRf
 /// * `source` - The source of the TECs, e.g, algorithm or analysts name.
 /// * `tecs` - The TECs that are written to JSON
 /// * `path` - Output path
-pub fn write_tecs_to_json(piece: &str, source: &str, tecs: &[Tec<Point2DRf64>], path: &Path) {
+pub fn write_tecs_to_json(
+    piece: &str,
+    source: &str,
+    tecs: &[Tec<Point2DRf64>],
+    path: &Path,
+) -> Result<(), PoSemirError> {
+    write_tecs_to_json_writer(piece, source, tecs, BufWriter::new(File::create(path)?))
+}
+
+/// Write a set of TECs as a JSON list into the given writer, e.g., the standard output.
+/// The TECs are written as in `write_tecs_to_json`.
+///
+/// # Arguments:
+/// * `piece` - Name of the piece
+/// * `source` - The source of the TECs, e.g, algorithm or analysts name.
+/// * `tecs` - The TECs that are written to JSON
+/// * `writer` - The writer into which the JSON is written
+pub fn write_tecs_to_json_writer<W: Write>(
+    piece: &str,
+    source: &str,
+    tecs: &[Tec<Point2DRf64>],
+    mut writer: W,
+) -> Result<(), PoSemirError> {
     let mut json_values = Vec::new();
     for (i, tec) in tecs.iter().enumerate() {
         let label = &format!("P{}", i);
-        let expanded = tec.expand();
-        let pattern = pattern_to_json(label, source, &expanded[0]);
-        let occurrences: Vec<Value> = expanded[1..]
-            .iter()
-            .map(|p| pattern_to_json(label, source, p))
-            .collect();
+        json_values.push(tec_to_json(piece, label, source, tec)?);
+    }
+
+    serde_json::to_writer_pretty(&mut writer, &json_values)?;
+    writer.flush()?;
+    Ok(())
+}
 
-        json_values.push(json!({
-            "piece": piece,
-            "pattern": pattern,
-            "occurrences": occurrences
-        }));
+/// Write a set of scored TECs into a single JSON file. The TECs are written as in
+/// `write_tecs_to_json`, with the scores of each TEC added to it as a `scores` object:
+/// ```json
+/// "scores": {
+///   "compression_ratio": 1.5,
+///   "compactness": 1.0,
+///   "coverage": 6,
+///   "pattern_width": 2.0,
+///   "pattern_area": 4.0
+/// }
+/// ```
+/// The scores are ignored by `read_tecs_from_json`.
+///
+/// # Arguments:
+/// * `piece` - Name of the piece
+/// * `source` - The source of the TECs, e.g, algorithm or analysts name.
+/// * `scored_tecs` - The scored TECs that are written to JSON
+/// * `path` - Output path
+#[cfg(feature = "discovery")]
+pub fn write_scored_tecs_to_json(
+    piece: &str,
+    source: &str,
+    scored_tecs: &[ScoredTec<Point2DRf64>],
+    path: &Path,
+) -> Result<(), PoSemirError> {
+    let mut json_values = Vec::new();
+    for (i, scored) in scored_tecs.iter().enumerate() {
+        let label = &format!("P{}", i);
+        let mut json_value = tec_to_json(piece, label, source, &scored.tec)?;
+        let scores = &scored.scores;
+        json_value["scores"] = json!({
+            "compression_ratio": scores.compression_ratio,
+            "compactness": scores.compactness,
+            "coverage": scores.coverage,
+            "pattern_width": scores.pattern_width,
+            "pattern_area": scores.pattern_area
+        });
+        json_values.push(json_value);
     }
 
-    let mut buffered_writer = BufWriter::new(File::create(path).unwrap());
-    serde_json::to_writer_pretty(&mut buffered_writer, &json_values).unwrap()
+    write_json(&json_values, path)
 }
 
-fn pattern_to_json(label: &str, source: &str, pattern: &Pattern<Point2DRf64>) -> Value {
-    let data: Vec<Value> = pattern
-        .into_iter()
-        .map(|p| {
-            Value::Array(vec![
-                json!(p.component_f64(0).unwrap()),
-                json!(p.component_f64(1).unwrap()),
-            ])
-        })
-        .collect();
+/// Write the occurrences of a query pattern found in a point set into a single JSON file.
+/// The occurrences are written as a JSON list with a single object that has the same format as
+/// the TECs in `write_tecs_to_json_files`, with the query as the pattern. The occurrences
+/// found by partial matching can have fewer points than the query.
+///
+/// # Arguments:
+/// * `piece` - Name of the piece
+/// * `source` - The source of the occurrences, e.g, the name of the matcher.
+/// * `query` - The query pattern
+/// * `occurrences` - The found occurrences of the query
+/// * `path` - Output path
+pub fn write_occurrences_to_json(
+    piece: &str,
+    source: &str,
+    query: &Pattern<Point2DRf64>,
+    occurrences: &[Pattern<Point2DRf64>],
+    path: &Path,
+) -> Result<(), PoSemirError> {
+    let label = "query";
+    let occurrences = occurrences
+        .iter()
+        .map(|occurrence| pattern_to_json(label, source, occurrence))
+        .collect::<Result<Vec<Value>, PoSemirError>>()?;
+
+    let json_value = json!({
+        "piece": piece,
+        "pattern": pattern_to_json(label, source, query)?,
+        "occurrences": occurrences
+    });
+
+    write_json(&[json_value], path)
+}
+
+/// Write a checkpoint of COSIATEC into a JSON file, so that the computation can be resumed
+/// from it (see `Cosiatec::compute_tecs_from_checkpoint`). The selected TECs are written
+/// in the format of `write_tecs_to_json` and the remaining points as a list of points:
+/// ```json
+/// {
+///   "source": "COSIATEC",
+///   "selected": [ list of TEC objects ],
+///   "remaining": [[1.0, 64.0], [2.0, 60.0], ...]
+/// }
+/// ```
+/// The file is replaced atomically, so an interruption while writing does not corrupt
+/// a previously written checkpoint.
+///
+/// # Arguments:
+/// * `source` - The source of the checkpoint, e.g, the name of the algorithm.
+/// * `checkpoint` - The checkpoint that is written to JSON
+/// * `path` - Output path
+#[cfg(feature = "discovery")]
+pub fn write_checkpoint_to_json(
+    source: &str,
+    checkpoint: &CosiatecCheckpoint<Point2DRf64>,
+    path: &Path,
+) -> Result<(), PoSemirError> {
+    let mut selected = Vec::with_capacity(checkpoint.selected.len());
+    for (i, tec) in checkpoint.selected.iter().enumerate() {
+        selected.push(tec_to_json("", &format!("P{}", i), source, tec)?);
+    }
+
+    let json_value = json!({
+        "source": source,
+        "selected": selected,
+        "remaining": points_to_json(&checkpoint.remaining)?
+    });
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let mut buffered_writer = BufWriter::new(File::create(&tmp_path)?);
+    serde_json::to_writer(&mut buffered_writer, &json_value)?;
+    buffered_writer.flush()?;
+    drop(buffered_writer);
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Reads a checkpoint of COSIATEC from a JSON file written with `write_checkpoint_to_json`.
+///
+/// # Arguments:
+/// * `path` - Path to the JSON file
+#[cfg(feature = "discovery")]
+pub fn read_checkpoint_from_json(
+    path: &Path,
+) -> Result<CosiatecCheckpoint<Point2DRf64>, PoSemirError> {
+    let reader = BufReader::new(File::open(path)?);
+    let json_value: Value = serde_json::from_reader(reader)?;
+
+    let selected = match json_value["selected"].as_array() {
+        Some(selected) => selected
+            .iter()
+            .map(tec_from_json)
+            .collect::<Result<Vec<Tec<Point2DRf64>>, PoSemirError>>()?,
+        None => {
+            return Err(PoSemirError::InvalidJson(String::from(
+                "missing selected TECs",
+            )))
+        }
+    };
+
+    Ok(CosiatecCheckpoint {
+        selected,
+        remaining: PointSet::new(points_from_json(&json_value["remaining"])?),
+    })
+}
+
+fn write_json(json_values: &[Value], path: &Path) -> Result<(), PoSemirError> {
+    let mut buffered_writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(&mut buffered_writer, json_values)?;
+    Ok(())
+}
+
+fn tec_to_json(
+    piece: &str,
+    label: &str,
+    source: &str,
+    tec: &Tec<Point2DRf64>,
+) -> Result<Value, PoSemirError> {
+    let pattern = pattern_to_json(label, source, &tec.pattern)?;
+    let occurrences = tec
+        .occurrences_iter()
+        .skip(1)
+        .map(|p| pattern_to_json(label, source, &p))
+        .collect::<Result<Vec<Value>, PoSemirError>>()?;
+
+    Ok(json!({
+        "piece": piece,
+        "pattern": pattern,
+        "occurrences": occurrences
+    }))
+}
 
-    json!({
+fn pattern_to_json(
+    label: &str,
+    source: &str,
+    pattern: &Pattern<Point2DRf64>,
+) -> Result<Value, PoSemirError> {
+    Ok(json!({
         "label": label,
         "source": source,
         "representation": "point_set",
         "dtype": "float",
-        "data": data
+        "data": points_to_json(pattern)?
+    }))
+}
+
+fn points_to_json<'a>(
+    points: impl IntoIterator<Item = &'a Point2DRf64>,
+) -> Result<Vec<Value>, PoSemirError> {
+    let mut data = Vec::new();
+    for p in points {
+        data.push(Value::Array(vec![
+            json!(component_f64(p, 0)?),
+            json!(component_f64(p, 1)?),
+        ]));
+    }
+    Ok(data)
+}
+
+/// Reads TECs from a JSON file written with `write_tecs_to_json` or `write_tecs_to_json_files`,
+/// that is, the file can contain either a list of TECs or a single TEC.
+/// The translators of the TECs are computed from the first points of the occurrences.
+///
+/// # Arguments:
+/// * `path` - Path to the JSON file
+pub fn read_tecs_from_json(path: &Path) -> Result<Vec<Tec<Point2DRf64>>, PoSemirError> {
+    let reader = BufReader::new(File::open(path)?);
+    let json_value: Value = serde_json::from_reader(reader)?;
+
+    match &json_value {
+        Value::Array(values) => values.iter().map(tec_from_json).collect(),
+        Value::Object(_) => Ok(vec![tec_from_json(&json_value)?]),
+        _ => Err(PoSemirError::InvalidJson(String::from(
+            "expected a TEC object or a list of TEC objects",
+        ))),
+    }
+}
+
+/// Reads a pattern from a JSON file that contains either a pattern object in the format of
+/// the patterns in `write_tecs_to_json_files` or a TEC object, in which case the pattern
+/// of the TEC is read. If the file contains a list, the pattern is read from its first item.
+///
+/// # Arguments:
+/// * `path` - Path to the JSON file
+pub fn read_pattern_from_json(path: &Path) -> Result<Pattern<Point2DRf64>, PoSemirError> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut json_value: Value = serde_json::from_reader(reader)?;
+    if let Value::Array(values) = json_value {
+        json_value = values.into_iter().next().unwrap_or(Value::Null);
+    }
+
+    match json_value.get("pattern") {
+        Some(pattern) => pattern_from_json(pattern),
+        None => pattern_from_json(&json_value),
+    }
+}
+
+fn tec_from_json(value: &Value) -> Result<Tec<Point2DRf64>, PoSemirError> {
+    let pattern = pattern_from_json(&value["pattern"])?;
+    let occurrences = match value["occurrences"].as_array() {
+        Some(occurrences) => occurrences,
+        None => {
+            return Err(PoSemirError::InvalidJson(String::from(
+                "missing occurrences",
+            )))
+        }
+    };
+
+    let mut translators = Vec::with_capacity(occurrences.len());
+    for occurrence in occurrences {
+        let occurrence = pattern_from_json(occurrence)?;
+        if occurrence.len() != pattern.len() || occurrence.is_empty() {
+            return Err(PoSemirError::InvalidJson(String::from(
+                "occurrence size does not match pattern size",
+            )));
+        }
+        translators.push(occurrence[0] - pattern[0]);
+    }
+
+    Ok(Tec {
+        pattern,
+        translators,
     })
 }
+
+fn pattern_from_json(value: &Value) -> Result<Pattern<Point2DRf64>, PoSemirError> {
+    let points = points_from_json(&value["data"])?;
+    Ok(Pattern::new(&points.iter().collect()))
+}
+
+fn points_from_json(value: &Value) -> Result<Vec<Point2DRf64>, PoSemirError> {
+    let data = match value.as_array() {
+        Some(data) => data,
+        None => {
+            return Err(PoSemirError::InvalidJson(String::from(
+                "missing pattern data",
+            )))
+        }
+    };
+
+    let mut points = Vec::with_capacity(data.len());
+    for point in data {
+        let x = point[0].as_f64();
+        let y = point[1].as_f64();
+        match (x, y) {
+            (Some(x), Some(y)) => points.push(Point2DRf64::new(x, y)),
+            _ => {
+                return Err(PoSemirError::InvalidJson(format!(
+                    "invalid point {}",
+                    point
+                )))
+            }
+        }
+    }
+
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use crate::error::PoSemirError;
+    use crate::io::json::{
+        read_pattern_from_json, read_tecs_from_json, write_occurrences_to_json, write_tecs_to_json,
+        write_tecs_to_json_files, write_tecs_to_json_writer,
+    };
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2DRf64;
+    use crate::point_set::tec::Tec;
+
+    fn test_tecs() -> Vec<Tec<Point2DRf64>> {
+        vec![
+            Tec {
+                pattern: Pattern::new(&vec![
+                    &Point2DRf64::new(0.0, 60.0),
+                    &Point2DRf64::new(1.0, 62.0),
+                ]),
+                translators: vec![Point2DRf64::new(2.0, 0.0), Point2DRf64::new(4.0, 5.0)],
+            },
+            Tec {
+                pattern: Pattern::new(&vec![&Point2DRf64::new(0.5, 60.0)]),
+                translators: vec![],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_write_and_read_tecs() {
+        let tmp_file = tempfile::NamedTempFile::new().unwrap();
+        let tecs = test_tecs();
+        write_tecs_to_json("piece", "test", &tecs, tmp_file.path()).unwrap();
+
+        assert_eq!(tecs, read_tecs_from_json(tmp_file.path()).unwrap());
+
+        let mut buffer = Vec::new();
+        write_tecs_to_json_writer("piece", "test", &tecs, &mut buffer).unwrap();
+        assert_eq!(std::fs::read(tmp_file.path()).unwrap(), buffer);
+    }
+
+    #[test]
+    fn test_write_and_read_tec_files() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let tecs = test_tecs();
+        write_tecs_to_json_files("piece", "test", &tecs, tmp_dir.path()).unwrap();
+
+        let read = read_tecs_from_json(&tmp_dir.path().join("P0.json")).unwrap();
+        assert_eq!(vec![tecs[0].clone()], read);
+    }
+
+    #[cfg(feature = "discovery")]
+    #[test]
+    fn test_write_and_read_checkpoint() {
+        use crate::discovery::cosiatec::CosiatecCheckpoint;
+        use crate::io::json::{read_checkpoint_from_json, write_checkpoint_to_json};
+        use crate::point_set::set::PointSet;
+
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let path = tmp_dir.path().join("checkpoint.json");
+        let checkpoint = CosiatecCheckpoint {
+            selected: test_tecs(),
+            remaining: PointSet::new(vec![
+                Point2DRf64::new(1.0 / 3.0, 60.0),
+                Point2DRf64::new(7.0, 61.0),
+            ]),
+        };
+        write_checkpoint_to_json("COSIATEC", &checkpoint, &path).unwrap();
+        // Writing again replaces the previous checkpoint.
+        write_checkpoint_to_json("COSIATEC", &checkpoint, &path).unwrap();
+
+        assert_eq!(checkpoint, read_checkpoint_from_json(&path).unwrap());
+        assert_eq!(1, std::fs::read_dir(tmp_dir.path()).unwrap().count());
+    }
+
+    #[cfg(feature = "discovery")]
+    #[test]
+    fn test_write_scored_tecs() {
+        use crate::discovery::scored_tec::score_tecs;
+        use crate::io::json::write_scored_tecs_to_json;
+
+        let tmp_file = tempfile::NamedTempFile::new().unwrap();
+        let tecs = test_tecs();
+        let point_set = tecs[0].covered_set();
+        let scored_tecs = score_tecs(tecs.clone(), &point_set);
+        write_scored_tecs_to_json("piece", "test", &scored_tecs, tmp_file.path()).unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_reader(std::fs::File::open(tmp_file.path()).unwrap()).unwrap();
+        assert_eq!(6, json[0]["scores"]["coverage"].as_u64().unwrap());
+        assert_eq!(tecs, read_tecs_from_json(tmp_file.path()).unwrap());
+    }
+
+    #[test]
+    fn test_write_occurrences_and_read_query() {
+        let tmp_file = tempfile::NamedTempFile::new().unwrap();
+        let tecs = test_tecs();
+        let query = tecs[0].pattern.clone();
+        write_occurrences_to_json("piece", "exact", &query, &tecs[0].expand(), tmp_file.path())
+            .unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_reader(std::fs::File::open(tmp_file.path()).unwrap()).unwrap();
+        assert_eq!(3, json[0]["occurrences"].as_array().unwrap().len());
+
+        // Lists of TECs and pattern objects are also accepted as queries.
+        assert_eq!(query, read_pattern_from_json(tmp_file.path()).unwrap());
+        let mut query_file = tempfile::NamedTempFile::new().unwrap();
+        query_file
+            .write_all(json[0]["pattern"].to_string().as_bytes())
+            .unwrap();
+        assert_eq!(query, read_pattern_from_json(query_file.path()).unwrap());
+    }
+
+    #[test]
+    fn test_read_invalid_json() {
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        tmp_file
+            .write_all("[{ \"pattern\": { \"data\": [[1.0]] } }]".as_bytes())
+            .unwrap();
+
+        assert!(matches!(
+            read_tecs_from_json(tmp_file.path()),
+            Err(PoSemirError::InvalidJson(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_to_missing_directory() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let path = tmp_dir.path().join("missing").join("patterns.json");
+
+        assert!(matches!(
+            write_tecs_to_json("piece", "test", &test_tecs(), &path),
+            Err(PoSemirError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,598 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use num_rational::Rational64;
+
+use crate::error::PoSemirError;
+use crate::io::xml::{attribute, Tag, TagReader};
+use crate::point_set::note_point_set::{NoteMetadata, NotePointSet, NotePointSetBuilder};
+use crate::point_set::point::Point2DRf64;
+use crate::point_set::point_pitch::chromatic_pitch;
+use crate::point_set::point_rational::to_f64;
+use crate::point_set::set::PointSet;
+
+/// The pitch of the notes read from an MEI file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MeiPitch {
+    /// The pitch as notated, e.g., the notated pitch of a transposing instrument.
+    Written,
+    /// The pitch as heard, which takes into account the transpositions of the staves
+    /// (`trans.semi` of `staffDef`) and the gestural pitches of the notes
+    /// (`pname.ges` and `oct.ges`).
+    Sounding,
+}
+
+/// Options for reading point sets from MEI files.
+#[derive(Debug, Clone)]
+pub struct MeiOptions {
+    /// The numbers of the staves (the `n` attributes of `staff`) from which the notes are read,
+    /// or all staves if empty.
+    pub staves: Option<Vec<usize>>,
+    /// The numbers of the layers (the `n` attributes of `layer`) from which the notes are read,
+    /// or all layers if empty.
+    pub layers: Option<Vec<usize>>,
+    /// The pitch of the notes.
+    pub pitch: MeiPitch,
+}
+
+impl Default for MeiOptions {
+    fn default() -> Self {
+        MeiOptions {
+            staves: None,
+            layers: None,
+            pitch: MeiPitch::Sounding,
+        }
+    }
+}
+
+fn format_error<V>(message: &str) -> Result<V, PoSemirError> {
+    Err(PoSemirError::InvalidMei(message.to_string()))
+}
+
+/// Returns a point set read from the MEI file at the given path.
+/// Each note is a point, where the x-coordinate is the onset time in quarter notes
+/// and the y-coordinate is the chromatic pitch as a MIDI note number.
+///
+/// The measures are read in the order in which they are encoded, so repeats are not expanded,
+/// and each measure starts where the longest layer of the previous measure ends. Grace notes
+/// are skipped, as they do not have an onset time of their own.
+///
+/// # Arguments
+///
+/// * `path` - The path to the MEI file
+/// * `options` - The options for reading the notes
+pub fn mei_to_point_set(
+    path: &Path,
+    options: &MeiOptions,
+) -> Result<PointSet<Point2DRf64>, PoSemirError> {
+    let mei = fs::read_to_string(path)?;
+    mei_str_to_point_set(&mei, options)
+}
+
+/// Returns a point set read from the contents of an MEI file (see `mei_to_point_set`).
+///
+/// # Arguments
+///
+/// * `mei` - The contents of the MEI file
+/// * `options` - The options for reading the notes
+pub fn mei_str_to_point_set(
+    mei: &str,
+    options: &MeiOptions,
+) -> Result<PointSet<Point2DRf64>, PoSemirError> {
+    Ok(mei_str_to_note_point_set(mei, options)?.point_set().clone())
+}
+
+/// Returns a note point set read from the MEI file at the given path, where the points
+/// are as in `mei_to_point_set`. The voice of each note is the number of its staff,
+/// the measure is the number of its measure, and the identifier is its `xml:id`.
+///
+/// # Arguments
+///
+/// * `path` - The path to the MEI file
+/// * `options` - The options for reading the notes
+pub fn mei_to_note_point_set(
+    path: &Path,
+    options: &MeiOptions,
+) -> Result<NotePointSet<Point2DRf64>, PoSemirError> {
+    let mei = fs::read_to_string(path)?;
+    mei_str_to_note_point_set(&mei, options)
+}
+
+/// Returns a note point set read from the contents of an MEI file
+/// (see `mei_to_note_point_set`).
+///
+/// # Arguments
+///
+/// * `mei` - The contents of the MEI file
+/// * `options` - The options for reading the notes
+pub fn mei_str_to_note_point_set(
+    mei: &str,
+    options: &MeiOptions,
+) -> Result<NotePointSet<Point2DRf64>, PoSemirError> {
+    let mut score = ScoreReader::new(options);
+    let mut reader = TagReader::new(mei);
+    loop {
+        match reader.next_tag() {
+            Ok(Some(Tag::Start {
+                name,
+                attributes,
+                self_closing,
+            })) => {
+                score.start(name, &attributes)?;
+                if self_closing {
+                    score.end(name)?;
+                }
+            }
+            Ok(Some(Tag::End { name })) => score.end(name)?,
+            Ok(None) => break,
+            Err(message) => return format_error(&message),
+        }
+    }
+
+    let mut builder = NotePointSetBuilder::new();
+    for (point, metadata) in score.notes {
+        builder = builder.note(point, metadata);
+    }
+    Ok(builder.build())
+}
+
+/// A note whose child elements (e.g., `accid`) have not been read yet.
+struct PendingNote {
+    step: Option<char>,
+    octave: Option<i32>,
+    alter: Option<i32>,
+    sounding_step: Option<char>,
+    sounding_octave: Option<i32>,
+    duration: Option<Rational64>,
+    grace: bool,
+    id: Option<String>,
+}
+
+/// A chord whose notes share the onset time and the duration of the chord.
+struct Chord {
+    duration: Option<Rational64>,
+    longest_note: Option<Rational64>,
+}
+
+/// Keeps track of the position in the score while reading the elements of an MEI file.
+struct ScoreReader<'a> {
+    options: &'a MeiOptions,
+    /// The transpositions of the staves in semitones.
+    transpositions: HashMap<usize, i32>,
+    /// The length of a full measure in quarter notes given by the meter.
+    meter: Rational64,
+    measure_count: usize,
+    measure: usize,
+    measure_start: Rational64,
+    measure_end: Rational64,
+    staff_count: usize,
+    staff: Option<usize>,
+    layer_count: usize,
+    layer: Option<usize>,
+    time: Rational64,
+    /// The duration ratios of the enclosing tuplets.
+    tuplets: Vec<Rational64>,
+    grace_groups: usize,
+    chord: Option<Chord>,
+    note: Option<PendingNote>,
+    notes: Vec<(Point2DRf64, NoteMetadata)>,
+}
+
+impl<'a> ScoreReader<'a> {
+    fn new(options: &'a MeiOptions) -> ScoreReader<'a> {
+        ScoreReader {
+            options,
+            transpositions: HashMap::new(),
+            meter: Rational64::from_integer(4),
+            measure_count: 0,
+            measure: 0,
+            measure_start: Rational64::ZERO,
+            measure_end: Rational64::ZERO,
+            staff_count: 0,
+            staff: None,
+            layer_count: 0,
+            layer: None,
+            time: Rational64::ZERO,
+            tuplets: Vec::new(),
+            grace_groups: 0,
+            chord: None,
+            note: None,
+            notes: Vec::new(),
+        }
+    }
+
+    fn start(&mut self, name: &str, attributes: &[(&str, String)]) -> Result<(), PoSemirError> {
+        match name {
+            "scoreDef" | "meterSig" => self.read_meter(attributes)?,
+            "staffDef" => {
+                self.read_meter(attributes)?;
+                if let Some(n) = attribute(attributes, "n") {
+                    let transposition = match attribute(attributes, "trans.semi") {
+                        Some(semitones) => parse_number(semitones, "trans.semi")?,
+                        None => 0,
+                    };
+                    self.transpositions
+                        .insert(parse_number(n, "n")?, transposition);
+                }
+            }
+            "measure" => {
+                self.measure_count += 1;
+                self.measure = attribute(attributes, "n")
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or(self.measure_count);
+                self.measure_end = self.measure_start;
+                self.staff_count = 0;
+            }
+            "staff" => {
+                self.staff_count += 1;
+                self.staff = Some(number_or(attributes, self.staff_count)?);
+                self.layer_count = 0;
+            }
+            "layer" => {
+                self.layer_count += 1;
+                self.layer = Some(number_or(attributes, self.layer_count)?);
+                self.time = self.measure_start;
+            }
+            "tuplet" => {
+                let num: i64 = optional_number(attributes, "num")?.unwrap_or(3);
+                let numbase: i64 = optional_number(attributes, "numbase")?.unwrap_or(2);
+                if num <= 0 || numbase <= 0 {
+                    return format_error("tuplet ratio must be positive");
+                }
+                self.tuplets.push(Rational64::new(numbase, num));
+            }
+            "graceGrp" => self.grace_groups += 1,
+            "chord" => {
+                self.chord = Some(Chord {
+                    duration: self.duration(attributes)?,
+                    longest_note: None,
+                });
+            }
+            "note" => {
+                self.note = Some(PendingNote {
+                    step: attribute(attributes, "pname").and_then(|pname| pname.chars().next()),
+                    octave: optional_number(attributes, "oct")?,
+                    alter: alteration(attributes)?,
+                    sounding_step: attribute(attributes, "pname.ges")
+                        .and_then(|pname| pname.chars().next()),
+                    sounding_octave: optional_number(attributes, "oct.ges")?,
+                    duration: self.duration(attributes)?,
+                    grace: attribute(attributes, "grace").is_some() || self.grace_groups > 0,
+                    id: attribute(attributes, "xml:id").map(|id| id.to_string()),
+                });
+            }
+            "accid" => {
+                let alter = alteration(attributes)?;
+                if let Some(note) = &mut self.note {
+                    note.alter = note.alter.or(alter);
+                }
+            }
+            "rest" | "space"
+                if attribute(attributes, "grace").is_none() && self.grace_groups == 0 =>
+            {
+                match self.duration(attributes)? {
+                    Some(duration) => self.advance(duration),
+                    None => return format_error("rest without duration"),
+                }
+            }
+            "mRest" | "mSpace" => self.advance(self.meter),
+            "multiRest" => {
+                let count: i64 = optional_number(attributes, "num")?.unwrap_or(1);
+                self.advance(self.meter * Rational64::from_integer(count));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn end(&mut self, name: &str) -> Result<(), PoSemirError> {
+        match name {
+            "note" => {
+                if let Some(note) = self.note.take() {
+                    self.add_note(note)?;
+                }
+            }
+            "chord" => {
+                if let Some(chord) = self.chord.take() {
+                    if let Some(duration) = chord.duration.or(chord.longest_note) {
+                        self.advance(duration);
+                    }
+                }
+            }
+            "tuplet" => {
+                self.tuplets.pop();
+            }
+            "graceGrp" => self.grace_groups = self.grace_groups.saturating_sub(1),
+            "layer" => {
+                self.measure_end = self.measure_end.max(self.time);
+                self.layer = None;
+            }
+            "staff" => self.staff = None,
+            "measure" => {
+                self.measure_start = if self.measure_end > self.measure_start {
+                    self.measure_end
+                } else {
+                    self.measure_start + self.meter
+                };
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Reads the meter from the `count` and `unit` attributes of `meterSig`, or from
+    /// the `meter.count` and `meter.unit` attributes of `scoreDef` and `staffDef`.
+    fn read_meter(&mut self, attributes: &[(&str, String)]) -> Result<(), PoSemirError> {
+        let (count_name, unit_name) = match attribute(attributes, "count") {
+            Some(_) => ("count", "unit"),
+            None => ("meter.count", "meter.unit"),
+        };
+        let count = match attribute(attributes, count_name) {
+            // Additive meters, e.g., 3+2, are summed.
+            Some(count) => count.split('+').try_fold(0, |sum, term| {
+                Ok::<i64, PoSemirError>(sum + parse_number::<i64>(term.trim(), count_name)?)
+            })?,
+            None => return Ok(()),
+        };
+        let unit: i64 = optional_number(attributes, unit_name)?.unwrap_or(4);
+        if count <= 0 || unit <= 0 {
+            return format_error("meter must be positive");
+        }
+        self.meter = Rational64::new(4 * count, unit);
+        Ok(())
+    }
+
+    /// Returns the duration in quarter notes given by the `dur` and `dots` attributes
+    /// in the enclosing tuplets, or empty if the element has no duration.
+    fn duration(&self, attributes: &[(&str, String)]) -> Result<Option<Rational64>, PoSemirError> {
+        let Some(dur) = attribute(attributes, "dur") else {
+            return Ok(None);
+        };
+        let base = match dur {
+            "long" => Rational64::from_integer(16),
+            "breve" => Rational64::from_integer(8),
+            _ => match dur.parse::<i64>() {
+                Ok(value) if value > 0 && (value as u64).is_power_of_two() => {
+                    Rational64::new(4, value)
+                }
+                _ => return format_error(&format!("unsupported duration {}", dur)),
+            },
+        };
+
+        let dots: u32 = optional_number(attributes, "dots")?.unwrap_or(0);
+        if dots > 8 {
+            return format_error("too many dots");
+        }
+        // Each dot adds half of the previous value: the total is base * (2 - 1 / 2^dots).
+        let dotted = base * Rational64::new((1 << (dots + 1)) - 1, 1 << dots);
+
+        Ok(Some(
+            self.tuplets
+                .iter()
+                .fold(dotted, |duration, ratio| duration * *ratio),
+        ))
+    }
+
+    fn advance(&mut self, duration: Rational64) {
+        if self.layer.is_some() {
+            self.time += duration;
+        }
+    }
+
+    fn add_note(&mut self, note: PendingNote) -> Result<(), PoSemirError> {
+        let (Some(staff), Some(layer)) = (self.staff, self.layer) else {
+            return Ok(());
+        };
+        if note.grace {
+            return Ok(());
+        }
+
+        let onset = self.time;
+        match &mut self.chord {
+            Some(chord) => {
+                chord.longest_note = chord.longest_note.max(note.duration);
+            }
+            None => match note.duration {
+                Some(duration) => self.advance(duration),
+                None => return format_error("note without duration"),
+            },
+        }
+
+        let is_selected = |selection: &Option<Vec<usize>>, number: usize| match selection {
+            Some(numbers) => numbers.contains(&number),
+            None => true,
+        };
+        if !is_selected(&self.options.staves, staff) || !is_selected(&self.options.layers, layer) {
+            return Ok(());
+        }
+
+        let (Some(step), Some(octave)) = (note.step, note.octave) else {
+            return format_error("note without pitch name or octave");
+        };
+        let alter = note.alter.unwrap_or(0);
+        let pitch = match self.options.pitch {
+            MeiPitch::Written => chromatic_pitch(step, alter, octave),
+            MeiPitch::Sounding => match (note.sounding_step, note.sounding_octave) {
+                (None, None) => chromatic_pitch(step, alter, octave)
+                    .map(|pitch| pitch + *self.transpositions.get(&staff).unwrap_or(&0) as f64),
+                (sounding_step, sounding_octave) => chromatic_pitch(
+                    sounding_step.unwrap_or(step),
+                    alter,
+                    sounding_octave.unwrap_or(octave),
+                ),
+            },
+        };
+        let Some(pitch) = pitch else {
+            return format_error(&format!("invalid pitch name {}", step));
+        };
+
+        self.notes.push((
+            Point2DRf64::new(to_f64(&onset), pitch),
+            NoteMetadata {
+                voice: Some(staff),
+                measure: Some(self.measure),
+                id: note.id,
+            },
+        ));
+        Ok(())
+    }
+}
+
+/// Returns the alteration in semitones given by the `accid.ges` attribute, which includes
+/// the alterations of the key signature, or by the `accid` attribute.
+fn alteration(attributes: &[(&str, String)]) -> Result<Option<i32>, PoSemirError> {
+    let Some(accidental) =
+        attribute(attributes, "accid.ges").or_else(|| attribute(attributes, "accid"))
+    else {
+        return Ok(None);
+    };
+    let alter = match accidental {
+        "n" => 0,
+        "s" | "ns" => 1,
+        "f" | "nf" => -1,
+        "ss" | "x" => 2,
+        "ff" => -2,
+        "xs" | "sx" | "ts" => 3,
+        "tf" => -3,
+        _ => return format_error(&format!("unsupported accidental {}", accidental)),
+    };
+    Ok(Some(alter))
+}
+
+fn parse_number<N: std::str::FromStr>(value: &str, name: &str) -> Result<N, PoSemirError> {
+    value
+        .trim()
+        .parse()
+        .or_else(|_| format_error(&format!("invalid {} {}", name, value)))
+}
+
+fn optional_number<N: std::str::FromStr>(
+    attributes: &[(&str, String)],
+    name: &str,
+) -> Result<Option<N>, PoSemirError> {
+    attribute(attributes, name)
+        .map(|value| parse_number(value, name))
+        .transpose()
+}
+
+/// Returns the `n` attribute of the element, or the position of the element among its
+/// siblings if it has no `n` attribute.
+fn number_or(attributes: &[(&str, String)], position: usize) -> Result<usize, PoSemirError> {
+    Ok(optional_number(attributes, "n")?.unwrap_or(position))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::mei::{mei_str_to_note_point_set, mei_str_to_point_set, MeiOptions, MeiPitch};
+    use crate::point_set::point::Point2DRf64;
+
+    const SCORE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef meter.count="2" meter.unit="4">
+      <staffGrp>
+        <staffDef n="1" lines="5"/>
+        <staffDef n="2" lines="5" trans.semi="-2"/>
+      </staffGrp>
+    </scoreDef>
+    <section>
+      <measure n="1">
+        <staff n="1">
+          <layer n="1">
+            <tuplet num="3" numbase="2">
+              <beam>
+                <note xml:id="a" pname="c" oct="4" dur="8"/>
+                <note xml:id="b" pname="d" oct="4" dur="8"/>
+                <note xml:id="c" pname="f" oct="4" dur="8" accid.ges="s"/>
+              </beam>
+            </tuplet>
+            <note pname="g" oct="4" dur="16" grace="acc"/>
+            <chord dur="4">
+              <note xml:id="d" pname="e" oct="4"><accid accid="f"/></note>
+              <note xml:id="e" pname="g" oct="4"/>
+            </chord>
+          </layer>
+          <layer n="2">
+            <note xml:id="f" pname="c" oct="3" dur="2"/>
+          </layer>
+        </staff>
+        <staff n="2">
+          <layer n="1">
+            <note xml:id="g" pname="d" oct="5" dur="4" dots="1"/>
+            <rest dur="8"/>
+          </layer>
+        </staff>
+      </measure>
+      <measure n="2">
+        <staff n="1"><layer n="1"><mRest/></layer></staff>
+        <staff n="2"><layer n="1"><note xml:id="h" pname="e" oct="5" dur="2"/></layer></staff>
+      </measure>
+    </section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+    #[test]
+    fn test_read_score() {
+        let point_set = mei_str_to_point_set(SCORE, &MeiOptions::default()).unwrap();
+        let third = 1.0 / 3.0;
+        let expected = vec![
+            Point2DRf64::new(0.0, 48.0),
+            Point2DRf64::new(0.0, 60.0),
+            Point2DRf64::new(0.0, 72.0),
+            Point2DRf64::new(third, 62.0),
+            Point2DRf64::new(2.0 * third, 66.0),
+            Point2DRf64::new(1.0, 63.0),
+            Point2DRf64::new(1.0, 67.0),
+            Point2DRf64::new(2.0, 74.0),
+        ];
+        assert_eq!(expected, point_set.points());
+
+        let written = MeiOptions {
+            pitch: MeiPitch::Written,
+            ..MeiOptions::default()
+        };
+        let point_set = mei_str_to_point_set(SCORE, &written).unwrap();
+        assert_eq!(Point2DRf64::new(0.0, 74.0), point_set[2]);
+        assert_eq!(Point2DRf64::new(2.0, 76.0), point_set[7]);
+    }
+
+    #[test]
+    fn test_select_staves_and_layers() {
+        let options = MeiOptions {
+            staves: Some(vec![1]),
+            layers: Some(vec![2]),
+            ..MeiOptions::default()
+        };
+        let notes = mei_str_to_note_point_set(SCORE, &options).unwrap();
+        assert_eq!(1, notes.point_set().len());
+        assert_eq!(Point2DRf64::new(0.0, 48.0), notes.point_set()[0]);
+        assert_eq!(Some(1), notes.notes(0)[0].voice);
+        assert_eq!(Some(1), notes.notes(0)[0].measure);
+        assert_eq!(Some("f".to_string()), notes.notes(0)[0].id);
+
+        let options = MeiOptions {
+            staves: Some(vec![2]),
+            ..MeiOptions::default()
+        };
+        let notes = mei_str_to_note_point_set(SCORE, &options).unwrap();
+        assert_eq!(2, notes.point_set().len());
+        assert_eq!(Some(2), notes.notes(1)[0].measure);
+        assert_eq!(Some("h".to_string()), notes.notes(1)[0].id);
+    }
+
+    #[test]
+    fn test_invalid_mei() {
+        let options = MeiOptions::default();
+        assert!(mei_str_to_point_set("<measure><staff><layer><note dur='4'", &options).is_err());
+        assert!(mei_str_to_point_set(
+            "<measure><staff><layer><note pname='c' oct='4' dur='3'/></layer></staff></measure>",
+            &options
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,881 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::fs;
+use std::path::Path;
+
+use crate::error::{component_f64, PoSemirError};
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::{Point, Point2DRf64, Point2DWeighted};
+use crate::point_set::point_rational::{approximate, Point2DRational};
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// The default tempo of MIDI files in microseconds per beat (120 bpm).
+const DEFAULT_TEMPO: f64 = 500000.0;
+
+/// The unit of the onset times of the points read from a MIDI file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TimeUnit {
+    /// Onsets in beats (quarter notes).
+    Beats,
+    /// Onsets in seconds, taking the tempo changes of the file into account.
+    Seconds,
+}
+
+/// Options for reading point sets from MIDI files.
+#[derive(Debug, Clone)]
+pub struct MidiOptions {
+    /// The channels (0-15) from which the notes are read, or all channels if empty.
+    pub channels: Option<Vec<u8>>,
+    /// The unit of the onset times.
+    pub time_unit: TimeUnit,
+    /// The number of ticks per beat used for converting ticks to beats. If empty, the division
+    /// given in the header of the file is used. This must be given for reading onsets in beats
+    /// from files that use SMPTE time division.
+    pub ticks_per_beat: Option<f64>,
+}
+
+impl Default for MidiOptions {
+    fn default() -> Self {
+        MidiOptions {
+            channels: None,
+            time_unit: TimeUnit::Beats,
+            ticks_per_beat: None,
+        }
+    }
+}
+
+/// Options for writing patterns into MIDI files.
+#[derive(Debug, Clone)]
+pub struct MidiWriteOptions {
+    /// The number of ticks per beat (the time division of the file), at most 32767.
+    pub ticks_per_beat: u16,
+    /// The tempo in beats per minute.
+    pub tempo: f64,
+    /// The duration of each note in beats, as the points do not have durations.
+    pub note_duration: f64,
+    /// The velocity of the notes (1-127).
+    pub velocity: u8,
+}
+
+impl Default for MidiWriteOptions {
+    fn default() -> Self {
+        MidiWriteOptions {
+            ticks_per_beat: 480,
+            tempo: 120.0,
+            note_duration: 0.5,
+            velocity: 100,
+        }
+    }
+}
+
+/// The channels of the tracks of written files, which skip the percussion channel
+/// of General MIDI (channel 10, i.e., 9 when counting from zero).
+const WRITE_CHANNELS: [u8; 15] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 10, 11, 12, 13, 14, 15];
+
+/// The largest tick of written files, so that the delta times fit into variable-length
+/// quantities of four bytes.
+const MAX_WRITE_TICK: f64 = 0x0FFFFFFF as f64;
+
+fn format_error<V>(message: &str) -> Result<V, PoSemirError> {
+    Err(PoSemirError::InvalidMidi(message.to_string()))
+}
+
+/// The time division given in the header of a MIDI file.
+enum Division {
+    TicksPerBeat(f64),
+    TicksPerSecond(f64),
+}
+
+/// Converts ticks into onset times.
+type OnsetConverter = Box<dyn Fn(u64) -> f64>;
+
+/// A note onset read from a track.
+struct NoteOn {
+    tick: u64,
+    channel: u8,
+    key: u8,
+    velocity: u8,
+}
+
+/// Reads the bytes of a MIDI file.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn is_at_end(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+
+    fn read_bytes(&mut self, count: usize) -> Result<&'a [u8], PoSemirError> {
+        if self.bytes.len() - self.position < count {
+            return format_error("unexpected end of data");
+        }
+        let bytes = &self.bytes[self.position..self.position + count];
+        self.position += count;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, PoSemirError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, PoSemirError> {
+        let bytes = self.read_bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, PoSemirError> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Reads a variable-length quantity of at most four bytes.
+    fn read_variable_length(&mut self) -> Result<u32, PoSemirError> {
+        let mut value = 0;
+        for _ in 0..4 {
+            let byte = self.read_u8()?;
+            value = (value << 7) | (byte & 0x7F) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        format_error("variable-length quantity is too long")
+    }
+}
+
+/// Returns a point set read from the standard MIDI file at the given path.
+/// Each note onset is a point, where the x-coordinate is the onset time
+/// and the y-coordinate is the MIDI note number.
+///
+/// # Arguments
+///
+/// * `path` - The path to the MIDI file
+/// * `options` - The options for reading the notes
+pub fn midi_to_point_set(
+    path: &Path,
+    options: &MidiOptions,
+) -> Result<PointSet<Point2DRf64>, PoSemirError> {
+    let bytes = fs::read(path)?;
+    midi_bytes_to_point_set(&bytes, options)
+}
+
+/// Returns a point set read from the contents of a standard MIDI file
+/// (see `midi_to_point_set`).
+///
+/// # Arguments
+///
+/// * `bytes` - The contents of the MIDI file
+/// * `options` - The options for reading the notes
+pub fn midi_bytes_to_point_set(
+    bytes: &[u8],
+    options: &MidiOptions,
+) -> Result<PointSet<Point2DRf64>, PoSemirError> {
+    let points = read_points(bytes, options, |onset, note| {
+        Point2DRf64::new(onset, note.key as f64)
+    })?;
+    Ok(PointSet::new(points))
+}
+
+/// Returns a point set read from the standard MIDI file at the given path as in
+/// `midi_to_point_set`, but with exact rational onsets. Onsets in beats are the exact
+/// fractions of ticks per beat, so, e.g., triplets are not rounded. Onsets in seconds are
+/// approximated as described in `Point2DRational`.
+///
+/// # Arguments
+///
+/// * `path` - The path to the MIDI file
+/// * `options` - The options for reading the notes
+pub fn midi_to_rational_point_set(
+    path: &Path,
+    options: &MidiOptions,
+) -> Result<PointSet<Point2DRational>, PoSemirError> {
+    let bytes = fs::read(path)?;
+    midi_bytes_to_rational_point_set(&bytes, options)
+}
+
+/// Returns a point set with rational onsets read from the contents of a standard MIDI file
+/// (see `midi_to_rational_point_set`).
+///
+/// # Arguments
+///
+/// * `bytes` - The contents of the MIDI file
+/// * `options` - The options for reading the notes
+pub fn midi_bytes_to_rational_point_set(
+    bytes: &[u8],
+    options: &MidiOptions,
+) -> Result<PointSet<Point2DRational>, PoSemirError> {
+    let notes = read_points(bytes, options, |onset, note| (onset, note.key as i64))?;
+    let points = notes
+        .into_iter()
+        .map(|(onset, pitch)| {
+            // The onsets in beats are fractions of at most 16-bit divisions, which the
+            // approximation recovers exactly.
+            match approximate(onset, Point2DRational::MAX_DENOMINATOR) {
+                Some(onset) => Ok(Point2DRational { onset, pitch }),
+                None => format_error(&format!("onset {} is not representable", onset)),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(PointSet::new(points))
+}
+
+/// Returns a point set read from the standard MIDI file at the given path, where the weight
+/// of each point is the salience of the note given by its velocity scaled to the range (0, 1].
+/// The onsets and pitches of the points are as in `midi_to_point_set`, except that the onsets
+/// are not rounded. The weights of notes
+/// with the same onset and pitch, e.g., on different channels, are summed. With the weights,
+/// the weighted coverage of TECs (see `heuristic::TecStats`) counts accented notes more.
+///
+/// # Arguments
+///
+/// * `path` - The path to the MIDI file
+/// * `options` - The options for reading the notes
+pub fn midi_to_weighted_point_set(
+    path: &Path,
+    options: &MidiOptions,
+) -> Result<PointSet<Point2DWeighted>, PoSemirError> {
+    let bytes = fs::read(path)?;
+    midi_bytes_to_weighted_point_set(&bytes, options)
+}
+
+/// Returns a point set with note velocities as weights read from the contents of a standard
+/// MIDI file (see `midi_to_weighted_point_set`).
+///
+/// # Arguments
+///
+/// * `bytes` - The contents of the MIDI file
+/// * `options` - The options for reading the notes
+pub fn midi_bytes_to_weighted_point_set(
+    bytes: &[u8],
+    options: &MidiOptions,
+) -> Result<PointSet<Point2DWeighted>, PoSemirError> {
+    let points = read_points(bytes, options, |onset, note| Point2DWeighted {
+        x: onset,
+        y: note.key as f64,
+        weight: note.velocity as f64 / 127.0,
+    })?;
+    Ok(PointSet::new_with_summed_weights(points))
+}
+
+/// Writes the occurrences of the TEC into a standard MIDI file at the given path, so that
+/// the occurrences can be listened to, e.g., in a DAW (see `tec_to_midi_bytes`).
+///
+/// # Arguments
+///
+/// * `tec` - The TEC whose occurrences are written
+/// * `path` - The path of the MIDI file
+/// * `options` - The options for writing the notes
+pub fn write_tec_to_midi<T: Point>(
+    tec: &Tec<T>,
+    path: &Path,
+    options: &MidiWriteOptions,
+) -> Result<(), PoSemirError> {
+    fs::write(path, tec_to_midi_bytes(tec, options)?)?;
+    Ok(())
+}
+
+/// Returns the contents of a standard MIDI file (format 1), where each occurrence of the TEC
+/// is a track of its own on a channel of its own. The first track sets the tempo, and the
+/// occurrences follow in the order of `Tec::expand`. The channels are assigned to the tracks
+/// in order, skipping the percussion channel, and they are reused if there are more than 15
+/// occurrences. The first component of each point is the onset time in beats and the second
+/// component is the MIDI note number.
+///
+/// # Arguments
+///
+/// * `tec` - The TEC whose occurrences are written
+/// * `options` - The options for writing the notes
+pub fn tec_to_midi_bytes<T: Point>(
+    tec: &Tec<T>,
+    options: &MidiWriteOptions,
+) -> Result<Vec<u8>, PoSemirError> {
+    let tracks: Vec<Vec<T>> = tec
+        .occurrences_iter()
+        .map(|occurrence| occurrence.into_iter().copied().collect())
+        .collect();
+    write_tracks(&tracks, "Occurrence", options)
+}
+
+/// Writes the patterns into a standard MIDI file at the given path
+/// (see `patterns_to_midi_bytes`).
+///
+/// # Arguments
+///
+/// * `patterns` - The patterns that are written
+/// * `path` - The path of the MIDI file
+/// * `options` - The options for writing the notes
+pub fn write_patterns_to_midi<T: Point>(
+    patterns: &[Pattern<T>],
+    path: &Path,
+    options: &MidiWriteOptions,
+) -> Result<(), PoSemirError> {
+    fs::write(path, patterns_to_midi_bytes(patterns, options)?)?;
+    Ok(())
+}
+
+/// Returns the contents of a standard MIDI file, where each pattern is a track of its own
+/// on a channel of its own, as the occurrences in `tec_to_midi_bytes`.
+///
+/// # Arguments
+///
+/// * `patterns` - The patterns that are written
+/// * `options` - The options for writing the notes
+pub fn patterns_to_midi_bytes<T: Point>(
+    patterns: &[Pattern<T>],
+    options: &MidiWriteOptions,
+) -> Result<Vec<u8>, PoSemirError> {
+    let tracks: Vec<Vec<T>> = patterns
+        .iter()
+        .map(|pattern| pattern.into_iter().copied().collect())
+        .collect();
+    write_tracks(&tracks, "Pattern", options)
+}
+
+/// Returns a format 1 file with a tempo track and a track for the points of each group,
+/// whose names are the given name followed by the number of the group.
+fn write_tracks<T: Point>(
+    groups: &[Vec<T>],
+    name: &str,
+    options: &MidiWriteOptions,
+) -> Result<Vec<u8>, PoSemirError> {
+    if options.ticks_per_beat == 0 || options.ticks_per_beat > 0x7FFF {
+        return format_error("ticks per beat must be between 1 and 32767");
+    }
+    if !(options.tempo > 0.0 && options.tempo.is_finite()) {
+        return format_error("tempo must be positive");
+    }
+    if !(options.note_duration > 0.0 && options.note_duration.is_finite()) {
+        return format_error("note duration must be positive");
+    }
+    if !(1..=127).contains(&options.velocity) {
+        return format_error("velocity must be between 1 and 127");
+    }
+    let track_count = groups.len() + 1;
+    if track_count > u16::MAX as usize {
+        return format_error("too many tracks");
+    }
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"MThd");
+    bytes.extend_from_slice(&6u32.to_be_bytes());
+    bytes.extend_from_slice(&1u16.to_be_bytes());
+    bytes.extend_from_slice(&(track_count as u16).to_be_bytes());
+    bytes.extend_from_slice(&options.ticks_per_beat.to_be_bytes());
+
+    let tempo = (60000000.0 / options.tempo)
+        .round()
+        .clamp(1.0, 0xFFFFFF as f64) as u32;
+    let tempo_bytes = tempo.to_be_bytes();
+    let mut tempo_track = vec![0x00, 0xFF, 0x51, 0x03];
+    tempo_track.extend_from_slice(&tempo_bytes[1..]);
+    write_chunk(&mut bytes, &end_track(tempo_track));
+
+    let ticks_per_beat = options.ticks_per_beat as f64;
+    let duration = (options.note_duration * ticks_per_beat).round().max(1.0);
+    for (index, points) in groups.iter().enumerate() {
+        let channel = WRITE_CHANNELS[index % WRITE_CHANNELS.len()];
+
+        // Events as (tick, note on, key), where note offs precede note ons at the same tick,
+        // so that repeated notes are not cut off.
+        let mut events = Vec::with_capacity(2 * points.len());
+        for point in points {
+            let onset = component_f64(point, 0)? * ticks_per_beat;
+            let key = component_f64(point, 1)?;
+            if !(0.0..=MAX_WRITE_TICK).contains(&(onset.round() + duration)) {
+                return Err(PoSemirError::InvalidPointSet(format!(
+                    "onset {} cannot be written into a MIDI file",
+                    onset / ticks_per_beat
+                )));
+            }
+            if !(0.0..=127.0).contains(&key.round()) {
+                return Err(PoSemirError::InvalidPointSet(format!(
+                    "pitch {} is not a MIDI note number",
+                    key
+                )));
+            }
+            let tick = onset.round() as u32;
+            events.push((tick, true, key.round() as u8));
+            events.push((tick + duration as u32, false, key.round() as u8));
+        }
+        events.sort_unstable();
+
+        let mut track = Vec::new();
+        let track_name = format!("{} {}", name, index + 1);
+        track.push(0x00);
+        track.extend_from_slice(&[0xFF, 0x03]);
+        write_variable_length(&mut track, track_name.len() as u32);
+        track.extend_from_slice(track_name.as_bytes());
+
+        let mut previous_tick = 0;
+        for (tick, note_on, key) in events {
+            write_variable_length(&mut track, tick - previous_tick);
+            previous_tick = tick;
+            if note_on {
+                track.extend_from_slice(&[0x90 | channel, key, options.velocity]);
+            } else {
+                track.extend_from_slice(&[0x80 | channel, key, 0]);
+            }
+        }
+        write_chunk(&mut bytes, &end_track(track));
+    }
+
+    Ok(bytes)
+}
+
+fn end_track(mut track: Vec<u8>) -> Vec<u8> {
+    track.extend_from_slice(&[0x00, 0xFF, 0x2F, 0x00]);
+    track
+}
+
+fn write_chunk(bytes: &mut Vec<u8>, track: &[u8]) {
+    bytes.extend_from_slice(b"MTrk");
+    bytes.extend_from_slice(&(track.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(track);
+}
+
+/// Writes a variable-length quantity, whose value must be at most `MAX_WRITE_TICK`.
+fn write_variable_length(bytes: &mut Vec<u8>, value: u32) {
+    let mut groups = vec![(value & 0x7F) as u8];
+    let mut rest = value >> 7;
+    while rest > 0 {
+        groups.push((rest & 0x7F) as u8 | 0x80);
+        rest >>= 7;
+    }
+    bytes.extend(groups.iter().rev());
+}
+
+/// Reads the note onsets of the channels selected in the options and converts them into
+/// points with `to_point`, which receives the onset time of the note and the note.
+fn read_points<T>(
+    bytes: &[u8],
+    options: &MidiOptions,
+    to_point: impl Fn(f64, &NoteOn) -> T,
+) -> Result<Vec<T>, PoSemirError> {
+    let mut reader = Reader { bytes, position: 0 };
+
+    if reader.read_bytes(4)? != b"MThd" {
+        return format_error("missing header chunk");
+    }
+    let header_length = reader.read_u32()? as usize;
+    if header_length < 6 {
+        return format_error("header chunk is too short");
+    }
+    let _format = reader.read_u16()?;
+    let track_count = reader.read_u16()?;
+    let division = read_division(reader.read_u16()?)?;
+    reader.read_bytes(header_length - 6)?;
+
+    let mut notes = Vec::new();
+    // Tempo changes as (tick, microseconds per beat).
+    let mut tempos = Vec::new();
+    let mut tracks_read = 0;
+    while tracks_read < track_count && !reader.is_at_end() {
+        let chunk_type = reader.read_bytes(4)?;
+        let chunk_length = reader.read_u32()? as usize;
+        let chunk = reader.read_bytes(chunk_length)?;
+        // Unknown chunks must be skipped.
+        if chunk_type == b"MTrk" {
+            read_track(chunk, &mut notes, &mut tempos)?;
+            tracks_read += 1;
+        }
+    }
+
+    let to_time = onset_converter(&division, options, &mut tempos)?;
+    Ok(notes
+        .iter()
+        .filter(|note| match &options.channels {
+            Some(channels) => channels.contains(&note.channel),
+            None => true,
+        })
+        .map(|note| to_point(to_time(note.tick), note))
+        .collect())
+}
+
+fn read_division(division: u16) -> Result<Division, PoSemirError> {
+    if division & 0x8000 == 0 {
+        if division == 0 {
+            return format_error("zero ticks per beat");
+        }
+        return Ok(Division::TicksPerBeat(division as f64));
+    }
+
+    // SMPTE division: negative frames per second in the upper byte
+    // and ticks per frame in the lower byte.
+    let frames_per_second = match -((division >> 8) as u8 as i8 as i16) {
+        29 => 29.97,
+        fps => fps as f64,
+    };
+    let ticks_per_frame = (division & 0xFF) as f64;
+    if ticks_per_frame == 0.0 {
+        return format_error("zero ticks per frame");
+    }
+    Ok(Division::TicksPerSecond(
+        frames_per_second * ticks_per_frame,
+    ))
+}
+
+fn read_track(
+    track: &[u8],
+    notes: &mut Vec<NoteOn>,
+    tempos: &mut Vec<(u64, f64)>,
+) -> Result<(), PoSemirError> {
+    let mut reader = Reader {
+        bytes: track,
+        position: 0,
+    };
+    let mut tick = 0;
+    let mut running_status = None;
+
+    while !reader.is_at_end() {
+        tick += reader.read_variable_length()? as u64;
+
+        let mut status = reader.read_u8()?;
+        match status {
+            0xFF => {
+                let meta_type = reader.read_u8()?;
+                let length = reader.read_variable_length()? as usize;
+                let data = reader.read_bytes(length)?;
+                match meta_type {
+                    // End of track
+                    0x2F => return Ok(()),
+                    // Set tempo
+                    0x51 if length == 3 => {
+                        let tempo = u32::from_be_bytes([0, data[0], data[1], data[2]]);
+                        tempos.push((tick, tempo as f64));
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            0xF0 | 0xF7 => {
+                let length = reader.read_variable_length()? as usize;
+                reader.read_bytes(length)?;
+                running_status = None;
+                continue;
+            }
+            _ => {}
+        }
+
+        // Data bytes without a status byte use the running status.
+        let first_data = if status < 0x80 {
+            let data = status;
+            status = match running_status {
+                Some(running_status) => running_status,
+                None => return format_error("data byte without running status"),
+            };
+            data
+        } else {
+            running_status = Some(status);
+            reader.read_u8()?
+        };
+
+        match status & 0xF0 {
+            0x90 => {
+                let velocity = reader.read_u8()?;
+                // Note on with zero velocity is a note off.
+                if velocity > 0 {
+                    notes.push(NoteOn {
+                        tick,
+                        channel: status & 0x0F,
+                        key: first_data,
+                        velocity,
+                    });
+                }
+            }
+            0xC0 | 0xD0 => {}
+            0x80 | 0xA0 | 0xB0 | 0xE0 => {
+                reader.read_u8()?;
+            }
+            _ => return format_error("unsupported event status"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the function for converting ticks into onset times in the unit given in the options.
+fn onset_converter(
+    division: &Division,
+    options: &MidiOptions,
+    tempos: &mut [(u64, f64)],
+) -> Result<OnsetConverter, PoSemirError> {
+    let ticks_per_beat = match (options.ticks_per_beat, division) {
+        (Some(ticks_per_beat), _) => Some(ticks_per_beat),
+        (None, Division::TicksPerBeat(ticks_per_beat)) => Some(*ticks_per_beat),
+        (None, Division::TicksPerSecond(_)) => None,
+    };
+    if let Some(ticks_per_beat) = ticks_per_beat {
+        if !(ticks_per_beat > 0.0 && ticks_per_beat.is_finite()) {
+            return format_error("ticks per beat must be positive and finite");
+        }
+    }
+
+    match (options.time_unit, ticks_per_beat, division) {
+        (TimeUnit::Beats, Some(ticks_per_beat), _) => {
+            Ok(Box::new(move |tick| tick as f64 / ticks_per_beat))
+        }
+        (TimeUnit::Beats, None, _) => {
+            format_error("ticks per beat must be given for files with SMPTE time division")
+        }
+        (TimeUnit::Seconds, _, Division::TicksPerSecond(ticks_per_second)) => {
+            let ticks_per_second = *ticks_per_second;
+            Ok(Box::new(move |tick| tick as f64 / ticks_per_second))
+        }
+        (TimeUnit::Seconds, ticks_per_beat, Division::TicksPerBeat(division_ticks)) => {
+            // The ticks per beat of the options or the division, validated above.
+            let ticks_per_beat = ticks_per_beat.unwrap_or(*division_ticks);
+            let tempo_map = tempo_map(tempos, ticks_per_beat);
+            Ok(Box::new(move |tick| {
+                // The last tempo change at or before the tick.
+                let i = tempo_map.partition_point(|(start, _, _)| *start <= tick) - 1;
+                let (start, seconds, seconds_per_tick) = tempo_map[i];
+                seconds + (tick - start) as f64 * seconds_per_tick
+            }))
+        }
+    }
+}
+
+/// Returns the tempo changes as (tick, seconds at the tick, seconds per tick),
+/// starting from tick zero.
+fn tempo_map(tempos: &mut [(u64, f64)], ticks_per_beat: f64) -> Vec<(u64, f64, f64)> {
+    // The sort is stable, so the last of simultaneous tempo changes applies.
+    tempos.sort_by_key(|(tick, _)| *tick);
+
+    let seconds_per_tick = |tempo: f64| tempo / 1000000.0 / ticks_per_beat;
+    let mut tempo_map = vec![(0, 0.0, seconds_per_tick(DEFAULT_TEMPO))];
+    for (tick, tempo) in tempos.iter() {
+        let (start, seconds, previous) = tempo_map[tempo_map.len() - 1];
+        let change = (
+            *tick,
+            seconds + (tick - start) as f64 * previous,
+            seconds_per_tick(*tempo),
+        );
+        if *tick == start {
+            let last = tempo_map.len() - 1;
+            tempo_map[last] = change;
+        } else {
+            tempo_map.push(change);
+        }
+    }
+
+    tempo_map
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use num_rational::Rational64;
+
+    use crate::error::PoSemirError;
+    use crate::io::midi::{
+        midi_bytes_to_point_set, midi_bytes_to_rational_point_set,
+        midi_bytes_to_weighted_point_set, midi_to_point_set, patterns_to_midi_bytes,
+        tec_to_midi_bytes, MidiOptions, MidiWriteOptions, TimeUnit,
+    };
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::{Point, Point2DRf64, Point2Df64};
+    use crate::point_set::tec::Tec;
+
+    /// Returns a format 1 file with a tempo track and a note track
+    /// with 480 ticks per beat.
+    fn test_midi() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"MThd");
+        bytes.extend_from_slice(&[0, 0, 0, 6, 0, 1, 0, 2, 0x01, 0xE0]);
+
+        // Tempo 120 bpm at tick 0 and 60 bpm at tick 960.
+        let tempo_track = [
+            0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20, // 500000
+            0x87, 0x40, 0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40, // 1000000
+            0x00, 0xFF, 0x2F, 0x00,
+        ];
+        bytes.extend_from_slice(b"MTrk");
+        bytes.extend_from_slice(&(tempo_track.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&tempo_track);
+
+        let note_track = [
+            0x00, 0x90, 60, 100, // C4 on channel 0 at beat 0
+            0x00, 0x91, 36, 100, // C2 on channel 1 at beat 0
+            0x83, 0x60, 0x90, 60, 0, // Note off with running status at beat 1
+            0x00, 62, 100, // D4 with running status at beat 1
+            0x00, 0xF0, 0x02, 0x01, 0xF7, // Sysex
+            0x87, 0x40, 0x80, 62, 0, // Note off at beat 3
+            0x00, 0x90, 64, 90, // E4 at beat 3
+            0x00, 0xFF, 0x2F, 0x00,
+        ];
+        bytes.extend_from_slice(b"MTrk");
+        bytes.extend_from_slice(&(note_track.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&note_track);
+
+        bytes
+    }
+
+    #[test]
+    fn test_midi_to_points_in_beats() {
+        let point_set = midi_bytes_to_point_set(&test_midi(), &MidiOptions::default()).unwrap();
+
+        assert_eq!(
+            vec![
+                Point2DRf64::new(0.0, 36.0),
+                Point2DRf64::new(0.0, 60.0),
+                Point2DRf64::new(1.0, 62.0),
+                Point2DRf64::new(3.0, 64.0),
+            ],
+            point_set.points()
+        );
+    }
+
+    #[test]
+    fn test_midi_to_rational_points() {
+        // With 1440 ticks per beat, the notes are at the beats 0, 1/3 and 1.
+        let options = MidiOptions {
+            ticks_per_beat: Some(1440.0),
+            ..MidiOptions::default()
+        };
+        let point_set = midi_bytes_to_rational_point_set(&test_midi(), &options).unwrap();
+
+        let onsets: Vec<Rational64> = point_set.into_iter().map(|point| point.onset).collect();
+        assert_eq!(
+            vec![
+                Rational64::ZERO,
+                Rational64::ZERO,
+                Rational64::new(1, 3),
+                Rational64::from_integer(1)
+            ],
+            onsets
+        );
+        assert_eq!(64, point_set[3].pitch);
+    }
+
+    #[test]
+    fn test_midi_with_invalid_ticks_per_beat() {
+        for ticks_per_beat in [0.0, -480.0, f64::NAN, f64::INFINITY] {
+            for time_unit in [TimeUnit::Beats, TimeUnit::Seconds] {
+                let options = MidiOptions {
+                    channels: None,
+                    time_unit,
+                    ticks_per_beat: Some(ticks_per_beat),
+                };
+                assert!(matches!(
+                    midi_bytes_to_point_set(&test_midi(), &options),
+                    Err(PoSemirError::InvalidMidi(_))
+                ));
+                assert!(matches!(
+                    midi_bytes_to_rational_point_set(&test_midi(), &options),
+                    Err(PoSemirError::InvalidMidi(_))
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn test_midi_velocities_to_weights() {
+        let point_set =
+            midi_bytes_to_weighted_point_set(&test_midi(), &MidiOptions::default()).unwrap();
+
+        assert_eq!(4, point_set.len());
+        assert_eq!(100.0 / 127.0, point_set[0].weight());
+        assert_eq!(64.0, point_set[3].y);
+        assert_eq!(90.0 / 127.0, point_set[3].weight());
+    }
+
+    #[test]
+    fn test_midi_to_points_in_seconds_with_channel_filter() {
+        let options = MidiOptions {
+            channels: Some(vec![0]),
+            time_unit: TimeUnit::Seconds,
+            ticks_per_beat: None,
+        };
+        let point_set = midi_bytes_to_point_set(&test_midi(), &options).unwrap();
+
+        // Beats 0-2 at 120 bpm and beat 3 at 60 bpm.
+        assert_eq!(
+            vec![
+                Point2DRf64::new(0.0, 60.0),
+                Point2DRf64::new(0.5, 62.0),
+                Point2DRf64::new(2.0, 64.0),
+            ],
+            point_set.points()
+        );
+    }
+
+    #[test]
+    fn test_midi_file() {
+        let mut tmp_file = tempfile::NamedTempFile::new().unwrap();
+        tmp_file.write_all(&test_midi()).unwrap();
+
+        let options = MidiOptions {
+            ticks_per_beat: Some(240.0),
+            ..Default::default()
+        };
+        let point_set = midi_to_point_set(tmp_file.path(), &options).unwrap();
+        assert_eq!(Point2DRf64::new(6.0, 64.0), point_set[3]);
+
+        assert!(midi_bytes_to_point_set(&test_midi()[..20], &options).is_err());
+    }
+
+    #[test]
+    fn test_tec_to_midi_tracks() {
+        let tec = Tec {
+            pattern: Pattern::new(&vec![
+                &Point2Df64 { x: 0.0, y: 60.0 },
+                &Point2Df64 { x: 0.5, y: 62.0 },
+            ]),
+            translators: vec![
+                Point2Df64 { x: 2.0, y: 0.0 },
+                Point2Df64 { x: 200.0, y: 5.0 },
+            ],
+        };
+        let bytes = tec_to_midi_bytes(&tec, &MidiWriteOptions::default()).unwrap();
+
+        let all = midi_bytes_to_point_set(&bytes, &MidiOptions::default()).unwrap();
+        assert_eq!(6, all.len());
+        for (channel, occurrence) in tec.expand().iter().enumerate() {
+            let options = MidiOptions {
+                channels: Some(vec![channel as u8]),
+                ..MidiOptions::default()
+            };
+            let read = midi_bytes_to_point_set(&bytes, &options).unwrap();
+            let expected: Vec<Point2DRf64> = occurrence
+                .into_iter()
+                .map(|point| Point2DRf64::new(point.x, point.y))
+                .collect();
+            assert_eq!(expected, read.points());
+        }
+
+        let invalid = Tec {
+            translators: vec![Point2Df64 { x: 0.0, y: 100.0 }],
+            ..tec
+        };
+        assert!(tec_to_midi_bytes(&invalid, &MidiWriteOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_patterns_to_midi_skips_percussion_channel() {
+        let patterns: Vec<Pattern<Point2Df64>> = (0..10)
+            .map(|i| {
+                Pattern::new(&vec![&Point2Df64 {
+                    x: i as f64,
+                    y: 60.0 + i as f64,
+                }])
+            })
+            .collect();
+        let bytes = patterns_to_midi_bytes(&patterns, &MidiWriteOptions::default()).unwrap();
+
+        let channel = |channel| {
+            let options = MidiOptions {
+                channels: Some(vec![channel]),
+                ..MidiOptions::default()
+            };
+            midi_bytes_to_point_set(&bytes, &options).unwrap().points()
+        };
+        assert!(channel(9).is_empty());
+        assert_eq!(vec![Point2DRf64::new(9.0, 69.0)], channel(10));
+    }
+}
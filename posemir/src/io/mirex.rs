@@ -0,0 +1,123 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::error::{component_f64, PoSemirError};
+use crate::point_set::point::Point;
+use crate::point_set::tec::Tec;
+
+/// Writes the TECs in the output format of the MIREX "Discovery of Repeated Themes & Sections"
+/// task into the file at the given path. Each TEC is written as a block of its occurrences,
+/// and each occurrence as a block of onset, pitch pairs:
+/// ```text
+/// pattern1
+/// occurrence1
+/// 1.00000, 64.00000
+/// 2.00000, 60.00000
+/// occurrence2
+/// 5.00000, 64.00000
+/// 6.00000, 60.00000
+/// pattern2
+/// ...
+/// ```
+/// The first component of the points is written as the onset and the second as the pitch.
+///
+/// # Arguments
+///
+/// * `tecs` - The TECs that are written
+/// * `path` - Output path
+pub fn write_tecs_to_mirex_file<T: Point>(
+    tecs: &[Tec<T>],
+    path: &Path,
+) -> Result<(), PoSemirError> {
+    write_tecs_to_mirex(tecs, BufWriter::new(File::create(path)?))
+}
+
+/// Writes the TECs in the MIREX format (see `write_tecs_to_mirex_file`) into the given writer.
+///
+/// # Arguments
+///
+/// * `tecs` - The TECs that are written
+/// * `writer` - The writer into which the TECs are written
+pub fn write_tecs_to_mirex<T: Point, W: Write>(
+    tecs: &[Tec<T>],
+    mut writer: W,
+) -> Result<(), PoSemirError> {
+    for (i, tec) in tecs.iter().enumerate() {
+        writeln!(writer, "pattern{}", i + 1)?;
+
+        for (j, occurrence) in tec.occurrences_iter().enumerate() {
+            writeln!(writer, "occurrence{}", j + 1)?;
+            for point in occurrence.as_ref() {
+                writeln!(
+                    writer,
+                    "{:.5}, {:.5}",
+                    component_f64(point, 0)?,
+                    component_f64(point, 1)?
+                )?;
+            }
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PoSemirError;
+    use crate::io::mirex::{write_tecs_to_mirex, write_tecs_to_mirex_file};
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2DRf64;
+    use crate::point_set::point_nd::PointNdf64;
+    use crate::point_set::tec::Tec;
+
+    #[test]
+    fn test_write_tecs_to_mirex() {
+        let tecs = vec![
+            Tec {
+                pattern: Pattern::new(&vec![
+                    &Point2DRf64::new(1.0, 64.0),
+                    &Point2DRf64::new(2.5, 60.0),
+                ]),
+                translators: vec![Point2DRf64::new(4.0, -2.0)],
+            },
+            Tec {
+                pattern: Pattern::new(&vec![&Point2DRf64::new(0.0, 48.0)]),
+                translators: vec![],
+            },
+        ];
+
+        let tmp_file = tempfile::NamedTempFile::new().unwrap();
+        write_tecs_to_mirex_file(&tecs, tmp_file.path()).unwrap();
+
+        let expected = "pattern1\n\
+                        occurrence1\n\
+                        1.00000, 64.00000\n\
+                        2.50000, 60.00000\n\
+                        occurrence2\n\
+                        5.00000, 62.00000\n\
+                        6.50000, 58.00000\n\
+                        pattern2\n\
+                        occurrence1\n\
+                        0.00000, 48.00000\n";
+        assert_eq!(expected, std::fs::read_to_string(tmp_file.path()).unwrap());
+    }
+
+    #[test]
+    fn test_write_points_without_pitch() {
+        let tecs = vec![Tec {
+            pattern: Pattern::new(&vec![&PointNdf64 { components: [1.0] }]),
+            translators: vec![],
+        }];
+
+        assert!(matches!(
+            write_tecs_to_mirex(&tecs, Vec::new()),
+            Err(PoSemirError::MissingComponent(1))
+        ));
+    }
+}
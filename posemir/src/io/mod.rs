@@ -2,5 +2,14 @@
  * (c) Otso Björklund (2021)
  * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
  */
+pub mod abc;
 pub mod csv;
 pub mod json;
+pub mod mei;
+pub mod midi;
+pub mod mirex;
+pub mod musicxml;
+pub mod session;
+pub mod svg;
+pub mod vega_lite;
+mod xml;
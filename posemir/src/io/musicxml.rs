@@ -0,0 +1,555 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use crate::error::{component_f64, PoSemirError};
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+
+/// Options for writing patterns as MusicXML excerpts.
+#[derive(Debug, Clone)]
+pub struct MusicXmlOptions {
+    /// The title of the excerpt, e.g., the label of the pattern.
+    pub title: Option<String>,
+    /// The number of divisions per quarter note, to which the onsets and the durations
+    /// are rounded. The default of 24 represents both 32nd notes and triplets exactly.
+    pub divisions: u32,
+    /// The time signature as (beats, beat type), e.g., (6, 8).
+    pub time_signature: (u32, u32),
+    /// The index of the component that gives the duration of a note in quarter notes, e.g.,
+    /// `point_duration::DURATION_COMPONENT` for `Point3DDuration`. If empty, each note lasts
+    /// until the onset of the next note, and the last note lasts one beat.
+    pub duration_component: Option<usize>,
+}
+
+impl Default for MusicXmlOptions {
+    fn default() -> Self {
+        MusicXmlOptions {
+            title: None,
+            divisions: 24,
+            time_signature: (4, 4),
+            duration_component: None,
+        }
+    }
+}
+
+/// Writes the pattern as a MusicXML excerpt into the file at the given path
+/// (see `pattern_to_musicxml`).
+///
+/// # Arguments
+///
+/// * `pattern` - The pattern that is written
+/// * `path` - The path of the MusicXML file
+/// * `options` - The options for writing the notes
+pub fn write_pattern_to_musicxml<T: Point>(
+    pattern: &Pattern<T>,
+    path: &Path,
+    options: &MusicXmlOptions,
+) -> Result<(), PoSemirError> {
+    fs::write(path, pattern_to_musicxml(pattern, options)?)?;
+    Ok(())
+}
+
+/// Returns the pattern as a MusicXML document with a single staff, so that the pattern can be
+/// opened in notation software. The first component of each point is the onset time in quarter
+/// notes and the second component is the MIDI note number. Notes with the same onset are
+/// written as chords, the gaps between the notes as rests, and notes that cross a barline or
+/// cannot be written as a single note value are split into tied notes. The pitches are spelled
+/// with sharps, except for E flat, A flat and B flat.
+///
+/// # Arguments
+///
+/// * `pattern` - The pattern that is written
+/// * `options` - The options for writing the notes
+pub fn pattern_to_musicxml<T: Point>(
+    pattern: &Pattern<T>,
+    options: &MusicXmlOptions,
+) -> Result<String, PoSemirError> {
+    let (beats, beat_type) = options.time_signature;
+    if options.divisions == 0 || beats == 0 || !beat_type.is_power_of_two() {
+        return Err(PoSemirError::InvalidConfiguration(
+            "divisions and time signature must be positive, and the beat type a power of two"
+                .to_string(),
+        ));
+    }
+    let divisions = options.divisions as i64;
+    let beat = 4 * divisions / beat_type as i64;
+    let measure = beats as i64 * beat;
+    if beat == 0 || 4 * divisions % beat_type as i64 != 0 {
+        return Err(PoSemirError::InvalidConfiguration(format!(
+            "beat type {} is shorter than a division",
+            beat_type
+        )));
+    }
+
+    let chords = chords(pattern, options.duration_component, divisions, beat)?;
+    let events = events(&chords, measure);
+    let values = note_values(divisions);
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n");
+    xml.push_str("<!DOCTYPE score-partwise PUBLIC \"-//Recordare//DTD MusicXML 4.0 Partwise//EN\" \"http://www.musicxml.org/dtds/partwise.dtd\">\n");
+    xml.push_str("<score-partwise version=\"4.0\">\n");
+    if let Some(title) = &options.title {
+        let _ = writeln!(
+            xml,
+            "  <work><work-title>{}</work-title></work>",
+            escape(title)
+        );
+    }
+    xml.push_str("  <part-list>\n    <score-part id=\"P1\"><part-name>Pattern</part-name></score-part>\n  </part-list>\n");
+    xml.push_str("  <part id=\"P1\">\n");
+
+    let pitches: Vec<i64> = chords
+        .iter()
+        .flat_map(|chord| &chord.pitches)
+        .copied()
+        .collect();
+    let mean_pitch = pitches.iter().sum::<i64>() as f64 / pitches.len().max(1) as f64;
+    let clef = if pitches.is_empty() || mean_pitch >= 60.0 {
+        "<sign>G</sign><line>2</line>"
+    } else {
+        "<sign>F</sign><line>4</line>"
+    };
+
+    let measure_count = events.last().map_or(1, |event| event.end() / measure);
+    for measure_index in 0..measure_count {
+        let _ = writeln!(xml, "    <measure number=\"{}\">", measure_index + 1);
+        if measure_index == 0 {
+            let _ = writeln!(
+                xml,
+                "      <attributes><divisions>{}</divisions><key><fifths>0</fifths></key><time><beats>{}</beats><beat-type>{}</beat-type></time><clef>{}</clef></attributes>",
+                divisions, beats, beat_type, clef
+            );
+        }
+        for event in events
+            .iter()
+            .filter(|event| event.start / measure == measure_index)
+        {
+            write_event(&mut xml, event, &values);
+        }
+        xml.push_str("    </measure>\n");
+    }
+
+    xml.push_str("  </part>\n</score-partwise>\n");
+    Ok(xml)
+}
+
+/// Notes that start at the same time, with the onset and the duration in divisions.
+struct Chord {
+    onset: i64,
+    duration: i64,
+    pitches: Vec<i64>,
+}
+
+/// Returns the chords of the notes of the pattern in the order of their onsets.
+fn chords<T: Point>(
+    pattern: &Pattern<T>,
+    duration_component: Option<usize>,
+    divisions: i64,
+    beat: i64,
+) -> Result<Vec<Chord>, PoSemirError> {
+    let to_divisions = |value: f64| {
+        let rounded = (value * divisions as f64).round();
+        if rounded.is_finite() && rounded.abs() < (i64::MAX / 4) as f64 {
+            Ok(rounded as i64)
+        } else {
+            Err(PoSemirError::InvalidPointSet(format!(
+                "time {} cannot be written",
+                value
+            )))
+        }
+    };
+
+    let mut notes = Vec::with_capacity(pattern.len());
+    for point in pattern {
+        let onset = to_divisions(component_f64(point, 0)?)?;
+        let pitch = component_f64(point, 1)?.round();
+        if !(0.0..=127.0).contains(&pitch) {
+            return Err(PoSemirError::InvalidPointSet(format!(
+                "pitch {} is not a MIDI note number",
+                pitch
+            )));
+        }
+        let duration = match duration_component {
+            Some(index) => Some(to_divisions(component_f64(point, index)?)?.max(1)),
+            None => None,
+        };
+        notes.push((onset, pitch as i64, duration));
+    }
+    notes.sort_unstable();
+
+    let mut chords: Vec<Chord> = Vec::new();
+    for (onset, pitch, duration) in notes {
+        match chords.last_mut() {
+            Some(chord) if chord.onset == onset => {
+                if !chord.pitches.contains(&pitch) {
+                    chord.pitches.push(pitch);
+                }
+                // The chord lasts as long as its shortest note.
+                chord.duration = chord.duration.min(duration.unwrap_or(i64::MAX));
+            }
+            _ => chords.push(Chord {
+                onset,
+                duration: duration.unwrap_or(i64::MAX),
+                pitches: vec![pitch],
+            }),
+        }
+    }
+
+    // A chord ends at the latest at the onset of the next chord.
+    let next_onsets: Vec<Option<i64>> = chords
+        .iter()
+        .skip(1)
+        .map(|chord| Some(chord.onset))
+        .chain(std::iter::once(None))
+        .collect();
+    for (chord, next_onset) in chords.iter_mut().zip(next_onsets) {
+        chord.duration = match next_onset {
+            Some(next) => chord.duration.min(next - chord.onset),
+            None if chord.duration == i64::MAX => beat,
+            None => chord.duration,
+        };
+    }
+    Ok(chords)
+}
+
+/// A note, chord or rest of the excerpt within a single measure.
+struct Event {
+    start: i64,
+    duration: i64,
+    /// The pitches of the notes, or empty for a rest.
+    pitches: Vec<i64>,
+    tie_start: bool,
+    tie_stop: bool,
+}
+
+impl Event {
+    fn end(&self) -> i64 {
+        self.start + self.duration
+    }
+}
+
+/// Returns the events of the chords and the rests between them from the start of the measure
+/// of the first chord to the end of the measure of the last chord, split at the barlines.
+fn events(chords: &[Chord], measure: i64) -> Vec<Event> {
+    let mut events = Vec::new();
+    let Some(first) = chords.first() else {
+        events.push(Event {
+            start: 0,
+            duration: measure,
+            pitches: Vec::new(),
+            tie_start: false,
+            tie_stop: false,
+        });
+        return events;
+    };
+
+    let offset = first.onset.div_euclid(measure) * measure;
+    let mut time = offset;
+    let mut add = |start: i64, end: i64, pitches: &[i64]| {
+        let mut position = start;
+        while position < end {
+            let barline = (position.div_euclid(measure) + 1) * measure;
+            let piece_end = end.min(barline);
+            events.push(Event {
+                start: position,
+                duration: piece_end - position,
+                pitches: pitches.to_vec(),
+                tie_start: !pitches.is_empty() && piece_end < end,
+                tie_stop: !pitches.is_empty() && position > start,
+            });
+            position = piece_end;
+        }
+    };
+
+    for chord in chords {
+        if chord.onset > time {
+            add(time, chord.onset, &[]);
+        }
+        add(chord.onset, chord.onset + chord.duration, &chord.pitches);
+        time = chord.onset + chord.duration;
+    }
+    let end = (time + measure - 1).div_euclid(measure) * measure;
+    if end > time {
+        add(time, end, &[]);
+    }
+
+    // Shift the events so that the first measure starts at zero.
+    for event in &mut events {
+        event.start -= offset;
+    }
+    events
+}
+
+/// A note value with its duration in divisions.
+struct NoteValue {
+    duration: i64,
+    name: &'static str,
+    dots: usize,
+    triplet: bool,
+}
+
+/// Returns the note values whose durations are whole numbers of divisions,
+/// from the longest to the shortest.
+fn note_values(divisions: i64) -> Vec<NoteValue> {
+    const NAMES: [(&str, i64, i64); 7] = [
+        ("whole", 4, 1),
+        ("half", 2, 1),
+        ("quarter", 1, 1),
+        ("eighth", 1, 2),
+        ("16th", 1, 4),
+        ("32nd", 1, 8),
+        ("64th", 1, 16),
+    ];
+
+    let mut values = Vec::new();
+    for (name, numer, denom) in NAMES {
+        // The plain, dotted, double dotted and triplet durations in 24ths of the plain value.
+        for (dots, triplet, factor) in [
+            (0, false, 24),
+            (1, false, 36),
+            (2, false, 42),
+            (0, true, 16),
+        ] {
+            let scaled = numer * divisions * factor;
+            if scaled % (24 * denom) == 0 {
+                values.push(NoteValue {
+                    duration: scaled / (24 * denom),
+                    name,
+                    dots,
+                    triplet,
+                });
+            }
+        }
+    }
+    values.sort_by_key(|value| std::cmp::Reverse(value.duration));
+    values
+}
+
+/// Returns the note values of the duration: a single value if there is one with the exact
+/// duration, and otherwise the longest values that fit, preferring values without triplets.
+fn split_duration(duration: i64, values: &[NoteValue]) -> Vec<(i64, &NoteValue)> {
+    let mut pieces = Vec::new();
+    let mut remaining = duration;
+    while remaining > 0 {
+        let value = values
+            .iter()
+            .find(|value| value.duration == remaining)
+            .or_else(|| {
+                values
+                    .iter()
+                    .find(|value| !value.triplet && value.duration <= remaining)
+            })
+            .or_else(|| values.iter().find(|value| value.duration <= remaining));
+        match value {
+            Some(value) => {
+                pieces.push((value.duration, value));
+                remaining -= value.duration;
+            }
+            None => {
+                // Shorter than the shortest value: the duration is exact, but the note is
+                // shown as the shortest value.
+                pieces.push((remaining, values.last().unwrap()));
+                remaining = 0;
+            }
+        }
+    }
+    pieces
+}
+
+fn write_event(xml: &mut String, event: &Event, values: &[NoteValue]) {
+    let pieces = split_duration(event.duration, values);
+    let piece_count = pieces.len();
+    for (index, (duration, value)) in pieces.into_iter().enumerate() {
+        let tie_stop = event.tie_stop || index > 0;
+        let tie_start = event.tie_start || index + 1 < piece_count;
+
+        if event.pitches.is_empty() {
+            let _ = write!(
+                xml,
+                "      <note><rest/><duration>{}</duration><voice>1</voice>",
+                duration
+            );
+            write_value(xml, value);
+            xml.push_str("</note>\n");
+            continue;
+        }
+
+        for (chord_index, pitch) in event.pitches.iter().enumerate() {
+            xml.push_str("      <note>");
+            if chord_index > 0 {
+                xml.push_str("<chord/>");
+            }
+            let (step, alter, octave) = spell(*pitch);
+            xml.push_str("<pitch><step>");
+            xml.push(step);
+            xml.push_str("</step>");
+            if alter != 0 {
+                let _ = write!(xml, "<alter>{}</alter>", alter);
+            }
+            let _ = write!(
+                xml,
+                "<octave>{}</octave></pitch><duration>{}</duration>",
+                octave, duration
+            );
+            if tie_stop {
+                xml.push_str("<tie type=\"stop\"/>");
+            }
+            if tie_start {
+                xml.push_str("<tie type=\"start\"/>");
+            }
+            xml.push_str("<voice>1</voice>");
+            write_value(xml, value);
+            if tie_stop || tie_start {
+                xml.push_str("<notations>");
+                if tie_stop {
+                    xml.push_str("<tied type=\"stop\"/>");
+                }
+                if tie_start {
+                    xml.push_str("<tied type=\"start\"/>");
+                }
+                xml.push_str("</notations>");
+            }
+            xml.push_str("</note>\n");
+        }
+    }
+}
+
+fn write_value(xml: &mut String, value: &NoteValue) {
+    let _ = write!(xml, "<type>{}</type>", value.name);
+    for _ in 0..value.dots {
+        xml.push_str("<dot/>");
+    }
+    if value.triplet {
+        xml.push_str("<time-modification><actual-notes>3</actual-notes><normal-notes>2</normal-notes></time-modification>");
+    }
+}
+
+/// Returns the step, the alteration and the octave of the MIDI note number.
+fn spell(pitch: i64) -> (char, i32, i64) {
+    const SPELLINGS: [(char, i32); 12] = [
+        ('C', 0),
+        ('C', 1),
+        ('D', 0),
+        ('E', -1),
+        ('E', 0),
+        ('F', 0),
+        ('F', 1),
+        ('G', 0),
+        ('A', -1),
+        ('A', 0),
+        ('B', -1),
+        ('B', 0),
+    ];
+    let (step, alter) = SPELLINGS[pitch.rem_euclid(12) as usize];
+    (step, alter, pitch.div_euclid(12) - 1)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::musicxml::{pattern_to_musicxml, MusicXmlOptions};
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::point_duration::{Point3DDuration, DURATION_COMPONENT};
+
+    fn notes(xml: &str) -> Vec<&str> {
+        xml.lines()
+            .map(|line| line.trim())
+            .filter(|line| line.starts_with("<note>"))
+            .collect()
+    }
+
+    #[test]
+    fn test_pattern_to_musicxml() {
+        let points = [
+            Point2Df64 { x: 4.0, y: 60.0 },
+            Point2Df64 { x: 5.0, y: 64.0 },
+            Point2Df64 { x: 5.0, y: 67.0 },
+            Point2Df64 { x: 5.5, y: 63.0 },
+            Point2Df64 { x: 9.0, y: 65.0 },
+        ];
+        let pattern = Pattern::new(&points.iter().collect());
+        let options = MusicXmlOptions {
+            title: Some("P0 & P1".to_string()),
+            ..MusicXmlOptions::default()
+        };
+        let xml = pattern_to_musicxml(&pattern, &options).unwrap();
+
+        assert!(xml.contains("<work-title>P0 &amp; P1</work-title>"));
+        assert_eq!(2, xml.matches("<measure ").count());
+        assert_eq!(
+            vec![
+                "<note><pitch><step>C</step><octave>4</octave></pitch><duration>24</duration><voice>1</voice><type>quarter</type></note>",
+                "<note><pitch><step>E</step><octave>4</octave></pitch><duration>12</duration><voice>1</voice><type>eighth</type></note>",
+                "<note><chord/><pitch><step>G</step><octave>4</octave></pitch><duration>12</duration><voice>1</voice><type>eighth</type></note>",
+                // E flat from 5.5 to 9 is split into a half, an eighth and a quarter.
+                "<note><pitch><step>E</step><alter>-1</alter><octave>4</octave></pitch><duration>48</duration><tie type=\"start\"/><voice>1</voice><type>half</type><notations><tied type=\"start\"/></notations></note>",
+                "<note><pitch><step>E</step><alter>-1</alter><octave>4</octave></pitch><duration>12</duration><tie type=\"stop\"/><tie type=\"start\"/><voice>1</voice><type>eighth</type><notations><tied type=\"stop\"/><tied type=\"start\"/></notations></note>",
+                "<note><pitch><step>E</step><alter>-1</alter><octave>4</octave></pitch><duration>24</duration><tie type=\"stop\"/><voice>1</voice><type>quarter</type><notations><tied type=\"stop\"/></notations></note>",
+                "<note><pitch><step>F</step><octave>4</octave></pitch><duration>24</duration><voice>1</voice><type>quarter</type></note>",
+                "<note><rest/><duration>48</duration><voice>1</voice><type>half</type></note>",
+            ],
+            notes(&xml)
+        );
+    }
+
+    #[test]
+    fn test_durations_and_triplets() {
+        let third = 1.0 / 3.0;
+        let points = [
+            Point3DDuration {
+                onset: 0.0,
+                pitch: 48.0,
+                duration: third,
+            },
+            Point3DDuration {
+                onset: third,
+                pitch: 50.0,
+                duration: third,
+            },
+            Point3DDuration {
+                onset: 2.0 * third,
+                pitch: 52.0,
+                duration: third,
+            },
+            Point3DDuration {
+                onset: 1.0,
+                pitch: 53.0,
+                duration: 0.5,
+            },
+        ];
+        let pattern = Pattern::new(&points.iter().collect());
+        let options = MusicXmlOptions {
+            time_signature: (2, 4),
+            duration_component: Some(DURATION_COMPONENT),
+            ..MusicXmlOptions::default()
+        };
+        let xml = pattern_to_musicxml(&pattern, &options).unwrap();
+
+        assert!(xml.contains("<clef><sign>F</sign><line>4</line></clef>"));
+        assert!(xml.contains("<beats>2</beats><beat-type>4</beat-type>"));
+        let notes = notes(&xml);
+        assert_eq!(5, notes.len());
+        assert_eq!(3, xml.matches("<actual-notes>3</actual-notes>").count());
+        assert!(notes[3].contains("<duration>12</duration>"));
+        assert_eq!(
+            "<note><rest/><duration>12</duration><voice>1</voice><type>eighth</type></note>",
+            notes[4]
+        );
+
+        let invalid = Pattern::new(&vec![&Point2Df64 { x: 0.0, y: 200.0 }]);
+        assert!(pattern_to_musicxml(&invalid, &MusicXmlOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,373 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+//! Analysis sessions in a versioned JSON Lines format, which stores the point set, the algorithm
+//! with its parameters, and the discovered TECs by the indices of their points, so that the
+//! results can be joined back to the source data. A session file consists of the following lines:
+//! ```json
+//! {"format":"posemir-session","version":1,"piece":"bwv772","algorithm":"SIATEC","parameters":{"min_pattern_length":2.0}}
+//! {"points":[[0.0,60.0],[0.5,62.0],[1.0,64.0], ...]}
+//! {"tec":0,"pattern":[0,1],"translators":[[0.5,2.0]],"occurrences":[[0,1],[1,2]]}
+//! ...
+//! ```
+//! The points are written with their unrounded onsets (see `Point2DRf64::get_raw_x`), and the
+//! numbers are written so that they are read back exactly. The pattern of each TEC is given by
+//! the indices of its points in the point set, and the occurrences by the indices of the points
+//! of each occurrence (`null` if an occurrence is not in the point set). The TECs are written
+//! one per line, so they can be written as they are discovered.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::error::PoSemirError;
+use crate::point_set::point::Point2DRf64;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// The identifier of the format in the header of session files.
+const FORMAT: &str = "posemir-session";
+
+/// The version of the format written by `SessionWriter`. Readers accept files up to this version.
+pub const SESSION_VERSION: u64 = 1;
+
+/// An analysis session: the point set of a piece, the algorithm and its parameters,
+/// and the TECs discovered by the algorithm in the point set.
+#[derive(Debug, Clone)]
+pub struct Session {
+    /// The name of the piece.
+    pub piece: String,
+    /// The name of the algorithm, e.g., as given to `create_algorithm`.
+    pub algorithm: String,
+    /// The parameters of the algorithm.
+    pub parameters: HashMap<String, f64>,
+    /// The point set on which the algorithm was run.
+    pub point_set: PointSet<Point2DRf64>,
+    /// The discovered TECs, whose patterns are in the point set.
+    pub tecs: Vec<Tec<Point2DRf64>>,
+}
+
+impl Session {
+    /// Returns the indices of the points of each occurrence of the TEC in the point set
+    /// of this session, in the order of `Tec::expand`, or None for the occurrences that
+    /// are not in the point set.
+    ///
+    /// # Arguments
+    ///
+    /// * `tec` - The TEC whose occurrences are mapped to indices
+    pub fn occurrence_indices(&self, tec: &Tec<Point2DRf64>) -> Vec<Option<Vec<usize>>> {
+        tec.occurrences_iter()
+            .map(|occurrence| self.point_set.find_indices(&occurrence))
+            .collect()
+    }
+}
+
+/// Writes a session file line by line: the header and the point set when the writer is created,
+/// and then the TECs one at a time, e.g., in the output callback of an algorithm.
+pub struct SessionWriter<W: Write> {
+    writer: W,
+    point_set: PointSet<Point2DRf64>,
+    tec_count: usize,
+}
+
+impl<W: Write> SessionWriter<W> {
+    /// Returns a writer that has written the header and the point set of the session.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The writer into which the session is written
+    /// * `piece` - The name of the piece
+    /// * `algorithm` - The name of the algorithm
+    /// * `parameters` - The parameters of the algorithm
+    /// * `point_set` - The point set on which the algorithm is run
+    pub fn new(
+        mut writer: W,
+        piece: &str,
+        algorithm: &str,
+        parameters: &HashMap<String, f64>,
+        point_set: &PointSet<Point2DRf64>,
+    ) -> Result<SessionWriter<W>, PoSemirError> {
+        let header = json!({
+            "format": FORMAT,
+            "version": SESSION_VERSION,
+            "piece": piece,
+            "algorithm": algorithm,
+            "parameters": parameters,
+        });
+        write_line(&mut writer, &header)?;
+
+        let points: Vec<Value> = point_set
+            .into_iter()
+            .map(|point| json!([point.get_raw_x(), point.y]))
+            .collect();
+        write_line(&mut writer, &json!({ "points": points }))?;
+
+        Ok(SessionWriter {
+            writer,
+            point_set: point_set.clone(),
+            tec_count: 0,
+        })
+    }
+
+    /// Writes a TEC, whose pattern must be in the point set of the session.
+    ///
+    /// # Arguments
+    ///
+    /// * `tec` - The TEC that is written
+    pub fn write_tec(&mut self, tec: &Tec<Point2DRf64>) -> Result<(), PoSemirError> {
+        let Some(pattern) = self.point_set.find_indices(&tec.pattern) else {
+            return Err(PoSemirError::InvalidPointSet(String::from(
+                "the pattern of the TEC is not in the point set of the session",
+            )));
+        };
+        let translators: Vec<Value> = tec
+            .translators
+            .iter()
+            .map(|translator| json!([translator.get_raw_x(), translator.y]))
+            .collect();
+        let occurrences: Vec<Option<Vec<usize>>> = tec
+            .occurrences_iter()
+            .map(|occurrence| self.point_set.find_indices(&occurrence))
+            .collect();
+
+        let line = json!({
+            "tec": self.tec_count,
+            "pattern": pattern,
+            "translators": translators,
+            "occurrences": occurrences,
+        });
+        write_line(&mut self.writer, &line)?;
+        self.tec_count += 1;
+        Ok(())
+    }
+
+    /// Flushes the written lines and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, PoSemirError> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+fn write_line<W: Write>(writer: &mut W, value: &Value) -> Result<(), PoSemirError> {
+    serde_json::to_writer(&mut *writer, value)?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Writes the session into a file at the given path.
+///
+/// # Arguments
+///
+/// * `session` - The session that is written
+/// * `path` - Output path
+pub fn write_session(session: &Session, path: &Path) -> Result<(), PoSemirError> {
+    let mut writer = SessionWriter::new(
+        BufWriter::new(File::create(path)?),
+        &session.piece,
+        &session.algorithm,
+        &session.parameters,
+        &session.point_set,
+    )?;
+    for tec in &session.tecs {
+        writer.write_tec(tec)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Reads a session from the file at the given path (see `read_session_from_reader`).
+///
+/// # Arguments
+///
+/// * `path` - Path to the session file
+pub fn read_session(path: &Path) -> Result<Session, PoSemirError> {
+    read_session_from_reader(BufReader::new(File::open(path)?))
+}
+
+/// Reads a session written with `SessionWriter` or `write_session`. Returns an error if the
+/// session was written with a newer version of the format, or if the indices of a TEC are
+/// not in the point set.
+///
+/// # Arguments
+///
+/// * `reader` - The reader from which the session is read
+pub fn read_session_from_reader<R: BufRead>(reader: R) -> Result<Session, PoSemirError> {
+    let mut lines = reader.lines().filter(|line| match line {
+        Ok(line) => !line.trim().is_empty(),
+        Err(_) => true,
+    });
+    let mut next_value = || -> Result<Option<Value>, PoSemirError> {
+        match lines.next() {
+            Some(line) => Ok(Some(serde_json::from_str(&line?)?)),
+            None => Ok(None),
+        }
+    };
+
+    let Some(header) = next_value()? else {
+        return invalid("missing header");
+    };
+    if header["format"] != FORMAT {
+        return invalid("not a session file");
+    }
+    match header["version"].as_u64() {
+        Some(version) if version <= SESSION_VERSION => {}
+        Some(version) => return invalid(&format!("unsupported session version {}", version)),
+        None => return invalid("missing version"),
+    }
+    let mut parameters = HashMap::new();
+    if let Some(values) = header["parameters"].as_object() {
+        for (name, value) in values {
+            let Some(value) = value.as_f64() else {
+                return invalid(&format!("parameter {} is not a number", name));
+            };
+            parameters.insert(name.clone(), value);
+        }
+    }
+
+    let Some(points) = next_value()? else {
+        return invalid("missing points");
+    };
+    let point_set = PointSet::new(
+        points_from_json(&points["points"])?
+            .into_iter()
+            .map(|[x, y]| Point2DRf64::new(x, y))
+            .collect(),
+    );
+
+    let mut tecs = Vec::new();
+    while let Some(value) = next_value()? {
+        let Some(indices) = value["pattern"].as_array() else {
+            return invalid("missing pattern indices");
+        };
+        let mut pattern = Vec::with_capacity(indices.len());
+        for index in indices {
+            match index.as_u64() {
+                Some(index) if (index as usize) < point_set.len() => pattern.push(index as usize),
+                _ => return invalid("pattern index is not in the point set"),
+            }
+        }
+        let translators = points_from_json(&value["translators"])?
+            .into_iter()
+            .map(|[x, y]| Point2DRf64::new(x, y))
+            .collect();
+        tecs.push(Tec {
+            pattern: point_set.get_pattern(&pattern),
+            translators,
+        });
+    }
+
+    Ok(Session {
+        piece: header["piece"].as_str().unwrap_or_default().to_string(),
+        algorithm: header["algorithm"].as_str().unwrap_or_default().to_string(),
+        parameters,
+        point_set,
+        tecs,
+    })
+}
+
+fn invalid<V>(message: &str) -> Result<V, PoSemirError> {
+    Err(PoSemirError::InvalidSession(message.to_string()))
+}
+
+fn points_from_json(value: &Value) -> Result<Vec<[f64; 2]>, PoSemirError> {
+    let Some(values) = value.as_array() else {
+        return invalid("missing points");
+    };
+    values
+        .iter()
+        .map(|point| match (point[0].as_f64(), point[1].as_f64()) {
+            (Some(x), Some(y)) => Ok([x, y]),
+            _ => invalid("a point must have two numeric components"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::io::Cursor;
+
+    use crate::io::session::{
+        read_session, read_session_from_reader, write_session, Session, SessionWriter,
+    };
+    use crate::point_set::point::Point2DRf64;
+    use crate::point_set::set::PointSet;
+    use crate::point_set::tec::Tec;
+
+    fn test_session() -> Session {
+        let third = 1.0 / 3.0;
+        let point_set = PointSet::new(vec![
+            Point2DRf64::new(0.0, 60.0),
+            Point2DRf64::new(third, 62.0),
+            Point2DRf64::new(1.0, 60.0),
+            Point2DRf64::new(1.0 + third, 62.0),
+            Point2DRf64::new(2.0, 67.0),
+        ]);
+        let tec = Tec {
+            pattern: point_set.get_pattern(&[0, 1]),
+            translators: vec![Point2DRf64::new(1.0, 0.0), Point2DRf64::new(5.0, 0.0)],
+        };
+        Session {
+            piece: "test".to_string(),
+            algorithm: "SIATEC".to_string(),
+            parameters: HashMap::from([("min_pattern_length".to_string(), 2.0)]),
+            point_set,
+            tecs: vec![tec],
+        }
+    }
+
+    #[test]
+    fn test_session_round_trip() {
+        let session = test_session();
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let path = tmp_dir.path().join("session.jsonl");
+        write_session(&session, &path).unwrap();
+
+        let read = read_session(&path).unwrap();
+        assert_eq!(session.piece, read.piece);
+        assert_eq!(session.algorithm, read.algorithm);
+        assert_eq!(session.parameters, read.parameters);
+        assert_eq!(session.point_set, read.point_set);
+        assert_eq!(session.tecs, read.tecs);
+        // The unrounded onsets are read back exactly.
+        assert_eq!(1.0 + 1.0 / 3.0, read.point_set[3].get_raw_x());
+
+        // The second occurrence is in the point set and the third is not.
+        assert_eq!(
+            vec![Some(vec![0, 1]), Some(vec![2, 3]), None],
+            read.occurrence_indices(&read.tecs[0])
+        );
+    }
+
+    #[test]
+    fn test_invalid_sessions() {
+        let session = test_session();
+        let mut writer = SessionWriter::new(
+            Vec::new(),
+            &session.piece,
+            &session.algorithm,
+            &session.parameters,
+            &session.point_set,
+        )
+        .unwrap();
+        let outside = Tec {
+            pattern: session.tecs[0]
+                .pattern
+                .translate(&Point2DRf64::new(10.0, 0.0)),
+            translators: Vec::new(),
+        };
+        assert!(writer.write_tec(&outside).is_err());
+        let bytes = writer.finish().unwrap();
+
+        let text = String::from_utf8(bytes).unwrap();
+        let newer = text.replace("\"version\":1", "\"version\":2");
+        assert!(read_session_from_reader(Cursor::new(newer)).is_err());
+        let out_of_range = format!("{}{{\"tec\":0,\"pattern\":[9],\"translators\":[]}}\n", text);
+        assert!(read_session_from_reader(Cursor::new(out_of_range)).is_err());
+        assert!(read_session_from_reader(Cursor::new(text)).is_ok());
+    }
+}
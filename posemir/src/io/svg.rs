@@ -0,0 +1,255 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use crate::error::{component_f64, PoSemirError};
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// The colors of the highlighted groups of points, which are used in a cycle.
+const PALETTE: [&str; 10] = [
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7",
+    "#9c755f", "#bab0ac",
+];
+const POINT_COLOR: &str = "#d3d3d3";
+const PIXELS_PER_ONSET: f64 = 40.0;
+const PIXELS_PER_PITCH: f64 = 6.0;
+const NOTE_WIDTH: f64 = 12.0;
+const MARGIN: f64 = 20.0;
+const TITLE_HEIGHT: f64 = 20.0;
+
+/// Returns an SVG piano roll of the point set with the occurrences of the TEC highlighted,
+/// each occurrence in its own color. The first component of the points is drawn on the
+/// horizontal axis (onset) and the second component on the vertical axis (pitch).
+///
+/// # Arguments
+///
+/// * `title` - The title of the piano roll, e.g., the name of the piece
+/// * `point_set` - The point set that is drawn
+/// * `tec` - The TEC whose occurrences are highlighted
+pub fn tec_to_svg<T: Point>(
+    title: &str,
+    point_set: &PointSet<T>,
+    tec: &Tec<T>,
+) -> Result<String, PoSemirError> {
+    let groups: Vec<Vec<T>> = tec
+        .occurrences_iter()
+        .map(|occurrence| occurrence.into_iter().copied().collect())
+        .collect();
+    piano_roll(title, point_set, &groups)
+}
+
+/// Returns an SVG piano roll of the point set with the points covered by each of the TECs
+/// highlighted, each TEC in its own color (see `tec_to_svg`). Points covered by several TECs
+/// are drawn in the color of the last of them.
+///
+/// # Arguments
+///
+/// * `title` - The title of the piano roll, e.g., the name of the piece
+/// * `point_set` - The point set that is drawn
+/// * `tecs` - The TECs whose covered points are highlighted
+pub fn tecs_to_svg<T: Point>(
+    title: &str,
+    point_set: &PointSet<T>,
+    tecs: &[Tec<T>],
+) -> Result<String, PoSemirError> {
+    let groups: Vec<Vec<T>> = tecs.iter().map(|tec| tec.covered_set().points()).collect();
+    piano_roll(title, point_set, &groups)
+}
+
+/// Writes an SVG piano roll of each TEC (see `tec_to_svg`) into a separate file in the
+/// directory at the given path. The files are named as in `write_tecs_to_json_files`,
+/// i.e., P0.svg, P1.svg, and so on.
+///
+/// # Arguments
+///
+/// * `title` - The title of the piano rolls, e.g., the name of the piece
+/// * `point_set` - The point set that is drawn
+/// * `tecs` - The TECs that are drawn
+/// * `path` - Output directory path
+pub fn write_tecs_to_svg_files<T: Point>(
+    title: &str,
+    point_set: &PointSet<T>,
+    tecs: &[Tec<T>],
+    path: &Path,
+) -> Result<(), PoSemirError> {
+    for (i, tec) in tecs.iter().enumerate() {
+        let svg = tec_to_svg(&format!("{} P{}", title, i), point_set, tec)?;
+        fs::write(path.join(format!("P{}.svg", i)), svg)?;
+    }
+
+    Ok(())
+}
+
+/// The extent of the drawn points.
+struct Bounds {
+    min_onset: f64,
+    max_onset: f64,
+    min_pitch: f64,
+    max_pitch: f64,
+}
+
+impl Bounds {
+    fn x(&self, onset: f64) -> f64 {
+        MARGIN + (onset - self.min_onset) * PIXELS_PER_ONSET
+    }
+
+    fn y(&self, pitch: f64) -> f64 {
+        MARGIN + TITLE_HEIGHT + (self.max_pitch - pitch) * PIXELS_PER_PITCH
+    }
+
+    fn width(&self) -> f64 {
+        self.x(self.max_onset) + NOTE_WIDTH + MARGIN
+    }
+
+    fn height(&self) -> f64 {
+        self.y(self.min_pitch) + PIXELS_PER_PITCH + MARGIN
+    }
+}
+
+fn piano_roll<T: Point>(
+    title: &str,
+    point_set: &PointSet<T>,
+    groups: &[Vec<T>],
+) -> Result<String, PoSemirError> {
+    let mut bounds = Bounds {
+        min_onset: f64::INFINITY,
+        max_onset: f64::NEG_INFINITY,
+        min_pitch: f64::INFINITY,
+        max_pitch: f64::NEG_INFINITY,
+    };
+    for point in point_set.into_iter().chain(groups.iter().flatten()) {
+        let (onset, pitch) = (component_f64(point, 0)?, component_f64(point, 1)?);
+        bounds.min_onset = bounds.min_onset.min(onset);
+        bounds.max_onset = bounds.max_onset.max(onset);
+        bounds.min_pitch = bounds.min_pitch.min(pitch);
+        bounds.max_pitch = bounds.max_pitch.max(pitch);
+    }
+    if bounds.min_onset > bounds.max_onset {
+        bounds = Bounds {
+            min_onset: 0.0,
+            max_onset: 0.0,
+            min_pitch: 0.0,
+            max_pitch: 0.0,
+        };
+    }
+
+    let mut svg = String::new();
+    // Writing into a string cannot fail.
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.1}\" height=\"{:.1}\">",
+        bounds.width(),
+        bounds.height()
+    );
+    let _ = writeln!(
+        svg,
+        "<title>{}</title>\n<text x=\"{:.1}\" y=\"{:.1}\" font-family=\"sans-serif\" font-size=\"14\">{}</text>",
+        escape(title),
+        MARGIN,
+        MARGIN,
+        escape(title)
+    );
+
+    for point in point_set {
+        note(&mut svg, &bounds, point, POINT_COLOR)?;
+    }
+    for (i, group) in groups.iter().enumerate() {
+        let color = PALETTE[i % PALETTE.len()];
+        for point in group {
+            note(&mut svg, &bounds, point, color)?;
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+fn note<T: Point>(
+    svg: &mut String,
+    bounds: &Bounds,
+    point: &T,
+    color: &str,
+) -> Result<(), PoSemirError> {
+    let onset = component_f64(point, 0)?;
+    let pitch = component_f64(point, 1)?;
+    let _ = writeln!(
+        svg,
+        "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"><title>{}, {}</title></rect>",
+        bounds.x(onset),
+        bounds.y(pitch),
+        NOTE_WIDTH,
+        PIXELS_PER_PITCH,
+        color,
+        onset,
+        pitch
+    );
+    Ok(())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::svg::{tec_to_svg, tecs_to_svg, write_tecs_to_svg_files, PALETTE};
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+    use crate::point_set::tec::Tec;
+
+    fn test_data() -> (PointSet<Point2Df64>, Vec<Tec<Point2Df64>>) {
+        let a = Point2Df64 { x: 0.0, y: 60.0 };
+        let b = Point2Df64 { x: 1.0, y: 62.0 };
+        let c = Point2Df64 { x: 2.0, y: 60.0 };
+        let d = Point2Df64 { x: 3.0, y: 62.0 };
+        let e = Point2Df64 { x: 4.0, y: 48.0 };
+        let tecs = vec![
+            Tec {
+                pattern: Pattern::new(&vec![&a, &b]),
+                translators: vec![c - a],
+            },
+            Tec {
+                pattern: Pattern::new(&vec![&e]),
+                translators: vec![],
+            },
+        ];
+        (PointSet::new(vec![a, b, c, d, e]), tecs)
+    }
+
+    #[test]
+    fn test_tec_to_svg() {
+        let (point_set, tecs) = test_data();
+        let svg = tec_to_svg("Op. 1 <test>", &point_set, &tecs[0]).unwrap();
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("<title>Op. 1 &lt;test&gt;</title>"));
+        // All points are drawn, and the occurrences are drawn on top of them.
+        assert_eq!(9, svg.matches("<rect").count());
+        assert_eq!(2, svg.matches(PALETTE[0]).count());
+        assert_eq!(2, svg.matches(PALETTE[1]).count());
+
+        let overview = tecs_to_svg("test", &point_set, &tecs).unwrap();
+        assert_eq!(4, overview.matches(PALETTE[0]).count());
+        assert_eq!(1, overview.matches(PALETTE[1]).count());
+    }
+
+    #[test]
+    fn test_write_tecs_to_svg_files() {
+        let (point_set, tecs) = test_data();
+        let tmp_dir = tempfile::tempdir().unwrap();
+        write_tecs_to_svg_files("test", &point_set, &tecs, tmp_dir.path()).unwrap();
+
+        assert!(tmp_dir.path().join("P0.svg").exists());
+        assert!(tmp_dir.path().join("P1.svg").exists());
+    }
+}
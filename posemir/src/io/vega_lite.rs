@@ -0,0 +1,126 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use serde_json::{json, Value};
+
+use crate::error::{component_f64, PoSemirError};
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+
+const SCHEMA: &str = "https://vega.github.io/schema/vega-lite/v5.json";
+
+/// Returns a Vega-Lite specification that visualizes the given point set as a scatter plot
+/// with the given pattern occurrences highlighted on top of it. The first component of
+/// the points is plotted on the x-axis (onset) and the second component on the y-axis (pitch).
+/// Each occurrence is drawn in its own color, so that the spec can be rendered directly
+/// in, e.g., Jupyter or Observable notebooks.
+///
+/// The returned spec has the form:
+/// ```json
+/// {
+///   "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+///   "title": "Beethoven op.1",
+///   "layer": [
+///     { "data": { "values": [ { "onset": 0.0, "pitch": 60.0 }, ... ] }, ... },
+///     { "data": { "values": [ { "onset": 0.0, "pitch": 60.0, "occurrence": 0 }, ... ] }, ... }
+///   ]
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `title` - The title of the visualization, e.g., the name of the piece
+/// * `point_set` - The point set that is visualized
+/// * `occurrences` - The pattern occurrences that are highlighted
+pub fn to_vega_lite_spec<T: Point>(
+    title: &str,
+    point_set: &PointSet<T>,
+    occurrences: &[Pattern<T>],
+) -> Result<Value, PoSemirError> {
+    let points = point_set
+        .into_iter()
+        .map(|p| point_to_json(p, None))
+        .collect::<Result<Vec<Value>, PoSemirError>>()?;
+
+    let mut occurrence_points = Vec::new();
+    for (i, occurrence) in occurrences.iter().enumerate() {
+        for point in occurrence {
+            occurrence_points.push(point_to_json(point, Some(i))?);
+        }
+    }
+
+    Ok(json!({
+        "$schema": SCHEMA,
+        "title": title,
+        "width": "container",
+        "layer": [
+            {
+                "data": { "values": points },
+                "mark": { "type": "point", "filled": true, "color": "lightgray" },
+                "encoding": {
+                    "x": { "field": "onset", "type": "quantitative" },
+                    "y": { "field": "pitch", "type": "quantitative", "scale": { "zero": false } }
+                }
+            },
+            {
+                "data": { "values": occurrence_points },
+                "mark": { "type": "point", "filled": true },
+                "encoding": {
+                    "x": { "field": "onset", "type": "quantitative" },
+                    "y": { "field": "pitch", "type": "quantitative", "scale": { "zero": false } },
+                    "color": { "field": "occurrence", "type": "nominal" },
+                    "detail": { "field": "occurrence", "type": "nominal" }
+                }
+            }
+        ]
+    }))
+}
+
+fn point_to_json<T: Point>(point: &T, occurrence: Option<usize>) -> Result<Value, PoSemirError> {
+    let onset = component_f64(point, 0)?;
+    let pitch = component_f64(point, 1)?;
+
+    Ok(match occurrence {
+        Some(i) => json!({ "onset": onset, "pitch": pitch, "occurrence": i }),
+        None => json!({ "onset": onset, "pitch": pitch }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::io::vega_lite::to_vega_lite_spec;
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+
+    #[test]
+    fn test_spec_contains_points_and_occurrences() {
+        let a = Point2Df64 { x: 0.0, y: 60.0 };
+        let b = Point2Df64 { x: 1.0, y: 62.0 };
+        let c = Point2Df64 { x: 2.0, y: 60.0 };
+        let d = Point2Df64 { x: 3.0, y: 62.0 };
+        let point_set = PointSet::new(vec![a, b, c, d]);
+        let occurrences = vec![Pattern::new(&vec![&a, &b]), Pattern::new(&vec![&c, &d])];
+
+        let spec = to_vega_lite_spec("test", &point_set, &occurrences).unwrap();
+
+        assert_eq!(json!("test"), spec["title"]);
+        let layers = spec["layer"].as_array().unwrap();
+        assert_eq!(2, layers.len());
+
+        let points = layers[0]["data"]["values"].as_array().unwrap();
+        assert_eq!(4, points.len());
+        assert_eq!(json!({ "onset": 0.0, "pitch": 60.0 }), points[0]);
+
+        let occurrence_points = layers[1]["data"]["values"].as_array().unwrap();
+        assert_eq!(4, occurrence_points.len());
+        assert_eq!(
+            json!({ "onset": 2.0, "pitch": 60.0, "occurrence": 1 }),
+            occurrence_points[2]
+        );
+    }
+}
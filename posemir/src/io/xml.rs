@@ -0,0 +1,251 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+//! A minimal reader for the element structure of XML documents, which is enough for reading
+//! the notes of XML-based score formats. Text content, comments, processing instructions,
+//! CDATA sections and document type declarations are skipped.
+
+/// An element tag read from an XML document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Tag<'a> {
+    /// A start tag with its local name (without a namespace prefix) and attributes,
+    /// which is also the end of the element if it is self-closing.
+    Start {
+        name: &'a str,
+        attributes: Vec<(&'a str, String)>,
+        self_closing: bool,
+    },
+    /// An end tag with its local name.
+    End { name: &'a str },
+}
+
+/// Returns the value of the attribute with the given name among the attributes of a tag.
+pub(crate) fn attribute<'b>(attributes: &'b [(&str, String)], name: &str) -> Option<&'b str> {
+    attributes
+        .iter()
+        .find(|(attribute, _)| *attribute == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// Reads the tags of an XML document in document order.
+pub(crate) struct TagReader<'a> {
+    xml: &'a str,
+    position: usize,
+}
+
+impl<'a> TagReader<'a> {
+    pub(crate) fn new(xml: &'a str) -> TagReader<'a> {
+        TagReader { xml, position: 0 }
+    }
+
+    /// Returns the next tag, None at the end of the document, or an error message
+    /// if the document is malformed.
+    pub(crate) fn next_tag(&mut self) -> Result<Option<Tag<'a>>, String> {
+        loop {
+            let rest = &self.xml[self.position..];
+            let Some(start) = rest.find('<') else {
+                self.position = self.xml.len();
+                return Ok(None);
+            };
+            self.position += start;
+            let rest = &self.xml[self.position..];
+
+            let skipped_end = if rest.starts_with("<!--") {
+                Some("-->")
+            } else if rest.starts_with("<![CDATA[") {
+                Some("]]>")
+            } else if rest.starts_with("<?") {
+                Some("?>")
+            } else {
+                None
+            };
+            if let Some(end) = skipped_end {
+                self.skip_past(end)?;
+                continue;
+            }
+            if rest.starts_with("<!") {
+                self.skip_declaration()?;
+                continue;
+            }
+
+            let end = self.find_tag_end()?;
+            let content = &self.xml[self.position + 1..end];
+            self.position = end + 1;
+            return parse_tag(content).map(Some);
+        }
+    }
+
+    fn skip_past(&mut self, end: &str) -> Result<(), String> {
+        match self.xml[self.position..].find(end) {
+            Some(index) => {
+                self.position += index + end.len();
+                Ok(())
+            }
+            None => Err(format!("missing {}", end)),
+        }
+    }
+
+    /// Skips a declaration such as a document type declaration, which may contain
+    /// an internal subset in brackets.
+    fn skip_declaration(&mut self) -> Result<(), String> {
+        let mut depth = 0;
+        for (index, c) in self.xml[self.position..].char_indices() {
+            match c {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                '>' if depth == 0 => {
+                    self.position += index + 1;
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+        Err("unterminated declaration".to_string())
+    }
+
+    /// Returns the index of the '>' that ends the tag at the current position,
+    /// skipping quoted attribute values.
+    fn find_tag_end(&self) -> Result<usize, String> {
+        let mut quote = None;
+        for (index, c) in self.xml[self.position..].char_indices() {
+            match (quote, c) {
+                (None, '"' | '\'') => quote = Some(c),
+                (Some(q), _) if c == q => quote = None,
+                (None, '>') => return Ok(self.position + index),
+                _ => {}
+            }
+        }
+        Err("unterminated tag".to_string())
+    }
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+fn parse_tag(content: &str) -> Result<Tag<'_>, String> {
+    if let Some(name) = content.strip_prefix('/') {
+        return Ok(Tag::End {
+            name: local_name(name.trim()),
+        });
+    }
+
+    let (content, self_closing) = match content.strip_suffix('/') {
+        Some(content) => (content, true),
+        None => (content, false),
+    };
+    let name_end = content
+        .find(|c: char| c.is_whitespace())
+        .unwrap_or(content.len());
+    let name = &content[..name_end];
+    if name.is_empty() {
+        return Err("missing element name".to_string());
+    }
+
+    let mut attributes = Vec::new();
+    let mut rest = content[name_end..].trim_start();
+    while !rest.is_empty() {
+        let Some(equals) = rest.find('=') else {
+            return Err(format!("malformed attributes in element {}", name));
+        };
+        let attribute = rest[..equals].trim();
+        let value = rest[equals + 1..].trim_start();
+        let quote = match value.chars().next() {
+            Some(q @ ('"' | '\'')) => q,
+            _ => return Err(format!("unquoted value of attribute {}", attribute)),
+        };
+        let Some(value_end) = value[1..].find(quote) else {
+            return Err(format!("unterminated value of attribute {}", attribute));
+        };
+        attributes.push((attribute, unescape(&value[1..value_end + 1])));
+        rest = value[value_end + 2..].trim_start();
+    }
+
+    Ok(Tag::Start {
+        name: local_name(name),
+        attributes,
+        self_closing,
+    })
+}
+
+/// Replaces the predefined entities and character references with the characters.
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let character = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(|decimal| decimal.parse()))
+                .and_then(|code| code.ok())
+                .and_then(char::from_u32),
+        };
+        match character {
+            Some(character) => {
+                unescaped.push(character);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::xml::{attribute, Tag, TagReader};
+
+    #[test]
+    fn test_read_tags() {
+        let xml = r#"<?xml version="1.0"?>
+            <!DOCTYPE score [ <!ENTITY x "y"> ]>
+            <!-- A comment with <tags> -->
+            <mei:score xmlns:mei="http://www.music-encoding.org/ns/mei">
+              <note xml:id='n1' label="a &amp; b &#x3e;"/>text
+            </mei:score>"#;
+        let mut reader = TagReader::new(xml);
+
+        assert!(matches!(
+            reader.next_tag(),
+            Ok(Some(Tag::Start {
+                name: "score",
+                self_closing: false,
+                ..
+            }))
+        ));
+        match reader.next_tag().unwrap().unwrap() {
+            Tag::Start {
+                name,
+                attributes,
+                self_closing,
+            } => {
+                assert_eq!("note", name);
+                assert!(self_closing);
+                assert_eq!(Some("n1"), attribute(&attributes, "xml:id"));
+                assert_eq!(Some("a & b >"), attribute(&attributes, "label"));
+            }
+            tag => panic!("unexpected tag {:?}", tag),
+        }
+        assert_eq!(Ok(Some(Tag::End { name: "score" })), reader.next_tag());
+        assert_eq!(Ok(None), reader.next_tag());
+
+        assert!(TagReader::new("<note pname=c/>").next_tag().is_err());
+    }
+}
@@ -4,7 +4,32 @@
  */
 extern crate core;
 
+#[cfg(feature = "discovery")]
 pub mod discovery;
+pub mod error;
+pub mod evaluation;
+#[cfg(feature = "io")]
 pub mod io;
 pub mod point_set;
+#[cfg(feature = "search")]
 pub mod search;
+pub mod structure;
+pub mod synthetic;
+
+#[cfg(any(feature = "discovery", feature = "search"))]
+pub(crate) mod utilities;
+
+// The types and traits that most users need are re-exported at the crate root, so that
+// their paths stay stable even if the modules that implement them are reorganized.
+#[cfg(feature = "discovery")]
+pub use discovery::algorithm::{MtpAlgorithm, TecAlgorithm};
+#[cfg(feature = "discovery")]
+pub use discovery::registry::{create_algorithm, DiscoveryAlgorithm};
+pub use error::PoSemirError;
+pub use point_set::mtp::Mtp;
+pub use point_set::pattern::Pattern;
+pub use point_set::point::{Point, Point2DRf64, Point2Df64, Point2Di64};
+pub use point_set::set::PointSet;
+pub use point_set::tec::Tec;
+#[cfg(feature = "search")]
+pub use search::pattern_matcher::PatternMatcher;
@@ -0,0 +1,218 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::fmt::{Display, Formatter};
+
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+use crate::point_set::tec::Tec;
+
+const CHROMATIC_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+const MORPHETIC_NAMES: [&str; 7] = ["A", "B", "C", "D", "E", "F", "G"];
+
+/// Defines how the pitch component of points is rendered.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PitchSpelling {
+    /// Pitches are MIDI note numbers (chromatic pitch), e.g., 60 is rendered as C4.
+    Chromatic,
+    /// Pitches are morphetic pitch numbers [Meredith 2006], where A0 is 0, e.g., 23 is rendered as C4.
+    Morphetic,
+    /// Pitches are rendered as plain numbers.
+    Numeric,
+}
+
+/// Renders points, patterns, and TECs in a human-readable form, where onsets (first component)
+/// are shown as bar:beat positions and pitches (second component) as note names.
+/// Onsets are expected to be expressed in beats, starting from zero at the beginning of the first bar.
+#[derive(Debug, Copy, Clone)]
+pub struct PatternFormatter {
+    /// The number of beats in a bar.
+    pub beats_per_bar: f64,
+    /// How the pitch component is rendered.
+    pub pitch_spelling: PitchSpelling,
+}
+
+impl Default for PatternFormatter {
+    /// Returns a formatter for 4 beats per bar and MIDI note numbers.
+    fn default() -> Self {
+        PatternFormatter {
+            beats_per_bar: 4.0,
+            pitch_spelling: PitchSpelling::Chromatic,
+        }
+    }
+}
+
+impl PatternFormatter {
+    /// Returns the onset rendered as a bar:beat position, where both bars and beats are numbered from 1.
+    ///
+    /// # Arguments
+    ///
+    /// * `onset` - The onset time in beats
+    pub fn format_onset(&self, onset: f64) -> String {
+        let bar = (onset / self.beats_per_bar).floor();
+        let beat = onset - bar * self.beats_per_bar + 1.0;
+        format!("{}:{}", bar as i64 + 1, format_number(beat))
+    }
+
+    /// Returns the pitch rendered as a note name, or as a number if the pitch
+    /// is not an integer.
+    ///
+    /// # Arguments
+    ///
+    /// * `pitch` - The pitch number
+    pub fn format_pitch(&self, pitch: f64) -> String {
+        if pitch.fract() != 0.0 {
+            return format_number(pitch);
+        }
+
+        let pitch = pitch as i64;
+        match self.pitch_spelling {
+            PitchSpelling::Chromatic => {
+                let name = CHROMATIC_NAMES[pitch.rem_euclid(12) as usize];
+                format!("{}{}", name, pitch.div_euclid(12) - 1)
+            }
+            PitchSpelling::Morphetic => {
+                let letter = pitch.rem_euclid(7);
+                // Octave numbers change at C, which is the third letter from A.
+                let octave = pitch.div_euclid(7) + if letter >= 2 { 1 } else { 0 };
+                format!("{}{}", MORPHETIC_NAMES[letter as usize], octave)
+            }
+            PitchSpelling::Numeric => pitch.to_string(),
+        }
+    }
+
+    /// Returns the point rendered as a bar:beat position followed by the pitch.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to render
+    pub fn format_point<T: Point>(&self, point: &T) -> String {
+        let onset = self.format_onset(point.component_f64(0).unwrap());
+        match point.component_f64(1) {
+            Some(pitch) => format!("{} {}", onset, self.format_pitch(pitch)),
+            None => onset,
+        }
+    }
+
+    /// Returns the pattern rendered as a list of points.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The pattern to render
+    pub fn format_pattern<T: Point>(&self, pattern: &Pattern<T>) -> String {
+        let points: Vec<String> = pattern.into_iter().map(|p| self.format_point(p)).collect();
+        format!("[{}]", points.join(", "))
+    }
+
+    /// Returns the TEC rendered as its pattern followed by the first points of all
+    /// of its occurrences.
+    ///
+    /// # Arguments
+    ///
+    /// * `tec` - The TEC to render
+    pub fn format_tec<T: Point>(&self, tec: &Tec<T>) -> String {
+        let pattern = self.format_pattern(&tec.pattern);
+        if tec.pattern.is_empty() {
+            return pattern;
+        }
+
+        let first = tec.pattern[0];
+        let mut occurrences = vec![self.format_point(&first)];
+        for translator in &tec.translators {
+            occurrences.push(self.format_point(&(first + *translator)));
+        }
+
+        format!(
+            "{} occurring {} times at: {}",
+            pattern,
+            occurrences.len(),
+            occurrences.join(", ")
+        )
+    }
+}
+
+/// Formats the number without trailing zeros, using at most three decimals.
+fn format_number(number: f64) -> String {
+    let formatted = format!("{:.3}", number);
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+impl<T: Point> Display for Pattern<T> {
+    /// Renders the pattern with the default `PatternFormatter`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", PatternFormatter::default().format_pattern(self))
+    }
+}
+
+impl<T: Point> Display for Tec<T> {
+    /// Renders the TEC with the default `PatternFormatter`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", PatternFormatter::default().format_tec(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::point_set::display::{PatternFormatter, PitchSpelling};
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::tec::Tec;
+
+    #[test]
+    fn test_format_points() {
+        let formatter = PatternFormatter::default();
+        assert_eq!(
+            "1:1 C4",
+            formatter.format_point(&Point2Df64 { x: 0.0, y: 60.0 })
+        );
+        assert_eq!(
+            "2:2.5 A#3",
+            formatter.format_point(&Point2Df64 { x: 5.5, y: 58.0 })
+        );
+        assert_eq!(
+            "1:1 60.5",
+            formatter.format_point(&Point2Df64 { x: 0.0, y: 60.5 })
+        );
+
+        let morphetic = PatternFormatter {
+            beats_per_bar: 3.0,
+            pitch_spelling: PitchSpelling::Morphetic,
+        };
+        assert_eq!(
+            "2:1 C4",
+            morphetic.format_point(&Point2Df64 { x: 3.0, y: 23.0 })
+        );
+        assert_eq!(
+            "1:3 B3",
+            morphetic.format_point(&Point2Df64 { x: 2.0, y: 22.0 })
+        );
+        assert_eq!(
+            "1:1 A0",
+            morphetic.format_point(&Point2Df64 { x: 0.0, y: 0.0 })
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let pattern = Pattern::new(&vec![
+            &Point2Df64 { x: 0.0, y: 60.0 },
+            &Point2Df64 { x: 1.0, y: 62.0 },
+        ]);
+        assert_eq!("[1:1 C4, 1:2 D4]", pattern.to_string());
+
+        let tec = Tec {
+            pattern,
+            translators: vec![Point2Df64 { x: 4.0, y: 7.0 }],
+        };
+        assert_eq!(
+            "[1:1 C4, 1:2 D4] occurring 2 times at: 1:1 C4, 2:1 G4",
+            tec.to_string()
+        );
+    }
+}
@@ -0,0 +1,250 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use crate::point_set::point::Point;
+
+/// An axis-aligned rectangle in the plane spanned by the first two components
+/// (time and pitch) of points. The bounds are inclusive. The second component of points
+/// that have only one component is taken to be zero.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoundingBox {
+    pub lower_x: f64,
+    pub lower_y: f64,
+    pub upper_x: f64,
+    pub upper_y: f64,
+}
+
+impl BoundingBox {
+    /// Returns the smallest bounding box that contains all of the given points. For no points,
+    /// an empty bounding box whose lower bounds are greater than its upper bounds is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `points` - The points that must be contained in the bounding box
+    pub fn of<'a, T: Point + 'a>(points: impl IntoIterator<Item = &'a T>) -> BoundingBox {
+        let mut bb = BoundingBox {
+            lower_x: f64::MAX,
+            lower_y: f64::MAX,
+            upper_x: f64::MIN,
+            upper_y: f64::MIN,
+        };
+
+        for point in points {
+            let (x, y) = xy(point);
+            bb.lower_x = bb.lower_x.min(x);
+            bb.upper_x = bb.upper_x.max(x);
+            bb.lower_y = bb.lower_y.min(y);
+            bb.upper_y = bb.upper_y.max(y);
+        }
+
+        bb
+    }
+
+    /// Returns true if the bounding box contains no points.
+    pub fn is_empty(&self) -> bool {
+        self.lower_x > self.upper_x || self.lower_y > self.upper_y
+    }
+
+    /// Returns the extent of the bounding box along the first component (time).
+    pub fn width(&self) -> f64 {
+        if self.is_empty() {
+            0.0
+        } else {
+            self.upper_x - self.lower_x
+        }
+    }
+
+    /// Returns the extent of the bounding box along the second component (pitch).
+    pub fn height(&self) -> f64 {
+        if self.is_empty() {
+            0.0
+        } else {
+            self.upper_y - self.lower_y
+        }
+    }
+
+    /// Returns the area of the bounding box.
+    pub fn area(&self) -> f64 {
+        self.width() * self.height()
+    }
+
+    /// Returns true if the point is inside or on the boundary of the bounding box.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to test
+    pub fn contains<T: Point>(&self, point: &T) -> bool {
+        let (x, y) = xy(point);
+        self.lower_x <= x && x <= self.upper_x && self.lower_y <= y && y <= self.upper_y
+    }
+}
+
+/// The convex hull of points in the plane spanned by the first two components
+/// (time and pitch) of the points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvexHull {
+    vertices: Vec<(f64, f64)>,
+}
+
+impl ConvexHull {
+    /// Returns the convex hull of the given points computed with the monotone chain algorithm.
+    /// The points are ordered with `f64::total_cmp`, so points with NaN components do not cause
+    /// a panic, but the hull of such points is not meaningful (see `PointSet::validate`).
+    ///
+    /// # Arguments
+    ///
+    /// * `points` - The points whose convex hull is computed
+    pub fn of<'a, T: Point + 'a>(points: impl IntoIterator<Item = &'a T>) -> ConvexHull {
+        let mut points: Vec<(f64, f64)> = points.into_iter().map(xy).collect();
+        points.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        points.dedup();
+        if points.len() < 3 {
+            return ConvexHull { vertices: points };
+        }
+
+        let mut vertices: Vec<(f64, f64)> = Vec::with_capacity(2 * points.len());
+        for chain in [points.clone(), points.into_iter().rev().collect()] {
+            let start = vertices.len();
+            for point in chain {
+                while vertices.len() >= start + 2
+                    && cross(
+                        vertices[vertices.len() - 2],
+                        vertices[vertices.len() - 1],
+                        point,
+                    ) <= 0.0
+                {
+                    vertices.pop();
+                }
+                vertices.push(point);
+            }
+            // The last point of each chain is the first point of the other chain.
+            vertices.pop();
+        }
+
+        ConvexHull { vertices }
+    }
+
+    /// Returns the vertices of the hull in counterclockwise order starting from the
+    /// lexicographically smallest one. Collinear points are not included as vertices,
+    /// so the hull of points on a line consists of the endpoints of the line segment.
+    pub fn vertices(&self) -> &[(f64, f64)] {
+        &self.vertices
+    }
+
+    /// Returns the area of the hull computed with the shoelace formula.
+    pub fn area(&self) -> f64 {
+        let n = self.vertices.len();
+        let twice_area: f64 = (0..n)
+            .map(|i| {
+                let (x_a, y_a) = self.vertices[i];
+                let (x_b, y_b) = self.vertices[(i + 1) % n];
+                x_a * y_b - x_b * y_a
+            })
+            .sum();
+        twice_area.abs() / 2.0
+    }
+
+    /// Returns true if the point is inside or on the boundary of the hull.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to test
+    pub fn contains<T: Point>(&self, point: &T) -> bool {
+        let point = xy(point);
+        let hull = &self.vertices;
+        match hull.len() {
+            0 => false,
+            1 => hull[0] == point,
+            2 => {
+                let (a, b) = (hull[0], hull[1]);
+                cross(a, b, point) == 0.0
+                    && a.0.min(b.0) <= point.0
+                    && point.0 <= a.0.max(b.0)
+                    && a.1.min(b.1) <= point.1
+                    && point.1 <= a.1.max(b.1)
+            }
+            n => (0..n).all(|i| cross(hull[i], hull[(i + 1) % n], point) >= 0.0),
+        }
+    }
+}
+
+/// Returns the first two components of the point, where a missing component is zero.
+fn xy<T: Point>(point: &T) -> (f64, f64) {
+    (
+        point.component_f64(0).unwrap_or(0.0),
+        point.component_f64(1).unwrap_or(0.0),
+    )
+}
+
+/// Returns the z-component of the cross product of the vectors from o to a and from o to b.
+/// The value is positive if the turn from a to b is counterclockwise.
+fn cross(o: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::point_set::geometry::{BoundingBox, ConvexHull};
+    use crate::point_set::point::Point2Df64;
+
+    #[test]
+    fn test_bounding_box() {
+        let points = vec![
+            Point2Df64 { x: 1.0, y: 60.0 },
+            Point2Df64 { x: 0.0, y: 64.0 },
+            Point2Df64 { x: 3.0, y: 62.0 },
+        ];
+        let bb = BoundingBox::of(&points);
+        assert_eq!(0.0, bb.lower_x);
+        assert_eq!(3.0, bb.upper_x);
+        assert_eq!(60.0, bb.lower_y);
+        assert_eq!(64.0, bb.upper_y);
+        assert_eq!(12.0, bb.area());
+        assert!(bb.contains(&Point2Df64 { x: 3.0, y: 64.0 }));
+        assert!(!bb.contains(&Point2Df64 { x: 3.0, y: 65.0 }));
+
+        let empty = BoundingBox::of(&Vec::<Point2Df64>::new());
+        assert!(empty.is_empty());
+        assert_eq!(0.0, empty.area());
+        assert!(!empty.contains(&Point2Df64 { x: 0.0, y: 0.0 }));
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        let points = vec![
+            Point2Df64 { x: 0.0, y: 0.0 },
+            Point2Df64 { x: 4.0, y: 0.0 },
+            Point2Df64 { x: 2.0, y: 1.0 },
+            Point2Df64 { x: 2.0, y: 0.0 },
+            Point2Df64 { x: 4.0, y: 4.0 },
+            Point2Df64 { x: 0.0, y: 4.0 },
+        ];
+        let hull = ConvexHull::of(&points);
+        assert_eq!(
+            &[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)],
+            hull.vertices()
+        );
+        assert_eq!(16.0, hull.area());
+        assert!(hull.contains(&Point2Df64 { x: 2.0, y: 4.0 }));
+        assert!(!hull.contains(&Point2Df64 { x: 2.0, y: 4.5 }));
+
+        let triangle = ConvexHull::of(&points[..4]);
+        assert_eq!(2.0, triangle.area());
+
+        let line = ConvexHull::of(&[points[0], points[3], points[1]]);
+        assert_eq!(&[(0.0, 0.0), (4.0, 0.0)], line.vertices());
+        assert_eq!(0.0, line.area());
+        assert!(line.contains(&points[3]));
+        assert!(!line.contains(&points[2]));
+
+        let with_nan = ConvexHull::of(&[
+            points[0],
+            Point2Df64 {
+                x: f64::NAN,
+                y: 1.0,
+            },
+        ]);
+        assert_eq!(2, with_nan.vertices().len());
+    }
+}
@@ -0,0 +1,291 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+
+/// A k-d tree over the points of a point set for rectangular range queries and
+/// nearest-neighbor lookups. The queries return the indices of the points in the point set.
+///
+/// The tree is stored implicitly in a single vector of point indices: the median (by the
+/// splitting component) of each subslice is the root of the subtree, and the points before
+/// and after it form the left and right subtrees. The splitting component cycles through
+/// the components of the points with the depth of the tree.
+pub struct KdTree<'a, T: Point> {
+    point_set: &'a PointSet<T>,
+    nodes: Vec<usize>,
+    /// The components of the points by point index, `dimensionality` values per point.
+    components: Vec<f64>,
+    dimensionality: usize,
+}
+
+impl<'a, T: Point> KdTree<'a, T> {
+    /// Builds the k-d tree of the given point set in O(n log n) time.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set that is indexed. All components of the points
+    ///   must be representable as f64.
+    pub fn new(point_set: &'a PointSet<T>) -> KdTree<'a, T> {
+        let dimensionality = if point_set.is_empty() {
+            0
+        } else {
+            point_set[0].dimensionality()
+        };
+
+        let components = KdTree::components_of_points(point_set, dimensionality);
+        let mut tree = KdTree {
+            point_set,
+            nodes: (0..point_set.len()).collect(),
+            components,
+            dimensionality,
+        };
+
+        let mut nodes = std::mem::take(&mut tree.nodes);
+        tree.build(&mut nodes, 0);
+        tree.nodes = nodes;
+        tree
+    }
+
+    /// Returns the point set indexed by this tree.
+    pub fn point_set(&self) -> &PointSet<T> {
+        self.point_set
+    }
+
+    /// Executes on_output on the index of each point whose components are all between the
+    /// components of lower and upper (inclusive). The indices are not in any specific order.
+    ///
+    /// # Arguments
+    ///
+    /// * `lower` - The lower corner of the range
+    /// * `upper` - The upper corner of the range
+    /// * `on_output` - The function executed for the index of each point in the range
+    pub fn range_with_callback(&self, lower: &T, upper: &T, mut on_output: impl FnMut(usize)) {
+        let lower = KdTree::components_of(lower, self.dimensionality);
+        let upper = KdTree::components_of(upper, self.dimensionality);
+        self.range_of_components(&lower, &upper, &mut on_output);
+    }
+
+    /// Executes on_output on the index of each point in the range given by the components
+    /// of its corners, so that the range need not be representable as points of type T.
+    pub(crate) fn range_of_components(
+        &self,
+        lower: &[f64],
+        upper: &[f64],
+        mut on_output: impl FnMut(usize),
+    ) {
+        self.range_recursive(&self.nodes, 0, lower, upper, &mut on_output);
+    }
+
+    /// Returns the components of the point at the given index of the point set as f64.
+    #[cfg(feature = "search")]
+    pub(crate) fn components(&self, index: usize) -> &[f64] {
+        let start = index * self.dimensionality;
+        &self.components[start..start + self.dimensionality]
+    }
+
+    /// Returns the indices of the points whose components are all between the components
+    /// of lower and upper (inclusive) in ascending order.
+    ///
+    /// # Arguments
+    ///
+    /// * `lower` - The lower corner of the range
+    /// * `upper` - The upper corner of the range
+    pub fn range(&self, lower: &T, upper: &T) -> Vec<usize> {
+        let mut indices = Vec::new();
+        self.range_with_callback(lower, upper, |index| indices.push(index));
+        indices.sort_unstable();
+        indices
+    }
+
+    /// Returns the index of the point that is nearest to the given point by the Euclidean
+    /// distance, or None if the point set is empty. Of equally near points, the one with
+    /// the smallest index is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point whose nearest neighbor is searched for
+    pub fn nearest(&self, point: &T) -> Option<usize> {
+        let target = KdTree::components_of(point, self.dimensionality);
+        let mut best: Option<(f64, usize)> = None;
+        self.nearest_recursive(&self.nodes, 0, &target, &mut best);
+        best.map(|(_, index)| index)
+    }
+
+    fn build(&self, nodes: &mut [usize], depth: usize) {
+        if nodes.len() <= 1 {
+            return;
+        }
+
+        let axis = depth % self.dimensionality;
+        let mid = nodes.len() / 2;
+        nodes.select_nth_unstable_by(mid, |a, b| {
+            self.component(*a, axis)
+                .total_cmp(&self.component(*b, axis))
+        });
+
+        let (left, right) = nodes.split_at_mut(mid);
+        self.build(left, depth + 1);
+        self.build(&mut right[1..], depth + 1);
+    }
+
+    fn range_recursive(
+        &self,
+        nodes: &[usize],
+        depth: usize,
+        lower: &[f64],
+        upper: &[f64],
+        on_output: &mut impl FnMut(usize),
+    ) {
+        if nodes.is_empty() {
+            return;
+        }
+
+        let axis = depth % self.dimensionality;
+        let mid = nodes.len() / 2;
+        let index = nodes[mid];
+        let value = self.component(index, axis);
+
+        let in_range = (0..self.dimensionality).all(|i| {
+            let component = self.component(index, i);
+            lower[i] <= component && component <= upper[i]
+        });
+        if in_range {
+            on_output(index);
+        }
+
+        // The points in the left subtree are at most and the points in the right subtree
+        // are at least the value of the splitting component.
+        if lower[axis] <= value {
+            self.range_recursive(&nodes[..mid], depth + 1, lower, upper, on_output);
+        }
+        if value <= upper[axis] {
+            self.range_recursive(&nodes[mid + 1..], depth + 1, lower, upper, on_output);
+        }
+    }
+
+    fn nearest_recursive(
+        &self,
+        nodes: &[usize],
+        depth: usize,
+        target: &[f64],
+        best: &mut Option<(f64, usize)>,
+    ) {
+        if nodes.is_empty() {
+            return;
+        }
+
+        let axis = depth % self.dimensionality;
+        let mid = nodes.len() / 2;
+        let index = nodes[mid];
+
+        let distance: f64 = (0..self.dimensionality)
+            .map(|i| (self.component(index, i) - target[i]).powi(2))
+            .sum();
+        let is_better = match best {
+            Some((best_distance, best_index)) => {
+                distance < *best_distance || (distance == *best_distance && index < *best_index)
+            }
+            None => true,
+        };
+        if is_better {
+            *best = Some((distance, index));
+        }
+
+        let diff = target[axis] - self.component(index, axis);
+        let (near, far) = if diff < 0.0 {
+            (&nodes[..mid], &nodes[mid + 1..])
+        } else {
+            (&nodes[mid + 1..], &nodes[..mid])
+        };
+
+        self.nearest_recursive(near, depth + 1, target, best);
+        if let Some((best_distance, _)) = best {
+            // Equally distant points on the far side may have smaller indices.
+            if diff * diff <= *best_distance {
+                self.nearest_recursive(far, depth + 1, target, best);
+            }
+        }
+    }
+
+    fn component(&self, index: usize, axis: usize) -> f64 {
+        self.components[index * self.dimensionality + axis]
+    }
+
+    fn components_of(point: &T, dimensionality: usize) -> Vec<f64> {
+        (0..dimensionality)
+            .map(|i| point.component_f64(i).unwrap())
+            .collect()
+    }
+
+    fn components_of_points(point_set: &PointSet<T>, dimensionality: usize) -> Vec<f64> {
+        point_set
+            .into_iter()
+            .flat_map(|point| KdTree::components_of(point, dimensionality))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::point_set::index::KdTree;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+
+    fn test_point_set() -> PointSet<Point2Df64> {
+        let mut points = Vec::new();
+        for i in 0..50 {
+            points.push(Point2Df64 {
+                x: ((i * 7) % 13) as f64,
+                y: ((i * 11) % 17) as f64,
+            });
+        }
+        PointSet::new(points)
+    }
+
+    #[test]
+    fn test_range() {
+        let point_set = test_point_set();
+        let tree = KdTree::new(&point_set);
+
+        let lower = Point2Df64 { x: 2.0, y: 3.5 };
+        let upper = Point2Df64 { x: 7.0, y: 9.0 };
+        let expected: Vec<usize> = (0..point_set.len())
+            .filter(|i| {
+                let p = point_set[*i];
+                lower.x <= p.x && p.x <= upper.x && lower.y <= p.y && p.y <= upper.y
+            })
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(expected, tree.range(&lower, &upper));
+        assert!(tree.range(&upper, &lower).is_empty());
+    }
+
+    #[test]
+    fn test_nearest() {
+        let point_set = test_point_set();
+        let tree = KdTree::new(&point_set);
+
+        for target in [
+            Point2Df64 { x: 4.2, y: 8.9 },
+            Point2Df64 { x: -3.0, y: 20.0 },
+            Point2Df64 { x: 6.5, y: 6.5 },
+        ] {
+            let distance = |i: &usize| {
+                let diff = point_set[*i] - target;
+                diff.x * diff.x + diff.y * diff.y
+            };
+            let expected = (0..point_set.len())
+                .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+                .unwrap();
+            assert_eq!(Some(expected), tree.nearest(&target));
+        }
+
+        let empty = PointSet::<Point2Df64>::new(Vec::new());
+        assert_eq!(
+            None,
+            KdTree::new(&empty).nearest(&Point2Df64 { x: 0.0, y: 0.0 })
+        );
+    }
+}
@@ -2,8 +2,22 @@
  * (c) Otso Björklund (2021)
  * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
  */
+pub mod display;
+pub mod geometry;
+pub mod index;
 pub mod mtp;
+pub mod note_point_set;
 pub mod pattern;
+pub mod pattern_ref;
 pub mod point;
+pub mod point_duration;
+pub mod point_nd;
+pub mod point_pitch;
+pub mod point_rational;
+pub mod quantization;
+#[cfg(feature = "serde")]
+mod serialization;
 pub mod set;
 pub mod tec;
+pub mod tec_cover;
+pub mod tec_with_scaling;
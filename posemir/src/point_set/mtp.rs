@@ -4,17 +4,40 @@
  */
 use crate::point_set::pattern::Pattern;
 use crate::point_set::point::Point;
+use crate::point_set::tec::Tec;
 
 /// Represents a Maximal Translatable Pattern (MTP) [Meredith et al. 2002].
 /// An MTP is the set of all points in a point set D that can be
 /// translated by a vector so that the translated points are also
 /// within the point set D.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mtp<T: Point> {
     pub translator: T,
     pub pattern: Pattern<T>,
 }
 
+impl<T: Point> Mtp<T> {
+    /// Returns a TEC consisting of the pattern of this MTP and the translator of this MTP
+    /// as its only translator.
+    pub fn to_tec(&self) -> Tec<T> {
+        Tec {
+            pattern: self.pattern.clone(),
+            translators: vec![self.translator],
+        }
+    }
+}
+
+/// Represents an MTP together with the indices of the points of its pattern in the
+/// point set in which the MTP was computed. The indices are in the same order as the
+/// points of the pattern.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IndexedMtp<T: Point> {
+    pub mtp: Mtp<T>,
+    pub indices: Vec<usize>,
+}
+
 impl<T: Point> PartialEq for Mtp<T> {
     fn eq(&self, other: &Self) -> bool {
         self.translator == other.translator && self.pattern == other.pattern
@@ -22,3 +45,30 @@ impl<T: Point> PartialEq for Mtp<T> {
 }
 
 impl<T: Point> Eq for Mtp<T> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::point_set::mtp::Mtp;
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::tec::Tec;
+
+    #[test]
+    fn test_to_tec() {
+        let a = Point2Df64 { x: 1.0, y: 0.0 };
+        let b = Point2Df64 { x: 2.0, y: 0.0 };
+        let translator = Point2Df64 { x: 1.0, y: 1.0 };
+        let mtp = Mtp {
+            translator,
+            pattern: Pattern::new(&vec![&a, &b]),
+        };
+
+        assert_eq!(
+            Tec {
+                pattern: Pattern::new(&vec![&a, &b]),
+                translators: vec![translator],
+            },
+            mtp.to_tec()
+        );
+    }
+}
@@ -0,0 +1,205 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// The metadata of a note from which a point is created.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoteMetadata {
+    /// The voice (or staff, or track) of the note.
+    pub voice: Option<usize>,
+    /// The number of the measure of the note.
+    pub measure: Option<usize>,
+    /// The identifier of the note in the source, e.g., an xml:id.
+    pub id: Option<String>,
+}
+
+/// A point set that keeps the metadata of the notes from which the points were created,
+/// so that the patterns found in the point set can be mapped back to the notes.
+///
+/// The points are sorted and deduplicated as in `PointSet::new`, and the metadata of the
+/// notes that map to the same point (e.g., unisons in different voices) are kept together,
+/// so the notes of the point at index i of `point_set()` are given by `notes(i)`. The indices
+/// produced by the algorithms, e.g., `IndexedMtp::indices` or the indices returned by
+/// `PatternMatcher::find_indices`, can be mapped to the notes with `notes_at`.
+#[derive(Debug, Clone)]
+pub struct NotePointSet<T: Point> {
+    point_set: PointSet<T>,
+    notes: Vec<Vec<NoteMetadata>>,
+}
+
+impl<T: Point> NotePointSet<T> {
+    /// Returns the point set on which the algorithms are run.
+    pub fn point_set(&self) -> &PointSet<T> {
+        &self.point_set
+    }
+
+    /// Returns the notes of the point at the given index of the point set
+    /// in the order in which they were added.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the point in the point set
+    pub fn notes(&self, index: usize) -> &[NoteMetadata] {
+        &self.notes[index]
+    }
+
+    /// Returns the notes of the points at the given indices of the point set.
+    ///
+    /// # Arguments
+    ///
+    /// * `indices` - The indices of the points in the point set
+    pub fn notes_at(&self, indices: &[usize]) -> Vec<&NoteMetadata> {
+        indices.iter().flat_map(|i| &self.notes[*i]).collect()
+    }
+
+    /// Returns the notes of the points of the pattern, or None if the pattern
+    /// is not in the point set.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The pattern whose notes are returned
+    pub fn pattern_notes(&self, pattern: &Pattern<T>) -> Option<Vec<&NoteMetadata>> {
+        let indices = self.point_set.find_indices(pattern)?;
+        Some(self.notes_at(&indices))
+    }
+
+    /// Returns the notes of each occurrence of the TEC (see `Tec::expand`), or None
+    /// if some occurrence is not in the point set.
+    ///
+    /// # Arguments
+    ///
+    /// * `tec` - The TEC whose occurrences are mapped to notes
+    pub fn occurrence_notes(&self, tec: &Tec<T>) -> Option<Vec<Vec<&NoteMetadata>>> {
+        tec.occurrences_iter()
+            .map(|occurrence| self.pattern_notes(&occurrence))
+            .collect()
+    }
+}
+
+/// Builder for note point sets. The notes are added as points with their metadata
+/// in any order, e.g., while reading a score.
+#[derive(Debug, Clone)]
+pub struct NotePointSetBuilder<T: Point> {
+    notes: Vec<(T, NoteMetadata)>,
+}
+
+impl<T: Point> Default for NotePointSetBuilder<T> {
+    fn default() -> Self {
+        NotePointSetBuilder { notes: Vec::new() }
+    }
+}
+
+impl<T: Point> NotePointSetBuilder<T> {
+    /// Returns a builder with no notes.
+    pub fn new() -> NotePointSetBuilder<T> {
+        NotePointSetBuilder::default()
+    }
+
+    /// Adds a note as the given point with the given metadata.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point that represents the note
+    /// * `metadata` - The metadata of the note
+    pub fn note(mut self, point: T, metadata: NoteMetadata) -> NotePointSetBuilder<T> {
+        self.notes.push((point, metadata));
+        self
+    }
+
+    /// Returns the note point set of the added notes.
+    pub fn build(mut self) -> NotePointSet<T> {
+        // The sort is stable, so the notes of a point stay in the order in which they were added.
+        self.notes.sort_by_key(|note| note.0);
+
+        let mut points: Vec<T> = Vec::with_capacity(self.notes.len());
+        let mut notes: Vec<Vec<NoteMetadata>> = Vec::with_capacity(self.notes.len());
+        for (point, metadata) in self.notes {
+            match (points.last(), notes.last_mut()) {
+                (Some(last), Some(last_notes)) if *last == point => last_notes.push(metadata),
+                _ => {
+                    points.push(point);
+                    notes.push(vec![metadata]);
+                }
+            }
+        }
+
+        NotePointSet {
+            point_set: PointSet::from_sorted(points),
+            notes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "discovery")]
+    use crate::discovery::algorithm::{MtpAlgorithm, TecAlgorithm};
+    #[cfg(feature = "discovery")]
+    use crate::discovery::sia::Sia;
+    #[cfg(feature = "discovery")]
+    use crate::discovery::siatec::Siatec;
+    use crate::point_set::note_point_set::{NoteMetadata, NotePointSetBuilder};
+    use crate::point_set::point::Point2Df64;
+
+    fn note(voice: usize, id: &str) -> NoteMetadata {
+        NoteMetadata {
+            voice: Some(voice),
+            measure: Some(1),
+            id: Some(id.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_unisons_are_kept() {
+        let note_point_set = NotePointSetBuilder::new()
+            .note(Point2Df64 { x: 1.0, y: 62.0 }, note(1, "n2"))
+            .note(Point2Df64 { x: 0.0, y: 60.0 }, note(1, "n1"))
+            .note(Point2Df64 { x: 0.0, y: 60.0 }, note(2, "n3"))
+            .build();
+
+        assert_eq!(2, note_point_set.point_set().len());
+        assert_eq!(&[note(1, "n1"), note(2, "n3")], note_point_set.notes(0));
+        assert_eq!(&[note(1, "n2")], note_point_set.notes(1));
+    }
+
+    #[cfg(feature = "discovery")]
+    #[test]
+    fn test_map_patterns_to_notes() {
+        let mut builder = NotePointSetBuilder::new();
+        for (i, (x, y)) in [(0.0, 60.0), (1.0, 62.0), (4.0, 60.0), (5.0, 62.0)]
+            .iter()
+            .enumerate()
+        {
+            builder = builder.note(Point2Df64 { x: *x, y: *y }, note(1, &format!("n{}", i)));
+        }
+        let note_point_set = builder.build();
+        let point_set = note_point_set.point_set();
+
+        let tec = Siatec {}
+            .compute_tecs(point_set)
+            .into_iter()
+            .find(|tec| tec.pattern.len() == 2)
+            .unwrap();
+        let ids: Vec<Vec<&str>> = note_point_set
+            .occurrence_notes(&tec)
+            .unwrap()
+            .iter()
+            .map(|notes| notes.iter().map(|n| n.id.as_deref().unwrap()).collect())
+            .collect();
+        assert_eq!(vec![vec!["n0", "n1"], vec!["n2", "n3"]], ids);
+
+        let indexed_mtps = Sia::new().compute_indexed_mtps(point_set);
+        for indexed in indexed_mtps {
+            assert_eq!(
+                note_point_set.pattern_notes(&indexed.mtp.pattern).unwrap(),
+                note_point_set.notes_at(&indexed.indices)
+            );
+        }
+    }
+}
@@ -4,17 +4,24 @@
  */
 use std::borrow::Borrow;
 use std::cmp::{min, Ordering};
+use std::hash::{Hash, Hasher};
 use std::ops::Index;
 use std::slice;
+use std::sync::Arc;
 
+use crate::point_set::geometry::{BoundingBox, ConvexHull};
 use crate::point_set::point::Point;
 use crate::point_set::set::PointSet;
 
 /// Represents a pattern in a point set.
 /// A lexicographical ordering is defined for patterns, so they can easily be sorted lexicographically.
+///
+/// The points of a pattern are immutable and shared between its clones, so cloning a pattern
+/// is cheap, as is creating a pattern from a point set (see `From<PointSet<T>>`).
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pattern<T: Point> {
-    points: Vec<T>,
+    points: Arc<Vec<T>>,
 }
 
 impl<T: Point> Pattern<T> {
@@ -25,14 +32,10 @@ impl<T: Point> Pattern<T> {
     /// * `points` - A borrowed vector of points. The returned pattern does not take ownership of these.
     ///
     pub fn new(points: &Vec<&T>) -> Pattern<T> {
-        let mut points_copy: Vec<T> = Vec::new();
-
-        for point in points {
-            points_copy.push(*(*point));
-        }
+        let points_copy: Vec<T> = points.iter().map(|point| **point).collect();
 
         Pattern {
-            points: points_copy,
+            points: points_copy.into(),
         }
     }
 
@@ -55,12 +58,52 @@ impl<T: Point> Pattern<T> {
     /// vectorized representations are equal.
     pub fn vectorize(&self) -> Pattern<T> {
         let length = self.len();
-        let mut diffs = Vec::with_capacity(length - 1);
-        for i in 0..length - 1 {
-            diffs.push(self[i + 1] - self[i]);
+        let mut diffs = Vec::with_capacity(length.saturating_sub(1));
+        for i in 1..length {
+            diffs.push(self[i] - self[i - 1]);
         }
 
-        Pattern { points: diffs }
+        Pattern {
+            points: diffs.into(),
+        }
+    }
+
+    /// Returns a point set consisting of the points of this pattern.
+    /// Duplicate points are included only once in the point set.
+    pub fn to_point_set(&self) -> PointSet<T> {
+        PointSet::new(self.points.to_vec())
+    }
+
+    /// Returns the octave-reduced (pitch class) version of this pattern, where the pitch
+    /// (the second component) of each point is mapped to the range [0, 12) by taking it modulo 12.
+    /// Points that become equal in the reduction are included only once, and the points
+    /// of the returned pattern are in lexicographical order.
+    pub fn pitch_class_reduced(&self) -> Pattern<T> {
+        let points: Vec<T> = self.points.iter().map(reduce_pitch_class).collect();
+        PointSet::new(points).into()
+    }
+
+    /// Returns the bounding box of this pattern in the plane spanned by time and pitch
+    /// (the first two components). The bounding box of an empty pattern is empty.
+    pub fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::of(self.points.iter())
+    }
+
+    /// Returns the convex hull of this pattern in the plane spanned by time and pitch
+    /// (the first two components).
+    pub fn convex_hull(&self) -> ConvexHull {
+        ConvexHull::of(self.points.iter())
+    }
+
+    /// Returns the area of the convex hull of this pattern.
+    pub fn hull_area(&self) -> f64 {
+        self.convex_hull().area()
+    }
+
+    /// Returns the time between the first and last onset in this pattern, where time is
+    /// the first component of the points. The time span of an empty pattern is zero.
+    pub fn time_span(&self) -> f64 {
+        self.bounding_box().width()
     }
 
     /// Returns a translated copy of this pattern
@@ -70,16 +113,28 @@ impl<T: Point> Pattern<T> {
     /// * `translator` - The vector by which this pattern is translated.
     pub fn translate(&self, translator: &T) -> Pattern<T> {
         let mut translated_points = Vec::with_capacity(self.len());
-        for point in &self.points {
+        for point in self.points.iter() {
             translated_points.push(*point + *translator);
         }
 
         Pattern {
-            points: translated_points,
+            points: translated_points.into(),
         }
     }
 }
 
+/// The number of pitch classes in an octave.
+const PITCH_CLASS_COUNT: f64 = 12.0;
+
+/// Returns the point with its pitch (second component) reduced modulo 12. Points
+/// without a pitch component, or whose pitch cannot be replaced, are returned as is.
+pub(crate) fn reduce_pitch_class<T: Point>(point: &T) -> T {
+    point
+        .component_f64(1)
+        .and_then(|pitch| point.with_component_f64(1, pitch.rem_euclid(PITCH_CLASS_COUNT)))
+        .unwrap_or(*point)
+}
+
 impl<T: Point> Index<usize> for Pattern<T> {
     type Output = T;
 
@@ -105,10 +160,16 @@ impl<T: Point> PartialEq for Pattern<T> {
 
 impl<T: Point> Eq for Pattern<T> {}
 
+impl<T: Point> Hash for Pattern<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.points.hash(state);
+    }
+}
+
 impl<T: Point> From<PointSet<T>> for Pattern<T> {
     fn from(point_set: PointSet<T>) -> Self {
         Pattern {
-            points: point_set.points(),
+            points: point_set.shared_points(),
         }
     }
 }
@@ -120,14 +181,10 @@ impl<T: Point> PartialOrd<Self> for Pattern<T> {
 }
 
 impl<T: Point> Clone for Pattern<T> {
+    /// Returns a pattern that shares the points of this pattern.
     fn clone(&self) -> Self {
-        let mut points_copy = Vec::with_capacity(self.points.len());
-        for point in &self.points {
-            points_copy.push(*point);
-        }
-
         Pattern {
-            points: points_copy,
+            points: Arc::clone(&self.points),
         }
     }
 }
@@ -156,8 +213,11 @@ impl<T: Point> Ord for Pattern<T> {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use crate::point_set::pattern::Pattern;
     use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
 
     #[test]
     fn test_constructor_and_access() {
@@ -180,6 +240,24 @@ mod tests {
         assert_eq!(c, pattern[2]);
     }
 
+    #[test]
+    fn test_clones_share_points() {
+        let mut point_set = PointSet::new(vec![
+            Point2Df64 { x: 1.0, y: 2.0 },
+            Point2Df64 { x: 0.0, y: 1.0 },
+        ]);
+        let pattern: Pattern<Point2Df64> = point_set.clone().into();
+        let clone = pattern.clone();
+        assert!(Arc::ptr_eq(&pattern.points, &clone.points));
+        assert_eq!(point_set[0], pattern[0]);
+
+        // Modifying the point set does not modify the shared points.
+        point_set.union_with(&PointSet::new(vec![Point2Df64 { x: -1.0, y: 0.0 }]));
+        assert_eq!(3, point_set.len());
+        assert_eq!(2, clone.len());
+        assert_eq!(Point2Df64 { x: 0.0, y: 1.0 }, clone[0]);
+    }
+
     #[test]
     fn test_iteration() {
         let mut points = Vec::new();
@@ -224,6 +302,43 @@ mod tests {
         assert_ne!(pattern_a, pattern_c);
     }
 
+    #[test]
+    fn test_pitch_class_reduced() {
+        let a = Point2Df64 { x: 0.0, y: 60.0 };
+        let b = Point2Df64 { x: 0.0, y: 72.0 };
+        let c = Point2Df64 { x: 1.0, y: -1.0 };
+
+        let reduced = Pattern::new(&vec![&c, &a, &b]).pitch_class_reduced();
+        assert_eq!(
+            Pattern::new(&vec![
+                &Point2Df64 { x: 0.0, y: 0.0 },
+                &Point2Df64 { x: 1.0, y: 11.0 }
+            ]),
+            reduced
+        );
+    }
+
+    #[test]
+    fn test_geometry() {
+        let a = Point2Df64 { x: 1.0, y: 60.0 };
+        let b = Point2Df64 { x: 3.0, y: 60.0 };
+        let c = Point2Df64 { x: 3.0, y: 64.0 };
+        let pattern = Pattern::new(&vec![&c, &a, &b]);
+
+        let bb = pattern.bounding_box();
+        assert_eq!(
+            (1.0, 60.0, 3.0, 64.0),
+            (bb.lower_x, bb.lower_y, bb.upper_x, bb.upper_y)
+        );
+        assert_eq!(
+            &[(1.0, 60.0), (3.0, 60.0), (3.0, 64.0)],
+            pattern.convex_hull().vertices()
+        );
+        assert_eq!(4.0, pattern.hull_area());
+        assert_eq!(2.0, pattern.time_span());
+        assert_eq!(0.0, Pattern::<Point2Df64>::new(&vec![]).time_span());
+    }
+
     #[test]
     fn test_vectorization_of_single_point_pattern() {
         let mut points = Vec::new();
@@ -0,0 +1,141 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::cmp::Ordering;
+use std::ops::Index;
+
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+
+/// Represents a pattern in a point set by the indices of its points in the point set,
+/// so that the points are not copied. Algorithms can keep large numbers of candidate
+/// patterns as pattern references and create owned patterns (see `to_pattern`) only for
+/// the patterns that they output.
+#[derive(Debug, Clone)]
+pub struct PatternRef<'a, T: Point> {
+    point_set: &'a PointSet<T>,
+    indices: Vec<usize>,
+}
+
+impl<'a, T: Point> PatternRef<'a, T> {
+    /// Returns a reference to the pattern of the points at the given indices of the point set,
+    /// in the order of the indices.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set that contains the points of the pattern
+    /// * `indices` - The indices of the points of the pattern, which must be in the point set
+    pub fn new(point_set: &'a PointSet<T>, indices: Vec<usize>) -> PatternRef<'a, T> {
+        assert!(
+            indices.iter().all(|index| *index < point_set.len()),
+            "pattern indices must be in the point set"
+        );
+        PatternRef { point_set, indices }
+    }
+
+    /// Returns the point set that contains the points of this pattern.
+    pub fn point_set(&self) -> &'a PointSet<T> {
+        self.point_set
+    }
+
+    /// Returns the indices of the points of this pattern in the point set.
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// Returns the number of points in this pattern
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Returns true if this pattern is empty
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Returns an iterator over the points of this pattern.
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> + '_ {
+        let point_set = self.point_set;
+        self.indices.iter().map(move |index| &point_set[*index])
+    }
+
+    /// Returns an iterator over the vectorized representation of this pattern, i.e.,
+    /// the differences between the adjacent points (see `Pattern::vectorize`), which are
+    /// computed as the iterator advances.
+    pub fn vectorized_iter(&self) -> impl Iterator<Item = T> + '_ {
+        self.indices
+            .windows(2)
+            .map(|pair| self.point_set[pair[1]] - self.point_set[pair[0]])
+    }
+
+    /// Compares the vectorized representations of this pattern and the other pattern
+    /// as in `Pattern`, so that the ordering is the same as the ordering of the vectorized
+    /// patterns, and translationally equivalent patterns are equal.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The pattern with which this pattern is compared
+    pub fn cmp_vectorized(&self, other: &PatternRef<T>) -> Ordering {
+        self.vectorized_iter().cmp(other.vectorized_iter())
+    }
+
+    /// Returns an owned pattern with copies of the points of this pattern.
+    pub fn to_pattern(&self) -> Pattern<T> {
+        self.point_set.get_pattern(&self.indices)
+    }
+}
+
+impl<'a, T: Point> Index<usize> for PatternRef<'a, T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.point_set[self.indices[index]]
+    }
+}
+
+impl<'a, T: Point> From<PatternRef<'a, T>> for Pattern<T> {
+    fn from(pattern: PatternRef<'a, T>) -> Self {
+        pattern.to_pattern()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::pattern_ref::PatternRef;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+
+    #[test]
+    fn test_pattern_ref_equals_copied_pattern() {
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 0.0, y: 60.0 },
+            Point2Df64 { x: 1.0, y: 62.0 },
+            Point2Df64 { x: 2.0, y: 60.0 },
+            Point2Df64 { x: 3.0, y: 62.0 },
+            Point2Df64 { x: 4.0, y: 67.0 },
+        ]);
+        let a = PatternRef::new(&point_set, vec![0, 1]);
+        let b = PatternRef::new(&point_set, vec![2, 3]);
+        let c = PatternRef::new(&point_set, vec![3, 4]);
+
+        let pattern = a.to_pattern();
+        assert_eq!(point_set.get_pattern(&[0, 1]), pattern);
+        assert_eq!(pattern[1], a[1]);
+        assert!(a.iter().eq(pattern.into_iter()));
+        assert!(a
+            .vectorized_iter()
+            .eq(pattern.vectorize().into_iter().copied()));
+
+        assert_eq!(Ordering::Equal, a.cmp_vectorized(&b));
+        assert_eq!(
+            b.to_pattern().vectorize().cmp(&c.to_pattern().vectorize()),
+            b.cmp_vectorized(&c)
+        );
+        assert_eq!(Pattern::from(c), point_set.get_pattern(&[3, 4]));
+    }
+}
@@ -25,6 +25,8 @@ pub trait Point:
     + Clone
     + Debug
     + Hash
+    + Send
+    + Sync
 {
     /// Returns true if this point is zero (all components are zero).
     fn is_zero(&self) -> bool;
@@ -36,14 +38,44 @@ pub trait Point:
     /// * `index` - the index of the component to return, or empty if the index is out of bounds
     fn component_f64(&self, index: usize) -> Option<f64>;
 
+    /// Returns a copy of this point with the component at the given index replaced by the value,
+    /// or empty if the index is out of bounds. By default no component can be replaced, so that
+    /// the transformations that need this, e.g., inversion, leave the points unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - the index of the component to replace
+    /// * `value` - the new value of the component
+    fn with_component_f64(&self, _index: usize, _value: f64) -> Option<Self> {
+        None
+    }
+
     /// Returns the dimensionality of this point.
     fn dimensionality(&self) -> usize;
+
+    /// Returns true if the component at the given index participates in translations, that is,
+    /// in the comparisons and hashing of the points. Inactive components are carried along,
+    /// but points that differ only in them are equal. By default all components are active.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - the index of the component
+    fn is_active(&self, index: usize) -> bool {
+        index < self.dimensionality()
+    }
+
+    /// Returns the weight of this point, e.g., the duration of the note, used by the heuristics
+    /// for weighting the coverage of patterns. By default all points have the weight 1.
+    fn weight(&self) -> f64 {
+        1.0
+    }
 }
 
 /// Represents a 2-dimensional point/vector with floating point (f64) components.
 /// No rounding or inexactness is used in comparisons, so this point type will not work
 /// correctly in all cases (e.g., even with music that contains triplets).
 #[derive(Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point2Df64 {
     /// The x coordinate of the point
     pub x: f64,
@@ -67,6 +99,20 @@ impl Point for Point2Df64 {
         }
     }
 
+    fn with_component_f64(&self, index: usize, value: f64) -> Option<Self> {
+        match index {
+            0 => Some(Point2Df64 {
+                x: value,
+                y: self.y,
+            }),
+            1 => Some(Point2Df64 {
+                x: self.x,
+                y: value,
+            }),
+            _ => None,
+        }
+    }
+
     fn dimensionality(&self) -> usize {
         2
     }
@@ -240,6 +286,14 @@ impl Point for Point2DRf64 {
         }
     }
 
+    fn with_component_f64(&self, index: usize, value: f64) -> Option<Self> {
+        match index {
+            0 => Some(Point2DRf64::new(value, self.y)),
+            1 => Some(Point2DRf64::new(self.raw_x, value)),
+            _ => None,
+        }
+    }
+
     fn dimensionality(&self) -> usize {
         2
     }
@@ -383,6 +437,7 @@ impl Hash for Point2DRf64 {
 
 /// Represents a 2-dimensional point/vector with integer components.
 #[derive(Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point2Di64 {
     /// The x coordinate of the point
     pub x: i64,
@@ -406,6 +461,22 @@ impl Point for Point2Di64 {
         }
     }
 
+    /// The value is rounded to the nearest integer.
+    fn with_component_f64(&self, index: usize, value: f64) -> Option<Self> {
+        let value = value.round() as i64;
+        match index {
+            0 => Some(Point2Di64 {
+                x: value,
+                y: self.y,
+            }),
+            1 => Some(Point2Di64 {
+                x: self.x,
+                y: value,
+            }),
+            _ => None,
+        }
+    }
+
     fn dimensionality(&self) -> usize {
         2
     }
@@ -531,6 +602,177 @@ impl Hash for Point2Di64 {
     }
 }
 
+/// Represents a 2-dimensional point/vector with floating point (f64) components and a weight,
+/// e.g., the duration of the note. The weight is not a component of the point: it does not
+/// participate in comparisons or hashing, so points that differ only by weight are equal.
+/// Arithmetic retains the weight of the left operand, so translated points carry the weights
+/// of the points of the pattern.
+#[derive(Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point2DWeighted {
+    /// The x coordinate of the point
+    pub x: f64,
+    /// The y coordinate of the point
+    pub y: f64,
+    /// The weight of the point
+    pub weight: f64,
+}
+
+impl Point for Point2DWeighted {
+    /// Returns true if this point is zero. The weight is ignored.
+    fn is_zero(&self) -> bool {
+        self.x == 0.0 && self.y == 0.0
+    }
+
+    fn component_f64(&self, index: usize) -> Option<f64> {
+        match index {
+            0 => Some(self.x),
+            1 => Some(self.y),
+            _ => None,
+        }
+    }
+
+    fn with_component_f64(&self, index: usize, value: f64) -> Option<Self> {
+        match index {
+            0 => Some(Point2DWeighted { x: value, ..*self }),
+            1 => Some(Point2DWeighted { y: value, ..*self }),
+            _ => None,
+        }
+    }
+
+    fn dimensionality(&self) -> usize {
+        2
+    }
+
+    fn weight(&self) -> f64 {
+        self.weight
+    }
+}
+
+// Traits for by value arithmetic
+impl ops::Add<Point2DWeighted> for Point2DWeighted {
+    type Output = Self;
+
+    fn add(self, rhs: Point2DWeighted) -> Point2DWeighted {
+        Point2DWeighted {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            weight: self.weight,
+        }
+    }
+}
+
+impl ops::Sub<Point2DWeighted> for Point2DWeighted {
+    type Output = Self;
+
+    fn sub(self, rhs: Point2DWeighted) -> Self::Output {
+        Point2DWeighted {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            weight: self.weight,
+        }
+    }
+}
+
+impl ops::Mul<f64> for Point2DWeighted {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Point2DWeighted {
+            x: self.x * rhs,
+            y: self.y * rhs,
+            weight: self.weight,
+        }
+    }
+}
+
+// Traits for by reference arithmetic
+impl ops::Add<&Point2DWeighted> for &Point2DWeighted {
+    type Output = Point2DWeighted;
+
+    fn add(self, rhs: &Point2DWeighted) -> Point2DWeighted {
+        Point2DWeighted {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            weight: self.weight,
+        }
+    }
+}
+
+impl ops::Sub<&Point2DWeighted> for &Point2DWeighted {
+    type Output = Point2DWeighted;
+
+    fn sub(self, rhs: &Point2DWeighted) -> Self::Output {
+        Point2DWeighted {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            weight: self.weight,
+        }
+    }
+}
+
+impl ops::Mul<f64> for &Point2DWeighted {
+    type Output = Point2DWeighted;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Point2DWeighted {
+            x: self.x * rhs,
+            y: self.y * rhs,
+            weight: self.weight,
+        }
+    }
+}
+
+// Comparisons
+impl PartialEq for Point2DWeighted {
+    fn eq(&self, other: &Self) -> bool {
+        self.x == other.x && self.y == other.y
+    }
+}
+
+impl Clone for Point2DWeighted {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl Eq for Point2DWeighted {}
+
+impl PartialOrd for Point2DWeighted {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Point2DWeighted {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.x < other.x {
+            return Ordering::Less;
+        }
+
+        if self.x > other.x {
+            return Ordering::Greater;
+        }
+
+        if self.y < other.y {
+            return Ordering::Less;
+        }
+
+        if self.y > other.y {
+            return Ordering::Greater;
+        }
+
+        Ordering::Equal
+    }
+}
+
+impl Hash for Point2DWeighted {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write(&self.x.to_ne_bytes());
+        state.write(&self.y.to_ne_bytes());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -550,6 +792,33 @@ mod tests {
         assert_ne!(Point2Di64 { x: 3, y: 1 }, Point2Di64 { x: 3, y: 2 });
     }
 
+    #[test]
+    fn test_weighted_points() {
+        let a = Point2DWeighted {
+            x: 1.0,
+            y: 60.0,
+            weight: 2.0,
+        };
+        let b = Point2DWeighted {
+            x: 1.0,
+            y: 60.0,
+            weight: 0.5,
+        };
+        assert_eq!(a, b);
+        assert_eq!(2.0, a.weight());
+        assert_eq!(1.0, Point2Df64 { x: 1.0, y: 60.0 }.weight());
+
+        let translator = Point2DWeighted {
+            x: 4.0,
+            y: 62.0,
+            weight: 1.0,
+        } - b;
+        let translated = a + translator;
+        assert_eq!(Some(4.0), translated.component_f64(0));
+        assert_eq!(Some(62.0), translated.component_f64(1));
+        assert_eq!(2.0, translated.weight());
+    }
+
     #[test]
     fn test_add() {
         let a = Point2Df64 { x: 1.0, y: 1.0 };
@@ -650,4 +919,55 @@ mod tests {
         assert_eq!(Some(2.0), c.component_f64(1));
         assert_eq!(None, c.component_f64(3));
     }
+
+    /// A point type that implements only the required methods of `Point`.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    struct Onset(i64);
+
+    impl Add for Onset {
+        type Output = Onset;
+
+        fn add(self, other: Onset) -> Onset {
+            Onset(self.0 + other.0)
+        }
+    }
+
+    impl Sub for Onset {
+        type Output = Onset;
+
+        fn sub(self, other: Onset) -> Onset {
+            Onset(self.0 - other.0)
+        }
+    }
+
+    impl Mul<f64> for Onset {
+        type Output = Onset;
+
+        fn mul(self, rhs: f64) -> Onset {
+            Onset((self.0 as f64 * rhs) as i64)
+        }
+    }
+
+    impl Point for Onset {
+        fn is_zero(&self) -> bool {
+            self.0 == 0
+        }
+
+        fn component_f64(&self, index: usize) -> Option<f64> {
+            (index == 0).then_some(self.0 as f64)
+        }
+
+        fn dimensionality(&self) -> usize {
+            1
+        }
+    }
+
+    #[test]
+    fn test_default_methods() {
+        let a = Onset(2);
+        assert_eq!(None, a.with_component_f64(0, 3.0));
+        assert!(a.is_active(0));
+        assert!(!a.is_active(1));
+        assert_eq!(1.0, a.weight());
+    }
 }
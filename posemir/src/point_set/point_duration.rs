@@ -0,0 +1,211 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::ops;
+
+use crate::point_set::point::{Point, Point2Df64};
+
+/// The index of the duration component of a `Point3DDuration`.
+pub const DURATION_COMPONENT: usize = 2;
+
+/// Represents a note as a 3-dimensional point of onset, pitch, and duration, i.e., the time
+/// from the onset to the note-off.
+///
+/// The duration is an inactive component (see `Point::is_active`): it does not participate in
+/// comparisons or hashing, so the discovery algorithms find the same patterns as with
+/// `Point2Df64`, and points that differ only by duration are equal. The pattern matchers can
+/// compare the durations of the matching notes (see `DurationMatching`). Arithmetic retains the
+/// duration of the left operand, so translated points carry the durations of the points of the
+/// pattern. The duration is also the weight of the point.
+#[derive(Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point3DDuration {
+    /// The onset time of the note
+    pub onset: f64,
+    /// The pitch of the note
+    pub pitch: f64,
+    /// The duration of the note
+    pub duration: f64,
+}
+
+impl Point3DDuration {
+    /// Returns the onset and the pitch of this point as a 2-dimensional point.
+    pub fn to_2d_point(&self) -> Point2Df64 {
+        Point2Df64 {
+            x: self.onset,
+            y: self.pitch,
+        }
+    }
+}
+
+impl Point for Point3DDuration {
+    /// Returns true if the onset and the pitch of this point are zero.
+    fn is_zero(&self) -> bool {
+        self.onset == 0.0 && self.pitch == 0.0
+    }
+
+    fn component_f64(&self, index: usize) -> Option<f64> {
+        match index {
+            0 => Some(self.onset),
+            1 => Some(self.pitch),
+            DURATION_COMPONENT => Some(self.duration),
+            _ => None,
+        }
+    }
+
+    fn with_component_f64(&self, index: usize, value: f64) -> Option<Self> {
+        match index {
+            0 => Some(Point3DDuration {
+                onset: value,
+                ..*self
+            }),
+            1 => Some(Point3DDuration {
+                pitch: value,
+                ..*self
+            }),
+            DURATION_COMPONENT => Some(Point3DDuration {
+                duration: value,
+                ..*self
+            }),
+            _ => None,
+        }
+    }
+
+    fn dimensionality(&self) -> usize {
+        3
+    }
+
+    fn is_active(&self, index: usize) -> bool {
+        index < DURATION_COMPONENT
+    }
+
+    fn weight(&self) -> f64 {
+        self.duration
+    }
+}
+
+// Traits for by value arithmetic
+impl ops::Add<Point3DDuration> for Point3DDuration {
+    type Output = Self;
+
+    fn add(self, rhs: Point3DDuration) -> Point3DDuration {
+        &self + &rhs
+    }
+}
+
+impl ops::Sub<Point3DDuration> for Point3DDuration {
+    type Output = Self;
+
+    fn sub(self, rhs: Point3DDuration) -> Self::Output {
+        &self - &rhs
+    }
+}
+
+impl ops::Mul<f64> for Point3DDuration {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Point3DDuration {
+            onset: self.onset * rhs,
+            pitch: self.pitch * rhs,
+            duration: self.duration,
+        }
+    }
+}
+
+// Traits for by reference arithmetic
+impl ops::Add<&Point3DDuration> for &Point3DDuration {
+    type Output = Point3DDuration;
+
+    fn add(self, rhs: &Point3DDuration) -> Point3DDuration {
+        Point3DDuration {
+            onset: self.onset + rhs.onset,
+            pitch: self.pitch + rhs.pitch,
+            duration: self.duration,
+        }
+    }
+}
+
+impl ops::Sub<&Point3DDuration> for &Point3DDuration {
+    type Output = Point3DDuration;
+
+    fn sub(self, rhs: &Point3DDuration) -> Self::Output {
+        Point3DDuration {
+            onset: self.onset - rhs.onset,
+            pitch: self.pitch - rhs.pitch,
+            duration: self.duration,
+        }
+    }
+}
+
+impl ops::Mul<f64> for &Point3DDuration {
+    type Output = Point3DDuration;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Point3DDuration {
+            onset: self.onset * rhs,
+            pitch: self.pitch * rhs,
+            duration: self.duration,
+        }
+    }
+}
+
+// Comparisons
+impl PartialEq for Point3DDuration {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Clone for Point3DDuration {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl Eq for Point3DDuration {}
+
+impl PartialOrd for Point3DDuration {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Point3DDuration {
+    /// Compares the points lexicographically by the onset and the pitch.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.to_2d_point().cmp(&other.to_2d_point())
+    }
+}
+
+impl Hash for Point3DDuration {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_2d_point().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::point_set::point::Point;
+    use crate::point_set::point_duration::{Point3DDuration, DURATION_COMPONENT};
+
+    #[test]
+    fn test_duration_is_inactive() {
+        let a = Point3DDuration {
+            onset: 1.0,
+            pitch: 60.0,
+            duration: 0.5,
+        };
+        let b = Point3DDuration { duration: 2.0, ..a };
+
+        assert_eq!(a, b);
+        assert!(!a.is_active(DURATION_COMPONENT));
+        assert_eq!(Some(2.0), b.component_f64(DURATION_COMPONENT));
+        assert_eq!(0.5, (a - b).duration);
+        assert!((a - b).is_zero());
+        assert_eq!(2.0, b.weight());
+    }
+}
@@ -0,0 +1,566 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::ops;
+
+use crate::error::PoSemirError;
+use crate::point_set::point::Point;
+
+/// Represents an N-dimensional point/vector with floating point (f64) components.
+/// This makes it possible to use, e.g., onset, chromatic pitch, morphetic pitch, duration
+/// and voice as the dimensions of the points. As with `Point2Df64`, no rounding or inexactness
+/// is used in comparisons.
+#[derive(Debug, Copy)]
+pub struct PointNdf64<const N: usize> {
+    /// The components of the point
+    pub components: [f64; N],
+}
+
+impl<const N: usize> Point for PointNdf64<N> {
+    /// Returns true if this point is zero.
+    fn is_zero(&self) -> bool {
+        self.components.iter().all(|c| *c == 0.0)
+    }
+
+    fn component_f64(&self, index: usize) -> Option<f64> {
+        self.components.get(index).copied()
+    }
+
+    fn with_component_f64(&self, index: usize, value: f64) -> Option<Self> {
+        if index >= N {
+            return None;
+        }
+
+        let mut point = *self;
+        point.components[index] = value;
+        Some(point)
+    }
+
+    fn dimensionality(&self) -> usize {
+        N
+    }
+}
+
+// Traits for by value arithmetic
+impl<const N: usize> ops::Add<PointNdf64<N>> for PointNdf64<N> {
+    type Output = Self;
+
+    fn add(self, rhs: PointNdf64<N>) -> PointNdf64<N> {
+        &self + &rhs
+    }
+}
+
+impl<const N: usize> ops::Sub<PointNdf64<N>> for PointNdf64<N> {
+    type Output = Self;
+
+    fn sub(self, rhs: PointNdf64<N>) -> Self::Output {
+        &self - &rhs
+    }
+}
+
+impl<const N: usize> ops::Mul<f64> for PointNdf64<N> {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        PointNdf64 {
+            components: self.components.map(|c| c * rhs),
+        }
+    }
+}
+
+// Traits for by reference arithmetic
+impl<const N: usize> ops::Add<&PointNdf64<N>> for &PointNdf64<N> {
+    type Output = PointNdf64<N>;
+
+    fn add(self, rhs: &PointNdf64<N>) -> PointNdf64<N> {
+        PointNdf64 {
+            components: std::array::from_fn(|i| self.components[i] + rhs.components[i]),
+        }
+    }
+}
+
+impl<const N: usize> ops::Sub<&PointNdf64<N>> for &PointNdf64<N> {
+    type Output = PointNdf64<N>;
+
+    fn sub(self, rhs: &PointNdf64<N>) -> Self::Output {
+        PointNdf64 {
+            components: std::array::from_fn(|i| self.components[i] - rhs.components[i]),
+        }
+    }
+}
+
+impl<const N: usize> ops::Mul<f64> for &PointNdf64<N> {
+    type Output = PointNdf64<N>;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        PointNdf64 {
+            components: self.components.map(|c| c * rhs),
+        }
+    }
+}
+
+// Comparisons
+impl<const N: usize> PartialEq for PointNdf64<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.components == other.components
+    }
+}
+
+impl<const N: usize> Clone for PointNdf64<N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<const N: usize> Eq for PointNdf64<N> {}
+
+impl<const N: usize> PartialOrd for PointNdf64<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> Ord for PointNdf64<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        for i in 0..N {
+            if self.components[i] < other.components[i] {
+                return Ordering::Less;
+            }
+
+            if self.components[i] > other.components[i] {
+                return Ordering::Greater;
+            }
+        }
+
+        Ordering::Equal
+    }
+}
+
+impl<const N: usize> Hash for PointNdf64<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for component in &self.components {
+            state.write(&component.to_ne_bytes());
+        }
+    }
+}
+
+/// Represents an N-dimensional point/vector with integer components.
+#[derive(Debug, Copy)]
+pub struct PointNdi64<const N: usize> {
+    /// The components of the point
+    pub components: [i64; N],
+}
+
+impl<const N: usize> Point for PointNdi64<N> {
+    /// Returns true if this point is zero.
+    fn is_zero(&self) -> bool {
+        self.components.iter().all(|c| *c == 0)
+    }
+
+    fn component_f64(&self, index: usize) -> Option<f64> {
+        self.components.get(index).map(|c| *c as f64)
+    }
+
+    /// The value is rounded to the nearest integer.
+    fn with_component_f64(&self, index: usize, value: f64) -> Option<Self> {
+        if index >= N {
+            return None;
+        }
+
+        let mut point = *self;
+        point.components[index] = value.round() as i64;
+        Some(point)
+    }
+
+    fn dimensionality(&self) -> usize {
+        N
+    }
+}
+
+// Traits for by value arithmetic
+impl<const N: usize> ops::Add<PointNdi64<N>> for PointNdi64<N> {
+    type Output = Self;
+
+    fn add(self, rhs: PointNdi64<N>) -> PointNdi64<N> {
+        &self + &rhs
+    }
+}
+
+impl<const N: usize> ops::Sub<PointNdi64<N>> for PointNdi64<N> {
+    type Output = Self;
+
+    fn sub(self, rhs: PointNdi64<N>) -> Self::Output {
+        &self - &rhs
+    }
+}
+
+impl<const N: usize> ops::Mul<f64> for PointNdi64<N> {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        let rhs_int = rhs as i64;
+        PointNdi64 {
+            components: self.components.map(|c| c * rhs_int),
+        }
+    }
+}
+
+// Traits for by reference arithmetic
+impl<const N: usize> ops::Add<&PointNdi64<N>> for &PointNdi64<N> {
+    type Output = PointNdi64<N>;
+
+    fn add(self, rhs: &PointNdi64<N>) -> PointNdi64<N> {
+        PointNdi64 {
+            components: std::array::from_fn(|i| self.components[i] + rhs.components[i]),
+        }
+    }
+}
+
+impl<const N: usize> ops::Sub<&PointNdi64<N>> for &PointNdi64<N> {
+    type Output = PointNdi64<N>;
+
+    fn sub(self, rhs: &PointNdi64<N>) -> Self::Output {
+        PointNdi64 {
+            components: std::array::from_fn(|i| self.components[i] - rhs.components[i]),
+        }
+    }
+}
+
+impl<const N: usize> ops::Mul<f64> for &PointNdi64<N> {
+    type Output = PointNdi64<N>;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        let rhs_int = rhs as i64;
+        PointNdi64 {
+            components: self.components.map(|c| c * rhs_int),
+        }
+    }
+}
+
+// Comparisons
+impl<const N: usize> PartialEq for PointNdi64<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.components == other.components
+    }
+}
+
+impl<const N: usize> Clone for PointNdi64<N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<const N: usize> Eq for PointNdi64<N> {}
+
+impl<const N: usize> PartialOrd for PointNdi64<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> Ord for PointNdi64<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.components.cmp(&other.components)
+    }
+}
+
+impl<const N: usize> Hash for PointNdi64<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for component in &self.components {
+            state.write_i64(*component);
+        }
+    }
+}
+
+/// The maximum number of components of a `PointDynf64`.
+pub const MAX_DYNAMIC_DIMENSIONALITY: usize = 8;
+
+/// Represents a point/vector with floating point (f64) components whose dimensionality is
+/// chosen at runtime, e.g., when the analysed columns of a CSV file are selected by the user
+/// (see `PointSet::project`). The components are stored inline so that the points can be
+/// copied, which limits the dimensionality to `MAX_DYNAMIC_DIMENSIONALITY`.
+///
+/// All points of a point set are expected to have the same dimensionality. Points of
+/// different dimensionalities are never equal, and arithmetic on them treats the missing
+/// components as zero. As with `PointNdf64`, no rounding or inexactness is used in comparisons.
+#[derive(Debug, Copy)]
+pub struct PointDynf64 {
+    components: [f64; MAX_DYNAMIC_DIMENSIONALITY],
+    dimensionality: usize,
+}
+
+impl PointDynf64 {
+    /// Returns a point with the given components, or an error if there are more than
+    /// `MAX_DYNAMIC_DIMENSIONALITY` components.
+    ///
+    /// # Arguments
+    ///
+    /// * `components` - The components of the point
+    pub fn new(components: &[f64]) -> Result<PointDynf64, PoSemirError> {
+        if components.len() > MAX_DYNAMIC_DIMENSIONALITY {
+            return Err(PoSemirError::InvalidPointSet(format!(
+                "points can have at most {} components, got {}",
+                MAX_DYNAMIC_DIMENSIONALITY,
+                components.len()
+            )));
+        }
+
+        let mut point = PointDynf64 {
+            components: [0.0; MAX_DYNAMIC_DIMENSIONALITY],
+            dimensionality: components.len(),
+        };
+        point.components[..components.len()].copy_from_slice(components);
+        Ok(point)
+    }
+
+    /// Returns the components of this point.
+    pub fn components(&self) -> &[f64] {
+        &self.components[..self.dimensionality]
+    }
+
+    fn combine(&self, rhs: &PointDynf64, op: impl Fn(f64, f64) -> f64) -> PointDynf64 {
+        PointDynf64 {
+            components: std::array::from_fn(|i| op(self.components[i], rhs.components[i])),
+            dimensionality: self.dimensionality.max(rhs.dimensionality),
+        }
+    }
+}
+
+impl Point for PointDynf64 {
+    /// Returns true if this point is zero.
+    fn is_zero(&self) -> bool {
+        self.components().iter().all(|c| *c == 0.0)
+    }
+
+    fn component_f64(&self, index: usize) -> Option<f64> {
+        self.components().get(index).copied()
+    }
+
+    fn with_component_f64(&self, index: usize, value: f64) -> Option<Self> {
+        if index >= self.dimensionality {
+            return None;
+        }
+
+        let mut point = *self;
+        point.components[index] = value;
+        Some(point)
+    }
+
+    fn dimensionality(&self) -> usize {
+        self.dimensionality
+    }
+}
+
+// Traits for by value arithmetic
+impl ops::Add<PointDynf64> for PointDynf64 {
+    type Output = Self;
+
+    fn add(self, rhs: PointDynf64) -> PointDynf64 {
+        self.combine(&rhs, |a, b| a + b)
+    }
+}
+
+impl ops::Sub<PointDynf64> for PointDynf64 {
+    type Output = Self;
+
+    fn sub(self, rhs: PointDynf64) -> Self::Output {
+        self.combine(&rhs, |a, b| a - b)
+    }
+}
+
+impl ops::Mul<f64> for PointDynf64 {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        PointDynf64 {
+            components: self.components.map(|c| c * rhs),
+            dimensionality: self.dimensionality,
+        }
+    }
+}
+
+// Traits for by reference arithmetic
+impl ops::Add<&PointDynf64> for &PointDynf64 {
+    type Output = PointDynf64;
+
+    fn add(self, rhs: &PointDynf64) -> PointDynf64 {
+        self.combine(rhs, |a, b| a + b)
+    }
+}
+
+impl ops::Sub<&PointDynf64> for &PointDynf64 {
+    type Output = PointDynf64;
+
+    fn sub(self, rhs: &PointDynf64) -> Self::Output {
+        self.combine(rhs, |a, b| a - b)
+    }
+}
+
+impl ops::Mul<f64> for &PointDynf64 {
+    type Output = PointDynf64;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        *self * rhs
+    }
+}
+
+// Comparisons
+impl PartialEq for PointDynf64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.components() == other.components()
+    }
+}
+
+impl Clone for PointDynf64 {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl Eq for PointDynf64 {}
+
+impl PartialOrd for PointDynf64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PointDynf64 {
+    /// Compares the points lexicographically, so that a point is less than the points
+    /// of higher dimensionality of which it is a prefix.
+    fn cmp(&self, other: &Self) -> Ordering {
+        for (a, b) in self.components().iter().zip(other.components()) {
+            if a < b {
+                return Ordering::Less;
+            }
+
+            if a > b {
+                return Ordering::Greater;
+            }
+        }
+
+        self.dimensionality.cmp(&other.dimensionality)
+    }
+}
+
+impl Hash for PointDynf64 {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for component in self.components() {
+            state.write(&component.to_ne_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use crate::point_set::point::Point;
+    use crate::point_set::point_nd::{PointDynf64, PointNdf64, PointNdi64};
+
+    #[test]
+    fn test_arithmetic() {
+        let a = PointNdf64 {
+            components: [1.0, 60.0, 35.0, 0.5, 1.0],
+        };
+        let b = PointNdf64 {
+            components: [2.0, 2.0, 1.0, 0.0, 0.0],
+        };
+        assert_eq!(
+            PointNdf64 {
+                components: [3.0, 62.0, 36.0, 0.5, 1.0]
+            },
+            a + b
+        );
+        assert_eq!(
+            PointNdf64 {
+                components: [-1.0, 58.0, 34.0, 0.5, 1.0]
+            },
+            a - b
+        );
+        assert!((a - a).is_zero());
+
+        let c = PointNdi64 {
+            components: [1, 60, 35],
+        };
+        let d = PointNdi64 {
+            components: [2, 2, 1],
+        };
+        assert_eq!(
+            PointNdi64 {
+                components: [3, 62, 36]
+            },
+            c + d
+        );
+        assert_eq!(
+            PointNdi64 {
+                components: [4, 4, 2]
+            },
+            d * 2.0
+        );
+    }
+
+    #[test]
+    fn test_cmp() {
+        let a = PointNdf64 {
+            components: [0.0, 1.0, 2.0],
+        };
+        let b = PointNdf64 {
+            components: [0.0, 1.0, 3.0],
+        };
+        assert_eq!(Ordering::Less, a.cmp(&b));
+        assert_eq!(Ordering::Equal, a.cmp(&a));
+
+        let c = PointNdi64 {
+            components: [1, 0, 0],
+        };
+        let d = PointNdi64 {
+            components: [0, 5, 5],
+        };
+        assert!(d < c);
+    }
+
+    #[test]
+    fn test_component_access() {
+        let a = PointNdf64 {
+            components: [1.0, 2.0, 3.0],
+        };
+        assert_eq!(3, a.dimensionality());
+        assert_eq!(Some(3.0), a.component_f64(2));
+        assert_eq!(None, a.component_f64(3));
+        assert_eq!(
+            Some(PointNdf64 {
+                components: [1.0, 5.0, 3.0]
+            }),
+            a.with_component_f64(1, 5.0)
+        );
+
+        let b = PointNdi64 {
+            components: [1, 2, 3, 4],
+        };
+        assert_eq!(4, b.dimensionality());
+        assert_eq!(Some(4.0), b.component_f64(3));
+        assert_eq!(None, b.with_component_f64(4, 1.0));
+    }
+
+    #[test]
+    fn test_dynamic_dimensionality() {
+        let a = PointDynf64::new(&[1.0, 60.0, 0.5]).unwrap();
+        let b = PointDynf64::new(&[2.0, 2.0, 0.0]).unwrap();
+        assert_eq!(3, a.dimensionality());
+        assert_eq!(None, a.component_f64(3));
+        assert_eq!(&[3.0, 62.0, 0.5], (a + b).components());
+        assert_eq!(&[-1.0, 58.0, 0.5], (a - b).components());
+        assert!((a - a).is_zero());
+        assert!(a < b);
+
+        // A prefix is less than the longer point and not equal to it.
+        let prefix = PointDynf64::new(&[1.0, 60.0]).unwrap();
+        assert_eq!(Ordering::Less, prefix.cmp(&a));
+        assert_ne!(prefix, a);
+        assert!(PointDynf64::new(&[0.0; 9]).is_err());
+    }
+}
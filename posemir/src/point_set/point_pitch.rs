@@ -0,0 +1,410 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::ops;
+
+use crate::point_set::point::{Point, Point2Df64};
+use crate::point_set::set::PointSet;
+
+/// The pitch components of a `Point3DPitch` that participate in translations.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ActivePitch {
+    /// Only the chromatic pitch is active, so the translations are chromatic transpositions.
+    Chromatic,
+    /// Only the morphetic pitch is active, so the translations are diatonic transpositions,
+    /// e.g., a motif and its sequence a step higher in the scale are translations of each
+    /// other even if their intervals differ in size by a semitone.
+    Morphetic,
+    /// Both pitches are active, so the translations preserve both the chromatic and the
+    /// diatonic intervals.
+    Both,
+}
+
+/// Represents a note as a 3-dimensional point of onset, chromatic pitch (MIDI note number),
+/// and morphetic pitch [Meredith 2006], where A0 is 0 and, e.g., C4 is 23.
+///
+/// Only the active pitch components participate in comparisons and hashing (see
+/// `Point::is_active`), so the discovery algorithms find patterns whose occurrences are
+/// translations in the onset and the active pitches. Points that differ only by an inactive
+/// pitch are equal, e.g., C4 and C#4 with only the morphetic pitch active. Arithmetic applies
+/// to all components, and it retains the active pitches of the left operand.
+#[derive(Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point3DPitch {
+    /// The onset time of the note
+    pub onset: f64,
+    /// The chromatic pitch of the note as a MIDI note number
+    pub chromatic: f64,
+    /// The morphetic pitch of the note
+    pub morphetic: f64,
+    /// The pitches that participate in translations
+    pub active_pitch: ActivePitch,
+}
+
+const STEP_NAMES: [char; 7] = ['C', 'D', 'E', 'F', 'G', 'A', 'B'];
+const STEP_PITCH_CLASSES: [i32; 7] = [0, 2, 4, 5, 7, 9, 11];
+
+/// Returns the chromatic pitch (MIDI note number) of the spelled pitch, e.g., 61 for C#4,
+/// or None if the step is not a note name.
+///
+/// # Arguments
+///
+/// * `step` - The note name from A to G (case insensitive)
+/// * `alter` - The number of semitones by which the step is altered, e.g., 1 for a sharp
+/// * `octave` - The octave number, where C4 is the middle C
+pub fn chromatic_pitch(step: char, alter: i32, octave: i32) -> Option<f64> {
+    let step_index = step_index(step)?;
+    Some((12 * (octave + 1) + STEP_PITCH_CLASSES[step_index] + alter) as f64)
+}
+
+/// Returns the morphetic pitch of the spelled pitch, e.g., 23 for C4 and C#4,
+/// or None if the step is not a note name.
+///
+/// # Arguments
+///
+/// * `step` - The note name from A to G (case insensitive)
+/// * `octave` - The octave number, where C4 is the middle C
+pub fn morphetic_pitch(step: char, octave: i32) -> Option<f64> {
+    let step_index = step_index(step)?;
+    // A0 is 0 and the octaves change at C, which is the third step from A.
+    Some((7 * octave + step_index as i32 - 5) as f64)
+}
+
+fn step_index(step: char) -> Option<usize> {
+    STEP_NAMES
+        .iter()
+        .position(|name| *name == step.to_ascii_uppercase())
+}
+
+impl Point3DPitch {
+    /// Returns the point of the spelled note, or None if the step is not a note name.
+    ///
+    /// # Arguments
+    ///
+    /// * `onset` - The onset time of the note
+    /// * `step` - The note name from A to G (case insensitive)
+    /// * `alter` - The number of semitones by which the step is altered, e.g., 1 for a sharp
+    /// * `octave` - The octave number, where C4 is the middle C
+    /// * `active_pitch` - The pitches that participate in translations
+    pub fn from_spelling(
+        onset: f64,
+        step: char,
+        alter: i32,
+        octave: i32,
+        active_pitch: ActivePitch,
+    ) -> Option<Point3DPitch> {
+        Some(Point3DPitch {
+            onset,
+            chromatic: chromatic_pitch(step, alter, octave)?,
+            morphetic: morphetic_pitch(step, octave)?,
+            active_pitch,
+        })
+    }
+
+    /// Returns a copy of this point with the given active pitches.
+    ///
+    /// # Arguments
+    ///
+    /// * `active_pitch` - The pitches that participate in translations
+    pub fn with_active_pitch(&self, active_pitch: ActivePitch) -> Point3DPitch {
+        Point3DPitch {
+            active_pitch,
+            ..*self
+        }
+    }
+
+    /// Returns the onset and the chromatic pitch of this point as a 2-dimensional point.
+    pub fn to_chromatic_point(&self) -> Point2Df64 {
+        Point2Df64 {
+            x: self.onset,
+            y: self.chromatic,
+        }
+    }
+
+    /// Returns the onset and the morphetic pitch of this point as a 2-dimensional point.
+    pub fn to_morphetic_point(&self) -> Point2Df64 {
+        Point2Df64 {
+            x: self.onset,
+            y: self.morphetic,
+        }
+    }
+
+    /// Returns the active components of this point in component order.
+    fn active_components(&self) -> impl Iterator<Item = f64> + '_ {
+        (0..3)
+            .filter(|i| self.is_active(*i))
+            .map(|i| self.component_f64(i).unwrap())
+    }
+}
+
+impl PointSet<Point3DPitch> {
+    /// Returns a copy of this point set where all points have the given active pitches.
+    /// Points that are equal in the active components are merged.
+    ///
+    /// # Arguments
+    ///
+    /// * `active_pitch` - The pitches that participate in translations
+    pub fn with_active_pitch(&self, active_pitch: ActivePitch) -> PointSet<Point3DPitch> {
+        PointSet::new(
+            self.into_iter()
+                .map(|point| point.with_active_pitch(active_pitch))
+                .collect(),
+        )
+    }
+}
+
+impl Point for Point3DPitch {
+    /// Returns true if the active components of this point are zero.
+    fn is_zero(&self) -> bool {
+        self.active_components().all(|c| c == 0.0)
+    }
+
+    fn component_f64(&self, index: usize) -> Option<f64> {
+        match index {
+            0 => Some(self.onset),
+            1 => Some(self.chromatic),
+            2 => Some(self.morphetic),
+            _ => None,
+        }
+    }
+
+    fn with_component_f64(&self, index: usize, value: f64) -> Option<Self> {
+        match index {
+            0 => Some(Point3DPitch {
+                onset: value,
+                ..*self
+            }),
+            1 => Some(Point3DPitch {
+                chromatic: value,
+                ..*self
+            }),
+            2 => Some(Point3DPitch {
+                morphetic: value,
+                ..*self
+            }),
+            _ => None,
+        }
+    }
+
+    fn dimensionality(&self) -> usize {
+        3
+    }
+
+    fn is_active(&self, index: usize) -> bool {
+        match index {
+            0 => true,
+            1 => self.active_pitch != ActivePitch::Morphetic,
+            2 => self.active_pitch != ActivePitch::Chromatic,
+            _ => false,
+        }
+    }
+}
+
+// Traits for by value arithmetic
+impl ops::Add<Point3DPitch> for Point3DPitch {
+    type Output = Self;
+
+    fn add(self, rhs: Point3DPitch) -> Point3DPitch {
+        &self + &rhs
+    }
+}
+
+impl ops::Sub<Point3DPitch> for Point3DPitch {
+    type Output = Self;
+
+    fn sub(self, rhs: Point3DPitch) -> Self::Output {
+        &self - &rhs
+    }
+}
+
+impl ops::Mul<f64> for Point3DPitch {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Point3DPitch {
+            onset: self.onset * rhs,
+            chromatic: self.chromatic * rhs,
+            morphetic: self.morphetic * rhs,
+            active_pitch: self.active_pitch,
+        }
+    }
+}
+
+// Traits for by reference arithmetic
+impl ops::Add<&Point3DPitch> for &Point3DPitch {
+    type Output = Point3DPitch;
+
+    fn add(self, rhs: &Point3DPitch) -> Point3DPitch {
+        Point3DPitch {
+            onset: self.onset + rhs.onset,
+            chromatic: self.chromatic + rhs.chromatic,
+            morphetic: self.morphetic + rhs.morphetic,
+            active_pitch: self.active_pitch,
+        }
+    }
+}
+
+impl ops::Sub<&Point3DPitch> for &Point3DPitch {
+    type Output = Point3DPitch;
+
+    fn sub(self, rhs: &Point3DPitch) -> Self::Output {
+        Point3DPitch {
+            onset: self.onset - rhs.onset,
+            chromatic: self.chromatic - rhs.chromatic,
+            morphetic: self.morphetic - rhs.morphetic,
+            active_pitch: self.active_pitch,
+        }
+    }
+}
+
+impl ops::Mul<f64> for &Point3DPitch {
+    type Output = Point3DPitch;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Point3DPitch {
+            onset: self.onset * rhs,
+            chromatic: self.chromatic * rhs,
+            morphetic: self.morphetic * rhs,
+            active_pitch: self.active_pitch,
+        }
+    }
+}
+
+// Comparisons
+impl PartialEq for Point3DPitch {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Clone for Point3DPitch {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl Eq for Point3DPitch {}
+
+impl PartialOrd for Point3DPitch {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Point3DPitch {
+    /// Compares the points lexicographically by the onset, the active pitches, and the
+    /// active components. The onset is compared first, so that point sets stay sorted
+    /// by onset as the algorithms expect.
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.onset < other.onset {
+            return Ordering::Less;
+        }
+
+        if self.onset > other.onset {
+            return Ordering::Greater;
+        }
+
+        let by_active_pitch = self.active_pitch.cmp(&other.active_pitch);
+        if by_active_pitch != Ordering::Equal {
+            return by_active_pitch;
+        }
+
+        for (a, b) in self.active_components().zip(other.active_components()) {
+            if a < b {
+                return Ordering::Less;
+            }
+
+            if a > b {
+                return Ordering::Greater;
+            }
+        }
+
+        Ordering::Equal
+    }
+}
+
+impl Hash for Point3DPitch {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.active_pitch.hash(state);
+        for component in self.active_components() {
+            state.write(&component.to_ne_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "discovery")]
+    use crate::discovery::algorithm::TecAlgorithm;
+    #[cfg(feature = "discovery")]
+    use crate::discovery::siatec::Siatec;
+    #[cfg(feature = "discovery")]
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point;
+    use crate::point_set::point_pitch::{
+        chromatic_pitch, morphetic_pitch, ActivePitch, Point3DPitch,
+    };
+    #[cfg(feature = "discovery")]
+    use crate::point_set::set::PointSet;
+
+    #[test]
+    fn test_spelled_pitches() {
+        assert_eq!(Some(60.0), chromatic_pitch('C', 0, 4));
+        assert_eq!(Some(61.0), chromatic_pitch('c', 1, 4));
+        assert_eq!(Some(21.0), chromatic_pitch('A', 0, 0));
+        assert_eq!(Some(23.0), morphetic_pitch('C', 4));
+        assert_eq!(Some(0.0), morphetic_pitch('A', 0));
+        assert_eq!(Some(22.0), morphetic_pitch('B', 3));
+        assert_eq!(None, morphetic_pitch('H', 3));
+
+        let c_sharp = Point3DPitch::from_spelling(0.0, 'C', 1, 4, ActivePitch::Both).unwrap();
+        let c = Point3DPitch::from_spelling(0.0, 'C', 0, 4, ActivePitch::Both).unwrap();
+        assert_ne!(c, c_sharp);
+        assert_eq!(
+            c.with_active_pitch(ActivePitch::Morphetic),
+            c_sharp.with_active_pitch(ActivePitch::Morphetic)
+        );
+        assert!(!c.with_active_pitch(ActivePitch::Morphetic).is_active(1));
+        assert!((c_sharp - c)
+            .with_active_pitch(ActivePitch::Morphetic)
+            .is_zero());
+    }
+
+    #[test]
+    #[cfg(feature = "discovery")]
+    fn test_diatonic_transpositions_are_translations() {
+        // C-D-E and its sequence a step higher, D-E-F, which is not a chromatic transposition
+        // as the last interval is a semitone instead of a whole tone.
+        let notes = [
+            ('C', 0, 0.0),
+            ('D', 0, 1.0),
+            ('E', 0, 2.0),
+            ('D', 0, 4.0),
+            ('E', 0, 5.0),
+            ('F', 0, 6.0),
+        ];
+        let points: Vec<Point3DPitch> = notes
+            .iter()
+            .map(|(step, alter, onset)| {
+                Point3DPitch::from_spelling(*onset, *step, *alter, 4, ActivePitch::Chromatic)
+                    .unwrap()
+            })
+            .collect();
+        let chromatic = PointSet::new(points);
+        let morphetic = chromatic.with_active_pitch(ActivePitch::Morphetic);
+
+        let is_sequence_found = |point_set: &PointSet<Point3DPitch>| {
+            let motif = Pattern::new(&point_set.into_iter().take(3).collect());
+            Siatec {}
+                .compute_tecs(point_set)
+                .iter()
+                .any(|tec| tec.pattern == motif && !tec.translators.is_empty())
+        };
+        assert!(!is_sequence_found(&chromatic));
+        assert!(is_sequence_found(&morphetic));
+    }
+}
@@ -0,0 +1,271 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::ops;
+
+use num_rational::Rational64;
+use num_traits::{CheckedAdd, CheckedMul, CheckedSub};
+
+use crate::point_set::point::Point;
+
+/// Returns the rational number closest to the value among the convergents of its continued
+/// fraction whose denominators are at most the given maximum, or empty if the value is not
+/// finite or too large. The value is recovered exactly if it is the nearest floating point
+/// number to a fraction with a small denominator, e.g., 1/3.
+///
+/// # Arguments
+///
+/// * `value` - The value to approximate
+/// * `max_denom` - The largest allowed denominator, at least 1
+pub fn approximate(value: f64, max_denom: i64) -> Option<Rational64> {
+    if !value.is_finite() || value.abs() >= i64::MAX as f64 {
+        return None;
+    }
+
+    let max_denom = max_denom.max(1) as i128;
+    // The previous and the current convergent.
+    let (mut numer_0, mut denom_0): (i128, i128) = (1, 0);
+    let (mut numer_1, mut denom_1): (i128, i128) = (value.floor() as i128, 1);
+    let mut remainder = value - value.floor();
+
+    while remainder > f64::EPSILON * value.abs().max(1.0) {
+        let reciprocal = 1.0 / remainder;
+        let term = reciprocal.floor() as i128;
+        let numer_2 = term * numer_1 + numer_0;
+        let denom_2 = term * denom_1 + denom_0;
+        if denom_2 > max_denom || numer_2.abs() > i64::MAX as i128 {
+            break;
+        }
+
+        (numer_0, denom_0) = (numer_1, denom_1);
+        (numer_1, denom_1) = (numer_2, denom_2);
+        remainder = reciprocal - reciprocal.floor();
+    }
+
+    // The convergents are in lowest terms with positive denominators.
+    Some(Rational64::new_raw(
+        i64::try_from(numer_1).ok()?,
+        i64::try_from(denom_1).ok()?,
+    ))
+}
+
+/// Returns the nearest floating point number to the rational number.
+pub fn to_f64(value: &Rational64) -> f64 {
+    *value.numer() as f64 / *value.denom() as f64
+}
+
+/// Returns the result of a checked operation on onsets, or if the operation overflowed,
+/// the approximation of the floating point result, which saturates to the range of i64.
+fn or_approximate(result: Option<Rational64>, value: f64) -> Rational64 {
+    result
+        .or_else(|| approximate(value, Point2DRational::MAX_DENOMINATOR))
+        .unwrap_or_else(|| Rational64::from_integer(value as i64))
+}
+
+/// Represents a 2-dimensional point with a rational onset (the first component) and an integer
+/// pitch (the second component). Unlike `Point2DRf64`, which rounds the onsets, tuplet onsets
+/// are exact, so their comparisons and translations are exact as well.
+///
+/// Scaling by a floating point number (e.g., in `ScalingSiatec`) approximates the factor
+/// as a rational number with a denominator of at most `MAX_DENOMINATOR`, and rounds the pitch.
+/// The arithmetic does not panic: if an exact onset overflows, it is approximated as above
+/// from the floating point result, and a non-finite scaling factor saturates the onset
+/// like a conversion of a float to an integer. The pitches saturate on overflow.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point2DRational {
+    /// The onset time of the point
+    pub onset: Rational64,
+    /// The pitch of the point
+    pub pitch: i64,
+}
+
+impl Point2DRational {
+    /// The largest denominator used for converting floating point numbers to rational numbers.
+    pub const MAX_DENOMINATOR: i64 = 1 << 20;
+}
+
+impl Point for Point2DRational {
+    fn is_zero(&self) -> bool {
+        self.onset == Rational64::ZERO && self.pitch == 0
+    }
+
+    fn component_f64(&self, index: usize) -> Option<f64> {
+        match index {
+            0 => Some(to_f64(&self.onset)),
+            1 => Some(self.pitch as f64),
+            _ => None,
+        }
+    }
+
+    fn with_component_f64(&self, index: usize, value: f64) -> Option<Self> {
+        match index {
+            0 => Some(Point2DRational {
+                onset: approximate(value, Point2DRational::MAX_DENOMINATOR)?,
+                pitch: self.pitch,
+            }),
+            1 => Some(Point2DRational {
+                onset: self.onset,
+                pitch: value.round() as i64,
+            }),
+            _ => None,
+        }
+    }
+
+    fn dimensionality(&self) -> usize {
+        2
+    }
+}
+
+impl ops::Add<Point2DRational> for Point2DRational {
+    type Output = Self;
+
+    fn add(self, rhs: Point2DRational) -> Point2DRational {
+        Point2DRational {
+            onset: or_approximate(
+                self.onset.checked_add(&rhs.onset),
+                to_f64(&self.onset) + to_f64(&rhs.onset),
+            ),
+            pitch: self.pitch.saturating_add(rhs.pitch),
+        }
+    }
+}
+
+impl ops::Sub<Point2DRational> for Point2DRational {
+    type Output = Self;
+
+    fn sub(self, rhs: Point2DRational) -> Point2DRational {
+        Point2DRational {
+            onset: or_approximate(
+                self.onset.checked_sub(&rhs.onset),
+                to_f64(&self.onset) - to_f64(&rhs.onset),
+            ),
+            pitch: self.pitch.saturating_sub(rhs.pitch),
+        }
+    }
+}
+
+impl ops::Mul<f64> for Point2DRational {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Point2DRational {
+        let onset = approximate(rhs, Point2DRational::MAX_DENOMINATOR)
+            .and_then(|factor| self.onset.checked_mul(&factor));
+        Point2DRational {
+            onset: or_approximate(onset, to_f64(&self.onset) * rhs),
+            pitch: (self.pitch as f64 * rhs).round() as i64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_rational::Rational64;
+
+    #[cfg(feature = "discovery")]
+    use crate::discovery::algorithm::TecAlgorithm;
+    #[cfg(feature = "discovery")]
+    use crate::discovery::siatec::Siatec;
+    use crate::point_set::point::Point;
+    use crate::point_set::point_rational::{approximate, to_f64, Point2DRational};
+    #[cfg(feature = "discovery")]
+    use crate::point_set::set::PointSet;
+
+    #[test]
+    fn test_approximate() {
+        let third = Rational64::new(1, 3);
+        assert_eq!(Some(third), approximate(1.0 / 3.0, 1000));
+        assert_eq!(Some(Rational64::new(-7, 5)), approximate(-1.4, 1000));
+        assert_eq!(Some(third), approximate(0.3334, 10));
+        assert_eq!(Some(Rational64::from_integer(3)), approximate(3.0, 10));
+        assert_eq!(None, approximate(f64::NAN, 10));
+        assert_eq!(None, approximate(f64::INFINITY, 10));
+        assert_eq!(1.0 / 3.0, to_f64(&third));
+    }
+
+    #[test]
+    fn test_overflow_is_approximated() {
+        let point = Point2DRational {
+            onset: Rational64::new(1, i64::MAX),
+            pitch: i64::MAX,
+        };
+        let other = Point2DRational {
+            onset: Rational64::new(1, i64::MAX - 1),
+            pitch: 1,
+        };
+        let sum = point + other;
+        assert_eq!(Rational64::ZERO, sum.onset);
+        assert_eq!(i64::MAX, sum.pitch);
+        assert_eq!(Rational64::ZERO, (point - other).onset);
+
+        let point = Point2DRational {
+            onset: Rational64::new(1, 3),
+            pitch: 60,
+        };
+        assert_eq!(Rational64::new(1, 6), (point * 0.5).onset);
+        assert_eq!(
+            Rational64::from_integer(i64::MAX),
+            (point * f64::INFINITY).onset
+        );
+        assert_eq!(Rational64::ZERO, (point * f64::NAN).onset);
+    }
+
+    #[test]
+    fn test_tuplet_onsets_are_exact() {
+        let quintuplet = Point2DRational {
+            onset: Rational64::new(1, 5),
+            pitch: 2,
+        };
+        let mut point = Point2DRational {
+            onset: Rational64::ZERO,
+            pitch: 60,
+        };
+        for _ in 0..5 {
+            point = point + quintuplet;
+        }
+
+        assert_eq!(
+            Point2DRational {
+                onset: Rational64::from_integer(1),
+                pitch: 70
+            },
+            point
+        );
+        assert_eq!(Some(1.0), point.component_f64(0));
+        assert_eq!(
+            Some(Point2DRational {
+                onset: Rational64::new(2, 3),
+                pitch: 70
+            }),
+            point.with_component_f64(0, 2.0 / 3.0)
+        );
+        assert_eq!(Rational64::new(1, 2), (point * 0.5).onset);
+    }
+
+    #[test]
+    #[cfg(feature = "discovery")]
+    fn test_triplet_motif_is_found() {
+        // A triplet motif on the first and the second beat.
+        let mut points = Vec::new();
+        for beat in 0..2 {
+            for (i, pitch) in [60, 62, 64].iter().enumerate() {
+                points.push(Point2DRational {
+                    onset: Rational64::from_integer(beat) + Rational64::new(i as i64, 3),
+                    pitch: *pitch,
+                });
+            }
+        }
+        let point_set = PointSet::new(points);
+
+        let tecs = Siatec {}.compute_tecs(&point_set);
+        let motif = tecs.iter().find(|tec| tec.pattern.len() == 3).unwrap();
+        assert_eq!(
+            vec![Point2DRational {
+                onset: Rational64::from_integer(1),
+                pitch: 0
+            }],
+            motif.translators
+        );
+    }
+}
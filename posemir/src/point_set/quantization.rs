@@ -0,0 +1,217 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use crate::error::{component_f64, PoSemirError};
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::{Point, Point2Di64};
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// Maps points with floating point onsets and pitches onto an integer grid, so that patterns
+/// can be computed with `Point2Di64`, whose comparisons are exact (see `Point2Df64` for the
+/// pitfalls of comparing floating point values).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Quantizer {
+    /// The number of grid steps per time unit, e.g., the number of ticks per quarter note
+    /// when the onsets are in quarter notes.
+    pub onset_resolution: f64,
+    /// The number of grid steps per pitch unit, e.g., 1 for MIDI note numbers.
+    pub pitch_resolution: f64,
+}
+
+impl Quantizer {
+    /// Returns a quantizer with the given onset resolution, which keeps the pitches
+    /// at the resolution of whole numbers.
+    ///
+    /// # Arguments
+    ///
+    /// * `onset_resolution` - The number of grid steps per time unit, e.g., 12 so that
+    ///   both sixteenths and eighth note triplets of quarter note onsets are on the grid
+    pub fn with(onset_resolution: f64) -> Quantizer {
+        Quantizer {
+            onset_resolution,
+            pitch_resolution: 1.0,
+        }
+    }
+
+    /// Returns the point rounded to the nearest point of the grid, where the onset (the first
+    /// component) and the pitch (the second component) are given in grid steps. Returns an
+    /// error if the point does not have both components, or if a component is not finite.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point that is quantized
+    pub fn quantize<T: Point>(&self, point: &T) -> Result<Point2Di64, PoSemirError> {
+        Ok(Point2Di64 {
+            x: quantize_component(component_f64(point, 0)?, self.onset_resolution)?,
+            y: quantize_component(component_f64(point, 1)?, self.pitch_resolution)?,
+        })
+    }
+
+    /// Returns the quantized point set of the points of the given point set.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set that is quantized
+    pub fn quantize_point_set<T: Point>(
+        &self,
+        point_set: &PointSet<T>,
+    ) -> Result<QuantizedPointSet<T>, PoSemirError> {
+        let mut pairs = Vec::with_capacity(point_set.len());
+        for point in point_set {
+            pairs.push((self.quantize(point)?, *point));
+        }
+        // The sort is stable, so the first of the points that are quantized to the same
+        // grid point is kept.
+        pairs.sort_by_key(|pair| pair.0);
+        pairs.dedup_by(|pair, previous| pair.0 == previous.0);
+
+        let (points, originals): (Vec<Point2Di64>, Vec<T>) = pairs.into_iter().unzip();
+        Ok(QuantizedPointSet {
+            point_set: PointSet::from_sorted(points),
+            originals,
+        })
+    }
+}
+
+fn quantize_component(value: f64, resolution: f64) -> Result<i64, PoSemirError> {
+    let steps = (value * resolution).round();
+    if !steps.is_finite() || steps.abs() > i64::MAX as f64 {
+        return Err(PoSemirError::InvalidPointSet(format!(
+            "component {} cannot be quantized with resolution {}",
+            value, resolution
+        )));
+    }
+    Ok(steps as i64)
+}
+
+/// A point set quantized by `Quantizer` together with the original points, so that the
+/// patterns computed from the quantized points can be mapped back to the original points.
+/// If several original points are quantized to the same point, the first of them in the
+/// lexicographical order is kept.
+#[derive(Debug, Clone)]
+pub struct QuantizedPointSet<T: Point> {
+    /// The quantized points.
+    pub point_set: PointSet<Point2Di64>,
+    originals: Vec<T>,
+}
+
+impl<T: Point> QuantizedPointSet<T> {
+    /// Returns the original point of the quantized point, or empty if the point
+    /// is not in the quantized point set.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The quantized point
+    pub fn original(&self, point: &Point2Di64) -> Option<&T> {
+        self.point_set
+            .find_index(point)
+            .ok()
+            .map(|index| &self.originals[index])
+    }
+
+    /// Returns the pattern of the original points of the quantized pattern, or empty
+    /// if some of its points are not in the quantized point set.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The pattern of quantized points
+    pub fn original_pattern(&self, pattern: &Pattern<Point2Di64>) -> Option<Pattern<T>> {
+        let points: Option<Vec<&T>> = pattern.into_iter().map(|p| self.original(p)).collect();
+        points.map(|points| Pattern::new(&points))
+    }
+
+    /// Returns the TEC of the original points of the TEC of quantized points, or empty if
+    /// some of its occurrences are not in the quantized point set. The translators are
+    /// the differences between the first points of the original occurrences, so they are
+    /// exact for occurrences whose original points are exact translations of each other.
+    ///
+    /// # Arguments
+    ///
+    /// * `tec` - The TEC of quantized points
+    pub fn original_tec(&self, tec: &Tec<Point2Di64>) -> Option<Tec<T>> {
+        let pattern = self.original_pattern(&tec.pattern)?;
+        if pattern.is_empty() {
+            return Some(Tec {
+                pattern,
+                translators: Vec::new(),
+            });
+        }
+
+        let first = tec.pattern[0];
+        let mut translators = Vec::with_capacity(tec.translators.len());
+        for translator in &tec.translators {
+            let translated = self.original(&(first + *translator))?;
+            translators.push(*translated - pattern[0]);
+        }
+
+        Some(Tec {
+            pattern,
+            translators,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::point_set::point::{Point2DRf64, Point2Df64, Point2Di64};
+    use crate::point_set::quantization::Quantizer;
+    use crate::point_set::set::PointSet;
+    use crate::point_set::tec::Tec;
+
+    #[test]
+    fn test_quantize_and_map_back() {
+        let third = 1.0 / 3.0;
+        let point_set = PointSet::new(vec![
+            Point2DRf64::new(0.0, 60.0),
+            Point2DRf64::new(third, 62.0),
+            Point2DRf64::new(1.0, 60.0),
+            Point2DRf64::new(1.0 + third, 62.0),
+        ]);
+
+        let quantized = Quantizer::with(12.0)
+            .quantize_point_set(&point_set)
+            .unwrap();
+        assert_eq!(
+            PointSet::new(vec![
+                Point2Di64 { x: 0, y: 60 },
+                Point2Di64 { x: 4, y: 62 },
+                Point2Di64 { x: 12, y: 60 },
+                Point2Di64 { x: 16, y: 62 },
+            ]),
+            quantized.point_set
+        );
+
+        let tec = Tec {
+            pattern: quantized.point_set.get_pattern(&[0, 1]),
+            translators: vec![Point2Di64 { x: 12, y: 0 }],
+        };
+        let original = quantized.original_tec(&tec).unwrap();
+        assert_eq!(point_set.get_pattern(&[0, 1]), original.pattern);
+        assert_eq!(point_set, original.covered_set());
+
+        let missing = Tec {
+            translators: vec![Point2Di64 { x: 1, y: 0 }],
+            ..tec
+        };
+        assert!(quantized.original_tec(&missing).is_none());
+    }
+
+    #[test]
+    fn test_invalid_points() {
+        let quantizer = Quantizer::with(4.0);
+        assert!(quantizer
+            .quantize(&Point2Df64 {
+                x: f64::NAN,
+                y: 60.0
+            })
+            .is_err());
+        assert!(quantizer
+            .quantize(&Point2Df64 {
+                x: f64::MAX,
+                y: 60.0
+            })
+            .is_err());
+    }
+}
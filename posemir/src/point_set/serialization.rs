@@ -0,0 +1,223 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+//! Serde implementations for the types that cannot derive them: point sets are deserialized
+//! through `PointSet::new` so that the points are sorted and unique, rounded points are
+//! deserialized through `Point2DRf64::new` so that the rounded onset matches the raw onset,
+//! and the N-dimensional points are serialized as sequences of N components. Dynamically
+//! dimensioned points are serialized as sequences of their components.
+use std::fmt::Formatter;
+use std::marker::PhantomData;
+
+use serde::de::{Error, SeqAccess, Visitor};
+use serde::ser::SerializeTuple;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::point_set::point::{Point, Point2DRf64};
+use crate::point_set::point_nd::{PointDynf64, PointNdf64, PointNdi64};
+use crate::point_set::set::PointSet;
+
+impl<T: Point + Serialize> Serialize for PointSet<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+impl<'de, T: Point + Deserialize<'de>> Deserialize<'de> for PointSet<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(PointSet::new(Vec::deserialize(deserializer)?))
+    }
+}
+
+/// The serialized form of `Point2DRf64`, which only contains the raw onset.
+#[derive(Serialize, Deserialize)]
+struct RoundedPoint {
+    x: f64,
+    y: f64,
+}
+
+impl Serialize for Point2DRf64 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RoundedPoint {
+            x: self.get_raw_x(),
+            y: self.y,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Point2DRf64 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let point = RoundedPoint::deserialize(deserializer)?;
+        Ok(Point2DRf64::new(point.x, point.y))
+    }
+}
+
+fn serialize_components<S: Serializer, C: Serialize>(
+    components: &[C],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut tuple = serializer.serialize_tuple(components.len())?;
+    for component in components {
+        tuple.serialize_element(component)?;
+    }
+    tuple.end()
+}
+
+/// Deserializes a sequence of exactly N components.
+struct ComponentVisitor<C, const N: usize> {
+    _c: PhantomData<C>,
+}
+
+impl<'de, C: Deserialize<'de> + Copy + Default, const N: usize> Visitor<'de>
+    for ComponentVisitor<C, N>
+{
+    type Value = [C; N];
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        write!(formatter, "a sequence of {} components", N)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut components = [C::default(); N];
+        for (i, component) in components.iter_mut().enumerate() {
+            *component = seq
+                .next_element()?
+                .ok_or_else(|| A::Error::invalid_length(i, &self))?;
+        }
+
+        if seq.next_element::<C>()?.is_some() {
+            return Err(A::Error::invalid_length(N + 1, &self));
+        }
+
+        Ok(components)
+    }
+}
+
+fn deserialize_components<'de, D, C, const N: usize>(deserializer: D) -> Result<[C; N], D::Error>
+where
+    D: Deserializer<'de>,
+    C: Deserialize<'de> + Copy + Default,
+{
+    deserializer.deserialize_tuple(
+        N,
+        ComponentVisitor {
+            _c: Default::default(),
+        },
+    )
+}
+
+impl<const N: usize> Serialize for PointNdf64<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_components(&self.components, serializer)
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for PointNdf64<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(PointNdf64 {
+            components: deserialize_components(deserializer)?,
+        })
+    }
+}
+
+impl<const N: usize> Serialize for PointNdi64<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_components(&self.components, serializer)
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for PointNdi64<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(PointNdi64 {
+            components: deserialize_components(deserializer)?,
+        })
+    }
+}
+
+impl Serialize for PointDynf64 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.components())
+    }
+}
+
+impl<'de> Deserialize<'de> for PointDynf64 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let components = Vec::<f64>::deserialize(deserializer)?;
+        PointDynf64::new(&components).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::point_set::mtp::Mtp;
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::{Point2DRf64, Point2Df64, Point2Di64};
+    use crate::point_set::point_nd::{PointDynf64, PointNdi64};
+    use crate::point_set::set::PointSet;
+    use crate::point_set::tec::Tec;
+
+    #[test]
+    fn test_point_set_round_trip() {
+        let json = "[{\"x\":2.0,\"y\":1.0},{\"x\":1.0,\"y\":1.0},{\"x\":1.0,\"y\":1.0}]";
+        let point_set: PointSet<Point2Df64> = serde_json::from_str(json).unwrap();
+
+        // Deserialized point sets are sorted and contain no duplicates.
+        assert_eq!(
+            PointSet::new(vec![
+                Point2Df64 { x: 1.0, y: 1.0 },
+                Point2Df64 { x: 2.0, y: 1.0 },
+            ]),
+            point_set
+        );
+
+        let serialized = serde_json::to_string(&point_set).unwrap();
+        assert_eq!(
+            point_set,
+            serde_json::from_str::<PointSet<Point2Df64>>(&serialized).unwrap()
+        );
+
+        let rounded = PointSet::new(vec![Point2DRf64::new(1.0 / 3.0, 60.0)]);
+        let serialized = serde_json::to_string(&rounded).unwrap();
+        assert_eq!(
+            rounded[0].get_raw_x(),
+            serde_json::from_str::<PointSet<Point2DRf64>>(&serialized).unwrap()[0].get_raw_x()
+        );
+    }
+
+    #[test]
+    fn test_tec_and_mtp_round_trip() {
+        let tec = Tec {
+            pattern: Pattern::new(&vec![
+                &PointNdi64 {
+                    components: [0, 60, 1],
+                },
+                &PointNdi64 {
+                    components: [1, 62, 1],
+                },
+            ]),
+            translators: vec![PointNdi64 {
+                components: [4, 0, 0],
+            }],
+        };
+        let serialized = serde_json::to_string(&tec).unwrap();
+        assert!(serialized.contains("[0,60,1]"));
+        assert_eq!(tec, serde_json::from_str(&serialized).unwrap());
+
+        let mtp = Mtp {
+            translator: Point2Di64 { x: 1, y: 0 },
+            pattern: Pattern::new(&vec![&Point2Di64 { x: 0, y: 0 }]),
+        };
+        let serialized = serde_json::to_string(&mtp).unwrap();
+        assert_eq!(mtp, serde_json::from_str(&serialized).unwrap());
+
+        assert!(serde_json::from_str::<PointNdi64<3>>("[1, 2]").is_err());
+
+        let point = PointDynf64::new(&[0.0, 60.0]).unwrap();
+        let serialized = serde_json::to_string(&point).unwrap();
+        assert_eq!("[0.0,60.0]", serialized);
+        assert_eq!(point, serde_json::from_str(&serialized).unwrap());
+        assert!(serde_json::from_str::<PointDynf64>("[0, 0, 0, 0, 0, 0, 0, 0, 0]").is_err());
+    }
+}
@@ -4,17 +4,24 @@
  */
 use std::borrow::Borrow;
 use std::cmp::Ordering;
-use std::ops::Index;
+use std::ops::{Index, Range};
 use std::slice;
+use std::sync::Arc;
 
+use crate::error::{component_f64, PoSemirError};
 use crate::point_set::pattern::Pattern;
-use crate::point_set::point::Point;
+use crate::point_set::point::{Point, Point2DWeighted};
+use crate::point_set::point_nd::PointDynf64;
 
 /// Represents a sorted set of points (i.e. vectors).
 /// The points in the set are in lexicographical order.
+///
+/// The points are shared between the clones of a point set and the patterns created from it,
+/// so cloning is cheap. The methods that modify a point set modify its points in place if they
+/// are not shared, and otherwise copy them, so the modifications are not visible to the clones.
 #[derive(Debug, Clone)]
 pub struct PointSet<T: Point> {
-    points: Vec<T>,
+    points: Arc<Vec<T>>,
 }
 
 impl<T: Point> PointSet<T> {
@@ -22,6 +29,7 @@ impl<T: Point> PointSet<T> {
     /// The given points do not have to be in any specific order, they are sorted
     /// when the point set is created. Point sets are sets in the sense that they
     /// do not contain duplicates, that is, duplicate points are removed on creation.
+    /// Use `new_with_multiplicities` to find out which points had duplicates.
     ///
     /// # Arguments
     ///
@@ -30,11 +38,59 @@ impl<T: Point> PointSet<T> {
     pub fn new(mut points: Vec<T>) -> PointSet<T> {
         points.sort();
         points.dedup();
-        PointSet { points }
+        PointSet {
+            points: points.into(),
+        }
     }
 
-    /// Returns and gives ownership of the points from this point set.
+    /// Returns a point set created from the given points as `new`, together with the
+    /// multiplicity of each point of the point set, that is, the number of the given points
+    /// that are equal to the point at the same index. Multiplicities greater than one show
+    /// which points had duplicates, e.g., unisons in different voices, that were removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `points` - A vector of points. The returned point set takes ownership of the points.
+    pub fn new_with_multiplicities(mut points: Vec<T>) -> (PointSet<T>, Vec<usize>) {
+        points.sort();
+
+        let mut unique: Vec<T> = Vec::with_capacity(points.len());
+        let mut multiplicities: Vec<usize> = Vec::with_capacity(points.len());
+        for point in points {
+            match multiplicities.last_mut() {
+                Some(multiplicity) if unique.last() == Some(&point) => *multiplicity += 1,
+                _ => {
+                    unique.push(point);
+                    multiplicities.push(1);
+                }
+            }
+        }
+
+        (
+            PointSet {
+                points: unique.into(),
+            },
+            multiplicities,
+        )
+    }
+
+    /// Returns a point set of points that are already in ascending lexicographical order
+    /// and contain no duplicates, so that no sorting is required.
+    pub(crate) fn from_sorted(points: Vec<T>) -> PointSet<T> {
+        debug_assert!(points.windows(2).all(|pair| pair[0] < pair[1]));
+        PointSet {
+            points: points.into(),
+        }
+    }
+
+    /// Returns the points of this point set. The points are copied only if they are shared
+    /// with clones of this point set or with patterns created from it.
     pub fn points(self) -> Vec<T> {
+        Arc::try_unwrap(self.points).unwrap_or_else(|points| points.to_vec())
+    }
+
+    /// Returns the points of this point set without copying them.
+    pub(crate) fn shared_points(self) -> Arc<Vec<T>> {
         self.points
     }
 
@@ -68,12 +124,12 @@ impl<T: Point> PointSet<T> {
     /// * `translator` - The translator by which the returned point set is translated
     pub fn translate(&self, translator: &T) -> PointSet<T> {
         let mut translated_points = Vec::with_capacity(self.len());
-        for point in &self.points {
+        for point in self.points.iter() {
             translated_points.push(*point + *translator);
         }
 
         PointSet {
-            points: translated_points,
+            points: translated_points.into(),
         }
     }
 
@@ -108,7 +164,7 @@ impl<T: Point> PointSet<T> {
         }
 
         PointSet {
-            points: common_points,
+            points: common_points.into(),
         }
     }
 
@@ -149,18 +205,288 @@ impl<T: Point> PointSet<T> {
             }
         }
 
-        PointSet { points: diff }
+        PointSet {
+            points: diff.into(),
+        }
+    }
+
+    /// Returns the range of indices of the points whose onset (the first component)
+    /// is in the half-open range from start to end. The range is found with binary search,
+    /// as the points are sorted by their onsets.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The smallest onset in the range
+    /// * `end` - The end of the range, which is not included in it
+    pub fn time_range(&self, start: f64, end: f64) -> Range<usize> {
+        let onset = |point: &T| point.component_f64(0).unwrap();
+        let from = self.points.partition_point(|point| onset(point) < start);
+        let to = from + self.points[from..].partition_point(|point| onset(point) < end);
+        from..to
+    }
+
+    /// Returns the point set of the points whose onset (the first component) is in the
+    /// half-open range from start to end, e.g., an excerpt of a piece (see `time_range`).
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The smallest onset in the range
+    /// * `end` - The end of the range, which is not included in it
+    pub fn slice_time(&self, start: f64, end: f64) -> PointSet<T> {
+        PointSet::from_sorted(self.points[self.time_range(start, end)].to_vec())
+    }
+
+    /// Returns the point set of the points whose component at the given dimension is in the
+    /// closed range from min to max, e.g., the points in a pitch range. The points are found
+    /// with binary search for the first dimension, and by scanning for the other dimensions.
+    /// Points that have no component at the dimension are not included.
+    ///
+    /// # Arguments
+    ///
+    /// * `dimension` - The index of the component
+    /// * `min` - The smallest value of the component in the range
+    /// * `max` - The largest value of the component in the range
+    pub fn slice_dimension(&self, dimension: usize, min: f64, max: f64) -> PointSet<T> {
+        if dimension == 0 {
+            let from = self
+                .points
+                .partition_point(|point| point.component_f64(0).unwrap() < min);
+            let to = from
+                + self.points[from..]
+                    .partition_point(|point| point.component_f64(0).unwrap() <= max);
+            return PointSet::from_sorted(self.points[from..to].to_vec());
+        }
+
+        let points = self
+            .points
+            .iter()
+            .filter(|point| match point.component_f64(dimension) {
+                Some(value) => min <= value && value <= max,
+                None => false,
+            })
+            .copied()
+            .collect();
+        PointSet::from_sorted(points)
     }
 
     pub fn find_index(&self, point: &T) -> Result<usize, usize> {
         self.points.binary_search(point)
     }
 
+    /// Returns true if the given point is in this point set.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point that is searched for
+    pub fn contains(&self, point: &T) -> bool {
+        self.find_index(point).is_ok()
+    }
+
+    /// Returns true if all of the points of the given pattern are in this point set,
+    /// that is, if the pattern occurs in this point set exactly as is (without translation).
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The pattern that is searched for
+    pub fn contains_pattern(&self, pattern: &Pattern<T>) -> bool {
+        pattern.into_iter().all(|point| self.contains(point))
+    }
+
+    /// Returns the indices of the points of the given pattern in this point set, in the order
+    /// of the points in the pattern, or empty if any of the points is not in this point set.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The pattern whose points are searched for
+    pub fn find_indices(&self, pattern: &Pattern<T>) -> Option<Vec<usize>> {
+        pattern
+            .into_iter()
+            .map(|point| self.find_index(point).ok())
+            .collect()
+    }
+
+    /// Returns an error if the points of this point set cannot be processed by the
+    /// discovery and search algorithms, that is, if a point has no onset component
+    /// (the first component) or any of the components is NaN or infinite.
+    pub fn validate(&self) -> Result<(), PoSemirError> {
+        validate_points(self)
+    }
+
+    /// Returns the point set of the points projected to the given dimensions in the given
+    /// order, e.g., `project(&[0, 2])` keeps the onset and the pitch of points that have the
+    /// onset, the duration, the pitch and the velocity as their components. Points that are
+    /// equal after the projection are merged. Returns an error if a point has no component
+    /// at one of the dimensions, or if there are more than `MAX_DYNAMIC_DIMENSIONALITY`
+    /// dimensions.
+    ///
+    /// # Arguments
+    ///
+    /// * `dimensions` - The indices of the components that are kept
+    pub fn project(&self, dimensions: &[usize]) -> Result<PointSet<PointDynf64>, PoSemirError> {
+        let mut components = Vec::with_capacity(dimensions.len());
+        let mut projected = Vec::with_capacity(self.len());
+        for point in self.points.iter() {
+            components.clear();
+            for dimension in dimensions {
+                components.push(component_f64(point, *dimension)?);
+            }
+            projected.push(PointDynf64::new(&components)?);
+        }
+
+        Ok(PointSet::new(projected))
+    }
+
+    /// Returns the union of this point set and the given point set.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set whose points are added to the points of this point set
     pub fn union(&self, point_set: &PointSet<T>) -> PointSet<T> {
-        let mut points = self.points.clone();
-        points.append(&mut point_set.points.clone());
+        let mut union = self.clone();
+        union.union_with(point_set);
+        union
+    }
+
+    /// Returns the symmetric difference of this point set and the other point set (all points
+    /// that are in exactly one of the point sets).
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The point set with which the symmetric difference is computed
+    pub fn symmetric_difference(&self, other: &PointSet<T>) -> PointSet<T> {
+        let mut points = Vec::new();
 
-        PointSet::new(points)
+        let mut i = 0;
+        let mut j = 0;
+
+        while i < self.len() && j < other.len() {
+            match self[i].cmp(&other[j]) {
+                Ordering::Equal => {
+                    i += 1;
+                    j += 1;
+                }
+                Ordering::Less => {
+                    points.push(self[i]);
+                    i += 1;
+                }
+                Ordering::Greater => {
+                    points.push(other[j]);
+                    j += 1;
+                }
+            }
+        }
+
+        points.extend_from_slice(&self.points[i..]);
+        points.extend_from_slice(&other.points[j..]);
+
+        PointSet {
+            points: points.into(),
+        }
+    }
+
+    /// Adds the points of the given point set to this point set, so that this point set
+    /// becomes the union of the point sets (see `union`).
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set whose points are added to this point set
+    pub fn union_with(&mut self, point_set: &PointSet<T>) {
+        let points = Arc::make_mut(&mut self.points);
+        points.extend_from_slice(&point_set.points);
+        // The points consist of two sorted runs, which the stable sort merges in linear time.
+        points.sort();
+        points.dedup();
+    }
+
+    /// Removes the points that are not in the other point set from this point set, so that
+    /// this point set becomes the intersection of the point sets (see `intersect`).
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The point set with which this point set is intersected
+    pub fn intersect_with(&mut self, other: &PointSet<T>) {
+        let mut others = other.points.iter().peekable();
+        self.retain(|point| {
+            while others.next_if(|other_point| *other_point < point).is_some() {}
+            others.peek() == Some(&point)
+        });
+    }
+
+    /// Removes the points of the other point set from this point set, so that this point
+    /// set becomes the difference of the point sets (see `difference`).
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The point set whose points are removed from this point set
+    pub fn difference_with(&mut self, other: &PointSet<T>) {
+        let mut others = other.points.iter().peekable();
+        self.retain(|point| {
+            while others.next_if(|other_point| *other_point < point).is_some() {}
+            others.peek() != Some(&point)
+        });
+    }
+
+    /// Keeps only the points for which the predicate returns true. The points are removed in
+    /// place if they are not shared, and otherwise the kept points are copied.
+    fn retain(&mut self, mut predicate: impl FnMut(&T) -> bool) {
+        match Arc::get_mut(&mut self.points) {
+            Some(points) => points.retain(|point| predicate(point)),
+            None => {
+                let points: Vec<T> = self
+                    .points
+                    .iter()
+                    .filter(|point| predicate(point))
+                    .copied()
+                    .collect();
+                self.points = points.into();
+            }
+        }
+    }
+}
+
+/// Returns an error if any of the points has no onset component or has a component that
+/// is NaN or infinite.
+pub(crate) fn validate_points<'a, T: Point + 'a>(
+    points: impl IntoIterator<Item = &'a T>,
+) -> Result<(), PoSemirError> {
+    for point in points {
+        component_f64(point, 0)?;
+        for i in 0..point.dimensionality() {
+            let component = component_f64(point, i)?;
+            if !component.is_finite() {
+                return Err(PoSemirError::InvalidPointSet(format!(
+                    "component {} of a point is {}",
+                    i, component
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl PointSet<Point2DWeighted> {
+    /// Returns a point set created from the given weighted points as `new`, but instead of
+    /// keeping only the weight of one of the duplicate points, the weights of the duplicates
+    /// are summed. With unit weights the weights of the points are their multiplicities.
+    ///
+    /// # Arguments
+    ///
+    /// * `points` - A vector of points. The returned point set takes ownership of the points.
+    pub fn new_with_summed_weights(mut points: Vec<Point2DWeighted>) -> PointSet<Point2DWeighted> {
+        points.sort();
+
+        let mut unique: Vec<Point2DWeighted> = Vec::with_capacity(points.len());
+        for point in points {
+            match unique.last_mut() {
+                Some(last) if *last == point => last.weight += point.weight,
+                _ => unique.push(point),
+            }
+        }
+
+        PointSet {
+            points: unique.into(),
+        }
     }
 }
 
@@ -189,9 +515,127 @@ impl<T: Point> PartialEq for PointSet<T> {
 
 #[cfg(test)]
 mod tests {
-    use crate::point_set::point::Point2Df64;
+    use crate::error::PoSemirError;
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::{Point, Point2DWeighted, Point2Df64};
+    use crate::point_set::point_nd::PointNdf64;
     use crate::point_set::set::PointSet;
 
+    #[test]
+    fn test_validate() {
+        let valid = PointSet::new(vec![
+            Point2Df64 { x: 0.0, y: 1.0 },
+            Point2Df64 { x: 1.0, y: 2.0 },
+        ]);
+        assert!(valid.validate().is_ok());
+
+        let invalid = PointSet::new(vec![
+            Point2Df64 { x: 0.0, y: 1.0 },
+            Point2Df64 {
+                x: 1.0,
+                y: f64::NAN,
+            },
+        ]);
+        assert!(matches!(
+            invalid.validate(),
+            Err(PoSemirError::InvalidPointSet(_))
+        ));
+    }
+
+    #[test]
+    fn test_slice_time_and_dimension() {
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 0.0, y: 60.0 },
+            Point2Df64 { x: 1.0, y: 72.0 },
+            Point2Df64 { x: 1.0, y: 48.0 },
+            Point2Df64 { x: 2.0, y: 64.0 },
+            Point2Df64 { x: 3.0, y: 55.0 },
+        ]);
+
+        assert_eq!(1..4, point_set.time_range(0.5, 3.0));
+        assert_eq!(
+            PointSet::new(vec![
+                Point2Df64 { x: 1.0, y: 48.0 },
+                Point2Df64 { x: 1.0, y: 72.0 },
+                Point2Df64 { x: 2.0, y: 64.0 },
+            ]),
+            point_set.slice_time(0.5, 3.0)
+        );
+        assert!(point_set.slice_time(4.0, 8.0).is_empty());
+        assert_eq!(
+            point_set.slice_time(1.0, 3.0),
+            point_set.slice_dimension(0, 1.0, 2.0)
+        );
+
+        assert_eq!(
+            PointSet::new(vec![
+                Point2Df64 { x: 0.0, y: 60.0 },
+                Point2Df64 { x: 2.0, y: 64.0 },
+                Point2Df64 { x: 3.0, y: 55.0 },
+            ]),
+            point_set.slice_dimension(1, 55.0, 64.0)
+        );
+        assert!(point_set.slice_dimension(2, 0.0, 100.0).is_empty());
+    }
+
+    #[test]
+    fn test_project() {
+        let point_set = PointSet::new(vec![
+            PointNdf64 {
+                components: [0.0, 0.5, 60.0, 80.0],
+            },
+            PointNdf64 {
+                components: [0.0, 1.0, 60.0, 90.0],
+            },
+            PointNdf64 {
+                components: [1.0, 1.0, 62.0, 80.0],
+            },
+        ]);
+
+        let projected = point_set.project(&[0, 2]).unwrap();
+        assert_eq!(2, projected.len());
+        assert_eq!(2, projected[0].dimensionality());
+        assert_eq!(&[1.0, 62.0], projected[1].components());
+
+        let reordered = point_set.project(&[3, 0]).unwrap();
+        assert_eq!(&[80.0, 0.0], reordered[0].components());
+        assert!(matches!(
+            point_set.project(&[4]),
+            Err(PoSemirError::MissingComponent(4))
+        ));
+        assert!(point_set.project(&[0; 9]).is_err());
+    }
+
+    #[test]
+    fn test_multiplicities() {
+        let a = Point2Df64 { x: 0.0, y: 60.0 };
+        let b = Point2Df64 { x: 1.0, y: 62.0 };
+        let (point_set, multiplicities) = PointSet::new_with_multiplicities(vec![b, a, b, b]);
+        assert_eq!(PointSet::new(vec![a, b]), point_set);
+        assert_eq!(vec![1, 3], multiplicities);
+
+        let weighted = PointSet::new_with_summed_weights(vec![
+            Point2DWeighted {
+                x: 1.0,
+                y: 62.0,
+                weight: 1.0,
+            },
+            Point2DWeighted {
+                x: 0.0,
+                y: 60.0,
+                weight: 1.0,
+            },
+            Point2DWeighted {
+                x: 1.0,
+                y: 62.0,
+                weight: 0.5,
+            },
+        ]);
+        assert_eq!(2, weighted.len());
+        assert_eq!(1.0, weighted[0].weight());
+        assert_eq!(1.5, weighted[1].weight());
+    }
+
     #[test]
     fn test_constructor_and_access() {
         let points = vec![
@@ -247,6 +691,30 @@ mod tests {
         assert_eq!(sorted_points[3], pattern[1]);
     }
 
+    #[test]
+    fn test_pattern_membership() {
+        let a = Point2Df64 { x: 0.0, y: 1.0 };
+        let b = Point2Df64 { x: 1.0, y: 0.0 };
+        let c = Point2Df64 { x: 2.0, y: 3.0 };
+        let point_set = PointSet::new(vec![c, a, b]);
+
+        assert!(point_set.contains(&b));
+        assert!(!point_set.contains(&Point2Df64 { x: 1.0, y: 1.0 }));
+
+        let pattern = Pattern::new(&vec![&c, &a]);
+        assert!(point_set.contains_pattern(&pattern));
+        assert_eq!(Some(vec![2, 0]), point_set.find_indices(&pattern));
+
+        let translated = pattern.translate(&Point2Df64 { x: 1.0, y: 0.0 });
+        assert!(!point_set.contains_pattern(&translated));
+        assert_eq!(None, point_set.find_indices(&translated));
+
+        assert_eq!(
+            PointSet::new(vec![a, c]),
+            point_set.get_pattern(&[0, 2]).to_point_set()
+        );
+    }
+
     #[test]
     fn test_intersect() {
         let points = vec![
@@ -287,4 +755,56 @@ mod tests {
         assert_eq!(Point2Df64 { x: 1.0, y: 1.0 }, diff[0]);
         assert_eq!(Point2Df64 { x: 4.0, y: 2.0 }, diff[1]);
     }
+
+    #[test]
+    fn test_set_algebra() {
+        let a = Point2Df64 { x: 1.0, y: 1.0 };
+        let b = Point2Df64 { x: 2.0, y: 1.0 };
+        let c = Point2Df64 { x: 3.0, y: 2.0 };
+        let d = Point2Df64 { x: 4.0, y: 2.0 };
+        let point_set_a = PointSet::new(vec![a, b, c]);
+        let point_set_b = PointSet::new(vec![b, c, d]);
+
+        let union = PointSet::new(vec![a, b, c, d]);
+        assert_eq!(union, point_set_a.union(&point_set_b));
+        assert_eq!(
+            PointSet::new(vec![a, d]),
+            point_set_a.symmetric_difference(&point_set_b)
+        );
+
+        let mut in_place = point_set_a.clone();
+        in_place.union_with(&point_set_b);
+        assert_eq!(union, in_place);
+
+        let mut in_place = point_set_a.clone();
+        in_place.intersect_with(&point_set_b);
+        assert_eq!(point_set_a.intersect(&point_set_b), in_place);
+
+        let mut in_place = point_set_a.clone();
+        in_place.difference_with(&point_set_b);
+        assert_eq!(point_set_a.difference(&point_set_b), in_place);
+
+        in_place.difference_with(&point_set_a);
+        assert!(in_place.is_empty());
+    }
+
+    #[test]
+    fn test_in_place_operations_without_copying() {
+        let a = Point2Df64 { x: 1.0, y: 1.0 };
+        let b = Point2Df64 { x: 2.0, y: 1.0 };
+        let c = Point2Df64 { x: 3.0, y: 2.0 };
+        let mut point_set = PointSet::new(vec![a, b, c]);
+        let shared = point_set.clone();
+
+        // The shared points are copied, so the clone is not modified.
+        point_set.difference_with(&PointSet::new(vec![a]));
+        assert_eq!(PointSet::new(vec![a, b, c]), shared);
+
+        // The points that are no longer shared are modified in place.
+        let address = &point_set[0] as *const Point2Df64;
+        point_set.difference_with(&PointSet::new(vec![b]));
+        point_set.union_with(&PointSet::new(vec![a]));
+        assert_eq!(PointSet::new(vec![a, c]), point_set);
+        assert_eq!(address, &point_set.points()[0] as *const Point2Df64);
+    }
 }
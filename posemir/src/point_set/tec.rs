@@ -2,7 +2,15 @@
  * (c) Otso Björklund (2021)
  * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
  */
-use crate::point_set::pattern::Pattern;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::error::{component_f64, PoSemirError};
+use crate::point_set::mtp::Mtp;
+use crate::point_set::pattern::{reduce_pitch_class, Pattern};
 use crate::point_set::point::Point;
 use crate::point_set::set::PointSet;
 
@@ -11,7 +19,14 @@ use crate::point_set::set::PointSet;
 /// TECs are represented as a pattern and the translators by which it can be translated
 /// to produce all of the translationally equivalent occurrences. The translators do *not* contain
 /// the zero vector.
+///
+/// Equality, ordering and hashing of TECs are defined by their canonical form (see `canonical`),
+/// so TECs that differ only in the order of their points or translators are considered equal.
+///
+/// Clones of a TEC share the points of its pattern (see `Pattern`), so only the translators
+/// are copied when a TEC is cloned.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tec<T: Point> {
     pub pattern: Pattern<T>,
     pub translators: Vec<T>,
@@ -21,30 +36,388 @@ impl<T: Point> Tec<T> {
     /// Returns the expansion of this TEC.
     ///
     /// The TEC is expanded by creating all translated copies of the pattern.
+    /// For TECs with many translators, `occurrences_iter` avoids keeping all of
+    /// the copies in memory.
     pub fn expand(&self) -> Vec<Pattern<T>> {
-        let mut occurrences = Vec::with_capacity(self.translators.len() + 1);
-        occurrences.push(self.pattern.clone());
+        self.occurrences_iter().map(Cow::into_owned).collect()
+    }
 
-        for translator in &self.translators {
-            occurrences.push(self.pattern.translate(translator));
-        }
+    /// Returns an iterator over the occurrences of the pattern of this TEC in the same
+    /// order as `expand`. The pattern itself is borrowed and each translated copy is
+    /// created only when the iterator reaches it.
+    pub fn occurrences_iter(&self) -> impl Iterator<Item = Cow<'_, Pattern<T>>> + '_ {
+        std::iter::once(Cow::Borrowed(&self.pattern)).chain(
+            self.translators
+                .iter()
+                .map(|translator| Cow::Owned(self.pattern.translate(translator))),
+        )
+    }
 
-        occurrences
+    /// Returns one MTP for each translator of this TEC. Each returned MTP consists of
+    /// the pattern of this TEC and one of its translators. Note that the patterns are not
+    /// necessarily maximal for their translators, unless the TEC was computed for an MTP.
+    pub fn mtps(&self) -> Vec<Mtp<T>> {
+        self.translators
+            .iter()
+            .map(|translator| Mtp {
+                translator: *translator,
+                pattern: self.pattern.clone(),
+            })
+            .collect()
     }
 
     /// Returns the set of points covered by this TEC.
     pub fn covered_set(&self) -> PointSet<T> {
-        let expanded = self.expand();
-        let mut points = Vec::new();
-        for pattern in &expanded {
-            for point in pattern {
-                points.push(*point);
-            }
+        let mut points = Vec::with_capacity(self.pattern.len() * self.occurrence_count());
+        for occurrence in self.occurrences_iter() {
+            points.extend(occurrence.as_ref());
         }
 
         PointSet::new(points)
     }
 
+    /// Returns the number of occurrences of the pattern in this TEC, including the pattern itself.
+    pub fn occurrence_count(&self) -> usize {
+        self.translators.len() + 1
+    }
+
+    /// Returns the number of distinct points covered by this TEC.
+    pub fn coverage_size(&self) -> usize {
+        let mut points = Vec::with_capacity(self.pattern.len() * self.occurrence_count());
+        for point in &self.pattern {
+            points.push(*point);
+            for translator in &self.translators {
+                points.push(*point + *translator);
+            }
+        }
+
+        points.sort();
+        points.dedup();
+        points.len()
+    }
+
+    /// Returns the fraction of the points in the given point set that are covered by this TEC.
+    /// Only points that belong to the given point set are counted, so the ratio is at most 1.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set in which this TEC occurs
+    pub fn coverage_ratio(&self, point_set: &PointSet<T>) -> f64 {
+        if point_set.is_empty() {
+            return 0.0;
+        }
+
+        let covered = self.covered_set().intersect(point_set);
+        covered.len() as f64 / point_set.len() as f64
+    }
+
+    /// Returns the time span of the pattern of this TEC, that is, the difference between
+    /// the largest and the smallest onset (first component) in the pattern.
+    pub fn time_span(&self) -> f64 {
+        self.pattern.time_span()
+    }
+
+    /// Returns the overlap between the points covered by this TEC and the other TEC.
+    /// Only points that belong to the given point set are taken into account.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The TEC with which the overlap is computed
+    /// * `point_set` - The point set in which the TECs occur
+    pub fn overlap_with(&self, other: &Tec<T>, point_set: &PointSet<T>) -> TecOverlap<T> {
+        let covered = self.covered_set().intersect(point_set);
+        let other_covered = other.covered_set().intersect(point_set);
+        TecOverlap::of(&covered, &other_covered)
+    }
+
+    /// Returns the pairwise overlap fractions of the given TECs as a matrix, where the
+    /// element at `[i][j]` is the overlap fraction of `tecs[i]` and `tecs[j]`
+    /// (see `TecOverlap`). The matrix is symmetric.
+    ///
+    /// # Arguments
+    ///
+    /// * `tecs` - The TECs for which the overlaps are computed
+    /// * `point_set` - The point set in which the TECs occur
+    pub fn overlap_matrix(tecs: &[Tec<T>], point_set: &PointSet<T>) -> Vec<Vec<f64>> {
+        let covered_sets: Vec<PointSet<T>> = tecs
+            .iter()
+            .map(|tec| tec.covered_set().intersect(point_set))
+            .collect();
+
+        let n = tecs.len();
+        let mut matrix = vec![vec![0.0; n]; n];
+        for i in 0..n {
+            for j in i..n {
+                let fraction = TecOverlap::of(&covered_sets[i], &covered_sets[j]).fraction;
+                matrix[i][j] = fraction;
+                matrix[j][i] = fraction;
+            }
+        }
+
+        matrix
+    }
+
+    /// Returns the number of points shared by each pair of occurrences of this TEC and the
+    /// other TEC (see `expand`), where the element at `[i][j]` is the number of points shared
+    /// by the occurrence i of this TEC and the occurrence j of the other TEC.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The TEC with whose occurrences the overlaps are computed
+    pub fn occurrence_overlaps(&self, other: &Tec<T>) -> Vec<Vec<usize>> {
+        let other_occurrences: Vec<PointSet<T>> = other
+            .occurrences_iter()
+            .map(|occurrence| occurrence.to_point_set())
+            .collect();
+
+        self.occurrences_iter()
+            .map(|occurrence| {
+                let occurrence = occurrence.to_point_set();
+                other_occurrences
+                    .iter()
+                    .map(|other_occurrence| occurrence.intersect(other_occurrence).len())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Merges the TECs whose covered sets are similar. A TEC is merged into the first
+    /// earlier merged TEC whose covered points differ from its own by less than the threshold,
+    /// measured as the Jaccard distance (one minus the overlap fraction, see `TecOverlap`).
+    /// Of the merged TECs, the one that covers the most points is kept. The kept TECs are
+    /// in the order of the first TEC merged into each of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `tecs` - The TECs to merge
+    /// * `point_set` - The point set in which the TECs occur
+    /// * `threshold` - The Jaccard distance below which TECs are merged
+    pub fn merge_overlapping(
+        tecs: &[Tec<T>],
+        point_set: &PointSet<T>,
+        threshold: f64,
+    ) -> Vec<Tec<T>> {
+        let mut merged: Vec<(&Tec<T>, PointSet<T>)> = Vec::new();
+
+        for tec in tecs {
+            let covered = tec.covered_set().intersect(point_set);
+            let similar = merged.iter_mut().find(|(_, merged_covered)| {
+                1.0 - TecOverlap::of(merged_covered, &covered).fraction < threshold
+            });
+
+            match similar {
+                Some(entry) => {
+                    if covered.len() > entry.1.len() {
+                        *entry = (tec, covered);
+                    }
+                }
+                None => merged.push((tec, covered)),
+            }
+        }
+
+        merged.into_iter().map(|(tec, _)| tec.clone()).collect()
+    }
+
+    /// Returns this TEC without the points covered by the other TEC, or None if all
+    /// occurrences of this TEC overlap the other TEC at every point. The pattern of the
+    /// returned TEC consists of the points of this TEC's pattern none of whose translated
+    /// copies are covered by the other TEC, and its translators are the translators of this
+    /// TEC, so the returned TEC covers a subset of the points covered only by this TEC.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The TEC whose covered points are removed from this TEC
+    pub fn subtract(&self, other: &Tec<T>) -> Option<Tec<T>> {
+        let other_covered = other.covered_set();
+        let points: Vec<&T> = self
+            .pattern
+            .into_iter()
+            .filter(|point| {
+                !other_covered.contains(point)
+                    && self
+                        .translators
+                        .iter()
+                        .all(|translator| !other_covered.contains(&(**point + *translator)))
+            })
+            .collect();
+
+        if points.is_empty() {
+            return None;
+        }
+
+        Some(Tec {
+            pattern: Pattern::new(&points),
+            translators: self.translators.clone(),
+        })
+    }
+
+    /// Splits this TEC into sub-TECs by the value of the component at the given index,
+    /// e.g., by voice or instrument. The pattern of each returned TEC consists of the points of
+    /// this TEC's pattern that share a value for the component, and the translators are those of
+    /// this TEC. The returned TECs are in ascending order of the component value. Returns an
+    /// error if the points have no component at the given index.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the component by which the TEC is split
+    pub fn split_by_component(&self, index: usize) -> Result<Vec<Tec<T>>, PoSemirError> {
+        let mut points: Vec<(f64, &T)> = self
+            .pattern
+            .into_iter()
+            .map(|p| Ok((component_f64(p, index)?, p)))
+            .collect::<Result<_, PoSemirError>>()?;
+        // Stable sort keeps the points of each sub-pattern in their original order.
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut split_tecs = Vec::new();
+        let mut i = 0;
+        while i < points.len() {
+            let value = points[i].0;
+            let mut sub_pattern = Vec::new();
+
+            let mut j = i;
+            while j < points.len() && points[j].0 == value {
+                sub_pattern.push(points[j].1);
+                j += 1;
+            }
+
+            i = j;
+            split_tecs.push(Tec {
+                pattern: Pattern::new(&sub_pattern),
+                translators: self.translators.clone(),
+            });
+        }
+
+        Ok(split_tecs)
+    }
+
+    /// Returns the canonical form of this TEC. In the canonical form the points of the pattern
+    /// are in lexicographical order, and the translators are in lexicographical order and
+    /// contain no duplicates or zero vectors.
+    pub fn canonical(&self) -> Tec<T> {
+        let mut translators = self.translators.clone();
+        translators.sort();
+        translators.dedup();
+        translators.retain(|translator| !translator.is_zero());
+
+        Tec {
+            pattern: self.pattern.to_point_set().into(),
+            translators,
+        }
+    }
+
+    /// Returns the octave-reduced (pitch class) version of this TEC in canonical form.
+    /// The pattern is reduced as in `Pattern::pitch_class_reduced`, and the pitch components
+    /// of the translators are reduced modulo 12, so that TECs whose occurrences differ only by octave
+    /// transpositions are merged. Note that translation in the reduced pitch dimension is modular:
+    /// the pitches of the expanded occurrences have to be reduced modulo 12 again.
+    pub fn pitch_class_reduced(&self) -> Tec<T> {
+        Tec {
+            pattern: self.pattern.pitch_class_reduced(),
+            translators: self.translators.iter().map(reduce_pitch_class).collect(),
+        }
+        .canonical()
+    }
+
+    /// Returns true if this TEC is in canonical form (see `canonical`).
+    pub fn is_canonical(&self) -> bool {
+        let pattern_sorted = (1..self.pattern.len()).all(|i| self.pattern[i - 1] < self.pattern[i]);
+        let translators_sorted = self.translators.windows(2).all(|w| w[0] < w[1]);
+
+        pattern_sorted && translators_sorted && !self.translators.iter().any(|t| t.is_zero())
+    }
+
+    fn as_canonical(&self) -> Cow<'_, Tec<T>> {
+        if self.is_canonical() {
+            Cow::Borrowed(self)
+        } else {
+            Cow::Owned(self.canonical())
+        }
+    }
+
+    /// Returns true if the pattern of this TEC is translationally equivalent to the pattern
+    /// of the other TEC, that is, the vectorized representations of the patterns are equal.
+    /// TECs that are translationally equivalent in the same point set have the same occurrences.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The TEC with which this TEC is compared
+    pub fn translationally_equivalent(&self, other: &Tec<T>) -> bool {
+        self.pattern.len() == other.pattern.len()
+            && self.sorted_pattern().vectorize() == other.sorted_pattern().vectorize()
+    }
+
+    /// Returns a hash of the vectorized representation of the pattern of this TEC.
+    /// Translationally equivalent TECs have equal hashes, so the hash can be used for
+    /// finding duplicates, e.g., in the outputs of different algorithms.
+    pub fn translation_invariant_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.sorted_pattern().vectorize().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Merges the translationally equivalent TECs in the given TECs, so that each class
+    /// of translationally equivalent patterns is represented by one TEC. The translators of
+    /// merged TECs are unioned, and the pattern of the merged TEC is the lexicographically first
+    /// occurrence, so that all of the translators are positive.
+    /// The merged TECs are in the order of the first occurrence of each class in the given TECs.
+    ///
+    /// # Arguments
+    ///
+    /// * `tecs` - The TECs to merge
+    pub fn merge_translationally_equivalent(tecs: &[Tec<T>]) -> Vec<Tec<T>> {
+        let mut class_indices: HashMap<Pattern<T>, usize> = HashMap::new();
+        // The sorted pattern and the starting points of the occurrences for each class.
+        let mut classes: Vec<(Pattern<T>, Vec<T>)> = Vec::new();
+
+        for tec in tecs {
+            let pattern = tec.sorted_pattern();
+            if pattern.is_empty() {
+                continue;
+            }
+
+            let first = pattern[0];
+            let index = *class_indices.entry(pattern.vectorize()).or_insert_with(|| {
+                classes.push((pattern.clone().into_owned(), Vec::new()));
+                classes.len() - 1
+            });
+
+            let class_origin = classes[index].0[0];
+            let origins = &mut classes[index].1;
+            origins.push(first);
+            for translator in &tec.translators {
+                origins.push(first + *translator);
+            }
+            // The class pattern may be a translated copy of this TEC's pattern.
+            if first != class_origin {
+                origins.push(class_origin);
+            }
+        }
+
+        classes
+            .into_iter()
+            .map(|(pattern, mut origins)| {
+                origins.sort();
+                origins.dedup();
+
+                let base = origins[0];
+                let pattern_origin = pattern[0];
+                Tec {
+                    pattern: pattern.translate(&(base - pattern_origin)),
+                    translators: origins[1..].iter().map(|o| *o - base).collect(),
+                }
+            })
+            .collect()
+    }
+
+    pub(crate) fn sorted_pattern(&self) -> Cow<'_, Pattern<T>> {
+        if (1..self.pattern.len()).all(|i| self.pattern[i - 1] < self.pattern[i]) {
+            Cow::Borrowed(&self.pattern)
+        } else {
+            Cow::Owned(self.pattern.to_point_set().into())
+        }
+    }
+
     /// Returns the conjugate TEC of this TEC (see [Meredith2013]).
     pub fn conjugate(&self) -> Tec<T> {
         let first = self.pattern[0];
@@ -104,18 +477,75 @@ impl<T: Point> Tec<T> {
     }
 }
 
+/// Represents the overlap of the points covered by two TECs.
+#[derive(Debug, Clone)]
+pub struct TecOverlap<T: Point> {
+    /// The points covered by both TECs.
+    pub shared: PointSet<T>,
+    /// The number of shared points divided by the number of points covered by either
+    /// of the TECs (Jaccard index). Zero if neither TEC covers any points.
+    pub fraction: f64,
+}
+
+impl<T: Point> TecOverlap<T> {
+    fn of(covered: &PointSet<T>, other_covered: &PointSet<T>) -> TecOverlap<T> {
+        let shared = covered.intersect(other_covered);
+        let union_size = covered.len() + other_covered.len() - shared.len();
+        let fraction = if union_size == 0 {
+            0.0
+        } else {
+            shared.len() as f64 / union_size as f64
+        };
+
+        TecOverlap { shared, fraction }
+    }
+}
+
 impl<T: Point> PartialEq for Tec<T> {
     fn eq(&self, other: &Self) -> bool {
-        self.translators == other.translators && self.pattern == other.pattern
+        self.cmp(other) == Ordering::Equal
     }
 }
 
 impl<T: Point> Eq for Tec<T> {}
 
+impl<T: Point> PartialOrd for Tec<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Point> Ord for Tec<T> {
+    /// Compares the canonical forms of the TECs: first lexicographically by the patterns,
+    /// and then lexicographically by the translators.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let a = self.as_canonical();
+        let b = other.as_canonical();
+
+        a.pattern
+            .cmp(&b.pattern)
+            .then_with(|| a.translators.cmp(&b.translators))
+    }
+}
+
+impl<T: Point> Hash for Tec<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let canonical = self.as_canonical();
+        canonical.pattern.hash(state);
+        canonical.translators.hash(state);
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::borrow::Cow;
+    use std::collections::HashSet;
+
+    use crate::error::PoSemirError;
+    use crate::point_set::mtp::Mtp;
     use crate::point_set::pattern::Pattern;
     use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
     use crate::point_set::tec::Tec;
 
     #[test]
@@ -140,6 +570,328 @@ mod tests {
         assert_eq!(Point2Df64 { x: 3.0, y: 1.0 }, cov[4]);
     }
 
+    #[test]
+    fn test_occurrences_iter() {
+        let pattern = Pattern::new(&vec![
+            &Point2Df64 { x: 1.0, y: 0.0 },
+            &Point2Df64 { x: 2.0, y: 0.0 },
+        ]);
+        let tec = Tec {
+            pattern,
+            translators: vec![Point2Df64 { x: 1.0, y: 0.0 }, Point2Df64 { x: 1.0, y: 1.0 }],
+        };
+
+        let mut occurrences = tec.occurrences_iter();
+        assert!(matches!(occurrences.next(), Some(Cow::Borrowed(p)) if *p == tec.pattern));
+        assert_eq!(
+            tec.expand()[1..],
+            occurrences.map(Cow::into_owned).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_coverage_and_span() {
+        let pattern = Pattern::new(&vec![
+            &Point2Df64 { x: 1.0, y: 0.0 },
+            &Point2Df64 { x: 2.5, y: 0.0 },
+        ]);
+        let translators = vec![Point2Df64 { x: 1.5, y: 0.0 }, Point2Df64 { x: 1.0, y: 1.0 }];
+        let tec = Tec {
+            pattern,
+            translators,
+        };
+        let point_set = tec.covered_set().union(&PointSet::new(vec![
+            Point2Df64 { x: 0.0, y: 0.0 },
+            Point2Df64 { x: 5.0, y: 0.0 },
+        ]));
+
+        assert_eq!(3, tec.occurrence_count());
+        assert_eq!(5, tec.coverage_size());
+        assert_eq!(tec.covered_set().len(), tec.coverage_size());
+        assert_eq!(5.0 / 7.0, tec.coverage_ratio(&point_set));
+        assert_eq!(1.5, tec.time_span());
+    }
+
+    #[test]
+    fn test_coverage_ratio_outside_point_set() {
+        let a = Point2Df64 { x: 1.0, y: 0.0 };
+        let b = Point2Df64 { x: 2.0, y: 0.0 };
+        let tec = Tec {
+            pattern: Pattern::new(&vec![&a, &b]),
+            translators: vec![Point2Df64 { x: 2.0, y: 0.0 }],
+        };
+
+        // The translated occurrence lies outside of the point set.
+        let point_set = PointSet::new(vec![a, b]);
+        assert_eq!(4, tec.coverage_size());
+        assert_eq!(1.0, tec.coverage_ratio(&point_set));
+
+        let point_set = PointSet::new(vec![a, Point2Df64 { x: 3.0, y: 0.0 }]);
+        assert_eq!(1.0, tec.coverage_ratio(&point_set));
+        assert_eq!(0.0, tec.coverage_ratio(&PointSet::new(vec![])));
+    }
+
+    #[test]
+    fn test_overlap() {
+        let a = Point2Df64 { x: 1.0, y: 0.0 };
+        let b = Point2Df64 { x: 2.0, y: 0.0 };
+        let c = Point2Df64 { x: 3.0, y: 0.0 };
+        let d = Point2Df64 { x: 4.0, y: 0.0 };
+        let point_set = PointSet::new(vec![a, b, c, d]);
+
+        let tec_a = Tec {
+            pattern: Pattern::new(&vec![&a, &b]),
+            translators: vec![Point2Df64 { x: 1.0, y: 0.0 }],
+        };
+        let tec_b = Tec {
+            pattern: Pattern::new(&vec![&c]),
+            translators: vec![Point2Df64 { x: 1.0, y: 0.0 }],
+        };
+
+        let overlap = tec_a.overlap_with(&tec_b, &point_set);
+        assert_eq!(PointSet::new(vec![c]), overlap.shared);
+        assert_eq!(0.25, overlap.fraction);
+
+        let matrix = Tec::overlap_matrix(&[tec_a, tec_b], &point_set);
+        assert_eq!(vec![vec![1.0, 0.25], vec![0.25, 1.0]], matrix);
+    }
+
+    #[test]
+    fn test_occurrence_overlaps_and_subtract() {
+        let a = Point2Df64 { x: 1.0, y: 0.0 };
+        let b = Point2Df64 { x: 2.0, y: 0.0 };
+        let c = Point2Df64 { x: 3.0, y: 0.0 };
+        let step = Point2Df64 { x: 2.0, y: 0.0 };
+
+        let tec_a = Tec {
+            pattern: Pattern::new(&vec![&a, &b]),
+            translators: vec![step],
+        };
+        let tec_b = Tec {
+            pattern: Pattern::new(&vec![&c]),
+            translators: vec![],
+        };
+
+        // The occurrences of tec_a are {a, b} and {c, d}.
+        assert_eq!(vec![vec![0], vec![1]], tec_a.occurrence_overlaps(&tec_b));
+
+        let expected = Tec {
+            pattern: Pattern::new(&vec![&b]),
+            translators: vec![step],
+        };
+        assert_eq!(Some(expected), tec_a.subtract(&tec_b));
+        assert_eq!(None, tec_b.subtract(&tec_a));
+    }
+
+    #[test]
+    fn test_merge_overlapping() {
+        let points: Vec<Point2Df64> = (0..5)
+            .map(|i| Point2Df64 {
+                x: i as f64,
+                y: 0.0,
+            })
+            .collect();
+        let point_set = PointSet::new(points.clone());
+        let step = Point2Df64 { x: 1.0, y: 0.0 };
+
+        // Covers points 0..4.
+        let small = Tec {
+            pattern: Pattern::new(&vec![&points[0], &points[1], &points[2]]),
+            translators: vec![step],
+        };
+        // Covers points 0..5.
+        let large = Tec {
+            pattern: Pattern::new(&vec![&points[0], &points[1], &points[2], &points[3]]),
+            translators: vec![step],
+        };
+        // Covers points 0 and 4.
+        let distant = Tec {
+            pattern: Pattern::new(&vec![&points[0]]),
+            translators: vec![Point2Df64 { x: 4.0, y: 0.0 }],
+        };
+
+        let tecs = [small.clone(), large.clone(), distant.clone()];
+        assert_eq!(
+            vec![large, distant],
+            Tec::merge_overlapping(&tecs, &point_set, 0.25)
+        );
+        assert_eq!(
+            tecs.to_vec(),
+            Tec::merge_overlapping(&tecs, &point_set, 0.1)
+        );
+    }
+
+    #[test]
+    fn test_canonical_ordering() {
+        let a = Point2Df64 { x: 1.0, y: 0.0 };
+        let b = Point2Df64 { x: 2.0, y: 0.0 };
+        let t_a = Point2Df64 { x: 1.0, y: 0.0 };
+        let t_b = Point2Df64 { x: 3.0, y: 0.0 };
+
+        let tec = Tec {
+            pattern: Pattern::new(&vec![&b, &a]),
+            translators: vec![t_b, Point2Df64 { x: 0.0, y: 0.0 }, t_a, t_b],
+        };
+        assert!(!tec.is_canonical());
+
+        let canonical = tec.canonical();
+        assert!(canonical.is_canonical());
+        assert_eq!(Pattern::new(&vec![&a, &b]), canonical.pattern);
+        assert_eq!(vec![t_a, t_b], canonical.translators);
+        assert_eq!(tec, canonical);
+
+        let other = Tec {
+            pattern: Pattern::new(&vec![&a, &b]),
+            translators: vec![t_a],
+        };
+        assert!(other < tec);
+
+        let set: HashSet<Tec<Point2Df64>> = vec![tec, canonical, other].into_iter().collect();
+        assert_eq!(2, set.len());
+    }
+
+    #[test]
+    fn test_pitch_class_reduced() {
+        let a = Point2Df64 { x: 0.0, y: 60.0 };
+        let b = Point2Df64 { x: 1.0, y: 64.0 };
+
+        let tec = Tec {
+            pattern: Pattern::new(&vec![&a, &b]),
+            translators: vec![
+                Point2Df64 { x: 4.0, y: 12.0 },
+                Point2Df64 { x: 4.0, y: -12.0 },
+                Point2Df64 { x: 0.0, y: 24.0 },
+                Point2Df64 { x: 8.0, y: 7.0 },
+            ],
+        };
+
+        let reduced = tec.pitch_class_reduced();
+        assert_eq!(
+            Pattern::new(&vec![
+                &Point2Df64 { x: 0.0, y: 0.0 },
+                &Point2Df64 { x: 1.0, y: 4.0 }
+            ]),
+            reduced.pattern
+        );
+        assert_eq!(
+            vec![Point2Df64 { x: 4.0, y: 0.0 }, Point2Df64 { x: 8.0, y: 7.0 }],
+            reduced.translators
+        );
+    }
+
+    #[test]
+    fn test_split_by_component() {
+        let a = Point2Df64 { x: 1.0, y: 1.0 };
+        let b = Point2Df64 { x: 2.0, y: 0.0 };
+        let c = Point2Df64 { x: 3.0, y: 1.0 };
+        let translators = vec![Point2Df64 { x: 4.0, y: 0.0 }];
+        let tec = Tec {
+            pattern: Pattern::new(&vec![&a, &b, &c]),
+            translators: translators.clone(),
+        };
+
+        let split = tec.split_by_component(1).unwrap();
+        assert_eq!(2, split.len());
+        assert_eq!(Pattern::new(&vec![&b]), split[0].pattern);
+        assert_eq!(Pattern::new(&vec![&a, &c]), split[1].pattern);
+        assert_eq!(translators, split[1].translators);
+
+        assert!(matches!(
+            tec.split_by_component(2),
+            Err(PoSemirError::MissingComponent(2))
+        ));
+    }
+
+    #[test]
+    fn test_translational_equivalence() {
+        let a = Point2Df64 { x: 1.0, y: 0.0 };
+        let b = Point2Df64 { x: 2.0, y: 1.0 };
+        let tec = Tec {
+            pattern: Pattern::new(&vec![&a, &b]),
+            translators: vec![Point2Df64 { x: 2.0, y: 2.0 }],
+        };
+
+        let translator = Point2Df64 { x: 2.0, y: 2.0 };
+        let translated = Tec {
+            pattern: Pattern::new(&vec![&(b + translator), &(a + translator)]),
+            translators: vec![Point2Df64 { x: -2.0, y: -2.0 }],
+        };
+        let other = Tec {
+            pattern: Pattern::new(&vec![&a, &(b + translator)]),
+            translators: vec![],
+        };
+
+        assert!(tec.translationally_equivalent(&translated));
+        assert!(!tec.translationally_equivalent(&other));
+        assert_eq!(
+            tec.translation_invariant_hash(),
+            translated.translation_invariant_hash()
+        );
+        assert_ne!(
+            tec.translation_invariant_hash(),
+            other.translation_invariant_hash()
+        );
+    }
+
+    #[test]
+    fn test_merge_translationally_equivalent() {
+        let a = Point2Df64 { x: 1.0, y: 0.0 };
+        let b = Point2Df64 { x: 2.0, y: 1.0 };
+        let t = Point2Df64 { x: 2.0, y: 0.0 };
+        let u = Point2Df64 { x: 4.0, y: 0.0 };
+
+        let tec_a = Tec {
+            pattern: Pattern::new(&vec![&(a + t), &(b + t)]),
+            translators: vec![u],
+        };
+        let tec_b = Tec {
+            pattern: Pattern::new(&vec![&a, &b]),
+            translators: vec![t],
+        };
+        let other = Tec {
+            pattern: Pattern::new(&vec![&a]),
+            translators: vec![t],
+        };
+
+        let merged = Tec::merge_translationally_equivalent(&[tec_a.clone(), other.clone(), tec_b]);
+        assert_eq!(2, merged.len());
+        assert_eq!(Pattern::new(&vec![&a, &b]), merged[0].pattern);
+        assert_eq!(vec![t, t + u], merged[0].translators);
+        assert_eq!(
+            tec_a.covered_set().union(&merged[0].covered_set()),
+            merged[0].covered_set()
+        );
+        assert_eq!(other, merged[1]);
+    }
+
+    #[test]
+    fn test_mtps() {
+        let a = Point2Df64 { x: 1.0, y: 0.0 };
+        let b = Point2Df64 { x: 2.0, y: 0.0 };
+        let t_a = Point2Df64 { x: 1.0, y: 0.0 };
+        let t_b = Point2Df64 { x: 1.0, y: 1.0 };
+        let tec = Tec {
+            pattern: Pattern::new(&vec![&a, &b]),
+            translators: vec![t_a, t_b],
+        };
+
+        let mtps = tec.mtps();
+        assert_eq!(
+            vec![
+                Mtp {
+                    translator: t_a,
+                    pattern: Pattern::new(&vec![&a, &b])
+                },
+                Mtp {
+                    translator: t_b,
+                    pattern: Pattern::new(&vec![&a, &b])
+                }
+            ],
+            mtps
+        );
+        assert_eq!(tec.translators[0..1], mtps[0].to_tec().translators);
+    }
+
     #[test]
     fn test_conjugate() {
         let a = Point2Df64 { x: 1.0, y: 1.0 };
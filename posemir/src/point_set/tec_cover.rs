@@ -0,0 +1,249 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// Represents a set of TECs over a point set (e.g., the output of a pattern discovery algorithm
+/// for a piece of music). The cover is indexed so that it can efficiently be queried for the
+/// TECs that cover a given point, the TECs that are active in a time range, and the parts
+/// of the point set not covered by any TEC.
+///
+/// Time is assumed to be the first component of the points.
+#[derive(Debug, Clone)]
+pub struct TecCover<T: Point> {
+    point_set: PointSet<T>,
+    tecs: Vec<Tec<T>>,
+    /// The indices of the TECs covering each point of the point set.
+    point_index: Vec<Vec<usize>>,
+    /// The time intervals of all occurrences sorted by the start of the interval.
+    intervals: Vec<OccurrenceInterval>,
+}
+
+#[derive(Debug, Clone)]
+struct OccurrenceInterval {
+    start: f64,
+    end: f64,
+    tec_index: usize,
+}
+
+impl<T: Point> TecCover<T> {
+    /// Returns a new cover of the given point set by the given TECs.
+    ///
+    /// # Arguments
+    ///
+    /// * `point_set` - The point set that is covered by the TECs
+    /// * `tecs` - The TECs that cover the point set
+    pub fn new(point_set: PointSet<T>, tecs: Vec<Tec<T>>) -> TecCover<T> {
+        let mut point_index = vec![Vec::new(); point_set.len()];
+        let mut intervals = Vec::new();
+
+        for (tec_index, tec) in tecs.iter().enumerate() {
+            for occurrence in tec.occurrences_iter() {
+                let mut start = f64::MAX;
+                let mut end = f64::MIN;
+
+                for point in occurrence.as_ref() {
+                    let onset = point.component_f64(0).unwrap();
+                    start = start.min(onset);
+                    end = end.max(onset);
+
+                    if let Ok(i) = point_set.find_index(point) {
+                        point_index[i].push(tec_index);
+                    }
+                }
+
+                if !occurrence.is_empty() {
+                    intervals.push(OccurrenceInterval {
+                        start,
+                        end,
+                        tec_index,
+                    });
+                }
+            }
+        }
+
+        for tec_indices in &mut point_index {
+            tec_indices.dedup();
+        }
+        intervals.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+        TecCover {
+            point_set,
+            tecs,
+            point_index,
+            intervals,
+        }
+    }
+
+    /// Returns the TECs in this cover.
+    pub fn tecs(&self) -> &[Tec<T>] {
+        &self.tecs
+    }
+
+    /// Returns the point set covered by the TECs.
+    pub fn point_set(&self) -> &PointSet<T> {
+        &self.point_set
+    }
+
+    /// Returns the indices of the TECs that cover the given point. The returned slice is empty
+    /// if the point is not covered or if it does not belong to the point set.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point for which the covering TECs are returned
+    pub fn indices_covering(&self, point: &T) -> &[usize] {
+        match self.point_set.find_index(point) {
+            Ok(i) => &self.point_index[i],
+            Err(_) => &[],
+        }
+    }
+
+    /// Returns the TECs that cover the given point.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point for which the covering TECs are returned
+    pub fn tecs_covering(&self, point: &T) -> Vec<&Tec<T>> {
+        self.indices_covering(point)
+            .iter()
+            .map(|i| &self.tecs[*i])
+            .collect()
+    }
+
+    /// Returns the points in the point set that are not covered by any of the TECs.
+    pub fn uncovered(&self) -> PointSet<T> {
+        let mut points = Vec::new();
+        for (i, point) in self.point_set.into_iter().enumerate() {
+            if self.point_index[i].is_empty() {
+                points.push(*point);
+            }
+        }
+
+        PointSet::new(points)
+    }
+
+    /// Returns the time intervals within the time span of the point set during which no
+    /// occurrence of any TEC is active. An occurrence is active from its first onset
+    /// to its last onset. The intervals are returned in ascending order as (start, end) pairs.
+    pub fn coverage_gaps(&self) -> Vec<(f64, f64)> {
+        let mut gaps = Vec::new();
+        if self.point_set.is_empty() {
+            return gaps;
+        }
+
+        let first_onset = self.point_set[0].component_f64(0).unwrap();
+        let last_onset = self.point_set[self.point_set.len() - 1]
+            .component_f64(0)
+            .unwrap();
+
+        let mut covered_until = first_onset;
+        for interval in &self.intervals {
+            if interval.start > covered_until {
+                gaps.push((covered_until, interval.start.min(last_onset)));
+            }
+            covered_until = covered_until.max(interval.end);
+
+            if covered_until >= last_onset {
+                break;
+            }
+        }
+
+        if covered_until < last_onset {
+            gaps.push((covered_until, last_onset));
+        }
+
+        gaps.retain(|gap| gap.0 < gap.1);
+        gaps
+    }
+
+    /// Returns the indices of the TECs that have at least one occurrence active within the given
+    /// time range (inclusive). The indices are returned in ascending order.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The start of the time range
+    /// * `end` - The end of the time range
+    pub fn active_in(&self, start: f64, end: f64) -> Vec<usize> {
+        // Only the occurrences that start before the end of the range can be active.
+        let candidate_count = self.intervals.partition_point(|i| i.start <= end);
+
+        let mut active: Vec<usize> = self.intervals[..candidate_count]
+            .iter()
+            .filter(|interval| interval.end >= start)
+            .map(|interval| interval.tec_index)
+            .collect();
+
+        active.sort();
+        active.dedup();
+        active
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+    use crate::point_set::tec::Tec;
+    use crate::point_set::tec_cover::TecCover;
+
+    fn test_cover() -> TecCover<Point2Df64> {
+        let points = vec![
+            Point2Df64 { x: 0.0, y: 60.0 },
+            Point2Df64 { x: 1.0, y: 62.0 },
+            Point2Df64 { x: 2.0, y: 60.0 },
+            Point2Df64 { x: 3.0, y: 62.0 },
+            Point2Df64 { x: 4.0, y: 70.0 },
+            Point2Df64 { x: 8.0, y: 60.0 },
+            Point2Df64 { x: 9.0, y: 62.0 },
+        ];
+        let tec_a = Tec {
+            pattern: Pattern::new(&vec![&points[0], &points[1]]),
+            translators: vec![Point2Df64 { x: 2.0, y: 0.0 }, Point2Df64 { x: 8.0, y: 0.0 }],
+        };
+        let tec_b = Tec {
+            pattern: Pattern::new(&vec![&points[1], &points[2]]),
+            translators: vec![],
+        };
+
+        TecCover::new(PointSet::new(points), vec![tec_a, tec_b])
+    }
+
+    #[test]
+    fn test_point_queries() {
+        let cover = test_cover();
+
+        assert_eq!(
+            &[0, 1],
+            cover.indices_covering(&Point2Df64 { x: 1.0, y: 62.0 })
+        );
+        assert_eq!(
+            &[0],
+            cover.indices_covering(&Point2Df64 { x: 9.0, y: 62.0 })
+        );
+        assert!(cover
+            .indices_covering(&Point2Df64 { x: 4.0, y: 70.0 })
+            .is_empty());
+        assert!(cover
+            .tecs_covering(&Point2Df64 { x: 4.5, y: 70.0 })
+            .is_empty());
+        assert_eq!(
+            PointSet::new(vec![Point2Df64 { x: 4.0, y: 70.0 }]),
+            cover.uncovered()
+        );
+    }
+
+    #[test]
+    fn test_time_queries() {
+        let cover = test_cover();
+
+        assert_eq!(vec![(3.0, 8.0)], cover.coverage_gaps());
+        assert_eq!(vec![0, 1], cover.active_in(1.5, 2.5));
+        assert_eq!(vec![0], cover.active_in(2.5, 2.5));
+        assert!(cover.active_in(4.0, 7.0).is_empty());
+        assert_eq!(vec![0], cover.active_in(7.0, 20.0));
+    }
+}
@@ -0,0 +1,117 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+
+/// Represents an equivalence class of a pattern under time-scaling and translation.
+/// Each occurrence of the pattern is produced by first scaling the onsets (the first components)
+/// of the points of the pattern by a scale factor and then translating the scaled pattern by
+/// a translator (see `transform`). This makes it possible to represent, e.g., augmented and
+/// diminished restatements of a pattern. The transformations do *not* contain the identity,
+/// that is, the zero vector with the scale factor 1.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TecWithScaling<T: Point> {
+    pub pattern: Pattern<T>,
+    /// The (translator, scale factor) pairs that produce the occurrences of the pattern.
+    pub transformations: Vec<(T, f64)>,
+}
+
+impl<T: Point> TecWithScaling<T> {
+    /// Returns the pattern with the onsets of its points scaled by the scale factor and then
+    /// translated by the translator.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The pattern that is transformed
+    /// * `translator` - The translator applied after scaling
+    /// * `scale` - The scale factor of the onsets
+    pub fn transform(pattern: &Pattern<T>, translator: &T, scale: f64) -> Pattern<T> {
+        let points: Vec<T> = pattern
+            .into_iter()
+            .map(|point| scale_onset(point, scale) + *translator)
+            .collect();
+        Pattern::new(&points.iter().collect())
+    }
+
+    /// Returns the expansion of this TEC, that is, the pattern and all of its transformed
+    /// occurrences.
+    pub fn expand(&self) -> Vec<Pattern<T>> {
+        let mut occurrences = Vec::with_capacity(self.transformations.len() + 1);
+        occurrences.push(self.pattern.clone());
+
+        for (translator, scale) in &self.transformations {
+            occurrences.push(TecWithScaling::transform(&self.pattern, translator, *scale));
+        }
+
+        occurrences
+    }
+
+    /// Returns the number of occurrences of the pattern in this TEC, including the pattern itself.
+    pub fn occurrence_count(&self) -> usize {
+        self.transformations.len() + 1
+    }
+
+    /// Returns the set of points covered by this TEC.
+    pub fn covered_set(&self) -> PointSet<T> {
+        let points = self
+            .expand()
+            .iter()
+            .flat_map(|occurrence| occurrence.into_iter().copied())
+            .collect();
+        PointSet::new(points)
+    }
+}
+
+/// Returns the point with its onset (the first component) multiplied by the scale factor,
+/// or the point as is if it has no onset.
+pub(crate) fn scale_onset<T: Point>(point: &T, scale: f64) -> T {
+    point
+        .component_f64(0)
+        .and_then(|onset| point.with_component_f64(0, onset * scale))
+        .unwrap_or(*point)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::tec_with_scaling::TecWithScaling;
+
+    #[test]
+    fn test_expand() {
+        let pattern = Pattern::new(&vec![
+            &Point2Df64 { x: 1.0, y: 60.0 },
+            &Point2Df64 { x: 2.0, y: 62.0 },
+        ]);
+        let tec = TecWithScaling {
+            pattern: pattern.clone(),
+            transformations: vec![
+                (Point2Df64 { x: 2.0, y: 0.0 }, 1.0),
+                (Point2Df64 { x: 8.0, y: 5.0 }, 2.0),
+            ],
+        };
+
+        let expanded = tec.expand();
+        assert_eq!(3, tec.occurrence_count());
+        assert_eq!(pattern, expanded[0]);
+        assert_eq!(
+            Pattern::new(&vec![
+                &Point2Df64 { x: 3.0, y: 60.0 },
+                &Point2Df64 { x: 4.0, y: 62.0 },
+            ]),
+            expanded[1]
+        );
+        assert_eq!(
+            Pattern::new(&vec![
+                &Point2Df64 { x: 10.0, y: 65.0 },
+                &Point2Df64 { x: 12.0, y: 67.0 },
+            ]),
+            expanded[2]
+        );
+        assert_eq!(6, tec.covered_set().len());
+    }
+}
@@ -0,0 +1,106 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use crate::point_set::point::Point;
+use crate::point_set::point_duration::DURATION_COMPONENT;
+
+/// How the pattern matchers compare the durations of the query points and the points of
+/// their matches. The duration is the third component of the points, e.g., the duration
+/// of a `Point3DDuration`. Points that have no third component, e.g., `Point2Df64`, are
+/// matched as with `Ignore`.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum DurationMatching {
+    /// The durations are not compared, so matches depend only on the onsets and the pitches.
+    #[default]
+    Ignore,
+    /// A query point matches only points with an equal duration.
+    Equal,
+    /// A query point matches only points whose duration differs from the duration of the
+    /// query point by at most the tolerance.
+    Tolerance(f64),
+}
+
+impl DurationMatching {
+    /// Returns true if the durations of the query point and the point match.
+    ///
+    /// # Arguments
+    ///
+    /// * `query_point` - The point of the query
+    /// * `point` - The point of the point-set to which the query point is matched
+    pub fn matches<T: Point>(&self, query_point: &T, point: &T) -> bool {
+        let tolerance = match self {
+            DurationMatching::Ignore => return true,
+            DurationMatching::Equal => 0.0,
+            DurationMatching::Tolerance(tolerance) => *tolerance,
+        };
+
+        match (
+            query_point.component_f64(DURATION_COMPONENT),
+            point.component_f64(DURATION_COMPONENT),
+        ) {
+            (Some(a), Some(b)) => (a - b).abs() <= tolerance,
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point_duration::Point3DDuration;
+    use crate::point_set::set::PointSet;
+    use crate::search::duration::DurationMatching;
+    use crate::search::exact_matcher::ExactMatcher;
+    use crate::search::partial_matcher::PartialMatcher;
+    use crate::search::pattern_matcher::PatternMatcher;
+
+    fn note(onset: f64, pitch: f64, duration: f64) -> Point3DDuration {
+        Point3DDuration {
+            onset,
+            pitch,
+            duration,
+        }
+    }
+
+    #[test]
+    fn test_match_durations() {
+        // The motif occurs three times: with equal durations, with the last note slightly
+        // shorter, and with the last note staccato.
+        let point_set = PointSet::new(vec![
+            note(0.0, 60.0, 1.0),
+            note(1.0, 62.0, 1.0),
+            note(2.0, 64.0, 2.0),
+            note(4.0, 60.0, 1.0),
+            note(5.0, 62.0, 1.0),
+            note(6.0, 64.0, 1.75),
+            note(8.0, 60.0, 1.0),
+            note(9.0, 62.0, 1.0),
+            note(10.0, 64.0, 0.25),
+        ]);
+        let query = Pattern::new(&vec![
+            &note(0.0, 60.0, 1.0),
+            &note(1.0, 62.0, 1.0),
+            &note(2.0, 64.0, 2.0),
+        ]);
+
+        let exact = |durations| ExactMatcher { durations }.find_indices(&query, &point_set);
+        assert_eq!(3, exact(DurationMatching::Ignore).len());
+        assert_eq!(vec![vec![0, 1, 2]], exact(DurationMatching::Equal));
+        assert_eq!(
+            vec![vec![0, 1, 2], vec![3, 4, 5]],
+            exact(DurationMatching::Tolerance(0.25))
+        );
+
+        let partial = |durations| {
+            PartialMatcher {
+                min_match_size: 2,
+                durations,
+            }
+            .find_indices(&query, &point_set)
+        };
+        let mut matches = partial(DurationMatching::Equal);
+        matches.sort();
+        assert_eq!(vec![vec![0, 1, 2], vec![3, 4], vec![6, 7]], matches);
+    }
+}
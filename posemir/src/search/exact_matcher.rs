@@ -5,20 +5,30 @@
 use crate::point_set::pattern::Pattern;
 use crate::point_set::point::Point;
 use crate::point_set::set::PointSet;
+use crate::search::duration::DurationMatching;
 use crate::search::pattern_matcher::PatternMatcher;
 
 /// Implements a pattern matcher that finds all translationally equivalent occurrences of a pattern
 /// from a point-set. Based on the exact matching algorithm presented in
 /// [Ukkonen2003] for the problem P1.
-pub struct ExactMatcher {}
+#[derive(Debug, Default)]
+pub struct ExactMatcher {
+    /// How the durations of the matching points are compared, see `DurationMatching`.
+    pub durations: DurationMatching,
+}
 
 impl<T: Point> PatternMatcher<T> for ExactMatcher {
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "exact_matcher", skip_all, fields(m = query.len(), n = point_set.len())))]
     fn find_indices_with_callback(
         &self,
         query: &Pattern<T>,
         point_set: &PointSet<T>,
         mut on_output: impl FnMut(Vec<usize>),
     ) {
+        if query.is_empty() || query.len() > point_set.len() {
+            return;
+        }
+
         for i in 0..(point_set.len() - query.len() + 1) {
             let mut candidate = Vec::with_capacity(query.len());
             let translator = point_set[i] - query[0];
@@ -33,7 +43,11 @@ impl<T: Point> PatternMatcher<T> for ExactMatcher {
             {
                 let translated_query_point = query[query_index] + translator;
 
-                if point_set[scan_index] == translated_query_point {
+                if point_set[scan_index] == translated_query_point
+                    && self
+                        .durations
+                        .matches(&query[query_index], &point_set[scan_index])
+                {
                     candidate.push(scan_index);
                 }
 
@@ -55,8 +69,10 @@ impl<T: Point> PatternMatcher<T> for ExactMatcher {
 mod tests {
     use super::ExactMatcher;
     use super::PatternMatcher;
+    use crate::error::PoSemirError;
     use crate::point_set::pattern::Pattern;
     use crate::point_set::point::Point2Df64;
+    use crate::point_set::point_nd::PointNdf64;
     use crate::point_set::set::PointSet;
 
     fn test_point_set() -> PointSet<Point2Df64> {
@@ -86,7 +102,7 @@ mod tests {
             &Point2Df64 { x: 0.875, y: 72.0 },
         ];
         let pattern = Pattern::new(&pattern_points);
-        let matcher = ExactMatcher {};
+        let matcher = ExactMatcher::default();
         let indices = matcher.find_indices(&pattern, &point_set);
 
         assert_eq!(2, indices.len());
@@ -115,6 +131,25 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_given_intervals_of_pattern_in_set_then_matches_found() {
+        let point_set = test_point_set();
+        let intervals = Pattern::new(&vec![
+            &Point2Df64 { x: 0.25, y: 2.0 },
+            &Point2Df64 { x: 0.25, y: -2.0 },
+            &Point2Df64 { x: 0.375, y: 0.0 },
+        ]);
+        let matcher = ExactMatcher::default();
+
+        assert_eq!(
+            vec![vec![0, 1, 2, 3], vec![4, 6, 8, 9]],
+            matcher.find_indices_by_intervals(&intervals, &point_set)
+        );
+        assert!(matcher
+            .find_indices_by_intervals(&Pattern::new(&vec![]), &point_set)
+            .is_empty());
+    }
+
     #[test]
     fn test_given_pattern_not_in_set_then_no_matches_found() {
         let point_set = test_point_set();
@@ -124,9 +159,63 @@ mod tests {
             &Point2Df64 { x: 0.375, y: 72.0 },
         ];
         let pattern = Pattern::new(&pattern_points);
-        let matcher = ExactMatcher {};
+        let matcher = ExactMatcher::default();
 
         assert!(matcher.find_indices(&pattern, &point_set).is_empty());
         assert!(matcher.find_occurrences(&pattern, &point_set).is_empty());
     }
+
+    #[test]
+    fn test_given_invalid_query_then_no_panic() {
+        let point_set = PointSet::new(vec![Point2Df64 { x: 0.0, y: 72.0 }]);
+        let matcher = ExactMatcher::default();
+
+        let longer_query = Pattern::new(&vec![
+            &Point2Df64 { x: 0.0, y: 72.0 },
+            &Point2Df64 { x: 1.0, y: 72.0 },
+        ]);
+        assert!(matcher.find_indices(&longer_query, &point_set).is_empty());
+        assert!(matcher
+            .find_indices(&Pattern::new(&vec![]), &point_set)
+            .is_empty());
+
+        let nan_query = Pattern::new(&vec![&Point2Df64 {
+            x: 0.0,
+            y: f64::NAN,
+        }]);
+        assert!(matches!(
+            matcher.try_find_occurrences(&nan_query, &point_set),
+            Err(PoSemirError::InvalidPointSet(_))
+        ));
+    }
+
+    #[test]
+    fn test_given_n_dimensional_pattern_in_set_then_matches_found() {
+        // The third component is the voice: the upper notes are in voice 0 and the lower in voice 1.
+        let to_voiced = |p: &Point2Df64| PointNdf64 {
+            components: [p.x, p.y, if p.y >= 60.0 { 0.0 } else { 1.0 }],
+        };
+        let point_set = PointSet::new(test_point_set().into_iter().map(to_voiced).collect());
+        let pattern_points = vec![
+            &PointNdf64 {
+                components: [0.0, 72.0, 0.0],
+            },
+            &PointNdf64 {
+                components: [0.25, 74.0, 0.0],
+            },
+            &PointNdf64 {
+                components: [0.5, 72.0, 0.0],
+            },
+            &PointNdf64 {
+                components: [0.875, 72.0, 0.0],
+            },
+        ];
+        let pattern = Pattern::new(&pattern_points);
+        let matcher = ExactMatcher::default();
+        let indices = matcher.find_indices(&pattern, &point_set);
+
+        assert_eq!(2, indices.len());
+        assert_eq!(vec![0, 1, 2, 3], indices[0]);
+        assert_eq!(vec![4, 6, 8, 9], indices[1]);
+    }
 }
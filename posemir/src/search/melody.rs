@@ -0,0 +1,222 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::collections::HashMap;
+
+use crate::point_set::index::KdTree;
+use crate::point_set::point::Point2Df64;
+use crate::point_set::set::PointSet;
+use crate::search::tolerant_matcher::TolerantMatcher;
+
+/// A match of a melodic query found by `MelodySearch`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MelodyMatch {
+    /// The index of the matched point in the point-set for each note of the query,
+    /// or None if the note was not matched.
+    pub alignment: Vec<Option<usize>>,
+    /// The translation from the query to the match: the time shift and the transposition.
+    pub translator: Point2Df64,
+    /// The score of the match between 0 and 1, where 1 is an exact match of the whole query.
+    pub score: f64,
+}
+
+impl MelodyMatch {
+    /// Returns the indices of the matched points in ascending order.
+    pub fn indices(&self) -> Vec<usize> {
+        let mut indices: Vec<usize> = self.alignment.iter().flatten().copied().collect();
+        indices.sort_unstable();
+        indices
+    }
+
+    /// Returns the number of matched notes of the query.
+    pub fn matched_notes(&self) -> usize {
+        self.alignment.iter().flatten().count()
+    }
+}
+
+/// Fuzzy search of a monophonic melody, e.g., a sung or hummed query, in a point-set of
+/// onset-pitch points.
+///
+/// The search is transposition invariant and the query can start at any time, i.e., the matches
+/// are translations of the query. Each note of the query is matched to the nearest point with
+/// the same (transposed) pitch whose onset differs by at most the onset tolerance from the
+/// translated onset of the note, as in `TolerantMatcher`. Notes without such a point are left
+/// unmatched, so that partial matches with at least `min_match_size` matched notes are found.
+///
+/// Each matched note contributes `1 - d / (2 * onset_tolerance)` to the score of a match,
+/// where d is the onset deviation of the note, so that a note at the limit of the tolerance
+/// counts half. The score is the sum of the contributions divided by the number of notes in
+/// the query. The candidate translations are those that map a query note exactly onto a point,
+/// so the search takes O(n m² log n) time for a query of m notes and a point-set of n points.
+pub struct MelodySearch {
+    /// The maximum difference in onset between a query note and its matching point.
+    pub onset_tolerance: f64,
+    /// Minimum number of matched query notes required for a match to be output.
+    pub min_match_size: usize,
+}
+
+impl MelodySearch {
+    /// Returns the matches of the query in the point-set ranked by descending score. Matches
+    /// of equal score are ordered by their matched points. Of the translations that match the
+    /// same points, only the one with the best score is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The notes of the query as (onset, pitch) pairs
+    /// * `point_set` - The point-set from which the query is searched
+    pub fn search(
+        &self,
+        query: &[(f64, f64)],
+        point_set: &PointSet<Point2Df64>,
+    ) -> Vec<MelodyMatch> {
+        if query.is_empty() || point_set.is_empty() {
+            return Vec::new();
+        }
+
+        let query: Vec<Point2Df64> = query
+            .iter()
+            .map(|(onset, pitch)| Point2Df64 {
+                x: *onset,
+                y: *pitch,
+            })
+            .collect();
+
+        let mut translators: Vec<Point2Df64> = query
+            .iter()
+            .flat_map(|note| point_set.into_iter().map(move |point| *point - *note))
+            .collect();
+        translators.sort();
+        translators.dedup();
+
+        let tree = KdTree::new(point_set);
+        let mut best_by_indices: HashMap<Vec<usize>, MelodyMatch> = HashMap::new();
+        for translator in translators {
+            if let Some(found) = self.match_with(&query, &tree, translator) {
+                let indices = found.indices();
+                let is_better = match best_by_indices.get(&indices) {
+                    Some(best) => found.score > best.score,
+                    None => true,
+                };
+                if is_better {
+                    best_by_indices.insert(indices, found);
+                }
+            }
+        }
+
+        let mut matches: Vec<(Vec<usize>, MelodyMatch)> = best_by_indices.into_iter().collect();
+        matches.sort_by(|(a_indices, a), (b_indices, b)| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a_indices.cmp(b_indices))
+        });
+        matches.into_iter().map(|(_, found)| found).collect()
+    }
+
+    /// Returns the match of the query translated by the translator, or None if too few
+    /// notes are matched.
+    fn match_with(
+        &self,
+        query: &[Point2Df64],
+        tree: &KdTree<Point2Df64>,
+        translator: Point2Df64,
+    ) -> Option<MelodyMatch> {
+        let tolerances = [self.onset_tolerance, 0.0];
+        let mut alignment = Vec::with_capacity(query.len());
+        let mut total = 0.0;
+
+        for note in query {
+            let target = *note + translator;
+            let index = TolerantMatcher::nearest_within(tree, &target, &tolerances)
+                .filter(|index| !alignment.contains(&Some(*index)));
+            if let Some(index) = index {
+                let deviation = (tree.point_set()[index].x - target.x).abs();
+                total += if self.onset_tolerance > 0.0 {
+                    1.0 - deviation / (2.0 * self.onset_tolerance)
+                } else {
+                    1.0
+                };
+            }
+            alignment.push(index);
+        }
+
+        let matched = alignment.iter().flatten().count();
+        if matched == 0 || matched < self.min_match_size {
+            return None;
+        }
+
+        Some(MelodyMatch {
+            alignment,
+            translator,
+            score: total / query.len() as f64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+    use crate::search::melody::MelodySearch;
+
+    fn test_point_set() -> PointSet<Point2Df64> {
+        let notes = [
+            // The melody at its original pitch.
+            (0.0, 60.0),
+            (1.0, 62.0),
+            (2.0, 64.0),
+            (3.0, 65.0),
+            // The melody transposed up a fifth with an inexact rhythm and a wrong last note.
+            (8.0, 67.0),
+            (9.2, 69.0),
+            (10.0, 71.0),
+            (11.0, 74.0),
+            // An accompaniment note.
+            (1.0, 48.0),
+        ];
+        PointSet::new(
+            notes
+                .iter()
+                .map(|(x, y)| Point2Df64 { x: *x, y: *y })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_given_exact_query_then_best_match_is_exact() {
+        let search = MelodySearch {
+            onset_tolerance: 0.25,
+            min_match_size: 3,
+        };
+        let query = [(10.0, 50.0), (11.0, 52.0), (12.0, 54.0), (13.0, 55.0)];
+        let matches = search.search(&query, &test_point_set());
+
+        assert_eq!(2, matches.len());
+        assert_eq!(vec![0, 2, 3, 4], matches[0].indices());
+        assert_eq!(1.0, matches[0].score);
+        assert_eq!(Point2Df64 { x: -10.0, y: 10.0 }, matches[0].translator);
+
+        // The transposed occurrence matches three notes, one of them inexactly.
+        assert_eq!(3, matches[1].matched_notes());
+        assert_eq!(None, matches[1].alignment[3]);
+        assert!((matches[1].score - (2.0 + 0.6) / 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_given_strict_parameters_then_inexact_matches_not_found() {
+        let query = [(0.0, 60.0), (1.0, 62.0), (2.0, 64.0), (3.0, 65.0)];
+
+        let exact = MelodySearch {
+            onset_tolerance: 0.0,
+            min_match_size: 3,
+        };
+        assert_eq!(1, exact.search(&query, &test_point_set()).len());
+
+        let complete = MelodySearch {
+            onset_tolerance: 0.25,
+            min_match_size: 4,
+        };
+        assert_eq!(1, complete.search(&query, &test_point_set()).len());
+        assert!(complete.search(&[], &test_point_set()).is_empty());
+    }
+}
@@ -2,6 +2,12 @@
  * (c) Otso Björklund (2023)
  * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
  */
+pub mod duration;
 pub mod exact_matcher;
+pub mod melody;
 pub mod partial_matcher;
 pub mod pattern_matcher;
+pub mod registry;
+pub mod tolerant_matcher;
+pub mod transformation_matcher;
+pub mod transposition_invariant_matcher;
@@ -2,11 +2,12 @@
  * (c) Otso Björklund (2023)
  * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
  */
-use crate::discovery::utilities::sort;
 use crate::point_set::pattern::Pattern;
 use crate::point_set::point::Point;
 use crate::point_set::set::PointSet;
+use crate::search::duration::DurationMatching;
 use crate::search::pattern_matcher::PatternMatcher;
+use crate::utilities::sort;
 
 /// Implements a pattern matcher that finds all partially translationally equivalent occurrences of a pattern
 /// from a point-set. Based on the partial matching algorithm presented in
@@ -14,9 +15,13 @@ use crate::search::pattern_matcher::PatternMatcher;
 pub struct PartialMatcher {
     /// Minimum number of matching points required for a match to be considered a match.
     pub min_match_size: usize,
+    /// How the durations of the matching points are compared, see `DurationMatching`.
+    /// Points whose durations do not match are not counted as matching points.
+    pub durations: DurationMatching,
 }
 
 impl<T: Point> PatternMatcher<T> for PartialMatcher {
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "partial_matcher", skip_all, fields(m = query.len(), n = point_set.len())))]
     fn find_indices_with_callback(
         &self,
         query: &Pattern<T>,
@@ -27,6 +32,9 @@ impl<T: Point> PatternMatcher<T> for PartialMatcher {
 
         for i in 0..query.len() {
             for j in 0..point_set.len() {
+                if !self.durations.matches(&query[i], &point_set[j]) {
+                    continue;
+                }
                 let diff = point_set[j] - query[i];
                 diff_indices.push((diff, j));
             }
@@ -39,7 +47,7 @@ impl<T: Point> PatternMatcher<T> for PartialMatcher {
 
 impl PartialMatcher {
     /// Partitions the sorted list of difference-index pairs into partial matches exceeding the min_match_size.
-    fn partition<T: Point>(&self, diffs: &Vec<(T, usize)>, mut on_output: impl FnMut(Vec<usize>)) {
+    fn partition<T: Point>(&self, diffs: &[(T, usize)], mut on_output: impl FnMut(Vec<usize>)) {
         let m = diffs.len();
         let mut i = 0;
         while i < m {
@@ -66,7 +74,9 @@ mod tests {
     use super::PatternMatcher;
     use crate::point_set::pattern::Pattern;
     use crate::point_set::point::Point2Df64;
+    use crate::point_set::point_nd::PointNdf64;
     use crate::point_set::set::PointSet;
+    use crate::search::duration::DurationMatching;
 
     fn test_point_set() -> PointSet<Point2Df64> {
         let points = vec![
@@ -95,7 +105,10 @@ mod tests {
             &Point2Df64 { x: 0.875, y: 72.0 },
         ];
         let pattern = Pattern::new(&pattern_points);
-        let matcher = PartialMatcher { min_match_size: 4 };
+        let matcher = PartialMatcher {
+            min_match_size: 4,
+            durations: DurationMatching::Ignore,
+        };
         let indices = matcher.find_indices(&pattern, &point_set);
 
         assert_eq!(2, indices.len());
@@ -136,7 +149,10 @@ mod tests {
             &Point2Df64 { x: 0.875, y: 72.0 },
         ];
         let pattern = Pattern::new(&pattern_points);
-        let matcher = PartialMatcher { min_match_size: 4 };
+        let matcher = PartialMatcher {
+            min_match_size: 4,
+            durations: DurationMatching::Ignore,
+        };
         let indices = matcher.find_indices(&pattern, &point_set);
 
         assert_eq!(2, indices.len());
@@ -174,9 +190,48 @@ mod tests {
             &Point2Df64 { x: 0.375, y: 72.0 },
         ];
         let pattern = Pattern::new(&pattern_points);
-        let matcher = PartialMatcher { min_match_size: 3 };
+        let matcher = PartialMatcher {
+            min_match_size: 3,
+            durations: DurationMatching::Ignore,
+        };
 
         assert!(matcher.find_indices(&pattern, &point_set).is_empty());
         assert!(matcher.find_occurrences(&pattern, &point_set).is_empty());
     }
+
+    #[test]
+    fn test_given_n_dimensional_pattern_in_set_then_matches_found() {
+        // The third component is the voice: the upper notes are in voice 0 and the lower in voice 1.
+        let to_voiced = |p: &Point2Df64| PointNdf64 {
+            components: [p.x, p.y, if p.y >= 60.0 { 0.0 } else { 1.0 }],
+        };
+        let point_set = PointSet::new(test_point_set().into_iter().map(to_voiced).collect());
+        let pattern_points = vec![
+            &PointNdf64 {
+                components: [-1.0, 10.0, 1.0],
+            },
+            &PointNdf64 {
+                components: [0.0, 72.0, 0.0],
+            },
+            &PointNdf64 {
+                components: [0.25, 74.0, 0.0],
+            },
+            &PointNdf64 {
+                components: [0.5, 72.0, 0.0],
+            },
+            &PointNdf64 {
+                components: [0.875, 72.0, 0.0],
+            },
+        ];
+        let pattern = Pattern::new(&pattern_points);
+        let matcher = PartialMatcher {
+            min_match_size: 4,
+            durations: DurationMatching::Ignore,
+        };
+        let indices = matcher.find_indices(&pattern, &point_set);
+
+        assert_eq!(2, indices.len());
+        assert_eq!(vec![0, 1, 2, 3], indices[0]);
+        assert_eq!(vec![4, 6, 8, 9], indices[1]);
+    }
 }
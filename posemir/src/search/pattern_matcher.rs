@@ -2,9 +2,10 @@
  * (c) Otso Björklund (2023)
  * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
  */
+use crate::error::PoSemirError;
 use crate::point_set::pattern::Pattern;
 use crate::point_set::point::Point;
-use crate::point_set::set::PointSet;
+use crate::point_set::set::{validate_points, PointSet};
 
 pub trait PatternMatcher<T: Point> {
     /// Finds occurrences of the given pattern in the point-set and on each found match executes
@@ -14,7 +15,7 @@ pub trait PatternMatcher<T: Point> {
     /// * `query` - The query pattern.
     /// * `point_set` - The point-set from which the occurrences of the query are searched.
     /// * `on_output` - The call back function that is executed on each matching occurrence.
-    ///                 The occurrence is given as a vector of indices in the point-set.
+    ///   The occurrence is given as a vector of indices in the point-set.
     fn find_indices_with_callback(
         &self,
         query: &Pattern<T>,
@@ -35,6 +36,35 @@ pub trait PatternMatcher<T: Point> {
         occurrences
     }
 
+    /// Finds occurrences of the pattern given in its vectorized representation (see
+    /// `Pattern::vectorize`), i.e., as the differences between its adjacent points, and returns
+    /// them as a vector of vectors of indices. This makes it possible to write queries as interval
+    /// sequences instead of absolute points. An empty interval sequence matches nothing.
+    ///
+    /// # Arguments
+    /// * `intervals` - The query pattern as the differences between its adjacent points.
+    /// * `point_set` - The point-set from which the occurrences of the query are searched.
+    fn find_indices_by_intervals(
+        &self,
+        intervals: &Pattern<T>,
+        point_set: &PointSet<T>,
+    ) -> Vec<Vec<usize>> {
+        if intervals.is_empty() {
+            return Vec::new();
+        }
+
+        // The query can start at any point, as the matches are invariant under translation.
+        let mut current = intervals[0];
+        let mut points = vec![current];
+        for interval in intervals {
+            current = current + *interval;
+            points.push(current);
+        }
+
+        let query: Pattern<T> = PointSet::new(points).into();
+        self.find_indices(&query, point_set)
+    }
+
     /// Finds occurrences of the given pattern in the point-set and on each found match executes
     /// the given callback. The matches are output as pattern instances.
     ///
@@ -42,7 +72,7 @@ pub trait PatternMatcher<T: Point> {
     /// * `query` - The query pattern.
     /// * `point_set` - The point-set from which the occurrences of the query are searched.
     /// * `on_output` - The call back function that is executed on each matching occurrence.
-    ///                 The matches are output as pattern instances.
+    ///   The matches are output as pattern instances.
     fn find_occurrences_with_callback(
         &self,
         query: &Pattern<T>,
@@ -70,4 +100,57 @@ pub trait PatternMatcher<T: Point> {
 
         occurrences
     }
+
+    /// Returns the occurrences of the given pattern in the point-set as `find_occurrences`, but
+    /// returns an error instead of searching if the query or the point-set contains points
+    /// that are not valid (see `PointSet::validate`).
+    ///
+    /// # Arguments
+    /// * `query` - The query pattern.
+    /// * `point_set` - The point-set from which the occurrences of the query are searched.
+    fn try_find_occurrences(
+        &self,
+        query: &Pattern<T>,
+        point_set: &PointSet<T>,
+    ) -> Result<Vec<Pattern<T>>, PoSemirError> {
+        validate_points(query)?;
+        point_set.validate()?;
+        Ok(self.find_occurrences(query, point_set))
+    }
+}
+
+/// Object-safe version of `PatternMatcher`. It is implemented for all pattern matchers,
+/// so that matchers can be selected at runtime and used as trait objects,
+/// e.g., `Box<dyn DynPatternMatcher<T>>`. Boxed matchers implement `PatternMatcher`.
+pub trait DynPatternMatcher<T: Point> {
+    /// See `PatternMatcher::find_indices_with_callback`.
+    fn find_indices_with_callback_dyn(
+        &self,
+        query: &Pattern<T>,
+        point_set: &PointSet<T>,
+        on_output: &mut dyn FnMut(Vec<usize>),
+    );
+}
+
+impl<T: Point, M: PatternMatcher<T>> DynPatternMatcher<T> for M {
+    fn find_indices_with_callback_dyn(
+        &self,
+        query: &Pattern<T>,
+        point_set: &PointSet<T>,
+        on_output: &mut dyn FnMut(Vec<usize>),
+    ) {
+        self.find_indices_with_callback(query, point_set, on_output)
+    }
+}
+
+impl<T: Point> PatternMatcher<T> for Box<dyn DynPatternMatcher<T>> {
+    fn find_indices_with_callback(
+        &self,
+        query: &Pattern<T>,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(Vec<usize>),
+    ) {
+        self.as_ref()
+            .find_indices_with_callback_dyn(query, point_set, &mut on_output)
+    }
 }
@@ -0,0 +1,175 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::collections::HashMap;
+
+use crate::error::PoSemirError;
+use crate::point_set::point::Point;
+use crate::search::duration::DurationMatching;
+use crate::search::exact_matcher::ExactMatcher;
+use crate::search::partial_matcher::PartialMatcher;
+use crate::search::pattern_matcher::DynPatternMatcher;
+use crate::search::tolerant_matcher::TolerantMatcher;
+use crate::search::transposition_invariant_matcher::TranspositionInvariantMatcher;
+
+/// The names of the matchers that can be created with `create_matcher`.
+pub const MATCHER_NAMES: [&str; 4] = ["EXACT", "PARTIAL", "TRANSPOSITION-INVARIANT", "TOLERANT"];
+
+/// The parameter for the minimum number of matching points of the partial matcher.
+pub const MIN_MATCH_SIZE: &str = "min_match_size";
+/// The parameter for the onset tolerance of the tolerant matcher.
+pub const ONSET_TOLERANCE: &str = "onset_tolerance";
+/// The parameter for the pitch tolerance of the tolerant matcher.
+pub const PITCH_TOLERANCE: &str = "pitch_tolerance";
+/// The optional parameter for the duration tolerance of the exact and the partial matcher.
+pub const DURATION_TOLERANCE: &str = "duration_tolerance";
+
+/// Returns the names of the parameters used by the matcher with the given name,
+/// or empty if there is no such matcher.
+///
+/// # Arguments
+///
+/// * `name` - The name of the matcher (case insensitive), see `MATCHER_NAMES`
+pub fn matcher_parameter_names(name: &str) -> Option<&'static [&'static str]> {
+    match name.to_uppercase().as_str() {
+        "EXACT" | "TRANSPOSITION-INVARIANT" => Some(&[]),
+        "PARTIAL" => Some(&[MIN_MATCH_SIZE]),
+        "TOLERANT" => Some(&[ONSET_TOLERANCE, PITCH_TOLERANCE]),
+        _ => None,
+    }
+}
+
+/// Creates the pattern matcher with the given name. The parameters of the matcher are read
+/// from the parameter map (see `matcher_parameter_names`), and all parameters used by the
+/// matcher must be present. The exact and the partial matcher also read the optional parameter
+/// `DURATION_TOLERANCE` if it is present, and then compare the durations of the matching points
+/// (see `DurationMatching`).
+///
+/// # Arguments
+///
+/// * `name` - The name of the matcher (case insensitive), see `MATCHER_NAMES`
+/// * `parameters` - The parameter values by parameter name
+pub fn create_matcher<T: Point + 'static>(
+    name: &str,
+    parameters: &HashMap<String, f64>,
+) -> Result<Box<dyn DynPatternMatcher<T>>, PoSemirError> {
+    let matcher: Box<dyn DynPatternMatcher<T>> = match name.to_uppercase().as_str() {
+        "EXACT" => Box::new(ExactMatcher {
+            durations: duration_matching(parameters)?,
+        }),
+        "PARTIAL" => {
+            let min_match_size = parameter(parameters, MIN_MATCH_SIZE)?;
+            if min_match_size < 1.0 || min_match_size.fract() != 0.0 {
+                return Err(PoSemirError::InvalidConfiguration(format!(
+                    "{} must be a positive integer",
+                    MIN_MATCH_SIZE
+                )));
+            }
+            Box::new(PartialMatcher {
+                min_match_size: min_match_size as usize,
+                durations: duration_matching(parameters)?,
+            })
+        }
+        "TRANSPOSITION-INVARIANT" => Box::new(TranspositionInvariantMatcher {}),
+        "TOLERANT" => Box::new(TolerantMatcher {
+            onset_tolerance: parameter(parameters, ONSET_TOLERANCE)?,
+            pitch_tolerance: parameter(parameters, PITCH_TOLERANCE)?,
+        }),
+        _ => {
+            return Err(PoSemirError::InvalidConfiguration(format!(
+                "unrecognized matcher {}",
+                name
+            )))
+        }
+    };
+
+    Ok(matcher)
+}
+
+/// Returns the duration matching of the optional duration tolerance, which must be at least 0.
+fn duration_matching(parameters: &HashMap<String, f64>) -> Result<DurationMatching, PoSemirError> {
+    match parameters.get(DURATION_TOLERANCE) {
+        Some(tolerance) if tolerance.is_nan() || *tolerance < 0.0 => {
+            Err(PoSemirError::InvalidConfiguration(format!(
+                "{} must be at least 0",
+                DURATION_TOLERANCE
+            )))
+        }
+        Some(tolerance) if *tolerance == 0.0 => Ok(DurationMatching::Equal),
+        Some(tolerance) => Ok(DurationMatching::Tolerance(*tolerance)),
+        None => Ok(DurationMatching::Ignore),
+    }
+}
+
+fn parameter(parameters: &HashMap<String, f64>, name: &str) -> Result<f64, PoSemirError> {
+    match parameters.get(name) {
+        Some(value) => Ok(*value),
+        None => Err(PoSemirError::InvalidConfiguration(format!(
+            "missing parameter {}",
+            name
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+    use crate::search::exact_matcher::ExactMatcher;
+    use crate::search::pattern_matcher::{DynPatternMatcher, PatternMatcher};
+    use crate::search::registry::{
+        create_matcher, matcher_parameter_names, DURATION_TOLERANCE, MATCHER_NAMES, MIN_MATCH_SIZE,
+        ONSET_TOLERANCE, PITCH_TOLERANCE,
+    };
+    use crate::search::transposition_invariant_matcher::TranspositionInvariantMatcher;
+
+    #[test]
+    fn test_create_all_matchers() {
+        let parameters = HashMap::from([
+            (MIN_MATCH_SIZE.to_string(), 2.0),
+            (ONSET_TOLERANCE.to_string(), 0.0),
+            (PITCH_TOLERANCE.to_string(), 0.0),
+        ]);
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 1.0, y: 1.0 },
+            Point2Df64 { x: 2.0, y: 3.0 },
+            Point2Df64 { x: 2.0, y: 1.0 },
+            Point2Df64 { x: 3.0, y: 3.0 },
+        ]);
+        let query = Pattern::new(&vec![&point_set[0], &point_set[2]]);
+        let expected = ExactMatcher::default().find_occurrences(&query, &point_set);
+
+        // Matchers of different types can be stored and used as trait objects.
+        let matchers: Vec<Box<dyn DynPatternMatcher<Point2Df64>>> = MATCHER_NAMES
+            .iter()
+            .map(|name| create_matcher(name, &parameters).unwrap())
+            .collect();
+        for (name, matcher) in MATCHER_NAMES.iter().zip(&matchers) {
+            assert!(matcher_parameter_names(name).is_some());
+            let occurrences = matcher.find_occurrences(&query, &point_set);
+            if *name == "TRANSPOSITION-INVARIANT" {
+                let direct = TranspositionInvariantMatcher {}.find_occurrences(&query, &point_set);
+                assert_eq!(direct, occurrences);
+            } else {
+                assert_eq!(expected, occurrences, "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_configurations() {
+        let parameters = HashMap::from([(MIN_MATCH_SIZE.to_string(), 0.0)]);
+
+        assert!(create_matcher::<Point2Df64>("FUZZY", &parameters).is_err());
+        assert!(create_matcher::<Point2Df64>("partial", &parameters).is_err());
+        assert!(create_matcher::<Point2Df64>("TOLERANT", &parameters).is_err());
+        assert!(matcher_parameter_names("FUZZY").is_none());
+
+        let parameters = HashMap::from([(DURATION_TOLERANCE.to_string(), -1.0)]);
+        assert!(create_matcher::<Point2Df64>("EXACT", &parameters).is_err());
+    }
+}
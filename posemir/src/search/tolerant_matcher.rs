@@ -0,0 +1,181 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use crate::point_set::index::KdTree;
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::search::pattern_matcher::PatternMatcher;
+
+/// Implements a pattern matcher that finds occurrences of a pattern whose points may deviate
+/// from the translated points of the pattern by at most the given tolerances in onset (the first
+/// component) and in pitch (the second component). Any other active components must match
+/// exactly, and the inactive components (see `Point::is_active`) are ignored.
+///
+/// The translation of an occurrence is fixed by matching the first point of the query exactly,
+/// and each other point of the query is matched to the nearest point of the point-set within
+/// the tolerances. The candidate points are found with range queries on a `KdTree`, so the
+/// point-set is not scanned for each query point. The indices of a match are output in
+/// ascending order, and matches in which several query points map to the same point are
+/// not output.
+pub struct TolerantMatcher {
+    /// The maximum difference in onset between a query point and its matching point.
+    pub onset_tolerance: f64,
+    /// The maximum difference in pitch between a query point and its matching point.
+    pub pitch_tolerance: f64,
+}
+
+impl<T: Point> PatternMatcher<T> for TolerantMatcher {
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "tolerant_matcher", skip_all, fields(m = query.len(), n = point_set.len())))]
+    fn find_indices_with_callback(
+        &self,
+        query: &Pattern<T>,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(Vec<usize>),
+    ) {
+        if query.is_empty() || point_set.is_empty() {
+            return;
+        }
+
+        let tree = KdTree::new(point_set);
+        let dimensionality = query[0].dimensionality();
+        let tolerances: Vec<f64> = (0..dimensionality)
+            .map(|i| match i {
+                _ if !query[0].is_active(i) => f64::INFINITY,
+                0 => self.onset_tolerance,
+                1 => self.pitch_tolerance,
+                _ => 0.0,
+            })
+            .collect();
+
+        for (anchor, anchor_point) in point_set.into_iter().enumerate() {
+            let translator = *anchor_point - query[0];
+            let mut indices = vec![anchor];
+
+            for query_point in query.into_iter().skip(1) {
+                let target = *query_point + translator;
+                match TolerantMatcher::nearest_within(&tree, &target, &tolerances) {
+                    Some(index) => indices.push(index),
+                    None => break,
+                }
+            }
+
+            if indices.len() == query.len() {
+                indices.sort_unstable();
+                indices.dedup();
+                if indices.len() == query.len() {
+                    on_output(indices);
+                }
+            }
+        }
+    }
+}
+
+impl TolerantMatcher {
+    /// Returns the index of the point nearest to the target among the points whose components
+    /// differ from the components of the target by at most the given tolerances. The components
+    /// with an infinite tolerance are ignored in the distances.
+    pub(crate) fn nearest_within<T: Point>(
+        tree: &KdTree<T>,
+        target: &T,
+        tolerances: &[f64],
+    ) -> Option<usize> {
+        let target: Vec<f64> = (0..tolerances.len())
+            .map(|i| target.component_f64(i).unwrap())
+            .collect();
+        let lower: Vec<f64> = target.iter().zip(tolerances).map(|(c, t)| c - t).collect();
+        let upper: Vec<f64> = target.iter().zip(tolerances).map(|(c, t)| c + t).collect();
+
+        let mut nearest: Option<(f64, usize)> = None;
+        tree.range_of_components(&lower, &upper, |index| {
+            let distance: f64 = tree
+                .components(index)
+                .iter()
+                .zip(&target)
+                .zip(tolerances)
+                .filter(|(_, tolerance)| tolerance.is_finite())
+                .map(|((a, b), _)| (a - b).powi(2))
+                .sum();
+            let is_nearer = match nearest {
+                Some((nearest_distance, nearest_index)) => {
+                    distance < nearest_distance
+                        || (distance == nearest_distance && index < nearest_index)
+                }
+                None => true,
+            };
+            if is_nearer {
+                nearest = Some((distance, index));
+            }
+        });
+
+        nearest.map(|(_, index)| index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+    use crate::search::exact_matcher::ExactMatcher;
+    use crate::search::pattern_matcher::PatternMatcher;
+    use crate::search::tolerant_matcher::TolerantMatcher;
+
+    fn test_point_set() -> PointSet<Point2Df64> {
+        PointSet::new(vec![
+            Point2Df64 { x: 0.0, y: 60.0 },
+            Point2Df64 { x: 1.0, y: 62.0 },
+            Point2Df64 { x: 2.0, y: 64.0 },
+            Point2Df64 { x: 4.0, y: 65.0 },
+            Point2Df64 { x: 5.1, y: 67.0 },
+            Point2Df64 { x: 5.9, y: 70.0 },
+            Point2Df64 { x: 8.0, y: 40.0 },
+        ])
+    }
+
+    fn query() -> Pattern<Point2Df64> {
+        Pattern::new(&vec![
+            &Point2Df64 { x: 0.0, y: 60.0 },
+            &Point2Df64 { x: 1.0, y: 62.0 },
+            &Point2Df64 { x: 2.0, y: 64.0 },
+        ])
+    }
+
+    #[test]
+    fn test_given_zero_tolerances_then_matches_equal_exact_matches() {
+        let point_set = test_point_set();
+        let matcher = TolerantMatcher {
+            onset_tolerance: 0.0,
+            pitch_tolerance: 0.0,
+        };
+
+        assert_eq!(
+            ExactMatcher::default().find_indices(&query(), &point_set),
+            matcher.find_indices(&query(), &point_set)
+        );
+    }
+
+    #[test]
+    fn test_given_tolerances_then_inexact_matches_found() {
+        let point_set = test_point_set();
+
+        let onset_only = TolerantMatcher {
+            onset_tolerance: 0.25,
+            pitch_tolerance: 0.0,
+        };
+        assert_eq!(
+            vec![vec![0, 1, 2]],
+            onset_only.find_indices(&query(), &point_set)
+        );
+
+        let matcher = TolerantMatcher {
+            onset_tolerance: 0.25,
+            pitch_tolerance: 1.0,
+        };
+        assert_eq!(
+            vec![vec![0, 1, 2], vec![3, 4, 5]],
+            matcher.find_indices(&query(), &point_set)
+        );
+    }
+}
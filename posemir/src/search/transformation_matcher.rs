@@ -0,0 +1,240 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::search::pattern_matcher::PatternMatcher;
+
+/// A contrapuntal transformation of a query pattern.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Transformation {
+    /// The query as is.
+    Prime,
+    /// The query with its pitches (the second component) mirrored, so ascending intervals
+    /// become descending ones.
+    Inversion,
+    /// The query with its onsets (the first component) mirrored, i.e., played backwards.
+    Retrograde,
+    /// The retrograde of the inversion of the query.
+    RetrogradeInversion,
+}
+
+/// All transformations in the order in which `TransformationMatcher` searches them.
+pub const ALL_TRANSFORMATIONS: [Transformation; 4] = [
+    Transformation::Prime,
+    Transformation::Inversion,
+    Transformation::Retrograde,
+    Transformation::RetrogradeInversion,
+];
+
+impl Transformation {
+    /// Returns the query pattern transformed by this transformation. The mirrored components
+    /// are negated, which is enough as the matches are invariant under translation.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The pattern to transform
+    pub fn apply<T: Point>(&self, query: &Pattern<T>) -> Pattern<T> {
+        let (negate_onset, negate_pitch) = match self {
+            Transformation::Prime => return query.clone(),
+            Transformation::Inversion => (false, true),
+            Transformation::Retrograde => (true, false),
+            Transformation::RetrogradeInversion => (true, true),
+        };
+
+        let negate = |point: T, index: usize, is_negated: bool| match point.component_f64(index) {
+            Some(value) if is_negated => point.with_component_f64(index, -value).unwrap_or(point),
+            _ => point,
+        };
+        let points = query
+            .into_iter()
+            .map(|point| negate(negate(*point, 0, negate_onset), 1, negate_pitch))
+            .collect();
+        PointSet::new(points).into()
+    }
+}
+
+/// An occurrence found by `TransformationMatcher`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransformedMatch {
+    /// The transformation of the query that matched.
+    pub transformation: Transformation,
+    /// The indices of the matched points in the point-set, as output by the wrapped matcher.
+    pub indices: Vec<usize>,
+}
+
+/// Wraps a pattern matcher so that it also finds the occurrences of the transformed query, e.g.,
+/// under inversion, retrograde, and retrograde-inversion, which are not translations of the query.
+///
+/// A transformation is not searched if the transformed query is a translation of a query
+/// searched before it, e.g., the inversion of a pattern that is symmetric in pitch, so each
+/// occurrence is reported only once with the first transformation that matches it.
+/// As a `PatternMatcher`, the matcher outputs the indices of the matches of all transformations.
+pub struct TransformationMatcher<M> {
+    /// The matcher used for searching each transformed query.
+    pub matcher: M,
+    /// The transformations that are searched, see `ALL_TRANSFORMATIONS`.
+    pub transformations: Vec<Transformation>,
+}
+
+impl<M> TransformationMatcher<M> {
+    /// Returns a matcher that searches all transformations with the given matcher.
+    ///
+    /// # Arguments
+    ///
+    /// * `matcher` - The matcher used for searching each transformed query
+    pub fn with(matcher: M) -> TransformationMatcher<M> {
+        TransformationMatcher {
+            matcher,
+            transformations: ALL_TRANSFORMATIONS.to_vec(),
+        }
+    }
+
+    /// Finds the occurrences of the transformed queries in the point-set and on each found match
+    /// executes the given callback with the match and the transformation that matched.
+    ///
+    /// # Arguments
+    /// * `query` - The query pattern.
+    /// * `point_set` - The point-set from which the occurrences of the query are searched.
+    /// * `on_output` - The call back function that is executed on each matching occurrence.
+    pub fn find_transformed_with_callback<T: Point>(
+        &self,
+        query: &Pattern<T>,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(TransformedMatch),
+    ) where
+        M: PatternMatcher<T>,
+    {
+        let mut searched: Vec<Pattern<T>> = Vec::with_capacity(self.transformations.len());
+        for transformation in &self.transformations {
+            let transformed = transformation.apply(query);
+            let vectorized = transformed.vectorize();
+            if searched.contains(&vectorized) {
+                continue;
+            }
+            searched.push(vectorized);
+
+            self.matcher
+                .find_indices_with_callback(&transformed, point_set, |indices| {
+                    on_output(TransformedMatch {
+                        transformation: *transformation,
+                        indices,
+                    })
+                });
+        }
+    }
+
+    /// Finds the occurrences of the transformed queries in the point-set and returns them
+    /// with the transformations that matched.
+    ///
+    /// # Arguments
+    /// * `query` - The query pattern.
+    /// * `point_set` - The point-set from which the occurrences of the query are searched.
+    pub fn find_transformed<T: Point>(
+        &self,
+        query: &Pattern<T>,
+        point_set: &PointSet<T>,
+    ) -> Vec<TransformedMatch>
+    where
+        M: PatternMatcher<T>,
+    {
+        let mut matches = Vec::new();
+        self.find_transformed_with_callback(query, point_set, |m| matches.push(m));
+        matches
+    }
+}
+
+impl<T: Point, M: PatternMatcher<T>> PatternMatcher<T> for TransformationMatcher<M> {
+    fn find_indices_with_callback(
+        &self,
+        query: &Pattern<T>,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(Vec<usize>),
+    ) {
+        self.find_transformed_with_callback(query, point_set, |m| on_output(m.indices));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+    use crate::search::exact_matcher::ExactMatcher;
+    use crate::search::pattern_matcher::PatternMatcher;
+    use crate::search::transformation_matcher::{
+        Transformation, TransformationMatcher, TransformedMatch,
+    };
+
+    fn point(x: f64, y: f64) -> Point2Df64 {
+        Point2Df64 { x, y }
+    }
+
+    #[test]
+    fn test_find_transformed_occurrences() {
+        // A rising motif C-D-G, its inversion a fifth higher, and its retrograde an octave lower.
+        let point_set = PointSet::new(vec![
+            point(0.0, 60.0),
+            point(1.0, 62.0),
+            point(2.0, 67.0),
+            point(4.0, 67.0),
+            point(5.0, 65.0),
+            point(6.0, 60.0),
+            point(8.0, 55.0),
+            point(9.0, 50.0),
+            point(10.0, 48.0),
+        ]);
+        let query = Pattern::new(&vec![
+            &point(0.0, 60.0),
+            &point(1.0, 62.0),
+            &point(2.0, 67.0),
+        ]);
+
+        let matcher = TransformationMatcher::with(ExactMatcher::default());
+        let matches = matcher.find_transformed(&query, &point_set);
+        assert_eq!(
+            vec![
+                TransformedMatch {
+                    transformation: Transformation::Prime,
+                    indices: vec![0, 1, 2]
+                },
+                TransformedMatch {
+                    transformation: Transformation::Inversion,
+                    indices: vec![3, 4, 5]
+                },
+                TransformedMatch {
+                    transformation: Transformation::Retrograde,
+                    indices: vec![6, 7, 8]
+                },
+            ],
+            matches
+        );
+        assert_eq!(3, matcher.find_indices(&query, &point_set).len());
+    }
+
+    #[test]
+    fn test_symmetric_query_is_searched_once() {
+        // The arch C-E-C is its own retrograde, and its inversion is its retrograde-inversion.
+        let point_set = PointSet::new(vec![point(0.0, 60.0), point(1.0, 64.0), point(2.0, 60.0)]);
+        let query = Pattern::new(&vec![
+            &point(0.0, 60.0),
+            &point(1.0, 64.0),
+            &point(2.0, 60.0),
+        ]);
+
+        let matches = TransformationMatcher::with(ExactMatcher::default())
+            .find_transformed(&query, &point_set);
+        assert_eq!(1, matches.len());
+        assert_eq!(Transformation::Prime, matches[0].transformation);
+
+        let inversion = Transformation::Inversion.apply(&query);
+        assert_eq!(
+            inversion.vectorize(),
+            Transformation::RetrogradeInversion
+                .apply(&query)
+                .vectorize()
+        );
+    }
+}
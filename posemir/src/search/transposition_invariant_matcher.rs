@@ -0,0 +1,211 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point;
+use crate::point_set::set::PointSet;
+use crate::search::pattern_matcher::PatternMatcher;
+
+/// Implements a pattern matcher that finds the occurrences of a pattern that have exactly
+/// the same rhythm as the pattern but any pitches. Only the onsets (the first components)
+/// of the points are matched: an occurrence is found at each time translation that maps
+/// the onsets of the pattern to onsets in the point-set that have at least as many points
+/// as the pattern has at the corresponding onset. The other dimensions are left free, so
+/// the occurrences can be transposed by any interval, or even have a different contour.
+///
+/// Each match contains the indices of all points at the matched onsets, so in polyphonic
+/// point-sets a match can have more points than the pattern.
+pub struct TranspositionInvariantMatcher {}
+
+/// The points at the same onset, given as a range of indices in a sorted list of points.
+struct OnsetGroup {
+    onset: f64,
+    start: usize,
+    end: usize,
+}
+
+impl OnsetGroup {
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+impl<T: Point> PatternMatcher<T> for TranspositionInvariantMatcher {
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "transposition_invariant_matcher", skip_all, fields(m = query.len(), n = point_set.len())))]
+    fn find_indices_with_callback(
+        &self,
+        query: &Pattern<T>,
+        point_set: &PointSet<T>,
+        mut on_output: impl FnMut(Vec<usize>),
+    ) {
+        let mut query_points: Vec<T> = query.into_iter().copied().collect();
+        query_points.sort();
+
+        let (query_groups, onset_groups) =
+            match (group_by_onset(&query_points), group_by_onset(point_set)) {
+                (Some(query_groups), Some(onset_groups)) => (query_groups, onset_groups),
+                _ => return,
+            };
+
+        if query_groups.is_empty() || query_groups.len() > onset_groups.len() {
+            return;
+        }
+
+        for (i, candidate) in onset_groups.iter().enumerate() {
+            let translator = point_set[candidate.start] - query_points[0];
+            let mut indices = Vec::with_capacity(query_points.len());
+            let mut scan_index = i;
+
+            for query_group in &query_groups {
+                let translated = query_points[query_group.start] + translator;
+                let onset = match translated.component_f64(0) {
+                    Some(onset) => onset,
+                    None => return,
+                };
+
+                while scan_index < onset_groups.len() && onset_groups[scan_index].onset < onset {
+                    scan_index += 1;
+                }
+
+                if scan_index == onset_groups.len()
+                    || onset_groups[scan_index].onset != onset
+                    || onset_groups[scan_index].len() < query_group.len()
+                {
+                    indices.clear();
+                    break;
+                }
+
+                let group = &onset_groups[scan_index];
+                indices.extend(group.start..group.end);
+            }
+
+            if !indices.is_empty() {
+                on_output(indices);
+            }
+        }
+    }
+}
+
+/// Returns the groups of points with equal onsets in lexicographically sorted points,
+/// or empty if a point has no onset component.
+fn group_by_onset<'a, T: Point + 'a>(
+    points: impl IntoIterator<Item = &'a T>,
+) -> Option<Vec<OnsetGroup>> {
+    let mut groups: Vec<OnsetGroup> = Vec::new();
+
+    for (i, point) in points.into_iter().enumerate() {
+        let onset = point.component_f64(0)?;
+        match groups.last_mut() {
+            Some(group) if group.onset == onset => group.end = i + 1,
+            _ => groups.push(OnsetGroup {
+                onset,
+                start: i,
+                end: i + 1,
+            }),
+        }
+    }
+
+    Some(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+    use crate::search::exact_matcher::ExactMatcher;
+    use crate::search::pattern_matcher::PatternMatcher;
+    use crate::search::transposition_invariant_matcher::TranspositionInvariantMatcher;
+
+    fn test_point_set() -> PointSet<Point2Df64> {
+        let points = vec![
+            // The query
+            Point2Df64 { x: 0.0, y: 60.0 },
+            Point2Df64 { x: 0.5, y: 62.0 },
+            Point2Df64 { x: 1.0, y: 64.0 },
+            // Transposed occurrence
+            Point2Df64 { x: 2.0, y: 65.0 },
+            Point2Df64 { x: 2.5, y: 67.0 },
+            Point2Df64 { x: 3.0, y: 69.0 },
+            // Same rhythm with a different contour
+            Point2Df64 { x: 4.0, y: 64.0 },
+            Point2Df64 { x: 4.5, y: 59.0 },
+            Point2Df64 { x: 5.0, y: 72.0 },
+            // Different rhythm
+            Point2Df64 { x: 6.0, y: 60.0 },
+            Point2Df64 { x: 6.25, y: 62.0 },
+            Point2Df64 { x: 7.0, y: 64.0 },
+        ];
+
+        PointSet::new(points)
+    }
+
+    fn query() -> Pattern<Point2Df64> {
+        Pattern::new(&vec![
+            &Point2Df64 { x: 0.0, y: 60.0 },
+            &Point2Df64 { x: 0.5, y: 62.0 },
+            &Point2Df64 { x: 1.0, y: 64.0 },
+        ])
+    }
+
+    #[test]
+    fn test_given_rhythm_in_set_then_matches_found_with_any_pitches() {
+        let point_set = test_point_set();
+        let matcher = TranspositionInvariantMatcher {};
+        let indices = matcher.find_indices(&query(), &point_set);
+
+        assert_eq!(vec![vec![0, 1, 2], vec![3, 4, 5], vec![6, 7, 8]], indices);
+        assert_eq!(
+            Pattern::new(&vec![
+                &Point2Df64 { x: 4.0, y: 64.0 },
+                &Point2Df64 { x: 4.5, y: 59.0 },
+                &Point2Df64 { x: 5.0, y: 72.0 },
+            ]),
+            matcher.find_occurrences(&query(), &point_set)[2]
+        );
+
+        // Only the first two occurrences are translationally equivalent to the query.
+        assert_eq!(
+            2,
+            ExactMatcher::default()
+                .find_indices(&query(), &point_set)
+                .len()
+        );
+    }
+
+    #[test]
+    fn test_given_chords_then_onsets_need_enough_points() {
+        let point_set = PointSet::new(vec![
+            Point2Df64 { x: 0.0, y: 48.0 },
+            Point2Df64 { x: 0.0, y: 60.0 },
+            Point2Df64 { x: 1.0, y: 62.0 },
+            Point2Df64 { x: 2.0, y: 50.0 },
+            Point2Df64 { x: 3.0, y: 64.0 },
+            Point2Df64 { x: 3.0, y: 67.0 },
+        ]);
+        let matcher = TranspositionInvariantMatcher {};
+
+        let melody = Pattern::new(&vec![
+            &Point2Df64 { x: 0.0, y: 60.0 },
+            &Point2Df64 { x: 1.0, y: 62.0 },
+        ]);
+        assert_eq!(
+            vec![vec![0, 1, 2], vec![2, 3], vec![3, 4, 5]],
+            matcher.find_indices(&melody, &point_set)
+        );
+
+        let chord_and_note = Pattern::new(&vec![
+            &Point2Df64 { x: 0.0, y: 60.0 },
+            &Point2Df64 { x: 0.0, y: 64.0 },
+            &Point2Df64 { x: 1.0, y: 62.0 },
+        ]);
+        assert_eq!(
+            vec![vec![0, 1, 2]],
+            matcher.find_indices(&chord_and_note, &point_set)
+        );
+        assert!(matcher
+            .find_indices(&Pattern::new(&vec![]), &point_set)
+            .is_empty());
+    }
+}
@@ -0,0 +1,257 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+//! Derives the sectional structure of a piece from the TECs found in it: the timeline is
+//! divided into contiguous segments, each labeled with the TEC that dominates it.
+use crate::point_set::point::Point;
+use crate::point_set::tec_cover::TecCover;
+
+/// A contiguous time region of a piece labeled with its dominant TEC.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Segment {
+    /// The onset at which the segment starts.
+    pub start: f64,
+    /// The onset at which the next segment starts, or the last onset of the piece
+    /// for the last segment.
+    pub end: f64,
+    /// The index of the dominant TEC of the segment in the cover,
+    /// or None if the segment is not covered by any TEC.
+    pub label: Option<usize>,
+}
+
+/// The method for placing the boundaries between the segments.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BoundaryOptimizer {
+    /// Labels each onset with the TEC that covers the most points at the onset, and places
+    /// a boundary wherever the label changes. Ties are broken by the smaller TEC index.
+    Greedy,
+    /// Finds the labels of the onsets that maximize the number of points covered by the label
+    /// of their onset minus the penalty for each boundary. Greater penalties produce fewer
+    /// and longer segments, so short interruptions of a dominant TEC are not segmented.
+    DynamicProgramming {
+        /// The penalty of each boundary in points, at least 0.
+        boundary_penalty: f64,
+    },
+}
+
+/// Returns the segmentation of the timeline of the covered point set, i.e., the segments
+/// in ascending order of onset. Time is assumed to be the first component of the points.
+/// The segments are contiguous, so each segment ends where the next one starts, and
+/// successive segments have different labels.
+///
+/// # Arguments
+///
+/// * `cover` - The TECs and the point set that they cover
+/// * `optimizer` - The method for placing the boundaries between the segments
+pub fn segment<T: Point>(cover: &TecCover<T>, optimizer: BoundaryOptimizer) -> Vec<Segment> {
+    let (onsets, scores) = onset_scores(cover);
+    let labels = match optimizer {
+        BoundaryOptimizer::Greedy => scores.iter().map(|score| dominant(score)).collect(),
+        BoundaryOptimizer::DynamicProgramming { boundary_penalty } => {
+            optimal_labels(&scores, cover.tecs().len(), boundary_penalty)
+        }
+    };
+
+    let mut segments: Vec<Segment> = Vec::new();
+    for (i, label) in labels.into_iter().enumerate() {
+        match segments.last_mut() {
+            Some(last) if last.label == label => {}
+            last => {
+                if let Some(last) = last {
+                    last.end = onsets[i];
+                }
+                segments.push(Segment {
+                    start: onsets[i],
+                    end: onsets[i],
+                    label,
+                });
+            }
+        }
+    }
+
+    if let (Some(last), Some(last_onset)) = (segments.last_mut(), onsets.last()) {
+        last.end = *last_onset;
+    }
+    segments
+}
+
+/// Returns the distinct onsets of the point set, and for each onset the number of points
+/// at the onset covered by each TEC as (TEC index, count) pairs in ascending order of index.
+fn onset_scores<T: Point>(cover: &TecCover<T>) -> (Vec<f64>, Vec<Vec<(usize, usize)>>) {
+    let mut onsets: Vec<f64> = Vec::new();
+    let mut scores: Vec<Vec<(usize, usize)>> = Vec::new();
+
+    for point in cover.point_set() {
+        let onset = point.component_f64(0).unwrap();
+        if onsets.last() != Some(&onset) {
+            onsets.push(onset);
+            scores.push(Vec::new());
+        }
+
+        let score = scores.last_mut().unwrap();
+        for tec_index in cover.indices_covering(point) {
+            match score.binary_search_by_key(tec_index, |(i, _)| *i) {
+                Ok(i) => score[i].1 += 1,
+                Err(i) => score.insert(i, (*tec_index, 1)),
+            }
+        }
+    }
+
+    (onsets, scores)
+}
+
+/// Returns the TEC that covers the most points at an onset, or None if no TEC covers any.
+fn dominant(score: &[(usize, usize)]) -> Option<usize> {
+    let mut best: Option<(usize, usize)> = None;
+    for &(tec_index, count) in score {
+        if best.is_none_or(|(_, best_count)| count > best_count) {
+            best = Some((tec_index, count));
+        }
+    }
+    best.map(|(tec_index, _)| tec_index)
+}
+
+/// Finds the optimal labels of the onsets with the Viterbi algorithm. The label with index
+/// tec_count denotes no TEC, which covers no points.
+fn optimal_labels(
+    scores: &[Vec<(usize, usize)>],
+    tec_count: usize,
+    boundary_penalty: f64,
+) -> Vec<Option<usize>> {
+    let label_count = tec_count + 1;
+    // The best total of the labelings of the onsets so far that end in each label.
+    let mut totals = vec![0.0; label_count];
+    // For each onset, the label that precedes each label in the best labeling,
+    // if the label changes at the onset.
+    let mut switches: Vec<Vec<Option<usize>>> = Vec::with_capacity(scores.len());
+
+    for (i, score) in scores.iter().enumerate() {
+        let mut best_previous = 0;
+        for label in 1..label_count {
+            if totals[label] > totals[best_previous] {
+                best_previous = label;
+            }
+        }
+        let switched_total = totals[best_previous] - boundary_penalty;
+
+        let mut onset_switches = vec![None; label_count];
+        let mut next_totals = totals.clone();
+        if i > 0 {
+            for (label, total) in next_totals.iter_mut().enumerate() {
+                if switched_total > *total {
+                    *total = switched_total;
+                    onset_switches[label] = Some(best_previous);
+                }
+            }
+        }
+        for &(tec_index, count) in score {
+            next_totals[tec_index] += count as f64;
+        }
+
+        totals = next_totals;
+        switches.push(onset_switches);
+    }
+
+    let mut label = 0;
+    for candidate in 1..label_count {
+        if totals[candidate] > totals[label] {
+            label = candidate;
+        }
+    }
+
+    let mut labels = vec![None; scores.len()];
+    for i in (0..scores.len()).rev() {
+        labels[i] = if label == tec_count {
+            None
+        } else {
+            Some(label)
+        };
+        if let Some(previous) = switches[i][label] {
+            label = previous;
+        }
+    }
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::point_set::pattern::Pattern;
+    use crate::point_set::point::Point2Df64;
+    use crate::point_set::set::PointSet;
+    use crate::point_set::tec::Tec;
+    use crate::point_set::tec_cover::TecCover;
+    use crate::structure::{segment, BoundaryOptimizer, Segment};
+
+    /// Returns a cover where the motif of TEC 0 repeats at onsets 0-7, and the motif of TEC 2
+    /// at onsets 10-15, except at onset 12 where the noise covered by TEC 1 dominates.
+    fn test_cover() -> TecCover<Point2Df64> {
+        let point = |x: f64, y: f64| Point2Df64 { x, y };
+        let mut points = Vec::new();
+        for x in 0..8 {
+            points.push(point(x as f64, 60.0 + (x % 2) as f64));
+        }
+        for x in 10..16 {
+            points.push(point(x as f64, 40.0));
+        }
+        points.push(point(12.0, 90.0));
+        points.push(point(12.0, 91.0));
+
+        let tec_a = Tec {
+            pattern: Pattern::new(&vec![&point(0.0, 60.0), &point(1.0, 61.0)]),
+            translators: vec![point(2.0, 0.0), point(4.0, 0.0), point(6.0, 0.0)],
+        };
+        let tec_b = Tec {
+            pattern: Pattern::new(&vec![&point(10.0, 40.0), &point(11.0, 40.0)]),
+            translators: vec![point(2.0, 0.0), point(4.0, 0.0)],
+        };
+        let tec_noise = Tec {
+            pattern: Pattern::new(&vec![&point(12.0, 90.0), &point(12.0, 91.0)]),
+            translators: vec![],
+        };
+
+        TecCover::new(PointSet::new(points), vec![tec_a, tec_noise, tec_b])
+    }
+
+    #[test]
+    fn test_greedy_segmentation() {
+        let segments = segment(&test_cover(), BoundaryOptimizer::Greedy);
+        let region = |start: f64, end: f64, label: Option<usize>| Segment { start, end, label };
+
+        assert_eq!(
+            vec![
+                region(0.0, 10.0, Some(0)),
+                region(10.0, 12.0, Some(2)),
+                region(12.0, 13.0, Some(1)),
+                region(13.0, 15.0, Some(2)),
+            ],
+            segments
+        );
+    }
+
+    #[test]
+    fn test_dynamic_programming_segmentation() {
+        let cover = test_cover();
+        let region = |start: f64, end: f64, label: Option<usize>| Segment { start, end, label };
+
+        // Without a penalty the segmentation is the same as the greedy one.
+        let optimizer = BoundaryOptimizer::DynamicProgramming {
+            boundary_penalty: 0.0,
+        };
+        assert_eq!(
+            segment(&cover, BoundaryOptimizer::Greedy),
+            segment(&cover, optimizer)
+        );
+
+        let optimizer = BoundaryOptimizer::DynamicProgramming {
+            boundary_penalty: 2.0,
+        };
+        assert_eq!(
+            vec![region(0.0, 10.0, Some(0)), region(10.0, 15.0, Some(2))],
+            segment(&cover, optimizer)
+        );
+
+        let empty: TecCover<Point2Df64> = TecCover::new(PointSet::new(vec![]), vec![]);
+        assert!(segment(&empty, optimizer).is_empty());
+    }
+}
@@ -0,0 +1,344 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::collections::HashSet;
+
+use crate::point_set::pattern::Pattern;
+use crate::point_set::point::Point2Df64;
+use crate::point_set::set::PointSet;
+use crate::point_set::tec::Tec;
+
+/// Generates synthetic point sets of arbitrary size, e.g., for benchmarks and for testing the
+/// recall of discovery algorithms. The generator is seeded, so the same seed always produces
+/// the same point sets. The first component of the points is the onset and the second the
+/// pitch, and all components are integers.
+pub struct SyntheticGenerator {
+    state: u64,
+}
+
+/// The configuration of a point set with a planted pattern (see `SyntheticGenerator::planted`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PlantedConfig {
+    /// The number of points in the planted pattern, at least 1.
+    pub pattern_size: usize,
+    /// The onsets of the points of the pattern are in the range [0, pattern_span).
+    pub pattern_span: i64,
+    /// The number of occurrences of the planted pattern, at least 1.
+    pub copies: usize,
+    /// The number of noise points added around the occurrences.
+    pub noise_size: usize,
+    /// The pitches of all points are in the range [0, max_pitch).
+    pub max_pitch: i64,
+}
+
+/// A point set with a planted pattern and the ground truth of the planted occurrences.
+#[derive(Debug, Clone)]
+pub struct PlantedDataset {
+    /// The point set with the planted occurrences and the noise.
+    pub point_set: PointSet<Point2Df64>,
+    /// The planted pattern and the translators of its other planted occurrences.
+    pub planted: Tec<Point2Df64>,
+}
+
+impl PlantedDataset {
+    /// Returns the largest fraction of the planted occurrences that are occurrences of a single
+    /// TEC whose pattern is translationally equivalent to the planted pattern, or 0 if there
+    /// is no such TEC.
+    ///
+    /// # Arguments
+    ///
+    /// * `tecs` - The TECs found in the point set, e.g., by a discovery algorithm
+    pub fn recall(&self, tecs: &[Tec<Point2Df64>]) -> f64 {
+        let planted: Vec<Pattern<Point2Df64>> = self
+            .planted
+            .occurrences_iter()
+            .map(|occurrence| occurrence.into_owned())
+            .collect();
+
+        let found = tecs
+            .iter()
+            .filter(|tec| tec.translationally_equivalent(&self.planted))
+            .map(|tec| {
+                let occurrences: HashSet<Pattern<Point2Df64>> = tec
+                    .occurrences_iter()
+                    .map(|occurrence| occurrence.to_point_set().into())
+                    .collect();
+                planted
+                    .iter()
+                    .filter(|occurrence| occurrences.contains(occurrence))
+                    .count()
+            })
+            .max()
+            .unwrap_or(0);
+
+        found as f64 / planted.len() as f64
+    }
+}
+
+impl SyntheticGenerator {
+    /// Returns a new generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed of the generator
+    pub fn new(seed: u64) -> SyntheticGenerator {
+        SyntheticGenerator {
+            // The state of the xorshift generator must not be zero.
+            state: seed | 1,
+        }
+    }
+
+    /// Returns a point set of distinct points with uniformly random onsets and pitches.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The number of points
+    /// * `max_onset` - The upper bound (exclusive) for the onsets of the points
+    /// * `max_pitch` - The upper bound (exclusive) for the pitches of the points
+    pub fn random(&mut self, size: usize, max_onset: i64, max_pitch: i64) -> PointSet<Point2Df64> {
+        assert!(
+            (size as i64) <= max_onset * max_pitch,
+            "the ranges must have room for the points"
+        );
+
+        let mut points = HashSet::with_capacity(size);
+        while points.len() < size {
+            points.insert(self.next_point(max_onset, max_pitch));
+        }
+
+        PointSet::new(points.into_iter().map(to_point).collect())
+    }
+
+    /// Returns a point set with the minimal number of MTPs: the points are at successive
+    /// onsets at the same pitch, so there are only size - 1 distinct translators.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The number of points
+    pub fn min_pattern_count(size: usize) -> PointSet<Point2Df64> {
+        PointSet::new((0..size as i64).map(|i| to_point((i, 0))).collect())
+    }
+
+    /// Returns a point set with the maximal number of MTPs: the differences between all pairs
+    /// of points are distinct, so there are size * (size - 1) / 2 MTPs of one point each.
+    /// The points are (i, i²), whose differences (d, d * s) with d = j - i and s = i + j
+    /// determine the pair (i, j).
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The number of points
+    pub fn max_pattern_count(size: usize) -> PointSet<Point2Df64> {
+        PointSet::new((0..size as i64).map(|i| to_point((i, i * i))).collect())
+    }
+
+    /// Returns a point set with copies of a random pattern planted into random noise.
+    ///
+    /// The occurrences of the pattern are in disjoint onset ranges, and the noise is spread
+    /// over all of them. No noise point is translated onto another point by a translator
+    /// between two planted occurrences, so the MTP of each such translator is exactly the
+    /// planted pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The sizes of the pattern and the noise
+    pub fn planted(&mut self, config: &PlantedConfig) -> PlantedDataset {
+        assert!(config.pattern_size >= 1, "the pattern must have points");
+        assert!(config.copies >= 1, "the pattern must have occurrences");
+        assert!(
+            (config.pattern_size as i64) <= config.pattern_span * config.max_pitch,
+            "the ranges must have room for the pattern"
+        );
+
+        let mut pattern = HashSet::with_capacity(config.pattern_size);
+        while pattern.len() < config.pattern_size {
+            pattern.insert(self.next_point(config.pattern_span, config.max_pitch));
+        }
+        let mut pattern: Vec<(i64, i64)> = pattern.into_iter().collect();
+        pattern.sort();
+
+        let translators = self.occurrence_translators(config, &pattern);
+        let mut points = translate_all(&pattern, &translators);
+
+        let mut differences = Vec::new();
+        for a in &translators {
+            for b in &translators {
+                if a != b {
+                    differences.push((b.0 - a.0, b.1 - a.1));
+                }
+            }
+        }
+
+        let max_onset = 2 * config.pattern_span * config.copies as i64;
+        let max_attempts = 1000 * (config.noise_size + 1);
+        let mut noise = 0;
+        let mut attempts = 0;
+        while noise < config.noise_size {
+            attempts += 1;
+            assert!(
+                attempts <= max_attempts,
+                "the ranges must have room for the noise"
+            );
+
+            let candidate = self.next_point(max_onset, config.max_pitch);
+            let is_coincident = points.contains(&candidate)
+                || differences
+                    .iter()
+                    .any(|d| points.contains(&(candidate.0 + d.0, candidate.1 + d.1)));
+            if !is_coincident {
+                points.insert(candidate);
+                noise += 1;
+            }
+        }
+
+        let first = translators[0];
+        let planted_points: Vec<Point2Df64> = pattern
+            .iter()
+            .map(|p| to_point((p.0 + first.0, p.1 + first.1)))
+            .collect();
+
+        PlantedDataset {
+            point_set: PointSet::new(points.into_iter().map(to_point).collect()),
+            planted: Tec {
+                pattern: Pattern::new(&planted_points.iter().collect()),
+                translators: translators[1..]
+                    .iter()
+                    .map(|t| to_point((t.0 - first.0, t.1 - first.1)))
+                    .collect(),
+            },
+        }
+    }
+
+    /// Returns the translators of the planted occurrences from the origin. Each occurrence is
+    /// in its own segment that is twice as long as the pattern, and the pitch of each occurrence
+    /// is shifted randomly within the pitch range. The shifts are chosen so that translating the
+    /// occurrences by the translator between two of them maps only one occurrence onto the
+    /// planted points.
+    fn occurrence_translators(
+        &mut self,
+        config: &PlantedConfig,
+        pattern: &[(i64, i64)],
+    ) -> Vec<(i64, i64)> {
+        let max_pitch_in_pattern = pattern.iter().map(|p| p.1).max().unwrap();
+        let segment = 2 * config.pattern_span;
+        let max_attempts = 1000;
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            assert!(
+                attempts <= max_attempts,
+                "the ranges must have room for the occurrences"
+            );
+
+            let translators: Vec<(i64, i64)> = (0..config.copies as i64)
+                .map(|i| {
+                    let shift = self.next_in_range(config.max_pitch - max_pitch_in_pattern);
+                    (i * segment, shift)
+                })
+                .collect();
+
+            let points = translate_all(pattern, &translators);
+            let is_distinct = translators.iter().all(|a| {
+                translators.iter().all(|b| {
+                    let d = (b.0 - a.0, b.1 - a.1);
+                    a == b
+                        || points
+                            .iter()
+                            .filter(|p| points.contains(&(p.0 + d.0, p.1 + d.1)))
+                            .count()
+                            == pattern.len()
+                })
+            });
+            if is_distinct {
+                return translators;
+            }
+        }
+    }
+
+    /// Returns the next pseudorandom number (xorshift64*).
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+
+    fn next_in_range(&mut self, upper: i64) -> i64 {
+        (self.next_u64() % upper.max(1) as u64) as i64
+    }
+
+    fn next_point(&mut self, max_onset: i64, max_pitch: i64) -> (i64, i64) {
+        (self.next_in_range(max_onset), self.next_in_range(max_pitch))
+    }
+}
+
+fn translate_all(pattern: &[(i64, i64)], translators: &[(i64, i64)]) -> HashSet<(i64, i64)> {
+    let mut points = HashSet::with_capacity(pattern.len() * translators.len());
+    for translator in translators {
+        points.extend(
+            pattern
+                .iter()
+                .map(|p| (p.0 + translator.0, p.1 + translator.1)),
+        );
+    }
+    points
+}
+
+fn to_point(point: (i64, i64)) -> Point2Df64 {
+    Point2Df64 {
+        x: point.0 as f64,
+        y: point.1 as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::point_set::set::PointSet;
+    use crate::synthetic::{PlantedConfig, SyntheticGenerator};
+
+    #[test]
+    fn test_pattern_count_datasets() {
+        let min = SyntheticGenerator::min_pattern_count(10);
+        assert_eq!(10, min.len());
+
+        let max = SyntheticGenerator::max_pattern_count(10);
+        let mut differences = Vec::new();
+        for i in 0..max.len() {
+            for j in (i + 1)..max.len() {
+                differences.push(max[j] - max[i]);
+            }
+        }
+        let count = differences.len();
+        differences.sort();
+        differences.dedup();
+        assert_eq!(45, count);
+        assert_eq!(count, differences.len());
+
+        let random = SyntheticGenerator::new(3).random(50, 20, 10);
+        assert_eq!(50, random.len());
+        assert_eq!(random, SyntheticGenerator::new(3).random(50, 20, 10));
+    }
+
+    #[test]
+    fn test_planted() {
+        let config = PlantedConfig {
+            pattern_size: 5,
+            pattern_span: 8,
+            copies: 3,
+            noise_size: 40,
+            max_pitch: 40,
+        };
+        let dataset = SyntheticGenerator::new(7).planted(&config);
+
+        assert_eq!(15 + 40, dataset.point_set.len());
+        assert_eq!(5, dataset.planted.pattern.len());
+        assert_eq!(2, dataset.planted.translators.len());
+        for occurrence in dataset.planted.occurrences_iter() {
+            let occurrence: PointSet<_> = occurrence.to_point_set();
+            assert_eq!(occurrence, dataset.point_set.intersect(&occurrence));
+        }
+
+        assert_eq!(1.0, dataset.recall(std::slice::from_ref(&dataset.planted)));
+        assert_eq!(0.0, dataset.recall(&[]));
+    }
+}
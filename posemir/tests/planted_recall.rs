@@ -0,0 +1,127 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+//! Checks that the discovery algorithms recover patterns planted into random noise
+//! (see `posemir::synthetic`).
+#![cfg(feature = "discovery")]
+
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+use posemir::discovery::registry::{ALGORITHM_NAMES, MAX_IOI, MEMORY_BUDGET, SUB_DIAGONALS};
+use posemir::synthetic::{PlantedConfig, PlantedDataset, SyntheticGenerator};
+use posemir::{
+    create_algorithm, DiscoveryAlgorithm, Mtp, MtpAlgorithm, Point2Df64, Tec, TecAlgorithm,
+};
+
+const SEEDS: std::ops::Range<u64> = 0..8;
+
+fn planted_datasets() -> Vec<PlantedDataset> {
+    let mut datasets = Vec::new();
+    for seed in SEEDS {
+        let mut generator = SyntheticGenerator::new(seed);
+        for copies in [2, 4] {
+            datasets.push(generator.planted(&PlantedConfig {
+                pattern_size: 6,
+                pattern_span: 8,
+                copies,
+                noise_size: 10 * copies,
+                max_pitch: 60,
+            }));
+        }
+    }
+    datasets
+}
+
+fn parameters(dataset: &PlantedDataset) -> HashMap<String, f64> {
+    HashMap::from([
+        // The IOIs of the planted pattern are less than its span.
+        (MAX_IOI.to_string(), 8.0),
+        // SIAR with all subdiagonals computes all the MTPs like SIA.
+        (SUB_DIAGONALS.to_string(), dataset.point_set.len() as f64),
+        (MEMORY_BUDGET.to_string(), 1.0),
+    ])
+}
+
+fn compute(name: &str, dataset: &PlantedDataset) -> (Vec<Mtp<Point2Df64>>, Vec<Tec<Point2Df64>>) {
+    let mut mtps = Vec::new();
+    let mut tecs = Vec::new();
+    match create_algorithm(name, &parameters(dataset)).unwrap() {
+        DiscoveryAlgorithm::Mtp(algorithm) => {
+            algorithm.compute_mtps_to_output(&dataset.point_set, |mtp| {
+                mtps.push(mtp);
+                ControlFlow::Continue(())
+            });
+        }
+        DiscoveryAlgorithm::Tec(algorithm) => {
+            algorithm.compute_tecs_to_output(&dataset.point_set, |tec| {
+                tecs.push(tec);
+                ControlFlow::Continue(())
+            });
+        }
+    }
+    (mtps, tecs)
+}
+
+/// The noise never coincides with the planted occurrences, so the MTP of the translator
+/// between two occurrences is exactly the planted pattern.
+#[test]
+fn test_mtp_algorithms_find_planted_pattern() {
+    for dataset in planted_datasets() {
+        for name in ["SIA", "SIA-CHUNKED", "SIAR"] {
+            let (mtps, _) = compute(name, &dataset);
+            let translator = dataset.planted.translators[0];
+            assert!(
+                mtps.iter()
+                    .any(|mtp| mtp.translator == translator
+                        && mtp.pattern == dataset.planted.pattern),
+                "{} did not find the planted pattern",
+                name
+            );
+        }
+    }
+}
+
+/// SIATEC computes the TEC of every MTP, so it finds all the planted occurrences.
+/// SIATEC-C finds them when the maximum IOI is at least the largest IOI of the pattern,
+/// and SIATEC-R when all the subdiagonals are used.
+#[test]
+fn test_tec_algorithms_recover_planted_occurrences() {
+    for dataset in planted_datasets() {
+        for name in ["SIATEC", "SIATEC-C", "SIATEC-CH", "SIATEC-R"] {
+            let (_, tecs) = compute(name, &dataset);
+            assert_eq!(1.0, dataset.recall(&tecs), "{}", name);
+        }
+    }
+}
+
+/// The planted TEC compresses the point set much better than the TECs of the sparse noise,
+/// so the compression based algorithms cover all the planted occurrences. They may encode
+/// the occurrences with a different, translationally inequivalent TEC, e.g., one that
+/// combines the planted pattern with noise points.
+#[test]
+fn test_compression_algorithms_cover_planted_occurrences() {
+    let compressing: Vec<&str> = ALGORITHM_NAMES
+        .iter()
+        .copied()
+        .filter(|name| name.starts_with("COSIATEC") || name.contains("COMPRESS"))
+        .collect();
+    assert_eq!(4, compressing.len());
+
+    for dataset in planted_datasets() {
+        let planted = dataset.planted.covered_set();
+        for name in &compressing {
+            let (_, tecs) = compute(name, &dataset);
+            let mut covered = Vec::new();
+            for tec in &tecs {
+                covered.extend(tec.covered_set().points());
+            }
+            assert!(
+                planted.into_iter().all(|point| covered.contains(point)),
+                "{} did not cover the planted occurrences",
+                name
+            );
+        }
+    }
+}
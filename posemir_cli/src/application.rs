@@ -1,187 +1,751 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
 
-use clap::ArgMatches;
+use clap::{ArgMatches, ValueSource};
+use indicatif::{ProgressBar, ProgressStyle};
+use serde_json::json;
+use tracing::{error, info, info_span};
 
 use posemir::discovery::algorithm::{MtpAlgorithm, TecAlgorithm};
-use posemir::discovery::cosiatec::Cosiatec;
+use posemir::discovery::canonical_order::CanonicalOrder;
+use posemir::discovery::cosiatec::CosiatecCheckpoint;
+use posemir::discovery::cross_check::{cross_check_random, Agreement, RandomPointSets};
+use posemir::discovery::duplicates::DuplicateStrategy;
+use posemir::discovery::mtp_to_tec::MtpToTec;
+use posemir::discovery::progress::{Progress, ProgressObserver};
+use posemir::discovery::registry::{
+    create_algorithm, create_algorithm_with_base, create_cosiatec, parameter_names,
+    DiscoveryAlgorithm, DUPLICATE_STRATEGY, HOP, MAX_GAP_COUNT, MAX_IOI, MAX_PATTERN_SIZE,
+    MEMORY_BUDGET, MERGE_WINDOWS, SUB_DIAGONALS, WINDOW_LENGTH,
+};
 use posemir::discovery::sia::Sia;
-use posemir::discovery::siar::SiaR;
 use posemir::discovery::siatec::Siatec;
 use posemir::discovery::siatec_c::SiatecC;
 use posemir::discovery::siatec_ch::SiatecCH;
-use posemir::discovery::siatec_compress::SiatecCompress;
-use posemir::io::csv::csv_to_rounded_2d_point_f64;
-use posemir::io::json::write_tecs_to_json;
+use posemir::discovery::tec_filter::{accepts_all, TecFilter};
+use posemir::error::PoSemirError;
+use posemir::io::abc::{abc_to_point_set, AbcOptions};
+use posemir::io::csv::{csv_reader_to_rounded_2d_point_f64, csv_to_rounded_2d_point_f64};
+use posemir::io::json::{
+    read_checkpoint_from_json, read_pattern_from_json, write_checkpoint_to_json,
+    write_occurrences_to_json,
+};
+use posemir::io::mei::{mei_to_point_set, MeiOptions};
+use posemir::io::midi::{midi_bytes_to_point_set, midi_to_point_set, MidiOptions};
 use posemir::point_set::mtp::Mtp;
+use posemir::point_set::pattern::Pattern;
 use posemir::point_set::point::Point2DRf64;
 use posemir::point_set::set::PointSet;
 use posemir::point_set::tec::Tec;
+use posemir::search::pattern_matcher::PatternMatcher;
+use posemir::search::registry::{create_matcher, MIN_MATCH_SIZE};
+
+use crate::output::{create_output_writer, Destination, OutputConfig, OutputFormat, OutputWriter};
 
 type Point = Point2DRf64;
 
+/// The path that denotes the standard input or output.
+const STREAM_PATH: &str = "-";
+
 pub struct PoSeMirRunner {
     input_path: PathBuf,
-    output_writer: OutputWriter,
+    algorithm: String,
+    piece: String,
+    output_config: OutputConfig,
+    filters: Vec<TecFilter<'static, Point>>,
+    base_algorithm: Option<String>,
     sub_diag: usize,
     max_ioi: f64,
+    max_pattern_size: Option<usize>,
+    max_gap_count: Option<usize>,
+    duplicates: String,
+    /// True if the duplicate handling is given explicitly instead of being the default.
+    duplicates_given: bool,
+    canonical_order: bool,
+    memory_budget: f64,
+    window_length: f64,
+    hop: f64,
+    merge_windows: bool,
+    /// The checkpoint file of COSIATEC, if the computation is resumable.
+    resume: Option<PathBuf>,
+    show_progress: bool,
 }
 
-struct OutputWriter {
-    algorithm: String,
-    piece: String,
-    output_dir_path: PathBuf,
-    batch: Vec<Tec<Point>>,
-    batch_number: usize,
-    batch_size: usize,
-    output_count: usize,
+/// Shows the progress of the algorithm as a progress bar in the standard error.
+/// The bar is not drawn if the standard error is not a terminal.
+struct ProgressBarObserver {
+    bar: ProgressBar,
 }
 
-impl OutputWriter {
-    pub fn output_mtp(&mut self, mtp: Mtp<Point>) {
-        let tec: Tec<Point> = Tec {
-            pattern: mtp.pattern.clone(),
-            translators: vec![mtp.translator],
+impl ProgressBarObserver {
+    fn new(visible: bool) -> ProgressBarObserver {
+        let bar = if visible {
+            ProgressBar::new(0)
+        } else {
+            ProgressBar::hidden()
         };
-        self.output_tec(tec);
+        bar.set_style(
+            ProgressStyle::with_template("[{elapsed_precise}] {bar:40} {pos}/{len} {msg}").unwrap(),
+        );
+        ProgressBarObserver { bar }
     }
+}
 
-    pub fn output_tec(&mut self, tec: Tec<Point>) {
-        self.batch.push(tec);
-
-        if self.batch.len() >= self.batch_size {
-            self.flush();
+impl ProgressObserver for ProgressBarObserver {
+    fn on_progress(&self, progress: Progress) {
+        self.bar.set_length(progress.total as u64);
+        self.bar.set_position(progress.completed as u64);
+        match progress.eta {
+            Some(eta) => {
+                self.bar
+                    .set_message(format!("{} (ETA {}s)", progress.unit.name(), eta.as_secs()))
+            }
+            None => self.bar.set_message(progress.unit.name()),
         }
     }
+}
 
-    pub fn flush(&mut self) {
-        if self.output_dir_path.to_str().unwrap() != "/dev/null" {
-            let mut output_path = self.output_dir_path.clone();
-            output_path.push(format!(
-                "patterns_{}_{}_{}.json",
-                self.piece, self.algorithm, self.batch_number
-            ));
-            write_tecs_to_json(
-                &self.piece,
-                &self.algorithm,
-                &self.batch,
-                output_path.as_path(),
-            );
+/// Filters the TECs output by an algorithm and writes them with the writer of the output format.
+/// The first write error stops the writing and the algorithm, and the error is returned when
+/// the output is finished.
+struct Output {
+    writer: Box<dyn OutputWriter>,
+    filters: Vec<TecFilter<'static, Point>>,
+    output_count: usize,
+    error: Option<PoSemirError>,
+}
+
+impl Output {
+    fn output_mtp(&mut self, mtp: Mtp<Point>) -> ControlFlow<()> {
+        self.output_tec(mtp.to_tec())
+    }
+
+    /// Writes the TEC if it is accepted by the filters. Returns `ControlFlow::Break`
+    /// if the writing has failed, so that the algorithm can be stopped.
+    fn output_tec(&mut self, tec: Tec<Point>) -> ControlFlow<()> {
+        if self.error.is_some() {
+            return ControlFlow::Break(());
+        }
+        if !accepts_all(&tec, &self.filters) {
+            return ControlFlow::Continue(());
         }
 
-        self.output_count += self.batch.len();
-        self.batch.clear();
-        self.batch_number += 1;
+        match self.writer.write_tec(tec) {
+            Ok(()) => {
+                self.output_count += 1;
+                ControlFlow::Continue(())
+            }
+            Err(error) => {
+                self.error = Some(error);
+                ControlFlow::Break(())
+            }
+        }
+    }
+
+    /// Writes the remaining TECs and returns the number of written TECs.
+    fn finish(self) -> Result<usize, PoSemirError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.writer.finish()?;
+        Ok(self.output_count)
     }
 }
 
 impl PoSeMirRunner {
     pub fn new(matches: &ArgMatches) -> PoSeMirRunner {
-        let algorithm = matches.value_of("algorithm").unwrap().to_uppercase();
         let input_path = matches.value_of("input").unwrap();
+        let piece = matches.value_of("piece").unwrap();
+        PoSeMirRunner::for_piece(matches, Path::new(input_path), piece)
+    }
+
+    /// Creates a runner for the given input file and piece name, with the rest of
+    /// the configuration read from the arguments.
+    fn for_piece(matches: &ArgMatches, input_path: &Path, piece: &str) -> PoSeMirRunner {
+        let algorithm = matches.value_of("algorithm").unwrap().to_uppercase();
+        let base_algorithm = matches
+            .value_of("base-algorithm")
+            .map(|base| base.to_uppercase());
         let output_path = matches.value_of("output").unwrap();
         let batch_size: usize = matches.value_of("batch-size").unwrap().parse().unwrap();
-
-        let piece = matches.value_of("piece").unwrap();
+        // The format has been validated by the argument parser.
+        let output_format =
+            OutputFormat::from_name(matches.value_of("output-format").unwrap()).unwrap();
 
         let sub_diag: usize = matches.value_of("sub-diagonals").unwrap().parse().unwrap();
         let max_ioi: f64 = matches.value_of("max-ioi").unwrap().parse().unwrap();
+        let max_pattern_size: Option<usize> = matches
+            .value_of("max-pattern-size")
+            .map(|size| size.parse().unwrap());
+        let max_gap_count: Option<usize> = matches
+            .value_of("max-gap-count")
+            .map(|count| count.parse().unwrap());
+        let duplicates = matches.value_of("duplicates").unwrap().to_uppercase();
+        let duplicates_given = matches.value_source("duplicates") == Some(ValueSource::CommandLine);
+        let canonical_order = matches.is_present("canonical-order");
+        let memory_budget: f64 = matches.value_of("memory-budget").unwrap().parse().unwrap();
+        let window_length: f64 = matches.value_of("window-length").unwrap().parse().unwrap();
+        let hop: f64 = matches.value_of("hop").unwrap().parse().unwrap();
+        let merge_windows = matches.is_present("merge-windows");
+        // In batch mode, each piece has its own checkpoint file in the given directory.
+        let resume = matches.value_of("resume").map(|path| {
+            if matches.is_present("input-dir") {
+                Path::new(path).join(format!("{}.checkpoint.json", piece))
+            } else {
+                PathBuf::from(path)
+            }
+        });
+
+        let mut filters = Vec::new();
+        if let Some(min_size) = matches.value_of("min-size") {
+            filters.push(TecFilter::MinPatternSize(min_size.parse().unwrap()));
+        }
+        if let Some(min_occurrences) = matches.value_of("min-occurrences") {
+            filters.push(TecFilter::MinOccurrences(min_occurrences.parse().unwrap()));
+        }
+        if let Some(max_span) = matches.value_of("max-span") {
+            filters.push(TecFilter::MaxTimeSpan(max_span.parse().unwrap()));
+        }
+
+        let destination = if output_path == STREAM_PATH {
+            Destination::Stream
+        } else {
+            Destination::Directory(PathBuf::from(output_path))
+        };
+        let output_config = OutputConfig {
+            format: output_format,
+            destination,
+            compress: matches.is_present("compress"),
+            batch_size,
+        };
 
         PoSeMirRunner {
-            input_path: PathBuf::from(input_path),
-            output_writer: OutputWriter {
-                algorithm,
-                piece: piece.to_string(),
-                output_dir_path: PathBuf::from(output_path),
-                batch: Vec::new(),
-                batch_number: 0,
-                batch_size,
-                output_count: 0,
-            },
+            input_path: input_path.to_path_buf(),
+            algorithm,
+            piece: piece.to_string(),
+            output_config,
+            filters,
+            base_algorithm,
             sub_diag,
             max_ioi,
+            max_pattern_size,
+            max_gap_count,
+            duplicates,
+            duplicates_given,
+            canonical_order,
+            memory_budget,
+            window_length,
+            hop,
+            merge_windows,
+            resume,
+            show_progress: true,
         }
     }
 
     pub fn run(&mut self) {
-        let input_data = csv_to_rounded_2d_point_f64(&self.input_path);
-        match input_data {
+        // The errors have already been reported.
+        let _ = self.run_piece();
+    }
+
+    /// Runs the algorithm on the input and returns the number of saved patterns,
+    /// or the error message if the run failed. The errors are also reported.
+    fn run_piece(&mut self) -> Result<usize, String> {
+        match load_points(&self.input_path) {
             Ok(points) => {
-                println!(
-                    "Loaded {:?}, size {} points",
-                    &self.output_writer.piece,
-                    points.len()
-                );
-                self.compute_patterns(points);
+                info!("Loaded {:?}, size {} points", &self.piece, points.len());
+                self.compute_patterns(points)
             }
             Err(error) => {
-                println!("Failed to read input file: {}", error);
+                let message = format!("Failed to read input file: {}", error);
+                error!("{}", message);
+                Err(message)
             }
         }
     }
 
-    fn compute_patterns(&mut self, points: Vec<Point>) {
+    fn compute_patterns(&mut self, points: Vec<Point>) -> Result<usize, String> {
         let point_set = PointSet::new(points);
+        if point_set.is_empty() {
+            let message = String::from("The input contains no points");
+            error!("{}", message);
+            return Err(message);
+        }
+        if let Err(error) = point_set.validate() {
+            error!("{}", error);
+            return Err(error.to_string());
+        }
+        if let Err(error) = self.check_resumable() {
+            error!("{}", error);
+            return Err(error.to_string());
+        }
 
-        let mut name = String::from(&self.output_writer.algorithm);
-        match name.as_str() {
-            "SIA" => {
-                Sia {}.compute_mtps_to_output(&point_set, |mtp| self.output_writer.output_mtp(mtp));
+        let mut name = String::from(&self.algorithm);
+        let mut parameters = HashMap::from([
+            (MAX_IOI.to_string(), self.max_ioi),
+            (SUB_DIAGONALS.to_string(), self.sub_diag as f64),
+            (MEMORY_BUDGET.to_string(), self.memory_budget),
+            (WINDOW_LENGTH.to_string(), self.window_length),
+            (HOP.to_string(), self.hop),
+        ]);
+        if let Some(max_pattern_size) = self.max_pattern_size {
+            parameters.insert(MAX_PATTERN_SIZE.to_string(), max_pattern_size as f64);
+        }
+        if let Some(max_gap_count) = self.max_gap_count {
+            parameters.insert(MAX_GAP_COUNT.to_string(), max_gap_count as f64);
+        }
+        if self.merge_windows {
+            parameters.insert(MERGE_WINDOWS.to_string(), 1.0);
+        }
+        // SIATEC-C finds the same patterns in several windows, so its duplicates are
+        // removed unless they are explicitly requested.
+        if matches!(name.as_str(), "SIATEC-C" | "SIATEC-CH") {
+            match duplicate_strategy(&self.duplicates) {
+                Ok(Some(strategy)) => {
+                    parameters.insert(DUPLICATE_STRATEGY.to_string(), strategy.parameter_value());
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    error!("{}", error);
+                    return Err(error.to_string());
+                }
             }
-            "SIAR" => {
-                SiaR { r: self.sub_diag }
-                    .compute_mtps_to_output(&point_set, |mtp| self.output_writer.output_mtp(mtp));
-                name.push_str(&format!(" (r={})", self.sub_diag));
+        }
+
+        let algorithm = match &self.base_algorithm {
+            Some(base) => create_algorithm_with_base(&name, base, &parameters),
+            None => create_algorithm(&name, &parameters),
+        };
+        let algorithm = match algorithm {
+            Ok(DiscoveryAlgorithm::Mtp(algorithm)) if self.canonical_order => Ok(
+                DiscoveryAlgorithm::Mtp(Box::new(CanonicalOrder::with(algorithm))),
+            ),
+            Ok(DiscoveryAlgorithm::Tec(algorithm)) if self.canonical_order => Ok(
+                DiscoveryAlgorithm::Tec(Box::new(CanonicalOrder::with(algorithm))),
+            ),
+            algorithm => algorithm,
+        };
+        let algorithm = match algorithm {
+            Ok(algorithm) => algorithm,
+            Err(error) => {
+                error!("{}", error);
+                return Err(error.to_string());
             }
-            "SIATEC" => {
-                Siatec {}
-                    .compute_tecs_to_output(&point_set, |tec| self.output_writer.output_tec(tec));
+        };
+
+        let writer = match create_output_writer(
+            &self.output_config,
+            &self.piece,
+            &self.algorithm,
+            &parameters,
+            &point_set,
+        ) {
+            Ok(writer) => writer,
+            Err(error) => {
+                let message = format!("Failed to create output: {}", error);
+                error!("{}", message);
+                return Err(message);
             }
-            "SIATEC-C" => {
-                SiatecC {
-                    max_ioi: self.max_ioi,
+        };
+        let mut output = Output {
+            writer,
+            filters: std::mem::take(&mut self.filters),
+            output_count: 0,
+            error: None,
+        };
+
+        let mut failure = None;
+        match algorithm {
+            _ if self.resume.is_some() => {
+                if let Err(error) = self.compute_resumable(&point_set, &parameters, &mut output) {
+                    error!("{}", error);
+                    failure = Some(error.to_string());
                 }
-                .compute_tecs_to_output(&point_set, |tec| self.output_writer.output_tec(tec));
-                name.push_str(&format!(" (max-ioi={})", self.max_ioi));
             }
-            "SIATEC-CH" => {
-                SiatecCH {
-                    max_ioi: self.max_ioi,
-                }
-                .compute_tecs_to_output(&point_set, |tec| self.output_writer.output_tec(tec));
-                name.push_str(&format!(" (max-ioi={})", self.max_ioi));
+            DiscoveryAlgorithm::Mtp(algorithm) => {
+                algorithm.compute_mtps_to_output(&point_set, |mtp| output.output_mtp(mtp));
             }
-            "COSIATEC" => {
-                Cosiatec::with(Siatec {})
-                    .compute_tecs_to_output(&point_set, |tec| self.output_writer.output_tec(tec));
+            DiscoveryAlgorithm::Tec(algorithm) => {
+                let observer = ProgressBarObserver::new(self.show_progress);
+                algorithm.compute_tecs_to_output_observed(&point_set, &observer, |tec| {
+                    output.output_tec(tec)
+                });
+                observer.bar.finish_and_clear();
             }
-            "COSIATEC-C" => {
-                Cosiatec::with(SiatecC {
-                    max_ioi: self.max_ioi,
-                })
-                .compute_tecs_to_output(&point_set, |tec| self.output_writer.output_tec(tec));
-                name.push_str(&format!(" (max-ioi={})", self.max_ioi));
+        }
+
+        let parameter_source = match &self.base_algorithm {
+            Some(base) => {
+                name.push_str(&format!(" (base={})", base));
+                base.clone()
             }
-            "SIATECCOMPRESS" => {
-                SiatecCompress::with(Siatec {})
-                    .compute_tecs_to_output(&point_set, |tec| self.output_writer.output_tec(tec));
+            None => name.clone(),
+        };
+        for parameter in parameter_names(&parameter_source).unwrap_or_default() {
+            name.push_str(&format!(
+                " ({}={})",
+                parameter.replace('_', "-"),
+                parameters[*parameter]
+            ));
+        }
+
+        // Ensure all patterns written to files.
+        let output_count = match output.finish() {
+            Ok(output_count) => output_count,
+            Err(error) => {
+                let message = format!("Failed to write output file: {}", error);
+                error!("{}", message);
+                return Err(failure.unwrap_or(message));
             }
-            "SIATEC-CCOMPRESS" => {
-                SiatecCompress::with(SiatecC {
-                    max_ioi: self.max_ioi,
-                })
-                .compute_tecs_to_output(&point_set, |tec| self.output_writer.output_tec(tec));
-                name.push_str(&format!(" (max-ioi={})", self.max_ioi));
+        };
+        info!("Executed {} and saved {} patterns.", name, output_count);
+
+        match failure {
+            Some(message) => Err(message),
+            None => Ok(output_count),
+        }
+    }
+
+    /// Returns an error if a checkpoint file is given, but the algorithm is not COSIATEC or
+    /// the options cannot be applied when resuming from a checkpoint.
+    fn check_resumable(&self) -> Result<(), PoSemirError> {
+        if self.resume.is_none() {
+            return Ok(());
+        }
+        if !matches!(self.algorithm.as_str(), "COSIATEC" | "COSIATEC-C") {
+            return Err(PoSemirError::InvalidConfiguration(format!(
+                "--resume applies only to COSIATEC and COSIATEC-C, not to {}",
+                self.algorithm
+            )));
+        }
+        if self.canonical_order || self.duplicates_given {
+            return Err(PoSemirError::InvalidConfiguration(String::from(
+                "--resume cannot be combined with --canonical-order or --duplicates",
+            )));
+        }
+        Ok(())
+    }
+
+    /// Runs COSIATEC from the checkpoint file if it exists, and saves the checkpoint into
+    /// the file after each selected TEC. The TECs selected before the checkpoint are output
+    /// first, so the output is the same as that of an uninterrupted run.
+    fn compute_resumable(
+        &mut self,
+        point_set: &PointSet<Point>,
+        parameters: &HashMap<String, f64>,
+        output: &mut Output,
+    ) -> Result<(), PoSemirError> {
+        let path = self.resume.clone().unwrap();
+        let algorithm = self.algorithm.clone();
+        let cosiatec =
+            create_cosiatec::<Point>(&algorithm, self.base_algorithm.as_deref(), parameters)?;
+
+        let checkpoint = if path.exists() {
+            let checkpoint = read_checkpoint_from_json(&path)?;
+            info!(
+                "Resuming from {} with {} selected TECs and {} remaining points",
+                path.display(),
+                checkpoint.selected.len(),
+                checkpoint.remaining.len()
+            );
+            checkpoint
+        } else {
+            CosiatecCheckpoint::new(point_set)
+        };
+        for tec in &checkpoint.selected {
+            // The write error is returned when the output is finished.
+            if output.output_tec(tec.clone()).is_break() {
+                return Ok(());
             }
-            _ => {
-                println!("Unrecognized algorithm: {}", name);
+        }
+
+        let mut write_error = None;
+        cosiatec.compute_tecs_from_checkpoint(point_set, checkpoint, |checkpoint| {
+            let tec = checkpoint.selected[checkpoint.selected.len() - 1].clone();
+            if output.output_tec(tec).is_break() {
+                return ControlFlow::Break(());
             }
+            match write_checkpoint_to_json(&algorithm, checkpoint, &path) {
+                Ok(()) => ControlFlow::Continue(()),
+                Err(error) => {
+                    write_error = Some(error);
+                    ControlFlow::Break(())
+                }
+            }
+        })?;
+
+        match write_error {
+            Some(error) => Err(error),
+            None => Ok(()),
         }
+    }
+}
 
-        // Ensure all patterns written to files.
-        self.output_writer.flush();
-        println!(
-            "Executed {} and saved {} patterns.",
-            name, self.output_writer.output_count
-        );
+/// Returns the duplicate strategy with the given name, or None if the duplicates are kept.
+fn duplicate_strategy(name: &str) -> Result<Option<DuplicateStrategy>, PoSemirError> {
+    match name {
+        "KEEP-FIRST" => Ok(Some(DuplicateStrategy::KeepFirst)),
+        "KEEP-LARGEST-COVER" => Ok(Some(DuplicateStrategy::KeepLargestCover)),
+        "MERGE-TRANSLATORS" => Ok(Some(DuplicateStrategy::MergeTranslators)),
+        "KEEP-ALL" => Ok(None),
+        _ => Err(PoSemirError::InvalidConfiguration(format!(
+            "unrecognized duplicate handling {}",
+            name
+        ))),
+    }
+}
+
+/// The outcome of processing one piece in batch mode.
+struct PieceSummary {
+    piece: String,
+    input_path: PathBuf,
+    result: Result<usize, String>,
+}
+
+/// Runs the selected algorithm on every CSV, MIDI, MEI and ABC file in the input directory, using
+/// the file names as the piece names, and writes the outputs of all pieces together with
+/// a manifest.json summary into the output directory. The pieces are processed by the
+/// given number of threads.
+pub fn run_batch(matches: &ArgMatches) {
+    let input_dir = PathBuf::from(matches.value_of("input-dir").unwrap());
+    let output_dir = PathBuf::from(matches.value_of("output").unwrap());
+    let jobs: usize = matches.value_of("jobs").unwrap().parse().unwrap();
+
+    if output_dir.to_str() == Some(STREAM_PATH) {
+        error!("Batch processing requires an output directory");
+        return;
+    }
+
+    let input_paths = match list_inputs(&input_dir) {
+        Ok(input_paths) => input_paths,
+        Err(error) => {
+            error!("Failed to read input directory: {}", error);
+            return;
+        }
+    };
+
+    let next = AtomicUsize::new(0);
+    let summaries = Mutex::new(Vec::with_capacity(input_paths.len()));
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, input_paths.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(input_path) = input_paths.get(index) else {
+                    break;
+                };
+
+                let piece = input_path.file_stem().unwrap().to_string_lossy();
+                // Attributes the events of concurrent pieces to the pieces.
+                let _span = info_span!("piece", name = %piece).entered();
+                let mut runner = PoSeMirRunner::for_piece(matches, input_path, &piece);
+                // The progress bars of concurrent pieces would overwrite each other.
+                runner.show_progress = false;
+                let summary = PieceSummary {
+                    piece: piece.to_string(),
+                    input_path: input_path.clone(),
+                    result: runner.run_piece(),
+                };
+                summaries.lock().unwrap().push((index, summary));
+            });
+        }
+    });
+
+    let mut summaries = summaries.into_inner().unwrap();
+    summaries.sort_by_key(|(index, _)| *index);
+    let summaries: Vec<PieceSummary> = summaries.into_iter().map(|(_, s)| s).collect();
+
+    let algorithm = matches.value_of("algorithm").unwrap().to_uppercase();
+    let manifest_path = output_dir.join("manifest.json");
+    match write_manifest(&algorithm, &summaries, &manifest_path) {
+        Ok(()) => info!(
+            "Processed {} pieces ({} failed), see {}",
+            summaries.len(),
+            summaries.iter().filter(|s| s.result.is_err()).count(),
+            manifest_path.display()
+        ),
+        Err(error) => error!("Failed to write manifest file: {}", error),
     }
 }
+
+/// Returns the paths of the CSV, MIDI, MEI and ABC files in the directory in alphabetical order.
+fn list_inputs(dir: &Path) -> Result<Vec<PathBuf>, PoSemirError> {
+    let mut input_paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && has_extension(&path, &["csv", "mid", "midi", "mei", "abc"]) {
+            input_paths.push(path);
+        }
+    }
+
+    input_paths.sort();
+    Ok(input_paths)
+}
+
+/// Writes the summary of a batch run as JSON:
+/// ```json
+/// {
+///   "algorithm": "SIATEC-C",
+///   "pieces": [
+///     { "input": "/corpus/bwv772.csv", "patterns": 120, "piece": "bwv772" },
+///     { "error": "...", "input": "/corpus/bwv773.csv", "piece": "bwv773" }
+///   ]
+/// }
+/// ```
+fn write_manifest(
+    algorithm: &str,
+    summaries: &[PieceSummary],
+    path: &Path,
+) -> Result<(), PoSemirError> {
+    let pieces: Vec<serde_json::Value> = summaries
+        .iter()
+        .map(|summary| {
+            let mut piece = json!({
+                "piece": summary.piece,
+                "input": summary.input_path.to_string_lossy(),
+            });
+            match &summary.result {
+                Ok(count) => piece["patterns"] = json!(count),
+                Err(message) => piece["error"] = json!(message),
+            }
+            piece
+        })
+        .collect();
+
+    let manifest = json!({ "algorithm": algorithm, "pieces": pieces });
+    fs::write(path, serde_json::to_string_pretty(&manifest)?)?;
+    Ok(())
+}
+
+/// Reads the points from a MIDI file (.mid or .midi), an MEI file (.mei), the first tune
+/// of an ABC file (.abc) or a CSV file. If the path is `-`, the points are read from
+/// the standard input, which can contain either MIDI or CSV data.
+fn load_points(path: &Path) -> Result<Vec<Point>, PoSemirError> {
+    if path.to_str() == Some(STREAM_PATH) {
+        let mut bytes = Vec::new();
+        io::stdin().read_to_end(&mut bytes)?;
+        return if bytes.starts_with(b"MThd") {
+            midi_bytes_to_point_set(&bytes, &MidiOptions::default())
+                .map(|point_set| point_set.points())
+        } else {
+            csv_reader_to_rounded_2d_point_f64(bytes.as_slice())
+        };
+    }
+
+    if has_extension(path, &["mid", "midi"]) {
+        midi_to_point_set(path, &MidiOptions::default()).map(|point_set| point_set.points())
+    } else if has_extension(path, &["mei"]) {
+        mei_to_point_set(path, &MeiOptions::default()).map(|point_set| point_set.points())
+    } else if has_extension(path, &["abc"]) {
+        abc_to_point_set(path, &AbcOptions::default()).map(|point_set| point_set.points())
+    } else {
+        csv_to_rounded_2d_point_f64(path)
+    }
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension().is_some_and(|extension| {
+        extensions
+            .iter()
+            .any(|expected| extension.eq_ignore_ascii_case(expected))
+    })
+}
+
+/// Reads the query pattern from a JSON file (.json) or from a CSV file.
+fn load_query(path: &Path) -> Result<Pattern<Point>, PoSemirError> {
+    if has_extension(path, &["json"]) {
+        read_pattern_from_json(path)
+    } else {
+        csv_to_rounded_2d_point_f64(path).map(|points| PointSet::new(points).into())
+    }
+}
+
+/// Searches for the occurrences of a query pattern in a point set with the matcher
+/// selected in the arguments and writes the occurrences to a JSON file.
+pub fn run_search(matches: &ArgMatches) {
+    let input_path = PathBuf::from(matches.value_of("input").unwrap());
+    let query_path = PathBuf::from(matches.value_of("query").unwrap());
+    let output_path = PathBuf::from(matches.value_of("output").unwrap());
+    let matcher = matches.value_of("matcher").unwrap().to_uppercase();
+    let piece = matches.value_of("piece").unwrap();
+    let min_match_size: usize = matches.value_of("min-match-size").unwrap().parse().unwrap();
+
+    let point_set = match load_points(&input_path) {
+        Ok(points) => PointSet::new(points),
+        Err(error) => {
+            error!("Failed to read input file: {}", error);
+            return;
+        }
+    };
+    let query = match load_query(&query_path) {
+        Ok(query) => query,
+        Err(error) => {
+            error!("Failed to read query file: {}", error);
+            return;
+        }
+    };
+
+    let parameters = HashMap::from([(MIN_MATCH_SIZE.to_string(), min_match_size as f64)]);
+    let occurrences = create_matcher(&matcher, &parameters)
+        .and_then(|matcher| matcher.try_find_occurrences(&query, &point_set));
+
+    match occurrences {
+        Ok(occurrences) => {
+            if let Err(error) = write_occurrences_to_json(
+                piece,
+                &matcher,
+                &query,
+                &occurrences,
+                output_path.as_path(),
+            ) {
+                error!("Failed to write output file: {}", error);
+                return;
+            }
+            info!(
+                "Executed {} search and saved {} occurrences.",
+                matcher,
+                occurrences.len()
+            );
+        }
+        Err(error) => error!("{}", error),
+    }
+}
+
+/// Cross-checks the algorithms that should agree with each other on random point sets
+/// and reports the results.
+pub fn run_verification(matches: &ArgMatches) {
+    let count: usize = matches.value_of("verify").unwrap().parse().unwrap();
+    let max_ioi: f64 = matches.value_of("max-ioi").unwrap().parse().unwrap();
+
+    let report = |name: &str, result: Result<(), _>| match result {
+        Ok(()) => info!("{}: agreed on {} point sets", name, count),
+        Err(divergence) => error!("{}: {}", name, divergence),
+    };
+
+    // The ranges have room for the points, so creating the generators cannot fail.
+    let generator = |seed| RandomPointSets::new(seed, 12, 16, 8).unwrap();
+    report(
+        "SIATEC vs SIA",
+        cross_check_random(
+            &Siatec {},
+            &MtpToTec::with(Sia::new()),
+            &mut generator(1),
+            count,
+            Agreement::DistinctTecs,
+        ),
+    );
+    report(
+        "SIATEC-C vs SIATEC-CH",
+        cross_check_random(
+            &SiatecC::new(max_ioi),
+            &SiatecCH::new(max_ioi),
+            &mut generator(2),
+            count,
+            Agreement::CoveredSets,
+        ),
+    );
+}
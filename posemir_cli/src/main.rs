@@ -1,8 +1,10 @@
-use clap::{Arg, Command};
+use clap::{Arg, ArgMatches, Command};
+use tracing_subscriber::EnvFilter;
 
-use crate::application::PoSeMirRunner;
+use crate::application::{run_batch, run_search, run_verification, PoSeMirRunner};
 
 mod application;
+mod output;
 
 pub fn main() {
     let app = Command::new("posemir_cli")
@@ -12,19 +14,65 @@ pub fn main() {
 
     let app = define_args(app);
     let matches = app.get_matches();
+    init_tracing(&matches);
+
+    if let Some(search_matches) = matches.subcommand_matches("search") {
+        run_search(search_matches);
+        return;
+    }
+
+    if matches.is_present("verify") {
+        run_verification(&matches);
+        return;
+    }
+
+    if matches.is_present("input-dir") {
+        run_batch(&matches);
+        return;
+    }
 
     let mut runner = PoSeMirRunner::new(&matches);
     runner.run();
 }
 
+/// Writes the diagnostics of the CLI and of the algorithms to the standard error, so that
+/// they are never mixed with output written to the standard output. The events are filtered
+/// with the log argument, or with the RUST_LOG environment variable if it is not given.
+fn init_tracing(matches: &ArgMatches) {
+    // The log argument is global, so its value is propagated to the subcommands.
+    let filter = match matches.subcommand_matches("search") {
+        Some(search_matches) => search_matches.value_of("log"),
+        None => matches.value_of("log"),
+    };
+    let filter = match filter {
+        Some(filter) => EnvFilter::new(filter),
+        None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+    };
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_target(false)
+        .without_time()
+        .init();
+}
+
 fn define_args(app: Command) -> Command {
     let app = app.arg(
         Arg::new("algorithm")
             .long("algo")
             .short('a')
             .takes_value(true)
-            .help("The algorithm to run [SIATEC, SIATEC-C, SIATEC-CH, SIA, SIAR, COSIATEC, COSIATEC-C, SIATECCompress, SIATEC-CCompress]")
-            .required(true),
+            .help("The algorithm to run [SIATEC, SIATEC-C, SIATEC-CH, SIATEC-R, SIA, SIA-CHUNKED, SIAR, COSIATEC, COSIATEC-C, SIATECCompress, SIATEC-Compress, SIATEC-CCompress, Sliding-Window (requires --base-algo)]")
+            .required_unless_present("verify"),
+    );
+
+    let app = app.arg(
+        Arg::new("base-algorithm")
+            .long("base-algo")
+            .takes_value(true)
+            .help("The TEC algorithm wrapped by COSIATEC, SIATEC-Compress or Sliding-Window [SIATEC, SIATEC-C, SIATEC-CH, SIATEC-R]")
+            .required(false),
     );
 
     let app = app.arg(
@@ -33,7 +81,7 @@ fn define_args(app: Command) -> Command {
             .short('p')
             .takes_value(true)
             .help("The name of the piece of music")
-            .required(true),
+            .required_unless_present_any(["verify", "input-dir"]),
     );
 
     let app = app.arg(
@@ -41,17 +89,61 @@ fn define_args(app: Command) -> Command {
             .long("input")
             .short('i')
             .takes_value(true)
-            .help("Path (absolute) to the input .csv file")
-            .required(true),
+            .help("Path (absolute) to the input .csv, MIDI (.mid), MEI (.mei) or ABC (.abc) file, or - to read CSV or MIDI data from the standard input")
+            .required_unless_present_any(["verify", "input-dir"]),
+    );
+
+    let app = app.arg(
+        Arg::new("input-dir")
+            .long("input-dir")
+            .takes_value(true)
+            .conflicts_with("input")
+            .help("Path to a directory whose .csv, MIDI, MEI and ABC files are all processed. The piece names are taken from the file names, and a manifest.json summary is written into the output directory")
+            .required(false),
+    );
+
+    let app = app.arg(
+        Arg::new("jobs")
+            .long("jobs")
+            .short('j')
+            .takes_value(true)
+            .help("Number of pieces processed in parallel (applies only to --input-dir)")
+            .required(false)
+            .default_value("1"),
+    );
+
+    let app = app.arg(
+        Arg::new("output")
+            .long("output")
+            .short('o')
+            .takes_value(true)
+            .help(
+                "Path (absolute) to the output directory where the output files are written, \
+                  or - to write all patterns as a single document to the standard output",
+            )
+            .required_unless_present("verify"),
+    );
+
+    let app = app.arg(
+        Arg::new("output-format")
+            .long("output-format")
+            .takes_value(true)
+            .possible_values(["json", "mirex", "csv", "session", "none"])
+            .ignore_case(true)
+            .help("The format of the output: batches of patterns as JSON files, all patterns as a MIREX text or a CSV file, \
+                   a session file with the input points, the parameters and the patterns by point indices, \
+                   or none to discard the patterns, e.g., for profiling")
+            .required(false)
+            .default_value("json"),
     );
 
-    let app = app.arg(Arg::new("output")
-        .long("output")
-        .short('o')
-        .takes_value(true)
-        .help("Path (absolute) to the output directory where the output JSON files are written. \
-                  For profiling purposes this can be set to /dev/null to avoid file writing operations.")
-        .required(true));
+    let app = app.arg(
+        Arg::new("compress")
+            .long("compress")
+            .takes_value(false)
+            .help("Compress the output files (or the standard output) with gzip and append .gz to the file names")
+            .required(false),
+    );
 
     let app = app.arg(
         Arg::new("batch-size")
@@ -59,7 +151,7 @@ fn define_args(app: Command) -> Command {
             .short('b')
             .takes_value(true)
             .help(
-                "Batch size for output files (= how many patters are written to same output file)",
+                "Batch size for output files (= how many patters are written to same output file, applies only to JSON)",
             )
             .required(false)
             .default_value("100"),
@@ -74,14 +166,189 @@ fn define_args(app: Command) -> Command {
             .default_value("10.0"),
     );
 
+    let app = app.arg(
+        Arg::new("max-pattern-size")
+            .long("max-pattern-size")
+            .takes_value(true)
+            .help("Maximum number of points in a pattern, larger patterns are split (applies only to SIATEC-C)")
+            .required(false),
+    );
+
+    let app = app.arg(
+        Arg::new("max-gap-count")
+            .long("max-gap-count")
+            .takes_value(true)
+            .help("Maximum number of non-zero inter-onset intervals in a pattern, patterns with more are split (applies only to SIATEC-C)")
+            .required(false),
+    );
+
+    let app = app.arg(
+        Arg::new("duplicates")
+            .long("duplicates")
+            .takes_value(true)
+            .help("How translationally equivalent duplicate TECs are handled (applies only to SIATEC-C and SIATEC-CH) [KEEP-FIRST, KEEP-LARGEST-COVER, MERGE-TRANSLATORS, KEEP-ALL]")
+            .required(false)
+            .default_value("KEEP-FIRST"),
+    );
+
+    let app = app.arg(
+        Arg::new("canonical-order")
+            .long("canonical-order")
+            .takes_value(false)
+            .help("Output the patterns in a canonical order that does not vary between runs, e.g., for comparing outputs")
+            .required(false),
+    );
+
     let app = app.arg(
         Arg::new("sub-diagonals")
             .long("sub-diag")
             .takes_value(true)
-            .help("Number of subdiagonals to use (applies only to SIAR)")
+            .help("Number of subdiagonals to use (applies only to SIAR and SIATEC-R)")
             .required(false)
             .default_value("3"),
     );
 
-    app
+    let app = app.arg(
+        Arg::new("memory-budget")
+            .long("memory-budget")
+            .takes_value(true)
+            .help("Memory budget in megabytes for the difference vectors (applies only to SIA-CHUNKED)")
+            .required(false)
+            .default_value("1024"),
+    );
+
+    let app = app.arg(
+        Arg::new("window-length")
+            .long("window-length")
+            .takes_value(true)
+            .help("The length of the time windows (applies only to Sliding-Window)")
+            .required(false)
+            .default_value("16"),
+    );
+
+    let app = app.arg(
+        Arg::new("hop")
+            .long("hop")
+            .takes_value(true)
+            .help("The time between the starts of successive windows (applies only to Sliding-Window)")
+            .required(false)
+            .default_value("8"),
+    );
+
+    let app = app.arg(
+        Arg::new("merge-windows")
+            .long("merge-windows")
+            .takes_value(false)
+            .help("Merge the patterns across the windows before output instead of outputting the patterns of each window as they are found, which keeps the patterns of all windows in memory (applies only to Sliding-Window)")
+            .required(false),
+    );
+
+    let app = app.arg(
+        Arg::new("min-size")
+            .long("min-size")
+            .takes_value(true)
+            .help("Minimum number of points in output patterns")
+            .required(false),
+    );
+
+    let app = app.arg(
+        Arg::new("min-occurrences")
+            .long("min-occurrences")
+            .takes_value(true)
+            .help("Minimum number of occurrences of output patterns")
+            .required(false),
+    );
+
+    let app = app.arg(
+        Arg::new("max-span")
+            .long("max-span")
+            .takes_value(true)
+            .help("Maximum time span of output patterns")
+            .required(false),
+    );
+
+    let app = app.arg(
+        Arg::new("resume")
+            .long("resume")
+            .takes_value(true)
+            .value_name("PATH")
+            .help("Checkpoint file from which COSIATEC is resumed if it exists and into which its state is saved after each selected TEC (applies only to COSIATEC and COSIATEC-C, and cannot be combined with --canonical-order or --duplicates). In batch processing, PATH is a directory for the checkpoints of the pieces")
+            .required(false),
+    );
+
+    let app = app.arg(
+        Arg::new("verify")
+            .long("verify")
+            .takes_value(true)
+            .value_name("COUNT")
+            .help("Instead of processing input, cross-check the algorithms against each other on COUNT random point sets")
+            .required(false),
+    );
+
+    let app = app.arg(
+        Arg::new("log")
+            .long("log")
+            .takes_value(true)
+            .global(true)
+            .help("Filter for the diagnostics written to the standard error, e.g., warn or posemir=debug (default: RUST_LOG or info)")
+            .required(false),
+    );
+
+    app.subcommand_negates_reqs(true)
+        .subcommand(define_search_args(Command::new("search")))
+}
+
+fn define_search_args(command: Command) -> Command {
+    command
+        .about("Searches for the occurrences of a query pattern in a point set")
+        .arg(
+            Arg::new("input")
+                .long("input")
+                .short('i')
+                .takes_value(true)
+                .help("Path to the input .csv, MIDI (.mid), MEI (.mei) or ABC (.abc) file")
+                .required(true),
+        )
+        .arg(
+            Arg::new("query")
+                .long("query")
+                .short('q')
+                .takes_value(true)
+                .help("Path to the query pattern as a .csv file or a .json pattern or TEC file")
+                .required(true),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .takes_value(true)
+                .help("Path to the output JSON file where the occurrences are written")
+                .required(true),
+        )
+        .arg(
+            Arg::new("matcher")
+                .long("matcher")
+                .short('m')
+                .takes_value(true)
+                .help("The pattern matcher to use [EXACT, PARTIAL, TRANSPOSITION-INVARIANT]")
+                .required(false)
+                .default_value("EXACT"),
+        )
+        .arg(
+            Arg::new("min-match-size")
+                .long("min-match-size")
+                .takes_value(true)
+                .help("Minimum number of matching points in a match (applies only to PARTIAL)")
+                .required(false)
+                .default_value("2"),
+        )
+        .arg(
+            Arg::new("piece")
+                .long("piece")
+                .short('p')
+                .takes_value(true)
+                .help("The name of the piece of music")
+                .required(false)
+                .default_value("piece"),
+        )
 }
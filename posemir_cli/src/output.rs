@@ -0,0 +1,416 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use posemir::error::PoSemirError;
+use posemir::io::csv::write_tecs_to_csv;
+use posemir::io::json::write_tecs_to_json_writer;
+use posemir::io::mirex::write_tecs_to_mirex;
+use posemir::io::session::SessionWriter;
+use posemir::point_set::point::Point2DRf64;
+use posemir::point_set::set::PointSet;
+use posemir::point_set::tec::Tec;
+
+type Point = Point2DRf64;
+
+/// The format in which the output patterns are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Batches of TECs as JSON lists, each batch in its own file.
+    Json,
+    /// All TECs in the text format of MIREX.
+    Mirex,
+    /// All TECs as CSV with a row for each point of each occurrence.
+    Csv,
+    /// A session file with the point set, the parameters and the TECs by point indices.
+    Session,
+    /// The TECs are discarded, e.g., for profiling the algorithms.
+    None,
+}
+
+impl OutputFormat {
+    /// Returns the format with the given name.
+    pub fn from_name(name: &str) -> Result<OutputFormat, PoSemirError> {
+        match name.to_uppercase().as_str() {
+            "JSON" => Ok(OutputFormat::Json),
+            "MIREX" => Ok(OutputFormat::Mirex),
+            "CSV" => Ok(OutputFormat::Csv),
+            "SESSION" => Ok(OutputFormat::Session),
+            "NONE" => Ok(OutputFormat::None),
+            _ => Err(PoSemirError::InvalidConfiguration(format!(
+                "unrecognized output format {}",
+                name
+            ))),
+        }
+    }
+}
+
+/// Where the output files are written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Destination {
+    /// Each output file is written into the directory.
+    Directory(PathBuf),
+    /// The output is written to the standard output as a single document.
+    Stream,
+}
+
+/// The configuration of the output of a piece, read from the arguments.
+#[derive(Debug, Clone)]
+pub struct OutputConfig {
+    pub format: OutputFormat,
+    pub destination: Destination,
+    /// Whether the output files are compressed with gzip.
+    pub compress: bool,
+    /// The number of TECs in each JSON file.
+    pub batch_size: usize,
+}
+
+impl OutputConfig {
+    /// Opens the output file with the given name, or the standard output, for writing.
+    /// If the output is compressed, `.gz` is appended to the file name.
+    fn open(&self, file_name: &str) -> Result<Sink, PoSemirError> {
+        let writer: Box<dyn Write> = match &self.destination {
+            Destination::Directory(path) => {
+                let file_name = if self.compress {
+                    format!("{}.gz", file_name)
+                } else {
+                    file_name.to_string()
+                };
+                Box::new(BufWriter::new(File::create(path.join(file_name))?))
+            }
+            Destination::Stream => Box::new(BufWriter::new(io::stdout())),
+        };
+
+        Ok(if self.compress {
+            Sink::Gzip(GzEncoder::new(writer, Compression::default()))
+        } else {
+            Sink::Plain(writer)
+        })
+    }
+}
+
+/// An output file or the standard output, which is optionally compressed.
+enum Sink {
+    Plain(Box<dyn Write>),
+    Gzip(GzEncoder<Box<dyn Write>>),
+}
+
+impl Sink {
+    /// Completes the compressed output and flushes the output.
+    fn finish(self) -> Result<(), PoSemirError> {
+        match self {
+            Sink::Plain(mut writer) => writer.flush()?,
+            Sink::Gzip(writer) => writer.finish()?.flush()?,
+        }
+        Ok(())
+    }
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Sink::Plain(writer) => writer.write(buf),
+            Sink::Gzip(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Sink::Plain(writer) => writer.flush(),
+            Sink::Gzip(writer) => writer.flush(),
+        }
+    }
+}
+
+/// Writes the TECs output by an algorithm for a piece in an output format.
+pub trait OutputWriter {
+    /// Writes the TEC, or keeps it until the TECs are written together.
+    fn write_tec(&mut self, tec: Tec<Point>) -> Result<(), PoSemirError>;
+
+    /// Writes the TECs that have not been written yet and completes the output.
+    fn finish(self: Box<Self>) -> Result<(), PoSemirError>;
+}
+
+/// Returns the writer of the configured output format for the piece.
+///
+/// # Arguments
+///
+/// * `config` - The configuration of the output
+/// * `piece` - The name of the piece
+/// * `algorithm` - The name of the algorithm
+/// * `parameters` - The parameters of the algorithm
+/// * `point_set` - The point set of the piece
+pub fn create_output_writer(
+    config: &OutputConfig,
+    piece: &str,
+    algorithm: &str,
+    parameters: &HashMap<String, f64>,
+    point_set: &PointSet<Point>,
+) -> Result<Box<dyn OutputWriter>, PoSemirError> {
+    let name = format!("{}_{}", piece, algorithm);
+    Ok(match config.format {
+        OutputFormat::Json => Box::new(JsonWriter {
+            config: config.clone(),
+            piece: piece.to_string(),
+            algorithm: algorithm.to_string(),
+            batch: Vec::new(),
+            batch_number: 0,
+        }),
+        OutputFormat::Mirex => Box::new(CollectingWriter {
+            sink: config.open(&format!("patterns_{}.txt", name))?,
+            tecs: Vec::new(),
+            write: |tecs, sink| write_tecs_to_mirex(tecs, sink),
+        }),
+        OutputFormat::Csv => Box::new(CollectingWriter {
+            sink: config.open(&format!("patterns_{}.csv", name))?,
+            tecs: Vec::new(),
+            write: |tecs, sink| write_tecs_to_csv(tecs, sink),
+        }),
+        OutputFormat::Session => Box::new(SessionWriter::new(
+            config.open(&format!("{}.session.jsonl", name))?,
+            piece,
+            algorithm,
+            parameters,
+            point_set,
+        )?),
+        OutputFormat::None => Box::new(DiscardingWriter {}),
+    })
+}
+
+/// Writes the TECs in batches into JSON files, or into the standard output
+/// as a single JSON list when the output is finished.
+struct JsonWriter {
+    config: OutputConfig,
+    piece: String,
+    algorithm: String,
+    batch: Vec<Tec<Point>>,
+    batch_number: usize,
+}
+
+impl JsonWriter {
+    fn write_batch(&mut self) -> Result<(), PoSemirError> {
+        let mut sink = self.config.open(&format!(
+            "patterns_{}_{}_{}.json",
+            self.piece, self.algorithm, self.batch_number
+        ))?;
+        write_tecs_to_json_writer(&self.piece, &self.algorithm, &self.batch, &mut sink)?;
+        sink.finish()?;
+
+        self.batch.clear();
+        self.batch_number += 1;
+        Ok(())
+    }
+}
+
+impl OutputWriter for JsonWriter {
+    fn write_tec(&mut self, tec: Tec<Point>) -> Result<(), PoSemirError> {
+        self.batch.push(tec);
+        if self.batch.len() >= self.config.batch_size
+            && self.config.destination != Destination::Stream
+        {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), PoSemirError> {
+        // At least one JSON list is written, even if there are no TECs.
+        if !self.batch.is_empty() || self.batch_number == 0 {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects all TECs and writes them into a single file when the output is finished,
+/// which is needed by the formats that number the TECs.
+struct CollectingWriter {
+    sink: Sink,
+    tecs: Vec<Tec<Point>>,
+    write: fn(&[Tec<Point>], &mut Sink) -> Result<(), PoSemirError>,
+}
+
+impl OutputWriter for CollectingWriter {
+    fn write_tec(&mut self, tec: Tec<Point>) -> Result<(), PoSemirError> {
+        self.tecs.push(tec);
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), PoSemirError> {
+        (self.write)(&self.tecs, &mut self.sink)?;
+        self.sink.finish()
+    }
+}
+
+impl OutputWriter for SessionWriter<Sink> {
+    fn write_tec(&mut self, tec: Tec<Point>) -> Result<(), PoSemirError> {
+        SessionWriter::write_tec(self, &tec)
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), PoSemirError> {
+        SessionWriter::finish(*self)?.finish()
+    }
+}
+
+struct DiscardingWriter {}
+
+impl OutputWriter for DiscardingWriter {
+    fn write_tec(&mut self, _tec: Tec<Point>) -> Result<(), PoSemirError> {
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), PoSemirError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fs;
+    use std::io::{Cursor, Read};
+    use std::path::Path;
+
+    use flate2::read::GzDecoder;
+    use posemir::io::csv::write_tecs_to_csv;
+    use posemir::io::json::read_tecs_from_json;
+    use posemir::io::mirex::write_tecs_to_mirex;
+    use posemir::io::session::read_session_from_reader;
+    use posemir::point_set::point::Point2DRf64;
+    use posemir::point_set::set::PointSet;
+    use posemir::point_set::tec::Tec;
+
+    use crate::output::{create_output_writer, Destination, OutputConfig, OutputFormat};
+
+    fn test_point_set() -> PointSet<Point2DRf64> {
+        PointSet::new(vec![
+            Point2DRf64::new(0.0, 60.0),
+            Point2DRf64::new(1.0, 62.0),
+            Point2DRf64::new(2.0, 64.0),
+            Point2DRf64::new(3.0, 66.0),
+        ])
+    }
+
+    fn test_tecs(point_set: &PointSet<Point2DRf64>) -> Vec<Tec<Point2DRf64>> {
+        let step = Point2DRf64::new(1.0, 2.0);
+        vec![
+            Tec {
+                pattern: point_set.get_pattern(&[0, 1]),
+                translators: vec![step, step + step],
+            },
+            Tec {
+                pattern: point_set.get_pattern(&[0, 1, 2]),
+                translators: vec![step],
+            },
+            Tec {
+                pattern: point_set.get_pattern(&[0, 1, 2, 3]),
+                translators: vec![],
+            },
+        ]
+    }
+
+    /// Writes the test TECs in the format into the directory, with a batch size of 2.
+    fn write_output(format: OutputFormat, compress: bool, directory: &Path) {
+        let config = OutputConfig {
+            format,
+            destination: Destination::Directory(directory.to_path_buf()),
+            compress,
+            batch_size: 2,
+        };
+        let point_set = test_point_set();
+        let parameters = HashMap::from([("max_ioi".to_string(), 2.0)]);
+        let mut writer =
+            create_output_writer(&config, "piece", "algo", &parameters, &point_set).unwrap();
+        for tec in test_tecs(&point_set) {
+            writer.write_tec(tec).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    /// Reads the output file, which is decompressed if the output is compressed.
+    fn read_output(directory: &Path, file_name: &str, compress: bool) -> Vec<u8> {
+        if !compress {
+            return fs::read(directory.join(file_name)).unwrap();
+        }
+        let compressed = fs::read(directory.join(format!("{}.gz", file_name))).unwrap();
+        let mut bytes = Vec::new();
+        GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut bytes)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        for compress in [false, true] {
+            let tmp_dir = tempfile::tempdir().unwrap();
+            write_output(OutputFormat::Json, compress, tmp_dir.path());
+            assert_eq!(2, fs::read_dir(tmp_dir.path()).unwrap().count());
+
+            let mut tecs = Vec::new();
+            for batch in 0..2 {
+                let file_name = format!("patterns_piece_algo_{}.json", batch);
+                let decoded = tmp_dir.path().join("decoded.json");
+                fs::write(&decoded, read_output(tmp_dir.path(), &file_name, compress)).unwrap();
+                tecs.extend(read_tecs_from_json(&decoded).unwrap());
+            }
+            assert_eq!(test_tecs(&test_point_set()), tecs);
+        }
+    }
+
+    #[test]
+    fn test_mirex_round_trip() {
+        let tecs = test_tecs(&test_point_set());
+        let mut expected = Vec::new();
+        write_tecs_to_mirex(&tecs, &mut expected).unwrap();
+
+        for compress in [false, true] {
+            let tmp_dir = tempfile::tempdir().unwrap();
+            write_output(OutputFormat::Mirex, compress, tmp_dir.path());
+            let output = read_output(tmp_dir.path(), "patterns_piece_algo.txt", compress);
+            assert_eq!(expected, output);
+        }
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let tecs = test_tecs(&test_point_set());
+        let mut expected = Vec::new();
+        write_tecs_to_csv(&tecs, &mut expected).unwrap();
+
+        for compress in [false, true] {
+            let tmp_dir = tempfile::tempdir().unwrap();
+            write_output(OutputFormat::Csv, compress, tmp_dir.path());
+            let output = read_output(tmp_dir.path(), "patterns_piece_algo.csv", compress);
+            assert_eq!(expected, output);
+        }
+    }
+
+    #[test]
+    fn test_session_round_trip() {
+        for compress in [false, true] {
+            let tmp_dir = tempfile::tempdir().unwrap();
+            write_output(OutputFormat::Session, compress, tmp_dir.path());
+            let output = read_output(tmp_dir.path(), "piece_algo.session.jsonl", compress);
+
+            let session = read_session_from_reader(Cursor::new(output)).unwrap();
+            assert_eq!("piece", session.piece);
+            assert_eq!("algo", session.algorithm);
+            assert_eq!(Some(&2.0), session.parameters.get("max_ioi"));
+            assert_eq!(test_point_set(), session.point_set);
+            assert_eq!(test_tecs(&test_point_set()), session.tecs);
+        }
+    }
+
+    #[test]
+    fn test_no_output() {
+        for compress in [false, true] {
+            let tmp_dir = tempfile::tempdir().unwrap();
+            write_output(OutputFormat::None, compress, tmp_dir.path());
+            assert_eq!(0, fs::read_dir(tmp_dir.path()).unwrap().count());
+        }
+    }
+}
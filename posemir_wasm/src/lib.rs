@@ -0,0 +1,289 @@
+/*
+ * (c) Otso Björklund (2023)
+ * Distributed under the MIT license (see LICENSE.txt or https://opensource.org/licenses/MIT).
+ */
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+use js_sys::{Array, Float64Array, Function, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use posemir::discovery::algorithm::{MtpAlgorithm, TecAlgorithm};
+use posemir::discovery::registry::{create_algorithm, parameter_names, DiscoveryAlgorithm};
+use posemir::error::PoSemirError;
+use posemir::io::midi::{midi_bytes_to_point_set, MidiOptions};
+use posemir::point_set::pattern::Pattern;
+use posemir::point_set::point::{Point, Point2DRf64};
+use posemir::point_set::set::PointSet;
+use posemir::search::pattern_matcher::PatternMatcher;
+use posemir::search::registry::{create_matcher, MIN_MATCH_SIZE};
+
+/// A point set of (onset, pitch) points. In JavaScript the points are passed as flat
+/// `Float64Array`s of onset, pitch pairs.
+#[wasm_bindgen(js_name = PointSet)]
+pub struct WasmPointSet {
+    point_set: PointSet<Point2DRf64>,
+}
+
+#[wasm_bindgen(js_class = PointSet)]
+impl WasmPointSet {
+    /// Creates a point set from a flat array of onset, pitch pairs.
+    #[wasm_bindgen(constructor)]
+    pub fn new(points: &[f64]) -> Result<WasmPointSet, JsError> {
+        let point_set = PointSet::new(to_points(points)?);
+        point_set.validate()?;
+        Ok(WasmPointSet { point_set })
+    }
+
+    /// Reads the point set from the contents of a MIDI file, with the onsets in beats.
+    #[wasm_bindgen(js_name = fromMidi)]
+    pub fn from_midi(bytes: &[u8]) -> Result<WasmPointSet, JsError> {
+        let point_set = midi_bytes_to_point_set(bytes, &MidiOptions::default())?;
+        Ok(WasmPointSet { point_set })
+    }
+
+    /// The number of points in the point set.
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.point_set.len()
+    }
+
+    /// Returns the points in lexicographic order as a flat array of onset, pitch pairs.
+    pub fn points(&self) -> Vec<f64> {
+        to_flat(&self.point_set)
+    }
+}
+
+/// A pattern and its translators as flat arrays of onset, pitch pairs. MTPs are
+/// represented as TECs with a single translator.
+#[wasm_bindgen(js_name = Tec)]
+pub struct WasmTec {
+    pattern: Vec<f64>,
+    translators: Vec<f64>,
+}
+
+#[wasm_bindgen(js_class = Tec)]
+impl WasmTec {
+    #[wasm_bindgen(getter)]
+    pub fn pattern(&self) -> Vec<f64> {
+        self.pattern.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn translators(&self) -> Vec<f64> {
+        self.translators.clone()
+    }
+}
+
+/// Runs the discovery algorithm with the given name on the point set and returns all found TECs.
+/// The parameters of the algorithm are read from the properties of the parameter object,
+/// e.g., `{ max_ioi: 4.0 }` for SIATEC-C.
+#[wasm_bindgen]
+pub fn discover(
+    point_set: &WasmPointSet,
+    algorithm: &str,
+    parameters: &Object,
+) -> Result<Vec<WasmTec>, JsError> {
+    let mut tecs = Vec::new();
+    compute(
+        &point_set.point_set,
+        algorithm,
+        &read_parameters(algorithm, parameters),
+        |tec| {
+            tecs.push(tec);
+            ControlFlow::Continue(())
+        },
+    )?;
+    Ok(tecs)
+}
+
+/// Runs the discovery algorithm as `discover`, but passes each found TEC to on_output instead of
+/// collecting them, so that the output can be shown while the computation is running. The
+/// computation is stopped if on_output returns false or throws, and the thrown error is rethrown.
+#[wasm_bindgen(js_name = discoverWithCallback)]
+pub fn discover_with_callback(
+    point_set: &WasmPointSet,
+    algorithm: &str,
+    parameters: &Object,
+    on_output: &Function,
+) -> Result<(), JsValue> {
+    let mut thrown = None;
+    compute(
+        &point_set.point_set,
+        algorithm,
+        &read_parameters(algorithm, parameters),
+        |tec| match on_output.call1(&JsValue::NULL, &tec.into()) {
+            Ok(value) if value.as_bool() == Some(false) => ControlFlow::Break(()),
+            Ok(_) => ControlFlow::Continue(()),
+            Err(error) => {
+                thrown = Some(error);
+                ControlFlow::Break(())
+            }
+        },
+    )
+    .map_err(|error| JsValue::from(JsError::from(error)))?;
+
+    match thrown {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// Returns the occurrences of the query, given as a flat array of onset, pitch pairs, in the
+/// point set. The matcher is one of EXACT, PARTIAL and TRANSPOSITION-INVARIANT, and the minimum
+/// match size only applies to PARTIAL. The occurrences are returned as an array of flat arrays.
+#[wasm_bindgen]
+pub fn search(
+    query: &[f64],
+    point_set: &WasmPointSet,
+    matcher: &str,
+    min_match_size: usize,
+) -> Result<Array, JsError> {
+    let occurrences = find_occurrences(query, &point_set.point_set, matcher, min_match_size)?;
+    Ok(occurrences
+        .iter()
+        .map(|occurrence| Float64Array::from(occurrence.as_slice()))
+        .collect())
+}
+
+fn to_points(values: &[f64]) -> Result<Vec<Point2DRf64>, PoSemirError> {
+    if !values.len().is_multiple_of(2) {
+        return Err(PoSemirError::InvalidPointSet(format!(
+            "expected onset, pitch pairs, got {} values",
+            values.len()
+        )));
+    }
+
+    Ok(values
+        .chunks(2)
+        .map(|pair| Point2DRf64::new(pair[0], pair[1]))
+        .collect())
+}
+
+fn to_flat<'a>(points: impl IntoIterator<Item = &'a Point2DRf64>) -> Vec<f64> {
+    points
+        .into_iter()
+        .flat_map(|point| [point.get_raw_x(), point.component_f64(1).unwrap()])
+        .collect()
+}
+
+fn read_parameters(algorithm: &str, parameters: &Object) -> HashMap<String, f64> {
+    parameter_names(algorithm)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|name| {
+            let value = Reflect::get(parameters, &JsValue::from_str(name)).ok()?;
+            Some((name.to_string(), value.as_f64()?))
+        })
+        .collect()
+}
+
+/// Runs the algorithm and passes the output to on_output as `WasmTec`s.
+fn compute(
+    point_set: &PointSet<Point2DRf64>,
+    algorithm: &str,
+    parameters: &HashMap<String, f64>,
+    mut on_output: impl FnMut(WasmTec) -> ControlFlow<()>,
+) -> Result<(), PoSemirError> {
+    match create_algorithm(algorithm, parameters)? {
+        DiscoveryAlgorithm::Mtp(algorithm) => algorithm.compute_mtps_to_output(point_set, |mtp| {
+            on_output(WasmTec {
+                pattern: to_flat(&mtp.pattern),
+                translators: to_flat(&[mtp.translator]),
+            })
+        }),
+        DiscoveryAlgorithm::Tec(algorithm) => algorithm.compute_tecs_to_output(point_set, |tec| {
+            on_output(WasmTec {
+                pattern: to_flat(&tec.pattern),
+                translators: to_flat(&tec.translators),
+            })
+        }),
+    }
+
+    Ok(())
+}
+
+fn find_occurrences(
+    query: &[f64],
+    point_set: &PointSet<Point2DRf64>,
+    matcher: &str,
+    min_match_size: usize,
+) -> Result<Vec<Vec<f64>>, PoSemirError> {
+    let query_points = to_points(query)?;
+    let query = Pattern::new(&query_points.iter().collect());
+
+    let parameters = HashMap::from([(MIN_MATCH_SIZE.to_string(), min_match_size as f64)]);
+    let occurrences =
+        create_matcher(matcher, &parameters)?.try_find_occurrences(&query, point_set)?;
+
+    Ok(occurrences.iter().map(to_flat).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::ops::ControlFlow;
+
+    use posemir::point_set::point::Point2DRf64;
+    use posemir::point_set::set::PointSet;
+
+    use crate::{compute, find_occurrences, to_flat, to_points};
+
+    const MOTIF: [f64; 8] = [0.0, 60.0, 1.0, 62.0, 2.0, 64.0, 3.0, 60.0];
+
+    fn test_point_set() -> PointSet<Point2DRf64> {
+        let mut values = MOTIF.to_vec();
+        values.extend(
+            MOTIF
+                .chunks(2)
+                .flat_map(|pair| [pair[0] + 8.0, pair[1] + 7.0]),
+        );
+        PointSet::new(to_points(&values).unwrap())
+    }
+
+    #[test]
+    fn test_flat_points() {
+        let points = to_points(&MOTIF).unwrap();
+        assert_eq!(4, points.len());
+        assert_eq!(MOTIF.to_vec(), to_flat(&points));
+        assert!(to_points(&MOTIF[..3]).is_err());
+    }
+
+    #[test]
+    fn test_compute_with_early_termination() {
+        let point_set = test_point_set();
+        let parameters = HashMap::from([("max_ioi".to_string(), 2.0)]);
+
+        let mut all = Vec::new();
+        compute(&point_set, "SIATEC-C", &parameters, |tec| {
+            all.push(tec);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert!(all
+            .iter()
+            .any(|tec| tec.pattern == MOTIF && tec.translators == [8.0, 7.0]));
+
+        let mut count = 0;
+        compute(&point_set, "SIA", &parameters, |_| {
+            count += 1;
+            ControlFlow::Break(())
+        })
+        .unwrap();
+        assert_eq!(1, count);
+
+        assert!(compute(&point_set, "SIATEC-C", &HashMap::new(), |_| {
+            ControlFlow::Continue(())
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_find_occurrences() {
+        let point_set = test_point_set();
+        let occurrences = find_occurrences(&MOTIF, &point_set, "exact", 2).unwrap();
+        assert_eq!(2, occurrences.len());
+        assert!(occurrences.contains(&MOTIF.to_vec()));
+        assert!(find_occurrences(&MOTIF, &point_set, "unknown", 2).is_err());
+    }
+}
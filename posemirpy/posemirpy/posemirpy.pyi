@@ -1,9 +1,32 @@
+from typing import Dict, List, Optional, Tuple
+
 import numpy as np
 
 
+class PointSet:
+    def __init__(self, points: np.ndarray) -> None:
+        ...
+
+    def __len__(self) -> int:
+        ...
+
+    def to_array(self) -> np.ndarray:
+        ...
+
+
 def run_siatec_c(np_points_array: np.ndarray, max_ioi: float):
     ...
 
 
+def discover(point_set: PointSet, algorithm: str,
+             parameters: Optional[Dict[str, float]] = None) -> List[Tuple[np.ndarray, np.ndarray]]:
+    ...
+
+
+def search(query: np.ndarray, point_set: PointSet, matcher: str = "EXACT",
+           min_match_size: int = 2) -> List[np.ndarray]:
+    ...
+
+
 def find_occurrences(query_points_array: np.ndarray, np_points_array: np.ndarray, min_match_size: int):
     ...
@@ -1,18 +1,89 @@
-use numpy::{PyArray2, PyReadonlyArrayDyn};
-use posemir::discovery::algorithm::TecAlgorithm;
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+use numpy::ndarray::Array2;
+use numpy::{IntoPyArray, PyArray2, PyReadonlyArray2, PyReadonlyArrayDyn};
+use posemir::discovery::algorithm::{MtpAlgorithm, TecAlgorithm};
+use posemir::discovery::registry::{create_algorithm, DiscoveryAlgorithm};
 use posemir::discovery::siatec_c::SiatecC;
+use posemir::error::PoSemirError;
 use posemir::point_set::pattern::Pattern;
 use posemir::point_set::point::{Point, Point2DRf64};
 use posemir::point_set::set::PointSet;
 use posemir::point_set::tec::Tec;
+use posemir::search::duration::DurationMatching;
 use posemir::search::exact_matcher::ExactMatcher;
 use posemir::search::partial_matcher::PartialMatcher;
 use posemir::search::pattern_matcher::PatternMatcher;
-use pyo3::{pymodule, PyResult, Python, types::PyModule};
+use posemir::search::registry::{create_matcher, MIN_MATCH_SIZE};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyModule;
+
+/// A point set of (onset, pitch) points that can be reused in several calls
+/// to `discover` and `search`.
+#[pyclass(name = "PointSet")]
+struct PyPointSet {
+    point_set: PointSet<Point2DRf64>,
+}
+
+#[pymethods]
+impl PyPointSet {
+    /// Creates a point set from an N x 2 array of (onset, pitch) rows.
+    #[new]
+    fn new(points: PyReadonlyArray2<f64>) -> PyResult<Self> {
+        let point_set = PointSet::new(rows_to_points(&points)?);
+        point_set.validate().map_err(to_py_error)?;
+        Ok(PyPointSet { point_set })
+    }
+
+    fn __len__(&self) -> usize {
+        self.point_set.len()
+    }
+
+    /// Returns the points as an N x 2 array of (onset, pitch) rows in lexicographic order.
+    fn to_array<'py>(&self, py: Python<'py>) -> &'py PyArray2<f64> {
+        points_to_array(py, &self.point_set)
+    }
+}
+
+fn to_py_error(error: PoSemirError) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+fn rows_to_points(array: &PyReadonlyArray2<f64>) -> PyResult<Vec<Point2DRf64>> {
+    let array = array.as_array();
+    if array.ncols() != 2 {
+        return Err(PyValueError::new_err(format!(
+            "expected an array of (onset, pitch) rows, got {} columns",
+            array.ncols()
+        )));
+    }
+
+    Ok(array
+        .rows()
+        .into_iter()
+        .map(|row| Point2DRf64::new(row[0], row[1]))
+        .collect())
+}
+
+fn points_to_array<'a, 'py>(
+    py: Python<'py>,
+    points: impl IntoIterator<Item = &'a Point2DRf64>,
+) -> &'py PyArray2<f64> {
+    let points: Vec<&Point2DRf64> = points.into_iter().collect();
+    Array2::from_shape_fn((points.len(), 2), |(i, j)| match j {
+        0 => points[i].get_raw_x(),
+        _ => points[i].component_f64(1).unwrap(),
+    })
+    .into_pyarray(py)
+}
 
 /// The Python module definition
 #[pymodule]
 fn posemirpy(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyPointSet>()?;
+
     fn pattern_to_array<'py>(py: Python<'py>, pattern: &Pattern<Point2DRf64>) -> &'py PyArray2<f64> {
         let arr = unsafe {
             let rows = pattern.len();
@@ -61,13 +132,74 @@ fn posemirpy(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
             }
 
             patterns.push((pat_array, translations));
+            ControlFlow::Continue(())
         };
 
-        SiatecC { max_ioi }.compute_tecs_to_output(&point_set, on_output);
+        SiatecC::new(max_ioi).compute_tecs_to_output(&point_set, on_output);
 
         patterns
     }
 
+    /// Runs the discovery algorithm with the given name (see `ALGORITHM_NAMES` in posemir)
+    /// on the point set. Returns the found patterns as (pattern, translators) pairs of
+    /// N x 2 arrays. MTP algorithms return the single translator of each MTP.
+    #[pyfn(m)]
+    #[pyo3(name = "discover", signature = (point_set, algorithm, parameters = None))]
+    fn discover<'py>(
+        py: Python<'py>,
+        point_set: PyRef<'_, PyPointSet>,
+        algorithm: &str,
+        parameters: Option<HashMap<String, f64>>,
+    ) -> PyResult<Vec<(&'py PyArray2<f64>, &'py PyArray2<f64>)>> {
+        let parameters = parameters.unwrap_or_default();
+        let mut patterns = Vec::new();
+
+        match create_algorithm(algorithm, &parameters).map_err(to_py_error)? {
+            DiscoveryAlgorithm::Mtp(algorithm) => {
+                algorithm.compute_mtps_to_output(&point_set.point_set, |mtp| {
+                    let translators = points_to_array(py, &[mtp.translator]);
+                    patterns.push((points_to_array(py, &mtp.pattern), translators));
+                    ControlFlow::Continue(())
+                });
+            }
+            DiscoveryAlgorithm::Tec(algorithm) => {
+                algorithm.compute_tecs_to_output(&point_set.point_set, |tec| {
+                    let translators = points_to_array(py, &tec.translators);
+                    patterns.push((points_to_array(py, &tec.pattern), translators));
+                    ControlFlow::Continue(())
+                });
+            }
+        }
+
+        Ok(patterns)
+    }
+
+    /// Returns the occurrences of the query, given as an N x 2 array of (onset, pitch) rows,
+    /// in the point set. The matcher is one of EXACT, PARTIAL and TRANSPOSITION-INVARIANT.
+    #[pyfn(m)]
+    #[pyo3(name = "search", signature = (query, point_set, matcher = "EXACT", min_match_size = 2))]
+    fn search<'py>(
+        py: Python<'py>,
+        query: PyReadonlyArray2<f64>,
+        point_set: PyRef<'_, PyPointSet>,
+        matcher: &str,
+        min_match_size: usize,
+    ) -> PyResult<Vec<&'py PyArray2<f64>>> {
+        let query_points = rows_to_points(&query)?;
+        let query = Pattern::new(&query_points.iter().collect());
+        let point_set = &point_set.point_set;
+
+        let parameters = HashMap::from([(MIN_MATCH_SIZE.to_string(), min_match_size as f64)]);
+        let occurrences = create_matcher(matcher, &parameters)
+            .and_then(|matcher| matcher.try_find_occurrences(&query, point_set))
+            .map_err(to_py_error)?;
+
+        Ok(occurrences
+            .iter()
+            .map(|occurrence| points_to_array(py, occurrence))
+            .collect())
+    }
+
     #[pyfn(m)]
     #[pyo3(name = "find_occurrences")]
     fn find_occurrences<'py>(
@@ -85,10 +217,13 @@ fn posemirpy(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
 
         let on_output = |pat: Pattern<Point2DRf64>| occurrences.push(pattern_to_array(py, &pat));
         if min_match_size < 0 {
-            let pattern_matcher = ExactMatcher {};
+            let pattern_matcher = ExactMatcher::default();
             pattern_matcher.find_occurrences_with_callback(&query, &point_set, on_output);
         } else {
-            let pattern_matcher = PartialMatcher { min_match_size: min_match_size as usize };
+            let pattern_matcher = PartialMatcher {
+                min_match_size: min_match_size as usize,
+                durations: DurationMatching::Ignore,
+            };
             pattern_matcher.find_occurrences_with_callback(&query, &point_set, on_output);
         }
 
@@ -96,4 +231,4 @@ fn posemirpy(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     }
 
     Ok(())
-}
\ No newline at end of file
+}
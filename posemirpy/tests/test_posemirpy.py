@@ -0,0 +1,71 @@
+import numpy as np
+import pytest
+
+import posemirpy
+
+# A four note motif that is repeated a fifth higher after eight beats.
+MOTIF = np.array([[0.0, 60.0], [1.0, 62.0], [2.0, 64.0], [3.0, 60.0]])
+POINTS = np.vstack([MOTIF, MOTIF + [8.0, 7.0], [[5.5, 40.0]]])
+
+
+def sorted_rows(array):
+    return sorted(map(tuple, array.tolist()))
+
+
+def test_point_set():
+    point_set = posemirpy.PointSet(POINTS)
+
+    assert len(point_set) == len(POINTS)
+    assert sorted_rows(point_set.to_array()) == sorted_rows(POINTS)
+
+    with pytest.raises(ValueError):
+        posemirpy.PointSet(np.zeros((3, 3)))
+
+
+def test_discover_siatec_c():
+    point_set = posemirpy.PointSet(POINTS)
+    tecs = posemirpy.discover(point_set, "SIATEC-C", {"max_ioi": 2.0})
+
+    motif_tecs = [(pattern, translators) for pattern, translators in tecs
+                  if sorted_rows(pattern) == sorted_rows(MOTIF)]
+    assert len(motif_tecs) == 1
+    assert sorted_rows(motif_tecs[0][1]) == [(8.0, 7.0)]
+
+    # No pattern of SIATEC-C has an inter-onset interval greater than max_ioi.
+    for pattern, _ in tecs:
+        assert np.all(np.diff(pattern[:, 0]) <= 2.0)
+
+
+def test_discover_siatec_c_matches_run_siatec_c():
+    point_set = posemirpy.PointSet(POINTS)
+    tecs = posemirpy.discover(point_set, "SIATEC-C", {"max_ioi": 2.0})
+
+    # The legacy function reads the onsets from the third column and the pitches from the second.
+    legacy_points = np.column_stack([np.zeros(len(POINTS)), POINTS[:, 1], POINTS[:, 0]])
+    legacy_tecs = posemirpy.run_siatec_c(legacy_points, 2.0)
+
+    assert [sorted_rows(pattern) for pattern, _ in tecs] == \
+           [sorted_rows(pattern) for pattern, _ in legacy_tecs]
+
+
+def test_discover_invalid_configuration():
+    point_set = posemirpy.PointSet(POINTS)
+
+    with pytest.raises(ValueError):
+        posemirpy.discover(point_set, "SIATEC-C")
+    with pytest.raises(ValueError):
+        posemirpy.discover(point_set, "NOT-AN-ALGORITHM")
+
+
+def test_search():
+    point_set = posemirpy.PointSet(POINTS)
+
+    exact = posemirpy.search(MOTIF, point_set)
+    assert sorted(sorted_rows(occurrence) for occurrence in exact) == \
+           [sorted_rows(MOTIF), sorted_rows(MOTIF + [8.0, 7.0])]
+
+    transposed = posemirpy.search(MOTIF, point_set, "TRANSPOSITION-INVARIANT")
+    assert len(transposed) == 2
+
+    with pytest.raises(ValueError):
+        posemirpy.search(MOTIF, point_set, "NOT-A-MATCHER")